@@ -0,0 +1,103 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::future::Future;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use tokio::sync::Notify;
+
+#[derive(Default)]
+struct AbortState {
+    aborted: AtomicBool,
+    notify: Notify,
+}
+
+/// Aborts the work registered by the `AbortRegistration`s of it, e.g. the tasks of a query.
+///
+/// The abort is cooperative: the work checks `AbortRegistration::check_aborted` in its loops, or
+/// races its futures against the abort by `AbortRegistration::abortable`.
+#[derive(Clone, Default)]
+pub struct AbortHandle {
+    state: Arc<AbortState>,
+}
+
+/// Observes the abort of an `AbortHandle`, cheap to clone and to check from many tasks.
+#[derive(Clone)]
+pub struct AbortRegistration {
+    state: Arc<AbortState>,
+}
+
+impl AbortHandle {
+    pub fn new_pair() -> (AbortHandle, AbortRegistration) {
+        let handle = AbortHandle::default();
+        let registration = handle.registration();
+        (handle, registration)
+    }
+
+    pub fn registration(&self) -> AbortRegistration {
+        AbortRegistration {
+            state: self.state.clone(),
+        }
+    }
+
+    /// Aborts the registered work, only the first call takes effect.
+    pub fn abort(&self) {
+        if !self.state.aborted.swap(true, Ordering::AcqRel) {
+            self.state.notify.notify_waiters();
+        }
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.state.aborted.load(Ordering::Acquire)
+    }
+}
+
+impl AbortRegistration {
+    pub fn is_aborted(&self) -> bool {
+        self.state.aborted.load(Ordering::Acquire)
+    }
+
+    /// Errors with `AbortedQuery` if it is aborted.
+    pub fn check_aborted(&self) -> Result<()> {
+        match self.is_aborted() {
+            true => Err(ErrorCode::AbortedQuery("the query is aborted")),
+            false => Ok(()),
+        }
+    }
+
+    /// Completes once it is aborted.
+    pub async fn aborted(&self) {
+        loop {
+            // register the waiter before checking, not to miss the notification between them
+            let notified = self.state.notify.notified();
+            if self.is_aborted() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Runs the future until it is aborted, the future is dropped on abort.
+    pub async fn abortable<F: Future>(&self, future: F) -> Result<F::Output> {
+        self.check_aborted()?;
+        tokio::select! {
+            output = future => Ok(output),
+            _ = self.aborted() => Err(ErrorCode::AbortedQuery("the query is aborted")),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::sync::RwLock;
+use std::thread;
+
+use common_exception::Result;
+use common_tracing::tracing;
+use once_cell::sync::Lazy;
+
+use crate::Runtime;
+
+static GLOBAL_IO_RUNTIME: Lazy<RwLock<Option<GlobalRuntime>>> = Lazy::new(|| RwLock::new(None));
+
+struct GlobalRuntime {
+    runtime: Arc<Runtime>,
+    // The worker threads, unknown for a runtime given by `set_instance`.
+    workers: Option<usize>,
+}
+
+/// The runtime of the storage I/O of the node, apart from the runtimes of the query execution,
+/// so that the CPU-heavy work of the queries does not delay the I/O completions and vice versa.
+pub struct GlobalIORuntime;
+
+impl GlobalIORuntime {
+    /// Initializes the runtime with `workers` threads, 0 for half of the cpus.
+    /// It returns the runtime initialized already if any, with a warning if it is of another
+    /// size, e.g., initialized by [GlobalIORuntime::instance] before.
+    pub fn init(workers: usize) -> Result<Arc<Runtime>> {
+        let workers = match workers {
+            0 => thread::available_parallelism().map_or(1, |v| (v.get() / 2).max(1)),
+            _ => workers,
+        };
+
+        let mut global = GLOBAL_IO_RUNTIME.write().unwrap();
+        if let Some(current) = global.as_ref() {
+            if let Some(initialized) = current.workers.filter(|v| *v != workers) {
+                tracing::warn!(
+                    "global I/O runtime is initialized with {} workers, {} workers are ignored",
+                    initialized,
+                    workers
+                );
+            }
+            return Ok(current.runtime.clone());
+        }
+        let runtime = Arc::new(Runtime::with_worker_threads_named(workers, "IO-worker")?);
+        *global = Some(GlobalRuntime {
+            runtime: runtime.clone(),
+            workers: Some(workers),
+        });
+        Ok(runtime)
+    }
+
+    /// The runtime, initialized with the default size if it is not initialized yet.
+    pub fn instance() -> Arc<Runtime> {
+        if let Some(global) = GLOBAL_IO_RUNTIME.read().unwrap().as_ref() {
+            return global.runtime.clone();
+        }
+        Self::init(0).expect("failed to create the global I/O runtime")
+    }
+
+    /// Replaces the runtime, e.g. by a small one in tests.
+    pub fn set_instance(runtime: Arc<Runtime>) {
+        *GLOBAL_IO_RUNTIME.write().unwrap() = Some(GlobalRuntime {
+            runtime,
+            workers: None,
+        });
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::hash_map::DefaultHasher;
+use std::future::Future;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::Weak;
+use std::time::Duration;
+
+use common_exception::Result;
+use common_tracing::tracing;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio::time::MissedTickBehavior;
+
+use crate::TrySpawn;
+
+/// The state of an interval task, for diagnostics.
+#[derive(Clone, Debug, Default)]
+pub struct IntervalTaskState {
+    /// The number of the runs started.
+    pub runs: u64,
+    pub last_run_at: Option<Instant>,
+    /// The error of the last run, None if it succeeded.
+    pub last_error: Option<String>,
+}
+
+/// Stops the interval task, the run in progress is not interrupted.
+#[derive(Clone)]
+pub struct IntervalTaskStopHandle {
+    stop: Arc<watch::Sender<bool>>,
+}
+
+impl IntervalTaskStopHandle {
+    pub fn stop(&self) {
+        let _ = self.stop.send(true);
+    }
+
+    /// A stop handle that does not keep the task running once its handle is dropped.
+    pub(crate) fn downgrade(&self) -> WeakIntervalTaskStopHandle {
+        WeakIntervalTaskStopHandle {
+            stop: Arc::downgrade(&self.stop),
+        }
+    }
+}
+
+pub(crate) struct WeakIntervalTaskStopHandle {
+    stop: Weak<watch::Sender<bool>>,
+}
+
+impl WeakIntervalTaskStopHandle {
+    /// Whether the handle and all its stop handles are dropped, i.e. the task is stopping.
+    pub(crate) fn is_dropped(&self) -> bool {
+        self.stop.strong_count() == 0
+    }
+
+    pub(crate) fn stop(&self) {
+        if let Some(stop) = self.stop.upgrade() {
+            let _ = stop.send(true);
+        }
+    }
+}
+
+pub struct IntervalTaskHandle {
+    name: String,
+    state: Arc<Mutex<IntervalTaskState>>,
+    stop_handle: IntervalTaskStopHandle,
+    join_handle: JoinHandle<()>,
+}
+
+impl IntervalTaskHandle {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn state(&self) -> IntervalTaskState {
+        self.state.lock().unwrap().clone()
+    }
+
+    pub fn stop_handle(&self) -> IntervalTaskStopHandle {
+        self.stop_handle.clone()
+    }
+
+    /// Stops the task and waits for the run in progress, if any, to finish.
+    pub async fn stop(self) {
+        self.stop_handle.stop();
+        let _ = self.join_handle.await;
+    }
+}
+
+/// Runs `f` every `period` on the spawner, at most one run at a time: the ticks are skipped
+/// while the previous run is still going.
+///
+/// The first run is delayed by a jitter of up to a tenth of the period by the name, so that the
+/// tasks started at the same time do not run at the same time.
+///
+/// The task is stopped by `IntervalTaskHandle::stop`, or once the handle and all its stop
+/// handles are dropped.
+pub fn spawn_interval<S, F, Fut>(
+    spawner: &S,
+    name: &str,
+    period: Duration,
+    f: F,
+) -> Result<IntervalTaskHandle>
+where
+    S: TrySpawn,
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    let (stop_tx, mut stop_rx) = watch::channel(false);
+    let state = Arc::new(Mutex::new(IntervalTaskState::default()));
+
+    let task_name = name.to_string();
+    let task_state = state.clone();
+    let start = Instant::now() + jitter(name, period);
+    let join_handle = spawner.try_spawn(async move {
+        let mut interval = tokio::time::interval_at(start, period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                // Err if the handle and all its stop handles are dropped.
+                res = stop_rx.changed() => if res.is_err() { break },
+            }
+            if *stop_rx.borrow() {
+                break;
+            }
+
+            {
+                let mut state = task_state.lock().unwrap();
+                state.runs += 1;
+                state.last_run_at = Some(Instant::now());
+            }
+            let res = f().await;
+            if let Err(cause) = &res {
+                tracing::warn!("interval task {} failed: {}", task_name, cause);
+            }
+            task_state.lock().unwrap().last_error = res.err().map(|e| e.to_string());
+        }
+    })?;
+
+    Ok(IntervalTaskHandle {
+        name: name.to_string(),
+        state,
+        stop_handle: IntervalTaskStopHandle {
+            stop: Arc::new(stop_tx),
+        },
+        join_handle,
+    })
+}
+
+fn jitter(name: &str, period: Duration) -> Duration {
+    let max_jitter_nanos = (period / 10).as_nanos() as u64;
+    if max_jitter_nanos == 0 {
+        return Duration::ZERO;
+    }
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    Duration::from_nanos(hasher.finish() % max_jitter_nanos)
+}
@@ -0,0 +1,81 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use tokio::task::JoinError;
+use tokio::task::JoinHandle;
+
+/// A JoinHandle which yields the panic or cancellation of the task as an ErrorCode, instead of
+/// a JoinError that is usually unwrapped into a panic of the node.
+pub struct TrackedJoinHandle<T> {
+    inner: JoinHandle<T>,
+}
+
+impl<T> TrackedJoinHandle<T> {
+    pub fn create(inner: JoinHandle<T>) -> Self {
+        TrackedJoinHandle { inner }
+    }
+
+    pub fn abort(&self) {
+        self.inner.abort()
+    }
+}
+
+impl<T> Future for TrackedJoinHandle<T> {
+    type Output = Result<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.inner)
+            .poll(cx)
+            .map(|res| res.map_err(join_error_code))
+    }
+}
+
+pub trait JoinHandleExt<T> {
+    /// Converts the panic or cancellation of the task into an ErrorCode.
+    fn err_into(self) -> TrackedJoinHandle<T>;
+}
+
+impl<T> JoinHandleExt<T> for JoinHandle<T> {
+    fn err_into(self) -> TrackedJoinHandle<T> {
+        TrackedJoinHandle::create(self)
+    }
+}
+
+fn join_error_code(error: JoinError) -> ErrorCode {
+    match error.try_into_panic() {
+        Ok(payload) => ErrorCode::PanicError(format!(
+            "task panicked: {}",
+            panic_message(payload.as_ref())
+        )),
+        Err(error) => ErrorCode::TokioError(format!("task is cancelled: {}", error)),
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic payload"
+    }
+}
@@ -14,12 +14,17 @@
 
 #![feature(thread_local)]
 
+mod abort;
 mod format;
+mod global_runtime;
 mod http_shutdown_handlers;
+mod interval_task;
+mod join_handle;
 mod net;
 mod profiling;
 mod progress;
 mod runtime;
+mod runtime_metrics;
 mod runtime_tracker;
 mod shutdown_signal;
 mod stop_handle;
@@ -28,17 +33,39 @@ mod string_func;
 mod thread;
 mod uniq_id;
 
+pub use abort::AbortHandle;
+pub use abort::AbortRegistration;
 pub use format::Format;
+pub use global_runtime::GlobalIORuntime;
 pub use http_shutdown_handlers::HttpShutdownHandler;
+pub use interval_task::spawn_interval;
+pub use interval_task::IntervalTaskHandle;
+pub use interval_task::IntervalTaskState;
+pub use interval_task::IntervalTaskStopHandle;
+pub use join_handle::JoinHandleExt;
+pub use join_handle::TrackedJoinHandle;
 pub use net::get_free_tcp_port;
 pub use net::get_free_udp_port;
 pub use profiling::Profiling;
 pub use progress::Progress;
 pub use progress::ProgressValues;
+pub use runtime::with_timeout;
+pub use runtime::ConcurrencyLimitedRuntime;
 pub use runtime::Dropper;
 pub use runtime::Runtime;
+pub use runtime::RuntimeBuilder;
 pub use runtime::TrySpawn;
+pub use runtime_metrics::RuntimeMetrics;
+pub use runtime_metrics::TaskCounters;
+pub use runtime_metrics::METRIC_RUNTIME_TASKS_ACTIVE;
+pub use runtime_metrics::METRIC_RUNTIME_TASKS_FINISHED;
+pub use runtime_metrics::METRIC_RUNTIME_TASKS_PANICKED;
+pub use runtime_metrics::METRIC_RUNTIME_TASKS_SPAWNED;
+pub use runtime_metrics::METRIC_RUNTIME_TASK_DURATION;
+pub use runtime_metrics::TASK_DURATION_BUCKETS_MS;
+pub use runtime_tracker::MemoryTracker;
 pub use runtime_tracker::RuntimeTracker;
+pub use runtime_tracker::TaskMemoryGuard;
 pub use runtime_tracker::ThreadTracker;
 pub use shutdown_signal::signal_stream;
 pub use shutdown_signal::DummySignalStream;
@@ -13,27 +13,119 @@
 // limitations under the License.
 
 use std::future::Future;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::mpsc::RecvTimeoutError;
 use std::sync::Arc;
+use std::sync::Mutex;
 use std::thread;
+use std::time::Duration;
 
 use common_exception::ErrorCode;
 use common_exception::Result;
+use common_tracing::tracing;
+use common_tracing::tracing::Span;
+use common_tracing::tracing_futures::Instrument;
+use common_tracing::tracing_futures::WithSubscriber;
 use tokio::runtime::Handle;
 use tokio::sync::oneshot;
+use tokio::sync::Notify;
+use tokio::sync::Semaphore;
 use tokio::task::JoinHandle;
 
+use crate::abort::AbortRegistration;
+use crate::interval_task::spawn_interval;
+use crate::interval_task::IntervalTaskHandle;
+use crate::interval_task::WeakIntervalTaskStopHandle;
+use crate::runtime_metrics::RuntimeMetrics;
+use crate::runtime_metrics::TaskCounters;
+use crate::runtime_tracker::MemoryTracker;
 use crate::runtime_tracker::RuntimeTracker;
+use crate::runtime_tracker::ThreadTracker;
+
+/// Runs the future, giving up with a `Timeout` error if it is not completed in `timeout`.
+///
+/// `operation` tells what is timed out in the error, e.g. `read column data of 'path'`.
+pub async fn with_timeout<F: Future>(
+    future: F,
+    timeout: Duration,
+    operation: &str,
+) -> Result<F::Output> {
+    tokio::time::timeout(timeout, future)
+        .await
+        .map_err(|_| ErrorCode::Timeout(format!("{} is not completed in {:?}", operation, timeout)))
+}
 
 /// Methods to spawn tasks.
 pub trait TrySpawn {
-    /// Tries to spawn a new asynchronous task, returning a tokio::JoinHandle for it.
+    /// Tries to spawn a new asynchronous task as it is, returning a tokio::JoinHandle for it.
     ///
     /// It allows to return an error before spawning the task.
-    fn try_spawn<T>(&self, task: T) -> Result<JoinHandle<T::Output>>
+    /// The task is not in the current tracing span, only for the hot paths.
+    fn try_spawn_untraced<T>(&self, task: T) -> Result<JoinHandle<T::Output>>
     where
         T: Future + Send + 'static,
         T::Output: Send + 'static;
 
+    /// Tries to spawn a new asynchronous task, returning a tokio::JoinHandle for it.
+    ///
+    /// The task is in the current tracing span, with the current subscriber.
+    fn try_spawn<T>(&self, task: T) -> Result<JoinHandle<T::Output>>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        self.try_spawn_in_span(Span::current(), task)
+    }
+
+    /// Tries to spawn a new asynchronous task in `span`, with the current subscriber.
+    fn try_spawn_in_span<T>(&self, span: Span, task: T) -> Result<JoinHandle<T::Output>>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        self.try_spawn_untraced(task.instrument(span).with_current_subscriber())
+    }
+
+    /// Tries to run a blocking or CPU-heavy function on a blocking thread, so that it does not
+    /// occupy an async worker thread, returning a tokio::JoinHandle for it.
+    fn try_spawn_blocking<F, R>(&self, f: F) -> Result<JoinHandle<R>>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static;
+
+    /// Same as `try_spawn`, but the task is cancelled and yields a `Timeout` error with the
+    /// `operation` if it is not completed in `timeout`, see `with_timeout`.
+    fn try_spawn_timeout<T>(
+        &self,
+        operation: &str,
+        timeout: Duration,
+        task: T,
+    ) -> Result<JoinHandle<Result<T::Output>>>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        let operation = operation.to_string();
+        self.try_spawn(async move { with_timeout(task, timeout, &operation).await })
+    }
+
+    /// Same as `try_spawn`, but the task is dropped and yields an `AbortedQuery` error once
+    /// `abort` is aborted, see `AbortRegistration::abortable`.
+    fn try_spawn_abortable<T>(
+        &self,
+        abort: AbortRegistration,
+        task: T,
+    ) -> Result<JoinHandle<Result<T::Output>>>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        self.try_spawn(async move { abort.abortable(task).await })
+    }
+
     /// Spawns a new asynchronous task, returning a tokio::JoinHandle for it.
     ///
     /// A default impl of this method just calls `try_spawn` and just panics if there is an error.
@@ -47,6 +139,14 @@ pub trait TrySpawn {
 }
 
 impl<S: TrySpawn> TrySpawn for Arc<S> {
+    fn try_spawn_untraced<T>(&self, task: T) -> Result<JoinHandle<T::Output>>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        self.as_ref().try_spawn_untraced(task)
+    }
+
     fn try_spawn<T>(&self, task: T) -> Result<JoinHandle<T::Output>>
     where
         T: Future + Send + 'static,
@@ -62,51 +162,210 @@ impl<S: TrySpawn> TrySpawn for Arc<S> {
     {
         self.as_ref().spawn(task)
     }
+
+    fn try_spawn_blocking<F, R>(&self, f: F) -> Result<JoinHandle<R>>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        self.as_ref().try_spawn_blocking(f)
+    }
+}
+
+/// Builds a `Runtime`, the options not set are the defaults of tokio.
+#[derive(Clone, Debug, Default)]
+pub struct RuntimeBuilder {
+    worker_threads: Option<usize>,
+    max_blocking_threads: Option<usize>,
+    thread_stack_size: Option<usize>,
+    thread_priority: Option<i32>,
+    name: Option<String>,
+    counters: Option<Arc<TaskCounters>>,
+}
+
+impl RuntimeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of the worker threads, the number of the CPUs by default.
+    pub fn worker_threads(mut self, workers: usize) -> Self {
+        self.worker_threads = Some(workers);
+        self
+    }
+
+    pub fn max_blocking_threads(mut self, max_blocking_threads: usize) -> Self {
+        self.max_blocking_threads = Some(max_blocking_threads);
+        self
+    }
+
+    /// The stack size of the threads in bytes, 2MiB by default of tokio. Raise it for the
+    /// deeply recursive tasks, e.g. evaluating a deeply nested expression.
+    pub fn thread_stack_size(mut self, stack_size: usize) -> Self {
+        self.thread_stack_size = Some(stack_size);
+        self
+    }
+
+    /// The nice value of the threads, lower is a higher priority, e.g. -5 for the I/O runtime.
+    ///
+    /// It is best-effort: only applied on Linux, and a higher priority than the process's
+    /// needs `CAP_SYS_NICE`, otherwise it is ignored with a warning.
+    pub fn thread_priority(mut self, nice: i32) -> Self {
+        self.thread_priority = Some(nice);
+        self
+    }
+
+    /// Names the threads by `name` and an incrementing suffix, e.g. `IO-worker-0`.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Counts the tasks of the runtime by `counters`, shared by the runtimes to be recorded as
+    /// one, e.g. the runtimes of the queries.
+    pub fn counters(mut self, counters: Arc<TaskCounters>) -> Self {
+        self.counters = Some(counters);
+        self
+    }
+
+    pub fn build(self) -> Result<Runtime> {
+        let tracker = RuntimeTracker::create();
+        let mut builder = tokio::runtime::Builder::new_multi_thread();
+        builder
+            .enable_all()
+            .on_thread_stop(tracker.on_stop_thread());
+
+        let on_start_thread = tracker.on_start_thread();
+        match self.thread_priority {
+            None => builder.on_thread_start(on_start_thread),
+            Some(nice) => builder.on_thread_start(move || {
+                set_current_thread_priority(nice);
+                on_start_thread();
+            }),
+        };
+
+        // Same as the default of tokio.
+        let workers = self
+            .worker_threads
+            .unwrap_or_else(|| thread::available_parallelism().map_or(1, |v| v.get()));
+        if self.worker_threads.is_some() {
+            builder.worker_threads(workers);
+        }
+        if let Some(max_blocking_threads) = self.max_blocking_threads {
+            builder.max_blocking_threads(max_blocking_threads);
+        }
+        if let Some(stack_size) = self.thread_stack_size {
+            builder.thread_stack_size(stack_size);
+        }
+        if let Some(name) = &self.name {
+            let prefix = name.clone();
+            let next_id = AtomicUsize::new(0);
+            builder.thread_name_fn(move || {
+                format!("{}-{}", prefix, next_id.fetch_add(1, Ordering::Relaxed))
+            });
+        }
+
+        let counters = self.counters.unwrap_or_default();
+        Runtime::create(self.name, workers, tracker, counters, &mut builder)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn set_current_thread_priority(nice: i32) {
+    // The nice value is per thread on Linux, the tid is the `who` of the process.
+    let res = unsafe {
+        let tid = libc::syscall(libc::SYS_gettid) as libc::id_t;
+        libc::setpriority(libc::PRIO_PROCESS as _, tid, nice)
+    };
+    if res != 0 {
+        tracing::warn!(
+            "failed to set the priority of the thread to {}: {}",
+            nice,
+            std::io::Error::last_os_error()
+        );
+    }
 }
 
+#[cfg(not(target_os = "linux"))]
+fn set_current_thread_priority(_nice: i32) {}
+
 /// Tokio Runtime wrapper.
 /// If a runtime is in an asynchronous context, shutdown it first.
 pub struct Runtime {
     // Handle to runtime.
     handle: Handle,
+    // The prefix of the names of the worker threads.
+    name: Option<String>,
+    worker_count: usize,
     // Runtime tracker
     tracker: Arc<RuntimeTracker>,
+    // The tasks spawned and not finished yet.
+    tasks: Arc<TaskCounter>,
+    counters: Arc<TaskCounters>,
+    // The interval tasks, stopped before the graceful shutdown waits for the tasks. Weak, not to
+    // keep a task running after its handle is dropped, and pruned as the tasks are spawned.
+    interval_tasks: Mutex<Vec<WeakIntervalTaskStopHandle>>,
+    // Set once the runtime is shutdown, i.e. its threads are exited.
+    shutdown: Arc<AtomicBool>,
     // Use to receive a drop signal when dropper is dropped.
-    _dropper: Dropper,
+    dropper: Mutex<Dropper>,
 }
 
 impl Runtime {
-    fn create(tracker: Arc<RuntimeTracker>, builder: &mut tokio::runtime::Builder) -> Result<Self> {
+    fn create(
+        name: Option<String>,
+        worker_count: usize,
+        tracker: Arc<RuntimeTracker>,
+        counters: Arc<TaskCounters>,
+        builder: &mut tokio::runtime::Builder,
+    ) -> Result<Self> {
         let runtime = builder
             .build()
             .map_err(|tokio_error| ErrorCode::TokioError(format!("{}", tokio_error)))?;
 
         let (send_stop, recv_stop) = oneshot::channel();
+        let (send_finished, recv_finished) = mpsc::channel();
 
         let handle = runtime.handle().clone();
+        let shutdown = Arc::new(AtomicBool::new(false));
 
         // Block the runtime to shutdown.
-        let _ = thread::spawn(move || runtime.block_on(recv_stop));
+        let thread_name = match &name {
+            Some(name) => format!("{}-shutdown", name),
+            None => "runtime-shutdown".to_string(),
+        };
+        let is_shutdown = shutdown.clone();
+        let join_handle = thread::Builder::new()
+            .name(thread_name)
+            .spawn(move || {
+                let _ = runtime.block_on(recv_stop);
+                drop(runtime);
+                is_shutdown.store(true, Ordering::SeqCst);
+                let _ = send_finished.send(());
+            })
+            .map_err(|e| {
+                ErrorCode::TokioError(format!("cannot spawn the shutdown thread: {}", e))
+            })?;
 
         Ok(Runtime {
             handle,
+            name,
+            worker_count,
             tracker,
-            _dropper: Dropper {
+            tasks: Arc::new(TaskCounter::default()),
+            counters,
+            interval_tasks: Mutex::new(vec![]),
+            shutdown,
+            dropper: Mutex::new(Dropper {
                 close: Some(send_stop),
-            },
+                shutdown_thread: Some(ShutdownThread {
+                    finished: recv_finished,
+                    join_handle,
+                }),
+            }),
         })
     }
 
-    fn tracker_builder(rt_tracker: Arc<RuntimeTracker>) -> tokio::runtime::Builder {
-        let mut builder = tokio::runtime::Builder::new_multi_thread();
-        builder
-            .enable_all()
-            .on_thread_stop(rt_tracker.on_stop_thread())
-            .on_thread_start(rt_tracker.on_start_thread());
-
-        builder
-    }
-
     pub fn get_tracker(&self) -> Arc<RuntimeTracker> {
         self.tracker.clone()
     }
@@ -115,47 +374,379 @@ impl Runtime {
     /// thread and returns a `Handle` which can be used to spawn tasks via
     /// its executor.
     pub fn with_default_worker_threads() -> Result<Self> {
-        let tracker = RuntimeTracker::create();
-        let mut runtime_builder = Self::tracker_builder(tracker.clone());
-        Self::create(tracker, &mut runtime_builder)
+        RuntimeBuilder::new().build()
     }
 
     pub fn with_worker_threads(workers: usize, thread_name: Option<String>) -> Result<Self> {
-        let tracker = RuntimeTracker::create();
-        let mut runtime_builder = Self::tracker_builder(tracker.clone());
-        if let Some(v) = thread_name {
-            runtime_builder.thread_name(v);
+        match thread_name {
+            Some(name) => Self::with_worker_threads_named(workers, &name),
+            None => RuntimeBuilder::new().worker_threads(workers).build(),
+        }
+    }
+
+    /// Spawns a new tokio runtime with `workers` threads, named by `name` and an incrementing
+    /// suffix, e.g. `IO-worker-0`, to tell the runtimes apart in a flamegraph or `top -H`.
+    pub fn with_worker_threads_named(workers: usize, name: &str) -> Result<Self> {
+        Self::with_blocking_threads_named(workers, None, name)
+    }
+
+    /// Same as `with_worker_threads_named`, but no more than `max_blocking_threads` threads run
+    /// the blocking tasks, 512 by default of tokio.
+    pub fn with_blocking_threads_named(
+        workers: usize,
+        max_blocking_threads: Option<usize>,
+        name: &str,
+    ) -> Result<Self> {
+        let mut builder = RuntimeBuilder::new().worker_threads(workers).name(name);
+        if let Some(max_blocking_threads) = max_blocking_threads {
+            builder = builder.max_blocking_threads(max_blocking_threads);
+        }
+        builder.build()
+    }
+
+    /// The name of the runtime, None if the threads are not named.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn worker_count(&self) -> usize {
+        self.worker_count
+    }
+
+    /// Spawns a task with `tracker` as its memory tracker, see `MemoryTracker::alloc_task_memory`.
+    pub fn spawn_tracked<T>(&self, tracker: Arc<MemoryTracker>, task: T) -> JoinHandle<T::Output>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        self.spawn(MemoryTracker::track_task(tracker, task))
+    }
+
+    /// Runs `f` every `period` in the runtime, see `spawn_interval`.
+    /// The task is stopped by the graceful shutdown of the runtime.
+    pub fn spawn_interval<F, Fut>(
+        &self,
+        name: &str,
+        period: Duration,
+        f: F,
+    ) -> Result<IntervalTaskHandle>
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let handle = spawn_interval(self, name, period, f)?;
+        {
+            let mut interval_tasks = self.interval_tasks.lock().unwrap();
+            interval_tasks.retain(|task| !task.is_dropped());
+            interval_tasks.push(handle.stop_handle().downgrade());
         }
-        Self::create(tracker, runtime_builder.worker_threads(workers))
+        Ok(handle)
+    }
+
+    /// A snapshot of the counters of the tasks spawned by `try_spawn`.
+    pub fn metrics(&self) -> RuntimeMetrics {
+        self.counters.snapshot()
+    }
+
+    /// Whether the runtime is shutdown, by dropping it or by `shutdown_gracefully`.
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown.load(Ordering::SeqCst)
+    }
+
+    // Whether it is called by a thread of this runtime.
+    fn in_this_runtime(&self) -> bool {
+        matches!(
+            ThreadTracker::current_runtime_tracker(),
+            Some(tracker) if Arc::ptr_eq(&tracker, &self.tracker)
+        )
     }
 
     pub fn inner(&self) -> tokio::runtime::Handle {
         self.handle.clone()
     }
 
-    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+    /// Runs the future to completion on the runtime, blocking the current thread.
+    ///
+    /// Errors instead of blocking a worker thread if it is called in an async context, which
+    /// would hang if all the worker threads of the runtime are blocked, use `.await` instead.
+    pub fn block_on<F: Future>(&self, future: F) -> Result<F::Output> {
+        if Handle::try_current().is_ok() {
+            return Err(ErrorCode::TokioError(match self.in_this_runtime() {
+                true => "block_on is called within the same runtime, use .await instead",
+                false => "block_on is called within an async context, use .await instead",
+            }));
+        }
+        Ok(self.handle.block_on(future))
+    }
+
+    /// Same as `block_on`, but errors with `Timeout` if the future is not completed in `timeout`.
+    pub fn block_on_with_timeout<F: Future>(
+        &self,
+        future: F,
+        timeout: Duration,
+    ) -> Result<F::Output> {
+        self.block_on(with_timeout(future, timeout, "block_on"))?
+    }
+
+    /// Same as `block_on`, but it is not checked whether it is called in an async context.
+    pub fn block_on_unchecked<F: Future>(&self, future: F) -> F::Output {
         self.handle.block_on(future)
     }
+
+    /// Stops accepting new tasks, waits for the tasks spawned to finish and then shuts down the
+    /// runtime. The runtime is shut down anyway after `timeout`, with a `Timeout` error.
+    pub async fn shutdown_gracefully(&self, timeout: Duration) -> Result<()> {
+        self.tasks.shutting_down.store(true, Ordering::SeqCst);
+        for interval_task in self.interval_tasks.lock().unwrap().drain(..) {
+            interval_task.stop();
+        }
+        let res = tokio::time::timeout(timeout, self.tasks.wait_finished()).await;
+        let shutdown_thread = self.dropper.lock().unwrap().close();
+        // The runtime can not wait for itself to shutdown.
+        if let Some(shutdown_thread) = shutdown_thread.filter(|_| !self.in_this_runtime()) {
+            let _ =
+                tokio::task::spawn_blocking(move || shutdown_thread.join(SHUTDOWN_JOIN_TIMEOUT))
+                    .await;
+        }
+        res.map_err(|_| {
+            ErrorCode::Timeout(format!(
+                "{} tasks not finished in {:?} to shutdown the runtime",
+                self.tasks.outstanding.load(Ordering::SeqCst),
+                timeout
+            ))
+        })
+    }
 }
 
 impl TrySpawn for Runtime {
+    fn try_spawn_untraced<T>(&self, task: T) -> Result<JoinHandle<T::Output>>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        let guard = self.tasks.try_track()?;
+        Ok(self.handle.spawn(self.counters.track(async move {
+            let _guard = guard;
+            task.await
+        })))
+    }
+
+    fn try_spawn_blocking<F, R>(&self, f: F) -> Result<JoinHandle<R>>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let guard = self.tasks.try_track()?;
+        let span = Span::current();
+        Ok(self.handle.spawn_blocking(move || {
+            let _guard = guard;
+            span.in_scope(f)
+        }))
+    }
+}
+
+impl TrySpawn for Handle {
+    fn try_spawn_untraced<T>(&self, task: T) -> Result<JoinHandle<T::Output>>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        Ok(self.spawn(task))
+    }
+
+    fn try_spawn_blocking<F, R>(&self, f: F) -> Result<JoinHandle<R>>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        Ok(self.spawn_blocking(f))
+    }
+}
+
+/// Spawns tasks by the inner spawner, of which no more than `permits` tasks are running at
+/// the same time, the others wait for a permit before they are polled.
+///
+/// A blocking task waits for a permit in an asynchronous task of the inner, before it takes a
+/// blocking thread.
+pub struct ConcurrencyLimitedRuntime<S: TrySpawn> {
+    inner: S,
+    semaphore: Arc<Semaphore>,
+}
+
+impl<S: TrySpawn> ConcurrencyLimitedRuntime<S> {
+    pub fn create(inner: S, permits: usize) -> Self {
+        ConcurrencyLimitedRuntime {
+            inner,
+            semaphore: Arc::new(Semaphore::new(permits.max(1))),
+        }
+    }
+
+    /// The number of tasks that can start running at once.
+    pub fn available_permits(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    fn limited<T>(&self, task: T) -> impl Future<Output = T::Output>
+    where T: Future {
+        let semaphore = self.semaphore.clone();
+        async move {
+            // The semaphore is never closed.
+            let _permit = semaphore.acquire_owned().await;
+            task.await
+        }
+    }
+}
+
+impl<S: TrySpawn> TrySpawn for ConcurrencyLimitedRuntime<S> {
+    fn try_spawn_untraced<T>(&self, task: T) -> Result<JoinHandle<T::Output>>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        self.inner.try_spawn_untraced(self.limited(task))
+    }
+
     fn try_spawn<T>(&self, task: T) -> Result<JoinHandle<T::Output>>
     where
         T: Future + Send + 'static,
         T::Output: Send + 'static,
     {
-        Ok(self.handle.spawn(task))
+        self.inner.try_spawn(self.limited(task))
+    }
+
+    fn try_spawn_in_span<T>(&self, span: Span, task: T) -> Result<JoinHandle<T::Output>>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        self.inner.try_spawn_in_span(span, self.limited(task))
+    }
+
+    fn try_spawn_blocking<F, R>(&self, f: F) -> Result<JoinHandle<R>>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let span = Span::current();
+        self.inner.try_spawn_untraced(self.limited(async move {
+            match tokio::task::spawn_blocking(move || span.in_scope(f)).await {
+                Ok(res) => res,
+                // A blocking task is never cancelled, but panics.
+                Err(e) => std::panic::resume_unwind(e.into_panic()),
+            }
+        }))
+    }
+}
+
+/// Counts the tasks of a runtime not finished yet, for the graceful shutdown.
+#[derive(Default)]
+struct TaskCounter {
+    shutting_down: AtomicBool,
+    outstanding: AtomicUsize,
+    finished: Notify,
+}
+
+impl TaskCounter {
+    fn try_track(self: &Arc<Self>) -> Result<TaskGuard> {
+        self.outstanding.fetch_add(1, Ordering::SeqCst);
+        // Checked after the counting, so that a task is either rejected or waited for.
+        if self.shutting_down.load(Ordering::SeqCst) {
+            drop(TaskGuard(self.clone()));
+            return Err(ErrorCode::TokioError("the runtime is shutting down"));
+        }
+        Ok(TaskGuard(self.clone()))
+    }
+
+    async fn wait_finished(&self) {
+        loop {
+            let finished = self.finished.notified();
+            if self.outstanding.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            finished.await;
+        }
+    }
+}
+
+/// Finishes the counting of a task when the task is completed, panicked or aborted.
+struct TaskGuard(Arc<TaskCounter>);
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        if self.0.outstanding.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.finished.notify_waiters();
+        }
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        let shutdown_thread = self.dropper.lock().unwrap().close();
+        // The runtime can not wait for itself to shutdown, the thread is detached.
+        if let Some(shutdown_thread) = shutdown_thread.filter(|_| !self.in_this_runtime()) {
+            match tokio::runtime::Handle::try_current() {
+                // Not to block the worker of another runtime, it is joined on a blocking thread.
+                Ok(handle) => {
+                    handle.spawn_blocking(move || shutdown_thread.join(SHUTDOWN_JOIN_TIMEOUT));
+                }
+                Err(_) => shutdown_thread.join(SHUTDOWN_JOIN_TIMEOUT),
+            }
+        }
+    }
+}
+
+/// The max time to wait for the threads of a runtime to exit, while dropping it.
+const SHUTDOWN_JOIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The thread blocked on the runtime until it is signaled to shutdown the runtime.
+struct ShutdownThread {
+    finished: mpsc::Receiver<()>,
+    join_handle: thread::JoinHandle<()>,
+}
+
+impl ShutdownThread {
+    fn join(self, timeout: Duration) {
+        match self.finished.recv_timeout(timeout) {
+            Ok(_) => {
+                let _ = self.join_handle.join();
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                tracing::warn!(
+                    "the runtime is not shutdown in {:?}, its threads are leaked",
+                    timeout
+                );
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                if let Err(cause) = self.join_handle.join() {
+                    tracing::warn!("the shutdown thread of the runtime panicked: {:?}", cause);
+                }
+            }
+        }
     }
 }
 
 /// Dropping the dropper will cause runtime to shutdown.
 pub struct Dropper {
     close: Option<oneshot::Sender<()>>,
+    shutdown_thread: Option<ShutdownThread>,
+}
+
+impl Dropper {
+    /// Sends the signal to shutdown the runtime, and returns the thread shutting it down to be
+    /// joined, only for the first call.
+    fn close(&mut self) -> Option<ShutdownThread> {
+        if let Some(close) = self.close.take() {
+            if close.send(()).is_err() {
+                tracing::warn!("the shutdown signal is not delivered, the runtime is shutdown");
+            }
+        }
+        self.shutdown_thread.take()
+    }
 }
 
 impl Drop for Dropper {
     fn drop(&mut self) {
-        // Send a signal to say i am dropping.
-        self.close.take().map(|v| v.send(()));
+        // The shutdown thread is detached, if it is not joined yet.
+        self.close();
     }
 }
@@ -0,0 +1,159 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::future::Future;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use metrics::absolute_counter;
+use metrics::gauge;
+
+pub static METRIC_RUNTIME_TASKS_SPAWNED: &str = "runtime.tasks_spawned";
+pub static METRIC_RUNTIME_TASKS_ACTIVE: &str = "runtime.tasks_active";
+pub static METRIC_RUNTIME_TASKS_FINISHED: &str = "runtime.tasks_finished";
+pub static METRIC_RUNTIME_TASKS_PANICKED: &str = "runtime.tasks_panicked";
+pub static METRIC_RUNTIME_TASK_DURATION: &str = "runtime.task_duration_ms";
+
+/// The upper bounds in milliseconds of the buckets of the task durations.
+pub static TASK_DURATION_BUCKETS_MS: [u64; 6] = [1, 10, 100, 1000, 10000, u64::MAX];
+
+/// A snapshot of the counters of the tasks of a runtime.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RuntimeMetrics {
+    pub tasks_spawned: u64,
+    /// The tasks polled and not finished yet.
+    pub tasks_active: u64,
+    /// The tasks completed or aborted, even before their first poll, besides the panicked ones.
+    pub tasks_finished: u64,
+    pub tasks_panicked: u64,
+    /// The number of the tasks whose duration since the first poll is no more than the upper
+    /// bound, one for each of `TASK_DURATION_BUCKETS_MS`, i.e. the buckets are cumulative.
+    /// A task never polled is not counted.
+    pub task_duration_buckets: Vec<u64>,
+}
+
+impl RuntimeMetrics {
+    /// Records the snapshot to the metrics recorder, labeled by the name of the runtime.
+    pub fn record(&self, runtime: &str) {
+        let labels = [("runtime", runtime.to_string())];
+        absolute_counter!(METRIC_RUNTIME_TASKS_SPAWNED, self.tasks_spawned, &labels);
+        gauge!(
+            METRIC_RUNTIME_TASKS_ACTIVE,
+            self.tasks_active as f64,
+            &labels
+        );
+        absolute_counter!(METRIC_RUNTIME_TASKS_FINISHED, self.tasks_finished, &labels);
+        absolute_counter!(METRIC_RUNTIME_TASKS_PANICKED, self.tasks_panicked, &labels);
+        for (le, count) in TASK_DURATION_BUCKETS_MS
+            .iter()
+            .zip(self.task_duration_buckets.iter())
+        {
+            let labels = [
+                ("runtime", runtime.to_string()),
+                ("le", match *le {
+                    u64::MAX => "+Inf".to_string(),
+                    le => le.to_string(),
+                }),
+            ];
+            absolute_counter!(METRIC_RUNTIME_TASK_DURATION, *count, &labels);
+        }
+    }
+}
+
+/// The counters of the tasks of the runtimes, see `RuntimeBuilder::counters`.
+#[derive(Debug, Default)]
+pub struct TaskCounters {
+    spawned: AtomicU64,
+    active: AtomicU64,
+    finished: AtomicU64,
+    panicked: AtomicU64,
+    durations: [AtomicU64; 6],
+}
+
+impl TaskCounters {
+    /// Counts the task as spawned, and as active since it is first polled.
+    pub(crate) fn track<T: Future>(self: &Arc<Self>, task: T) -> impl Future<Output = T::Output> {
+        // The guard is in the future from the spawn on, so that a task aborted before its first
+        // poll is counted as finished too, when the future is dropped.
+        let mut guard = TaskGuard::spawn(self.clone());
+        async move {
+            guard.start();
+            task.await
+        }
+    }
+
+    pub fn snapshot(&self) -> RuntimeMetrics {
+        let mut cumulative = 0;
+        RuntimeMetrics {
+            tasks_spawned: self.spawned.load(Ordering::Relaxed),
+            tasks_active: self.active.load(Ordering::Relaxed),
+            tasks_finished: self.finished.load(Ordering::Relaxed),
+            tasks_panicked: self.panicked.load(Ordering::Relaxed),
+            task_duration_buckets: self
+                .durations
+                .iter()
+                .map(|v| {
+                    cumulative += v.load(Ordering::Relaxed);
+                    cumulative
+                })
+                .collect(),
+        }
+    }
+}
+
+struct TaskGuard {
+    counters: Arc<TaskCounters>,
+    // Since the first poll, none if the task is not polled.
+    started: Option<Instant>,
+}
+
+impl TaskGuard {
+    fn spawn(counters: Arc<TaskCounters>) -> Self {
+        counters.spawned.fetch_add(1, Ordering::Relaxed);
+        TaskGuard {
+            counters,
+            started: None,
+        }
+    }
+
+    fn start(&mut self) {
+        self.counters.active.fetch_add(1, Ordering::Relaxed);
+        self.started = Some(Instant::now());
+    }
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        // A panicked task is dropped while unwinding, the runtime catches the panic.
+        match thread::panicking() {
+            true => self.counters.panicked.fetch_add(1, Ordering::Relaxed),
+            false => self.counters.finished.fetch_add(1, Ordering::Relaxed),
+        };
+
+        // A task aborted before its first poll has no duration.
+        if let Some(started) = self.started {
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+            // Counted in the first bucket it falls in, accumulated by the snapshot.
+            let bucket = TASK_DURATION_BUCKETS_MS
+                .iter()
+                .position(|le| elapsed_ms <= *le)
+                .unwrap_or(TASK_DURATION_BUCKETS_MS.len() - 1);
+            self.counters.durations[bucket].fetch_add(1, Ordering::Relaxed);
+            self.counters.active.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
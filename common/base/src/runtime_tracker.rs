@@ -13,10 +13,14 @@
 // limitations under the License.
 
 use std::alloc::Layout;
+use std::future::Future;
 use std::sync::atomic::AtomicI64;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
+use common_exception::ErrorCode;
+use common_exception::Result;
+
 #[thread_local]
 static mut TRACKER: *mut ThreadTracker = std::ptr::null_mut();
 
@@ -98,19 +102,82 @@ impl ThreadTracker {
     }
 }
 
+tokio::task_local! {
+    // The memory tracker of the task spawned by `Runtime::spawn_tracked`.
+    static TASK_MEMORY_TRACKER: Arc<MemoryTracker>;
+}
+
 pub struct MemoryTracker {
     memory_usage: AtomicI64,
+    // The max memory usage, 0 is unlimited.
+    limit: i64,
     parent_memory_tracker: Option<Arc<MemoryTracker>>,
 }
 
 impl MemoryTracker {
     pub fn create(parent_memory_tracker: Option<Arc<MemoryTracker>>) -> Arc<MemoryTracker> {
+        Self::create_with_limit(parent_memory_tracker, 0)
+    }
+
+    pub fn create_with_limit(
+        parent_memory_tracker: Option<Arc<MemoryTracker>>,
+        limit: i64,
+    ) -> Arc<MemoryTracker> {
         Arc::new(MemoryTracker {
             parent_memory_tracker,
+            limit,
             memory_usage: AtomicI64::new(0),
         })
     }
 
+    /// Errors with `MemoryExceeded` if the memory usage of the tracker or any of its parents
+    /// exceeds the limit.
+    pub fn check_limit(&self) -> Result<()> {
+        let usage = self.get_memory_usage();
+        if self.limit > 0 && usage > self.limit {
+            return Err(ErrorCode::MemoryExceeded(format!(
+                "memory usage {} bytes exceeds the limit {} bytes",
+                usage, self.limit
+            )));
+        }
+        match &self.parent_memory_tracker {
+            Some(parent_memory_tracker) => parent_memory_tracker.check_limit(),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs the task with `tracker` as the memory tracker of the task.
+    pub fn track_task<T: Future>(
+        tracker: Arc<MemoryTracker>,
+        task: T,
+    ) -> impl Future<Output = T::Output> {
+        TASK_MEMORY_TRACKER.scope(tracker, task)
+    }
+
+    /// The memory tracker of the current task, None if the task is not tracked.
+    pub fn current_task() -> Option<Arc<MemoryTracker>> {
+        TASK_MEMORY_TRACKER.try_with(|tracker| tracker.clone()).ok()
+    }
+
+    /// Accounts `size` bytes to the memory tracker of the current task, until the returned
+    /// guard is dropped. It does nothing if the task is not tracked.
+    pub fn alloc_task_memory(size: i64) -> TaskMemoryGuard {
+        let mut guard = TaskMemoryGuard {
+            tracker: Self::current_task(),
+            size: 0,
+        };
+        guard.grow(size);
+        guard
+    }
+
+    /// Errors with `MemoryExceeded` if the memory tracker of the current task exceeds the limit.
+    pub fn check_task_memory() -> Result<()> {
+        match Self::current_task() {
+            Some(tracker) => tracker.check_limit(),
+            None => Ok(()),
+        }
+    }
+
     #[inline]
     pub fn alloc_memory(&self, size: i64) {
         self.memory_usage.fetch_add(size, Ordering::Relaxed);
@@ -146,6 +213,29 @@ impl MemoryTracker {
     }
 }
 
+/// The memory accounted to the memory tracker of a task, given back when it is dropped.
+pub struct TaskMemoryGuard {
+    tracker: Option<Arc<MemoryTracker>>,
+    size: i64,
+}
+
+impl TaskMemoryGuard {
+    pub fn grow(&mut self, size: i64) {
+        if let Some(tracker) = &self.tracker {
+            tracker.alloc_memory(size);
+            self.size += size;
+        }
+    }
+}
+
+impl Drop for TaskMemoryGuard {
+    fn drop(&mut self) {
+        if let Some(tracker) = &self.tracker {
+            tracker.dealloc_memory(self.size);
+        }
+    }
+}
+
 pub struct RuntimeTracker {
     memory_tracker: Arc<MemoryTracker>,
 }
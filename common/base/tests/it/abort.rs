@@ -0,0 +1,77 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use common_base::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_abort_handle() -> Result<()> {
+    let (handle, registration) = AbortHandle::new_pair();
+    assert!(registration.check_aborted().is_ok());
+
+    // observed by all the tasks waiting for it
+    let waiters = (0..4)
+        .map(|_| {
+            let registration = registration.clone();
+            tokio::spawn(async move { registration.aborted().await })
+        })
+        .collect::<Vec<_>>();
+
+    handle.abort();
+    // idempotent
+    handle.abort();
+    assert!(handle.is_aborted());
+
+    for waiter in waiters {
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("the abort is not observed in time")
+            .unwrap();
+    }
+    let err = registration.check_aborted().unwrap_err();
+    assert_eq!(err.code(), ErrorCode::AbortedQuery("").code());
+    // a registration taken after the abort observes it as well
+    assert!(handle.registration().is_aborted());
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_try_spawn_abortable() -> Result<()> {
+    let runtime = tokio::runtime::Handle::current();
+    let (handle, registration) = AbortHandle::new_pair();
+
+    let completed = runtime.try_spawn_abortable(registration.clone(), async { 1 })?;
+    assert_eq!(completed.await.unwrap()?, 1);
+
+    let pending = runtime.try_spawn_abortable(registration.clone(), async {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+    })?;
+    handle.abort();
+    let res = tokio::time::timeout(Duration::from_secs(1), pending)
+        .await
+        .expect("the task is not aborted in time")
+        .unwrap();
+    assert_eq!(res.unwrap_err().code(), ErrorCode::AbortedQuery("").code());
+
+    // not run at all once aborted
+    let res = runtime
+        .try_spawn_abortable(registration, async { 1 })?
+        .await
+        .unwrap();
+    assert!(res.is_err());
+    Ok(())
+}
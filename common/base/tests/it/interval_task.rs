@@ -0,0 +1,169 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_base::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use tokio::runtime::Handle;
+
+#[tokio::test(start_paused = true)]
+async fn test_interval_task() -> Result<()> {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let task_runs = runs.clone();
+    let handle = spawn_interval(
+        &Handle::current(),
+        "test",
+        Duration::from_secs(10),
+        move || {
+            task_runs.fetch_add(1, Ordering::SeqCst);
+            async { Ok(()) }
+        },
+    )?;
+
+    // The runs at 0s..50s, with a jitter less than 1s.
+    tokio::time::sleep(Duration::from_secs(55)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 6);
+
+    let state = handle.state();
+    assert_eq!(state.runs, 6);
+    assert!(state.last_run_at.is_some());
+    assert!(state.last_error.is_none());
+
+    handle.stop().await;
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 6);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_interval_task_skip_on_overrun() -> Result<()> {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let task_runs = runs.clone();
+    let handle = spawn_interval(
+        &Handle::current(),
+        "test",
+        Duration::from_secs(10),
+        move || {
+            task_runs.fetch_add(1, Ordering::SeqCst);
+            async {
+                tokio::time::sleep(Duration::from_secs(25)).await;
+                Ok(())
+            }
+        },
+    )?;
+
+    // The runs at 0s and 30s, the ticks at 10s, 20s and 40s, 50s are skipped.
+    tokio::time::sleep(Duration::from_secs(55)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+    handle.stop().await;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_interval_task_last_error() -> Result<()> {
+    let handle = spawn_interval(
+        &Handle::current(),
+        "test",
+        Duration::from_secs(10),
+        || async { Err(ErrorCode::UnknownException("interval task error")) },
+    )?;
+
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    let state = handle.state();
+    assert_eq!(state.runs, 1);
+    assert!(state.last_error.unwrap().contains("interval task error"));
+
+    handle.stop().await;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_interval_task_stopped_on_drop() -> Result<()> {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let task_runs = runs.clone();
+    let handle = spawn_interval(
+        &Handle::current(),
+        "test",
+        Duration::from_secs(10),
+        move || {
+            task_runs.fetch_add(1, Ordering::SeqCst);
+            async { Ok(()) }
+        },
+    )?;
+
+    // The runs at 0s and 10s.
+    tokio::time::sleep(Duration::from_secs(15)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+    // A stop handle keeps the task running after the handle is dropped: the runs at 20s and 30s.
+    let stop_handle = handle.stop_handle();
+    drop(handle);
+    tokio::time::sleep(Duration::from_secs(20)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 4);
+
+    // Dropping the last stop handle stops the task, instead of running it with no period.
+    drop(stop_handle);
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 4);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_runtime_shutdown_stops_interval_tasks() -> Result<()> {
+    let runtime = Runtime::with_worker_threads(2, None)?;
+    let runs = Arc::new(AtomicUsize::new(0));
+    let task_runs = runs.clone();
+    let _handle = runtime.spawn_interval("test", Duration::from_millis(10), move || {
+        task_runs.fetch_add(1, Ordering::SeqCst);
+        async { Ok(()) }
+    })?;
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    runtime.shutdown_gracefully(Duration::from_secs(1)).await?;
+
+    let runs_at_shutdown = runs.load(Ordering::SeqCst);
+    assert!(runs_at_shutdown > 0);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), runs_at_shutdown);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_runtime_interval_task_stopped_on_drop() -> Result<()> {
+    let runtime = Runtime::with_worker_threads(2, None)?;
+    let runs = Arc::new(AtomicUsize::new(0));
+    let task_runs = runs.clone();
+    let handle = runtime.spawn_interval("test", Duration::from_millis(10), move || {
+        task_runs.fetch_add(1, Ordering::SeqCst);
+        async { Ok(()) }
+    })?;
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(runs.load(Ordering::SeqCst) > 0);
+
+    // The runtime does not keep the task running once its handle is dropped.
+    drop(handle);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let runs_at_drop = runs.load(Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), runs_at_drop);
+
+    runtime.shutdown_gracefully(Duration::from_secs(1)).await
+}
@@ -12,7 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod abort;
 mod format;
+mod interval_task;
 mod progress;
 mod runtime;
 mod stoppable;
@@ -12,11 +12,28 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
 
 use common_base::*;
+use common_exception::ErrorCode;
 use common_exception::Result;
+use common_tracing::tracing;
+use common_tracing::tracing::field::Field;
+use common_tracing::tracing::field::Visit;
+use common_tracing::tracing::span::Attributes;
+use common_tracing::tracing::Event;
+use common_tracing::tracing::Id;
+use common_tracing::tracing::Subscriber;
+use common_tracing::tracing_subscriber::layer::Context;
+use common_tracing::tracing_subscriber::prelude::*;
+use common_tracing::tracing_subscriber::registry::LookupSpan;
+use common_tracing::tracing_subscriber::Layer;
+use common_tracing::tracing_subscriber::Registry;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
 async fn test_runtime() -> Result<()> {
@@ -59,3 +76,526 @@ async fn test_runtime() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_runtime_named() -> Result<()> {
+    let runtime = Runtime::with_worker_threads_named(2, "test-named")?;
+    assert_eq!(runtime.name(), Some("test-named"));
+    assert_eq!(runtime.worker_count(), 2);
+
+    let thread_name = runtime
+        .spawn(async { std::thread::current().name().map(|v| v.to_string()) })
+        .await
+        .unwrap();
+    assert!(thread_name.unwrap().starts_with("test-named-"));
+
+    let runtime = Runtime::with_worker_threads(1, None)?;
+    assert_eq!(runtime.name(), None);
+    assert_eq!(runtime.worker_count(), 1);
+    Ok(())
+}
+
+/// Uses a stack frame of `SIZE` bytes, which overflows the stack smaller than it.
+#[inline(never)]
+fn use_stack_frame<const SIZE: usize>() -> u8 {
+    let mut frame = [0u8; SIZE];
+    unsafe {
+        std::ptr::write_volatile(&mut frame[SIZE - 1], 1);
+        std::ptr::read_volatile(&frame[SIZE - 1])
+    }
+}
+
+#[test]
+fn test_runtime_builder() -> Result<()> {
+    let runtime = RuntimeBuilder::new()
+        .worker_threads(2)
+        .thread_stack_size(16 * 1024 * 1024)
+        .thread_priority(0)
+        .name("builder")
+        .build()?;
+    assert_eq!(runtime.name(), Some("builder"));
+    assert_eq!(runtime.worker_count(), 2);
+
+    // Runs on a worker thread, not on the current thread of `block_on`.
+    let res = runtime.block_on(runtime.spawn(async {
+        let thread_name = std::thread::current().name().map(|v| v.to_string());
+        (use_stack_frame::<{ 8 * 1024 * 1024 }>(), thread_name)
+    }))?;
+    let (value, thread_name) = res.unwrap();
+    assert_eq!(value, 1);
+    assert!(thread_name.unwrap().starts_with("builder-"));
+    Ok(())
+}
+
+// It aborts the process by the stack overflow, run it by `--ignored` to see the overflow.
+#[test]
+#[ignore]
+fn test_runtime_builder_stack_overflow() {
+    let runtime = RuntimeBuilder::new()
+        .worker_threads(1)
+        .thread_stack_size(64 * 1024)
+        .build()
+        .unwrap();
+    let _ = runtime.block_on(runtime.spawn(async { use_stack_frame::<{ 8 * 1024 * 1024 }>() }));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_runtime_shutdown_gracefully() -> Result<()> {
+    let runtime = Runtime::with_worker_threads(2, None)?;
+    let completed = Arc::new(AtomicBool::new(false));
+    let task_completed = completed.clone();
+    runtime.spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        task_completed.store(true, Ordering::SeqCst);
+    });
+
+    runtime.shutdown_gracefully(Duration::from_secs(1)).await?;
+    assert!(completed.load(Ordering::SeqCst));
+
+    // No more tasks after the shutdown.
+    assert!(runtime.try_spawn(async {}).is_err());
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_runtime_shutdown_gracefully_timeout() -> Result<()> {
+    let runtime = Runtime::with_worker_threads(2, None)?;
+    runtime.spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    });
+
+    let res = runtime.shutdown_gracefully(Duration::from_millis(50)).await;
+    assert_eq!(res.unwrap_err().code(), ErrorCode::Timeout("").code());
+    Ok(())
+}
+
+/// Records the fields of the spans of each event, e.g. `query_id=q1`.
+#[derive(Clone, Default)]
+struct SpanFieldsLayer {
+    events: Arc<Mutex<Vec<Vec<String>>>>,
+}
+
+struct SpanFields(Vec<String>);
+
+impl Visit for SpanFields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.push(format!("{}={:?}", field.name(), value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push(format!("{}={}", field.name(), value));
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for SpanFieldsLayer {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = SpanFields(vec![]);
+        attrs.record(&mut fields);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(fields);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = vec![];
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                if let Some(span_fields) = span.extensions().get::<SpanFields>() {
+                    fields.extend(span_fields.0.iter().cloned());
+                }
+            }
+        }
+        self.events.lock().unwrap().push(fields);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_runtime_spawn_traced() -> Result<()> {
+    let layer = SpanFieldsLayer::default();
+    let _guard = tracing::subscriber::set_default(Registry::default().with(layer.clone()));
+    let runtime = Runtime::with_worker_threads(1, None)?;
+
+    // In the current span.
+    let span = tracing::info_span!("query", query_id = "q1");
+    let handle = span.in_scope(|| runtime.spawn(async { tracing::info!("in task") }));
+    handle.await.unwrap();
+
+    // In an explicit span.
+    let span = tracing::info_span!("query", query_id = "q2");
+    let handle = runtime.try_spawn_in_span(span, async { tracing::info!("in task") })?;
+    handle.await.unwrap();
+
+    // Untraced.
+    let handle = runtime.try_spawn_untraced(async { tracing::info!("in task") })?;
+    handle.await.unwrap();
+
+    let events = layer.events.lock().unwrap().clone();
+    assert_eq!(events, vec![vec!["query_id=q1".to_string()], vec![
+        "query_id=q2".to_string()
+    ]]);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_concurrency_limited_runtime() -> Result<()> {
+    let runtime = ConcurrencyLimitedRuntime::create(Runtime::with_worker_threads(8, None)?, 4);
+    assert_eq!(runtime.available_permits(), 4);
+
+    let running = Arc::new(AtomicUsize::new(0));
+    let high_water_mark = Arc::new(AtomicUsize::new(0));
+    let mut handles = Vec::with_capacity(100);
+    for _ in 0..100 {
+        let running = running.clone();
+        let high_water_mark = high_water_mark.clone();
+        handles.push(runtime.spawn(async move {
+            let current = running.fetch_add(1, Ordering::SeqCst) + 1;
+            high_water_mark.fetch_max(current, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            running.fetch_sub(1, Ordering::SeqCst);
+        }));
+    }
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    assert_eq!(high_water_mark.load(Ordering::SeqCst), 4);
+    assert_eq!(runtime.available_permits(), 4);
+
+    // The blocking tasks are limited too.
+    let mut handles = Vec::with_capacity(20);
+    for i in 0..20 {
+        let running = running.clone();
+        let high_water_mark = high_water_mark.clone();
+        handles.push(runtime.try_spawn_blocking(move || {
+            let current = running.fetch_add(1, Ordering::SeqCst) + 1;
+            high_water_mark.fetch_max(current, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(5));
+            running.fetch_sub(1, Ordering::SeqCst);
+            i
+        })?);
+    }
+    for (i, handle) in handles.into_iter().enumerate() {
+        assert_eq!(handle.await.unwrap(), i);
+    }
+
+    assert_eq!(high_water_mark.load(Ordering::SeqCst), 4);
+    assert_eq!(runtime.available_permits(), 4);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_runtime_metrics() -> Result<()> {
+    let runtime = Runtime::with_worker_threads(2, None)?;
+    assert_eq!(runtime.metrics(), RuntimeMetrics {
+        task_duration_buckets: vec![0; TASK_DURATION_BUCKETS_MS.len()],
+        ..Default::default()
+    });
+
+    for _ in 0..10 {
+        runtime.spawn(async {}).await.unwrap();
+    }
+    let metrics = runtime.metrics();
+    assert_eq!(metrics.tasks_spawned, 10);
+    assert_eq!(metrics.tasks_finished, 10);
+    assert_eq!(metrics.tasks_active, 0);
+    // The buckets are cumulative, all the tasks are in the last one.
+    let buckets = &metrics.task_duration_buckets;
+    assert!(buckets.windows(2).all(|w| w[0] <= w[1]));
+    assert_eq!(buckets.last(), Some(&10));
+
+    // Active until it is finished.
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    let handle = runtime.spawn(async move {
+        let _ = rx.await;
+    });
+    while runtime.metrics().tasks_active == 0 {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    tx.send(()).unwrap();
+    handle.await.unwrap();
+    assert_eq!(runtime.metrics().tasks_active, 0);
+
+    // A panicked task is counted, and the runtime keeps running.
+    let res = runtime.spawn(async { panic!("test panic") }).await;
+    assert!(res.unwrap_err().is_panic());
+    let metrics = runtime.metrics();
+    assert_eq!(metrics.tasks_spawned, 12);
+    assert_eq!(metrics.tasks_finished, 11);
+    assert_eq!(metrics.tasks_panicked, 1);
+    assert_eq!(metrics.tasks_active, 0);
+    runtime.spawn(async {}).await.unwrap();
+    assert_eq!(runtime.metrics().tasks_finished, 12);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_runtime_metrics_aborted_before_poll() -> Result<()> {
+    let runtime = Runtime::with_worker_threads(1, None)?;
+
+    // The only worker is blocked, the task spawned next is not polled until it is aborted.
+    let (tx, rx) = std::sync::mpsc::channel::<()>();
+    let blocking = runtime.spawn(async move {
+        let _ = rx.recv();
+    });
+    while runtime.metrics().tasks_active == 0 {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    let aborted = runtime.spawn(async {});
+    aborted.abort();
+    tx.send(()).unwrap();
+    blocking.await.unwrap();
+    assert!(aborted.await.unwrap_err().is_cancelled());
+
+    let metrics = runtime.metrics();
+    assert_eq!(metrics.tasks_spawned, 2);
+    assert_eq!(metrics.tasks_finished, 2);
+    assert_eq!(metrics.tasks_active, 0);
+    // The aborted task has no duration.
+    assert_eq!(metrics.task_duration_buckets.last(), Some(&1));
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_runtime_shared_counters() -> Result<()> {
+    let counters = Arc::new(TaskCounters::default());
+    for _ in 0..2 {
+        let runtime = RuntimeBuilder::new()
+            .worker_threads(1)
+            .counters(counters.clone())
+            .build()?;
+        runtime.spawn(async {}).await.unwrap();
+        drop(runtime);
+    }
+    // Kept after the runtimes are dropped.
+    let metrics = counters.snapshot();
+    assert_eq!(metrics.tasks_spawned, 2);
+    assert_eq!(metrics.tasks_finished, 2);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_runtime_spawn_blocking() -> Result<()> {
+    let runtime = Runtime::with_blocking_threads_named(1, Some(2), "test-blocking")?;
+    let blocking = runtime.try_spawn_blocking(|| {
+        std::thread::sleep(Duration::from_millis(500));
+        1
+    })?;
+
+    // The only worker thread is not occupied by the blocking task.
+    let task = runtime.spawn(async { 2 });
+    let res = tokio::time::timeout(Duration::from_millis(100), task).await;
+    assert_eq!(res.unwrap().unwrap(), 2);
+
+    assert_eq!(blocking.await.unwrap(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_runtime_block_on() -> Result<()> {
+    let runtime = Arc::new(Runtime::with_worker_threads(1, None)?);
+    assert_eq!(runtime.block_on(async { 1 })?, 1);
+
+    // With a timeout.
+    let res = runtime.block_on_with_timeout(async { 2 }, Duration::from_secs(1))?;
+    assert_eq!(res, 2);
+    let res = runtime.block_on_with_timeout(
+        tokio::time::sleep(Duration::from_secs(10)),
+        Duration::from_millis(50),
+    );
+    assert_eq!(res.unwrap_err().code(), ErrorCode::Timeout("").code());
+
+    // Nested in the same runtime.
+    let nested_runtime = runtime.clone();
+    let handle = runtime.spawn(async move { nested_runtime.block_on(async {}) });
+    let err = runtime.block_on(handle)?.unwrap().unwrap_err();
+    assert_eq!(err.code(), ErrorCode::TokioError("").code());
+    assert!(err.message().contains("same runtime"));
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_runtime_spawn_tracked() -> Result<()> {
+    let runtime = Runtime::with_worker_threads(1, None)?;
+    let session_tracker = MemoryTracker::create(None);
+    let query_tracker = MemoryTracker::create_with_limit(Some(session_tracker.clone()), 1024);
+
+    let tracker = query_tracker.clone();
+    let res = runtime
+        .spawn_tracked(query_tracker.clone(), async move {
+            let mut memory = MemoryTracker::alloc_task_memory(1000);
+            MemoryTracker::check_task_memory()?;
+            assert_eq!(tracker.get_memory_usage(), 1000);
+
+            memory.grow(100);
+            MemoryTracker::check_task_memory()
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::MemoryExceeded("").code()
+    );
+
+    // Given back to the query and its parent once dropped.
+    assert_eq!(query_tracker.get_memory_usage(), 0);
+    assert_eq!(session_tracker.get_memory_usage(), 0);
+
+    // Untracked tasks are not limited.
+    let res = runtime
+        .spawn(async move {
+            let _memory = MemoryTracker::alloc_task_memory(1 << 30);
+            assert!(MemoryTracker::current_task().is_none());
+            MemoryTracker::check_task_memory()
+        })
+        .await
+        .unwrap();
+    assert!(res.is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_global_io_runtime_not_blocked_by_query_runtime() -> Result<()> {
+    GlobalIORuntime::set_instance(Arc::new(Runtime::with_worker_threads_named(1, "test-io")?));
+
+    // Saturates the query runtime by spin loops.
+    let query_runtime = Runtime::with_worker_threads(1, None)?;
+    let stop = Arc::new(AtomicBool::new(false));
+    for _ in 0..4 {
+        let stop = stop.clone();
+        query_runtime.spawn(async move {
+            while !stop.load(Ordering::Relaxed) {
+                std::hint::spin_loop();
+            }
+        });
+    }
+
+    let start = std::time::Instant::now();
+    let io = GlobalIORuntime::instance().spawn(async {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        std::thread::current().name().map(|v| v.to_string())
+    });
+    let thread_name = query_runtime.block_on(io)?.unwrap();
+    stop.store(true, Ordering::Relaxed);
+
+    assert!(thread_name.unwrap().starts_with("test-io-"));
+    assert!(start.elapsed() < Duration::from_secs(1));
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_tracked_join_handle() -> Result<()> {
+    let runtime = Runtime::with_worker_threads(1, None)?;
+    assert_eq!(runtime.spawn(async { 1 }).err_into().await?, 1);
+
+    let err = runtime
+        .spawn(async { panic!("test panic") })
+        .err_into()
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::PanicError("").code());
+    assert!(err.message().contains("test panic"));
+
+    let handle = runtime
+        .spawn(tokio::time::sleep(Duration::from_secs(10)))
+        .err_into();
+    handle.abort();
+    let err = handle.await.unwrap_err();
+    assert_eq!(err.code(), ErrorCode::TokioError("").code());
+    assert!(err.message().contains("cancelled"));
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_with_timeout() -> Result<()> {
+    let fut = async {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        1
+    };
+    assert_eq!(
+        with_timeout(fut, Duration::from_secs(10), "sleep").await?,
+        1
+    );
+
+    let fut = tokio::time::sleep(Duration::from_secs(15));
+    let res = with_timeout(fut, Duration::from_secs(10), "sleep").await;
+    let err = res.unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Timeout("").code());
+    assert_eq!(err.message(), "sleep is not completed in 10s");
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_try_spawn_timeout() -> Result<()> {
+    let handle = tokio::runtime::Handle::current();
+    let join_handle = handle.try_spawn_timeout("sleep", Duration::from_secs(10), async {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        1
+    })?;
+    assert_eq!(join_handle.await.unwrap()?, 1);
+
+    let join_handle = handle.try_spawn_timeout("sleep", Duration::from_secs(10), async {
+        tokio::time::sleep(Duration::from_secs(15)).await;
+        1
+    })?;
+    let err = join_handle.await.unwrap().unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Timeout("").code());
+    assert_eq!(err.message(), "sleep is not completed in 10s");
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_runtime_is_shutdown() -> Result<()> {
+    let runtime = Runtime::with_worker_threads(2, None)?;
+    assert!(!runtime.is_shutdown());
+    runtime.shutdown_gracefully(Duration::from_secs(1)).await?;
+    assert!(runtime.is_shutdown());
+    Ok(())
+}
+
+/// The number of the threads of the process, whose names start with `prefix`.
+#[cfg(target_os = "linux")]
+fn count_threads(prefix: &str) -> usize {
+    std::fs::read_dir("/proc/self/task")
+        .unwrap()
+        .filter_map(|task| std::fs::read_to_string(task.ok()?.path().join("comm")).ok())
+        .filter(|comm| comm.starts_with(prefix))
+        .count()
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_runtime_drop_no_thread_leaks() -> Result<()> {
+    // The threads are named by the runtime, not to count the threads of the other tests.
+    let name = "drop-leak";
+    assert_eq!(count_threads(name), 0);
+    for _ in 0..50 {
+        let runtime = Runtime::with_worker_threads_named(2, name)?;
+        runtime.block_on(runtime.spawn(async { 1 }))?.unwrap();
+        runtime
+            .block_on(runtime.try_spawn_blocking(|| 1)?)?
+            .unwrap();
+        drop(runtime);
+    }
+
+    // The exiting threads may be listed for a moment after they are joined by tokio.
+    for _ in 0..100 {
+        if count_threads(name) == 0 {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(count_threads(name), 0);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_runtime_drop_in_async_context_not_blocked() -> Result<()> {
+    let runtime = Runtime::with_worker_threads(2, None)?;
+    // The runtime can not shutdown until the blocking task finishes.
+    runtime.try_spawn_blocking(|| std::thread::sleep(Duration::from_millis(500)))?;
+
+    let start = std::time::Instant::now();
+    drop(runtime);
+    assert!(start.elapsed() < Duration::from_millis(250));
+    Ok(())
+}
@@ -150,4 +150,10 @@ where
 
         unreachable!("the loop should always return!");
     }
+
+    /// Drop the item of `key`, e.g., a connection found broken, so that the next `get()` builds a new one.
+    pub fn invalidate(&self, key: &Mgr::Key) {
+        let mut items = self.items.lock().unwrap();
+        items.remove(key);
+    }
 }
@@ -137,6 +137,12 @@ build_exceptions! {
     // Network error codes.
     NetworkRequestError(1073),
 
+    // Memory error codes.
+    MemoryExceeded(1074),
+
+    // Task error codes.
+    PanicError(1075),
+
     // Tenant error codes.
     TenantIsEmpty(1101),
     IndexOutOfBounds(1102),
@@ -148,9 +154,17 @@ build_exceptions! {
     MetaServiceError(2001),
     InvalidConfig(2002),
     MetaStorageError(2003),
+    // The meta service is unreachable and the outcome of a request is unknown.
+    MetaServiceUnavailable(2004),
+    // A request to the meta service is not completed before its deadline.
+    MetaServiceTimeout(2005),
 
     TableVersionMismatched(2009),
     OCCRetryFailure(2011),
+    KVSeqMismatched(2012),
+    IllegalKVCounter(2013),
+    // A kv key or a segment of it can not be stored, e.g., it is empty or contains a NUL.
+    InvalidMetaKey(2014),
 
     // User api error codes.
     UnknownUser(2201),
@@ -158,6 +172,13 @@ build_exceptions! {
     IllegalUserInfoFormat(2203),
     UnknownRole(2204),
     IllegalUserSettingFormat(2205),
+    QuotaExceeded(2206),
+    AmbiguousUser(2207),
+    PasswordPolicyViolation(2208),
+    PasswordMustBeChanged(2209),
+    BuiltinUserProtected(2210),
+    IllegalUserIdentity(2211),
+    UserSessionCountConflict(2212),
 
     // Meta api error codes.
     DatabaseAlreadyExists(2301),
@@ -197,12 +218,22 @@ build_exceptions! {
     WarehouseAlreadyExists(2902),
     IllegalWarehouseMetaFormat(2903),
     IllegalWarehouseInfoFormat(2904),
+
+    // Network policy error codes.
+    UnknownNetworkPolicy(2951),
+    NetworkPolicyAlreadyExists(2952),
+    IllegalNetworkPolicyFormat(2953),
+    NetworkPolicyIsUsedByUser(2954),
+
+    // Lease error codes.
+    LeaseNotHeld(2961),
 }
 
 // Storage errors [3001, 4000].
 build_exceptions! {
     StorageNotFound(3001),
     StoragePermissionDenied(3002),
+    InsertLimitExceeded(3003),
     StorageOther(4000)
 }
 
@@ -21,6 +21,7 @@ use common_exception::Result;
 use super::cast_with_type::cast_column_field;
 use crate::scalars::function::Function;
 use crate::scalars::FunctionContext;
+use crate::scalars::Monotonicity;
 
 #[derive(Clone)]
 pub struct CastFunction {
@@ -57,6 +58,38 @@ impl CastFunction {
             cast_type: Arc::new(nullable_type),
         }))
     }
+
+    /// Whether every value of `from` keeps its order after being cast into `to`.
+    /// Narrowing integer casts wrap around on overflow, so they are excluded.
+    fn is_order_preserving(from: &TypeID, to: &TypeID) -> bool {
+        if from == to {
+            return true;
+        }
+
+        if from.is_date_or_date_time() && to.is_date_or_date_time() {
+            return true;
+        }
+
+        if !from.is_numeric() || !to.is_numeric() {
+            return false;
+        }
+
+        if to.is_floating() {
+            return true;
+        }
+
+        if from.is_floating() {
+            return false;
+        }
+
+        match (from.numeric_byte_size(), to.numeric_byte_size()) {
+            (Ok(from_size), Ok(to_size)) if from.is_signed_integer() == to.is_signed_integer() => {
+                from_size <= to_size
+            }
+            (Ok(from_size), Ok(to_size)) if from.is_unsigned_integer() => from_size < to_size,
+            _ => false,
+        }
+    }
 }
 
 impl Function for CastFunction {
@@ -68,6 +101,24 @@ impl Function for CastFunction {
         self.cast_type.clone()
     }
 
+    fn get_monotonicity(&self, args: &[Monotonicity]) -> Result<Monotonicity> {
+        if args[0].is_constant {
+            return Ok(Monotonicity::clone_without_range(&args[0]));
+        }
+
+        // The source type is only known from the boundaries of the argument.
+        let from_type = match (&args[0].left, &args[0].right) {
+            (Some(left), Some(_)) => remove_nullable(left.data_type()).data_type_id(),
+            _ => return Ok(Monotonicity::default()),
+        };
+        let to_type = remove_nullable(&self.cast_type).data_type_id();
+
+        if args[0].is_monotonic && Self::is_order_preserving(&from_type, &to_type) {
+            return Ok(Monotonicity::clone_without_range(&args[0]));
+        }
+        Ok(Monotonicity::default())
+    }
+
     fn eval(
         &self,
         _func_ctx: FunctionContext,
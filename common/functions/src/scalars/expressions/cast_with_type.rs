@@ -178,6 +178,63 @@ pub fn cast_with_type(
     Ok(result)
 }
 
+/// Check if the columns of `from_type` can be casted to `data_type`, without looking at the values.
+/// The casts which depend on the values, e.g. from strings, are accepted and checked while casting.
+pub fn check_cast_types(from_type: &DataTypePtr, data_type: &DataTypePtr) -> Result<()> {
+    if from_type == data_type || data_type.data_type_id() == TypeID::Null {
+        return Ok(());
+    }
+
+    let error = || {
+        Err(ErrorCode::BadDataValueType(format!(
+            "Can't cast from {} to {}",
+            from_type.name(),
+            data_type.name()
+        )))
+    };
+
+    if from_type.data_type_id() == TypeID::Null {
+        return match data_type.is_nullable() || data_type.data_type_id() == TypeID::Boolean {
+            true => Ok(()),
+            false => error(),
+        };
+    }
+
+    let nonull_from_type = remove_nullable(from_type);
+    let nonull_data_type = remove_nullable(data_type);
+    let from_id = nonull_from_type.data_type_id();
+    let to_id = nonull_data_type.data_type_id();
+
+    if from_id == TypeID::String || from_id.is_variant() {
+        return Ok(());
+    }
+
+    if from_id.is_date_or_date_time() && (to_id == TypeID::String || to_id.is_date_or_date_time()) {
+        return Ok(());
+    }
+
+    if to_id.is_variant() {
+        return match to_id == TypeID::Variant
+            && (from_id.is_numeric() || from_id == TypeID::Boolean)
+        {
+            true => Ok(()),
+            false => error(),
+        };
+    }
+
+    if to_id == TypeID::String && (from_id.is_numeric() || from_id == TypeID::Boolean) {
+        return Ok(());
+    }
+
+    match cast::can_cast_types(
+        &nonull_from_type.arrow_type(),
+        &nonull_data_type.arrow_type(),
+    ) {
+        true => Ok(()),
+        false => error(),
+    }
+}
+
 pub fn cast_to_variant(
     column: &ColumnRef,
     from_type: &DataTypePtr,
@@ -656,6 +656,35 @@ fn test_variant_cast_function() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_check_cast_types() -> Result<()> {
+    let castable = vec![
+        (i32::to_data_type(), i64::to_data_type()),
+        (i64::to_data_type(), u8::to_data_type()),
+        (Vu8::to_data_type(), i32::to_data_type()),
+        (u64::to_data_type(), Vu8::to_data_type()),
+        (Date16Type::arc(), Vu8::to_data_type()),
+        (f64::to_data_type(), VariantType::arc()),
+        (NullType::arc(), wrap_nullable(&i32::to_data_type())),
+        (wrap_nullable(&i32::to_data_type()), i64::to_data_type()),
+    ];
+    for (from_type, data_type) in castable {
+        check_cast_types(&from_type, &data_type)?;
+    }
+
+    let not_castable = vec![
+        (Date16Type::arc(), VariantType::arc()),
+        (i32::to_data_type(), VariantArrayType::arc()),
+        (NullType::arc(), i32::to_data_type()),
+    ];
+    for (from_type, data_type) in not_castable {
+        let result = check_cast_types(&from_type, &data_type);
+        assert!(result.is_err(), "{:?} to {:?}", from_type, data_type);
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_binary_contains() {
     fn contains(a: &'_ [u8], b: &'_ [u8], _ctx: &mut EvalContext) -> bool {
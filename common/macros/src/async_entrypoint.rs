@@ -114,7 +114,7 @@ fn parse_knobs(mut input: syn::ItemFn, is_test: bool, has_tracker: bool) -> Toke
             {
                 let runtime = #rt;
                 let wait_future = #wait_in_future;
-                #tail_return runtime.block_on(wait_future)#tail_semicolon
+                #tail_return runtime.block_on(wait_future).unwrap()#tail_semicolon
             }
         }
     })
@@ -17,12 +17,17 @@ use std::sync::Arc;
 use std::time::Duration;
 use std::time::UNIX_EPOCH;
 
-use common_base::escape_for_key;
-use common_base::unescape_for_key;
+use common_base::spawn_interval;
+use common_base::tokio::runtime::Handle;
+use common_base::IntervalTaskHandle;
+use common_base::IntervalTaskState;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_meta_api::KVApi;
+use common_meta_types::escape_key_segment;
+use common_meta_types::unescape_key_segment;
 use common_meta_types::KVMeta;
+use common_meta_types::KeyBuilder;
 use common_meta_types::MatchSeq;
 use common_meta_types::NodeInfo;
 use common_meta_types::OkOrExist;
@@ -30,8 +35,11 @@ use common_meta_types::Operation;
 use common_meta_types::SeqV;
 use common_meta_types::UpsertKVAction;
 use common_meta_types::UpsertKVActionReply;
+use common_tracing::tracing;
 
 use crate::cluster::ClusterApi;
+use crate::cluster::ClusterNodeCache;
+use crate::kv_list::list_typed;
 
 pub static CLUSTER_API_KEY_PREFIX: &str = "__fd_clusters";
 
@@ -57,15 +65,19 @@ impl ClusterMgr {
         Ok(ClusterMgr {
             kv_api,
             lift_time,
-            cluster_prefix: format!(
-                "{}/{}/{}/databend_query",
-                CLUSTER_API_KEY_PREFIX,
-                escape_for_key(tenant)?,
-                escape_for_key(cluster_id)?
-            ),
+            // The cluster id is empty by default, it is not validated as a segment.
+            cluster_prefix: KeyBuilder::new(CLUSTER_API_KEY_PREFIX)
+                .push(tenant)?
+                .push_raw(escape_key_segment(cluster_id))
+                .push_raw("databend_query")
+                .done(),
         })
     }
 
+    fn node_key(&self, node_id: &str) -> Result<String> {
+        Ok(KeyBuilder::new(&self.cluster_prefix).push(node_id)?.done())
+    }
+
     fn new_lift_time(&self) -> KVMeta {
         let now = std::time::SystemTime::now();
         let expire_at = now
@@ -79,6 +91,36 @@ impl ClusterMgr {
     }
 }
 
+impl ClusterMgr {
+    /// Starts a cache of the live nodes, kept up to date by watching the nodes.
+    pub async fn start_node_cache(&self) -> Result<ClusterNodeCache> {
+        ClusterNodeCache::start(self.kv_api.clone(), self.cluster_prefix.clone()).await
+    }
+}
+
+/// Lists the nodes under `cluster_prefix`, an expired node is not listed by the meta service.
+pub(crate) async fn list_nodes(
+    kv_api: &dyn KVApi,
+    cluster_prefix: &str,
+) -> Result<Vec<SeqV<NodeInfo>>> {
+    let listed = list_typed::<NodeInfo>(kv_api, cluster_prefix).await?;
+    for (node_key, e) in &listed.invalid {
+        tracing::warn!("skip invalid node {}: {}", node_key, e);
+    }
+
+    let mut nodes = Vec::with_capacity(listed.items.len());
+    for (node_key, mut node) in listed.items {
+        node.data.id = node_id_of(cluster_prefix, &node_key)?;
+        nodes.push(node);
+    }
+    Ok(nodes)
+}
+
+/// The node id is the last segment of the key of a node, escaped.
+pub(crate) fn node_id_of(cluster_prefix: &str, node_key: &str) -> Result<String> {
+    unescape_key_segment(&node_key[cluster_prefix.len() + 1..])
+}
+
 #[async_trait::async_trait]
 impl ClusterApi for ClusterMgr {
     async fn add_node(&self, node: NodeInfo) -> Result<u64> {
@@ -86,7 +128,7 @@ impl ClusterApi for ClusterMgr {
         let seq = MatchSeq::Exact(0);
         let meta = Some(self.new_lift_time());
         let value = Operation::Update(serde_json::to_vec(&node)?);
-        let node_key = format!("{}/{}", self.cluster_prefix, escape_for_key(&node.id)?);
+        let node_key = self.node_key(&node.id)?;
         let upsert_node = self
             .kv_api
             .upsert_kv(UpsertKVAction::new(&node_key, seq, value, meta));
@@ -103,22 +145,12 @@ impl ClusterApi for ClusterMgr {
     }
 
     async fn get_nodes(&self) -> Result<Vec<NodeInfo>> {
-        let values = self.kv_api.prefix_list_kv(&self.cluster_prefix).await?;
-
-        let mut nodes_info = Vec::with_capacity(values.len());
-        for (node_key, value) in values {
-            let mut node_info = serde_json::from_slice::<NodeInfo>(&value.data)?;
-
-            let node_key = unescape_for_key(&node_key)?;
-            node_info.id = node_key[self.cluster_prefix.len() + 1..].to_string();
-            nodes_info.push(node_info);
-        }
-
-        Ok(nodes_info)
+        let nodes = list_nodes(self.kv_api.as_ref(), &self.cluster_prefix).await?;
+        Ok(nodes.into_iter().map(|n| n.data).collect())
     }
 
     async fn drop_node(&self, node_id: String, seq: Option<u64>) -> Result<()> {
-        let node_key = format!("{}/{}", self.cluster_prefix, escape_for_key(&node_id)?);
+        let node_key = self.node_key(&node_id)?;
         let upsert_node = self.kv_api.upsert_kv(UpsertKVAction::new(
             &node_key,
             seq.into(),
@@ -141,7 +173,7 @@ impl ClusterApi for ClusterMgr {
 
     async fn heartbeat(&self, node: &NodeInfo, seq: Option<u64>) -> Result<u64> {
         let meta = Some(self.new_lift_time());
-        let node_key = format!("{}/{}", self.cluster_prefix, escape_for_key(&node.id)?);
+        let node_key = self.node_key(&node.id)?;
         let seq = match seq {
             None => MatchSeq::GE(1),
             Some(exact) => MatchSeq::Exact(exact),
@@ -161,3 +193,41 @@ impl ClusterApi for ClusterMgr {
         }
     }
 }
+
+/// Keeps a node alive by a heartbeat in a background task, until it is dropped.
+///
+/// The node is not removed when the guard is dropped, it expires after the lift time, unless it
+/// is dropped explicitly by `ClusterApi::drop_node`.
+pub struct NodeHeartbeat {
+    task: IntervalTaskHandle,
+}
+
+impl NodeHeartbeat {
+    /// Sends a heartbeat every `interval` in the current runtime, see `spawn_interval`. The
+    /// interval should be well below the lift time of the node: a failed heartbeat is only
+    /// retried on the next tick, and a tick is skipped while the previous heartbeat is going.
+    pub fn start(
+        cluster_api: Arc<dyn ClusterApi>,
+        node: NodeInfo,
+        interval: Duration,
+    ) -> Result<Self> {
+        let name = format!("node-heartbeat-{}", node.id);
+        let task = spawn_interval(&Handle::current(), &name, interval, move || {
+            let cluster_api = cluster_api.clone();
+            let node = node.clone();
+            async move { cluster_api.heartbeat(&node, None).await.map(|_| ()) }
+        })?;
+        Ok(NodeHeartbeat { task })
+    }
+
+    /// The state of the heartbeat task, e.g., the error of the last heartbeat.
+    pub fn state(&self) -> IntervalTaskState {
+        self.task.state()
+    }
+
+    /// Stops the heartbeat and waits for the one in progress, if any, so that the node is not
+    /// added back by it after being dropped.
+    pub async fn stop(self) {
+        self.task.stop().await
+    }
+}
@@ -14,6 +14,9 @@
 
 mod cluster_api;
 mod cluster_mgr;
+mod node_cache;
 
 pub use cluster_api::ClusterApi;
 pub use cluster_mgr::ClusterMgr;
+pub use cluster_mgr::NodeHeartbeat;
+pub use node_cache::ClusterNodeCache;
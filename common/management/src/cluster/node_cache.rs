@@ -0,0 +1,180 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use common_base::tokio;
+use common_base::tokio::sync::oneshot;
+use common_exception::Result;
+use common_infallible::RwLock;
+use common_meta_api::KVApi;
+use common_meta_api::WatchStream;
+use common_meta_types::NodeInfo;
+use common_meta_types::SeqV;
+use common_meta_types::WatchEvent;
+use common_tracing::tracing;
+use futures::StreamExt;
+
+use crate::cluster::cluster_mgr::list_nodes;
+use crate::cluster::cluster_mgr::node_id_of;
+
+type Nodes = Arc<RwLock<BTreeMap<String, SeqV<NodeInfo>>>>;
+
+/// The live nodes of a cluster, kept up to date by watching the keys of the nodes.
+///
+/// If the meta service can not be watched, e.g., an embedded one, or the watch stream breaks, the
+/// nodes are listed from the meta service on every read instead.
+pub struct ClusterNodeCache {
+    kv_api: Arc<dyn KVApi>,
+    cluster_prefix: String,
+    nodes: Nodes,
+    watching: Arc<AtomicBool>,
+    stop_tx: Option<oneshot::Sender<()>>,
+}
+
+impl ClusterNodeCache {
+    pub(crate) async fn start(kv_api: Arc<dyn KVApi>, cluster_prefix: String) -> Result<Self> {
+        // Watch before listing, not to miss a change in between.
+        let watch_prefix = format!("{}/", cluster_prefix);
+        let strm = match kv_api.watch(&watch_prefix, None).await {
+            Ok(strm) => Some(strm),
+            Err(e) => {
+                tracing::info!("list the nodes on every read, can not watch them: {}", e);
+                None
+            }
+        };
+
+        let nodes = list_nodes(kv_api.as_ref(), &cluster_prefix).await?;
+        let nodes: Nodes = Arc::new(RwLock::new(
+            nodes.into_iter().map(|n| (n.data.id.clone(), n)).collect(),
+        ));
+
+        let watching = Arc::new(AtomicBool::new(strm.is_some()));
+        let (stop_tx, stop_rx) = oneshot::channel::<()>();
+        if let Some(strm) = strm {
+            tokio::spawn(Self::watch_loop(
+                strm,
+                cluster_prefix.clone(),
+                nodes.clone(),
+                watching.clone(),
+                stop_rx,
+            ));
+        }
+
+        Ok(ClusterNodeCache {
+            kv_api,
+            cluster_prefix,
+            nodes,
+            watching,
+            stop_tx: Some(stop_tx),
+        })
+    }
+
+    /// The live nodes, the expired ones are not returned even if the removal is not seen yet.
+    pub async fn get_nodes(&self) -> Result<Vec<NodeInfo>> {
+        if !self.watching.load(Ordering::SeqCst) {
+            let nodes = list_nodes(self.kv_api.as_ref(), &self.cluster_prefix).await?;
+            return Ok(nodes.into_iter().map(|n| n.data).collect());
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs();
+
+        let nodes = self.nodes.read();
+        Ok(nodes
+            .values()
+            .filter(|n| n.get_expire_at() >= now)
+            .map(|n| n.data.clone())
+            .collect())
+    }
+
+    /// Whether the nodes are kept by watching, or listed on every read.
+    pub fn is_watching(&self) -> bool {
+        self.watching.load(Ordering::SeqCst)
+    }
+
+    async fn watch_loop(
+        mut strm: WatchStream,
+        cluster_prefix: String,
+        nodes: Nodes,
+        watching: Arc<AtomicBool>,
+        mut stop_rx: oneshot::Receiver<()>,
+    ) {
+        loop {
+            let ev = tokio::select! {
+                _ = &mut stop_rx => return,
+                ev = strm.next() => ev,
+            };
+
+            let res = match ev {
+                Some(Ok(ev)) => Self::apply_event(&cluster_prefix, &nodes, ev),
+                Some(Err(e)) => Err(e.into()),
+                None => {
+                    tracing::warn!("the watch of the nodes is closed, list them on every read");
+                    watching.store(false, Ordering::SeqCst);
+                    return;
+                }
+            };
+
+            if let Err(e) = res {
+                tracing::warn!("stop watching the nodes, list them on every read: {}", e);
+                watching.store(false, Ordering::SeqCst);
+                return;
+            }
+        }
+    }
+
+    /// Applies a change of a node, unless the cache already has a newer one, e.g., from the listing.
+    fn apply_event(cluster_prefix: &str, nodes: &Nodes, ev: WatchEvent) -> Result<()> {
+        let id = node_id_of(cluster_prefix, &ev.key)?;
+        let mut nodes = nodes.write();
+
+        let cached_seq = nodes.get(&id).map(|n| n.seq).unwrap_or_default();
+        match ev.current {
+            Some(current) => {
+                if current.seq > cached_seq {
+                    match serde_json::from_slice::<NodeInfo>(&current.data) {
+                        Ok(mut node) => {
+                            node.id = id.clone();
+                            nodes.insert(id, SeqV::with_meta(current.seq, current.meta, node));
+                        }
+                        Err(e) => tracing::warn!("skip invalid node {}: {}", ev.key, e),
+                    }
+                }
+            }
+            None => {
+                let removed_seq = ev.prev.map(|p| p.seq).unwrap_or(u64::MAX);
+                if removed_seq >= cached_seq {
+                    nodes.remove(&id);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ClusterNodeCache {
+    fn drop(&mut self) {
+        if let Some(stop_tx) = self.stop_tx.take() {
+            let _ = stop_tx.send(());
+        }
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_meta_api::KVApi;
+use common_meta_types::SeqV;
+use common_tracing::tracing;
+use serde::de::DeserializeOwned;
+
+/// The values under a prefix, decoded, and the ones failed to decode.
+///
+/// A corrupt value does not fail the listing, so that it does not hide all the others.
+#[derive(Debug)]
+pub struct ListTyped<T> {
+    /// The decoded values with their keys, in the order of the keys.
+    pub items: Vec<(String, SeqV<T>)>,
+
+    /// The keys of the values failed to decode, with the errors.
+    pub invalid: Vec<(String, ErrorCode)>,
+}
+
+impl<T> ListTyped<T> {
+    /// Logs every invalid value, of the records of `kind`, and returns the decoded values.
+    pub fn into_values_warn_invalid(self, kind: &str) -> Vec<SeqV<T>> {
+        for (key, e) in &self.invalid {
+            tracing::warn!("skip invalid {} {}: {}", kind, key, e);
+        }
+        self.items.into_iter().map(|(_, v)| v).collect()
+    }
+}
+
+/// Lists the values under `prefix`, decoded from json.
+pub async fn list_typed<T: DeserializeOwned>(
+    kv_api: &dyn KVApi,
+    prefix: &str,
+) -> Result<ListTyped<T>> {
+    list_decoded(kv_api, prefix, |data| {
+        Ok(serde_json::from_slice::<T>(data)?)
+    })
+    .await
+}
+
+/// Lists the values under `prefix`, decoded by `decode`.
+pub async fn list_decoded<T, F>(
+    kv_api: &dyn KVApi,
+    prefix: &str,
+    decode: F,
+) -> Result<ListTyped<T>>
+where
+    F: Fn(&[u8]) -> Result<T>,
+{
+    let values = kv_api.prefix_list_kv(prefix).await?;
+
+    let mut items = Vec::with_capacity(values.len());
+    let mut invalid = vec![];
+    for (key, val) in values {
+        match decode(&val.data) {
+            Ok(v) => items.push((key, SeqV::with_meta(val.seq, val.meta, v))),
+            Err(e) => invalid.push((key, e)),
+        }
+    }
+
+    Ok(ListTyped { items, invalid })
+}
@@ -0,0 +1,59 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use common_exception::Result;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// The holder of a lease, stored as the value of the lease.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct LeaseInfo {
+    pub owner_id: String,
+    /// The seconds since the unix epoch, when the lease expires if it is not renewed.
+    pub expire_at: u64,
+}
+
+/// A lease held by this owner, with the seq to renew or release it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LeaseHandle {
+    pub name: String,
+    pub seq: u64,
+    pub info: LeaseInfo,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LeaseAcquire {
+    Acquired(LeaseHandle),
+    /// The lease is held by another owner.
+    Held(LeaseInfo),
+}
+
+/// A lease on a name, held by at most one owner at a time, e.g., to let only one node run a job.
+///
+/// A lease expires if it is not renewed in its ttl, so that a crashed holder frees it.
+#[async_trait::async_trait]
+pub trait LeaseApi: Sync + Send {
+    /// Acquires the lease for `ttl` if no one holds it, otherwise returns the holder.
+    async fn acquire(&self, name: &str, owner_id: &str, ttl: Duration) -> Result<LeaseAcquire>;
+
+    /// Extends the lease for `ttl` from now.
+    ///
+    /// It fails with `LeaseNotHeld` if the lease is lost, e.g., it expired or is released.
+    async fn renew(&self, handle: &LeaseHandle, ttl: Duration) -> Result<LeaseHandle>;
+
+    /// Releases the lease, returns false if it is already lost.
+    async fn release(&self, handle: &LeaseHandle) -> Result<bool>;
+}
@@ -0,0 +1,241 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use common_base::tokio;
+use common_base::tokio::sync::oneshot;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_infallible::Mutex;
+use common_meta_api::KVApi;
+use common_meta_types::KVMeta;
+use common_meta_types::KeyBuilder;
+use common_meta_types::MatchSeq;
+use common_meta_types::Operation;
+use common_meta_types::UpsertKVAction;
+use common_tracing::tracing;
+
+use crate::lease::LeaseAcquire;
+use crate::lease::LeaseApi;
+use crate::lease::LeaseHandle;
+use crate::lease::LeaseInfo;
+
+static LEASE_API_KEY_PREFIX: &str = "__fd_leases";
+
+// A lease held by a LeaseGuard is taken as lost this part of its ttl before it may expire, so
+// that the work under the lease stops before another node could acquire it.
+const LEASE_SAFETY_MARGIN_DIVISOR: u32 = 5;
+
+#[derive(Clone)]
+pub struct LeaseMgr {
+    kv_api: Arc<dyn KVApi>,
+    lease_prefix: String,
+}
+
+impl LeaseMgr {
+    pub fn create(kv_api: Arc<dyn KVApi>, tenant: &str) -> Result<Self> {
+        if tenant.is_empty() {
+            return Err(ErrorCode::TenantIsEmpty(
+                "Tenant can not empty(while lease mgr create)",
+            ));
+        }
+
+        Ok(LeaseMgr {
+            kv_api,
+            lease_prefix: KeyBuilder::new(LEASE_API_KEY_PREFIX).push(tenant)?.done(),
+        })
+    }
+
+    fn make_key(&self, name: &str) -> Result<String> {
+        Ok(KeyBuilder::new(&self.lease_prefix).push(name)?.done())
+    }
+
+    /// Writes the lease if its seq matches, returns the new handle, or the current value if the
+    /// seq does not match.
+    async fn write_lease(
+        &self,
+        name: &str,
+        owner_id: &str,
+        seq: MatchSeq,
+        ttl: Duration,
+    ) -> Result<std::result::Result<LeaseHandle, Option<LeaseInfo>>> {
+        let expire_at = (SystemTime::now().duration_since(UNIX_EPOCH).unwrap() + ttl).as_secs();
+        let info = LeaseInfo {
+            owner_id: owner_id.to_string(),
+            expire_at,
+        };
+
+        let key = self.make_key(name)?;
+        let res = self
+            .kv_api
+            .upsert_kv(UpsertKVAction::new(
+                &key,
+                seq,
+                Operation::Update(serde_json::to_vec(&info)?),
+                Some(KVMeta {
+                    expire_at: Some(expire_at),
+                }),
+            ))
+            .await?;
+
+        if !res.changed() {
+            let holder = match res.result {
+                None => None,
+                Some(v) => Some(serde_json::from_slice::<LeaseInfo>(&v.data)?),
+            };
+            return Ok(Err(holder));
+        }
+
+        let seq = res.result.map(|v| v.seq).unwrap_or_default();
+        Ok(Ok(LeaseHandle {
+            name: name.to_string(),
+            seq,
+            info,
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl LeaseApi for LeaseMgr {
+    async fn acquire(&self, name: &str, owner_id: &str, ttl: Duration) -> Result<LeaseAcquire> {
+        // An expired lease is absent to the kv store, thus it is acquired the same way.
+        match self
+            .write_lease(name, owner_id, MatchSeq::Exact(0), ttl)
+            .await?
+        {
+            Ok(handle) => Ok(LeaseAcquire::Acquired(handle)),
+            Err(Some(holder)) => Ok(LeaseAcquire::Held(holder)),
+            Err(None) => Err(ErrorCode::LogicalError(format!(
+                "lease {} is neither acquired nor held",
+                name
+            ))),
+        }
+    }
+
+    async fn renew(&self, handle: &LeaseHandle, ttl: Duration) -> Result<LeaseHandle> {
+        let seq = MatchSeq::Exact(handle.seq);
+        match self
+            .write_lease(&handle.name, &handle.info.owner_id, seq, ttl)
+            .await?
+        {
+            Ok(handle) => Ok(handle),
+            Err(_) => Err(ErrorCode::LeaseNotHeld(format!(
+                "lease {} is not held by {} any more",
+                handle.name, handle.info.owner_id
+            ))),
+        }
+    }
+
+    async fn release(&self, handle: &LeaseHandle) -> Result<bool> {
+        let key = self.make_key(&handle.name)?;
+        let res = self
+            .kv_api
+            .delete_kv(&key, MatchSeq::Exact(handle.seq))
+            .await?;
+        Ok(res.prev.is_some() && res.result.is_none())
+    }
+}
+
+/// Keeps a lease renewed in a background task, and releases it when dropped.
+pub struct LeaseGuard {
+    held: Arc<AtomicBool>,
+    // The lease is surely held until then, by the last successful renew.
+    held_until: Arc<Mutex<Instant>>,
+    stop_tx: Option<oneshot::Sender<()>>,
+}
+
+impl LeaseGuard {
+    /// Renews the lease every third of `ttl`, until the guard is dropped or the lease is lost.
+    ///
+    /// The lease is lost if a renew finds it held by another, or if it is not renewed for so
+    /// long that it may have expired, e.g., while the meta service is unreachable.
+    pub fn start(lease_mgr: LeaseMgr, handle: LeaseHandle, ttl: Duration) -> Self {
+        let held = Arc::new(AtomicBool::new(true));
+        // The lease is acquired before the guard starts, the margin covers the time since then.
+        let held_until = Arc::new(Mutex::new(Self::held_until(Instant::now(), ttl)));
+        let (stop_tx, mut stop_rx) = oneshot::channel::<()>();
+
+        let task_held = held.clone();
+        let task_held_until = held_until.clone();
+        tokio::spawn(async move {
+            let mut handle = handle;
+            loop {
+                tokio::select! {
+                    _ = &mut stop_rx => {
+                        if let Err(e) = lease_mgr.release(&handle).await {
+                            tracing::warn!("failed to release lease {}: {}", handle.name, e);
+                        }
+                        return;
+                    }
+                    _ = tokio::time::sleep(ttl / 3) => {
+                        if Instant::now() >= *task_held_until.lock() {
+                            tracing::warn!("lost lease {}: not renewed in time", handle.name);
+                            task_held.store(false, Ordering::SeqCst);
+                            return;
+                        }
+
+                        // Renewed, the lease expires at most `ttl` after the renew is sent.
+                        let sent_at = Instant::now();
+                        match lease_mgr.renew(&handle, ttl).await {
+                            Ok(renewed) => {
+                                handle = renewed;
+                                *task_held_until.lock() = Self::held_until(sent_at, ttl);
+                            }
+                            Err(e) if e.code() == ErrorCode::LeaseNotHeld("").code() => {
+                                tracing::warn!("lost lease {}: {}", handle.name, e);
+                                task_held.store(false, Ordering::SeqCst);
+                                return;
+                            }
+                            // Retried on the next tick, unless the lease may have expired by then.
+                            Err(e) => {
+                                tracing::warn!("failed to renew lease {}: {}", handle.name, e);
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        LeaseGuard {
+            held,
+            held_until,
+            stop_tx: Some(stop_tx),
+        }
+    }
+
+    /// Returns false once the lease is lost, e.g., it is not renewed in time and may have
+    /// expired, by the local clock, even if the background task has not noticed it yet.
+    pub fn is_held(&self) -> bool {
+        self.held.load(Ordering::SeqCst) && Instant::now() < *self.held_until.lock()
+    }
+
+    fn held_until(renewed_at: Instant, ttl: Duration) -> Instant {
+        renewed_at + ttl - ttl / LEASE_SAFETY_MARGIN_DIVISOR
+    }
+}
+
+impl Drop for LeaseGuard {
+    fn drop(&mut self) {
+        if let Some(stop_tx) = self.stop_tx.take() {
+            let _ = stop_tx.send(());
+        }
+    }
+}
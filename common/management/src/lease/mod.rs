@@ -0,0 +1,23 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod lease_api;
+mod lease_mgr;
+
+pub use lease_api::LeaseAcquire;
+pub use lease_api::LeaseApi;
+pub use lease_api::LeaseHandle;
+pub use lease_api::LeaseInfo;
+pub use lease_mgr::LeaseGuard;
+pub use lease_mgr::LeaseMgr;
@@ -13,7 +13,11 @@
 // limitations under the License.
 
 mod cluster;
+mod kv_list;
+mod lease;
+mod network_policy;
 mod role;
+mod session_count;
 mod setting;
 mod stage;
 mod udf;
@@ -22,15 +26,36 @@ mod warehouse;
 
 pub use cluster::ClusterApi;
 pub use cluster::ClusterMgr;
+pub use cluster::ClusterNodeCache;
+pub use cluster::NodeHeartbeat;
+pub use kv_list::list_decoded;
+pub use kv_list::list_typed;
+pub use kv_list::ListTyped;
+pub use lease::LeaseAcquire;
+pub use lease::LeaseApi;
+pub use lease::LeaseGuard;
+pub use lease::LeaseHandle;
+pub use lease::LeaseInfo;
+pub use lease::LeaseMgr;
+pub use network_policy::NetworkPolicyApi;
+pub use network_policy::NetworkPolicyMgr;
 pub use role::RoleApi;
 pub use role::RoleMgr;
+pub use session_count::SessionCountApi;
+pub use session_count::SessionCountMgr;
 pub use setting::SettingApi;
 pub use setting::SettingMgr;
 pub use stage::StageApi;
 pub use stage::StageMgr;
 pub use udf::UdfApi;
 pub use udf::UdfMgr;
+pub use user::CachedUserMgr;
+pub use user::GrantSource;
+pub use user::ImportMode;
+pub use user::ResolvedGrants;
 pub use user::UserApi;
+pub use user::UserImportStatus;
 pub use user::UserMgr;
+pub use user::UserPage;
 pub use warehouse::WarehouseApi;
 pub use warehouse::WarehouseMgr;
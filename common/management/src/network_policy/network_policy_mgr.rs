@@ -0,0 +1,178 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_base::escape_for_key;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_meta_api::KVApi;
+use common_meta_types::IntoSeqV;
+use common_meta_types::MatchSeq;
+use common_meta_types::MatchSeqExt;
+use common_meta_types::NetworkPolicy;
+use common_meta_types::OkOrExist;
+use common_meta_types::Operation;
+use common_meta_types::SeqV;
+use common_meta_types::UpsertKVAction;
+
+use crate::kv_list::list_typed;
+use crate::network_policy::NetworkPolicyApi;
+use crate::UserApi;
+use crate::UserMgr;
+
+static NETWORK_POLICY_API_KEY_PREFIX: &str = "__fd_network_policies";
+
+pub struct NetworkPolicyMgr {
+    kv_api: Arc<dyn KVApi>,
+    tenant: String,
+    network_policy_prefix: String,
+}
+
+impl NetworkPolicyMgr {
+    pub fn create(kv_api: Arc<dyn KVApi>, tenant: &str) -> Result<Self> {
+        if tenant.is_empty() {
+            return Err(ErrorCode::TenantIsEmpty(
+                "Tenant can not empty(while network policy mgr create)",
+            ));
+        }
+
+        Ok(NetworkPolicyMgr {
+            kv_api,
+            tenant: tenant.to_string(),
+            network_policy_prefix: format!(
+                "{}/{}",
+                NETWORK_POLICY_API_KEY_PREFIX,
+                escape_for_key(tenant)?
+            ),
+        })
+    }
+
+    fn make_key(&self, name: &str) -> Result<String> {
+        Ok(format!(
+            "{}/{}",
+            self.network_policy_prefix,
+            escape_for_key(name)?
+        ))
+    }
+}
+
+#[async_trait::async_trait]
+impl NetworkPolicyApi for NetworkPolicyMgr {
+    async fn add_network_policy(&self, policy: NetworkPolicy) -> Result<u64> {
+        let seq = MatchSeq::Exact(0);
+        let val = Operation::Update(serde_json::to_vec(&policy)?);
+        let key = self.make_key(&policy.name)?;
+        let upsert_info = self
+            .kv_api
+            .upsert_kv(UpsertKVAction::new(&key, seq, val, None));
+
+        let res = upsert_info.await?.into_add_result()?;
+
+        match res.res {
+            OkOrExist::Ok(v) => Ok(v.seq),
+            OkOrExist::Exists(v) => Err(ErrorCode::NetworkPolicyAlreadyExists(format!(
+                "Network policy already exists, seq [{}]",
+                v.seq
+            ))),
+        }
+    }
+
+    async fn get_network_policy(
+        &self,
+        name: &str,
+        seq: Option<u64>,
+    ) -> Result<SeqV<NetworkPolicy>> {
+        let key = self.make_key(name)?;
+        let res = self.kv_api.get_kv(&key).await?;
+        let seq_value = res.ok_or_else(|| {
+            ErrorCode::UnknownNetworkPolicy(format!("Unknown network policy {}", name))
+        })?;
+
+        match MatchSeq::from(seq).match_seq(&seq_value) {
+            Ok(_) => Ok(seq_value.into_seqv()?),
+            Err(_) => Err(ErrorCode::UnknownNetworkPolicy(format!(
+                "Unknown network policy {}",
+                name
+            ))),
+        }
+    }
+
+    async fn get_network_policies(&self) -> Result<Vec<NetworkPolicy>> {
+        let listed =
+            list_typed::<NetworkPolicy>(self.kv_api.as_ref(), &self.network_policy_prefix).await?;
+        let policies = listed.into_values_warn_invalid("network policy");
+        Ok(policies.into_iter().map(|v| v.data).collect())
+    }
+
+    async fn update_network_policy(&self, policy: NetworkPolicy, seq: Option<u64>) -> Result<u64> {
+        let key = self.make_key(&policy.name)?;
+        let match_seq = match seq {
+            None => MatchSeq::GE(1),
+            Some(s) => MatchSeq::Exact(s),
+        };
+        let val = Operation::Update(serde_json::to_vec(&policy)?);
+        let res = self
+            .kv_api
+            .upsert_kv(UpsertKVAction::new(&key, match_seq, val, None))
+            .await?;
+
+        match res.result {
+            Some(SeqV { seq: s, .. }) => Ok(s),
+            None => Err(ErrorCode::UnknownNetworkPolicy(format!(
+                "Unknown network policy, or seq not match {}",
+                policy.name
+            ))),
+        }
+    }
+
+    async fn drop_network_policy(&self, name: &str, seq: Option<u64>) -> Result<()> {
+        // The users are checked before the policy is dropped, a user attached in between is
+        // rejected on connecting since the policy can not be resolved.
+        let user_api = UserMgr::create(self.kv_api.clone(), &self.tenant)?;
+        let users = user_api.get_users().await?;
+        let attached = users
+            .iter()
+            .map(|user| &user.data)
+            .filter(|user| user.network_policy.as_deref() == Some(name))
+            .map(|user| user.identity().to_string())
+            .collect::<Vec<_>>();
+        if !attached.is_empty() {
+            return Err(ErrorCode::NetworkPolicyIsUsedByUser(format!(
+                "Network policy {} is used by the users: {}",
+                name,
+                attached.join(", ")
+            )));
+        }
+
+        let key = self.make_key(name)?;
+        let res = self
+            .kv_api
+            .upsert_kv(UpsertKVAction::new(
+                &key,
+                seq.into(),
+                Operation::Delete,
+                None,
+            ))
+            .await?;
+        if res.prev.is_some() && res.result.is_none() {
+            Ok(())
+        } else {
+            Err(ErrorCode::UnknownNetworkPolicy(format!(
+                "Unknown network policy {}",
+                name
+            )))
+        }
+    }
+}
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+
 use common_exception::Result;
 use common_meta_types::GrantObject;
 use common_meta_types::RoleInfo;
@@ -26,6 +28,10 @@ pub trait RoleApi: Sync + Send {
 
     async fn get_roles(&self) -> Result<Vec<SeqV<RoleInfo>>>;
 
+    /// Returns the roles of `roles` that exist, by name, in one read.
+    async fn get_roles_by_names(&self, roles: &[String])
+        -> Result<HashMap<String, SeqV<RoleInfo>>>;
+
     async fn grant_privileges(
         &self,
         role: String,
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use common_exception::ErrorCode;
@@ -20,21 +21,29 @@ use common_exception::ToErrorCode;
 use common_meta_api::KVApi;
 use common_meta_types::GrantObject;
 use common_meta_types::IntoSeqV;
+use common_meta_types::KeyBuilder;
 use common_meta_types::MatchSeq;
 use common_meta_types::MatchSeqExt;
 use common_meta_types::OkOrExist;
 use common_meta_types::Operation;
 use common_meta_types::RoleInfo;
 use common_meta_types::SeqV;
+use common_meta_types::TxnCondition;
+use common_meta_types::TxnOp;
+use common_meta_types::TxnRequest;
 use common_meta_types::UpsertKVAction;
 use common_meta_types::UserPrivilegeSet;
 
+use crate::kv_list::list_decoded;
 use crate::role::role_api::RoleApi;
 
 static ROLE_API_KEY_PREFIX: &str = "__fd_roles";
 
+const MAX_UPDATE_RETRIES: usize = 10;
+
 pub struct RoleMgr {
     kv_api: Arc<dyn KVApi>,
+    tenant: String,
     role_prefix: String,
 }
 
@@ -48,7 +57,8 @@ impl RoleMgr {
 
         Ok(RoleMgr {
             kv_api,
-            role_prefix: format!("{}/{}", ROLE_API_KEY_PREFIX, tenant),
+            tenant: tenant.to_string(),
+            role_prefix: KeyBuilder::new(ROLE_API_KEY_PREFIX).push(tenant)?.done(),
         })
     }
 
@@ -57,7 +67,7 @@ impl RoleMgr {
         role_info: &RoleInfo,
         seq: Option<u64>,
     ) -> common_exception::Result<u64> {
-        let key = self.make_role_key(&role_info.identity());
+        let key = self.make_role_key(&role_info.identity())?;
         let value = serde_json::to_vec(&role_info)?;
 
         let match_seq = match seq {
@@ -74,17 +84,98 @@ impl RoleMgr {
                 None,
             ))
             .await?;
-        match res.result {
-            Some(SeqV { seq: s, .. }) => Ok(s),
-            None => Err(ErrorCode::UnknownRole(format!(
-                "unknown role, or seq not match {}",
+        match (res.prev, res.result) {
+            (_, Some(SeqV { seq: s, .. })) => Ok(s),
+            (None, None) => Err(ErrorCode::UnknownRole(format!(
+                "unknown role {}",
                 role_info.name
             ))),
+            (Some(prev), None) => Err(ErrorCode::KVSeqMismatched(format!(
+                "role {} is changed concurrently, seq not match, current seq [{}]",
+                role_info.name, prev.seq
+            ))),
         }
     }
 
-    fn make_role_key(&self, role: &str) -> String {
-        format!("{}/{}", self.role_prefix, role)
+    fn make_role_key(&self, role: &str) -> Result<String> {
+        Ok(KeyBuilder::new(&self.role_prefix).push(role)?.done())
+    }
+
+    // The key of the role by the earlier versions, which escape neither the tenant nor the role,
+    // if it is not the same as the escaped `key`.
+    fn legacy_role_key(&self, role: &str, key: &str) -> Option<String> {
+        let legacy_key = format!("{}/{}/{}", ROLE_API_KEY_PREFIX, self.tenant, role);
+        if legacy_key == key {
+            None
+        } else {
+            Some(legacy_key)
+        }
+    }
+
+    // Moves a role stored by an earlier version to the escaped key, before it is changed.
+    //
+    // The move fails if either key is changed meanwhile, e.g., moved by another node, then the
+    // change is left to find the role as it is.
+    async fn migrate_legacy_role(&self, role: &str) -> Result<()> {
+        let key = self.make_role_key(role)?;
+        let legacy_key = match self.legacy_role_key(role, &key) {
+            None => return Ok(()),
+            Some(legacy_key) => legacy_key,
+        };
+        let legacy = match self.kv_api.get_kv(&legacy_key).await? {
+            None => return Ok(()),
+            Some(legacy) => legacy,
+        };
+
+        let txn = TxnRequest {
+            condition: vec![
+                TxnCondition::new(&legacy_key, MatchSeq::Exact(legacy.seq)),
+                TxnCondition::new(&key, MatchSeq::Exact(0)),
+            ],
+            if_then: vec![TxnOp::put(&key, legacy.data), TxnOp::delete(&legacy_key)],
+            else_then: vec![],
+        };
+        self.kv_api.transaction(txn).await?;
+        Ok(())
+    }
+
+    /// Applies `update` on the role, and writes it back only if the role is not changed since
+    /// it is read.
+    ///
+    /// With a `seq`, the role must be of the seq, `KVSeqMismatched` if it is changed
+    /// concurrently. Otherwise the update is retried, so that the concurrent updates are not
+    /// lost, `OCCRetryFailure` once the retries are exhausted.
+    async fn update_role_with<F>(
+        &self,
+        role: String,
+        seq: Option<u64>,
+        update: F,
+    ) -> Result<Option<u64>>
+    where
+        F: Fn(&mut RoleInfo) + Send,
+    {
+        self.migrate_legacy_role(&role).await?;
+        for _ in 0..MAX_UPDATE_RETRIES {
+            let SeqV {
+                seq: read_seq,
+                data: mut role_info,
+                ..
+            } = self.get_role(role.clone(), seq).await?;
+            update(&mut role_info);
+
+            match self.upsert_role_info(&role_info, Some(read_seq)).await {
+                Ok(seq) => return Ok(Some(seq)),
+                Err(e) if seq.is_none() && e.code() == ErrorCode::KVSeqMismatched("").code() => {
+                    continue
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(ErrorCode::OCCRetryFailure(format!(
+            "role {} is changed concurrently, updates are retried {} times",
+            role, MAX_UPDATE_RETRIES
+        )))
     }
 }
 
@@ -92,9 +183,18 @@ impl RoleMgr {
 impl RoleApi for RoleMgr {
     async fn add_role(&self, role_info: RoleInfo) -> common_exception::Result<u64> {
         let match_seq = MatchSeq::Exact(0);
-        let key = self.make_role_key(&role_info.identity());
+        let key = self.make_role_key(&role_info.identity())?;
         let value = serde_json::to_vec(&role_info)?;
 
+        if let Some(legacy_key) = self.legacy_role_key(&role_info.identity(), &key) {
+            if let Some(v) = self.kv_api.get_kv(&legacy_key).await? {
+                return Err(ErrorCode::UserAlreadyExists(format!(
+                    "Role already exists, seq [{}]",
+                    v.seq
+                )));
+            }
+        }
+
         let kv_api = self.kv_api.clone();
         let upsert_kv = kv_api.upsert_kv(UpsertKVAction::new(
             &key,
@@ -113,9 +213,13 @@ impl RoleApi for RoleMgr {
     }
 
     async fn get_role(&self, role: String, seq: Option<u64>) -> Result<SeqV<RoleInfo>> {
-        let key = self.make_role_key(&role);
-        let kv_api = self.kv_api.clone();
-        let res = kv_api.get_kv(&key).await?;
+        let key = self.make_role_key(&role)?;
+        let mut res = self.kv_api.get_kv(&key).await?;
+        if res.is_none() {
+            if let Some(legacy_key) = self.legacy_role_key(&role, &key) {
+                res = self.kv_api.get_kv(&legacy_key).await?;
+            }
+        }
         let seq_value =
             res.ok_or_else(|| ErrorCode::UnknownRole(format!("unknown role {}", role)))?;
 
@@ -126,16 +230,65 @@ impl RoleApi for RoleMgr {
     }
 
     async fn get_roles(&self) -> Result<Vec<SeqV<RoleInfo>>> {
-        let role_prefix = self.role_prefix.clone();
-        let kv_api = self.kv_api.clone();
-        let values = kv_api.prefix_list_kv(role_prefix.as_str()).await?;
+        let decode = |data: &[u8]| {
+            serde_json::from_slice::<RoleInfo>(data)
+                .map_err_to_code(ErrorCode::IllegalUserInfoFormat, || "")
+        };
+        let prefix = format!("{}/", self.role_prefix);
+        let mut roles = list_decoded(self.kv_api.as_ref(), &prefix, decode)
+            .await?
+            .into_values_warn_invalid("role");
+
+        // The roles of the earlier versions are under the same prefix, unless the tenant is
+        // escaped to another one. A role under both keys is the one being moved.
+        let legacy_prefix = format!("{}/{}/", ROLE_API_KEY_PREFIX, self.tenant);
+        if legacy_prefix != prefix {
+            let legacy = list_decoded(self.kv_api.as_ref(), &legacy_prefix, decode)
+                .await?
+                .into_values_warn_invalid("role");
+            for role in legacy {
+                if !roles.iter().any(|r| r.data.name == role.data.name) {
+                    roles.push(role);
+                }
+            }
+        }
+        Ok(roles)
+    }
+
+    async fn get_roles_by_names(
+        &self,
+        roles: &[String],
+    ) -> Result<HashMap<String, SeqV<RoleInfo>>> {
+        let keys = roles
+            .iter()
+            .map(|role| self.make_role_key(role))
+            .collect::<Result<Vec<_>>>()?;
+        let mut values = self.kv_api.mget_kv(&keys).await?;
 
-        let mut r = vec![];
-        for (_key, val) in values {
-            let u = serde_json::from_slice::<RoleInfo>(&val.data)
-                .map_err_to_code(ErrorCode::IllegalUserInfoFormat, || "")?;
+        // The roles absent under the escaped keys may be under the keys of the earlier versions.
+        let mut legacy = vec![];
+        for (i, (role, key)) in roles.iter().zip(&keys).enumerate() {
+            if values[i].is_none() {
+                if let Some(legacy_key) = self.legacy_role_key(role, key) {
+                    legacy.push((i, legacy_key));
+                }
+            }
+        }
+        if !legacy.is_empty() {
+            let legacy_keys = legacy.iter().map(|(_, k)| k.clone()).collect::<Vec<_>>();
+            let legacy_values = self.kv_api.mget_kv(&legacy_keys).await?;
+            for ((i, _), value) in legacy.into_iter().zip(legacy_values) {
+                values[i] = value;
+            }
+        }
 
-            r.push(SeqV::new(val.seq, u));
+        let mut r = HashMap::new();
+        for (role, value) in roles.iter().zip(values) {
+            if let Some(val) = value {
+                let u = serde_json::from_slice::<RoleInfo>(&val.data)
+                    .map_err_to_code(ErrorCode::IllegalUserInfoFormat, || "")?;
+                r.insert(role.clone(), SeqV::new(val.seq, u));
+            }
         }
 
         Ok(r)
@@ -148,11 +301,10 @@ impl RoleApi for RoleMgr {
         privileges: UserPrivilegeSet,
         seq: Option<u64>,
     ) -> Result<Option<u64>> {
-        let role_val_seq = self.get_role(role, seq);
-        let mut role_info = role_val_seq.await?.data;
-        role_info.grants.grant_privileges(&object, privileges);
-        let seq = self.upsert_role_info(&role_info, seq).await?;
-        Ok(Some(seq))
+        self.update_role_with(role, seq, |role_info| {
+            role_info.grants.grant_privileges(&object, privileges)
+        })
+        .await
     }
 
     async fn revoke_privileges(
@@ -162,11 +314,10 @@ impl RoleApi for RoleMgr {
         privileges: UserPrivilegeSet,
         seq: Option<u64>,
     ) -> Result<Option<u64>> {
-        let role_val_seq = self.get_role(role, seq);
-        let mut role_info = role_val_seq.await?.data;
-        role_info.grants.revoke_privileges(&object, privileges);
-        let seq = self.upsert_role_info(&role_info, seq).await?;
-        Ok(Some(seq))
+        self.update_role_with(role, seq, |role_info| {
+            role_info.grants.revoke_privileges(&object, privileges)
+        })
+        .await
     }
 
     async fn grant_role(
@@ -175,11 +326,10 @@ impl RoleApi for RoleMgr {
         grant_role: String,
         seq: Option<u64>,
     ) -> Result<Option<u64>> {
-        let role_val_seq = self.get_role(role, seq);
-        let mut role_info = role_val_seq.await?.data;
-        role_info.grants.grant_role(grant_role);
-        let seq = self.upsert_role_info(&role_info, seq).await?;
-        Ok(Some(seq))
+        self.update_role_with(role, seq, |role_info| {
+            role_info.grants.grant_role(grant_role.clone())
+        })
+        .await
     }
 
     async fn revoke_role(
@@ -188,15 +338,15 @@ impl RoleApi for RoleMgr {
         revoke_role: String,
         seq: Option<u64>,
     ) -> Result<Option<u64>> {
-        let role_val_seq = self.get_role(role, seq);
-        let mut role_info = role_val_seq.await?.data;
-        role_info.grants.revoke_role(&revoke_role);
-        let seq = self.upsert_role_info(&role_info, seq).await?;
-        Ok(Some(seq))
+        self.update_role_with(role, seq, |role_info| {
+            role_info.grants.revoke_role(&revoke_role)
+        })
+        .await
     }
 
     async fn drop_role(&self, role: String, seq: Option<u64>) -> Result<()> {
-        let key = self.make_role_key(&role);
+        self.migrate_legacy_role(&role).await?;
+        let key = self.make_role_key(&role)?;
         let kv_api = self.kv_api.clone();
         let res = kv_api
             .upsert_kv(UpsertKVAction::new(
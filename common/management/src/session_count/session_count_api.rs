@@ -0,0 +1,38 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use common_exception::Result;
+
+/// Counts the sessions of every user over all the query nodes of a tenant, by the sessions of
+/// every node, with the time the node updated the count last, as its lease.
+#[async_trait::async_trait]
+pub trait SessionCountApi: Sync + Send {
+    /// Counts a new session of the user on this node, returns the sessions of all the nodes.
+    async fn increment_user_sessions(&self, username: &str) -> Result<u64>;
+
+    /// Counts a closed session of the user on this node, returns the sessions of all the nodes.
+    async fn decrement_user_sessions(&self, username: &str) -> Result<u64>;
+
+    /// Gets the sessions of the user of all the nodes.
+    async fn get_user_sessions(&self, username: &str) -> Result<u64>;
+
+    /// Renews the lease of this node in the counts of all the users it has sessions of.
+    async fn heartbeat(&self) -> Result<()>;
+
+    /// Drops the sessions of the nodes which do not renew the lease for `lease` long, e.g. the
+    /// nodes died without decrementing. Returns the number of the sessions dropped.
+    async fn expire_dead_nodes(&self, lease: Duration) -> Result<u64>;
+}
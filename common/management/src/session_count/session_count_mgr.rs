@@ -0,0 +1,210 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_meta_api::KVApi;
+use common_meta_types::KeyBuilder;
+use common_meta_types::MatchSeq;
+use common_meta_types::Operation;
+use common_meta_types::UpsertKVAction;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::session_count::SessionCountApi;
+
+static USER_SESSIONS_API_KEY_PREFIX: &str = "__fd_user_sessions";
+
+// The counts are updated by many nodes at the same time, retried more than the other updates.
+const MAX_UPDATE_RETRIES: usize = 64;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(default)]
+struct NodeSessions {
+    count: u64,
+    // The milliseconds since the unix epoch, when the node updates the count last.
+    lease_at: u64,
+}
+
+// The sessions of a user, by the node id.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(default)]
+struct UserSessions {
+    nodes: BTreeMap<String, NodeSessions>,
+}
+
+impl UserSessions {
+    fn total(&self) -> u64 {
+        self.nodes.values().map(|node| node.count).sum()
+    }
+}
+
+pub struct SessionCountMgr {
+    kv_api: Arc<dyn KVApi>,
+    node_id: String,
+    sessions_prefix: String,
+}
+
+impl SessionCountMgr {
+    pub fn create(kv_api: Arc<dyn KVApi>, tenant: &str, node_id: &str) -> Result<Self> {
+        if tenant.is_empty() {
+            return Err(ErrorCode::TenantIsEmpty(
+                "Tenant can not empty(while session count mgr create)",
+            ));
+        }
+
+        Ok(SessionCountMgr {
+            kv_api,
+            node_id: node_id.to_string(),
+            sessions_prefix: KeyBuilder::new(USER_SESSIONS_API_KEY_PREFIX)
+                .push(tenant)?
+                .done(),
+        })
+    }
+
+    fn now_ms() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    }
+
+    // Updates the sessions of the key by `update` with compare-and-swap, retried on conflicts.
+    // The key is deleted if no node has sessions. Returns the sessions of all the nodes.
+    async fn update_sessions<F>(&self, key: &str, update: F) -> Result<u64>
+    where F: Fn(&mut UserSessions) + Send {
+        for _ in 0..MAX_UPDATE_RETRIES {
+            let (seq, mut sessions) = match self.kv_api.get_kv(key).await? {
+                None => (0, UserSessions::default()),
+                Some(v) => (v.seq, serde_json::from_slice::<UserSessions>(&v.data)?),
+            };
+            let prev = sessions.clone();
+            update(&mut sessions);
+            if sessions == prev {
+                return Ok(sessions.total());
+            }
+
+            let operation = match sessions.nodes.is_empty() {
+                true => Operation::Delete,
+                false => Operation::Update(serde_json::to_vec(&sessions)?),
+            };
+            let res = self
+                .kv_api
+                .upsert_kv(UpsertKVAction::new(
+                    key,
+                    MatchSeq::Exact(seq),
+                    operation,
+                    None,
+                ))
+                .await?;
+            if res.changed() {
+                return Ok(sessions.total());
+            }
+        }
+
+        Err(ErrorCode::UserSessionCountConflict(format!(
+            "sessions of {} are changed concurrently, updates are retried {} times",
+            key, MAX_UPDATE_RETRIES
+        )))
+    }
+
+    fn make_key(&self, username: &str) -> Result<String> {
+        Ok(KeyBuilder::new(&self.sessions_prefix)
+            .push(username)?
+            .done())
+    }
+}
+
+#[async_trait::async_trait]
+impl SessionCountApi for SessionCountMgr {
+    async fn increment_user_sessions(&self, username: &str) -> Result<u64> {
+        let key = self.make_key(username)?;
+        self.update_sessions(&key, |sessions| {
+            let node = sessions.nodes.entry(self.node_id.clone()).or_default();
+            node.count += 1;
+            node.lease_at = Self::now_ms();
+        })
+        .await
+    }
+
+    async fn decrement_user_sessions(&self, username: &str) -> Result<u64> {
+        let key = self.make_key(username)?;
+        self.update_sessions(&key, |sessions| {
+            if let Some(node) = sessions.nodes.get_mut(&self.node_id) {
+                node.count = node.count.saturating_sub(1);
+                node.lease_at = Self::now_ms();
+                if node.count == 0 {
+                    sessions.nodes.remove(&self.node_id);
+                }
+            }
+        })
+        .await
+    }
+
+    async fn get_user_sessions(&self, username: &str) -> Result<u64> {
+        let key = self.make_key(username)?;
+        match self.kv_api.get_kv(&key).await? {
+            None => Ok(0),
+            Some(v) => Ok(serde_json::from_slice::<UserSessions>(&v.data)?.total()),
+        }
+    }
+
+    async fn heartbeat(&self) -> Result<()> {
+        let list_prefix = format!("{}/", self.sessions_prefix);
+        let values = self.kv_api.prefix_list_kv(&list_prefix).await?;
+        for (key, value) in values {
+            let sessions = serde_json::from_slice::<UserSessions>(&value.data)?;
+            if !sessions.nodes.contains_key(&self.node_id) {
+                continue;
+            }
+            let now = Self::now_ms();
+            self.update_sessions(&key, |sessions| {
+                if let Some(node) = sessions.nodes.get_mut(&self.node_id) {
+                    node.lease_at = now;
+                }
+            })
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn expire_dead_nodes(&self, lease: Duration) -> Result<u64> {
+        let expire_before = Self::now_ms().saturating_sub(lease.as_millis() as u64);
+        let is_dead = |node: &NodeSessions| node.lease_at < expire_before;
+
+        let list_prefix = format!("{}/", self.sessions_prefix);
+        let values = self.kv_api.prefix_list_kv(&list_prefix).await?;
+        let mut expired = 0;
+        for (key, value) in values {
+            let sessions = serde_json::from_slice::<UserSessions>(&value.data)?;
+            if !sessions.nodes.values().any(is_dead) {
+                continue;
+            }
+            let before = sessions.total();
+            let after = self
+                .update_sessions(&key, |sessions| {
+                    sessions.nodes.retain(|_, node| !is_dead(node))
+                })
+                .await?;
+            expired += before.saturating_sub(after);
+        }
+        Ok(expired)
+    }
+}
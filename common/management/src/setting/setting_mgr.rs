@@ -26,6 +26,7 @@ use common_meta_types::SeqV;
 use common_meta_types::UpsertKVAction;
 use common_meta_types::UserSetting;
 
+use crate::kv_list::list_typed;
 use crate::setting::SettingApi;
 
 static USER_SETTING_API_KEY_PREFIX: &str = "__fd_settings";
@@ -70,14 +71,9 @@ impl SettingApi for SettingMgr {
     }
 
     async fn get_settings(&self) -> Result<Vec<UserSetting>> {
-        let values = self.kv_api.prefix_list_kv(&self.setting_prefix).await?;
-
-        let mut settings = Vec::with_capacity(values.len());
-        for (_, value) in values {
-            let setting = serde_json::from_slice::<UserSetting>(&value.data)?;
-            settings.push(setting);
-        }
-        Ok(settings)
+        let listed = list_typed::<UserSetting>(self.kv_api.as_ref(), &self.setting_prefix).await?;
+        let settings = listed.into_values_warn_invalid("setting");
+        Ok(settings.into_iter().map(|v| v.data).collect())
     }
 
     async fn get_setting(&self, name: &str, seq: Option<u64>) -> Result<SeqV<UserSetting>> {
@@ -27,6 +27,7 @@ use common_meta_types::SeqV;
 use common_meta_types::UpsertKVAction;
 use common_meta_types::UserStageInfo;
 
+use crate::kv_list::list_typed;
 use crate::stage::StageApi;
 
 static USER_STAGE_API_KEY_PREFIX: &str = "__fd_stages";
@@ -91,14 +92,9 @@ impl StageApi for StageMgr {
     }
 
     async fn get_stages(&self) -> Result<Vec<UserStageInfo>> {
-        let values = self.kv_api.prefix_list_kv(&self.stage_prefix).await?;
-
-        let mut stage_infos = Vec::with_capacity(values.len());
-        for (_, value) in values {
-            let stage_info = serde_json::from_slice::<UserStageInfo>(&value.data)?;
-            stage_infos.push(stage_info);
-        }
-        Ok(stage_infos)
+        let listed = list_typed::<UserStageInfo>(self.kv_api.as_ref(), &self.stage_prefix).await?;
+        let stage_infos = listed.into_values_warn_invalid("stage");
+        Ok(stage_infos.into_iter().map(|v| v.data).collect())
     }
 
     async fn drop_stage(&self, name: &str, seq: Option<u64>) -> Result<()> {
@@ -29,6 +29,7 @@ use common_meta_types::SeqV;
 use common_meta_types::UpsertKVAction;
 use common_meta_types::UserDefinedFunction;
 
+use crate::kv_list::list_typed;
 use crate::udf::UdfApi;
 
 static UDF_API_KEY_PREFIX: &str = "__fd_udfs";
@@ -128,14 +129,10 @@ impl UdfApi for UdfMgr {
     }
 
     async fn get_udfs(&self) -> Result<Vec<UserDefinedFunction>> {
-        let values = self.kv_api.prefix_list_kv(&self.udf_prefix).await?;
-
-        let mut udfs = Vec::with_capacity(values.len());
-        for (_, value) in values {
-            let udf = serde_json::from_slice::<UserDefinedFunction>(&value.data)?;
-            udfs.push(udf);
-        }
-        Ok(udfs)
+        let listed =
+            list_typed::<UserDefinedFunction>(self.kv_api.as_ref(), &self.udf_prefix).await?;
+        let udfs = listed.into_values_warn_invalid("udf");
+        Ok(udfs.into_iter().map(|v| v.data).collect())
     }
 
     async fn drop_udf(&self, udf_name: &str, seq: Option<u64>) -> Result<()> {
@@ -0,0 +1,374 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use common_exception::Result;
+use common_infallible::RwLock;
+use common_meta_types::AuthInfo;
+use common_meta_types::GrantObject;
+use common_meta_types::PasswordPolicy;
+use common_meta_types::SeqV;
+use common_meta_types::UserAuditEntry;
+use common_meta_types::UserIdentity;
+use common_meta_types::UserInfo;
+use common_meta_types::UserOption;
+use common_meta_types::UserPrivilegeSet;
+use common_meta_types::UserQuota;
+
+use crate::user::user_api::ImportMode;
+use crate::user::user_api::ResolvedGrants;
+use crate::user::user_api::UserApi;
+use crate::user::user_api::UserImportStatus;
+use crate::user::user_api::UserPage;
+
+/// A [UserApi] caching the users it reads, so that the authentications do not read the meta
+/// service every time.
+///
+/// A cached user is served for at most `ttl` after it is read, a change made through another
+/// [UserApi], e.g. by another node, may be invisible for that long. After that, the cached user
+/// is served again if its seq is still the latest, which is checked without reading the user
+/// info. The changes made through this one are always written to the underlying [UserApi], and
+/// invalidate the cached user.
+pub struct CachedUserMgr {
+    inner: Arc<dyn UserApi>,
+    ttl: Duration,
+    users: Arc<RwLock<HashMap<UserIdentity, (Instant, SeqV<UserInfo>)>>>,
+}
+
+impl CachedUserMgr {
+    pub fn create(inner: Arc<dyn UserApi>, ttl: Duration) -> Self {
+        CachedUserMgr {
+            inner,
+            ttl,
+            users: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    async fn get_cached(&self, user: &UserIdentity) -> Result<Option<SeqV<UserInfo>>> {
+        let cached = self.users.read().get(user).cloned();
+        let user_info = match cached {
+            None => return Ok(None),
+            Some((read_at, user_info)) if read_at.elapsed() < self.ttl => {
+                return Ok(Some(user_info))
+            }
+            Some((_, user_info)) => user_info,
+        };
+
+        // Expired: keep serving it if it has not changed since it was read.
+        if self.inner.get_user_seq(user.clone()).await? == Some(user_info.seq) {
+            self.cache(user_info.clone());
+            Ok(Some(user_info))
+        } else {
+            self.invalidate(user);
+            Ok(None)
+        }
+    }
+
+    fn cache(&self, user_info: SeqV<UserInfo>) {
+        let mut users = self.users.write();
+        users.insert(user_info.data.identity(), (Instant::now(), user_info));
+    }
+
+    fn invalidate(&self, user: &UserIdentity) {
+        self.users.write().remove(user);
+    }
+}
+
+#[async_trait::async_trait]
+impl UserApi for CachedUserMgr {
+    async fn add_user(&self, user_info: UserInfo, if_not_exists: bool) -> Result<u64> {
+        let identity = user_info.identity();
+        let res = self.inner.add_user(user_info, if_not_exists).await;
+        self.invalidate(&identity);
+        res
+    }
+
+    async fn get_user(&self, user: UserIdentity, seq: Option<u64>) -> Result<SeqV<UserInfo>> {
+        if let Some(user_info) = self.get_cached(&user).await? {
+            if seq.is_none() || seq == Some(user_info.seq) {
+                return Ok(user_info);
+            }
+        }
+
+        let user_info = self.inner.get_user(user, seq).await?;
+        self.cache(user_info.clone());
+        Ok(user_info)
+    }
+
+    async fn exists_user(&self, user: UserIdentity) -> Result<bool> {
+        if self.get_cached(&user).await?.is_some() {
+            return Ok(true);
+        }
+        self.inner.exists_user(user).await
+    }
+
+    async fn get_user_seq(&self, user: UserIdentity) -> Result<Option<u64>> {
+        self.inner.get_user_seq(user).await
+    }
+
+    /// Lists the users from the underlying [UserApi], and refreshes the cached users by them.
+    async fn get_users(&self) -> Result<Vec<SeqV<UserInfo>>> {
+        let users = self.inner.get_users().await?;
+        let now = Instant::now();
+        let mut cached = self.users.write();
+        cached.clear();
+        for user_info in &users {
+            cached.insert(user_info.data.identity(), (now, user_info.clone()));
+        }
+        Ok(users)
+    }
+
+    async fn get_users_of_name(&self, username: &str) -> Result<Vec<SeqV<UserInfo>>> {
+        let users = self.inner.get_users_of_name(username).await?;
+        for user_info in &users {
+            self.cache(user_info.clone());
+        }
+        Ok(users)
+    }
+
+    async fn export_users(&self) -> Result<Vec<UserInfo>> {
+        self.inner.export_users().await
+    }
+
+    async fn import_users(
+        &self,
+        users: Vec<UserInfo>,
+        mode: ImportMode,
+    ) -> Result<Vec<(UserIdentity, Result<UserImportStatus>)>> {
+        let identities = users.iter().map(|u| u.identity()).collect::<Vec<_>>();
+        let res = self.inner.import_users(users, mode).await;
+        for identity in &identities {
+            self.invalidate(identity);
+        }
+        res
+    }
+
+    async fn get_users_paged(
+        &self,
+        pattern: Option<&str>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<UserPage> {
+        self.inner.get_users_paged(pattern, offset, limit).await
+    }
+
+    async fn update_user(
+        &self,
+        user: UserIdentity,
+        auth_info: Option<AuthInfo>,
+        user_option: Option<UserOption>,
+        seq: Option<u64>,
+    ) -> Result<Option<u64>> {
+        let res = self
+            .inner
+            .update_user(user.clone(), auth_info, user_option, seq)
+            .await;
+        self.invalidate(&user);
+        res
+    }
+
+    async fn rename_user(
+        &self,
+        user: UserIdentity,
+        new_name: &str,
+        seq: Option<u64>,
+    ) -> Result<u64> {
+        let res = self.inner.rename_user(user.clone(), new_name, seq).await;
+        self.invalidate(&user);
+        self.invalidate(&UserIdentity::new(new_name, &user.hostname));
+        res
+    }
+
+    async fn update_user_quota(
+        &self,
+        user: UserIdentity,
+        quota: UserQuota,
+        seq: Option<u64>,
+    ) -> Result<Option<u64>> {
+        let res = self.inner.update_user_quota(user.clone(), quota, seq).await;
+        self.invalidate(&user);
+        res
+    }
+
+    async fn lock_user(
+        &self,
+        user: UserIdentity,
+        reason: Option<String>,
+        seq: Option<u64>,
+        force: bool,
+    ) -> Result<Option<u64>> {
+        let res = self.inner.lock_user(user.clone(), reason, seq, force).await;
+        self.invalidate(&user);
+        res
+    }
+
+    async fn unlock_user(&self, user: UserIdentity, seq: Option<u64>) -> Result<Option<u64>> {
+        let res = self.inner.unlock_user(user.clone(), seq).await;
+        self.invalidate(&user);
+        res
+    }
+
+    async fn set_user_setting(
+        &self,
+        user: UserIdentity,
+        name: String,
+        value: String,
+        seq: Option<u64>,
+    ) -> Result<Option<u64>> {
+        let res = self
+            .inner
+            .set_user_setting(user.clone(), name, value, seq)
+            .await;
+        self.invalidate(&user);
+        res
+    }
+
+    async fn unset_user_setting(
+        &self,
+        user: UserIdentity,
+        name: String,
+        seq: Option<u64>,
+    ) -> Result<Option<u64>> {
+        let res = self.inner.unset_user_setting(user.clone(), name, seq).await;
+        self.invalidate(&user);
+        res
+    }
+
+    async fn set_user_network_policy(
+        &self,
+        user: UserIdentity,
+        network_policy: Option<String>,
+        seq: Option<u64>,
+    ) -> Result<Option<u64>> {
+        let res = self
+            .inner
+            .set_user_network_policy(user.clone(), network_policy, seq)
+            .await;
+        self.invalidate(&user);
+        res
+    }
+
+    async fn grant_privileges(
+        &self,
+        user: UserIdentity,
+        object: GrantObject,
+        privileges: UserPrivilegeSet,
+        seq: Option<u64>,
+    ) -> Result<Option<u64>> {
+        let res = self
+            .inner
+            .grant_privileges(user.clone(), object, privileges, seq)
+            .await;
+        self.invalidate(&user);
+        res
+    }
+
+    async fn revoke_privileges(
+        &self,
+        user: UserIdentity,
+        object: GrantObject,
+        privileges: UserPrivilegeSet,
+        seq: Option<u64>,
+        force: bool,
+    ) -> Result<Option<u64>> {
+        let res = self
+            .inner
+            .revoke_privileges(user.clone(), object, privileges, seq, force)
+            .await;
+        self.invalidate(&user);
+        res
+    }
+
+    async fn grant_role(
+        &self,
+        user: UserIdentity,
+        grant_role: String,
+        seq: Option<u64>,
+    ) -> Result<Option<u64>> {
+        let res = self.inner.grant_role(user.clone(), grant_role, seq).await;
+        self.invalidate(&user);
+        res
+    }
+
+    async fn revoke_role(
+        &self,
+        user: UserIdentity,
+        revoke_role: String,
+        seq: Option<u64>,
+        force: bool,
+    ) -> Result<Option<u64>> {
+        let res = self
+            .inner
+            .revoke_role(user.clone(), revoke_role, seq, force)
+            .await;
+        self.invalidate(&user);
+        res
+    }
+
+    async fn get_user_grants(&self, user: UserIdentity) -> Result<ResolvedGrants> {
+        self.inner.get_user_grants(user).await
+    }
+
+    async fn drop_user(
+        &self,
+        user: UserIdentity,
+        seq: Option<u64>,
+        if_exists: bool,
+        force: bool,
+    ) -> Result<()> {
+        let res = self
+            .inner
+            .drop_user(user.clone(), seq, if_exists, force)
+            .await;
+        self.invalidate(&user);
+        res
+    }
+
+    async fn get_password_policy(&self) -> Result<PasswordPolicy> {
+        self.inner.get_password_policy().await
+    }
+
+    async fn set_password_policy(&self, policy: PasswordPolicy) -> Result<u64> {
+        self.inner.set_password_policy(policy).await
+    }
+
+    async fn list_user_audit(&self, username: &str, limit: usize) -> Result<Vec<UserAuditEntry>> {
+        self.inner.list_user_audit(username, limit).await
+    }
+
+    async fn verify_auth(
+        &self,
+        user: UserIdentity,
+        client_response: &[u8],
+        salt: &[u8],
+    ) -> Result<bool> {
+        let user_info = self.get_user(user, None).await?.data;
+        user_info.check_not_locked()?;
+        user_info.auth_info.auth(client_response, salt)
+    }
+
+    /// The returned one shares the cached users with this one, so that the changes made through
+    /// either invalidate them.
+    fn with_operator(&self, operator: &str) -> Arc<dyn UserApi> {
+        Arc::new(CachedUserMgr {
+            inner: self.inner.with_operator(operator),
+            ttl: self.ttl,
+            users: self.users.clone(),
+        })
+    }
+}
@@ -12,8 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod cached_user_mgr;
 mod user_api;
 mod user_mgr;
 
+pub use cached_user_mgr::CachedUserMgr;
+pub use user_api::GrantSource;
+pub use user_api::ImportMode;
+pub use user_api::ResolvedGrants;
 pub use user_api::UserApi;
+pub use user_api::UserImportStatus;
+pub use user_api::UserPage;
 pub use user_mgr::UserMgr;
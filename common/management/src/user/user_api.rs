@@ -12,23 +12,121 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
+use common_exception::ErrorCode;
 use common_exception::Result;
 use common_meta_types::AuthInfo;
+use common_meta_types::GrantEntry;
 use common_meta_types::GrantObject;
+use common_meta_types::PasswordPolicy;
 use common_meta_types::SeqV;
+use common_meta_types::UserAuditEntry;
+use common_meta_types::UserGrantSet;
 use common_meta_types::UserIdentity;
 use common_meta_types::UserInfo;
 use common_meta_types::UserOption;
 use common_meta_types::UserPrivilegeSet;
+use common_meta_types::UserQuota;
+
+/// A page of the users listed by [UserApi::get_users_paged].
+#[derive(Debug)]
+pub struct UserPage {
+    /// The users of the page, ordered by name and hostname.
+    pub users: Vec<SeqV<UserInfo>>,
+    /// The number of the users matching the pattern, of all the pages.
+    pub total: usize,
+    /// The users of the page which can not be deserialized, as (`'name'@'host'`, error).
+    pub invalid: Vec<(String, ErrorCode)>,
+}
+
+/// How [UserApi::import_users] handles a user which exists already.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ImportMode {
+    /// Keeps the existing user.
+    Skip,
+    /// Replaces the existing user by the imported one.
+    Overwrite,
+    /// Fails the import of the user with `UserAlreadyExists`.
+    Fail,
+}
+
+/// What [UserApi::import_users] did with a user.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UserImportStatus {
+    /// Added as a new user, with the seq.
+    Added(u64),
+    /// Replaced the existing user, with the seq.
+    Overwritten(u64),
+    /// The user exists and is kept.
+    Skipped,
+}
+
+/// Where a grant entry of [ResolvedGrants] is from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GrantSource {
+    /// Granted to the user directly.
+    User,
+    /// Granted to the role, which the user has directly or by other roles.
+    Role(String),
+}
+
+/// The effective privileges of a user, resolved by [UserApi::get_user_grants].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ResolvedGrants {
+    /// The privileges of the user and of all the roles it has, merged by the object, with all
+    /// the roles it has.
+    pub merged: UserGrantSet,
+    /// The grant entries of the user and of every role it has, by where they are from.
+    pub provenance: Vec<(GrantSource, GrantEntry)>,
+    /// The roles granted but not found, e.g. the dropped ones.
+    pub unknown_roles: Vec<String>,
+}
 
 #[async_trait::async_trait]
 pub trait UserApi: Sync + Send {
-    async fn add_user(&self, user_info: UserInfo) -> Result<u64>;
+    /// Adds a user, returns the seq of it.
+    ///
+    /// If the user exists and `if_not_exists` is true, returns the seq of the existing one.
+    async fn add_user(&self, user_info: UserInfo, if_not_exists: bool) -> Result<u64>;
 
     async fn get_user(&self, user: UserIdentity, seq: Option<u64>) -> Result<SeqV<UserInfo>>;
 
+    /// Whether the user exists, without deserializing the user info.
+    async fn exists_user(&self, user: UserIdentity) -> Result<bool>;
+
+    /// Returns the seq of the user, or `None` if it is absent, without reading the user info.
+    async fn get_user_seq(&self, user: UserIdentity) -> Result<Option<u64>>;
+
     async fn get_users(&self) -> Result<Vec<SeqV<UserInfo>>>;
 
+    /// Gets the users of the name, of all the hostnames, by listing only the keys of the name.
+    async fn get_users_of_name(&self, username: &str) -> Result<Vec<SeqV<UserInfo>>>;
+
+    /// Exports all the users, ordered by name and hostname, to be imported by [UserApi::import_users].
+    async fn export_users(&self) -> Result<Vec<UserInfo>>;
+
+    /// Imports the users, the conflicts with the existing users are handled by the `mode`.
+    ///
+    /// A failure of a user does not stop the others, the result of every user is returned in
+    /// the order of `users`.
+    async fn import_users(
+        &self,
+        users: Vec<UserInfo>,
+        mode: ImportMode,
+    ) -> Result<Vec<(UserIdentity, Result<UserImportStatus>)>>;
+
+    /// Lists at most `limit` users from `offset`, of the names matching the `pattern` of LIKE.
+    ///
+    /// The literal prefix of the pattern is pushed down to the listing of the keys, and only the
+    /// users of the page are deserialized.
+    async fn get_users_paged(
+        &self,
+        pattern: Option<&str>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<UserPage>;
+
     async fn update_user(
         &self,
         user: UserIdentity,
@@ -37,6 +135,62 @@ pub trait UserApi: Sync + Send {
         seq: Option<u64>,
     ) -> Result<Option<u64>>;
 
+    /// Renames the user, keeping the hostname, returns the seq of the renamed one.
+    ///
+    /// The user is added by the new name before dropped by the old name, and the new one is
+    /// dropped again if the old one is changed meanwhile. The old one stays authoritative until
+    /// it is dropped.
+    async fn rename_user(
+        &self,
+        user: UserIdentity,
+        new_name: &str,
+        seq: Option<u64>,
+    ) -> Result<u64>;
+
+    async fn update_user_quota(
+        &self,
+        user: UserIdentity,
+        quota: UserQuota,
+        seq: Option<u64>,
+    ) -> Result<Option<u64>>;
+
+    /// Locks the user, a locked user can not be authenticated.
+    /// A built-in user is locked only if `force`.
+    async fn lock_user(
+        &self,
+        user: UserIdentity,
+        reason: Option<String>,
+        seq: Option<u64>,
+        force: bool,
+    ) -> Result<Option<u64>>;
+
+    async fn unlock_user(&self, user: UserIdentity, seq: Option<u64>) -> Result<Option<u64>>;
+
+    /// Sets a default setting of the sessions of the user.
+    async fn set_user_setting(
+        &self,
+        user: UserIdentity,
+        name: String,
+        value: String,
+        seq: Option<u64>,
+    ) -> Result<Option<u64>>;
+
+    async fn unset_user_setting(
+        &self,
+        user: UserIdentity,
+        name: String,
+        seq: Option<u64>,
+    ) -> Result<Option<u64>>;
+
+    /// Attaches the user to the network policy by name, detaches if `None`.
+    /// The existence of the policy is not checked here.
+    async fn set_user_network_policy(
+        &self,
+        user: UserIdentity,
+        network_policy: Option<String>,
+        seq: Option<u64>,
+    ) -> Result<Option<u64>>;
+
     async fn grant_privileges(
         &self,
         user: UserIdentity,
@@ -45,12 +199,14 @@ pub trait UserApi: Sync + Send {
         seq: Option<u64>,
     ) -> Result<Option<u64>>;
 
+    /// Revokes from a built-in user only if `force`.
     async fn revoke_privileges(
         &self,
         user: UserIdentity,
         object: GrantObject,
         privileges: UserPrivilegeSet,
         seq: Option<u64>,
+        force: bool,
     ) -> Result<Option<u64>>;
 
     async fn grant_role(
@@ -60,12 +216,47 @@ pub trait UserApi: Sync + Send {
         seq: Option<u64>,
     ) -> Result<Option<u64>>;
 
+    /// Revokes from a built-in user only if `force`.
     async fn revoke_role(
         &self,
         user: UserIdentity,
         revoke_role: String,
         seq: Option<u64>,
+        force: bool,
     ) -> Result<Option<u64>>;
 
-    async fn drop_user(&self, user: UserIdentity, seq: Option<u64>) -> Result<()>;
+    /// Resolves the privileges of the user, granted directly or by the roles it has, walking the
+    /// roles granted to roles, each role once even if the roles are granted in a cycle.
+    async fn get_user_grants(&self, user: UserIdentity) -> Result<ResolvedGrants>;
+
+    /// Drops a user, it is ok to drop an absent user if `if_exists` is true.
+    /// A built-in user is dropped only if `force`.
+    async fn drop_user(
+        &self,
+        user: UserIdentity,
+        seq: Option<u64>,
+        if_exists: bool,
+        force: bool,
+    ) -> Result<()>;
+
+    /// Gets the password rules of the tenant, the default one if not set.
+    async fn get_password_policy(&self) -> Result<PasswordPolicy>;
+
+    /// Sets the password rules of the tenant, checked with the ones of the user on a new password.
+    async fn set_password_policy(&self, policy: PasswordPolicy) -> Result<u64>;
+
+    /// Lists at most `limit` audit entries of the user of the name, the newest first.
+    async fn list_user_audit(&self, username: &str, limit: usize) -> Result<Vec<UserAuditEntry>>;
+
+    /// Verifies the response of a client by the password of the user, see [AuthInfo::auth].
+    async fn verify_auth(
+        &self,
+        user: UserIdentity,
+        client_response: &[u8],
+        salt: &[u8],
+    ) -> Result<bool>;
+
+    /// Returns the api which makes the changes on behalf of `operator`, who is recorded in the
+    /// audit entries. It shares the state, e.g., the cached users, with this one.
+    fn with_operator(&self, operator: &str) -> Arc<dyn UserApi>;
 }
@@ -12,34 +12,79 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
-use common_base::escape_for_key;
+use common_base::uuid;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_exception::ToErrorCode;
 use common_meta_api::KVApi;
+use common_meta_types::escape_key_segment;
+use common_meta_types::like_match;
+use common_meta_types::unescape_key_segment_compat;
 use common_meta_types::AuthInfo;
+use common_meta_types::Change;
 use common_meta_types::GrantObject;
 use common_meta_types::IntoSeqV;
+use common_meta_types::KeyBuilder;
 use common_meta_types::MatchSeq;
 use common_meta_types::MatchSeqExt;
 use common_meta_types::OkOrExist;
 use common_meta_types::Operation;
+use common_meta_types::PasswordPolicy;
+use common_meta_types::ReadConsistency;
 use common_meta_types::SeqV;
+use common_meta_types::TxnCondition;
+use common_meta_types::TxnOp;
+use common_meta_types::TxnOpResponse;
+use common_meta_types::TxnRequest;
 use common_meta_types::UpsertKVAction;
+use common_meta_types::UpsertKVActionReply;
+use common_meta_types::UserAuditEntry;
+use common_meta_types::UserAuditOperation;
+use common_meta_types::UserGrantSet;
 use common_meta_types::UserIdentity;
 use common_meta_types::UserInfo;
 use common_meta_types::UserOption;
 use common_meta_types::UserPrivilegeSet;
+use common_meta_types::UserQuota;
+use common_tracing::tracing;
 
+use crate::kv_list::list_decoded;
+use crate::role::RoleApi;
+use crate::role::RoleMgr;
+use crate::user::user_api::GrantSource;
+use crate::user::user_api::ImportMode;
+use crate::user::user_api::ResolvedGrants;
 use crate::user::user_api::UserApi;
+use crate::user::user_api::UserImportStatus;
+use crate::user::user_api::UserPage;
 
 static USER_API_KEY_PREFIX: &str = "__fd_users";
+static USER_AUDIT_API_KEY_PREFIX: &str = "__fd_user_audit";
+static PASSWORD_POLICY_API_KEY_PREFIX: &str = "__fd_password_policy";
 
+// Who changes the users, for the audit entries.
+#[derive(Clone)]
+struct UserAuditor {
+    operator: String,
+    strict: bool,
+}
+
+#[derive(Clone)]
 pub struct UserMgr {
     kv_api: Arc<dyn KVApi>,
+    tenant: String,
     user_prefix: String,
+    audit_prefix: String,
+    password_policy_key: String,
+    auditor: Option<UserAuditor>,
+    read_timeout: Option<Duration>,
 }
 
 impl UserMgr {
@@ -52,32 +97,391 @@ impl UserMgr {
 
         Ok(UserMgr {
             kv_api,
-            user_prefix: format!("{}/{}", USER_API_KEY_PREFIX, escape_for_key(tenant)?),
+            tenant: tenant.to_string(),
+            user_prefix: KeyBuilder::new(USER_API_KEY_PREFIX).push(tenant)?.done(),
+            audit_prefix: KeyBuilder::new(USER_AUDIT_API_KEY_PREFIX)
+                .push(tenant)?
+                .done(),
+            password_policy_key: KeyBuilder::new(PASSWORD_POLICY_API_KEY_PREFIX)
+                .push(tenant)?
+                .done(),
+            auditor: None,
+            read_timeout: None,
         })
     }
 
+    /// Gives up reading a user, e.g., to authenticate, if it is not completed in `timeout`,
+    /// instead of the default timeout of the kv api.
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Records an audit entry of every change of the users, changed by the `operator`, unless
+    /// another one is given by [UserApi::with_operator].
+    ///
+    /// A failure of the recording is logged and ignored, unless `strict`, then a change and its
+    /// entry are applied by one transaction, neither is applied without the other.
+    pub fn with_audit(mut self, operator: &str, strict: bool) -> Self {
+        self.auditor = Some(UserAuditor {
+            operator: operator.to_string(),
+            strict,
+        });
+        self
+    }
+
+    fn is_audit_strict(&self) -> bool {
+        matches!(&self.auditor, Some(auditor) if auditor.strict)
+    }
+
+    // Records the audit entry of a change applied already, a failure is logged and ignored.
+    async fn audit(&self, user: &UserIdentity, operation: UserAuditOperation, prev_seq: u64) {
+        let auditor = match &self.auditor {
+            None => return,
+            Some(auditor) => auditor,
+        };
+
+        let res = match self.audit_entry(auditor, user, operation, prev_seq) {
+            Ok((key, entry)) => self.put_audit_entry(&key, &entry).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = res {
+            tracing::warn!("fail to record the audit entry of user {}: {}", user, e);
+        }
+    }
+
+    // Same as `audit`, but records the entries of several changes by one batch.
+    async fn audit_batch(&self, changes: Vec<(UserIdentity, UserAuditOperation, u64)>) {
+        let auditor = match &self.auditor {
+            None => return,
+            Some(auditor) => auditor,
+        };
+        if changes.is_empty() {
+            return;
+        }
+
+        let count = changes.len();
+        let mut actions = Vec::with_capacity(count);
+        for (user, operation, prev_seq) in changes {
+            let action = self
+                .audit_entry(auditor, &user, operation, prev_seq)
+                .and_then(|(key, entry)| {
+                    Ok(UpsertKVAction::new(
+                        &key,
+                        MatchSeq::Exact(0),
+                        Operation::Update(serde_json::to_vec(&entry)?),
+                        None,
+                    ))
+                });
+            match action {
+                Ok(action) => actions.push(action),
+                Err(e) => tracing::warn!("fail to record the audit entry of user {}: {}", user, e),
+            }
+        }
+        if let Err(e) = self.kv_api.upsert_kv_batch(actions).await {
+            tracing::warn!("fail to record {} audit entries: {}", count, e);
+        }
+    }
+
+    // Applies the `action` on the key of the `user`, and the audit entry of the `operation` if
+    // the key is changed, replying as `upsert_kv` does. `prev_seq` is the seq of the user read
+    // before the change, 0 if it is absent.
+    //
+    // With a strict audit, the change and its entry are applied by one transaction, on the
+    // condition that the user is of `prev_seq` still, and a deletion on that the user exists.
+    async fn upsert_user_kv(
+        &self,
+        action: UpsertKVAction,
+        user: &UserIdentity,
+        operation: UserAuditOperation,
+        prev_seq: u64,
+    ) -> Result<UpsertKVActionReply> {
+        let auditor = match &self.auditor {
+            Some(auditor) if auditor.strict => auditor,
+            _ => {
+                let res = self.kv_api.upsert_kv(action).await?;
+                if res.changed() {
+                    let prev_seq = res.prev.as_ref().map_or(0, |v| v.seq);
+                    self.audit(user, operation, prev_seq).await;
+                }
+                return Ok(res);
+            }
+        };
+
+        let mut condition = vec![
+            TxnCondition::new(&action.key, MatchSeq::Exact(prev_seq)),
+            TxnCondition::new(&action.key, action.seq),
+        ];
+        let op = match action.value {
+            Operation::Update(value) => TxnOp::Put {
+                key: action.key.clone(),
+                value,
+                value_meta: action.value_meta,
+            },
+            Operation::Delete => {
+                condition.push(TxnCondition::new(&action.key, MatchSeq::GE(1)));
+                TxnOp::delete(&action.key)
+            }
+            Operation::AsIs => {
+                return Err(ErrorCode::LogicalError(format!(
+                    "no change of user {} to audit",
+                    user
+                )));
+            }
+        };
+        let (audit_key, entry) = self.audit_entry(auditor, user, operation, prev_seq)?;
+        let txn = TxnRequest {
+            condition,
+            if_then: vec![op, TxnOp::put(&audit_key, serde_json::to_vec(&entry)?)],
+            else_then: vec![TxnOp::get(&action.key)],
+        };
+
+        let reply = self.kv_api.transaction(txn).await?;
+        match (reply.success, reply.responses.into_iter().next()) {
+            (true, Some(TxnOpResponse::Put(change)))
+            | (true, Some(TxnOpResponse::Delete(change))) => Ok(change),
+            (false, Some(TxnOpResponse::Get(current))) => {
+                Ok(UpsertKVActionReply::new(current.clone(), current))
+            }
+            (_, resp) => Err(ErrorCode::MetaServiceError(format!(
+                "unexpected reply of changing user {}: {:?}",
+                user, resp
+            ))),
+        }
+    }
+
+    // Builds the audit entry of a change and the key to record it at.
+    fn audit_entry(
+        &self,
+        auditor: &UserAuditor,
+        user: &UserIdentity,
+        operation: UserAuditOperation,
+        prev_seq: u64,
+    ) -> Result<(String, UserAuditEntry)> {
+        let timestamp_ns = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        let entry = UserAuditEntry {
+            timestamp_ns,
+            operator: auditor.operator.clone(),
+            user: user.clone(),
+            operation,
+            prev_seq,
+        };
+        // The keys are grouped by the user name, and ordered by the time.
+        let key = KeyBuilder::new(&self.audit_prefix)
+            .push(&user.username)?
+            .push_raw(format!(
+                "{:020}-{}",
+                timestamp_ns,
+                uuid::Uuid::new_v4().to_simple()
+            ))
+            .done();
+        Ok((key, entry))
+    }
+
+    async fn put_audit_entry(&self, key: &str, entry: &UserAuditEntry) -> Result<()> {
+        let value = serde_json::to_vec(entry)?;
+        self.kv_api
+            .upsert_kv(UpsertKVAction::new(
+                key,
+                MatchSeq::Exact(0),
+                Operation::Update(value),
+                None,
+            ))
+            .await?;
+        Ok(())
+    }
+
+    // The kv key of the user, with the name and the hostname escaped so that the separators in them
+    // do not nest the key. The names are escaped the same by the earlier versions, no migration of
+    // the stored users is needed.
+    fn user_key(&self, username: &str, hostname: &str) -> Result<String> {
+        let user_key = format_user_key(username, hostname);
+        Ok(KeyBuilder::new(&self.user_prefix).push(&user_key)?.done())
+    }
+
+    // The upsert of the user info, if the seq of the user matches `seq`.
+    fn user_info_action(&self, user_info: &UserInfo, seq: MatchSeq) -> Result<UpsertKVAction> {
+        check_user_identity(&user_info.name, &user_info.hostname)?;
+        let key = self.user_key(&user_info.name, &user_info.hostname)?;
+        let value = user_info.to_versioned_json()?;
+        Ok(UpsertKVAction::new(
+            &key,
+            seq,
+            Operation::Update(value),
+            None,
+        ))
+    }
+
+    // Upserts by one batch the users of the `actions`, with the index of the user of each,
+    // and returns the change of every one.
+    //
+    // An error of the batch is the error of all the users in it.
+    async fn upsert_user_batch(
+        &self,
+        actions: Vec<(usize, UpsertKVAction)>,
+    ) -> Vec<(usize, Result<Change<Vec<u8>>>)> {
+        let (indexes, actions): (Vec<_>, Vec<_>) = actions.into_iter().unzip();
+        match self.kv_api.upsert_kv_batch(actions).await {
+            Ok(changes) => indexes
+                .into_iter()
+                .zip(changes.into_iter().map(Ok))
+                .collect(),
+            Err(e) => {
+                let e = ErrorCode::from(e);
+                indexes.into_iter().map(|i| (i, Err(e.clone()))).collect()
+            }
+        }
+    }
+
+    // Imports a user the same as `import_users`, but with the strict audit of the changes.
+    async fn import_user_audited(
+        &self,
+        user_info: &UserInfo,
+        mode: ImportMode,
+    ) -> Result<UserImportStatus> {
+        let identity = user_info.identity();
+        let action = self.user_info_action(user_info, MatchSeq::Exact(0))?;
+        let res = self
+            .upsert_user_kv(action, &identity, UserAuditOperation::Add, 0)
+            .await?;
+        let existing = match res.into_add_result()?.res {
+            OkOrExist::Ok(v) => return Ok(UserImportStatus::Added(v.seq)),
+            OkOrExist::Exists(v) => v,
+        };
+        match mode {
+            ImportMode::Skip => Ok(UserImportStatus::Skipped),
+            ImportMode::Fail => Err(ErrorCode::UserAlreadyExists(format!(
+                "User already exists, seq [{}]",
+                existing.seq
+            ))),
+            ImportMode::Overwrite => {
+                let action = self.user_info_action(user_info, MatchSeq::Exact(existing.seq))?;
+                let res = self
+                    .upsert_user_kv(action, &identity, UserAuditOperation::Update, existing.seq)
+                    .await?;
+                if res.changed() {
+                    let seq = res.result.map(|v| v.seq).unwrap_or(0);
+                    Ok(UserImportStatus::Overwritten(seq))
+                } else {
+                    Err(ErrorCode::UnknownUser(format!(
+                        "unknown user, or seq not match {}",
+                        user_info.name
+                    )))
+                }
+            }
+        }
+    }
+
+    async fn insert_user_info(&self, user_info: &UserInfo) -> Result<OkOrExist<Vec<u8>>> {
+        check_user_identity(&user_info.name, &user_info.hostname)?;
+        let match_seq = MatchSeq::Exact(0);
+        let key = self.user_key(&user_info.name, &user_info.hostname)?;
+        let value = user_info.to_versioned_json()?;
+
+        let kv_api = self.kv_api.clone();
+        let upsert_kv = kv_api.upsert_kv(UpsertKVAction::new(
+            &key,
+            match_seq,
+            Operation::Update(value),
+            None,
+        ));
+        let res = upsert_kv.await?.into_add_result()?;
+        Ok(res.res)
+    }
+
+    // Updates the user by `f`, with the audit of the `operation`.
+    async fn update_user_with<F>(
+        &self,
+        user: UserIdentity,
+        seq: Option<u64>,
+        operation: UserAuditOperation,
+        f: F,
+    ) -> Result<Option<u64>>
+    where
+        F: FnOnce(&mut UserInfo) + Send,
+    {
+        self.update_protected_user_with(user, seq, operation, true, f)
+            .await
+    }
+
+    // Same as `update_user_with`, but a built-in user is updated only if `force`.
+    async fn update_protected_user_with<F>(
+        &self,
+        user: UserIdentity,
+        seq: Option<u64>,
+        operation: UserAuditOperation,
+        force: bool,
+        f: F,
+    ) -> Result<Option<u64>>
+    where
+        F: FnOnce(&mut UserInfo) + Send,
+    {
+        self.try_update_user_with(user, seq, operation, force, |user_info| {
+            f(user_info);
+            Ok(())
+        })
+        .await
+    }
+
+    // Same as `update_protected_user_with`, but the user is not updated if `f` errors.
+    async fn try_update_user_with<F>(
+        &self,
+        user: UserIdentity,
+        seq: Option<u64>,
+        operation: UserAuditOperation,
+        force: bool,
+        f: F,
+    ) -> Result<Option<u64>>
+    where
+        F: FnOnce(&mut UserInfo) -> Result<()> + Send,
+    {
+        let user_val_seq = self.get_user_latest(&user, seq).await?;
+        let prev_seq = user_val_seq.seq;
+        let mut user_info = user_val_seq.data;
+        user_info.check_not_builtin(force)?;
+        f(&mut user_info)?;
+        let seq = self
+            .upsert_user_info(&user_info, seq, operation, prev_seq)
+            .await?;
+        Ok(Some(seq))
+    }
+
+    // Reads the user that is about to be changed, a lagging one would only be a retry later.
+    async fn get_user_latest(
+        &self,
+        user: &UserIdentity,
+        seq: Option<u64>,
+    ) -> Result<SeqV<UserInfo>> {
+        let key = self.user_key(&user.username, &user.hostname)?;
+        let res = self
+            .kv_api
+            .get_kv_with_consistency(&key, ReadConsistency::Linearizable)
+            .await?;
+        check_user_seq(user, seq, res)
+    }
+
+    // Upserts the user of `prev_seq`, with the audit of the `operation`.
     async fn upsert_user_info(
         &self,
         user_info: &UserInfo,
         seq: Option<u64>,
+        operation: UserAuditOperation,
+        prev_seq: u64,
     ) -> common_exception::Result<u64> {
-        let user_key = format_user_key(&user_info.name, &user_info.hostname);
-        let key = format!("{}/{}", self.user_prefix, escape_for_key(&user_key)?);
-        let value = serde_json::to_vec(&user_info)?;
+        let key = self.user_key(&user_info.name, &user_info.hostname)?;
+        let value = user_info.to_versioned_json()?;
 
         let match_seq = match seq {
             None => MatchSeq::GE(1),
             Some(s) => MatchSeq::Exact(s),
         };
 
-        let kv_api = self.kv_api.clone();
-        let res = kv_api
-            .upsert_kv(UpsertKVAction::new(
-                &key,
-                match_seq,
-                Operation::Update(value),
-                None,
-            ))
+        let action = UpsertKVAction::new(&key, match_seq, Operation::Update(value), None);
+        let res = self
+            .upsert_user_kv(action, &user_info.identity(), operation, prev_seq)
             .await?;
         match res.result {
             Some(SeqV { seq: s, .. }) => Ok(s),
@@ -91,22 +495,18 @@ impl UserMgr {
 
 #[async_trait::async_trait]
 impl UserApi for UserMgr {
-    async fn add_user(&self, user_info: UserInfo) -> common_exception::Result<u64> {
-        let match_seq = MatchSeq::Exact(0);
-        let user_key = format_user_key(&user_info.name, &user_info.hostname);
-        let key = format!("{}/{}", self.user_prefix, escape_for_key(&user_key)?);
-        let value = serde_json::to_vec(&user_info)?;
-
-        let kv_api = self.kv_api.clone();
-        let upsert_kv = kv_api.upsert_kv(UpsertKVAction::new(
-            &key,
-            match_seq,
-            Operation::Update(value),
-            None,
-        ));
-        let res = upsert_kv.await?.into_add_result()?;
-        match res.res {
+    async fn add_user(
+        &self,
+        user_info: UserInfo,
+        if_not_exists: bool,
+    ) -> common_exception::Result<u64> {
+        let action = self.user_info_action(&user_info, MatchSeq::Exact(0))?;
+        let res = self
+            .upsert_user_kv(action, &user_info.identity(), UserAuditOperation::Add, 0)
+            .await?;
+        match res.into_add_result()?.res {
             OkOrExist::Ok(v) => Ok(v.seq),
+            OkOrExist::Exists(v) if if_not_exists => Ok(v.seq),
             OkOrExist::Exists(v) => Err(ErrorCode::UserAlreadyExists(format!(
                 "User already exists, seq [{}]",
                 v.seq
@@ -115,31 +515,213 @@ impl UserApi for UserMgr {
     }
 
     async fn get_user(&self, user: UserIdentity, seq: Option<u64>) -> Result<SeqV<UserInfo>> {
-        let user_key = format_user_key(&user.username, &user.hostname);
-        let key = format!("{}/{}", self.user_prefix, escape_for_key(&user_key)?);
-        let res = self.kv_api.get_kv(&key).await?;
-        let seq_value =
-            res.ok_or_else(|| ErrorCode::UnknownUser(format!("unknown user {}", user_key)))?;
+        let key = self.user_key(&user.username, &user.hostname)?;
+        let res = match self.read_timeout {
+            None => self.kv_api.get_kv(&key).await?,
+            Some(timeout) => self.kv_api.get_kv_with_deadline(&key, timeout).await?,
+        };
+        check_user_seq(&user, seq, res)
+    }
 
-        match MatchSeq::from(seq).match_seq(&seq_value) {
-            Ok(_) => Ok(seq_value.into_seqv()?),
-            Err(_) => Err(ErrorCode::UnknownUser(format!("unknown user {}", user_key))),
-        }
+    async fn exists_user(&self, user: UserIdentity) -> Result<bool> {
+        Ok(self.get_user_seq(user).await?.is_some())
+    }
+
+    async fn get_user_seq(&self, user: UserIdentity) -> Result<Option<u64>> {
+        let key = self.user_key(&user.username, &user.hostname)?;
+        let res = self.kv_api.get_kv_meta(&key).await?;
+        Ok(res.map(|(seq, _)| seq))
     }
 
     async fn get_users(&self) -> Result<Vec<SeqV<UserInfo>>> {
-        let user_prefix = self.user_prefix.clone();
-        let values = self.kv_api.prefix_list_kv(user_prefix.as_str()).await?;
+        // Ends with the separator, or the users of the tenants prefixed by this one are listed.
+        let list_prefix = KeyBuilder::new(&self.user_prefix).dir();
+        let listed = list_decoded(
+            self.kv_api.as_ref(),
+            &list_prefix,
+            UserInfo::from_versioned_json,
+        )
+        .await?;
+
+        Ok(listed.into_values_warn_invalid("user"))
+    }
 
-        let mut r = vec![];
-        for (_key, val) in values {
-            let u = serde_json::from_slice::<UserInfo>(&val.data)
-                .map_err_to_code(ErrorCode::IllegalUserInfoFormat, || "")?;
+    async fn get_users_of_name(&self, username: &str) -> Result<Vec<SeqV<UserInfo>>> {
+        // The user key is `'name'@'host'`, the keys of the name are prefixed by `'name'@`.
+        let list_prefix = format!(
+            "{}/{}",
+            self.user_prefix,
+            escape_key_segment(&format!("'{}'@", username))
+        );
+        let listed = list_decoded(
+            self.kv_api.as_ref(),
+            &list_prefix,
+            UserInfo::from_versioned_json,
+        )
+        .await?;
 
-            r.push(SeqV::new(val.seq, u));
+        // The prefix of a name is the prefix of the keys of other names too, e.g., the key of the
+        // user `bob'@x` on host `%` is `'bob'@x'@'%'`, which is prefixed by `'bob'@`.
+        Ok(listed
+            .into_values_warn_invalid("user")
+            .into_iter()
+            .filter(|user| user.data.name == username)
+            .collect())
+    }
+
+    async fn export_users(&self) -> Result<Vec<UserInfo>> {
+        let mut users = self
+            .get_users()
+            .await?
+            .into_iter()
+            .map(|user| user.data)
+            .collect::<Vec<_>>();
+        users.sort_by(|a, b| (&a.name, &a.hostname).cmp(&(&b.name, &b.hostname)));
+        Ok(users)
+    }
+
+    async fn import_users(
+        &self,
+        users: Vec<UserInfo>,
+        mode: ImportMode,
+    ) -> Result<Vec<(UserIdentity, Result<UserImportStatus>)>> {
+        if self.is_audit_strict() {
+            // Every user with its audit entries, by its own transactions.
+            let mut statuses = Vec::with_capacity(users.len());
+            for user_info in users {
+                let status = self.import_user_audited(&user_info, mode).await;
+                statuses.push((user_info.identity(), status));
+            }
+            return Ok(statuses);
+        }
+
+        // The users are added by one batch, then the existing ones are overwritten by another.
+        let mut statuses: Vec<Option<Result<UserImportStatus>>> =
+            users.iter().map(|_| None).collect();
+        let mut audits = vec![];
+
+        let mut adding = vec![];
+        for (i, user_info) in users.iter().enumerate() {
+            match self.user_info_action(user_info, MatchSeq::Exact(0)) {
+                Ok(act) => adding.push((i, act)),
+                Err(e) => statuses[i] = Some(Err(e)),
+            }
+        }
+
+        let mut overwriting = vec![];
+        let mut existing_seqs = HashMap::new();
+        for (i, change) in self.upsert_user_batch(adding).await {
+            let existing = match change.and_then(|ch| Ok(ch.into_add_result()?.res)) {
+                Ok(OkOrExist::Ok(v)) => {
+                    audits.push((i, UserAuditOperation::Add, 0));
+                    statuses[i] = Some(Ok(UserImportStatus::Added(v.seq)));
+                    continue;
+                }
+                Ok(OkOrExist::Exists(v)) => v,
+                Err(e) => {
+                    statuses[i] = Some(Err(e));
+                    continue;
+                }
+            };
+            statuses[i] = match mode {
+                ImportMode::Skip => Some(Ok(UserImportStatus::Skipped)),
+                ImportMode::Fail => Some(Err(ErrorCode::UserAlreadyExists(format!(
+                    "User already exists, seq [{}]",
+                    existing.seq
+                )))),
+                ImportMode::Overwrite => {
+                    // Fails if the user is changed meanwhile, instead of overwriting the change.
+                    match self.user_info_action(&users[i], MatchSeq::Exact(existing.seq)) {
+                        Ok(act) => {
+                            overwriting.push((i, act));
+                            existing_seqs.insert(i, existing.seq);
+                            None
+                        }
+                        Err(e) => Some(Err(e)),
+                    }
+                }
+            };
         }
 
-        Ok(r)
+        for (i, change) in self.upsert_user_batch(overwriting).await {
+            statuses[i] = Some(match change {
+                Ok(ch) if ch.changed() => {
+                    let seq = ch.result.map(|v| v.seq).unwrap_or(0);
+                    audits.push((i, UserAuditOperation::Update, existing_seqs[&i]));
+                    Ok(UserImportStatus::Overwritten(seq))
+                }
+                Ok(_) => Err(ErrorCode::UnknownUser(format!(
+                    "unknown user, or seq not match {}",
+                    users[i].name
+                ))),
+                Err(e) => Err(e),
+            });
+        }
+
+        let changes = audits
+            .iter()
+            .map(|(i, operation, prev_seq)| (users[*i].identity(), operation.clone(), *prev_seq))
+            .collect::<Vec<_>>();
+        self.audit_batch(changes).await;
+
+        Ok(users
+            .iter()
+            .zip(statuses)
+            .map(|(user_info, status)| {
+                let status = status.unwrap_or_else(|| {
+                    Err(ErrorCode::MetaServiceError(format!(
+                        "no result of importing user {}",
+                        user_info.identity()
+                    )))
+                });
+                (user_info.identity(), status)
+            })
+            .collect())
+    }
+
+    async fn get_users_paged(
+        &self,
+        pattern: Option<&str>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<UserPage> {
+        // The user key is `'name'@'host'`, the literal prefix of the pattern is a prefix of it.
+        let literal_prefix = pattern
+            .map(|p| p.split(|c| c == '%' || c == '_').next().unwrap_or(""))
+            .unwrap_or("");
+        let list_prefix = format!(
+            "{}/{}",
+            self.user_prefix,
+            escape_key_segment(&format!("'{}", literal_prefix))
+        );
+        let values = self.kv_api.prefix_list_kv(list_prefix.as_str()).await?;
+
+        let mut matched = Vec::with_capacity(values.len());
+        for (key, val) in values {
+            // A key stored unescaped by an older version is kept as is.
+            let user_key = unescape_key_segment_compat(&key[self.user_prefix.len() + 1..]);
+            let (name, _) = parse_user_key(&user_key);
+            if pattern.map_or(true, |p| like_match(p, name)) {
+                matched.push((user_key, val));
+            }
+        }
+        matched.sort_by(|(l, _), (r, _)| l.cmp(r));
+
+        let total = matched.len();
+        let mut users = vec![];
+        let mut invalid = vec![];
+        for (user_key, val) in matched.into_iter().skip(offset).take(limit) {
+            match UserInfo::from_versioned_json(&val.data) {
+                Ok(u) => users.push(SeqV::new(val.seq, u)),
+                Err(e) => invalid.push((user_key.clone(), e.add_message(&user_key))),
+            }
+        }
+
+        Ok(UserPage {
+            users,
+            total,
+            invalid,
+        })
     }
 
     async fn update_user(
@@ -149,17 +731,171 @@ impl UserApi for UserMgr {
         new_user_option: Option<UserOption>,
         seq: Option<u64>,
     ) -> Result<Option<u64>> {
-        let user_val_seq = self.get_user(user, seq);
-        let mut user_info = user_val_seq.await?.data;
+        let tenant_policy = match new_auth_info {
+            Some(_) => Some(self.get_password_policy().await?),
+            None => None,
+        };
+        self.try_update_user_with(user, seq, UserAuditOperation::Update, true, |user_info| {
+            if let (Some(auth_info), Some(tenant_policy)) = (new_auth_info, tenant_policy) {
+                let history_depth = user_info
+                    .password_policy
+                    .stricter(&tenant_policy)
+                    .history_depth;
+                user_info.change_password(auth_info, history_depth)?;
+                // The password is changed as required.
+                user_info.password_policy.must_change = false;
+                user_info.password_policy.expire_at = None;
+            };
+            if let Some(user_option) = new_user_option {
+                user_info.option = user_option;
+            };
+            Ok(())
+        })
+        .await
+    }
 
-        if let Some(auth_info) = new_auth_info {
-            user_info.auth_info = auth_info;
+    async fn rename_user(
+        &self,
+        user: UserIdentity,
+        new_name: &str,
+        seq: Option<u64>,
+    ) -> Result<u64> {
+        let old = self.get_user_latest(&user, seq).await?;
+        let mut user_info = old.data;
+        user_info.name = new_name.to_string();
+        let new_identity = user_info.identity();
+        // The new user is recorded by the audit of the renaming, rather than an addition.
+        let new_seq = match self.insert_user_info(&user_info).await? {
+            OkOrExist::Ok(v) => v.seq,
+            OkOrExist::Exists(v) => {
+                return Err(ErrorCode::UserAlreadyExists(format!(
+                    "User already exists, seq [{}]",
+                    v.seq
+                )));
+            }
         };
-        if let Some(user_option) = new_user_option {
-            user_info.option = user_option;
+
+        let old_key = self.user_key(&user.username, &user.hostname)?;
+        let action =
+            UpsertKVAction::new(&old_key, MatchSeq::Exact(old.seq), Operation::Delete, None);
+        let operation = UserAuditOperation::Rename(new_name.to_string());
+        let res = self.upsert_user_kv(action, &user, operation, old.seq).await;
+        let cause = match res {
+            Ok(res) if res.prev.is_some() && res.result.is_none() => None,
+            Ok(_) => Some(format!("user {} is changed while renaming", user)),
+            // The deletion may be applied though it fails, e.g., the reply is lost. Rolling back
+            // the new user then would lose the user, it is decided by the old one.
+            Err(e) => match self.kv_api.get_kv(&old_key).await {
+                Ok(None) => None,
+                Ok(Some(_)) => Some(format!("fail to drop user {} while renaming: {}", user, e)),
+                Err(read_err) => {
+                    return Err(ErrorCode::MetaServiceError(format!(
+                        "fail to drop user {} while renaming: {}, and fail to read it: {}, \
+                         both {} and {} may exist",
+                        user, e, read_err, user, new_identity
+                    )));
+                }
+            },
         };
-        let seq = self.upsert_user_info(&user_info, seq).await?;
-        Ok(Some(seq))
+        match cause {
+            None => Ok(new_seq),
+            Some(cause) => {
+                // The old user is changed or dropped meanwhile, roll back the new one.
+                let new_key = self.user_key(&new_identity.username, &new_identity.hostname)?;
+                let rollback = self
+                    .kv_api
+                    .upsert_kv(UpsertKVAction::new(
+                        &new_key,
+                        MatchSeq::Exact(new_seq),
+                        Operation::Delete,
+                        None,
+                    ))
+                    .await;
+
+                match rollback {
+                    Ok(_) => Err(ErrorCode::UnknownUser(cause)),
+                    Err(e) => Err(ErrorCode::UnknownUser(format!(
+                        "{}, and fail to drop the renamed user {}: {}",
+                        cause, new_identity, e
+                    ))),
+                }
+            }
+        }
+    }
+
+    async fn update_user_quota(
+        &self,
+        user: UserIdentity,
+        quota: UserQuota,
+        seq: Option<u64>,
+    ) -> Result<Option<u64>> {
+        self.update_user_with(user, seq, UserAuditOperation::UpdateQuota, |user_info| {
+            user_info.quota = quota;
+        })
+        .await
+    }
+
+    async fn lock_user(
+        &self,
+        user: UserIdentity,
+        reason: Option<String>,
+        seq: Option<u64>,
+        force: bool,
+    ) -> Result<Option<u64>> {
+        let operation = UserAuditOperation::Lock;
+        self.update_protected_user_with(user, seq, operation, force, |user_info| {
+            user_info.is_locked = true;
+            user_info.locked_reason = reason;
+        })
+        .await
+    }
+
+    async fn unlock_user(&self, user: UserIdentity, seq: Option<u64>) -> Result<Option<u64>> {
+        self.update_user_with(user, seq, UserAuditOperation::Unlock, |user_info| {
+            user_info.is_locked = false;
+            user_info.locked_reason = None;
+        })
+        .await
+    }
+
+    async fn set_user_setting(
+        &self,
+        user: UserIdentity,
+        name: String,
+        value: String,
+        seq: Option<u64>,
+    ) -> Result<Option<u64>> {
+        let operation = UserAuditOperation::SetSetting(name.clone());
+        self.update_user_with(user, seq, operation, |user_info| {
+            user_info.settings.insert(name, value);
+        })
+        .await
+    }
+
+    async fn unset_user_setting(
+        &self,
+        user: UserIdentity,
+        name: String,
+        seq: Option<u64>,
+    ) -> Result<Option<u64>> {
+        let operation = UserAuditOperation::UnsetSetting(name.clone());
+        self.update_user_with(user, seq, operation, |user_info| {
+            user_info.settings.remove(&name);
+        })
+        .await
+    }
+
+    async fn set_user_network_policy(
+        &self,
+        user: UserIdentity,
+        network_policy: Option<String>,
+        seq: Option<u64>,
+    ) -> Result<Option<u64>> {
+        let operation = UserAuditOperation::SetNetworkPolicy(network_policy.clone());
+        self.update_user_with(user, seq, operation, |user_info| {
+            user_info.network_policy = network_policy;
+        })
+        .await
     }
 
     async fn grant_privileges(
@@ -169,11 +905,15 @@ impl UserApi for UserMgr {
         privileges: UserPrivilegeSet,
         seq: Option<u64>,
     ) -> Result<Option<u64>> {
-        let user_val_seq = self.get_user(user, seq);
-        let mut user_info = user_val_seq.await?.data;
-        user_info.grants.grant_privileges(&object, privileges);
-        let seq = self.upsert_user_info(&user_info, seq).await?;
-        Ok(Some(seq))
+        self.update_user_with(
+            user,
+            seq,
+            UserAuditOperation::GrantPrivileges,
+            |user_info| {
+                user_info.grants.grant_privileges(&object, privileges);
+            },
+        )
+        .await
     }
 
     async fn revoke_privileges(
@@ -182,12 +922,13 @@ impl UserApi for UserMgr {
         object: GrantObject,
         privileges: UserPrivilegeSet,
         seq: Option<u64>,
+        force: bool,
     ) -> Result<Option<u64>> {
-        let user_val_seq = self.get_user(user, seq);
-        let mut user_info = user_val_seq.await?.data;
-        user_info.grants.revoke_privileges(&object, privileges);
-        let seq = self.upsert_user_info(&user_info, seq).await?;
-        Ok(Some(seq))
+        let operation = UserAuditOperation::RevokePrivileges;
+        self.update_protected_user_with(user, seq, operation, force, |user_info| {
+            user_info.grants.revoke_privileges(&object, privileges);
+        })
+        .await
     }
 
     async fn grant_role(
@@ -196,11 +937,11 @@ impl UserApi for UserMgr {
         grant_role: String,
         seq: Option<u64>,
     ) -> Result<Option<u64>> {
-        let user_val_seq = self.get_user(user, seq);
-        let mut user_info = user_val_seq.await?.data;
-        user_info.grants.grant_role(grant_role);
-        let seq = self.upsert_user_info(&user_info, seq).await?;
-        Ok(Some(seq))
+        let operation = UserAuditOperation::GrantRole(grant_role.clone());
+        self.update_user_with(user, seq, operation, |user_info| {
+            user_info.grants.grant_role(grant_role);
+        })
+        .await
     }
 
     async fn revoke_role(
@@ -208,34 +949,217 @@ impl UserApi for UserMgr {
         user: UserIdentity,
         revoke_role: String,
         seq: Option<u64>,
+        force: bool,
     ) -> Result<Option<u64>> {
-        let user_val_seq = self.get_user(user, seq);
-        let mut user_info = user_val_seq.await?.data;
-        user_info.grants.revoke_role(&revoke_role);
-        let seq = self.upsert_user_info(&user_info, seq).await?;
-        Ok(Some(seq))
+        let operation = UserAuditOperation::RevokeRole(revoke_role.clone());
+        self.update_protected_user_with(user, seq, operation, force, |user_info| {
+            user_info.grants.revoke_role(&revoke_role);
+        })
+        .await
     }
 
-    async fn drop_user(&self, user: UserIdentity, seq: Option<u64>) -> Result<()> {
-        let user_key = format_user_key(&user.username, &user.hostname);
-        let key = format!("{}/{}", self.user_prefix, escape_for_key(&user_key)?);
+    async fn get_user_grants(&self, user: UserIdentity) -> Result<ResolvedGrants> {
+        let user_info = self.get_user(user, None).await?.data;
+        let role_api = RoleMgr::create(self.kv_api.clone(), &self.tenant)?;
+
+        let mut resolved = ResolvedGrants::default();
+        let mut add_grants = |source: GrantSource, grants: &UserGrantSet| {
+            for entry in grants.entries() {
+                resolved
+                    .merged
+                    .grant_privileges(entry.object(), entry.privileges());
+                resolved.provenance.push((source.clone(), entry));
+            }
+        };
+        add_grants(GrantSource::User, &user_info.grants);
+
+        // Walks the roles breadth first, in the order of the names among the siblings.
+        // The roles of a level are read in one batch.
+        let mut visited = HashSet::new();
+        let mut unknown_roles = vec![];
+        let mut level = user_info.grants.roles();
+        level.sort();
+        while !level.is_empty() {
+            let to_fetch = level
+                .iter()
+                .filter(|name| !visited.contains(*name))
+                .cloned()
+                .collect::<Vec<_>>();
+            let roles = role_api.get_roles_by_names(&to_fetch).await?;
+
+            let mut next_level = vec![];
+            for name in level {
+                if !visited.insert(name.clone()) {
+                    continue;
+                }
+                let role = match roles.get(&name) {
+                    None => {
+                        unknown_roles.push(name);
+                        continue;
+                    }
+                    Some(role) => &role.data,
+                };
+                add_grants(GrantSource::Role(name.clone()), &role.grants);
+                let mut granted_roles = role.grants.roles();
+                granted_roles.sort();
+                next_level.extend(granted_roles);
+            }
+            level = next_level;
+        }
+
+        for name in visited {
+            if !unknown_roles.contains(&name) {
+                resolved.merged.grant_role(name);
+            }
+        }
+        resolved.unknown_roles = unknown_roles;
+        Ok(resolved)
+    }
+
+    async fn drop_user(
+        &self,
+        user: UserIdentity,
+        seq: Option<u64>,
+        if_exists: bool,
+        force: bool,
+    ) -> Result<()> {
+        // The seq of the user is the condition of the deletion in a strict audit.
+        let mut prev_seq = 0;
+        if !force || self.is_audit_strict() {
+            // An absent user is left to the deletion below to report.
+            match self.get_user_latest(&user, seq).await {
+                Ok(user_info) => {
+                    user_info.data.check_not_builtin(force)?;
+                    prev_seq = user_info.seq;
+                }
+                Err(e) if e.code() == ErrorCode::unknown_user_code() => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let key = self.user_key(&user.username, &user.hostname)?;
+        let action = UpsertKVAction::new(&key, seq.into(), Operation::Delete, None);
         let res = self
-            .kv_api
-            .upsert_kv(UpsertKVAction::new(
-                &key,
-                seq.into(),
-                Operation::Delete,
-                None,
-            ))
+            .upsert_user_kv(action, &user, UserAuditOperation::Drop, prev_seq)
             .await?;
-        if res.prev.is_some() && res.result.is_none() {
+        if let (Some(_), None) = (&res.prev, &res.result) {
+            Ok(())
+        } else if res.prev.is_none() && if_exists {
+            // the user is absent, rather than kept by a mismatched seq
             Ok(())
         } else {
-            Err(ErrorCode::UnknownUser(format!("unknown user {}", user_key)))
+            Err(ErrorCode::UnknownUser(format!("unknown user {}", user)))
         }
     }
+
+    async fn get_password_policy(&self) -> Result<PasswordPolicy> {
+        match self.kv_api.get_kv(&self.password_policy_key).await? {
+            None => Ok(PasswordPolicy::default()),
+            Some(v) => serde_json::from_slice::<PasswordPolicy>(&v.data)
+                .map_err_to_code(ErrorCode::IllegalUserInfoFormat, || "password policy"),
+        }
+    }
+
+    async fn set_password_policy(&self, policy: PasswordPolicy) -> Result<u64> {
+        // Upsert.
+        let value = Operation::Update(serde_json::to_vec(&policy)?);
+        let upsert = self.kv_api.upsert_kv(UpsertKVAction::new(
+            &self.password_policy_key,
+            MatchSeq::Any,
+            value,
+            None,
+        ));
+
+        let res = upsert.await?.into_add_result()?;
+        match res.res {
+            OkOrExist::Ok(v) => Ok(v.seq),
+            OkOrExist::Exists(v) => Ok(v.seq),
+        }
+    }
+
+    async fn list_user_audit(&self, username: &str, limit: usize) -> Result<Vec<UserAuditEntry>> {
+        let list_prefix = KeyBuilder::new(&self.audit_prefix).push(username)?.dir();
+        let mut values = self.kv_api.prefix_list_kv(&list_prefix).await?;
+        values.sort_by(|(l, _), (r, _)| r.cmp(l));
+
+        values
+            .into_iter()
+            .take(limit)
+            .map(|(_key, val)| {
+                serde_json::from_slice::<UserAuditEntry>(&val.data)
+                    .map_err_to_code(ErrorCode::IllegalUserInfoFormat, || "")
+            })
+            .collect()
+    }
+
+    async fn verify_auth(
+        &self,
+        user: UserIdentity,
+        client_response: &[u8],
+        salt: &[u8],
+    ) -> Result<bool> {
+        let user_info = self.get_user(user, None).await?.data;
+        user_info.check_not_locked()?;
+        user_info.auth_info.auth(client_response, salt)
+    }
+
+    fn with_operator(&self, operator: &str) -> Arc<dyn UserApi> {
+        let mut user_mgr = self.clone();
+        if let Some(auditor) = &mut user_mgr.auditor {
+            auditor.operator = operator.to_string();
+        }
+        Arc::new(user_mgr)
+    }
+}
+
+// The names are checked on adding, the users of the illegal names can not be parsed back from the
+// keys, see parse_user_key.
+fn check_user_seq(
+    user: &UserIdentity,
+    seq: Option<u64>,
+    res: Option<SeqV<Vec<u8>>>,
+) -> Result<SeqV<UserInfo>> {
+    let seq_value = res.ok_or_else(|| ErrorCode::UnknownUser(format!("unknown user {}", user)))?;
+
+    match MatchSeq::from(seq).match_seq(&seq_value) {
+        Ok(_) => Ok(seq_value.into_seqv()?),
+        Err(_) => Err(ErrorCode::UnknownUser(format!("unknown user {}", user))),
+    }
+}
+
+fn check_user_identity(username: &str, hostname: &str) -> Result<()> {
+    for (kind, name) in [("name", username), ("hostname", hostname)] {
+        if name.chars().any(|c| c.is_control()) {
+            return Err(ErrorCode::IllegalUserIdentity(format!(
+                "user {} can not contain control characters: {:?}",
+                kind, name
+            )));
+        }
+        if name.contains("'@'") {
+            return Err(ErrorCode::IllegalUserIdentity(format!(
+                "user {} can not contain the separator \"'@'\": {}",
+                kind, name
+            )));
+        }
+    }
+    Ok(())
 }
 
 fn format_user_key(username: &str, hostname: &str) -> String {
     format!("'{}'@'{}'", username, hostname)
 }
+
+// The reverse of format_user_key, as (name, hostname), the hostname never contains `'@'`.
+fn parse_user_key(user_key: &str) -> (&str, &str) {
+    match user_key.rfind("'@'") {
+        Some(i) => {
+            let name = &user_key[..i];
+            let hostname = &user_key[i + 3..];
+            (
+                name.strip_prefix('\'').unwrap_or(name),
+                hostname.strip_suffix('\'').unwrap_or(hostname),
+            )
+        }
+        None => (user_key, ""),
+    }
+}
@@ -27,6 +27,7 @@ use common_meta_types::SeqV;
 use common_meta_types::UpsertKVAction;
 use common_meta_types::WarehouseInfo;
 
+use crate::kv_list::list_decoded;
 use crate::warehouse::warehouse_api::WarehouseApi;
 
 static WAREHOUSE_API_KEY_PREFIX: &str = "__fd_warehouses";
@@ -94,16 +95,12 @@ impl WarehouseApi for WarehouseMgr {
     }
 
     async fn get_warehouses(&self) -> Result<Vec<SeqV<WarehouseInfo>>> {
-        let warehuose_prefx = self.warehouse_prefix.clone();
-        let values = self.kv_api.prefix_list_kv(warehuose_prefx.as_str()).await?;
-        let mut r = vec![];
-        for (_key, val) in values {
-            let u = serde_json::from_slice::<WarehouseInfo>(&val.data)
-                .map_err_to_code(ErrorCode::IllegalWarehouseInfoFormat, || "")?;
-
-            r.push(SeqV::new(val.seq, u));
-        }
-        Ok(r)
+        let listed = list_decoded(self.kv_api.as_ref(), &self.warehouse_prefix, |data| {
+            serde_json::from_slice::<WarehouseInfo>(data)
+                .map_err_to_code(ErrorCode::IllegalWarehouseInfoFormat, || "")
+        })
+        .await?;
+        Ok(listed.into_values_warn_invalid("warehouse"))
     }
 
     async fn update_warehouse_size(&self, name: &str, size: &str, seq: Option<u64>) -> Result<u64> {
@@ -13,16 +13,37 @@
 // limitations under the License.
 
 use std::sync::Arc;
+use std::sync::Mutex;
 use std::time::Duration;
 use std::time::UNIX_EPOCH;
 
+use async_trait::async_trait;
 use common_base::tokio;
 use common_exception::Result;
 use common_management::*;
 use common_meta_api::KVApi;
+use common_meta_api::WatchStream;
 use common_meta_embedded::MetaEmbedded;
+use common_meta_types::DeleteKVReply;
+use common_meta_types::GetKVActionReply;
+use common_meta_types::GetKVMetaReply;
+use common_meta_types::IncrKVReply;
+use common_meta_types::MGetKVActionReply;
+use common_meta_types::MatchSeq;
+use common_meta_types::MetaError;
 use common_meta_types::NodeInfo;
+use common_meta_types::Operation;
+use common_meta_types::PrefixListPage;
+use common_meta_types::PrefixListReply;
 use common_meta_types::SeqV;
+use common_meta_types::TxnReply;
+use common_meta_types::TxnRequest;
+use common_meta_types::UpsertKVAction;
+use common_meta_types::UpsertKVActionReply;
+use common_meta_types::UpsertKVBatchReply;
+use common_meta_types::WatchEvent;
+use futures::channel::mpsc::UnboundedSender;
+use futures::StreamExt;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_successfully_add_node() -> Result<()> {
@@ -137,6 +158,206 @@ async fn test_successfully_heartbeat_node() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_node_stops_heartbeat() -> Result<()> {
+    // - Node a keeps heartbeating, node b is added and never heartbeats.
+    // - After the lift time, only node a is listed.
+    // - Once the heartbeat of node a is stopped and a is dropped, no node is listed.
+
+    let kv_api = Arc::new(MetaEmbedded::new_temp().await?);
+    let cluster_api = Arc::new(ClusterMgr::create(
+        kv_api.clone(),
+        "admin",
+        "c1",
+        Duration::from_secs(2),
+    )?);
+
+    let a = NodeInfo::create("a".to_string(), 1, "1.1.1.1:1".to_string());
+    let b = NodeInfo::create("b".to_string(), 1, "2.2.2.2:2".to_string())
+        .with_capabilities(vec!["exchange".to_string()]);
+    cluster_api.add_node(a.clone()).await?;
+    cluster_api.add_node(b.clone()).await?;
+    assert_eq!(cluster_api.get_nodes().await?, vec![a.clone(), b]);
+
+    let heartbeat =
+        NodeHeartbeat::start(cluster_api.clone(), a.clone(), Duration::from_millis(500))?;
+
+    // expire_at is in seconds, wait one more second than the lift time.
+    tokio::time::sleep(Duration::from_secs(4)).await;
+    assert_eq!(cluster_api.get_nodes().await?, vec![a]);
+
+    let state = heartbeat.state();
+    assert!(state.runs > 1);
+    assert!(state.last_error.is_none());
+
+    // A stopped heartbeat does not add the dropped node back.
+    heartbeat.stop().await;
+    cluster_api.drop_node("a".to_string(), None).await?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(cluster_api.get_nodes().await?, vec![]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_node_cache_without_watch() -> Result<()> {
+    // An embedded meta can not be watched, the cache lists the nodes on every read.
+
+    let (_, cluster_api) = new_cluster_api().await?;
+    let cache = cluster_api.start_node_cache().await?;
+    assert!(!cache.is_watching());
+    assert_eq!(cache.get_nodes().await?, vec![]);
+
+    let node_info = create_test_node_info();
+    cluster_api.add_node(node_info.clone()).await?;
+    assert_eq!(cache.get_nodes().await?, vec![node_info]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_node_cache_with_watch() -> Result<()> {
+    let kv_api = Arc::new(WatchedKV::new(MetaEmbedded::new_temp().await?));
+    let cluster_api = ClusterMgr::create(kv_api.clone(), "admin", "c1", Duration::from_secs(60))?;
+    let short_lived = ClusterMgr::create(kv_api.clone(), "admin", "c1", Duration::from_secs(1))?;
+
+    let a = NodeInfo::create("a".to_string(), 1, "1.1.1.1:1".to_string());
+    cluster_api.add_node(a.clone()).await?;
+
+    let cache = cluster_api.start_node_cache().await?;
+    assert!(cache.is_watching());
+    assert_eq!(cache.get_nodes().await?, vec![a.clone()]);
+
+    // A node added later is seen by the watch.
+    let b = NodeInfo::create("b".to_string(), 1, "2.2.2.2:2".to_string());
+    short_lived.add_node(b.clone()).await?;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(cache.get_nodes().await?, vec![a.clone(), b]);
+
+    // A node that is not renewed is filtered out once expired, there is no event for it.
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    assert_eq!(cache.get_nodes().await?, vec![a.clone()]);
+
+    // The removal of a node is seen by the watch.
+    cluster_api.drop_node(a.id.clone(), None).await?;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(cache.get_nodes().await?, vec![]);
+
+    Ok(())
+}
+
+/// A KVApi that publishes the changes made by `upsert_kv` to the last watcher, as a meta-service
+/// does, since an embedded meta can not be watched.
+struct WatchedKV {
+    inner: MetaEmbedded,
+    watcher: Mutex<Option<UnboundedSender<std::result::Result<WatchEvent, MetaError>>>>,
+}
+
+impl WatchedKV {
+    fn new(inner: MetaEmbedded) -> Self {
+        WatchedKV {
+            inner,
+            watcher: Mutex::new(None),
+        }
+    }
+}
+
+#[async_trait]
+impl KVApi for WatchedKV {
+    async fn upsert_kv(
+        &self,
+        act: UpsertKVAction,
+    ) -> std::result::Result<UpsertKVActionReply, MetaError> {
+        let key = act.key.clone();
+        let res = self.inner.upsert_kv(act).await?;
+        if res.prev != res.result {
+            if let Some(tx) = self.watcher.lock().unwrap().as_ref() {
+                let _ = tx.unbounded_send(Ok(WatchEvent {
+                    seq: 0,
+                    key,
+                    prev: res.prev.clone(),
+                    current: res.result.clone(),
+                }));
+            }
+        }
+        Ok(res)
+    }
+
+    async fn upsert_kv_batch(
+        &self,
+        actions: Vec<UpsertKVAction>,
+    ) -> std::result::Result<UpsertKVBatchReply, MetaError> {
+        self.inner.upsert_kv_batch(actions).await
+    }
+
+    async fn get_kv(&self, key: &str) -> std::result::Result<GetKVActionReply, MetaError> {
+        self.inner.get_kv(key).await
+    }
+
+    async fn get_kv_meta(&self, key: &str) -> std::result::Result<GetKVMetaReply, MetaError> {
+        self.inner.get_kv_meta(key).await
+    }
+
+    async fn delete_kv(
+        &self,
+        key: &str,
+        seq: MatchSeq,
+    ) -> std::result::Result<DeleteKVReply, MetaError> {
+        self.upsert_kv(UpsertKVAction::new(key, seq, Operation::Delete, None))
+            .await
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> std::result::Result<u64, MetaError> {
+        self.inner.delete_prefix(prefix).await
+    }
+
+    async fn incr_kv(
+        &self,
+        key: &str,
+        delta: i64,
+        seq: MatchSeq,
+    ) -> std::result::Result<IncrKVReply, MetaError> {
+        self.inner.incr_kv(key, delta, seq).await
+    }
+
+    async fn mget_kv(&self, keys: &[String]) -> std::result::Result<MGetKVActionReply, MetaError> {
+        self.inner.mget_kv(keys).await
+    }
+
+    async fn prefix_list_kv(
+        &self,
+        prefix: &str,
+    ) -> std::result::Result<PrefixListReply, MetaError> {
+        self.inner.prefix_list_kv(prefix).await
+    }
+
+    async fn prefix_list_kv_paged(
+        &self,
+        prefix: &str,
+        after_key: Option<String>,
+        limit: usize,
+        keys_only: bool,
+    ) -> std::result::Result<PrefixListPage, MetaError> {
+        self.inner
+            .prefix_list_kv_paged(prefix, after_key, limit, keys_only)
+            .await
+    }
+
+    async fn transaction(&self, txn: TxnRequest) -> std::result::Result<TxnReply, MetaError> {
+        self.inner.transaction(txn).await
+    }
+
+    async fn watch(
+        &self,
+        _prefix: &str,
+        _from_seq: Option<u64>,
+    ) -> std::result::Result<WatchStream, MetaError> {
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        *self.watcher.lock().unwrap() = Some(tx);
+        Ok(rx.boxed())
+    }
+}
+
 fn current_seconds_time() -> u64 {
     let now = std::time::SystemTime::now();
     now.duration_since(UNIX_EPOCH)
@@ -150,6 +371,7 @@ fn create_test_node_info() -> NodeInfo {
         cpu_nums: 0,
         version: 0,
         flight_address: String::from("ip:port"),
+        capabilities: vec![],
     }
 }
 
@@ -0,0 +1,99 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::tokio;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_management::*;
+use common_meta_api::KVApi;
+use common_meta_embedded::MetaEmbedded;
+use common_meta_types::MatchSeq;
+use common_meta_types::Operation;
+use common_meta_types::UpsertKVAction;
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Item {
+    name: String,
+}
+
+// Two good values and a corrupt one under `p/`, and a value out of the prefix.
+async fn prepare() -> Result<MetaEmbedded> {
+    let kv = MetaEmbedded::new_temp().await?;
+    for (key, value) in [
+        ("p/a", r#"{"name":"a"}"#),
+        ("p/b", "not json"),
+        ("p/c", r#"{"name":"c"}"#),
+        ("q/d", r#"{"name":"d"}"#),
+    ] {
+        kv.upsert_kv(UpsertKVAction::new(
+            key,
+            MatchSeq::Any,
+            Operation::Update(value.as_bytes().to_vec()),
+            None,
+        ))
+        .await?;
+    }
+    Ok(kv)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_list_typed_partial_failure() -> Result<()> {
+    let kv = prepare().await?;
+
+    let listed = list_typed::<Item>(&kv, "p/").await?;
+
+    assert_eq!(
+        vec![
+            ("p/a".to_string(), "a".to_string()),
+            ("p/c".to_string(), "c".to_string()),
+        ],
+        listed
+            .items
+            .iter()
+            .map(|(k, v)| (k.clone(), v.data.name.clone()))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(listed.invalid.len(), 1);
+    assert_eq!(listed.invalid[0].0, "p/b");
+
+    let values = listed.into_values_warn_invalid("item");
+    assert_eq!(
+        vec!["a", "c"],
+        values
+            .iter()
+            .map(|v| v.data.name.as_str())
+            .collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_list_decoded_error_code() -> Result<()> {
+    let kv = prepare().await?;
+
+    let listed = list_decoded(&kv, "p/", |data| {
+        serde_json::from_slice::<Item>(data)
+            .map_err(|e| ErrorCode::IllegalUserInfoFormat(e.to_string()))
+    })
+    .await?;
+
+    assert_eq!(listed.items.len(), 2);
+    assert_eq!(listed.invalid.len(), 1);
+    assert_eq!(
+        listed.invalid[0].1.code(),
+        ErrorCode::IllegalUserInfoFormat("").code()
+    );
+    Ok(())
+}
@@ -0,0 +1,292 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use async_trait::async_trait;
+use common_base::tokio;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_management::*;
+use common_meta_api::KVApi;
+use common_meta_api::WatchStream;
+use common_meta_embedded::MetaEmbedded;
+use common_meta_types::DeleteKVReply;
+use common_meta_types::GetKVActionReply;
+use common_meta_types::GetKVMetaReply;
+use common_meta_types::IncrKVReply;
+use common_meta_types::MGetKVActionReply;
+use common_meta_types::MatchSeq;
+use common_meta_types::MetaError;
+use common_meta_types::PrefixListPage;
+use common_meta_types::PrefixListReply;
+use common_meta_types::TxnReply;
+use common_meta_types::TxnRequest;
+use common_meta_types::UpsertKVAction;
+use common_meta_types::UpsertKVActionReply;
+use common_meta_types::UpsertKVBatchReply;
+
+// A KVApi whose writes fail while it is unreachable, as if the meta service were down.
+struct UnreachableKV {
+    inner: Arc<MetaEmbedded>,
+    unreachable: AtomicBool,
+}
+
+impl UnreachableKV {
+    fn check(&self) -> std::result::Result<(), MetaError> {
+        match self.unreachable.load(Ordering::SeqCst) {
+            true => Err(MetaError::MetaServiceError("unreachable".to_string())),
+            false => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl KVApi for UnreachableKV {
+    async fn upsert_kv(
+        &self,
+        act: UpsertKVAction,
+    ) -> std::result::Result<UpsertKVActionReply, MetaError> {
+        self.check()?;
+        self.inner.upsert_kv(act).await
+    }
+
+    async fn upsert_kv_batch(
+        &self,
+        actions: Vec<UpsertKVAction>,
+    ) -> std::result::Result<UpsertKVBatchReply, MetaError> {
+        self.check()?;
+        self.inner.upsert_kv_batch(actions).await
+    }
+
+    async fn get_kv(&self, key: &str) -> std::result::Result<GetKVActionReply, MetaError> {
+        self.check()?;
+        self.inner.get_kv(key).await
+    }
+
+    async fn get_kv_meta(&self, key: &str) -> std::result::Result<GetKVMetaReply, MetaError> {
+        self.check()?;
+        self.inner.get_kv_meta(key).await
+    }
+
+    async fn delete_kv(
+        &self,
+        key: &str,
+        seq: MatchSeq,
+    ) -> std::result::Result<DeleteKVReply, MetaError> {
+        self.check()?;
+        self.inner.delete_kv(key, seq).await
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> std::result::Result<u64, MetaError> {
+        self.check()?;
+        self.inner.delete_prefix(prefix).await
+    }
+
+    async fn incr_kv(
+        &self,
+        key: &str,
+        delta: i64,
+        seq: MatchSeq,
+    ) -> std::result::Result<IncrKVReply, MetaError> {
+        self.check()?;
+        self.inner.incr_kv(key, delta, seq).await
+    }
+
+    async fn mget_kv(&self, keys: &[String]) -> std::result::Result<MGetKVActionReply, MetaError> {
+        self.check()?;
+        self.inner.mget_kv(keys).await
+    }
+
+    async fn prefix_list_kv(
+        &self,
+        prefix: &str,
+    ) -> std::result::Result<PrefixListReply, MetaError> {
+        self.check()?;
+        self.inner.prefix_list_kv(prefix).await
+    }
+
+    async fn prefix_list_kv_paged(
+        &self,
+        prefix: &str,
+        after_key: Option<String>,
+        limit: usize,
+        keys_only: bool,
+    ) -> std::result::Result<PrefixListPage, MetaError> {
+        self.check()?;
+        self.inner
+            .prefix_list_kv_paged(prefix, after_key, limit, keys_only)
+            .await
+    }
+
+    async fn transaction(&self, txn: TxnRequest) -> std::result::Result<TxnReply, MetaError> {
+        self.check()?;
+        self.inner.transaction(txn).await
+    }
+
+    async fn watch(
+        &self,
+        prefix: &str,
+        from_seq: Option<u64>,
+    ) -> std::result::Result<WatchStream, MetaError> {
+        self.check()?;
+        self.inner.watch(prefix, from_seq).await
+    }
+}
+
+fn acquired(res: LeaseAcquire) -> LeaseHandle {
+    match res {
+        LeaseAcquire::Acquired(handle) => handle,
+        LeaseAcquire::Held(holder) => panic!("expect acquired, but held by {:?}", holder),
+    }
+}
+
+fn held(res: LeaseAcquire) -> LeaseInfo {
+    match res {
+        LeaseAcquire::Acquired(handle) => panic!("expect held, but acquired {:?}", handle),
+        LeaseAcquire::Held(holder) => holder,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_lease_contention() -> Result<()> {
+    let kv_api = Arc::new(MetaEmbedded::new_temp().await?);
+    let mgr = LeaseMgr::create(kv_api, "admin")?;
+    let ttl = Duration::from_secs(10);
+
+    // Only one of the concurrent contenders wins.
+    let (a, b) = tokio::join!(
+        mgr.acquire("compact", "n1", ttl),
+        mgr.acquire("compact", "n2", ttl)
+    );
+    let (handle, holder) = match (a?, b?) {
+        (LeaseAcquire::Acquired(handle), LeaseAcquire::Held(holder)) => (handle, holder),
+        (LeaseAcquire::Held(holder), LeaseAcquire::Acquired(handle)) => (handle, holder),
+        res => panic!("expect exactly one acquired, got: {:?}", res),
+    };
+    assert_eq!(holder, handle.info);
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    assert!(holder.expire_at >= now + 9);
+
+    // Other names are not affected.
+    acquired(mgr.acquire("purge", "n2", ttl).await?);
+
+    // A renewed lease gets a new seq, the stale handle can not renew or release it.
+    let renewed = mgr.renew(&handle, ttl).await?;
+    assert!(renewed.seq > handle.seq);
+    let res = mgr.renew(&handle, ttl).await;
+    assert_eq!(res.unwrap_err().code(), ErrorCode::LeaseNotHeld("").code());
+    assert!(!mgr.release(&handle).await?);
+    held(mgr.acquire("compact", "n3", ttl).await?);
+
+    // Once released, it is acquired by another.
+    assert!(mgr.release(&renewed).await?);
+    let handle = acquired(mgr.acquire("compact", "n3", ttl).await?);
+    assert_eq!("n3", handle.info.owner_id);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_lease_expire_after_holder_crash() -> Result<()> {
+    let kv_api = Arc::new(MetaEmbedded::new_temp().await?);
+    let mgr = LeaseMgr::create(kv_api, "admin")?;
+    let ttl = Duration::from_secs(2);
+
+    // The holder crashes: it never renews or releases the lease.
+    let handle = acquired(mgr.acquire("bootstrap", "n1", ttl).await?);
+    assert_eq!(
+        "n1",
+        held(mgr.acquire("bootstrap", "n2", ttl).await?).owner_id
+    );
+
+    tokio::time::sleep(Duration::from_secs(4)).await;
+
+    acquired(mgr.acquire("bootstrap", "n2", ttl).await?);
+    let res = mgr.renew(&handle, ttl).await;
+    assert_eq!(res.unwrap_err().code(), ErrorCode::LeaseNotHeld("").code());
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_lease_guard() -> Result<()> {
+    let kv_api = Arc::new(MetaEmbedded::new_temp().await?);
+    let mgr = LeaseMgr::create(kv_api, "admin")?;
+    let ttl = Duration::from_secs(3);
+
+    let handle = acquired(mgr.acquire("compact", "n1", ttl).await?);
+    let guard = LeaseGuard::start(mgr.clone(), handle, ttl);
+
+    // Renewed in the background, it outlives its ttl.
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    assert!(guard.is_held());
+    assert_eq!(
+        "n1",
+        held(mgr.acquire("compact", "n2", ttl).await?).owner_id
+    );
+
+    // Released on drop.
+    drop(guard);
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    acquired(mgr.acquire("compact", "n2", ttl).await?);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_lease_guard_meta_unreachable() -> Result<()> {
+    let inner = Arc::new(MetaEmbedded::new_temp().await?);
+    let kv_api = Arc::new(UnreachableKV {
+        inner: inner.clone(),
+        unreachable: AtomicBool::new(false),
+    });
+    let mgr = LeaseMgr::create(kv_api.clone(), "admin")?;
+    let other = LeaseMgr::create(inner, "admin")?;
+    let ttl = Duration::from_secs(3);
+
+    let handle = acquired(mgr.acquire("compact", "n1", ttl).await?);
+    let guard = LeaseGuard::start(mgr.clone(), handle, ttl);
+    assert!(guard.is_held());
+
+    // The renews fail, the lease is taken as lost before it may expire.
+    kv_api.unreachable.store(true, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(2600)).await;
+    assert!(!guard.is_held());
+
+    // Another node acquires it after it expires, the guard never holds it again.
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    acquired(other.acquire("compact", "n2", ttl).await?);
+    kv_api.unreachable.store(false, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert!(!guard.is_held());
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_lease_mgr_empty_tenant() -> Result<()> {
+    let kv_api = Arc::new(MetaEmbedded::new_temp().await?);
+    let res = LeaseMgr::create(kv_api, "");
+    assert_eq!(
+        res.err().unwrap().code(),
+        ErrorCode::TenantIsEmpty("").code()
+    );
+    Ok(())
+}
@@ -13,6 +13,11 @@
 // limitations under the License.
 
 mod cluster;
+mod kv_list;
+mod lease;
+mod network_policy;
+mod role;
+mod session_count;
 mod setting;
 mod stage;
 mod udf;
@@ -0,0 +1,162 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_base::tokio;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_management::*;
+use common_meta_api::KVApi;
+use common_meta_embedded::MetaEmbedded;
+use common_meta_types::AuthInfo;
+use common_meta_types::NetworkPolicy;
+use common_meta_types::SeqV;
+use common_meta_types::UserInfo;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_add_network_policy() -> Result<()> {
+    let (kv_api, policy_api) = new_network_policy_api().await?;
+
+    let policy = create_test_network_policy();
+    policy_api.add_network_policy(policy.clone()).await?;
+    let value = kv_api
+        .get_kv("__fd_network_policies/admin/mypolicy")
+        .await?;
+
+    match value {
+        Some(SeqV {
+            seq: 1,
+            meta: _,
+            data: value,
+        }) => {
+            assert_eq!(value, serde_json::to_vec(&policy)?);
+        }
+        catch => panic!("GetKVActionReply{:?}", catch),
+    }
+
+    let res = policy_api.add_network_policy(policy.clone()).await;
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::NetworkPolicyAlreadyExists("").code()
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_get_update_network_policy() -> Result<()> {
+    let (_, policy_api) = new_network_policy_api().await?;
+
+    let res = policy_api.get_network_policy("mypolicy", None).await;
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::UnknownNetworkPolicy("").code()
+    );
+
+    let mut policy = create_test_network_policy();
+    let seq = policy_api.add_network_policy(policy.clone()).await?;
+    assert_eq!(
+        policy_api
+            .get_network_policy("mypolicy", Some(seq))
+            .await?
+            .data,
+        policy
+    );
+
+    policy.blocked_ip_list = vec!["192.168.1.0/24".parse()?];
+    let res = policy_api
+        .update_network_policy(policy.clone(), Some(seq + 1))
+        .await;
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::UnknownNetworkPolicy("").code()
+    );
+    policy_api
+        .update_network_policy(policy.clone(), Some(seq))
+        .await?;
+    assert_eq!(policy_api.get_network_policies().await?, vec![policy]);
+
+    let unknown = NetworkPolicy {
+        name: "unknown".to_string(),
+        ..Default::default()
+    };
+    let res = policy_api.update_network_policy(unknown, None).await;
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::UnknownNetworkPolicy("").code()
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_drop_network_policy_used_by_user() -> Result<()> {
+    let (kv_api, policy_api) = new_network_policy_api().await?;
+    let user_api = UserMgr::create(kv_api.clone(), "admin")?;
+
+    let policy = create_test_network_policy();
+    policy_api.add_network_policy(policy.clone()).await?;
+
+    let user = UserInfo::new("u1".to_string(), "%".to_string(), AuthInfo::None);
+    user_api.add_user(user.clone(), false).await?;
+    user_api
+        .set_user_network_policy(user.identity(), Some(policy.name.clone()), None)
+        .await?;
+    let got = user_api.get_user(user.identity(), None).await?;
+    assert_eq!(got.data.network_policy, Some(policy.name.clone()));
+
+    let res = policy_api.drop_network_policy(&policy.name, None).await;
+    let err = res.unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NetworkPolicyIsUsedByUser("").code());
+    assert!(err.message().contains("'u1'@'%'"), "{}", err.message());
+    assert_eq!(policy_api.get_network_policies().await?, vec![
+        policy.clone()
+    ]);
+
+    // The users of the other tenants do not count.
+    let other_user_api = UserMgr::create(kv_api.clone(), "other")?;
+    other_user_api.add_user(user.clone(), false).await?;
+    other_user_api
+        .set_user_network_policy(user.identity(), Some(policy.name.clone()), None)
+        .await?;
+
+    user_api
+        .set_user_network_policy(user.identity(), None, None)
+        .await?;
+    policy_api.drop_network_policy(&policy.name, None).await?;
+    assert_eq!(policy_api.get_network_policies().await?, vec![]);
+
+    let res = policy_api.drop_network_policy(&policy.name, None).await;
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::UnknownNetworkPolicy("").code()
+    );
+
+    Ok(())
+}
+
+fn create_test_network_policy() -> NetworkPolicy {
+    NetworkPolicy {
+        name: "mypolicy".to_string(),
+        allowed_ip_list: vec!["192.168.0.0/16".parse().unwrap()],
+        ..Default::default()
+    }
+}
+
+async fn new_network_policy_api() -> Result<(Arc<MetaEmbedded>, NetworkPolicyMgr)> {
+    let test_api = Arc::new(MetaEmbedded::new_temp().await?);
+    let mgr = NetworkPolicyMgr::create(test_api.clone(), "admin")?;
+    Ok((test_api, mgr))
+}
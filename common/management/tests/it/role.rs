@@ -0,0 +1,291 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common_base::tokio;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_management::*;
+use common_meta_api::KVApi;
+use common_meta_api::WatchStream;
+use common_meta_embedded::MetaEmbedded;
+use common_meta_types::DeleteKVReply;
+use common_meta_types::GetKVActionReply;
+use common_meta_types::GetKVMetaReply;
+use common_meta_types::GrantObject;
+use common_meta_types::IncrKVReply;
+use common_meta_types::MGetKVActionReply;
+use common_meta_types::MatchSeq;
+use common_meta_types::MetaError;
+use common_meta_types::Operation;
+use common_meta_types::PrefixListPage;
+use common_meta_types::PrefixListReply;
+use common_meta_types::RoleInfo;
+use common_meta_types::TxnReply;
+use common_meta_types::TxnRequest;
+use common_meta_types::UpsertKVAction;
+use common_meta_types::UpsertKVActionReply;
+use common_meta_types::UpsertKVBatchReply;
+use common_meta_types::UserPrivilegeSet;
+use common_meta_types::UserPrivilegeType;
+
+// A KVApi which updates the key of an upsert by itself, before the first `conflicts` upserts,
+// as if the key is updated concurrently.
+struct ConflictingKV {
+    inner: MetaEmbedded,
+    conflicts: AtomicUsize,
+}
+
+#[async_trait]
+impl KVApi for ConflictingKV {
+    async fn upsert_kv(&self, act: UpsertKVAction) -> Result<UpsertKVActionReply, MetaError> {
+        let conflict = self
+            .conflicts
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |v| v.checked_sub(1))
+            .is_ok();
+        if conflict {
+            if let Some(current) = self.inner.get_kv(&act.key).await? {
+                self.inner
+                    .upsert_kv(UpsertKVAction::new(
+                        &act.key,
+                        MatchSeq::Any,
+                        Operation::Update(current.data),
+                        None,
+                    ))
+                    .await?;
+            }
+        }
+        self.inner.upsert_kv(act).await
+    }
+
+    async fn upsert_kv_batch(
+        &self,
+        actions: Vec<UpsertKVAction>,
+    ) -> Result<UpsertKVBatchReply, MetaError> {
+        self.inner.upsert_kv_batch(actions).await
+    }
+
+    async fn get_kv(&self, key: &str) -> Result<GetKVActionReply, MetaError> {
+        self.inner.get_kv(key).await
+    }
+
+    async fn get_kv_meta(&self, key: &str) -> Result<GetKVMetaReply, MetaError> {
+        self.inner.get_kv_meta(key).await
+    }
+
+    async fn delete_kv(&self, key: &str, seq: MatchSeq) -> Result<DeleteKVReply, MetaError> {
+        self.inner.delete_kv(key, seq).await
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64, MetaError> {
+        self.inner.delete_prefix(prefix).await
+    }
+
+    async fn incr_kv(
+        &self,
+        key: &str,
+        delta: i64,
+        seq: MatchSeq,
+    ) -> Result<IncrKVReply, MetaError> {
+        self.inner.incr_kv(key, delta, seq).await
+    }
+
+    async fn mget_kv(&self, keys: &[String]) -> Result<MGetKVActionReply, MetaError> {
+        self.inner.mget_kv(keys).await
+    }
+
+    async fn prefix_list_kv(&self, prefix: &str) -> Result<PrefixListReply, MetaError> {
+        self.inner.prefix_list_kv(prefix).await
+    }
+
+    async fn prefix_list_kv_paged(
+        &self,
+        prefix: &str,
+        after_key: Option<String>,
+        limit: usize,
+        keys_only: bool,
+    ) -> Result<PrefixListPage, MetaError> {
+        self.inner
+            .prefix_list_kv_paged(prefix, after_key, limit, keys_only)
+            .await
+    }
+
+    async fn transaction(&self, txn: TxnRequest) -> Result<TxnReply, MetaError> {
+        self.inner.transaction(txn).await
+    }
+
+    async fn watch(&self, prefix: &str, from_seq: Option<u64>) -> Result<WatchStream, MetaError> {
+        self.inner.watch(prefix, from_seq).await
+    }
+}
+
+async fn new_role_api(conflicts: usize) -> Result<(Arc<ConflictingKV>, RoleMgr)> {
+    let kv_api = Arc::new(ConflictingKV {
+        inner: MetaEmbedded::new_temp().await?,
+        conflicts: AtomicUsize::new(0),
+    });
+    let mgr = RoleMgr::create(kv_api.clone(), "admin")?;
+    mgr.add_role(RoleInfo::new("role1".to_string())).await?;
+    mgr.add_role(RoleInfo::new("role2".to_string())).await?;
+    kv_api.conflicts.store(conflicts, Ordering::SeqCst);
+    Ok((kv_api, mgr))
+}
+
+fn select_privilege() -> UserPrivilegeSet {
+    let mut privileges = UserPrivilegeSet::empty();
+    privileges.set_privilege(UserPrivilegeType::Select);
+    privileges
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_grant_revoke_role_privileges() -> Result<()> {
+    let (_, role_api) = new_role_api(0).await?;
+    let object = GrantObject::Database("db1".to_string());
+
+    role_api
+        .grant_privileges(
+            "role1".to_string(),
+            object.clone(),
+            select_privilege(),
+            None,
+        )
+        .await?;
+    let role = role_api.get_role("role1".to_string(), None).await?.data;
+    assert!(role
+        .grants
+        .verify_privilege(&object, UserPrivilegeType::Select));
+
+    role_api
+        .revoke_privileges(
+            "role1".to_string(),
+            object.clone(),
+            select_privilege(),
+            None,
+        )
+        .await?;
+    let role = role_api.get_role("role1".to_string(), None).await?.data;
+    assert!(!role
+        .grants
+        .verify_privilege(&object, UserPrivilegeType::Select));
+
+    role_api
+        .grant_role("role1".to_string(), "role2".to_string(), None)
+        .await?;
+    let role = role_api.get_role("role1".to_string(), None).await?.data;
+    assert_eq!(vec!["role2".to_string()], role.grants.roles());
+
+    role_api
+        .revoke_role("role1".to_string(), "role2".to_string(), None)
+        .await?;
+    let role = role_api.get_role("role1".to_string(), None).await?.data;
+    assert!(role.grants.roles().is_empty());
+
+    // unknown role
+    let res = role_api
+        .grant_role("role3".to_string(), "role2".to_string(), None)
+        .await;
+    assert_eq!(res.unwrap_err().code(), ErrorCode::UnknownRole("").code());
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_role_concurrent_update() -> Result<()> {
+    // without a seq, the update conflicting with the concurrent ones is retried
+    let (kv_api, role_api) = new_role_api(2).await?;
+    let seq = role_api
+        .grant_privileges(
+            "role1".to_string(),
+            GrantObject::Global,
+            select_privilege(),
+            None,
+        )
+        .await?;
+    assert_eq!(0, kv_api.conflicts.load(Ordering::SeqCst));
+    let role = role_api.get_role("role1".to_string(), None).await?;
+    assert_eq!(seq, Some(role.seq));
+    assert!(role
+        .data
+        .grants
+        .verify_privilege(&GrantObject::Global, UserPrivilegeType::Select));
+
+    // with a seq, the update conflicting with a concurrent one fails
+    let (_, role_api) = new_role_api(1).await?;
+    let seq = role_api.get_role("role1".to_string(), None).await?.seq;
+    let res = role_api
+        .grant_role("role1".to_string(), "role2".to_string(), Some(seq))
+        .await;
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::KVSeqMismatched("").code()
+    );
+    let role = role_api.get_role("role1".to_string(), None).await?.data;
+    assert!(role.grants.roles().is_empty());
+
+    // the updates conflicting too many times fail
+    let (_, role_api) = new_role_api(usize::MAX).await?;
+    let res = role_api
+        .revoke_role("role1".to_string(), "role2".to_string(), None)
+        .await;
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::OCCRetryFailure("").code()
+    );
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_legacy_role_keys() -> Result<()> {
+    let kv_api = Arc::new(MetaEmbedded::new_temp().await?);
+    // the earlier versions escape neither the tenant nor the role
+    let legacy_key = "__fd_roles/test-tenant/role-a";
+    let value = serde_json::to_vec(&RoleInfo::new("role-a".to_string()))?;
+    kv_api
+        .upsert_kv(UpsertKVAction::new(
+            legacy_key,
+            MatchSeq::Exact(0),
+            Operation::Update(value),
+            None,
+        ))
+        .await?;
+
+    let role_api = RoleMgr::create(kv_api.clone(), "test-tenant")?;
+    role_api.get_role("role-a".to_string(), None).await?;
+    let roles = role_api.get_roles().await?;
+    assert_eq!(roles.len(), 1);
+    let roles = role_api.get_roles_by_names(&["role-a".to_string()]).await?;
+    assert!(roles.contains_key("role-a"));
+    let res = role_api.add_role(RoleInfo::new("role-a".to_string())).await;
+    assert!(res.is_err());
+
+    // the role is moved to the escaped key by a change
+    role_api
+        .grant_role("role-a".to_string(), "role-b".to_string(), None)
+        .await?;
+    assert!(kv_api.get_kv(legacy_key).await?.is_none());
+    assert!(kv_api
+        .get_kv("__fd_roles/test%2dtenant/role%2da")
+        .await?
+        .is_some());
+    let role = role_api.get_role("role-a".to_string(), None).await?.data;
+    assert_eq!(vec!["role-b".to_string()], role.grants.roles());
+    assert_eq!(role_api.get_roles().await?.len(), 1);
+
+    role_api.drop_role("role-a".to_string(), None).await?;
+    assert!(role_api.get_roles().await?.is_empty());
+    Ok(())
+}
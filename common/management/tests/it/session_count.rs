@@ -0,0 +1,112 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_base::tokio;
+use common_exception::Result;
+use common_management::*;
+use common_meta_api::KVApi;
+use common_meta_embedded::MetaEmbedded;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_increment_decrement_user_sessions() -> Result<()> {
+    let kv_api = Arc::new(MetaEmbedded::new_temp().await?);
+    let node1 = SessionCountMgr::create(kv_api.clone(), "admin", "node1")?;
+    let node2 = SessionCountMgr::create(kv_api.clone(), "admin", "node2")?;
+
+    assert_eq!(node1.get_user_sessions("u1").await?, 0);
+    assert_eq!(node1.increment_user_sessions("u1").await?, 1);
+    assert_eq!(node1.increment_user_sessions("u1").await?, 2);
+    assert_eq!(node2.increment_user_sessions("u1").await?, 3);
+    assert_eq!(node2.increment_user_sessions("u2").await?, 1);
+    assert_eq!(node2.get_user_sessions("u1").await?, 3);
+
+    // Only the sessions of this node are decremented.
+    assert_eq!(node2.decrement_user_sessions("u1").await?, 2);
+    assert_eq!(node2.decrement_user_sessions("u1").await?, 2);
+    assert_eq!(node1.decrement_user_sessions("u1").await?, 1);
+    assert_eq!(node1.decrement_user_sessions("u1").await?, 0);
+
+    // The counts of no session are removed.
+    assert!(kv_api
+        .get_kv("__fd_user_sessions/admin/u1")
+        .await?
+        .is_none());
+    assert!(kv_api
+        .get_kv("__fd_user_sessions/admin/u2")
+        .await?
+        .is_some());
+
+    // Per tenant.
+    let other = SessionCountMgr::create(kv_api.clone(), "other", "node1")?;
+    assert_eq!(other.get_user_sessions("u2").await?, 0);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_increment_user_sessions() -> Result<()> {
+    let kv_api = Arc::new(MetaEmbedded::new_temp().await?);
+
+    let tasks = 8;
+    let increments = 10;
+    let mut handles = vec![];
+    for i in 0..tasks {
+        let mgr = SessionCountMgr::create(kv_api.clone(), "admin", &format!("node{}", i % 3))?;
+        handles.push(tokio::spawn(async move {
+            for _ in 0..increments {
+                mgr.increment_user_sessions("u1").await?;
+            }
+            Result::Ok(())
+        }));
+    }
+    for handle in handles {
+        handle.await.unwrap()?;
+    }
+
+    let mgr = SessionCountMgr::create(kv_api.clone(), "admin", "node0")?;
+    assert_eq!(mgr.get_user_sessions("u1").await?, tasks * increments);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_expire_dead_nodes() -> Result<()> {
+    let kv_api = Arc::new(MetaEmbedded::new_temp().await?);
+    let alive = SessionCountMgr::create(kv_api.clone(), "admin", "alive")?;
+    let dead = SessionCountMgr::create(kv_api.clone(), "admin", "dead")?;
+
+    alive.increment_user_sessions("u1").await?;
+    dead.increment_user_sessions("u1").await?;
+    dead.increment_user_sessions("u1").await?;
+    dead.increment_user_sessions("u2").await?;
+
+    let lease = Duration::from_millis(500);
+    assert_eq!(alive.expire_dead_nodes(lease).await?, 0);
+    assert_eq!(alive.get_user_sessions("u1").await?, 3);
+
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+    alive.heartbeat().await?;
+    assert_eq!(alive.expire_dead_nodes(lease).await?, 3);
+    assert_eq!(alive.get_user_sessions("u1").await?, 1);
+    assert_eq!(alive.get_user_sessions("u2").await?, 0);
+    assert!(kv_api
+        .get_kv("__fd_user_sessions/admin/u2")
+        .await?
+        .is_none());
+
+    // The sessions of the expired node are not counted again.
+    assert_eq!(dead.decrement_user_sessions("u1").await?, 1);
+    Ok(())
+}
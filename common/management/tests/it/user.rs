@@ -20,17 +20,25 @@ use common_base::tokio;
 use common_exception::ErrorCode;
 use common_management::*;
 use common_meta_api::KVApi;
+use common_meta_api::WatchStream;
 use common_meta_types::AuthInfo;
+use common_meta_types::DeleteKVReply;
 use common_meta_types::GetKVActionReply;
+use common_meta_types::GetKVMetaReply;
+use common_meta_types::IncrKVReply;
 use common_meta_types::MGetKVActionReply;
 use common_meta_types::MatchSeq;
 use common_meta_types::MetaError;
 use common_meta_types::Operation;
 use common_meta_types::PasswordHashMethod;
+use common_meta_types::PrefixListPage;
 use common_meta_types::PrefixListReply;
 use common_meta_types::SeqV;
+use common_meta_types::TxnReply;
+use common_meta_types::TxnRequest;
 use common_meta_types::UpsertKVAction;
 use common_meta_types::UpsertKVActionReply;
+use common_meta_types::UpsertKVBatchReply;
 use common_meta_types::UserIdentity;
 use mockall::predicate::*;
 use mockall::*;
@@ -45,14 +53,44 @@ mock! {
             act: UpsertKVAction,
         ) -> Result<UpsertKVActionReply, MetaError>;
 
+        async fn upsert_kv_batch(
+            &self,
+            actions: Vec<UpsertKVAction>,
+        ) -> Result<UpsertKVBatchReply, MetaError>;
+
         async fn get_kv(&self, key: &str) -> Result<GetKVActionReply,MetaError>;
 
+        async fn get_kv_meta(&self, key: &str) -> Result<GetKVMetaReply,MetaError>;
+
+        async fn delete_kv(&self, key: &str, seq: MatchSeq) -> Result<DeleteKVReply, MetaError>;
+
+        async fn delete_prefix(&self, prefix: &str) -> Result<u64, MetaError>;
+
+        async fn incr_kv(
+            &self,
+            key: &str,
+            delta: i64,
+            seq: MatchSeq,
+        ) -> Result<IncrKVReply, MetaError>;
+
         async fn mget_kv(
             &self,
             key: &[String],
         ) -> Result<MGetKVActionReply,MetaError>;
 
         async fn prefix_list_kv(&self, prefix: &str) -> Result<PrefixListReply, MetaError>;
+
+        async fn prefix_list_kv_paged(
+            &self,
+            prefix: &str,
+            after_key: Option<String>,
+            limit: usize,
+            keys_only: bool,
+        ) -> Result<PrefixListPage, MetaError>;
+
+        async fn transaction(&self, txn: TxnRequest) -> Result<TxnReply, MetaError>;
+
+        async fn watch(&self, prefix: &str, from_seq: Option<u64>) -> Result<WatchStream, MetaError>;
         }
 }
 
@@ -83,7 +121,7 @@ mod add {
             default_test_auth_info(),
         );
         let v = serde_json::to_vec(&user_info)?;
-        let value = Operation::Update(serde_json::to_vec(&user_info)?);
+        let value = Operation::Update(user_info.to_versioned_json()?);
 
         let test_key = format!(
             "__fd_users/tenant1/{}",
@@ -106,7 +144,7 @@ mod add {
                 .return_once(|_u| Ok(UpsertKVActionReply::new(None, Some(SeqV::new(1, v)))));
             let api = Arc::new(api);
             let user_mgr = UserMgr::create(api, "tenant1")?;
-            let res = user_mgr.add_user(user_info);
+            let res = user_mgr.add_user(user_info, false);
 
             assert!(res.await.is_ok());
         }
@@ -139,7 +177,7 @@ mod add {
                 default_test_auth_info(),
             );
 
-            let res = user_mgr.add_user(user_info).await;
+            let res = user_mgr.add_user(user_info, false).await;
 
             assert_eq!(
                 res.unwrap_err().code(),
@@ -147,6 +185,40 @@ mod add {
             );
         }
 
+        // already exists, if not exists
+        {
+            let test_key = test_key.clone();
+            let mut api = MockKV::new();
+            api.expect_upsert_kv()
+                .with(predicate::eq(UpsertKVAction::new(
+                    &test_key,
+                    test_seq,
+                    value.clone(),
+                    None,
+                )))
+                .times(1)
+                .returning(|_u| {
+                    Ok(UpsertKVActionReply::new(
+                        Some(SeqV::new(3, vec![])),
+                        Some(SeqV::new(3, vec![])),
+                    ))
+                });
+
+            let api = Arc::new(api);
+            let user_mgr = UserMgr::create(api, "tenant1")?;
+
+            let user_info = UserInfo::new(
+                test_user_name.to_string(),
+                test_hostname.to_string(),
+                default_test_auth_info(),
+            );
+
+            let res = user_mgr.add_user(user_info, true).await;
+
+            // the seq of the existing user
+            assert_eq!(res?, 3);
+        }
+
         // unknown exception
         {
             let mut api = MockKV::new();
@@ -169,7 +241,7 @@ mod add {
                 default_test_auth_info(),
             );
 
-            let res = user_mgr.add_user(user_info).await;
+            let res = user_mgr.add_user(user_info, false).await;
 
             assert_eq!(
                 res.unwrap_err().code(),
@@ -360,7 +432,7 @@ mod get_users {
         let (res, user_infos) = prepare()?;
         let mut kv = MockKV::new();
         {
-            let k = "__fd_users/tenant1";
+            let k = "__fd_users/tenant1/";
             kv.expect_prefix_list_kv()
                 .with(predicate::eq(k))
                 .times(1)
@@ -377,7 +449,8 @@ mod get_users {
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
     async fn test_get_all_users_invalid_user_info_encoding() -> common_exception::Result<()> {
-        let (mut res, _user_infos) = prepare()?;
+        // A corrupt user is skipped, it does not hide the others.
+        let (mut res, user_infos) = prepare()?;
         res.insert(
             8,
             (
@@ -388,7 +461,7 @@ mod get_users {
 
         let mut kv = MockKV::new();
         {
-            let k = "__fd_users/tenant1";
+            let k = "__fd_users/tenant1/";
             kv.expect_prefix_list_kv()
                 .with(predicate::eq(k))
                 .times(1)
@@ -398,16 +471,14 @@ mod get_users {
         let kv = Arc::new(kv);
         let user_mgr = UserMgr::create(kv, "tenant1")?;
         let res = user_mgr.get_users();
-        assert_eq!(
-            res.await.unwrap_err().code(),
-            ErrorCode::IllegalUserInfoFormat("").code()
-        );
+        assert_eq!(res.await?, user_infos);
 
         Ok(())
     }
 }
 
 mod drop {
+    use common_meta_types::UserInfo;
 
     use super::*;
 
@@ -420,6 +491,59 @@ mod drop {
             "__fd_users/tenant1/{}",
             escape_for_key(&format_user_key(test_user, test_hostname))?
         );
+        let user_info = UserInfo::new(
+            test_user.to_string(),
+            test_hostname.to_string(),
+            AuthInfo::None,
+        );
+        let value = user_info.to_versioned_json()?;
+        let get_key = test_key.clone();
+        kv.expect_get_kv()
+            .with(predicate::function(move |v| v == get_key.as_str()))
+            .times(1)
+            .return_once(move |_k| Ok(Some(SeqV::new(1, value))));
+        kv.expect_upsert_kv()
+            .with(predicate::eq(UpsertKVAction::new(
+                &test_key,
+                MatchSeq::Any,
+                Operation::Delete,
+                None,
+            )))
+            .times(1)
+            .returning(|_k| Ok(UpsertKVActionReply::new(Some(SeqV::new(1, vec![])), None)));
+        let kv = Arc::new(kv);
+        let user_mgr = UserMgr::create(kv, "tenant1")?;
+        let res = user_mgr.drop_user(
+            UserIdentity::new(test_user, test_hostname),
+            None,
+            false,
+            false,
+        );
+        assert!(res.await.is_ok());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_drop_user_normal_case_if_exists() -> common_exception::Result<()> {
+        let mut kv = MockKV::new();
+        let test_user = "test";
+        let test_hostname = "localhost";
+        let test_key = format!(
+            "__fd_users/tenant1/{}",
+            escape_for_key(&format_user_key(test_user, test_hostname))?
+        );
+        let user_info = UserInfo::new(
+            test_user.to_string(),
+            test_hostname.to_string(),
+            AuthInfo::None,
+        );
+        let value = user_info.to_versioned_json()?;
+        let get_key = test_key.clone();
+        kv.expect_get_kv()
+            .with(predicate::function(move |v| v == get_key.as_str()))
+            .times(1)
+            .return_once(move |_k| Ok(Some(SeqV::new(1, value))));
         kv.expect_upsert_kv()
             .with(predicate::eq(UpsertKVAction::new(
                 &test_key,
@@ -431,7 +555,12 @@ mod drop {
             .returning(|_k| Ok(UpsertKVActionReply::new(Some(SeqV::new(1, vec![])), None)));
         let kv = Arc::new(kv);
         let user_mgr = UserMgr::create(kv, "tenant1")?;
-        let res = user_mgr.drop_user(UserIdentity::new(test_user, test_hostname), None);
+        let res = user_mgr.drop_user(
+            UserIdentity::new(test_user, test_hostname),
+            None,
+            true,
+            false,
+        );
         assert!(res.await.is_ok());
 
         Ok(())
@@ -446,6 +575,17 @@ mod drop {
             "__fd_users/tenant1/{}",
             escape_for_key(&format_user_key(test_user, test_hostname))?
         );
+        let user_info = UserInfo::new(
+            test_user.to_string(),
+            test_hostname.to_string(),
+            AuthInfo::None,
+        );
+        let value = user_info.to_versioned_json()?;
+        let get_key = test_key.clone();
+        kv.expect_get_kv()
+            .with(predicate::function(move |v| v == get_key.as_str()))
+            .times(1)
+            .return_once(move |_k| Ok(Some(SeqV::new(1, value))));
         kv.expect_upsert_kv()
             .with(predicate::eq(UpsertKVAction::new(
                 &test_key,
@@ -457,13 +597,59 @@ mod drop {
             .returning(|_k| Ok(UpsertKVActionReply::new(None, None)));
         let kv = Arc::new(kv);
         let user_mgr = UserMgr::create(kv, "tenant1")?;
-        let res = user_mgr.drop_user(UserIdentity::new(test_user, test_hostname), None);
+        let res = user_mgr.drop_user(
+            UserIdentity::new(test_user, test_hostname),
+            None,
+            false,
+            false,
+        );
         assert_eq!(
             res.await.unwrap_err().code(),
             ErrorCode::UnknownUser("").code()
         );
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_drop_user_unknown_if_exists() -> common_exception::Result<()> {
+        let mut kv = MockKV::new();
+        let test_user = "test";
+        let test_hostname = "localhost";
+        let test_key = format!(
+            "__fd_users/tenant1/{}",
+            escape_for_key(&format_user_key(test_user, test_hostname))?
+        );
+        let user_info = UserInfo::new(
+            test_user.to_string(),
+            test_hostname.to_string(),
+            AuthInfo::None,
+        );
+        let value = user_info.to_versioned_json()?;
+        let get_key = test_key.clone();
+        kv.expect_get_kv()
+            .with(predicate::function(move |v| v == get_key.as_str()))
+            .times(1)
+            .return_once(move |_k| Ok(Some(SeqV::new(1, value))));
+        kv.expect_upsert_kv()
+            .with(predicate::eq(UpsertKVAction::new(
+                &test_key,
+                MatchSeq::Any,
+                Operation::Delete,
+                None,
+            )))
+            .times(1)
+            .returning(|_k| Ok(UpsertKVActionReply::new(None, None)));
+        let kv = Arc::new(kv);
+        let user_mgr = UserMgr::create(kv, "tenant1")?;
+        let res = user_mgr.drop_user(
+            UserIdentity::new(test_user, test_hostname),
+            None,
+            true,
+            false,
+        );
+        assert!(res.await.is_ok());
+        Ok(())
+    }
 }
 
 mod update {
@@ -483,6 +669,14 @@ mod update {
         }
     }
 
+    // The password policy of the tenant is read if the password is changed.
+    fn expect_default_password_policy(kv: &mut MockKV) {
+        kv.expect_get_kv()
+            .with(predicate::eq("__fd_password_policy/tenant1"))
+            .times(1)
+            .return_once(|_k| Ok(None));
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
     async fn test_update_user_normal_update_full() -> common_exception::Result<()> {
         test_update_user_normal(true).await
@@ -512,6 +706,7 @@ mod update {
 
         // get_kv should be called
         let mut kv = MockKV::new();
+        expect_default_password_policy(&mut kv);
         {
             let test_key = test_key.clone();
             kv.expect_get_kv()
@@ -526,7 +721,7 @@ mod update {
             test_hostname.to_string(),
             new_test_auth_info(full),
         );
-        let new_value_with_old_salt = serde_json::to_vec(&new_user_info)?;
+        let new_value_with_old_salt = new_user_info.to_versioned_json()?;
 
         kv.expect_upsert_kv()
             .with(predicate::eq(UpsertKVAction::new(
@@ -565,6 +760,7 @@ mod update {
         // if partial update, and get_kv returns None
         // update_kv should NOT be called
         let mut kv = MockKV::new();
+        expect_default_password_policy(&mut kv);
         kv.expect_get_kv()
             .with(predicate::function(move |v| v == test_key.as_str()))
             .times(1)
@@ -605,6 +801,7 @@ mod update {
 
         // - get_kv should be called
         let mut kv = MockKV::new();
+        expect_default_password_policy(&mut kv);
         {
             let test_key = test_key.clone();
             kv.expect_get_kv()
@@ -678,7 +875,7 @@ mod set_user_privileges {
         user_info
             .grants
             .grant_privileges(&GrantObject::Global, privileges);
-        let new_value = serde_json::to_vec(&user_info)?;
+        let new_value = user_info.to_versioned_json()?;
 
         kv.expect_upsert_kv()
             .with(predicate::eq(UpsertKVAction::new(
@@ -703,3 +900,2107 @@ mod set_user_privileges {
         Ok(())
     }
 }
+
+mod verify_auth {
+    use std::time::Duration;
+
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::AuthType;
+    use common_meta_types::UserInfo;
+
+    use super::*;
+
+    fn mock_user(auth_info: AuthInfo) -> common_exception::Result<UserMgr> {
+        let test_key = format!(
+            "__fd_users/tenant1/{}",
+            escape_for_key(&format_user_key("test", "localhost"))?
+        );
+        let user_info = UserInfo::new("test".to_string(), "localhost".to_string(), auth_info);
+        let value = serde_json::to_vec(&user_info)?;
+
+        let mut kv = MockKV::new();
+        kv.expect_get_kv()
+            .with(predicate::function(move |v| v == test_key.as_str()))
+            .returning(move |_k| Ok(Some(SeqV::new(1, value.clone()))));
+        UserMgr::create(Arc::new(kv), "tenant1")
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_verify_auth() -> common_exception::Result<()> {
+        let user = UserIdentity::new("test", "localhost");
+        for auth_type in [AuthType::DoubleSha1Password, AuthType::Sha256Password] {
+            let auth_info = AuthInfo::new(auth_type.clone(), &Some("pwd".to_string()))?;
+            let user_mgr = mock_user(auth_info)?;
+
+            assert!(
+                user_mgr.verify_auth(user.clone(), b"pwd", b"").await?,
+                "{:?}",
+                auth_type
+            );
+            assert!(
+                !user_mgr.verify_auth(user.clone(), b"wrong", b"").await?,
+                "{:?}",
+                auth_type
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_verify_auth_unknown_user() -> common_exception::Result<()> {
+        let mut kv = MockKV::new();
+        kv.expect_get_kv().returning(|_k| Ok(None));
+        let user_mgr = UserMgr::create(Arc::new(kv), "tenant1")?;
+
+        let res = user_mgr
+            .verify_auth(UserIdentity::new("test", "localhost"), b"pwd", b"")
+            .await;
+        assert_eq!(res.unwrap_err().code(), ErrorCode::UnknownUser("").code());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_verify_auth_locked_user() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = Arc::new(UserMgr::create(kv, "tenant1")?);
+        let auth_info = AuthInfo::new(AuthType::Sha256Password, &Some("pwd".to_string()))?;
+        let user_info = UserInfo::new("test".to_string(), "%".to_string(), auth_info);
+        let identity = user_info.identity();
+        user_mgr.add_user(user_info, false).await?;
+        user_mgr
+            .lock_user(identity.clone(), None, None, false)
+            .await?;
+
+        let cached = CachedUserMgr::create(user_mgr.clone(), Duration::from_secs(60));
+        let user_apis: [&dyn UserApi; 2] = [user_mgr.as_ref(), &cached];
+        for user_api in user_apis {
+            let res = user_api.verify_auth(identity.clone(), b"pwd", b"").await;
+            assert_eq!(
+                res.unwrap_err().code(),
+                ErrorCode::AuthenticateFailure("").code()
+            );
+        }
+        Ok(())
+    }
+}
+
+mod get_users_paged {
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::UserInfo;
+
+    use super::*;
+
+    // 60 users of `svc_00`..`svc_59`, 40 of `user_00`..`user_39`, and a corrupt `svc_zz`.
+    async fn prepare() -> common_exception::Result<UserMgr> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv.clone(), "tenant1")?;
+        for i in 0..100 {
+            let name = if i < 60 {
+                format!("svc_{:02}", i)
+            } else {
+                format!("user_{:02}", i - 60)
+            };
+            let user_info = UserInfo::new(name, "%".to_string(), default_test_auth_info());
+            user_mgr.add_user(user_info, false).await?;
+        }
+
+        let corrupt_key = format!(
+            "__fd_users/tenant1/{}",
+            escape_for_key(&format_user_key("svc_zz", "%"))?
+        );
+        kv.upsert_kv(UpsertKVAction::new(
+            &corrupt_key,
+            MatchSeq::Any,
+            Operation::Update(b"corrupt".to_vec()),
+            None,
+        ))
+        .await?;
+        Ok(user_mgr)
+    }
+
+    fn names(page: &UserPage) -> Vec<String> {
+        page.users.iter().map(|u| u.data.name.clone()).collect()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_get_users_paged_all() -> common_exception::Result<()> {
+        let user_mgr = prepare().await?;
+
+        let page = user_mgr.get_users_paged(None, 0, 1000).await?;
+        assert_eq!(page.total, 101);
+        assert_eq!(page.users.len(), 100);
+        assert_eq!(page.invalid.len(), 1);
+        assert_eq!(page.invalid[0].0, "'svc_zz'@'%'");
+        assert_eq!(
+            page.invalid[0].1.code(),
+            ErrorCode::IllegalUserInfoFormat("").code()
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_get_users_paged_prefix_pattern() -> common_exception::Result<()> {
+        let user_mgr = prepare().await?;
+
+        let page = user_mgr.get_users_paged(Some("svc_%"), 0, 25).await?;
+        assert_eq!(page.total, 61);
+        assert_eq!(page.users.len(), 25);
+        assert_eq!(names(&page)[0], "svc_00");
+        assert_eq!(names(&page)[24], "svc_24");
+        assert!(page.invalid.is_empty());
+
+        // the last page, with the corrupt user
+        let page = user_mgr.get_users_paged(Some("svc_%"), 50, 25).await?;
+        assert_eq!(page.total, 61);
+        assert_eq!(
+            names(&page),
+            (50..60).map(|i| format!("svc_{}", i)).collect::<Vec<_>>()
+        );
+        assert_eq!(page.invalid.len(), 1);
+
+        // out of the pages
+        let page = user_mgr.get_users_paged(Some("svc_%"), 61, 25).await?;
+        assert_eq!(page.total, 61);
+        assert!(page.users.is_empty());
+        assert!(page.invalid.is_empty());
+
+        let page = user_mgr.get_users_paged(Some("user_1_"), 0, 5).await?;
+        assert_eq!(page.total, 10);
+        assert_eq!(names(&page), vec![
+            "user_10", "user_11", "user_12", "user_13", "user_14"
+        ]);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_get_users_paged_filtered_pattern() -> common_exception::Result<()> {
+        let user_mgr = prepare().await?;
+
+        // no literal prefix to push down
+        let page = user_mgr.get_users_paged(Some("%9"), 0, 100).await?;
+        assert_eq!(page.total, 10);
+        assert_eq!(names(&page), vec![
+            "svc_09", "svc_19", "svc_29", "svc_39", "svc_49", "svc_59", "user_09", "user_19",
+            "user_29", "user_39"
+        ]);
+
+        let page = user_mgr.get_users_paged(Some("nobody%"), 0, 100).await?;
+        assert_eq!(page.total, 0);
+        assert!(page.users.is_empty());
+        Ok(())
+    }
+}
+
+mod update_user_quota {
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::UserInfo;
+    use common_meta_types::UserQuota;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_update_user_quota() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv, "tenant1")?;
+        let user_info = UserInfo::new(
+            "test".to_string(),
+            "%".to_string(),
+            default_test_auth_info(),
+        );
+        let identity = user_info.identity();
+        let seq = user_mgr.add_user(user_info, false).await?;
+
+        // no limit by default
+        let user = user_mgr.get_user(identity.clone(), None).await?;
+        assert_eq!(user.data.quota, UserQuota::no_limit());
+
+        let quota = UserQuota {
+            max_memory_in_bytes: 1 << 30,
+            max_sessions: 2,
+            ..UserQuota::no_limit()
+        };
+        let new_seq = user_mgr
+            .update_user_quota(identity.clone(), quota.clone(), Some(seq))
+            .await?;
+        let user = user_mgr.get_user(identity.clone(), None).await?;
+        assert_eq!(Some(user.seq), new_seq);
+        assert_eq!(user.data.quota, quota);
+
+        // the seq is out of date
+        let res = user_mgr
+            .update_user_quota(identity.clone(), UserQuota::no_limit(), Some(seq))
+            .await;
+        assert_eq!(res.unwrap_err().code(), ErrorCode::UnknownUser("").code());
+
+        // the user is absent
+        let res = user_mgr
+            .update_user_quota(UserIdentity::new("nobody", "%"), quota, None)
+            .await;
+        assert_eq!(res.unwrap_err().code(), ErrorCode::UnknownUser("").code());
+        Ok(())
+    }
+}
+
+mod exists_and_names {
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::UserInfo;
+
+    use super::*;
+
+    async fn prepare(names: &[&str]) -> common_exception::Result<UserMgr> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv, "tenant1")?;
+        for name in names {
+            let user_info = UserInfo::new(
+                name.to_string(),
+                "localhost".to_string(),
+                default_test_auth_info(),
+            );
+            user_mgr.add_user(user_info, false).await?;
+        }
+        Ok(user_mgr)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_exists_user() -> common_exception::Result<()> {
+        let user_mgr = prepare(&["alice"]).await?;
+
+        assert!(
+            user_mgr
+                .exists_user(UserIdentity::new("alice", "localhost"))
+                .await?
+        );
+        assert!(
+            !user_mgr
+                .exists_user(UserIdentity::new("alice", "%"))
+                .await?
+        );
+        assert!(
+            !user_mgr
+                .exists_user(UserIdentity::new("ALICE", "localhost"))
+                .await?
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_get_users_of_name() -> common_exception::Result<()> {
+        let user_mgr = prepare(&["bob", "bobby", "BOB"]).await?;
+        let user_info = UserInfo::new("bob".to_string(), "%".to_string(), default_test_auth_info());
+        user_mgr.add_user(user_info, false).await?;
+
+        // only the exact name, of all the hostnames
+        let mut hostnames = user_mgr
+            .get_users_of_name("bob")
+            .await?
+            .into_iter()
+            .map(|user| (user.data.name, user.data.hostname))
+            .collect::<Vec<_>>();
+        hostnames.sort();
+        assert_eq!(
+            vec![
+                ("bob".to_string(), "%".to_string()),
+                ("bob".to_string(), "localhost".to_string())
+            ],
+            hostnames
+        );
+
+        assert!(user_mgr.get_users_of_name("carol").await?.is_empty());
+
+        // the keys of `bob'@x` are prefixed by `'bob'@` too, but it is another name
+        let user_info = UserInfo::new(
+            "bob'@x".to_string(),
+            "%".to_string(),
+            default_test_auth_info(),
+        );
+        user_mgr.add_user(user_info, false).await?;
+        let names = user_mgr
+            .get_users_of_name("bob")
+            .await?
+            .into_iter()
+            .map(|user| user.data.name)
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["bob".to_string(), "bob".to_string()], names);
+        Ok(())
+    }
+}
+
+mod key_escaping {
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::UserInfo;
+
+    use super::*;
+
+    fn user(name: &str) -> UserInfo {
+        UserInfo::new(name.to_string(), "%".to_string(), default_test_auth_info())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_user_names_with_separators() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv.clone(), "tenant1")?;
+
+        let names = ["a/b", "a", "a%2fb", "x'@'y", "50%", "中文/"];
+        for name in names {
+            user_mgr.add_user(user(name), false).await?;
+        }
+
+        for name in names {
+            let got = user_mgr
+                .get_user(UserIdentity::new(name, "%"), None)
+                .await?;
+            assert_eq!(got.data.name, name);
+        }
+
+        let page = user_mgr.get_users_paged(None, 0, 100).await?;
+        let mut got = page
+            .users
+            .iter()
+            .map(|u| u.data.name.clone())
+            .collect::<Vec<_>>();
+        got.sort();
+        let mut want = names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        want.sort();
+        assert_eq!(got, want);
+
+        // A NUL can not be a part of a key.
+        let res = user_mgr.add_user(user("a\0b"), false).await;
+        assert_eq!(
+            res.unwrap_err().code(),
+            ErrorCode::InvalidMetaKey("").code()
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_tenant_with_separators() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let mgr = UserMgr::create(kv.clone(), "tenant1")?;
+        let nested_mgr = UserMgr::create(kv.clone(), "tenant1/a")?;
+
+        nested_mgr.add_user(user("b"), false).await?;
+
+        assert!(mgr.get_users().await?.is_empty());
+        assert_eq!(nested_mgr.get_users().await?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_list_unescaped_legacy_key() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv.clone(), "tenant1")?;
+        user_mgr.add_user(user("alice"), false).await?;
+
+        // Written without escaping, `%'@` is not a valid escape.
+        kv.upsert_kv(UpsertKVAction::new(
+            "__fd_users/tenant1/'50%'@'%'",
+            MatchSeq::Any,
+            Operation::Update(b"corrupt".to_vec()),
+            None,
+        ))
+        .await?;
+
+        let page = user_mgr.get_users_paged(None, 0, 100).await?;
+        assert_eq!(page.users.len(), 1);
+        assert_eq!(page.invalid.len(), 1);
+        assert_eq!(page.invalid[0].0, "'50%'@'%'");
+        Ok(())
+    }
+}
+
+mod rename {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::UserInfo;
+
+    use super::*;
+
+    // A KVApi which updates the `changed_key` once another key is added, as if it is updated
+    // concurrently, or fails the deletion of it after it is applied, as if the reply is lost.
+    struct ChangingKV {
+        inner: MetaEmbedded,
+        changed_key: String,
+        changing: AtomicBool,
+        failing_delete: AtomicBool,
+    }
+
+    #[async_trait]
+    impl KVApi for ChangingKV {
+        async fn upsert_kv(&self, act: UpsertKVAction) -> Result<UpsertKVActionReply, MetaError> {
+            let deleting = act.key == self.changed_key && matches!(act.value, Operation::Delete);
+            if deleting && self.failing_delete.swap(false, Ordering::SeqCst) {
+                self.inner.upsert_kv(act).await?;
+                return Err(MetaError::MetaServiceError("reply lost".to_string()));
+            }
+            let adding = act.key != self.changed_key && act.seq == MatchSeq::Exact(0);
+            let res = self.inner.upsert_kv(act).await?;
+            if adding && self.changing.swap(false, Ordering::SeqCst) {
+                if let Some(current) = self.inner.get_kv(&self.changed_key).await? {
+                    self.inner
+                        .upsert_kv(UpsertKVAction::new(
+                            &self.changed_key,
+                            MatchSeq::Any,
+                            Operation::Update(current.data),
+                            None,
+                        ))
+                        .await?;
+                }
+            }
+            Ok(res)
+        }
+
+        async fn upsert_kv_batch(
+            &self,
+            actions: Vec<UpsertKVAction>,
+        ) -> Result<UpsertKVBatchReply, MetaError> {
+            self.inner.upsert_kv_batch(actions).await
+        }
+
+        async fn get_kv(&self, key: &str) -> Result<GetKVActionReply, MetaError> {
+            self.inner.get_kv(key).await
+        }
+
+        async fn get_kv_meta(&self, key: &str) -> Result<GetKVMetaReply, MetaError> {
+            self.inner.get_kv_meta(key).await
+        }
+
+        async fn delete_kv(&self, key: &str, seq: MatchSeq) -> Result<DeleteKVReply, MetaError> {
+            self.inner.delete_kv(key, seq).await
+        }
+
+        async fn delete_prefix(&self, prefix: &str) -> Result<u64, MetaError> {
+            self.inner.delete_prefix(prefix).await
+        }
+
+        async fn incr_kv(
+            &self,
+            key: &str,
+            delta: i64,
+            seq: MatchSeq,
+        ) -> Result<IncrKVReply, MetaError> {
+            self.inner.incr_kv(key, delta, seq).await
+        }
+
+        async fn mget_kv(&self, keys: &[String]) -> Result<MGetKVActionReply, MetaError> {
+            self.inner.mget_kv(keys).await
+        }
+
+        async fn prefix_list_kv(&self, prefix: &str) -> Result<PrefixListReply, MetaError> {
+            self.inner.prefix_list_kv(prefix).await
+        }
+
+        async fn prefix_list_kv_paged(
+            &self,
+            prefix: &str,
+            after_key: Option<String>,
+            limit: usize,
+            keys_only: bool,
+        ) -> Result<PrefixListPage, MetaError> {
+            self.inner
+                .prefix_list_kv_paged(prefix, after_key, limit, keys_only)
+                .await
+        }
+
+        async fn transaction(&self, txn: TxnRequest) -> Result<TxnReply, MetaError> {
+            self.inner.transaction(txn).await
+        }
+
+        async fn watch(
+            &self,
+            prefix: &str,
+            from_seq: Option<u64>,
+        ) -> Result<WatchStream, MetaError> {
+            self.inner.watch(prefix, from_seq).await
+        }
+    }
+
+    async fn prepare(changing: bool) -> common_exception::Result<UserMgr> {
+        prepare_failing(changing, false).await
+    }
+
+    async fn prepare_failing(
+        changing: bool,
+        failing_delete: bool,
+    ) -> common_exception::Result<UserMgr> {
+        let kv = Arc::new(ChangingKV {
+            inner: MetaEmbedded::new_temp().await?,
+            changed_key: format!(
+                "__fd_users/tenant1/{}",
+                escape_for_key(&format_user_key("old", "%"))?
+            ),
+            changing: AtomicBool::new(false),
+            failing_delete: AtomicBool::new(false),
+        });
+        let user_mgr = UserMgr::create(kv.clone(), "tenant1")?;
+        for name in ["old", "taken"] {
+            let user_info =
+                UserInfo::new(name.to_string(), "%".to_string(), default_test_auth_info());
+            user_mgr.add_user(user_info, false).await?;
+        }
+        kv.changing.store(changing, Ordering::SeqCst);
+        kv.failing_delete.store(failing_delete, Ordering::SeqCst);
+        Ok(user_mgr)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_rename_user() -> common_exception::Result<()> {
+        let user_mgr = prepare(false).await?;
+        let old = user_mgr
+            .get_user(UserIdentity::new("old", "%"), None)
+            .await?;
+
+        let seq = user_mgr
+            .rename_user(UserIdentity::new("old", "%"), "new", Some(old.seq))
+            .await?;
+        let new = user_mgr
+            .get_user(UserIdentity::new("new", "%"), None)
+            .await?;
+        assert_eq!(new.seq, seq);
+        assert_eq!(new.data.name, "new");
+        assert_eq!(new.data.auth_info, old.data.auth_info);
+        assert!(!user_mgr.exists_user(UserIdentity::new("old", "%")).await?);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_rename_user_target_exists() -> common_exception::Result<()> {
+        let user_mgr = prepare(false).await?;
+
+        let res = user_mgr
+            .rename_user(UserIdentity::new("old", "%"), "taken", None)
+            .await;
+        assert_eq!(
+            res.unwrap_err().code(),
+            ErrorCode::UserAlreadyExists("").code()
+        );
+        assert!(user_mgr.exists_user(UserIdentity::new("old", "%")).await?);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_rename_user_source_missing() -> common_exception::Result<()> {
+        let user_mgr = prepare(false).await?;
+
+        let res = user_mgr
+            .rename_user(UserIdentity::new("missing", "%"), "new", None)
+            .await;
+        assert_eq!(res.unwrap_err().code(), ErrorCode::UnknownUser("").code());
+
+        // the seq is out of date
+        let res = user_mgr
+            .rename_user(UserIdentity::new("old", "%"), "new", Some(100))
+            .await;
+        assert_eq!(res.unwrap_err().code(), ErrorCode::UnknownUser("").code());
+        assert!(!user_mgr.exists_user(UserIdentity::new("new", "%")).await?);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_rename_user_rollback() -> common_exception::Result<()> {
+        let user_mgr = prepare(true).await?;
+
+        let res = user_mgr
+            .rename_user(UserIdentity::new("old", "%"), "new", None)
+            .await;
+        assert_eq!(res.unwrap_err().code(), ErrorCode::UnknownUser("").code());
+        assert!(user_mgr.exists_user(UserIdentity::new("old", "%")).await?);
+        assert!(!user_mgr.exists_user(UserIdentity::new("new", "%")).await?);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_rename_user_delete_applied_but_failed() -> common_exception::Result<()> {
+        let user_mgr = prepare_failing(false, true).await?;
+
+        // the old user is dropped, the renamed one is kept
+        user_mgr
+            .rename_user(UserIdentity::new("old", "%"), "new", None)
+            .await?;
+        assert!(!user_mgr.exists_user(UserIdentity::new("old", "%")).await?);
+        assert!(user_mgr.exists_user(UserIdentity::new("new", "%")).await?);
+        Ok(())
+    }
+}
+
+mod lock {
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::UserInfo;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_lock_user() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv, "tenant1")?;
+        let user_info = UserInfo::new(
+            "test".to_string(),
+            "%".to_string(),
+            default_test_auth_info(),
+        );
+        let identity = user_info.identity();
+        let seq = user_mgr.add_user(user_info, false).await?;
+
+        let new_seq = user_mgr
+            .lock_user(
+                identity.clone(),
+                Some("leaked".to_string()),
+                Some(seq),
+                false,
+            )
+            .await?;
+        let user = user_mgr.get_user(identity.clone(), None).await?;
+        assert_eq!(Some(user.seq), new_seq);
+        assert!(user.data.is_locked);
+        assert_eq!(user.data.locked_reason, Some("leaked".to_string()));
+
+        // the seq is out of date
+        let res = user_mgr.unlock_user(identity.clone(), Some(seq)).await;
+        assert_eq!(res.unwrap_err().code(), ErrorCode::UnknownUser("").code());
+        let res = user_mgr
+            .lock_user(identity.clone(), None, Some(seq), false)
+            .await;
+        assert_eq!(res.unwrap_err().code(), ErrorCode::UnknownUser("").code());
+
+        user_mgr.unlock_user(identity.clone(), new_seq).await?;
+        let user = user_mgr.get_user(identity, None).await?;
+        assert!(!user.data.is_locked);
+        assert_eq!(user.data.locked_reason, None);
+        Ok(())
+    }
+}
+
+mod settings {
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::UserInfo;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_user_settings() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv, "tenant1")?;
+        let user_info = UserInfo::new(
+            "test".to_string(),
+            "%".to_string(),
+            default_test_auth_info(),
+        );
+        let identity = user_info.identity();
+        let seq = user_mgr.add_user(user_info, false).await?;
+
+        let new_seq = user_mgr
+            .set_user_setting(
+                identity.clone(),
+                "max_threads".to_string(),
+                "4".to_string(),
+                Some(seq),
+            )
+            .await?;
+        let user = user_mgr.get_user(identity.clone(), None).await?;
+        assert_eq!(Some(user.seq), new_seq);
+        assert_eq!(
+            user.data.settings.get("max_threads"),
+            Some(&"4".to_string())
+        );
+
+        // the seq is out of date
+        let res = user_mgr
+            .unset_user_setting(identity.clone(), "max_threads".to_string(), Some(seq))
+            .await;
+        assert_eq!(res.unwrap_err().code(), ErrorCode::UnknownUser("").code());
+
+        user_mgr
+            .unset_user_setting(identity.clone(), "max_threads".to_string(), new_seq)
+            .await?;
+        let user = user_mgr.get_user(identity, None).await?;
+        assert!(user.data.settings.is_empty());
+        Ok(())
+    }
+}
+
+mod import_export {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::GrantObject;
+    use common_meta_types::UserAuditOperation;
+    use common_meta_types::UserInfo;
+    use common_meta_types::UserPrivilegeSet;
+    use common_meta_types::UserQuota;
+
+    use super::*;
+
+    // A KVApi which counts the round trips of the writes.
+    struct CountingWritesKV {
+        inner: MetaEmbedded,
+        writes: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl KVApi for CountingWritesKV {
+        async fn upsert_kv(&self, act: UpsertKVAction) -> Result<UpsertKVActionReply, MetaError> {
+            self.writes.fetch_add(1, Ordering::SeqCst);
+            self.inner.upsert_kv(act).await
+        }
+
+        async fn upsert_kv_batch(
+            &self,
+            actions: Vec<UpsertKVAction>,
+        ) -> Result<UpsertKVBatchReply, MetaError> {
+            self.writes.fetch_add(1, Ordering::SeqCst);
+            self.inner.upsert_kv_batch(actions).await
+        }
+
+        async fn get_kv(&self, key: &str) -> Result<GetKVActionReply, MetaError> {
+            self.inner.get_kv(key).await
+        }
+
+        async fn get_kv_meta(&self, key: &str) -> Result<GetKVMetaReply, MetaError> {
+            self.inner.get_kv_meta(key).await
+        }
+
+        async fn delete_kv(&self, key: &str, seq: MatchSeq) -> Result<DeleteKVReply, MetaError> {
+            self.writes.fetch_add(1, Ordering::SeqCst);
+            self.inner.delete_kv(key, seq).await
+        }
+
+        async fn delete_prefix(&self, prefix: &str) -> Result<u64, MetaError> {
+            self.writes.fetch_add(1, Ordering::SeqCst);
+            self.inner.delete_prefix(prefix).await
+        }
+
+        async fn incr_kv(
+            &self,
+            key: &str,
+            delta: i64,
+            seq: MatchSeq,
+        ) -> Result<IncrKVReply, MetaError> {
+            self.writes.fetch_add(1, Ordering::SeqCst);
+            self.inner.incr_kv(key, delta, seq).await
+        }
+
+        async fn mget_kv(&self, keys: &[String]) -> Result<MGetKVActionReply, MetaError> {
+            self.inner.mget_kv(keys).await
+        }
+
+        async fn prefix_list_kv(&self, prefix: &str) -> Result<PrefixListReply, MetaError> {
+            self.inner.prefix_list_kv(prefix).await
+        }
+
+        async fn prefix_list_kv_paged(
+            &self,
+            prefix: &str,
+            after_key: Option<String>,
+            limit: usize,
+            keys_only: bool,
+        ) -> Result<PrefixListPage, MetaError> {
+            self.inner
+                .prefix_list_kv_paged(prefix, after_key, limit, keys_only)
+                .await
+        }
+
+        async fn transaction(&self, txn: TxnRequest) -> Result<TxnReply, MetaError> {
+            self.writes.fetch_add(1, Ordering::SeqCst);
+            self.inner.transaction(txn).await
+        }
+
+        async fn watch(
+            &self,
+            prefix: &str,
+            from_seq: Option<u64>,
+        ) -> Result<WatchStream, MetaError> {
+            self.inner.watch(prefix, from_seq).await
+        }
+    }
+
+    fn new_user(i: usize) -> UserInfo {
+        let auth_info = AuthInfo::create(
+            &Some("sha256_password".to_string()),
+            &Some(format!("password{}", i)),
+        )
+        .unwrap();
+        let mut user = UserInfo::new(format!("user{:02}", i), "%".to_string(), auth_info);
+        user.grants.grant_privileges(
+            &GrantObject::Database("db1".to_string()),
+            UserPrivilegeSet::available_privileges_on_database(),
+        );
+        user.quota = UserQuota {
+            max_sessions: i as u64,
+            ..UserQuota::no_limit()
+        };
+        user.settings
+            .insert("max_threads".to_string(), format!("{}", i));
+        user
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_export_import_users() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv, "tenant1")?;
+        for i in 0..20 {
+            user_mgr.add_user(new_user(i), false).await?;
+        }
+
+        let exported = user_mgr.export_users().await?;
+        assert_eq!(exported, (0..20).map(new_user).collect::<Vec<_>>());
+
+        for user in &exported {
+            user_mgr
+                .drop_user(user.identity(), None, false, false)
+                .await?;
+        }
+        assert!(user_mgr.export_users().await?.is_empty());
+
+        let results = user_mgr
+            .import_users(exported.clone(), ImportMode::Fail)
+            .await?;
+        assert_eq!(results.len(), 20);
+        for ((identity, res), user) in results.iter().zip(exported.iter()) {
+            assert_eq!(identity, &user.identity());
+            assert!(matches!(res, Ok(UserImportStatus::Added(_))));
+        }
+
+        // The password digests round trip unchanged.
+        assert_eq!(user_mgr.export_users().await?, exported);
+        let user = user_mgr
+            .get_user(UserIdentity::new("user07", "%"), None)
+            .await?;
+        assert_eq!(user.data.auth_info, AuthInfo::Password {
+            hash_value: PasswordHashMethod::Sha256.hash(b"password7"),
+            hash_method: PasswordHashMethod::Sha256,
+        });
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_import_users_conflicts() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv, "tenant1")?;
+        user_mgr.add_user(new_user(1), false).await?;
+
+        // user01 conflicts, user02 does not.
+        let mut changed = new_user(1);
+        changed.quota.max_sessions = 100;
+        let users = vec![changed.clone(), new_user(2)];
+
+        // Skip
+        let results = user_mgr
+            .import_users(users.clone(), ImportMode::Skip)
+            .await?;
+        assert_eq!(results[0].1.as_ref().unwrap(), &UserImportStatus::Skipped);
+        assert!(matches!(results[1].1, Ok(UserImportStatus::Added(_))));
+        let user = user_mgr.get_user(changed.identity(), None).await?;
+        assert_eq!(user.data, new_user(1));
+
+        // Fail, the others are imported still.
+        user_mgr
+            .drop_user(new_user(2).identity(), None, false, false)
+            .await?;
+        let results = user_mgr
+            .import_users(users.clone(), ImportMode::Fail)
+            .await?;
+        assert_eq!(
+            results[0].1.as_ref().unwrap_err().code(),
+            ErrorCode::UserAlreadyExists("").code()
+        );
+        assert!(matches!(results[1].1, Ok(UserImportStatus::Added(_))));
+        let user = user_mgr.get_user(changed.identity(), None).await?;
+        assert_eq!(user.data, new_user(1));
+
+        // Overwrite
+        let results = user_mgr.import_users(users, ImportMode::Overwrite).await?;
+        let seq = match results[0].1 {
+            Ok(UserImportStatus::Overwritten(seq)) => seq,
+            ref res => panic!("expect overwritten, got {:?}", res),
+        };
+        assert!(matches!(results[1].1, Ok(UserImportStatus::Overwritten(_))));
+        let user = user_mgr.get_user(changed.identity(), None).await?;
+        assert_eq!(user.seq, seq);
+        assert_eq!(user.data, changed);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_import_users_batched() -> common_exception::Result<()> {
+        let kv = Arc::new(CountingWritesKV {
+            inner: MetaEmbedded::new_temp().await?,
+            writes: AtomicUsize::new(0),
+        });
+        let user_mgr = UserMgr::create(kv.clone(), "tenant1")?.with_audit("root", false);
+        for i in 0..5 {
+            user_mgr.add_user(new_user(i), false).await?;
+        }
+
+        // user00..user04 exist, user05..user19 do not.
+        let users = (0..20)
+            .map(|i| {
+                let mut user = new_user(i);
+                user.quota.max_sessions = 100;
+                user
+            })
+            .collect::<Vec<_>>();
+
+        // The new users by one batch, their audit entries by another.
+        kv.writes.store(0, Ordering::SeqCst);
+        let results = user_mgr
+            .import_users(users.clone(), ImportMode::Skip)
+            .await?;
+        assert_eq!(kv.writes.load(Ordering::SeqCst), 2);
+        for (i, (identity, res)) in results.iter().enumerate() {
+            assert_eq!(identity, &users[i].identity());
+            if i < 5 {
+                assert_eq!(res.as_ref().unwrap(), &UserImportStatus::Skipped);
+            } else {
+                assert!(matches!(res, Ok(UserImportStatus::Added(_))));
+            }
+        }
+
+        // The existing users are overwritten by one more batch.
+        kv.writes.store(0, Ordering::SeqCst);
+        let results = user_mgr
+            .import_users(users.clone(), ImportMode::Overwrite)
+            .await?;
+        assert_eq!(kv.writes.load(Ordering::SeqCst), 3);
+        for (i, (_, res)) in results.iter().enumerate() {
+            let seq = match res {
+                Ok(UserImportStatus::Overwritten(seq)) => *seq,
+                res => panic!("expect overwritten, got {:?}", res),
+            };
+            let user = user_mgr.get_user(users[i].identity(), None).await?;
+            assert_eq!(user.seq, seq);
+            assert_eq!(user.data, users[i]);
+        }
+
+        // Every change is audited.
+        for name in ["user02", "user07"] {
+            let operations = user_mgr
+                .list_user_audit(name, 10)
+                .await?
+                .into_iter()
+                .map(|e| e.operation)
+                .collect::<Vec<_>>();
+            assert_eq!(operations.len(), 2);
+            assert!(operations.contains(&UserAuditOperation::Add));
+            assert!(operations.contains(&UserAuditOperation::Update));
+        }
+        Ok(())
+    }
+}
+
+mod versions {
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::USER_INFO_VERSION;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_get_users_of_mixed_versions() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let fixtures = [
+            (
+                "v0",
+                r#"{"name":"v0","hostname":"%","password":[112],"auth_type":"Sha256"}"#,
+            ),
+            ("v1", r#"{"name":"v1","hostname":"%","auth_info":"None"}"#),
+            (
+                "v2",
+                r#"{"version":2,"name":"v2","hostname":"%","auth_info":"None"}"#,
+            ),
+        ];
+        for (name, value) in fixtures {
+            let key = format!(
+                "__fd_users/tenant1/{}",
+                escape_for_key(&format_user_key(name, "%"))?
+            );
+            kv.upsert_kv(UpsertKVAction::new(
+                &key,
+                MatchSeq::Exact(0),
+                Operation::Update(value.as_bytes().to_vec()),
+                None,
+            ))
+            .await?;
+        }
+
+        let user_mgr = UserMgr::create(kv.clone(), "tenant1")?;
+        let users = user_mgr.get_users().await?;
+        let names = users
+            .iter()
+            .map(|u| u.data.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["v0", "v1", "v2"]);
+        assert_eq!(users[0].data.auth_info, AuthInfo::Password {
+            hash_value: vec![112],
+            hash_method: PasswordHashMethod::Sha256,
+        });
+
+        // Written back as the latest version.
+        let identity = UserIdentity::new("v0", "%");
+        user_mgr
+            .lock_user(identity.clone(), None, None, false)
+            .await?;
+        let user = user_mgr.get_user(identity, None).await?;
+        assert!(user.data.is_locked);
+        assert_eq!(user.data.auth_info, users[0].data.auth_info);
+        let key = format!(
+            "__fd_users/tenant1/{}",
+            escape_for_key(&format_user_key("v0", "%"))?
+        );
+        let raw = kv.get_kv(&key).await?.unwrap();
+        let value = serde_json::from_slice::<serde_json::Value>(&raw.data)?;
+        assert_eq!(value["version"], USER_INFO_VERSION);
+        Ok(())
+    }
+}
+
+mod password_policy {
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::PasswordPolicy;
+    use common_meta_types::UserInfo;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_password_policy() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv.clone(), "tenant1")?;
+        assert_eq!(
+            user_mgr.get_password_policy().await?,
+            PasswordPolicy::default()
+        );
+
+        let policy = PasswordPolicy {
+            min_length: 8,
+            require_complexity: true,
+            ..Default::default()
+        };
+        user_mgr.set_password_policy(policy.clone()).await?;
+        assert_eq!(user_mgr.get_password_policy().await?, policy);
+
+        // per tenant
+        let other_mgr = UserMgr::create(kv, "tenant2")?;
+        assert_eq!(
+            other_mgr.get_password_policy().await?,
+            PasswordPolicy::default()
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_password_change_clears_must_change() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv, "tenant1")?;
+        let mut user_info = UserInfo::new(
+            "test".to_string(),
+            "%".to_string(),
+            default_test_auth_info(),
+        );
+        user_info.password_policy.must_change = true;
+        user_info.password_policy.min_length = 8;
+        let identity = user_info.identity();
+        user_mgr.add_user(user_info, false).await?;
+
+        // not the password is changed
+        user_mgr
+            .update_user(identity.clone(), None, Some(Default::default()), None)
+            .await?;
+        let user = user_mgr.get_user(identity.clone(), None).await?;
+        assert!(user.data.password_policy.must_change);
+
+        let new_auth_info = AuthInfo::Password {
+            hash_value: Vec::from("new_password"),
+            hash_method: PasswordHashMethod::DoubleSha1,
+        };
+        user_mgr
+            .update_user(identity.clone(), Some(new_auth_info), None, None)
+            .await?;
+        let user = user_mgr.get_user(identity, None).await?;
+        assert!(!user.data.password_policy.must_change);
+        assert_eq!(user.data.password_policy.min_length, 8);
+        Ok(())
+    }
+}
+
+mod password_history {
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::PasswordPolicy;
+    use common_meta_types::UserInfo;
+
+    use super::*;
+
+    fn password(p: &str) -> AuthInfo {
+        AuthInfo::Password {
+            hash_value: PasswordHashMethod::Sha256.hash(p.as_bytes()),
+            hash_method: PasswordHashMethod::Sha256,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_password_reuse_rejected() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv, "tenant1")?;
+        user_mgr
+            .set_password_policy(PasswordPolicy {
+                history_depth: 3,
+                ..Default::default()
+            })
+            .await?;
+
+        let user_info = UserInfo::new("test".to_string(), "%".to_string(), password("p1"));
+        let identity = user_info.identity();
+        user_mgr.add_user(user_info, false).await?;
+        for p in ["p2", "p3", "p4"] {
+            user_mgr
+                .update_user(identity.clone(), Some(password(p)), None, None)
+                .await?;
+        }
+
+        // The last 3 passwords, including the current one.
+        for p in ["p4", "p3", "p2"] {
+            let res = user_mgr
+                .update_user(identity.clone(), Some(password(p)), None, None)
+                .await;
+            assert_eq!(
+                res.unwrap_err().code(),
+                ErrorCode::PasswordPolicyViolation("").code()
+            );
+        }
+        let user = user_mgr.get_user(identity.clone(), None).await?;
+        assert_eq!(user.data.auth_info, password("p4"));
+        assert_eq!(user.data.password_history.len(), 2);
+
+        // The fourth-oldest one.
+        user_mgr
+            .update_user(identity.clone(), Some(password("p1")), None, None)
+            .await?;
+        let user = user_mgr.get_user(identity.clone(), None).await?;
+        assert_eq!(user.data.auth_info, password("p1"));
+        assert_eq!(user.data.password_history.len(), 2);
+
+        // A plaintext password is compared by its digest.
+        let res = user_mgr
+            .update_user(
+                identity,
+                Some(AuthInfo::Password {
+                    hash_value: Vec::from("p4"),
+                    hash_method: PasswordHashMethod::PlainText,
+                }),
+                None,
+                None,
+            )
+            .await;
+        assert_eq!(
+            res.unwrap_err().code(),
+            ErrorCode::PasswordPolicyViolation("").code()
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_password_history_disabled() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv, "tenant1")?;
+        let user_info = UserInfo::new("test".to_string(), "%".to_string(), password("p1"));
+        let identity = user_info.identity();
+        user_mgr.add_user(user_info, false).await?;
+
+        user_mgr
+            .update_user(identity.clone(), Some(password("p1")), None, None)
+            .await?;
+        let user = user_mgr.get_user(identity, None).await?;
+        assert!(user.data.password_history.is_empty());
+        Ok(())
+    }
+}
+
+mod key_escaping {
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::UserInfo;
+    use common_meta_types::UserQuota;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_user_names_escaped() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv.clone(), "tenant1")?;
+
+        let names = ["a/b", "a b", "用户", "tenant2/c"];
+        for name in names {
+            let user_info = UserInfo::new(name.to_string(), "%".to_string(), AuthInfo::None);
+            user_mgr.add_user(user_info, false).await?;
+        }
+
+        // Stored flat under the tenant.
+        let value = kv
+            .get_kv("__fd_users/tenant1/%27a%2fb%27%40%27%25%27")
+            .await?;
+        assert!(value.is_some());
+        let keys = kv.prefix_list_kv("__fd_users/tenant1/").await?;
+        assert_eq!(keys.len(), names.len());
+        for (key, _) in keys {
+            assert!(!key["__fd_users/tenant1/".len()..].contains('/'), "{}", key);
+        }
+        let other_mgr = UserMgr::create(kv.clone(), "tenant2")?;
+        assert!(other_mgr.get_users().await?.is_empty());
+
+        for name in names {
+            let identity = UserIdentity::new(name, "%");
+            let user = user_mgr.get_user(identity.clone(), None).await?;
+            assert_eq!(user.data.name, name);
+            user_mgr
+                .update_user_quota(identity.clone(), UserQuota::no_limit(), None)
+                .await?;
+        }
+
+        let mut listed = user_mgr
+            .get_users()
+            .await?
+            .into_iter()
+            .map(|user| user.data.name)
+            .collect::<Vec<_>>();
+        listed.sort();
+        let mut expected = names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(listed, expected);
+
+        let page = user_mgr.get_users_paged(Some("a%"), 0, 10).await?;
+        assert_eq!(page.total, 2);
+
+        for name in names {
+            user_mgr
+                .drop_user(UserIdentity::new(name, "%"), None, false, false)
+                .await?;
+        }
+        assert!(user_mgr.get_users().await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_tenants_of_common_prefix() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv.clone(), "tenant1")?;
+        let other_mgr = UserMgr::create(kv.clone(), "tenant10")?;
+
+        let user_info = UserInfo::new("u1".to_string(), "%".to_string(), AuthInfo::None);
+        other_mgr.add_user(user_info, false).await?;
+        assert!(user_mgr.get_users().await?.is_empty());
+        assert_eq!(other_mgr.get_users().await?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_illegal_user_identity() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv.clone(), "tenant1")?;
+
+        for (name, hostname) in [("a\nb", "%"), ("a\u{0}", "%"), ("a", "x'@'y")] {
+            let user_info = UserInfo::new(name.to_string(), hostname.to_string(), AuthInfo::None);
+            let res = user_mgr.add_user(user_info, false).await;
+            assert_eq!(
+                res.unwrap_err().code(),
+                ErrorCode::IllegalUserIdentity("").code()
+            );
+        }
+
+        let user_info = UserInfo::new("a".to_string(), "%".to_string(), AuthInfo::None);
+        user_mgr.add_user(user_info.clone(), false).await?;
+        let res = user_mgr
+            .rename_user(user_info.identity(), "a\tb", None)
+            .await;
+        assert_eq!(
+            res.unwrap_err().code(),
+            ErrorCode::IllegalUserIdentity("").code()
+        );
+        assert!(user_mgr.get_user(user_info.identity(), None).await.is_ok());
+        assert_eq!(user_mgr.get_users().await?.len(), 1);
+        Ok(())
+    }
+}
+
+mod grants {
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::GrantObject;
+    use common_meta_types::RoleInfo;
+    use common_meta_types::UserInfo;
+    use common_meta_types::UserPrivilegeSet;
+    use common_meta_types::UserPrivilegeType;
+
+    use super::*;
+
+    fn privileges(types: &[UserPrivilegeType]) -> UserPrivilegeSet {
+        let mut privileges = UserPrivilegeSet::empty();
+        for t in types {
+            privileges.set_privilege(*t);
+        }
+        privileges
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_get_user_grants() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv.clone(), "tenant1")?;
+        let role_mgr = RoleMgr::create(kv, "tenant1")?;
+        let table = GrantObject::Table("db1".to_string(), "t1".to_string());
+
+        let user_info = UserInfo::new("u1".to_string(), "%".to_string(), AuthInfo::None);
+        let identity = user_info.identity();
+        user_mgr.add_user(user_info, false).await?;
+        user_mgr
+            .grant_privileges(
+                identity.clone(),
+                table.clone(),
+                privileges(&[UserPrivilegeType::Select]),
+                None,
+            )
+            .await?;
+
+        // r1 -> r2 -> r3 -> r1, and r4 dropped.
+        for name in ["r1", "r2", "r3"] {
+            role_mgr.add_role(RoleInfo::new(name.to_string())).await?;
+        }
+        role_mgr
+            .grant_privileges(
+                "r1".to_string(),
+                table.clone(),
+                privileges(&[UserPrivilegeType::Select, UserPrivilegeType::Insert]),
+                None,
+            )
+            .await?;
+        role_mgr
+            .grant_privileges(
+                "r3".to_string(),
+                GrantObject::Database("db2".to_string()),
+                privileges(&[UserPrivilegeType::Create]),
+                None,
+            )
+            .await?;
+        role_mgr
+            .grant_role("r1".to_string(), "r2".to_string(), None)
+            .await?;
+        role_mgr
+            .grant_role("r2".to_string(), "r3".to_string(), None)
+            .await?;
+        role_mgr
+            .grant_role("r3".to_string(), "r1".to_string(), None)
+            .await?;
+        for role in ["r1", "r4"] {
+            user_mgr
+                .grant_role(identity.clone(), role.to_string(), None)
+                .await?;
+        }
+
+        let resolved = user_mgr.get_user_grants(identity.clone()).await?;
+        // The overlapping grants on the table are merged into one entry.
+        let merged = resolved.merged.entries();
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].object(), &table);
+        assert_eq!(
+            merged[0].privileges(),
+            privileges(&[UserPrivilegeType::Select, UserPrivilegeType::Insert])
+        );
+        assert!(resolved
+            .merged
+            .verify_privilege(&table, UserPrivilegeType::Insert));
+        assert!(resolved.merged.verify_privilege(
+            &GrantObject::Table("db2".to_string(), "t2".to_string()),
+            UserPrivilegeType::Create
+        ));
+        let mut roles = resolved.merged.roles();
+        roles.sort();
+        assert_eq!(roles, vec!["r1", "r2", "r3"]);
+        assert_eq!(resolved.unknown_roles, vec!["r4".to_string()]);
+
+        let provenance = resolved
+            .provenance
+            .iter()
+            .map(|(source, entry)| (source.clone(), entry.object().clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(provenance, vec![
+            (GrantSource::User, table.clone()),
+            (GrantSource::Role("r1".to_string()), table.clone()),
+            (
+                GrantSource::Role("r3".to_string()),
+                GrantObject::Database("db2".to_string())
+            ),
+        ]);
+
+        // The privileges of a revoked role are gone.
+        user_mgr
+            .revoke_role(identity.clone(), "r1".to_string(), None, false)
+            .await?;
+        let resolved = user_mgr.get_user_grants(identity.clone()).await?;
+        assert_eq!(resolved.merged.entries().len(), 1);
+        assert!(!resolved
+            .merged
+            .verify_privilege(&table, UserPrivilegeType::Insert));
+        assert!(resolved.merged.roles().is_empty());
+        assert_eq!(resolved.provenance.len(), 1);
+
+        let res = user_mgr
+            .get_user_grants(UserIdentity::new("unknown", "%"))
+            .await;
+        assert_eq!(res.unwrap_err().code(), ErrorCode::UnknownUser("").code());
+        Ok(())
+    }
+}
+
+mod builtin {
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::GrantObject;
+    use common_meta_types::UserInfo;
+    use common_meta_types::UserPrivilegeSet;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_builtin_user_protected() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv, "tenant1")?;
+
+        let mut user_info = UserInfo::new("admin".to_string(), "%".to_string(), AuthInfo::None);
+        user_info.is_builtin = true;
+        user_info.grants.grant_privileges(
+            &GrantObject::Global,
+            UserPrivilegeSet::available_privileges_on_global(),
+        );
+        user_info.grants.grant_role("r1".to_string());
+        let identity = user_info.identity();
+        user_mgr.add_user(user_info.clone(), false).await?;
+
+        let protected = ErrorCode::BuiltinUserProtected("").code();
+        let res = user_mgr
+            .drop_user(identity.clone(), None, false, false)
+            .await;
+        assert_eq!(res.unwrap_err().code(), protected);
+        let res = user_mgr
+            .drop_user(identity.clone(), None, true, false)
+            .await;
+        assert_eq!(res.unwrap_err().code(), protected);
+        let res = user_mgr
+            .lock_user(identity.clone(), None, None, false)
+            .await;
+        assert_eq!(res.unwrap_err().code(), protected);
+        let res = user_mgr
+            .revoke_privileges(
+                identity.clone(),
+                GrantObject::Global,
+                UserPrivilegeSet::available_privileges_on_global(),
+                None,
+                false,
+            )
+            .await;
+        assert_eq!(res.unwrap_err().code(), protected);
+        let res = user_mgr
+            .revoke_role(identity.clone(), "r1".to_string(), None, false)
+            .await;
+        assert_eq!(res.unwrap_err().code(), protected);
+        assert_eq!(
+            user_mgr.get_user(identity.clone(), None).await?.data,
+            user_info
+        );
+
+        // Forced.
+        user_mgr
+            .lock_user(identity.clone(), None, None, true)
+            .await?;
+        user_mgr
+            .revoke_role(identity.clone(), "r1".to_string(), None, true)
+            .await?;
+        let got = user_mgr.get_user(identity.clone(), None).await?.data;
+        assert!(got.is_locked);
+        assert!(got.grants.roles().is_empty());
+
+        user_mgr
+            .drop_user(identity.clone(), None, false, true)
+            .await?;
+        assert!(!user_mgr.exists_user(identity.clone()).await?);
+
+        // A dropped user is reported as unknown whether forced or not.
+        let res = user_mgr
+            .drop_user(identity.clone(), None, false, false)
+            .await;
+        assert_eq!(res.unwrap_err().code(), ErrorCode::UnknownUser("").code());
+        user_mgr.drop_user(identity, None, true, false).await?;
+        Ok(())
+    }
+}
+
+mod audit {
+    use std::time::Duration;
+
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::GrantObject;
+    use common_meta_types::TxnOp;
+    use common_meta_types::UserAuditOperation;
+    use common_meta_types::UserInfo;
+    use common_meta_types::UserPrivilegeSet;
+    use common_meta_types::UserPrivilegeType;
+
+    use super::*;
+
+    // A KVApi which fails the writes of the audit entries.
+    struct FailingAuditKV {
+        inner: MetaEmbedded,
+    }
+
+    #[async_trait]
+    impl KVApi for FailingAuditKV {
+        async fn upsert_kv(&self, act: UpsertKVAction) -> Result<UpsertKVActionReply, MetaError> {
+            if act.key.starts_with("__fd_user_audit/") {
+                return Err(MetaError::MetaServiceError("audit unavailable".to_string()));
+            }
+            self.inner.upsert_kv(act).await
+        }
+
+        async fn upsert_kv_batch(
+            &self,
+            actions: Vec<UpsertKVAction>,
+        ) -> Result<UpsertKVBatchReply, MetaError> {
+            if actions
+                .iter()
+                .any(|act| act.key.starts_with("__fd_user_audit/"))
+            {
+                return Err(MetaError::MetaServiceError("audit unavailable".to_string()));
+            }
+            self.inner.upsert_kv_batch(actions).await
+        }
+
+        async fn get_kv(&self, key: &str) -> Result<GetKVActionReply, MetaError> {
+            self.inner.get_kv(key).await
+        }
+
+        async fn get_kv_meta(&self, key: &str) -> Result<GetKVMetaReply, MetaError> {
+            self.inner.get_kv_meta(key).await
+        }
+
+        async fn delete_kv(&self, key: &str, seq: MatchSeq) -> Result<DeleteKVReply, MetaError> {
+            self.inner.delete_kv(key, seq).await
+        }
+
+        async fn delete_prefix(&self, prefix: &str) -> Result<u64, MetaError> {
+            self.inner.delete_prefix(prefix).await
+        }
+
+        async fn incr_kv(
+            &self,
+            key: &str,
+            delta: i64,
+            seq: MatchSeq,
+        ) -> Result<IncrKVReply, MetaError> {
+            self.inner.incr_kv(key, delta, seq).await
+        }
+
+        async fn mget_kv(&self, keys: &[String]) -> Result<MGetKVActionReply, MetaError> {
+            self.inner.mget_kv(keys).await
+        }
+
+        async fn prefix_list_kv(&self, prefix: &str) -> Result<PrefixListReply, MetaError> {
+            self.inner.prefix_list_kv(prefix).await
+        }
+
+        async fn prefix_list_kv_paged(
+            &self,
+            prefix: &str,
+            after_key: Option<String>,
+            limit: usize,
+            keys_only: bool,
+        ) -> Result<PrefixListPage, MetaError> {
+            self.inner
+                .prefix_list_kv_paged(prefix, after_key, limit, keys_only)
+                .await
+        }
+
+        async fn transaction(&self, txn: TxnRequest) -> Result<TxnReply, MetaError> {
+            let audited = txn.if_then.iter().any(
+                |op| matches!(op, TxnOp::Put { key, .. } if key.starts_with("__fd_user_audit/")),
+            );
+            if audited {
+                return Err(MetaError::MetaServiceError("audit unavailable".to_string()));
+            }
+            self.inner.transaction(txn).await
+        }
+
+        async fn watch(
+            &self,
+            prefix: &str,
+            from_seq: Option<u64>,
+        ) -> Result<WatchStream, MetaError> {
+            self.inner.watch(prefix, from_seq).await
+        }
+    }
+
+    fn new_user(name: &str) -> UserInfo {
+        UserInfo::new(name.to_string(), "%".to_string(), default_test_auth_info())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_user_audit() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv.clone(), "tenant1")?.with_audit("admin", true);
+        let identity = UserIdentity::new("test", "%");
+
+        let add_seq = user_mgr.add_user(new_user("test"), false).await?;
+        user_mgr.add_user(new_user("other"), false).await?;
+        let update_seq = user_mgr
+            .update_user(identity.clone(), Some(AuthInfo::None), None, None)
+            .await?
+            .unwrap();
+        let mut privileges = UserPrivilegeSet::empty();
+        privileges.set_privilege(UserPrivilegeType::Select);
+        let grant_seq = user_mgr
+            .grant_privileges(identity.clone(), GrantObject::Global, privileges, None)
+            .await?
+            .unwrap();
+        let grant_role_seq = user_mgr
+            .grant_role(identity.clone(), "role1".to_string(), None)
+            .await?
+            .unwrap();
+        user_mgr
+            .drop_user(identity.clone(), None, false, false)
+            .await?;
+
+        // the entries of the user, the newest first
+        let entries = user_mgr.list_user_audit("test", 10).await?;
+        let operations = entries
+            .iter()
+            .map(|entry| (entry.operation.clone(), entry.prev_seq))
+            .collect::<Vec<_>>();
+        assert_eq!(operations, vec![
+            (UserAuditOperation::Drop, grant_role_seq),
+            (
+                UserAuditOperation::GrantRole("role1".to_string()),
+                grant_seq
+            ),
+            (UserAuditOperation::GrantPrivileges, update_seq),
+            (UserAuditOperation::Update, add_seq),
+            (UserAuditOperation::Add, 0),
+        ]);
+        for entry in &entries {
+            assert_eq!(entry.operator, "admin");
+            assert_eq!(entry.user, identity);
+        }
+        assert!(entries
+            .windows(2)
+            .all(|w| w[0].timestamp_ns >= w[1].timestamp_ns));
+
+        let entries = user_mgr.list_user_audit("test", 2).await?;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].operation, UserAuditOperation::Drop);
+
+        let entries = user_mgr.list_user_audit("other", 10).await?;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].operation, UserAuditOperation::Add);
+
+        // the entries are keyed by the user name, to be listed by the prefix
+        let keys = kv.prefix_list_kv("__fd_user_audit/tenant1/other/").await?;
+        assert_eq!(keys.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_user_audit_without_operator() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv, "tenant1")?;
+        user_mgr.add_user(new_user("test"), false).await?;
+
+        assert!(user_mgr.list_user_audit("test", 10).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_user_audit_with_operator() -> common_exception::Result<()> {
+        let kv = Arc::new(MetaEmbedded::new_temp().await?);
+        let user_mgr = UserMgr::create(kv, "tenant1")?.with_audit("system", true);
+        let cached = CachedUserMgr::create(Arc::new(user_mgr), Duration::from_secs(60));
+        let identity = UserIdentity::new("test", "%");
+        cached.add_user(new_user("test"), false).await?;
+        assert!(
+            !cached
+                .get_user(identity.clone(), None)
+                .await?
+                .data
+                .is_locked
+        );
+
+        // the change is recorded on behalf of the operator
+        cached
+            .with_operator("'alice'@'%'")
+            .lock_user(identity.clone(), None, None, false)
+            .await?;
+        let operators = cached
+            .list_user_audit("test", 10)
+            .await?
+            .into_iter()
+            .map(|entry| entry.operator)
+            .collect::<Vec<_>>();
+        assert_eq!(operators, vec!["'alice'@'%'", "system"]);
+
+        // the cache is shared, the user cached before the change is invalidated
+        assert!(cached.get_user(identity, None).await?.data.is_locked);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_user_audit_failure() -> common_exception::Result<()> {
+        let kv = Arc::new(FailingAuditKV {
+            inner: MetaEmbedded::new_temp().await?,
+        });
+
+        // the failure is ignored
+        let user_mgr = UserMgr::create(kv.clone(), "tenant1")?.with_audit("admin", false);
+        user_mgr.add_user(new_user("test"), false).await?;
+        assert!(user_mgr.exists_user(UserIdentity::new("test", "%")).await?);
+
+        // the failure fails the operation, which is not applied either
+        let user_mgr = UserMgr::create(kv, "tenant1")?.with_audit("admin", true);
+        let res = user_mgr
+            .lock_user(UserIdentity::new("test", "%"), None, None, false)
+            .await;
+        assert!(res.is_err());
+        let user = user_mgr
+            .get_user(UserIdentity::new("test", "%"), None)
+            .await?;
+        assert!(!user.data.is_locked);
+
+        let res = user_mgr
+            .drop_user(UserIdentity::new("test", "%"), None, false, false)
+            .await;
+        assert!(res.is_err());
+        assert!(user_mgr.exists_user(UserIdentity::new("test", "%")).await?);
+
+        assert!(user_mgr.add_user(new_user("other"), false).await.is_err());
+        let res = user_mgr
+            .import_users(vec![new_user("other")], ImportMode::Skip)
+            .await?;
+        assert!(res[0].1.is_err());
+        assert!(
+            !user_mgr
+                .exists_user(UserIdentity::new("other", "%"))
+                .await?
+        );
+        Ok(())
+    }
+}
+
+mod cached {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::UserInfo;
+
+    use super::*;
+
+    // A KVApi which counts the reads of the values, and of the seqs only.
+    struct CountingKV {
+        inner: MetaEmbedded,
+        reads: AtomicUsize,
+        meta_reads: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl KVApi for CountingKV {
+        async fn upsert_kv(&self, act: UpsertKVAction) -> Result<UpsertKVActionReply, MetaError> {
+            self.inner.upsert_kv(act).await
+        }
+
+        async fn upsert_kv_batch(
+            &self,
+            actions: Vec<UpsertKVAction>,
+        ) -> Result<UpsertKVBatchReply, MetaError> {
+            self.inner.upsert_kv_batch(actions).await
+        }
+
+        async fn get_kv(&self, key: &str) -> Result<GetKVActionReply, MetaError> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            self.inner.get_kv(key).await
+        }
+
+        async fn get_kv_meta(&self, key: &str) -> Result<GetKVMetaReply, MetaError> {
+            self.meta_reads.fetch_add(1, Ordering::SeqCst);
+            self.inner.get_kv_meta(key).await
+        }
+
+        async fn delete_kv(&self, key: &str, seq: MatchSeq) -> Result<DeleteKVReply, MetaError> {
+            self.inner.delete_kv(key, seq).await
+        }
+
+        async fn delete_prefix(&self, prefix: &str) -> Result<u64, MetaError> {
+            self.inner.delete_prefix(prefix).await
+        }
+
+        async fn incr_kv(
+            &self,
+            key: &str,
+            delta: i64,
+            seq: MatchSeq,
+        ) -> Result<IncrKVReply, MetaError> {
+            self.inner.incr_kv(key, delta, seq).await
+        }
+
+        async fn mget_kv(&self, keys: &[String]) -> Result<MGetKVActionReply, MetaError> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            self.inner.mget_kv(keys).await
+        }
+
+        async fn prefix_list_kv(&self, prefix: &str) -> Result<PrefixListReply, MetaError> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            self.inner.prefix_list_kv(prefix).await
+        }
+
+        async fn prefix_list_kv_paged(
+            &self,
+            prefix: &str,
+            after_key: Option<String>,
+            limit: usize,
+            keys_only: bool,
+        ) -> Result<PrefixListPage, MetaError> {
+            self.inner
+                .prefix_list_kv_paged(prefix, after_key, limit, keys_only)
+                .await
+        }
+
+        async fn transaction(&self, txn: TxnRequest) -> Result<TxnReply, MetaError> {
+            self.inner.transaction(txn).await
+        }
+
+        async fn watch(
+            &self,
+            prefix: &str,
+            from_seq: Option<u64>,
+        ) -> Result<WatchStream, MetaError> {
+            self.inner.watch(prefix, from_seq).await
+        }
+    }
+
+    async fn prepare(ttl: Duration) -> common_exception::Result<(Arc<CountingKV>, CachedUserMgr)> {
+        let kv = Arc::new(CountingKV {
+            inner: MetaEmbedded::new_temp().await?,
+            reads: AtomicUsize::new(0),
+            meta_reads: AtomicUsize::new(0),
+        });
+        let user_mgr = UserMgr::create(kv.clone(), "tenant1")?;
+        let cached = CachedUserMgr::create(Arc::new(user_mgr), ttl);
+        for name in ["u1", "u2"] {
+            let user_info =
+                UserInfo::new(name.to_string(), "%".to_string(), default_test_auth_info());
+            cached.add_user(user_info, false).await?;
+        }
+        Ok((kv, cached))
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_cached_user_mgr_hit() -> common_exception::Result<()> {
+        let (kv, cached) = prepare(Duration::from_secs(600)).await?;
+        let identity = UserIdentity::new("u1", "%");
+
+        let user = cached.get_user(identity.clone(), None).await?;
+        assert_eq!(kv.reads.load(Ordering::SeqCst), 1);
+
+        // served by the cache
+        assert_eq!(cached.get_user(identity.clone(), None).await?, user);
+        assert_eq!(
+            cached.get_user(identity.clone(), Some(user.seq)).await?,
+            user
+        );
+        assert!(cached.exists_user(identity.clone()).await?);
+        cached.verify_auth(identity.clone(), b"", b"").await?;
+        assert_eq!(kv.reads.load(Ordering::SeqCst), 1);
+
+        // the listing refreshes all the users
+        cached.get_users().await?;
+        assert_eq!(kv.reads.load(Ordering::SeqCst), 2);
+        cached.get_user(UserIdentity::new("u2", "%"), None).await?;
+        assert_eq!(kv.reads.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_cached_user_mgr_invalidate() -> common_exception::Result<()> {
+        let (kv, cached) = prepare(Duration::from_secs(600)).await?;
+        let identity = UserIdentity::new("u1", "%");
+        let user = cached.get_user(identity.clone(), None).await?;
+
+        // the update is written through and invalidates the user
+        cached
+            .update_user(identity.clone(), Some(AuthInfo::None), None, None)
+            .await?;
+        let reads = kv.reads.load(Ordering::SeqCst);
+        let updated = cached.get_user(identity.clone(), None).await?;
+        assert_eq!(kv.reads.load(Ordering::SeqCst), reads + 1);
+        assert!(updated.seq > user.seq);
+        assert_eq!(updated.data.auth_info, AuthInfo::None);
+
+        cached
+            .drop_user(identity.clone(), None, false, false)
+            .await?;
+        let res = cached.get_user(identity, None).await;
+        assert_eq!(res.unwrap_err().code(), ErrorCode::UnknownUser("").code());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_cached_user_mgr_expired() -> common_exception::Result<()> {
+        let (kv, cached) = prepare(Duration::from_millis(0)).await?;
+        let identity = UserIdentity::new("u1", "%");
+
+        // an unchanged user is revalidated by its seq, without reading it again
+        let user = cached.get_user(identity.clone(), None).await?;
+        assert_eq!(cached.get_user(identity.clone(), None).await?, user);
+        assert_eq!(kv.reads.load(Ordering::SeqCst), 1);
+        assert_eq!(kv.meta_reads.load(Ordering::SeqCst), 1);
+
+        // a user changed by another node is read again
+        let other = UserMgr::create(kv.clone(), "tenant1")?;
+        other
+            .update_user(identity.clone(), Some(AuthInfo::None), None, None)
+            .await?;
+        let updated = cached.get_user(identity, None).await?;
+        assert!(updated.seq > user.seq);
+        assert_eq!(updated.data.auth_info, AuthInfo::None);
+        assert_eq!(kv.reads.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+}
+
+mod concurrent_lookups {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    use common_meta_embedded::MetaEmbedded;
+    use common_meta_types::UserInfo;
+
+    use super::*;
+
+    // A KVApi whose reads take a round trip, and which records the max number of reads in flight.
+    struct SlowKV {
+        inner: MetaEmbedded,
+        delay: Duration,
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl KVApi for SlowKV {
+        async fn upsert_kv(&self, act: UpsertKVAction) -> Result<UpsertKVActionReply, MetaError> {
+            self.inner.upsert_kv(act).await
+        }
+
+        async fn upsert_kv_batch(
+            &self,
+            actions: Vec<UpsertKVAction>,
+        ) -> Result<UpsertKVBatchReply, MetaError> {
+            self.inner.upsert_kv_batch(actions).await
+        }
+
+        async fn get_kv(&self, key: &str) -> Result<GetKVActionReply, MetaError> {
+            let n = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(n, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            self.inner.get_kv(key).await
+        }
+
+        async fn get_kv_meta(&self, key: &str) -> Result<GetKVMetaReply, MetaError> {
+            self.inner.get_kv_meta(key).await
+        }
+
+        async fn delete_kv(&self, key: &str, seq: MatchSeq) -> Result<DeleteKVReply, MetaError> {
+            self.inner.delete_kv(key, seq).await
+        }
+
+        async fn delete_prefix(&self, prefix: &str) -> Result<u64, MetaError> {
+            self.inner.delete_prefix(prefix).await
+        }
+
+        async fn incr_kv(
+            &self,
+            key: &str,
+            delta: i64,
+            seq: MatchSeq,
+        ) -> Result<IncrKVReply, MetaError> {
+            self.inner.incr_kv(key, delta, seq).await
+        }
+
+        async fn mget_kv(&self, keys: &[String]) -> Result<MGetKVActionReply, MetaError> {
+            self.inner.mget_kv(keys).await
+        }
+
+        async fn prefix_list_kv(&self, prefix: &str) -> Result<PrefixListReply, MetaError> {
+            self.inner.prefix_list_kv(prefix).await
+        }
+
+        async fn prefix_list_kv_paged(
+            &self,
+            prefix: &str,
+            after_key: Option<String>,
+            limit: usize,
+            keys_only: bool,
+        ) -> Result<PrefixListPage, MetaError> {
+            self.inner
+                .prefix_list_kv_paged(prefix, after_key, limit, keys_only)
+                .await
+        }
+
+        async fn transaction(&self, txn: TxnRequest) -> Result<TxnReply, MetaError> {
+            self.inner.transaction(txn).await
+        }
+
+        async fn watch(
+            &self,
+            prefix: &str,
+            from_seq: Option<u64>,
+        ) -> Result<WatchStream, MetaError> {
+            self.inner.watch(prefix, from_seq).await
+        }
+    }
+
+    // The user lookups are async all the way down to the KVApi: a lookup waiting for the meta
+    // service does not hold a worker, thus the lookups of many sessions overlap even with a single
+    // worker thread.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_concurrent_get_user_not_serialized() -> common_exception::Result<()> {
+        let delay = Duration::from_millis(100);
+        let n = 50;
+
+        let kv = Arc::new(SlowKV {
+            inner: MetaEmbedded::new_temp().await?,
+            delay,
+            in_flight: AtomicUsize::new(0),
+            max_in_flight: AtomicUsize::new(0),
+        });
+        let user_mgr = Arc::new(UserMgr::create(kv.clone(), "tenant1")?);
+        for i in 0..n {
+            let user_info =
+                UserInfo::new(format!("u{}", i), "%".to_string(), default_test_auth_info());
+            user_mgr.add_user(user_info, false).await?;
+        }
+
+        let start = Instant::now();
+        let handles = (0..n)
+            .map(|i| {
+                let user_mgr = user_mgr.clone();
+                tokio::spawn(async move {
+                    user_mgr
+                        .get_user(UserIdentity::new(&format!("u{}", i), "%"), None)
+                        .await
+                })
+            })
+            .collect::<Vec<_>>();
+        for (i, h) in handles.into_iter().enumerate() {
+            let user = h.await.unwrap()?;
+            assert_eq!(user.data.name, format!("u{}", i));
+        }
+        let elapsed = start.elapsed();
+
+        // Serialized lookups would take `n * delay`, i.e., 5 seconds.
+        assert!(elapsed < delay * 10, "took {:?}", elapsed);
+        assert!(kv.max_in_flight.load(Ordering::SeqCst) > 1);
+        Ok(())
+    }
+}
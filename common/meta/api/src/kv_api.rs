@@ -13,15 +13,58 @@
 //  limitations under the License.
 //
 
+use std::collections::HashMap;
+use std::future::Future;
 use std::ops::Deref;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 use async_trait::async_trait;
+use common_base::tokio;
+use common_meta_types::DeleteKVReply;
 use common_meta_types::GetKVActionReply;
+use common_meta_types::GetKVMetaReply;
+use common_meta_types::IncrKVReply;
+use common_meta_types::KVHistoryReply;
+use common_meta_types::KVMeta;
 use common_meta_types::MGetKVActionReply;
+use common_meta_types::MatchSeq;
 use common_meta_types::MetaError;
+use common_meta_types::MetaNetworkError;
+use common_meta_types::MetaResultError;
+use common_meta_types::Operation;
+use common_meta_types::PrefixListPage;
 use common_meta_types::PrefixListReply;
+use common_meta_types::ReadConsistency;
+use common_meta_types::SeqV;
+use common_meta_types::TxnReply;
+use common_meta_types::TxnRequest;
 use common_meta_types::UpsertKVAction;
 use common_meta_types::UpsertKVActionReply;
+use common_meta_types::UpsertKVBatchReply;
+use common_meta_types::WatchEvent;
+use futures::stream::BoxStream;
+
+/// The stream of the changes under a watched prefix, in the order they are applied.
+pub type WatchStream = BoxStream<'static, Result<WatchEvent, MetaError>>;
+
+/// Gives up the `request` of the `action` with a `MetaNetworkError::Timeout` if it is not
+/// completed in `timeout`.
+pub async fn with_deadline<R>(
+    action: &str,
+    timeout: Duration,
+    request: impl Future<Output = Result<R, MetaError>>,
+) -> Result<R, MetaError> {
+    match tokio::time::timeout(timeout, request).await {
+        Ok(res) => res,
+        Err(_) => Err(MetaNetworkError::Timeout(format!(
+            "{} is not completed in {:?}",
+            action, timeout
+        ))
+        .into()),
+    }
+}
 
 #[async_trait]
 pub trait KVApiBuilder<T>
@@ -38,12 +81,188 @@ where T: KVApi
 pub trait KVApi: Send + Sync {
     async fn upsert_kv(&self, act: UpsertKVAction) -> Result<UpsertKVActionReply, MetaError>;
 
+    /// Upserts the keys in order, each by its own seq, and returns the change of every action,
+    /// in the same order.
+    ///
+    /// It is not atomic: an action whose seq does not match leaves its key unchanged, the others
+    /// are applied still. The actions are applied in batches of a bounded size, every batch by one
+    /// raft log.
+    async fn upsert_kv_batch(
+        &self,
+        actions: Vec<UpsertKVAction>,
+    ) -> Result<UpsertKVBatchReply, MetaError>;
+
+    /// Upserts a value that is treated as absent once `ttl` elapses, in a resolution of seconds.
+    async fn upsert_kv_with_ttl(
+        &self,
+        key: &str,
+        seq: MatchSeq,
+        value: Vec<u8>,
+        ttl: Duration,
+    ) -> Result<UpsertKVActionReply, MetaError> {
+        let expire_at = (SystemTime::now().duration_since(UNIX_EPOCH).unwrap() + ttl).as_secs();
+
+        self.upsert_kv(UpsertKVAction::new(
+            key,
+            seq,
+            Operation::Update(value),
+            Some(KVMeta {
+                expire_at: Some(expire_at),
+            }),
+        ))
+        .await
+    }
+
     async fn get_kv(&self, key: &str) -> Result<GetKVActionReply, MetaError>;
 
+    /// Returns the seq and the meta of `key` without the value, or `None` if it is absent.
+    ///
+    /// It is cheaper than `get_kv` to check whether a key has changed since a seq.
+    async fn get_kv_meta(&self, key: &str) -> Result<GetKVMetaReply, MetaError>;
+
+    /// Returns at most `limit` of the recent versions of `key`, newest first.
+    ///
+    /// Only the keys under the prefixes configured on the server keep a history, it is empty for
+    /// any other key, and for every key of an implementation that keeps no history.
+    async fn get_kv_history(&self, key: &str, limit: usize) -> Result<KVHistoryReply, MetaError> {
+        let _ = (key, limit);
+        Ok(vec![])
+    }
+
+    /// Deletes `key` if its seq matches `seq`.
+    async fn delete_kv(&self, key: &str, seq: MatchSeq) -> Result<DeleteKVReply, MetaError>;
+
+    /// Deletes all the keys starting with `prefix` and returns the number of the removed keys.
+    ///
+    /// It is not atomic: the keys are removed in chunks.
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64, MetaError>;
+
+    /// Adds `delta` to the i64 counter at `key` atomically, if the seq of the key matches `seq`.
+    ///
+    /// An absent key is a counter of 0, so it is created if `seq` allows an absent key.
+    /// Returns the seq and the value of the counter after the addition.
+    async fn incr_kv(&self, key: &str, delta: i64, seq: MatchSeq)
+        -> Result<IncrKVReply, MetaError>;
+
+    /// Returns exactly one value for every key in `key`, in the same order, with `None` for an
+    /// absent key. A duplicated key gets its value at every position it appears.
     // mockall complains about AsRef... so we use String here
     async fn mget_kv(&self, key: &[String]) -> Result<MGetKVActionReply, MetaError>;
 
+    /// Returns the values of the present keys among `keys`, by key.
+    async fn mget_kv_map(
+        &self,
+        keys: &[String],
+    ) -> Result<HashMap<String, SeqV<Vec<u8>>>, MetaError> {
+        let values = self.mget_kv(keys).await?;
+        if values.len() != keys.len() {
+            return Err(MetaResultError::InvalidMGetResult {
+                expect: keys.len(),
+                got: values.len(),
+            }
+            .into());
+        }
+
+        Ok(keys
+            .iter()
+            .zip(values)
+            .filter_map(|(key, value)| value.map(|v| (key.clone(), v)))
+            .collect())
+    }
+
     async fn prefix_list_kv(&self, prefix: &str) -> Result<PrefixListReply, MetaError>;
+
+    /// Lists at most `limit` keys starting with `prefix`, after `after_key` if it is given.
+    ///
+    /// To list the next page, pass the `last_key` of a page as `after_key`.
+    async fn prefix_list_kv_paged(
+        &self,
+        prefix: &str,
+        after_key: Option<String>,
+        limit: usize,
+        keys_only: bool,
+    ) -> Result<PrefixListPage, MetaError>;
+
+    /// Executes the operations of one of the branches of `txn` atomically, by whether all the
+    /// conditions of it hold.
+    async fn transaction(&self, txn: TxnRequest) -> Result<TxnReply, MetaError>;
+
+    /// Watches the changes of the keys starting with `prefix`.
+    ///
+    /// With `from_seq`, the events since it that the server still keeps are replayed first, e.g.,
+    /// to resume a watch after reconnecting with the seq of the last received event plus one.
+    async fn watch(&self, prefix: &str, from_seq: Option<u64>) -> Result<WatchStream, MetaError>;
+
+    // The `*_with_deadline` ones are the same as the ones without, but give up with a
+    // `MetaNetworkError::Timeout` if not completed in `timeout`, instead of the default timeout of
+    // the implementation. A timed out write may have been applied or not.
+
+    async fn get_kv_with_deadline(
+        &self,
+        key: &str,
+        timeout: Duration,
+    ) -> Result<GetKVActionReply, MetaError> {
+        with_deadline("get_kv", timeout, self.get_kv(key)).await
+    }
+
+    async fn mget_kv_with_deadline(
+        &self,
+        keys: &[String],
+        timeout: Duration,
+    ) -> Result<MGetKVActionReply, MetaError> {
+        with_deadline("mget_kv", timeout, self.mget_kv(keys)).await
+    }
+
+    async fn prefix_list_kv_with_deadline(
+        &self,
+        prefix: &str,
+        timeout: Duration,
+    ) -> Result<PrefixListReply, MetaError> {
+        with_deadline("prefix_list_kv", timeout, self.prefix_list_kv(prefix)).await
+    }
+
+    async fn upsert_kv_with_deadline(
+        &self,
+        act: UpsertKVAction,
+        timeout: Duration,
+    ) -> Result<UpsertKVActionReply, MetaError> {
+        with_deadline("upsert_kv", timeout, self.upsert_kv(act)).await
+    }
+
+    async fn upsert_kv_batch_with_deadline(
+        &self,
+        actions: Vec<UpsertKVAction>,
+        timeout: Duration,
+    ) -> Result<UpsertKVBatchReply, MetaError> {
+        with_deadline("upsert_kv_batch", timeout, self.upsert_kv_batch(actions)).await
+    }
+
+    // The `*_with_consistency` ones are the same as the ones without, but read as up to date as
+    // `consistency` requires. A store without replicas is always up to date and ignores it.
+
+    async fn get_kv_with_consistency(
+        &self,
+        key: &str,
+        _consistency: ReadConsistency,
+    ) -> Result<GetKVActionReply, MetaError> {
+        self.get_kv(key).await
+    }
+
+    async fn mget_kv_with_consistency(
+        &self,
+        keys: &[String],
+        _consistency: ReadConsistency,
+    ) -> Result<MGetKVActionReply, MetaError> {
+        self.mget_kv(keys).await
+    }
+
+    async fn prefix_list_kv_with_consistency(
+        &self,
+        prefix: &str,
+        _consistency: ReadConsistency,
+    ) -> Result<PrefixListReply, MetaError> {
+        self.prefix_list_kv(prefix).await
+    }
 }
 
 #[async_trait]
@@ -52,10 +271,42 @@ impl<U: KVApi, T: Deref<Target = U> + Send + Sync> KVApi for T {
         self.deref().upsert_kv(act).await
     }
 
+    async fn upsert_kv_batch(
+        &self,
+        actions: Vec<UpsertKVAction>,
+    ) -> Result<UpsertKVBatchReply, MetaError> {
+        self.deref().upsert_kv_batch(actions).await
+    }
+
     async fn get_kv(&self, key: &str) -> Result<GetKVActionReply, MetaError> {
         self.deref().get_kv(key).await
     }
 
+    async fn get_kv_meta(&self, key: &str) -> Result<GetKVMetaReply, MetaError> {
+        self.deref().get_kv_meta(key).await
+    }
+
+    async fn get_kv_history(&self, key: &str, limit: usize) -> Result<KVHistoryReply, MetaError> {
+        self.deref().get_kv_history(key, limit).await
+    }
+
+    async fn delete_kv(&self, key: &str, seq: MatchSeq) -> Result<DeleteKVReply, MetaError> {
+        self.deref().delete_kv(key, seq).await
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64, MetaError> {
+        self.deref().delete_prefix(prefix).await
+    }
+
+    async fn incr_kv(
+        &self,
+        key: &str,
+        delta: i64,
+        seq: MatchSeq,
+    ) -> Result<IncrKVReply, MetaError> {
+        self.deref().incr_kv(key, delta, seq).await
+    }
+
     async fn mget_kv(&self, key: &[String]) -> Result<MGetKVActionReply, MetaError> {
         self.deref().mget_kv(key).await
     }
@@ -63,4 +314,96 @@ impl<U: KVApi, T: Deref<Target = U> + Send + Sync> KVApi for T {
     async fn prefix_list_kv(&self, prefix: &str) -> Result<PrefixListReply, MetaError> {
         self.deref().prefix_list_kv(prefix).await
     }
+
+    async fn prefix_list_kv_paged(
+        &self,
+        prefix: &str,
+        after_key: Option<String>,
+        limit: usize,
+        keys_only: bool,
+    ) -> Result<PrefixListPage, MetaError> {
+        self.deref()
+            .prefix_list_kv_paged(prefix, after_key, limit, keys_only)
+            .await
+    }
+
+    async fn transaction(&self, txn: TxnRequest) -> Result<TxnReply, MetaError> {
+        self.deref().transaction(txn).await
+    }
+
+    async fn watch(&self, prefix: &str, from_seq: Option<u64>) -> Result<WatchStream, MetaError> {
+        self.deref().watch(prefix, from_seq).await
+    }
+
+    async fn get_kv_with_deadline(
+        &self,
+        key: &str,
+        timeout: Duration,
+    ) -> Result<GetKVActionReply, MetaError> {
+        self.deref().get_kv_with_deadline(key, timeout).await
+    }
+
+    async fn mget_kv_with_deadline(
+        &self,
+        keys: &[String],
+        timeout: Duration,
+    ) -> Result<MGetKVActionReply, MetaError> {
+        self.deref().mget_kv_with_deadline(keys, timeout).await
+    }
+
+    async fn prefix_list_kv_with_deadline(
+        &self,
+        prefix: &str,
+        timeout: Duration,
+    ) -> Result<PrefixListReply, MetaError> {
+        self.deref()
+            .prefix_list_kv_with_deadline(prefix, timeout)
+            .await
+    }
+
+    async fn upsert_kv_with_deadline(
+        &self,
+        act: UpsertKVAction,
+        timeout: Duration,
+    ) -> Result<UpsertKVActionReply, MetaError> {
+        self.deref().upsert_kv_with_deadline(act, timeout).await
+    }
+
+    async fn upsert_kv_batch_with_deadline(
+        &self,
+        actions: Vec<UpsertKVAction>,
+        timeout: Duration,
+    ) -> Result<UpsertKVBatchReply, MetaError> {
+        self.deref()
+            .upsert_kv_batch_with_deadline(actions, timeout)
+            .await
+    }
+
+    async fn get_kv_with_consistency(
+        &self,
+        key: &str,
+        consistency: ReadConsistency,
+    ) -> Result<GetKVActionReply, MetaError> {
+        self.deref().get_kv_with_consistency(key, consistency).await
+    }
+
+    async fn mget_kv_with_consistency(
+        &self,
+        keys: &[String],
+        consistency: ReadConsistency,
+    ) -> Result<MGetKVActionReply, MetaError> {
+        self.deref()
+            .mget_kv_with_consistency(keys, consistency)
+            .await
+    }
+
+    async fn prefix_list_kv_with_consistency(
+        &self,
+        prefix: &str,
+        consistency: ReadConsistency,
+    ) -> Result<PrefixListReply, MetaError> {
+        self.deref()
+            .prefix_list_kv_with_consistency(prefix, consistency)
+            .await
+    }
 }
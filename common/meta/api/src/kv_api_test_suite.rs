@@ -12,15 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
 use common_base::tokio;
+use common_meta_types::encode_kv_counter;
+use common_meta_types::AppError;
 use common_meta_types::KVMeta;
 use common_meta_types::MatchSeq;
+use common_meta_types::MetaError;
 use common_meta_types::Operation;
 use common_meta_types::SeqV;
+use common_meta_types::TxnCondition;
+use common_meta_types::TxnOp;
+use common_meta_types::TxnOpResponse;
+use common_meta_types::TxnRequest;
 use common_meta_types::UpsertKVAction;
+use common_meta_types::UPSERT_KV_BATCH_MAX_ACTIONS;
 use common_tracing::tracing;
 
 use crate::KVApi;
@@ -37,11 +46,18 @@ impl KVApiTestSuite {
     {
         self.kv_write_read(&builder.build().await).await?;
         self.kv_delete(&builder.build().await).await?;
+        self.kv_delete_kv_and_prefix(&builder.build().await).await?;
         self.kv_update(&builder.build().await).await?;
         self.kv_timeout(&builder.build().await).await?;
+        self.kv_upsert_with_ttl(&builder.build().await).await?;
         self.kv_meta(&builder.build().await).await?;
+        self.kv_get_meta(&builder.build().await).await?;
         self.kv_list(&builder.build().await).await?;
+        self.kv_list_paged(&builder.build().await).await?;
         self.kv_mget(&builder.build().await).await?;
+        self.kv_transaction(&builder.build().await).await?;
+        self.kv_incr(&builder.build().await).await?;
+        self.kv_upsert_batch(&builder.build().await).await?;
 
         // Run cross node test on every 2 adjacent nodes
 
@@ -179,7 +195,7 @@ impl KVApiTestSuite {
         assert_eq!(None, res.prev);
         assert_eq!(None, res.result);
 
-        // do not care seq
+        // do not care seq, the deletion above took seq 2
         kv.upsert_kv(UpsertKVAction::new(
             test_key,
             MatchSeq::Any,
@@ -197,13 +213,74 @@ impl KVApiTestSuite {
             ))
             .await?;
         assert_eq!(
-            (Some(SeqV::with_meta(2, None, b"v2".to_vec())), None),
+            (Some(SeqV::with_meta(3, None, b"v2".to_vec())), None),
             (res.prev, res.result)
         );
 
         Ok(())
     }
 
+    #[tracing::instrument(level = "info", skip(self, kv))]
+    pub async fn kv_delete_kv_and_prefix<KV: KVApi>(&self, kv: &KV) -> anyhow::Result<()> {
+        kv.upsert_kv(UpsertKVAction::new(
+            "dk",
+            MatchSeq::Any,
+            Operation::Update(b"v".to_vec()),
+            None,
+        ))
+        .await?;
+
+        tracing::info!("--- delete with unmatched seq conflicts");
+        {
+            let res = kv.delete_kv("dk", MatchSeq::Exact(2)).await?;
+            assert_eq!(
+                (
+                    Some(SeqV::new(1, b"v".to_vec())),
+                    Some(SeqV::new(1, b"v".to_vec()))
+                ),
+                (res.prev, res.result),
+                "nothing changed"
+            );
+            assert!(kv.get_kv("dk").await?.is_some());
+        }
+
+        tracing::info!("--- delete with matching seq");
+        {
+            let res = kv.delete_kv("dk", MatchSeq::Exact(1)).await?;
+            assert_eq!(
+                (Some(SeqV::new(1, b"v".to_vec())), None),
+                (res.prev, res.result)
+            );
+            assert!(kv.get_kv("dk").await?.is_none());
+        }
+
+        tracing::info!("--- delete by prefix");
+        {
+            for key in ["p/a", "p/b", "p/c/d", "p2/x", "q"] {
+                kv.upsert_kv(UpsertKVAction::new(
+                    key,
+                    MatchSeq::Any,
+                    Operation::Update(key.as_bytes().to_vec()),
+                    None,
+                ))
+                .await?;
+            }
+
+            let removed = kv.delete_prefix("p/").await?;
+            assert_eq!(3, removed);
+
+            let res = kv.prefix_list_kv("p").await?;
+            let keys = res.iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>();
+            assert_eq!(vec!["p2/x"], keys, "the sibling prefix is untouched");
+            assert!(kv.get_kv("q").await?.is_some());
+
+            let removed = kv.delete_prefix("p/").await?;
+            assert_eq!(0, removed);
+        }
+
+        Ok(())
+    }
+
     #[tracing::instrument(level = "info", skip(self, kv))]
     pub async fn kv_update<KV: KVApi>(&self, kv: &KV) -> anyhow::Result<()> {
         let test_key = "test_key_for_update";
@@ -379,6 +456,51 @@ impl KVApiTestSuite {
         Ok(())
     }
 
+    #[tracing::instrument(level = "info", skip(self, kv))]
+    pub async fn kv_upsert_with_ttl<KV: KVApi>(&self, kv: &KV) -> anyhow::Result<()> {
+        // - Test a value with ttl is visible before it expires, and invisible after that.
+        // - Test a CAS over an expired key behaves as a create.
+
+        let ttl = Duration::from_secs(1);
+
+        kv.upsert_kv_with_ttl("ttl_k1", MatchSeq::Any, b"v1".to_vec(), ttl)
+            .await?;
+
+        tracing::info!("--- visible before expiration");
+        {
+            let res = kv.get_kv("ttl_k1").await?;
+            assert_eq!(Some(b"v1".to_vec()), res.map(|x| x.data));
+
+            let res = kv.prefix_list_kv("ttl_").await?;
+            assert_eq!(1, res.len());
+        }
+
+        tokio::time::sleep(Duration::from_millis(2500)).await;
+
+        tracing::info!("--- invisible after expiration");
+        {
+            let res = kv.get_kv("ttl_k1").await?;
+            assert!(res.is_none(), "got expired: {:?}", res);
+
+            let res = kv.prefix_list_kv("ttl_").await?;
+            assert!(res.is_empty(), "listed expired: {:?}", res);
+        }
+
+        tracing::info!("--- CAS with seq 0 over an expired key creates it");
+        {
+            let res = kv
+                .upsert_kv_with_ttl("ttl_k1", MatchSeq::Exact(0), b"v2".to_vec(), ttl)
+                .await?;
+            assert_eq!(None, res.prev);
+            assert_eq!(Some(b"v2".to_vec()), res.result.map(|x| x.data));
+
+            let res = kv.get_kv("ttl_k1").await?;
+            assert_eq!(Some(b"v2".to_vec()), res.map(|x| x.data));
+        }
+
+        Ok(())
+    }
+
     #[tracing::instrument(level = "info", skip(self, kv))]
     pub async fn kv_meta<KV: KVApi>(&self, kv: &KV) -> anyhow::Result<()> {
         let test_key = "test_key_for_update_meta";
@@ -455,6 +577,66 @@ impl KVApiTestSuite {
         Ok(())
     }
 
+    #[tracing::instrument(level = "info", skip(self, kv))]
+    pub async fn kv_get_meta<KV: KVApi>(&self, kv: &KV) -> anyhow::Result<()> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        tracing::info!("--- absent key");
+        assert_eq!(None, kv.get_kv_meta("get_meta_absent").await?);
+
+        tracing::info!("--- key without meta");
+        kv.upsert_kv(UpsertKVAction::new(
+            "get_meta_plain",
+            MatchSeq::Any,
+            Operation::Update(b"v1".to_vec()),
+            None,
+        ))
+        .await?;
+        let got = kv.get_kv("get_meta_plain").await?.unwrap();
+        assert_eq!(
+            Some((got.seq, KVMeta::default())),
+            kv.get_kv_meta("get_meta_plain").await?
+        );
+
+        tracing::info!("--- key with meta");
+        let meta = KVMeta {
+            expire_at: Some(now + 20),
+        };
+        kv.upsert_kv(UpsertKVAction::new(
+            "get_meta_ttl",
+            MatchSeq::Any,
+            Operation::Update(b"v2".to_vec()),
+            Some(meta.clone()),
+        ))
+        .await?;
+        let got = kv.get_kv("get_meta_ttl").await?.unwrap();
+        assert_eq!(Some(meta.clone()), got.meta);
+        assert_eq!(Some((got.seq, meta)), kv.get_kv_meta("get_meta_ttl").await?);
+
+        tracing::info!("--- the seq changes with the value");
+        kv.upsert_kv(UpsertKVAction::new(
+            "get_meta_plain",
+            MatchSeq::Any,
+            Operation::Update(b"v3".to_vec()),
+            None,
+        ))
+        .await?;
+        let got = kv.get_kv("get_meta_plain").await?.unwrap();
+        assert_eq!(
+            Some((got.seq, KVMeta::default())),
+            kv.get_kv_meta("get_meta_plain").await?
+        );
+
+        tracing::info!("--- deleted key");
+        kv.delete_kv("get_meta_plain", MatchSeq::Any).await?;
+        assert_eq!(None, kv.get_kv_meta("get_meta_plain").await?);
+
+        Ok(())
+    }
+
     #[tracing::instrument(level = "info", skip(self, kv))]
     pub async fn kv_list<KV: KVApi>(&self, kv: &KV) -> anyhow::Result<()> {
         let mut values = vec![];
@@ -501,6 +683,82 @@ impl KVApiTestSuite {
         Ok(())
     }
 
+    #[tracing::instrument(level = "info", skip(self, kv))]
+    pub async fn kv_list_paged<KV: KVApi>(&self, kv: &KV) -> anyhow::Result<()> {
+        let n = 10_000;
+        let keys = (0..n).map(|i| format!("pg/{:05}", i)).collect::<Vec<_>>();
+
+        // Write in batches, with keys just outside of the prefix on both sides.
+        for chunk in keys.chunks(1000) {
+            kv.transaction(TxnRequest {
+                condition: vec![],
+                if_then: chunk
+                    .iter()
+                    .map(|k| TxnOp::put(k, k.as_bytes().to_vec()))
+                    .collect(),
+                else_then: vec![],
+            })
+            .await?;
+        }
+        for k in ["pg.", "pg0", "pf/x"] {
+            kv.upsert_kv(UpsertKVAction::new(
+                k,
+                MatchSeq::Any,
+                Operation::Update(b"x".to_vec()),
+                None,
+            ))
+            .await?;
+        }
+
+        for (limit, keys_only) in [
+            (7, true),
+            (37, false),
+            (1000, true),
+            (n, false),
+            (n + 1, true),
+        ] {
+            tracing::info!("--- list pages of {} keys, keys_only: {}", limit, keys_only);
+
+            let mut got = vec![];
+            let mut after_key = None;
+            let mut pages = 0;
+            loop {
+                let page = kv
+                    .prefix_list_kv_paged("pg/", after_key, limit, keys_only)
+                    .await?;
+                pages += 1;
+                assert!(page.items.len() <= limit);
+
+                for (k, v) in page.items {
+                    if keys_only {
+                        assert!(v.is_none(), "no value for keys_only");
+                    } else {
+                        assert_eq!(Some(k.as_bytes().to_vec()), v.map(|x| x.data));
+                    }
+                    got.push(k);
+                }
+
+                if page.last_key.is_none() {
+                    break;
+                }
+                assert_eq!(got.last(), page.last_key.as_ref());
+                after_key = page.last_key;
+            }
+
+            assert_eq!(keys, got, "no key is missed or duplicated");
+            assert_eq!((n + limit - 1) / limit, pages);
+        }
+
+        tracing::info!("--- the unpaged list returns all");
+        {
+            let res = kv.prefix_list_kv("pg/").await?;
+            let got = res.into_iter().map(|(k, _)| k).collect::<Vec<_>>();
+            assert_eq!(keys, got);
+        }
+
+        Ok(())
+    }
+
     #[tracing::instrument(level = "info", skip(self, kv))]
     pub async fn kv_mget<KV: KVApi>(&self, kv: &KV) -> anyhow::Result<()> {
         kv.upsert_kv(UpsertKVAction::new(
@@ -530,6 +788,281 @@ impl KVApiTestSuite {
             .await?;
         assert_eq!(res, vec![Some(SeqV::new(1, b"v1".to_vec())), None]);
 
+        tracing::info!("--- one value per key, in the order of the keys");
+        let keys = ["k2", "absent1", "k1", "k2", "absent2", "absent1", "k1"]
+            .iter()
+            .map(|k| k.to_string())
+            .collect::<Vec<_>>();
+        let res = kv.mget_kv(&keys).await?;
+        let v1 = Some(SeqV::new(1, b"v1".to_vec()));
+        let v2 = Some(SeqV::new(2, b"v2".to_vec()));
+        assert_eq!(res, vec![
+            v2.clone(),
+            None,
+            v1.clone(),
+            v2.clone(),
+            None,
+            None,
+            v1.clone()
+        ]);
+
+        let res = kv.mget_kv(&[]).await?;
+        assert!(res.is_empty());
+
+        tracing::info!("--- mget_kv_map contains only the present keys");
+        let res = kv.mget_kv_map(&keys).await?;
+        assert_eq!(res.len(), 2);
+        assert_eq!(res.get("k1").cloned(), v1);
+        assert_eq!(res.get("k2").cloned(), v2);
+        assert!(!res.contains_key("absent1"));
+
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "info", skip(self, kv))]
+    pub async fn kv_upsert_batch<KV: KVApi>(&self, kv: &KV) -> anyhow::Result<()> {
+        let upsert = |key: &str, seq: MatchSeq, value: &str| {
+            UpsertKVAction::new(key, seq, Operation::Update(value.as_bytes().to_vec()), None)
+        };
+
+        tracing::info!("--- empty batch");
+        assert!(kv.upsert_kv_batch(vec![]).await?.is_empty());
+
+        let r = kv.upsert_kv(upsert("b1", MatchSeq::Any, "v1")).await?;
+        let b1_seq = r.result.unwrap().seq;
+
+        tracing::info!("--- every action gets its own result, a conflict does not stop the others");
+        let changes = kv
+            .upsert_kv_batch(vec![
+                upsert("b1", MatchSeq::Exact(0), "conflict"),
+                upsert("b2", MatchSeq::Exact(0), "v2"),
+                upsert("b1", MatchSeq::Exact(b1_seq + 100), "conflict"),
+                upsert("b1", MatchSeq::Exact(b1_seq), "v1-new"),
+                UpsertKVAction::new("b2", MatchSeq::GE(1), Operation::Delete, None),
+                upsert("b3", MatchSeq::GE(1), "absent"),
+            ])
+            .await?;
+        assert_eq!(6, changes.len());
+
+        // conflicting add
+        assert!(!changes[0].changed());
+        assert_eq!(Some(b1_seq), changes[0].result.as_ref().map(|v| v.seq));
+        // add
+        assert_eq!(None, changes[1].prev);
+        assert_eq!(
+            Some(b"v2".to_vec()),
+            changes[1].result.as_ref().map(|v| v.data.clone())
+        );
+        // conflicting update
+        assert!(!changes[2].changed());
+        // update
+        assert_eq!(Some(b1_seq), changes[3].prev.as_ref().map(|v| v.seq));
+        let b1_new = changes[3].result.clone().unwrap();
+        assert!(b1_new.seq > b1_seq);
+        assert_eq!(b"v1-new".to_vec(), b1_new.data);
+        // delete of the key added in the same batch
+        assert_eq!(changes[1].result, changes[4].prev);
+        assert_eq!(None, changes[4].result);
+        // update of an absent key
+        assert!(!changes[5].changed());
+        assert_eq!(None, changes[5].result);
+
+        assert_eq!(Some(b1_new), kv.get_kv("b1").await?);
+        assert_eq!(None, kv.get_kv("b2").await?);
+        assert_eq!(None, kv.get_kv("b3").await?);
+
+        tracing::info!("--- a batch larger than the limit is split");
+        let n = UPSERT_KV_BATCH_MAX_ACTIONS + 10;
+        let actions = (0..n)
+            .map(|i| upsert(&format!("big/{:05}", i), MatchSeq::Exact(0), "v"))
+            .collect::<Vec<_>>();
+        let changes = kv.upsert_kv_batch(actions).await?;
+        assert_eq!(n, changes.len());
+        assert!(changes
+            .iter()
+            .all(|ch| ch.prev.is_none() && ch.result.is_some()));
+        assert_eq!(n, kv.prefix_list_kv("big/").await?.len());
+
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "info", skip(self, kv))]
+    pub async fn kv_incr<KV: KVApi>(&self, kv: &KV) -> anyhow::Result<()> {
+        tracing::info!("--- an absent counter is created");
+        let (seq, value) = kv.incr_kv("cnt", 5, MatchSeq::Exact(0)).await?;
+        assert_eq!(5, value);
+        assert_eq!(
+            Some(SeqV::new(seq, encode_kv_counter(5))),
+            kv.get_kv("cnt").await?
+        );
+
+        let (seq, value) = kv.incr_kv("cnt", -7, MatchSeq::Exact(seq)).await?;
+        assert_eq!(-2, value);
+
+        tracing::info!("--- seq mismatch");
+        let res = kv.incr_kv("cnt", 1, MatchSeq::Exact(0)).await;
+        assert!(matches!(
+            res.unwrap_err(),
+            MetaError::AppError(AppError::KVSeqMismatched(_))
+        ));
+        let res = kv.incr_kv("absent", 1, MatchSeq::GE(1)).await;
+        assert!(matches!(
+            res.unwrap_err(),
+            MetaError::AppError(AppError::KVSeqMismatched(_))
+        ));
+        assert_eq!(None, kv.get_kv("absent").await?);
+
+        tracing::info!("--- not a counter, or overflow");
+        kv.upsert_kv(UpsertKVAction::new(
+            "not_cnt",
+            MatchSeq::Any,
+            Operation::Update(b"foo".to_vec()),
+            None,
+        ))
+        .await?;
+        let res = kv.incr_kv("not_cnt", 1, MatchSeq::Any).await;
+        assert!(matches!(
+            res.unwrap_err(),
+            MetaError::AppError(AppError::IllegalKVCounter(_))
+        ));
+
+        kv.incr_kv("max", i64::MAX, MatchSeq::Any).await?;
+        let res = kv.incr_kv("max", 1, MatchSeq::Any).await;
+        assert!(matches!(
+            res.unwrap_err(),
+            MetaError::AppError(AppError::IllegalKVCounter(_))
+        ));
+        let (_, value) = kv.incr_kv("cnt", 0, MatchSeq::Any).await?;
+        assert_eq!(-2, value, "a failed incr changes nothing");
+
+        tracing::info!("--- concurrent increments are not lost");
+        let n = 100;
+        let futs = (0..n).map(|_| kv.incr_kv("concurrent", 1, MatchSeq::Any));
+        let mut values = vec![];
+        for res in futures::future::join_all(futs).await {
+            values.push(res?.1);
+        }
+        values.sort_unstable();
+        assert_eq!((1..=n).collect::<Vec<_>>(), values);
+
+        let (_, value) = kv.incr_kv("concurrent", 0, MatchSeq::Any).await?;
+        assert_eq!(n, value);
+
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "info", skip(self, kv))]
+    pub async fn kv_transaction<KV: KVApi>(&self, kv: &KV) -> anyhow::Result<()> {
+        kv.upsert_kv(UpsertKVAction::new(
+            "txn_a",
+            MatchSeq::Any,
+            Operation::Update(b"a".to_vec()),
+            None,
+        ))
+        .await?;
+        kv.upsert_kv(UpsertKVAction::new(
+            "txn_b",
+            MatchSeq::Any,
+            Operation::Update(b"b".to_vec()),
+            None,
+        ))
+        .await?;
+
+        tracing::info!("--- swap two keys if both are unchanged");
+        {
+            let res = kv
+                .transaction(TxnRequest {
+                    condition: vec![
+                        TxnCondition::new("txn_a", MatchSeq::Exact(1)),
+                        TxnCondition::new("txn_b", MatchSeq::Exact(2)),
+                    ],
+                    if_then: vec![
+                        TxnOp::put("txn_a", b"b".to_vec()),
+                        TxnOp::put("txn_b", b"a".to_vec()),
+                    ],
+                    else_then: vec![],
+                })
+                .await?;
+            assert!(res.success);
+            assert_eq!(2, res.responses.len());
+
+            let res = kv
+                .mget_kv(&["txn_a".to_string(), "txn_b".to_string()])
+                .await?;
+            assert_eq!(res, vec![
+                Some(SeqV::new(3, b"b".to_vec())),
+                Some(SeqV::new(4, b"a".to_vec())),
+            ]);
+        }
+
+        tracing::info!("--- a stale seq takes the else branch");
+        {
+            let res = kv
+                .transaction(TxnRequest {
+                    condition: vec![TxnCondition::new("txn_a", MatchSeq::Exact(1))],
+                    if_then: vec![TxnOp::delete("txn_a")],
+                    else_then: vec![TxnOp::get("txn_a")],
+                })
+                .await?;
+            assert!(!res.success);
+            assert_eq!(res.responses, vec![TxnOpResponse::Get(Some(SeqV::new(
+                3,
+                b"b".to_vec()
+            )))]);
+
+            let res = kv.get_kv("txn_a").await?;
+            assert_eq!(Some(SeqV::new(3, b"b".to_vec())), res, "nothing changed");
+        }
+
+        tracing::info!("--- readers never see a partially applied transaction");
+        {
+            let keys = ["txn_a".to_string(), "txn_b".to_string()];
+
+            kv.transaction(TxnRequest {
+                condition: vec![],
+                if_then: vec![
+                    TxnOp::put("txn_a", b"x".to_vec()),
+                    TxnOp::put("txn_b", b"x".to_vec()),
+                ],
+                else_then: vec![],
+            })
+            .await?;
+
+            let write = async {
+                for i in 0..20u8 {
+                    kv.transaction(TxnRequest {
+                        condition: vec![],
+                        if_then: vec![
+                            TxnOp::delete("txn_a"),
+                            TxnOp::put("txn_b", vec![i]),
+                            TxnOp::put("txn_a", vec![i]),
+                        ],
+                        else_then: vec![],
+                    })
+                    .await?;
+                }
+                Ok::<(), anyhow::Error>(())
+            };
+
+            let read = async {
+                for _ in 0..20 {
+                    let res = kv.mget_kv(&keys).await?;
+                    let values = res
+                        .into_iter()
+                        .map(|x| x.map(|v| v.data))
+                        .collect::<Vec<_>>();
+                    assert!(values[0].is_some(), "txn_a is never seen deleted");
+                    assert_eq!(values[0], values[1], "both keys are from one transaction");
+                    tokio::task::yield_now().await;
+                }
+                Ok::<(), anyhow::Error>(())
+            };
+
+            let (w, r) = tokio::join!(write, read);
+            w?;
+            r?;
+        }
+
         Ok(())
     }
 }
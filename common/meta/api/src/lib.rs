@@ -20,8 +20,10 @@ mod kv_api_test_suite;
 mod meta_api;
 mod meta_api_test_suite;
 
+pub use kv_api::with_deadline;
 pub use kv_api::KVApi;
 pub use kv_api::KVApiBuilder;
+pub use kv_api::WatchStream;
 pub use kv_api_test_suite::KVApiTestSuite;
 pub use meta_api::MetaApi;
 pub use meta_api_test_suite::MetaApiTestSuite;
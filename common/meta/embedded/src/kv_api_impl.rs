@@ -14,13 +14,23 @@
 
 use async_trait::async_trait;
 use common_meta_api::KVApi;
+use common_meta_api::WatchStream;
 pub use common_meta_sled_store::init_temp_sled_db;
+use common_meta_types::DeleteKVReply;
 use common_meta_types::GetKVActionReply;
+use common_meta_types::GetKVMetaReply;
+use common_meta_types::IncrKVReply;
+use common_meta_types::KVHistoryReply;
 use common_meta_types::MGetKVActionReply;
+use common_meta_types::MatchSeq;
 use common_meta_types::MetaError;
+use common_meta_types::PrefixListPage;
 use common_meta_types::PrefixListReply;
+use common_meta_types::TxnReply;
+use common_meta_types::TxnRequest;
 use common_meta_types::UpsertKVAction;
 use common_meta_types::UpsertKVActionReply;
+use common_meta_types::UpsertKVBatchReply;
 
 use crate::MetaEmbedded;
 
@@ -31,11 +41,49 @@ impl KVApi for MetaEmbedded {
         sm.upsert_kv(act).await
     }
 
+    async fn upsert_kv_batch(
+        &self,
+        actions: Vec<UpsertKVAction>,
+    ) -> Result<UpsertKVBatchReply, MetaError> {
+        let sm = self.inner.lock().await;
+        sm.upsert_kv_batch(actions).await
+    }
+
     async fn get_kv(&self, key: &str) -> Result<GetKVActionReply, MetaError> {
         let sm = self.inner.lock().await;
         sm.get_kv(key).await
     }
 
+    async fn get_kv_meta(&self, key: &str) -> Result<GetKVMetaReply, MetaError> {
+        let sm = self.inner.lock().await;
+        sm.get_kv_meta(key).await
+    }
+
+    async fn get_kv_history(&self, key: &str, limit: usize) -> Result<KVHistoryReply, MetaError> {
+        let sm = self.inner.lock().await;
+        sm.get_kv_history(key, limit).await
+    }
+
+    async fn delete_kv(&self, key: &str, seq: MatchSeq) -> Result<DeleteKVReply, MetaError> {
+        let sm = self.inner.lock().await;
+        sm.delete_kv(key, seq).await
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64, MetaError> {
+        let sm = self.inner.lock().await;
+        sm.delete_prefix(prefix).await
+    }
+
+    async fn incr_kv(
+        &self,
+        key: &str,
+        delta: i64,
+        seq: MatchSeq,
+    ) -> Result<IncrKVReply, MetaError> {
+        let sm = self.inner.lock().await;
+        sm.incr_kv(key, delta, seq).await
+    }
+
     async fn mget_kv(&self, key: &[String]) -> Result<MGetKVActionReply, MetaError> {
         let sm = self.inner.lock().await;
         sm.mget_kv(key).await
@@ -45,4 +93,26 @@ impl KVApi for MetaEmbedded {
         let sm = self.inner.lock().await;
         sm.prefix_list_kv(prefix).await
     }
+
+    async fn prefix_list_kv_paged(
+        &self,
+        prefix: &str,
+        after_key: Option<String>,
+        limit: usize,
+        keys_only: bool,
+    ) -> Result<PrefixListPage, MetaError> {
+        let sm = self.inner.lock().await;
+        sm.prefix_list_kv_paged(prefix, after_key, limit, keys_only)
+            .await
+    }
+
+    async fn transaction(&self, txn: TxnRequest) -> Result<TxnReply, MetaError> {
+        let sm = self.inner.lock().await;
+        sm.transaction(txn).await
+    }
+
+    async fn watch(&self, prefix: &str, from_seq: Option<u64>) -> Result<WatchStream, MetaError> {
+        let sm = self.inner.lock().await;
+        sm.watch(prefix, from_seq).await
+    }
 }
@@ -34,26 +34,68 @@ async fn test_kv_update() -> anyhow::Result<()> {
     KVApiTestSuite {}.kv_update(&kv).await
 }
 
+#[tokio::test]
+async fn test_kv_delete_kv_and_prefix() -> anyhow::Result<()> {
+    let kv = MetaEmbedded::new_temp().await?;
+    KVApiTestSuite {}.kv_delete_kv_and_prefix(&kv).await
+}
+
 #[tokio::test]
 async fn test_kv_timeout() -> anyhow::Result<()> {
     let kv = MetaEmbedded::new_temp().await?;
     KVApiTestSuite {}.kv_timeout(&kv).await
 }
 
+#[tokio::test]
+async fn test_kv_upsert_with_ttl() -> anyhow::Result<()> {
+    let kv = MetaEmbedded::new_temp().await?;
+    KVApiTestSuite {}.kv_upsert_with_ttl(&kv).await
+}
+
 #[tokio::test]
 async fn test_kv_meta() -> anyhow::Result<()> {
     let kv = MetaEmbedded::new_temp().await?;
     KVApiTestSuite {}.kv_meta(&kv).await
 }
 
+#[tokio::test]
+async fn test_kv_get_meta() -> anyhow::Result<()> {
+    let kv = MetaEmbedded::new_temp().await?;
+    KVApiTestSuite {}.kv_get_meta(&kv).await
+}
+
 #[tokio::test]
 async fn test_kv_list() -> anyhow::Result<()> {
     let kv = MetaEmbedded::new_temp().await?;
     KVApiTestSuite {}.kv_list(&kv).await
 }
 
+#[tokio::test]
+async fn test_kv_list_paged() -> anyhow::Result<()> {
+    let kv = MetaEmbedded::new_temp().await?;
+    KVApiTestSuite {}.kv_list_paged(&kv).await
+}
+
 #[tokio::test]
 async fn test_kv_mget() -> anyhow::Result<()> {
     let kv = MetaEmbedded::new_temp().await?;
     KVApiTestSuite {}.kv_mget(&kv).await
 }
+
+#[tokio::test]
+async fn test_kv_transaction() -> anyhow::Result<()> {
+    let kv = MetaEmbedded::new_temp().await?;
+    KVApiTestSuite {}.kv_transaction(&kv).await
+}
+
+#[tokio::test]
+async fn test_kv_incr() -> anyhow::Result<()> {
+    let kv = MetaEmbedded::new_temp().await?;
+    KVApiTestSuite {}.kv_incr(&kv).await
+}
+
+#[tokio::test]
+async fn test_kv_upsert_batch() -> anyhow::Result<()> {
+    let kv = MetaEmbedded::new_temp().await?;
+    KVApiTestSuite {}.kv_upsert_batch(&kv).await
+}
@@ -24,6 +24,9 @@ use common_meta_types::CreateShareReq;
 use common_meta_types::CreateTableReply;
 use common_meta_types::CreateTableReq;
 use common_meta_types::DatabaseInfo;
+use common_meta_types::DeleteKVByPrefixReq;
+use common_meta_types::DeleteKVReply;
+use common_meta_types::DeleteKVReq;
 use common_meta_types::DropDatabaseReply;
 use common_meta_types::DropDatabaseReq;
 use common_meta_types::DropShareReply;
@@ -32,19 +35,31 @@ use common_meta_types::DropTableReply;
 use common_meta_types::DropTableReq;
 use common_meta_types::GetDatabaseReq;
 use common_meta_types::GetKVActionReply;
+use common_meta_types::GetKVHistoryReq;
+use common_meta_types::GetKVMetaReply;
+use common_meta_types::GetKVMetaReq;
 use common_meta_types::GetShareReq;
 use common_meta_types::GetTableReq;
+use common_meta_types::IncrKVReply;
+use common_meta_types::IncrKVReq;
+use common_meta_types::KVHistoryReply;
 use common_meta_types::ListDatabaseReq;
+use common_meta_types::ListKVPageReq;
 use common_meta_types::ListTableReq;
 use common_meta_types::MGetKVActionReply;
 use common_meta_types::MetaId;
+use common_meta_types::PrefixListPage;
 use common_meta_types::PrefixListReply;
 use common_meta_types::RenameTableReply;
 use common_meta_types::RenameTableReq;
 use common_meta_types::ShareInfo;
 use common_meta_types::TableInfo;
+use common_meta_types::TxnReply;
+use common_meta_types::TxnRequest;
 use common_meta_types::UpsertKVAction;
 use common_meta_types::UpsertKVActionReply;
+use common_meta_types::UpsertKVBatchReply;
+use common_meta_types::UpsertKVBatchReq;
 use common_meta_types::UpsertTableOptionReply;
 use common_meta_types::UpsertTableOptionReq;
 use tonic::Request;
@@ -68,6 +83,11 @@ pub enum MetaGrpcWriteReq {
     DropShare(DropShareReq),
 
     UpsertKV(UpsertKVAction),
+    DeleteKV(DeleteKVReq),
+    DeleteKVByPrefix(DeleteKVByPrefixReq),
+    IncrKV(IncrKVReq),
+    UpsertKVBatch(UpsertKVBatchReq),
+    Transaction(TxnRequest),
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, derive_more::From)]
@@ -82,8 +102,103 @@ pub enum MetaGrpcReadReq {
     GetShare(GetShareReq),
 
     GetKV(GetKVAction),
+    GetKVMeta(GetKVMetaReq),
+    GetKVHistory(GetKVHistoryReq),
     MGetKV(MGetKVAction),
     PrefixListKV(PrefixListReq),
+    PrefixListKVPage(ListKVPageReq),
+}
+
+impl MetaGrpcWriteReq {
+    /// The name of the action, e.g., to tell which request is timed out.
+    pub fn name(&self) -> &'static str {
+        match self {
+            MetaGrpcWriteReq::CreateDatabase(_) => "CreateDatabase",
+            MetaGrpcWriteReq::DropDatabase(_) => "DropDatabase",
+            MetaGrpcWriteReq::CreateTable(_) => "CreateTable",
+            MetaGrpcWriteReq::DropTable(_) => "DropTable",
+            MetaGrpcWriteReq::RenameTable(_) => "RenameTable",
+            MetaGrpcWriteReq::CommitTable(_) => "CommitTable",
+            MetaGrpcWriteReq::CreateShare(_) => "CreateShare",
+            MetaGrpcWriteReq::DropShare(_) => "DropShare",
+            MetaGrpcWriteReq::UpsertKV(_) => "UpsertKV",
+            MetaGrpcWriteReq::DeleteKV(_) => "DeleteKV",
+            MetaGrpcWriteReq::DeleteKVByPrefix(_) => "DeleteKVByPrefix",
+            MetaGrpcWriteReq::IncrKV(_) => "IncrKV",
+            MetaGrpcWriteReq::UpsertKVBatch(_) => "UpsertKVBatch",
+            MetaGrpcWriteReq::Transaction(_) => "Transaction",
+        }
+    }
+
+    /// The key or the prefix of a kv request, for logging: the value is never included.
+    pub fn key(&self) -> Option<String> {
+        match self {
+            MetaGrpcWriteReq::UpsertKV(a) => Some(a.key.clone()),
+            MetaGrpcWriteReq::DeleteKV(a) => Some(a.key.clone()),
+            MetaGrpcWriteReq::DeleteKVByPrefix(a) => Some(format!("{}*", a.prefix)),
+            MetaGrpcWriteReq::IncrKV(a) => Some(a.key.clone()),
+            MetaGrpcWriteReq::UpsertKVBatch(a) => {
+                summarize_keys(a.actions.iter().map(|a| a.key.as_str()), a.actions.len())
+            }
+            _ => None,
+        }
+    }
+    /// Whether the server applies the request at most once if it is sent with a txid, in which
+    /// case it is safe to retry it with the same txid.
+    pub fn is_deduplicated(&self) -> bool {
+        matches!(
+            self,
+            MetaGrpcWriteReq::UpsertKV(_)
+                | MetaGrpcWriteReq::DeleteKV(_)
+                | MetaGrpcWriteReq::IncrKV(_)
+                | MetaGrpcWriteReq::UpsertKVBatch(_)
+                | MetaGrpcWriteReq::Transaction(_)
+        )
+    }
+}
+
+impl MetaGrpcReadReq {
+    /// The name of the action, e.g., to tell which request is timed out.
+    pub fn name(&self) -> &'static str {
+        match self {
+            MetaGrpcReadReq::GetDatabase(_) => "GetDatabase",
+            MetaGrpcReadReq::ListDatabases(_) => "ListDatabases",
+            MetaGrpcReadReq::GetTable(_) => "GetTable",
+            MetaGrpcReadReq::GetTableExt(_) => "GetTableExt",
+            MetaGrpcReadReq::ListTables(_) => "ListTables",
+            MetaGrpcReadReq::GetShare(_) => "GetShare",
+            MetaGrpcReadReq::GetKV(_) => "GetKV",
+            MetaGrpcReadReq::GetKVMeta(_) => "GetKVMeta",
+            MetaGrpcReadReq::GetKVHistory(_) => "GetKVHistory",
+            MetaGrpcReadReq::MGetKV(_) => "MGetKV",
+            MetaGrpcReadReq::PrefixListKV(_) => "PrefixListKV",
+            MetaGrpcReadReq::PrefixListKVPage(_) => "PrefixListKVPage",
+        }
+    }
+
+    /// The key or the prefix of a kv request, for logging.
+    pub fn key(&self) -> Option<String> {
+        match self {
+            MetaGrpcReadReq::GetKV(a) => Some(a.key.clone()),
+            MetaGrpcReadReq::GetKVMeta(a) => Some(a.key.clone()),
+            MetaGrpcReadReq::GetKVHistory(a) => Some(a.key.clone()),
+            MetaGrpcReadReq::MGetKV(a) => {
+                summarize_keys(a.keys.iter().map(|k| k.as_str()), a.keys.len())
+            }
+            MetaGrpcReadReq::PrefixListKV(a) => Some(format!("{}*", a.0)),
+            MetaGrpcReadReq::PrefixListKVPage(a) => Some(format!("{}*", a.prefix)),
+            _ => None,
+        }
+    }
+}
+
+// The first key and the number of the others, a batch may have thousands of keys.
+fn summarize_keys<'a>(mut keys: impl Iterator<Item = &'a str>, n: usize) -> Option<String> {
+    let first = keys.next()?;
+    match n {
+        1 => Some(first.to_string()),
+        _ => Some(format!("{} and {} more", first, n - 1)),
+    }
 }
 
 /// Try convert tonic::Request<RaftRequest> to DoActionAction.
@@ -161,6 +276,14 @@ impl RequestFor for GetKVAction {
     type Reply = GetKVActionReply;
 }
 
+impl RequestFor for GetKVMetaReq {
+    type Reply = GetKVMetaReply;
+}
+
+impl RequestFor for GetKVHistoryReq {
+    type Reply = KVHistoryReply;
+}
+
 // - MGetKV
 
 // Again, impl chooses to wrap it up
@@ -181,10 +304,34 @@ impl RequestFor for PrefixListReq {
     type Reply = PrefixListReply;
 }
 
+impl RequestFor for ListKVPageReq {
+    type Reply = PrefixListPage;
+}
+
 impl RequestFor for UpsertKVAction {
     type Reply = UpsertKVActionReply;
 }
 
+impl RequestFor for DeleteKVReq {
+    type Reply = DeleteKVReply;
+}
+
+impl RequestFor for DeleteKVByPrefixReq {
+    type Reply = u64;
+}
+
+impl RequestFor for IncrKVReq {
+    type Reply = IncrKVReply;
+}
+
+impl RequestFor for UpsertKVBatchReq {
+    type Reply = UpsertKVBatchReply;
+}
+
+impl RequestFor for TxnRequest {
+    type Reply = TxnReply;
+}
+
 // == database actions ==
 
 impl RequestFor for CreateDatabaseReq {
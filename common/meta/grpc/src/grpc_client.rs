@@ -12,28 +12,57 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
 use std::convert::Infallible;
 use std::fmt::Debug;
+use std::future::Future;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
 
 use common_arrow::arrow_format::flight::data::BasicAuth;
 use common_base::tokio::sync::RwLock;
+use common_base::uuid;
+use common_base::with_timeout;
 use common_containers::ItemManager;
 use common_containers::Pool;
 use common_exception::Result;
 use common_grpc::ConnectionFactory;
 use common_grpc::GrpcConnectionError;
 use common_grpc::RpcClientTlsConfig;
+use common_meta_api::with_deadline;
+use common_meta_api::KVApi;
+use common_meta_api::WatchStream;
 use common_meta_types::anyerror::AnyError;
 use common_meta_types::protobuf::meta_service_client::MetaServiceClient;
+use common_meta_types::protobuf::ExportPrefixRequest;
 use common_meta_types::protobuf::HandshakeRequest;
 use common_meta_types::protobuf::RaftReply;
 use common_meta_types::protobuf::RaftRequest;
+use common_meta_types::protobuf::WatchRequest;
+use common_meta_types::AppError;
 use common_meta_types::ConnectionError;
+use common_meta_types::ImportMode;
+use common_meta_types::ImportReport;
+use common_meta_types::KVEntry;
+use common_meta_types::KVEntryAssembler;
+use common_meta_types::KVSeqMismatched;
+use common_meta_types::MatchSeq;
 use common_meta_types::MetaError;
 use common_meta_types::MetaNetworkError;
+use common_meta_types::Operation;
+use common_meta_types::RaftTxId;
+use common_meta_types::ReadConsistency;
+use common_meta_types::UpsertKVAction;
+use common_meta_types::UpsertKVBatchReq;
+use common_meta_types::WatchEvent;
+use common_meta_types::READ_CONSISTENCY_KEY;
+use common_meta_types::TXID_CLIENT_KEY;
+use common_meta_types::TXID_SERIAL_KEY;
 use common_tracing::tracing;
+use futures::stream::BoxStream;
 use futures::stream::StreamExt;
 use prost::Message;
 use serde::de::DeserializeOwned;
@@ -50,6 +79,11 @@ use tonic::Status;
 use crate::grpc_action::MetaGrpcReadReq;
 use crate::grpc_action::MetaGrpcWriteReq;
 use crate::grpc_action::RequestFor;
+use crate::grpc_metrics::ActionMetrics;
+use crate::grpc_metrics::MetaClientMetrics;
+use crate::grpc_retry::RequestError;
+use crate::grpc_retry::RetryConfig;
+use crate::kv_compression::compress_action;
 use crate::MetaGrpcClientConf;
 
 #[derive(Debug)]
@@ -90,12 +124,28 @@ impl ItemManager for MetaChannelManager {
     }
 }
 
+/// The exported kv entries, in the order of the keys.
+pub type KVEntryStream = BoxStream<'static, std::result::Result<KVEntry, MetaError>>;
+
 pub struct MetaGrpcClient {
     conn_pool: Pool<MetaChannelManager>,
     addr: String,
     username: String,
     password: String,
     token: Arc<RwLock<Option<Vec<u8>>>>,
+    // The timeout of connecting and the handshake, and the default deadline of a request.
+    timeout: Option<Duration>,
+    retry_config: RetryConfig,
+    // The number of times the channel is dropped for a connection error.
+    reconnects: AtomicU64,
+    metrics: MetaClientMetrics,
+    // A request taking longer than this is logged.
+    slow_call_threshold: Option<Duration>,
+    // A value to upsert of at least this size is compressed.
+    compress_value_threshold: Option<usize>,
+    // Identifies the writes of this client, with a serial number, to apply a retried one once.
+    client_id: String,
+    serial: AtomicU64,
 }
 
 const AUTH_TOKEN_KEY: &str = "auth-token-bin";
@@ -104,17 +154,30 @@ impl MetaGrpcClient {
     pub async fn try_new(
         conf: &MetaGrpcClientConf,
     ) -> std::result::Result<MetaGrpcClient, Infallible> {
+        Ok(Self::new(conf))
+    }
+
+    pub fn new(conf: &MetaGrpcClientConf) -> MetaGrpcClient {
+        let timeout = Some(Duration::from_secs(conf.client_timeout_in_second));
         let mgr = MetaChannelManager {
-            timeout: Some(Duration::from_secs(conf.client_timeout_in_second)),
+            timeout,
             conf: conf.meta_service_config.tls_conf.clone(),
         };
-        Ok(Self {
+        Self {
             conn_pool: Pool::new(mgr, Duration::from_millis(50)),
             addr: conf.meta_service_config.address.to_string(),
             username: conf.meta_service_config.username.to_string(),
             password: conf.meta_service_config.password.to_string(),
             token: Arc::new(RwLock::new(None)),
-        })
+            timeout,
+            retry_config: conf.retry_config.clone(),
+            reconnects: AtomicU64::new(0),
+            metrics: MetaClientMetrics::default(),
+            slow_call_threshold: conf.slow_call_threshold,
+            compress_value_threshold: conf.compress_value_threshold,
+            client_id: uuid::Uuid::new_v4().to_string(),
+            serial: AtomicU64::new(0),
+        }
     }
 
     #[tracing::instrument(level = "debug", skip(password))]
@@ -133,9 +196,80 @@ impl MetaGrpcClient {
             username: username.to_string(),
             password: password.to_string(),
             token: Arc::new(RwLock::new(None)),
+            timeout,
+            retry_config: RetryConfig::default(),
+            reconnects: AtomicU64::new(0),
+            metrics: MetaClientMetrics::default(),
+            slow_call_threshold: None,
+            compress_value_threshold: None,
+            client_id: uuid::Uuid::new_v4().to_string(),
+            serial: AtomicU64::new(0),
         })
     }
 
+    /// Sets the default deadline of the requests, a request not completed in it fails with
+    /// `MetaNetworkError::Timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how the requests are retried on connection errors.
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
+    /// Logs every request that takes longer than `threshold`, with its key but not the value.
+    pub fn with_slow_call_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_call_threshold = Some(threshold);
+        self
+    }
+
+    /// Compresses every value to upsert of at least `threshold` bytes.
+    ///
+    /// A compressed value can not be read by a client that does not support compression, enable
+    /// it only after all the clients reading the same keys are upgraded.
+    pub fn with_value_compression(mut self, threshold: usize) -> Self {
+        self.compress_value_threshold = Some(threshold);
+        self
+    }
+
+    pub(crate) fn compress_action(&self, act: UpsertKVAction) -> UpsertKVAction {
+        match self.compress_value_threshold {
+            None => act,
+            Some(threshold) => compress_action(act, threshold),
+        }
+    }
+
+    /// Builds the txid of a new write, which is reused by all the attempts of it.
+    ///
+    /// The serial starts from 1: the state machine takes 0 as no write of the client is applied.
+    fn next_txid(&self) -> RaftTxId {
+        let serial = self.serial.fetch_add(1, Ordering::Relaxed) + 1;
+        RaftTxId::new(&self.client_id, serial)
+    }
+
+    /// The requests sent by this client so far, by the action name.
+    pub fn metrics(&self) -> BTreeMap<String, ActionMetrics> {
+        self.metrics.snapshot()
+    }
+
+    /// The number of times a broken channel is dropped, to connect again on the next request.
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects.load(Ordering::Relaxed)
+    }
+
+    /// Drops the channel if `e` is a connection error: a channel may go stale, e.g., after the
+    /// server restarts or an idle timeout, and then every request through it fails.
+    fn on_error(&self, e: &MetaError) {
+        if let MetaError::MetaNetworkError(MetaNetworkError::ConnectionError(_)) = e {
+            tracing::info!("drop the channel to {} on error: {}", self.addr, e);
+            self.conn_pool.invalidate(&self.addr);
+            self.reconnects.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn make_client(
         &self,
@@ -192,17 +326,78 @@ impl MetaGrpcClient {
         Ok(token)
     }
 
-    #[tracing::instrument(level = "debug", skip(self, v))]
     pub(crate) async fn do_write<T, R>(&self, v: T) -> std::result::Result<R, MetaError>
+    where
+        T: RequestFor<Reply = R> + Into<MetaGrpcWriteReq>,
+        R: DeserializeOwned,
+    {
+        self.do_write_with_timeout(v, self.timeout).await
+    }
+
+    /// Same as `do_write`, but gives up if it is not completed in `timeout`, including the retries.
+    #[tracing::instrument(level = "debug", skip(self, v))]
+    pub(crate) async fn do_write_with_timeout<T, R>(
+        &self,
+        v: T,
+        timeout: Option<Duration>,
+    ) -> std::result::Result<R, MetaError>
     where
         T: RequestFor<Reply = R> + Into<MetaGrpcWriteReq>,
         R: DeserializeOwned,
     {
         let act: MetaGrpcWriteReq = v.into();
+        let name = act.name();
+        let start = Instant::now();
+        let deadline = timeout.map(|t| start + t);
+        let mut attempts = 0;
+        // A write with a txid is applied at most once by the server, thus it is safe to retry
+        // once sent. Other writes are not retried once sent: they might have been applied.
+        let txid = if act.is_deduplicated() {
+            Some(self.next_txid())
+        } else {
+            None
+        };
+        let idempotent = txid.is_some();
+        let request = self.retry_config.run(idempotent, || {
+            attempts += 1;
+            let act = act.clone();
+            let txid = txid.clone();
+            async move {
+                let client = self
+                    .with_request_timeout("meta-service connect", self.make_client())
+                    .await
+                    .map_err(|e| {
+                        self.on_error(&e);
+                        RequestError::NotSent(e)
+                    })?;
+                self.write_msg(client, act, txid, deadline)
+                    .await
+                    .map_err(|e| {
+                        self.on_error(&e);
+                        RequestError::Sent(e)
+                    })
+            }
+        });
+        let res = Self::with_request_deadline(name, timeout, request).await;
+        self.record(name, || act.key(), res.is_ok(), attempts, start);
+        res
+    }
+
+    async fn write_msg<R>(
+        &self,
+        mut client: MetaServiceClient<InterceptedService<Channel, AuthInterceptor>>,
+        act: MetaGrpcWriteReq,
+        txid: Option<RaftTxId>,
+        deadline: Option<Instant>,
+    ) -> std::result::Result<R, MetaError>
+    where
+        R: DeserializeOwned,
+    {
         let req: Request<RaftRequest> = act.clone().try_into()?;
         let req = common_tracing::inject_span_to_tonic_request(req);
+        let req = with_grpc_deadline(req, deadline);
+        let req = with_txid(req, &txid);
 
-        let mut client = self.make_client().await?;
         let result = client.write_msg(req).await;
         let result: std::result::Result<RaftReply, Status> = match result {
             Ok(r) => Ok(r.into_inner()),
@@ -215,6 +410,8 @@ impl MetaGrpcClient {
                     let mut client = self.make_client().await?;
                     let req: Request<RaftRequest> = act.try_into()?;
                     let req = common_tracing::inject_span_to_tonic_request(req);
+                    let req = with_grpc_deadline(req, deadline);
+                    let req = with_txid(req, &txid);
                     Ok(client.write_msg(req).await?.into_inner())
                 } else {
                     Err(s)
@@ -229,18 +426,103 @@ impl MetaGrpcClient {
         res
     }
 
-    #[tracing::instrument(level = "debug", skip(self, v))]
     pub(crate) async fn do_read<T, R>(&self, v: T) -> std::result::Result<R, MetaError>
+    where
+        T: RequestFor<Reply = R>,
+        T: Into<MetaGrpcReadReq>,
+        R: DeserializeOwned,
+    {
+        self.do_read_with_timeout(v, self.timeout).await
+    }
+
+    /// Same as `do_read`, but gives up if it is not completed in `timeout`, including the retries.
+    #[tracing::instrument(level = "debug", skip(self, v))]
+    pub(crate) async fn do_read_with_timeout<T, R>(
+        &self,
+        v: T,
+        timeout: Option<Duration>,
+    ) -> std::result::Result<R, MetaError>
+    where
+        T: RequestFor<Reply = R>,
+        T: Into<MetaGrpcReadReq>,
+        R: DeserializeOwned,
+    {
+        self.do_read_with_options(v, timeout, ReadConsistency::default())
+            .await
+    }
+
+    pub(crate) async fn do_read_with_consistency<T, R>(
+        &self,
+        v: T,
+        consistency: ReadConsistency,
+    ) -> std::result::Result<R, MetaError>
+    where
+        T: RequestFor<Reply = R>,
+        T: Into<MetaGrpcReadReq>,
+        R: DeserializeOwned,
+    {
+        self.do_read_with_options(v, self.timeout, consistency)
+            .await
+    }
+
+    /// Same as `do_read_with_timeout`, and asks the server to serve the read with `consistency`.
+    #[tracing::instrument(level = "debug", skip(self, v))]
+    pub(crate) async fn do_read_with_options<T, R>(
+        &self,
+        v: T,
+        timeout: Option<Duration>,
+        consistency: ReadConsistency,
+    ) -> std::result::Result<R, MetaError>
     where
         T: RequestFor<Reply = R>,
         T: Into<MetaGrpcReadReq>,
         R: DeserializeOwned,
     {
         let act: MetaGrpcReadReq = v.into();
+        let name = act.name();
+        let start = Instant::now();
+        let deadline = timeout.map(|t| start + t);
+        let mut attempts = 0;
+        // A read is always safe to retry.
+        let request = self.retry_config.run(true, || {
+            attempts += 1;
+            let act = act.clone();
+            async move {
+                let client = self
+                    .with_request_timeout("meta-service connect", self.make_client())
+                    .await
+                    .map_err(|e| {
+                        self.on_error(&e);
+                        RequestError::NotSent(e)
+                    })?;
+                self.read_msg(client, act, deadline, consistency)
+                    .await
+                    .map_err(|e| {
+                        self.on_error(&e);
+                        RequestError::Sent(e)
+                    })
+            }
+        });
+        let res = Self::with_request_deadline(name, timeout, request).await;
+        self.record(name, || act.key(), res.is_ok(), attempts, start);
+        res
+    }
+
+    async fn read_msg<R>(
+        &self,
+        mut client: MetaServiceClient<InterceptedService<Channel, AuthInterceptor>>,
+        act: MetaGrpcReadReq,
+        deadline: Option<Instant>,
+        consistency: ReadConsistency,
+    ) -> std::result::Result<R, MetaError>
+    where
+        R: DeserializeOwned,
+    {
         let req: Request<RaftRequest> = act.clone().try_into()?;
         let req = common_tracing::inject_span_to_tonic_request(req);
+        let req = with_grpc_deadline(req, deadline);
+        let req = with_read_consistency(req, consistency);
 
-        let mut client = self.make_client().await?;
         let result = client.read_msg(req).await;
 
         let rpc_res: std::result::Result<RaftReply, Status> = match result {
@@ -254,6 +536,8 @@ impl MetaGrpcClient {
                     let mut client = self.make_client().await?;
                     let req: Request<RaftRequest> = act.try_into()?;
                     let req = common_tracing::inject_span_to_tonic_request(req);
+                    let req = with_grpc_deadline(req, deadline);
+                    let req = with_read_consistency(req, consistency);
                     Ok(client.read_msg(req).await?.into_inner())
                 } else {
                     Err(s)
@@ -265,6 +549,217 @@ impl MetaGrpcClient {
         let res: std::result::Result<R, MetaError> = raft_reply.into();
         res
     }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) async fn do_watch(
+        &self,
+        req: WatchRequest,
+    ) -> std::result::Result<WatchStream, MetaError> {
+        let strm = self
+            .with_request_timeout("meta-service watch", async {
+                let mut client = self.make_client().await?;
+                let strm = client.watch(req).await?.into_inner();
+                Ok(strm)
+            })
+            .await?;
+
+        let strm = strm.map(|resp| resp.map_err(MetaError::from).and_then(WatchEvent::try_from));
+        Ok(strm.boxed())
+    }
+
+    /// Exports the kv entries with the `prefix`, with their seq, meta and value.
+    ///
+    /// The server sends them in chunks of bounded size, a large value is assembled from several
+    /// chunks.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn export_prefix(
+        &self,
+        prefix: &str,
+    ) -> std::result::Result<KVEntryStream, MetaError> {
+        let strm = self
+            .with_request_timeout("meta-service export", async {
+                let mut client = self.make_client().await?;
+                let req = ExportPrefixRequest {
+                    prefix: prefix.to_string(),
+                };
+                let strm = client.export_prefix(req).await?.into_inner();
+                Ok(strm)
+            })
+            .await?;
+
+        // `None` marks the end, to check that no value is left incomplete.
+        let pieces = strm
+            .map(|res| match res {
+                Ok(chunk) => futures::stream::iter(chunk.pieces.into_iter().map(Ok)).left_stream(),
+                Err(s) => futures::stream::once(async { Err(MetaError::from(s)) }).right_stream(),
+            })
+            .flatten()
+            .map(Some)
+            .chain(futures::stream::once(async { None }));
+
+        let mut assembler = KVEntryAssembler::default();
+        let entries = pieces.filter_map(move |piece| {
+            let res = match piece {
+                Some(Ok(piece)) => assembler.push(piece).transpose(),
+                Some(Err(e)) => Some(Err(e)),
+                None => std::mem::take(&mut assembler).finish().err().map(Err),
+            };
+            futures::future::ready(res)
+        });
+        Ok(entries.boxed())
+    }
+
+    /// Imports the exported entries, keeping the values and the meta, with new seqs.
+    ///
+    /// The entries are written in batches: an import failed in the middle is partly applied.
+    /// With `ImportMode::FailOnConflict`, nothing is written if any of the keys exists when the
+    /// import starts.
+    #[tracing::instrument(level = "debug", skip(self, entries))]
+    pub async fn import_entries(
+        &self,
+        entries: Vec<KVEntry>,
+        mode: ImportMode,
+    ) -> std::result::Result<ImportReport, MetaError> {
+        if mode == ImportMode::FailOnConflict {
+            let keys = entries.iter().map(|e| e.key.clone()).collect::<Vec<_>>();
+            let existing = self.mget_kv(&keys).await?;
+            for (key, v) in keys.iter().zip(existing) {
+                if let Some(v) = v {
+                    return Err(import_conflict(key, v.seq));
+                }
+            }
+        }
+
+        let seq = match mode {
+            ImportMode::Overwrite => MatchSeq::Any,
+            ImportMode::SkipExisting | ImportMode::FailOnConflict => MatchSeq::Exact(0),
+        };
+        let actions = entries
+            .into_iter()
+            .map(|e| UpsertKVAction::new(&e.key, seq, Operation::Update(e.value), e.meta))
+            .collect();
+
+        let mut report = ImportReport::default();
+        for batch in UpsertKVBatchReq::split(actions) {
+            let keys = batch
+                .actions
+                .iter()
+                .map(|a| a.key.clone())
+                .collect::<Vec<_>>();
+            let replies = self.upsert_kv_batch(batch.actions).await?;
+
+            for (key, reply) in keys.iter().zip(replies) {
+                if reply.changed() {
+                    report.imported += 1;
+                } else if mode == ImportMode::SkipExisting {
+                    report.skipped += 1;
+                } else {
+                    // Created after the check.
+                    let curr = reply.prev.map(|v| v.seq).unwrap_or_default();
+                    return Err(import_conflict(key, curr));
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Records a completed request of the action `name`, and logs it if it is slow.
+    ///
+    /// `key` is only evaluated for a slow request.
+    fn record(
+        &self,
+        name: &'static str,
+        key: impl FnOnce() -> Option<String>,
+        ok: bool,
+        attempts: u64,
+        start: Instant,
+    ) {
+        let elapsed = start.elapsed();
+        let retries = attempts.saturating_sub(1);
+        self.metrics.record(name, ok, retries, elapsed);
+
+        if let Some(threshold) = self.slow_call_threshold {
+            if elapsed >= threshold {
+                tracing::warn!(
+                    "slow meta-service request {} key: {:?}, ok: {}, retries: {}, took {:?}",
+                    name,
+                    key(),
+                    ok,
+                    retries,
+                    elapsed
+                );
+            }
+        }
+    }
+
+    /// Gives up the request of the action with a `MetaNetworkError::Timeout` if it is not
+    /// completed in `timeout`.
+    async fn with_request_deadline<R>(
+        name: &str,
+        timeout: Option<Duration>,
+        request: impl Future<Output = std::result::Result<R, MetaError>>,
+    ) -> std::result::Result<R, MetaError> {
+        match timeout {
+            None => request.await,
+            Some(timeout) => {
+                with_deadline(&format!("meta-service {}", name), timeout, request).await
+            }
+        }
+    }
+
+    /// Gives up the request with a `ConnectionError` if it is not completed in the timeout.
+    async fn with_request_timeout<R>(
+        &self,
+        operation: &str,
+        request: impl Future<Output = std::result::Result<R, MetaError>>,
+    ) -> std::result::Result<R, MetaError> {
+        match self.timeout {
+            None => request.await,
+            Some(timeout) => with_timeout(request, timeout, operation)
+                .await
+                .map_err(|e| {
+                    MetaNetworkError::ConnectionError(ConnectionError::new(e, "while requesting"))
+                })?,
+        }
+    }
+}
+
+fn import_conflict(key: &str, curr: u64) -> MetaError {
+    AppError::from(KVSeqMismatched::new(
+        key,
+        MatchSeq::Exact(0),
+        curr,
+        "import_entries",
+    ))
+    .into()
+}
+
+/// Attaches the time left before the `deadline` to the request, for the server to give up too.
+fn with_grpc_deadline<T>(mut req: Request<T>, deadline: Option<Instant>) -> Request<T> {
+    if let Some(deadline) = deadline {
+        req.set_timeout(deadline.saturating_duration_since(Instant::now()));
+    }
+    req
+}
+
+fn with_read_consistency<T>(mut req: Request<T>, consistency: ReadConsistency) -> Request<T> {
+    req.metadata_mut().insert(
+        READ_CONSISTENCY_KEY,
+        MetadataValue::from_static(consistency.as_str()),
+    );
+    req
+}
+
+fn with_txid<T>(mut req: Request<T>, txid: &Option<RaftTxId>) -> Request<T> {
+    if let Some(txid) = txid {
+        if let Ok(client) = MetadataValue::from_str(&txid.client) {
+            let metadata = req.metadata_mut();
+            metadata.insert(TXID_CLIENT_KEY, client);
+            metadata.insert(TXID_SERIAL_KEY, MetadataValue::from(txid.serial));
+        }
+    }
+    req
 }
 
 fn status_is_retryable(status: &Status) -> bool {
@@ -12,11 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
+
 use common_grpc::RpcClientConf;
 
+use crate::RetryConfig;
+
 #[derive(Clone, Debug, Default)]
 pub struct MetaGrpcClientConf {
     pub meta_service_config: RpcClientConf,
     pub kv_service_config: RpcClientConf,
     pub client_timeout_in_second: u64,
+    pub retry_config: RetryConfig,
+    /// A request taking longer than this is logged, with its key but not the value.
+    pub slow_call_threshold: Option<Duration>,
+    /// A value to upsert of at least this size is compressed, `None` to disable compression.
+    /// A compressed value is readable only by the clients that support compression.
+    pub compress_value_threshold: Option<usize>,
 }
@@ -0,0 +1,130 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use common_grpc::RpcClientTlsConfig;
+use once_cell::sync::Lazy;
+
+use crate::MetaGrpcClient;
+use crate::MetaGrpcClientConf;
+use crate::RetryConfig;
+
+static GLOBAL_META_CLIENT_POOL: Lazy<MetaClientPool> = Lazy::new(MetaClientPool::new);
+
+/// Identifies the clients that can be shared: every setting a client is built with is the same.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct MetaClientKey {
+    address: String,
+    username: String,
+    password: String,
+    tls_conf: Option<RpcClientTlsConfig>,
+    client_timeout_in_second: u64,
+    retry_config: RetryConfig,
+    slow_call_threshold: Option<Duration>,
+    compress_value_threshold: Option<usize>,
+}
+
+impl From<&MetaGrpcClientConf> for MetaClientKey {
+    fn from(conf: &MetaGrpcClientConf) -> Self {
+        let c = &conf.meta_service_config;
+        Self {
+            address: c.address.clone(),
+            username: c.username.clone(),
+            password: c.password.clone(),
+            tls_conf: c.tls_conf.clone(),
+            client_timeout_in_second: conf.client_timeout_in_second,
+            retry_config: conf.retry_config.clone(),
+            slow_call_threshold: conf.slow_call_threshold,
+            compress_value_threshold: conf.compress_value_threshold,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MetaClientPoolStats {
+    /// The number of clients in the pool, one for every distinct client configuration.
+    pub clients: usize,
+
+    /// The number of requests served by an existent client.
+    pub hits: u64,
+
+    /// The number of requests that created a new client.
+    pub misses: u64,
+
+    /// The number of the broken channels dropped by the pooled clients, to reconnect.
+    pub reconnects: u64,
+}
+
+/// Shares the meta-service clients among the components of a process.
+///
+/// A pooled client multiplexes its requests over one channel, which is checked before being
+/// reused and is rebuilt once it is found broken, so the callers do not see a stale connection.
+pub struct MetaClientPool {
+    clients: Mutex<HashMap<MetaClientKey, Arc<MetaGrpcClient>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl MetaClientPool {
+    pub fn new() -> Self {
+        Self {
+            clients: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The pool shared by the whole process.
+    pub fn global() -> &'static MetaClientPool {
+        &GLOBAL_META_CLIENT_POOL
+    }
+
+    /// Returns the client built with the settings of `conf`, creating it if absent.
+    pub fn get(&self, conf: &MetaGrpcClientConf) -> Arc<MetaGrpcClient> {
+        let key = MetaClientKey::from(conf);
+        let mut clients = self.clients.lock().unwrap();
+
+        if let Some(client) = clients.get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return client.clone();
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let client = Arc::new(MetaGrpcClient::new(conf));
+        clients.insert(key, client.clone());
+        client
+    }
+
+    pub fn stats(&self) -> MetaClientPoolStats {
+        let clients = self.clients.lock().unwrap();
+        MetaClientPoolStats {
+            clients: clients.len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            reconnects: clients.values().map(|c| c.reconnects()).sum(),
+        }
+    }
+}
+
+impl Default for MetaClientPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
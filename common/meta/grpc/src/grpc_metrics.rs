@@ -0,0 +1,90 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use metrics::counter;
+use metrics::histogram;
+
+pub static METRIC_META_CLIENT_REQUESTS: &str = "meta_client.requests";
+pub static METRIC_META_CLIENT_RETRIES: &str = "meta_client.retries";
+pub static METRIC_META_CLIENT_LATENCY_MS: &str = "meta_client.latency_ms";
+
+/// The upper bounds in milliseconds of the buckets of the request latencies.
+pub static REQUEST_LATENCY_BUCKETS_MS: [u64; 8] = [1, 5, 10, 50, 100, 500, 1000, u64::MAX];
+
+/// A snapshot of the requests of one action sent by a client.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ActionMetrics {
+    pub ok: u64,
+    pub error: u64,
+    /// The retries after the first attempt, of all the requests.
+    pub retries: u64,
+    /// The number of the requests by the upper bound of the latency, including the retries,
+    /// one for each of `REQUEST_LATENCY_BUCKETS_MS`.
+    pub latency_buckets: Vec<u64>,
+}
+
+/// The requests of a client by the action name.
+#[derive(Debug, Default)]
+pub(crate) struct MetaClientMetrics {
+    actions: Mutex<BTreeMap<&'static str, ActionMetrics>>,
+}
+
+impl MetaClientMetrics {
+    /// Records a completed request, to the snapshot and to the metrics recorder.
+    pub(crate) fn record(&self, action: &'static str, ok: bool, retries: u64, elapsed: Duration) {
+        let ms = elapsed.as_millis() as u64;
+        let bucket = REQUEST_LATENCY_BUCKETS_MS
+            .iter()
+            .position(|le| ms <= *le)
+            .unwrap_or(REQUEST_LATENCY_BUCKETS_MS.len() - 1);
+
+        {
+            let mut actions = self.actions.lock().unwrap();
+            let m = actions.entry(action).or_insert_with(|| ActionMetrics {
+                latency_buckets: vec![0; REQUEST_LATENCY_BUCKETS_MS.len()],
+                ..Default::default()
+            });
+            if ok {
+                m.ok += 1;
+            } else {
+                m.error += 1;
+            }
+            m.retries += retries;
+            m.latency_buckets[bucket] += 1;
+        }
+
+        let labels = [
+            ("action", action.to_string()),
+            ("result", if ok { "ok" } else { "error" }.to_string()),
+        ];
+        counter!(METRIC_META_CLIENT_REQUESTS, 1, &labels);
+        histogram!(METRIC_META_CLIENT_LATENCY_MS, ms as f64, &labels);
+        if retries > 0 {
+            let labels = [("action", action.to_string())];
+            counter!(METRIC_META_CLIENT_RETRIES, retries, &labels);
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> BTreeMap<String, ActionMetrics> {
+        let actions = self.actions.lock().unwrap();
+        actions
+            .iter()
+            .map(|(name, m)| (name.to_string(), m.clone()))
+            .collect()
+    }
+}
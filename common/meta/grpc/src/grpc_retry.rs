@@ -0,0 +1,126 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::future::Future;
+use std::time::Duration;
+use std::time::Instant;
+
+use common_base::tokio;
+use common_meta_types::ConnectionError;
+use common_meta_types::MetaError;
+use common_meta_types::MetaNetworkError;
+use common_tracing::tracing;
+
+/// How a request to the meta service is retried on connection errors.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RetryConfig {
+    /// The max number of retries after the first attempt.
+    pub max_retries: u32,
+
+    /// The delay before the first retry, which is doubled for every following retry.
+    pub initial_backoff: Duration,
+
+    pub max_backoff: Duration,
+
+    /// No retry is made once this much time elapsed since the first attempt.
+    pub total_deadline: Duration,
+}
+
+/// The error of one attempt of a request, telling whether the request reached the meta service.
+#[derive(Debug)]
+pub enum RequestError {
+    /// The request is not sent, e.g., the connection is refused. It is safe to retry.
+    NotSent(MetaError),
+
+    /// The request is sent, or may be sent. The server may or may not have applied it.
+    Sent(MetaError),
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(1),
+            total_deadline: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// A config that never retries.
+    pub fn no_retry() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Runs `attempt` until it succeeds or a retry is not allowed.
+    ///
+    /// Only connection errors are retried.
+    /// An `idempotent` request, e.g., a read or a write with a txid, is retried no matter where it
+    /// failed; otherwise it is retried only if it is not sent, and a connection error after sending is
+    /// returned as a `MetaNetworkError::OutcomeUnknown`, since retrying might apply it twice.
+    pub async fn run<R, F, Fut>(&self, idempotent: bool, mut attempt: F) -> Result<R, MetaError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<R, RequestError>>,
+    {
+        let start = Instant::now();
+        let mut backoff = self.initial_backoff;
+        let mut retries = 0;
+
+        loop {
+            let (sent, err) = match attempt().await {
+                Ok(r) => return Ok(r),
+                Err(RequestError::NotSent(e)) => (false, e),
+                Err(RequestError::Sent(e)) => (true, e),
+            };
+
+            if !is_connection_error(&err) {
+                return Err(err);
+            }
+
+            if sent && !idempotent {
+                return Err(MetaNetworkError::OutcomeUnknown(ConnectionError::new(
+                    err,
+                    "the request may or may not have been applied",
+                ))
+                .into());
+            }
+
+            if retries >= self.max_retries || start.elapsed() + backoff > self.total_deadline {
+                return Err(err);
+            }
+
+            retries += 1;
+            tracing::warn!(
+                "retry({}) meta-service request in {:?} on error: {}",
+                retries,
+                backoff,
+                err
+            );
+            tokio::time::sleep(backoff).await;
+            backoff = std::cmp::min(backoff * 2, self.max_backoff);
+        }
+    }
+}
+
+fn is_connection_error(e: &MetaError) -> bool {
+    matches!(
+        e,
+        MetaError::MetaNetworkError(MetaNetworkError::ConnectionError(_))
+    )
+}
@@ -12,24 +12,74 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
+
 use common_meta_api::KVApi;
+use common_meta_api::WatchStream;
+use common_meta_types::protobuf::WatchRequest;
+use common_meta_types::DeleteKVByPrefixReq;
+use common_meta_types::DeleteKVReply;
+use common_meta_types::DeleteKVReq;
 use common_meta_types::GetKVActionReply;
+use common_meta_types::GetKVHistoryReq;
+use common_meta_types::GetKVMetaReply;
+use common_meta_types::GetKVMetaReq;
+use common_meta_types::IncrKVReply;
+use common_meta_types::IncrKVReq;
+use common_meta_types::KVHistoryReply;
+use common_meta_types::ListKVPageReq;
 use common_meta_types::MGetKVActionReply;
+use common_meta_types::MatchSeq;
 use common_meta_types::MetaError;
+use common_meta_types::MetaResultError;
+use common_meta_types::PrefixListPage;
 use common_meta_types::PrefixListReply;
+use common_meta_types::ReadConsistency;
+use common_meta_types::TxnReply;
+use common_meta_types::TxnRequest;
 use common_meta_types::UpsertKVAction;
 use common_meta_types::UpsertKVActionReply;
+use common_meta_types::UpsertKVBatchReply;
+use common_meta_types::UpsertKVBatchReq;
+use futures::StreamExt;
 
 use crate::grpc_action::GetKVAction;
 use crate::grpc_action::MGetKVAction;
 use crate::grpc_action::PrefixListReq;
+use crate::kv_compression::decompress_change;
+use crate::kv_compression::decompress_event;
+use crate::kv_compression::decompress_opt_seqv;
+use crate::kv_compression::decompress_page;
+use crate::kv_compression::decompress_seqv;
 use crate::MetaGrpcClient;
 
+/// Number of the keys in a page listed by `prefix_list_kv`.
+const PREFIX_LIST_PAGE_SIZE: usize = 1000;
+
 #[tonic::async_trait]
 impl KVApi for MetaGrpcClient {
     async fn upsert_kv(&self, act: UpsertKVAction) -> Result<UpsertKVActionReply, MetaError> {
-        let reply = self.do_write(act).await?;
-        Ok(reply)
+        let reply = self.do_write(self.compress_action(act)).await?;
+        decompress_change(reply)
+    }
+
+    async fn upsert_kv_batch(
+        &self,
+        actions: Vec<UpsertKVAction>,
+    ) -> Result<UpsertKVBatchReply, MetaError> {
+        let actions = actions
+            .into_iter()
+            .map(|act| self.compress_action(act))
+            .collect();
+        let mut changes = Vec::with_capacity(actions.len());
+        // One round trip per batch, a batch is bounded so that it fits in one raft log.
+        for req in UpsertKVBatchReq::split(actions) {
+            let reply: UpsertKVBatchReply = self.do_write(req).await?;
+            for change in reply {
+                changes.push(decompress_change(change)?);
+            }
+        }
+        Ok(changes)
     }
 
     async fn get_kv(&self, key: &str) -> Result<GetKVActionReply, MetaError> {
@@ -38,17 +88,231 @@ impl KVApi for MetaGrpcClient {
                 key: key.to_string(),
             })
             .await?;
+        decompress_opt_seqv(reply)
+    }
+
+    async fn get_kv_meta(&self, key: &str) -> Result<GetKVMetaReply, MetaError> {
+        let reply = self
+            .do_read(GetKVMetaReq {
+                key: key.to_string(),
+            })
+            .await?;
         Ok(reply)
     }
 
-    async fn mget_kv(&self, keys: &[String]) -> Result<MGetKVActionReply, MetaError> {
-        let keys = keys.to_vec();
-        let reply = self.do_read(MGetKVAction { keys }).await?;
+    async fn get_kv_history(&self, key: &str, limit: usize) -> Result<KVHistoryReply, MetaError> {
+        let reply: KVHistoryReply = self
+            .do_read(GetKVHistoryReq {
+                key: key.to_string(),
+                limit,
+            })
+            .await?;
+        reply.into_iter().map(decompress_seqv).collect()
+    }
+
+    async fn delete_kv(&self, key: &str, seq: MatchSeq) -> Result<DeleteKVReply, MetaError> {
+        let reply = self.do_write(DeleteKVReq::new(key, seq)).await?;
+        decompress_change(reply)
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64, MetaError> {
+        let reply = self
+            .do_write(DeleteKVByPrefixReq {
+                prefix: prefix.to_string(),
+            })
+            .await?;
+        Ok(reply)
+    }
+
+    async fn incr_kv(
+        &self,
+        key: &str,
+        delta: i64,
+        seq: MatchSeq,
+    ) -> Result<IncrKVReply, MetaError> {
+        let reply = self
+            .do_write(IncrKVReq {
+                key: key.to_string(),
+                delta,
+                seq,
+            })
+            .await?;
         Ok(reply)
     }
 
+    async fn mget_kv(&self, keys: &[String]) -> Result<MGetKVActionReply, MetaError> {
+        let reply = self
+            .do_read(MGetKVAction {
+                keys: keys.to_vec(),
+            })
+            .await?;
+        decompress_mget_reply(check_mget_reply(keys.len(), reply)?)
+    }
+
+    /// Lists all the keys and values under `prefix`, page by page, to keep every reply small.
     async fn prefix_list_kv(&self, prefix: &str) -> Result<PrefixListReply, MetaError> {
-        let reply = self.do_read(PrefixListReq(prefix.to_string())).await?;
+        let mut res = vec![];
+        let mut after_key = None;
+
+        loop {
+            let page = self
+                .prefix_list_kv_paged(prefix, after_key, PREFIX_LIST_PAGE_SIZE, false)
+                .await?;
+
+            res.extend(
+                page.items
+                    .into_iter()
+                    .filter_map(|(k, v)| v.map(|v| (k, v))),
+            );
+
+            if page.last_key.is_none() {
+                return Ok(res);
+            }
+            after_key = page.last_key;
+        }
+    }
+
+    async fn prefix_list_kv_paged(
+        &self,
+        prefix: &str,
+        after_key: Option<String>,
+        limit: usize,
+        keys_only: bool,
+    ) -> Result<PrefixListPage, MetaError> {
+        let reply = self
+            .do_read(ListKVPageReq {
+                prefix: prefix.to_string(),
+                after_key,
+                limit,
+                keys_only,
+            })
+            .await?;
+        decompress_page(reply)
+    }
+
+    async fn transaction(&self, txn: TxnRequest) -> Result<TxnReply, MetaError> {
+        let reply = self.do_write(txn).await?;
         Ok(reply)
     }
+
+    async fn watch(&self, prefix: &str, from_seq: Option<u64>) -> Result<WatchStream, MetaError> {
+        let strm = self
+            .do_watch(WatchRequest::prefix(prefix, from_seq))
+            .await?;
+        Ok(strm.map(|ev| ev.and_then(decompress_event)).boxed())
+    }
+
+    // The ones of a single request attach the deadline to it, for the server to give up too.
+
+    async fn get_kv_with_deadline(
+        &self,
+        key: &str,
+        timeout: Duration,
+    ) -> Result<GetKVActionReply, MetaError> {
+        let reply = self
+            .do_read_with_timeout(
+                GetKVAction {
+                    key: key.to_string(),
+                },
+                Some(timeout),
+            )
+            .await?;
+        decompress_opt_seqv(reply)
+    }
+
+    async fn mget_kv_with_deadline(
+        &self,
+        keys: &[String],
+        timeout: Duration,
+    ) -> Result<MGetKVActionReply, MetaError> {
+        let reply = self
+            .do_read_with_timeout(
+                MGetKVAction {
+                    keys: keys.to_vec(),
+                },
+                Some(timeout),
+            )
+            .await?;
+        decompress_mget_reply(check_mget_reply(keys.len(), reply)?)
+    }
+
+    async fn upsert_kv_with_deadline(
+        &self,
+        act: UpsertKVAction,
+        timeout: Duration,
+    ) -> Result<UpsertKVActionReply, MetaError> {
+        let reply = self
+            .do_write_with_timeout(self.compress_action(act), Some(timeout))
+            .await?;
+        decompress_change(reply)
+    }
+
+    // The consistency is sent along with the request, the server decides how to serve it.
+
+    async fn get_kv_with_consistency(
+        &self,
+        key: &str,
+        consistency: ReadConsistency,
+    ) -> Result<GetKVActionReply, MetaError> {
+        let reply = self
+            .do_read_with_consistency(
+                GetKVAction {
+                    key: key.to_string(),
+                },
+                consistency,
+            )
+            .await?;
+        decompress_opt_seqv(reply)
+    }
+
+    async fn mget_kv_with_consistency(
+        &self,
+        keys: &[String],
+        consistency: ReadConsistency,
+    ) -> Result<MGetKVActionReply, MetaError> {
+        let reply = self
+            .do_read_with_consistency(
+                MGetKVAction {
+                    keys: keys.to_vec(),
+                },
+                consistency,
+            )
+            .await?;
+        decompress_mget_reply(check_mget_reply(keys.len(), reply)?)
+    }
+
+    /// Unlike `prefix_list_kv`, it lists in one request: the pages would not be read at the same
+    /// point and the guarantee would only hold for each of them.
+    async fn prefix_list_kv_with_consistency(
+        &self,
+        prefix: &str,
+        consistency: ReadConsistency,
+    ) -> Result<PrefixListReply, MetaError> {
+        let reply = self
+            .do_read_with_consistency(PrefixListReq(prefix.to_string()), consistency)
+            .await?;
+        decompress_list_reply(reply)
+    }
+}
+
+fn check_mget_reply(n: usize, reply: MGetKVActionReply) -> Result<MGetKVActionReply, MetaError> {
+    if reply.len() != n {
+        return Err(MetaResultError::InvalidMGetResult {
+            expect: n,
+            got: reply.len(),
+        }
+        .into());
+    }
+    Ok(reply)
+}
+
+fn decompress_mget_reply(reply: MGetKVActionReply) -> Result<MGetKVActionReply, MetaError> {
+    reply.into_iter().map(decompress_opt_seqv).collect()
+}
+
+fn decompress_list_reply(reply: PrefixListReply) -> Result<PrefixListReply, MetaError> {
+    reply
+        .into_iter()
+        .map(|(k, v)| Ok((k, decompress_seqv(v)?)))
+        .collect()
 }
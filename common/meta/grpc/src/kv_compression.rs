@@ -0,0 +1,126 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Client-side compression of large kv values.
+//!
+//! A compressed value is the zstd frame of the value, prefixed with `COMPRESSED_VALUE_TAG`.
+//! The server stores it as is, thus a compressed value is readable by any server version, but
+//! only by the clients that know the tag: compressing is enabled by a client only after all the
+//! clients are upgraded, while reading a compressed one is always supported.
+
+use common_meta_types::Change;
+use common_meta_types::MetaError;
+use common_meta_types::MetaResultError;
+use common_meta_types::Operation;
+use common_meta_types::PrefixListPage;
+use common_meta_types::SeqV;
+use common_meta_types::UpsertKVAction;
+use common_meta_types::WatchEvent;
+
+/// The prefix of a compressed value.
+///
+/// A value in json never starts with `0xFF`, which is not valid in utf-8, thus an uncompressed
+/// value written before is never taken as a compressed one.
+pub const COMPRESSED_VALUE_TAG: &[u8] = b"\xffzstd";
+
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Compresses `data` if it is not smaller than `threshold` and the compressed one is smaller.
+///
+/// An incompressible value, e.g., one already compressed, is returned as is.
+pub fn compress_value(data: Vec<u8>, threshold: usize) -> Vec<u8> {
+    if data.len() < threshold {
+        return data;
+    }
+
+    let compressed = match zstd::encode_all(data.as_slice(), COMPRESSION_LEVEL) {
+        Ok(x) => x,
+        Err(_) => return data,
+    };
+    if COMPRESSED_VALUE_TAG.len() + compressed.len() >= data.len() {
+        return data;
+    }
+
+    let mut res = Vec::with_capacity(COMPRESSED_VALUE_TAG.len() + compressed.len());
+    res.extend_from_slice(COMPRESSED_VALUE_TAG);
+    res.extend_from_slice(&compressed);
+    res
+}
+
+/// Decompresses a value built by `compress_value`, an uncompressed value is returned as is.
+pub fn decompress_value(data: Vec<u8>) -> Result<Vec<u8>, MetaError> {
+    if !is_compressed_value(&data) {
+        return Ok(data);
+    }
+
+    zstd::decode_all(&data[COMPRESSED_VALUE_TAG.len()..]).map_err(|e| {
+        MetaError::from(MetaResultError::InvalidCompressedValue(format!(
+            "fail to decompress a value of {} bytes: {}",
+            data.len(),
+            e
+        )))
+    })
+}
+
+pub fn is_compressed_value(data: &[u8]) -> bool {
+    data.starts_with(COMPRESSED_VALUE_TAG)
+}
+
+pub(crate) fn compress_action(mut act: UpsertKVAction, threshold: usize) -> UpsertKVAction {
+    if let Operation::Update(data) = act.value {
+        act.value = Operation::Update(compress_value(data, threshold));
+    }
+    act
+}
+
+pub(crate) fn decompress_seqv(seqv: SeqV) -> Result<SeqV, MetaError> {
+    Ok(SeqV {
+        seq: seqv.seq,
+        meta: seqv.meta,
+        data: decompress_value(seqv.data)?,
+    })
+}
+
+pub(crate) fn decompress_opt_seqv(seqv: Option<SeqV>) -> Result<Option<SeqV>, MetaError> {
+    seqv.map(decompress_seqv).transpose()
+}
+
+pub(crate) fn decompress_change(change: Change<Vec<u8>>) -> Result<Change<Vec<u8>>, MetaError> {
+    Ok(Change {
+        ident: change.ident,
+        prev: decompress_opt_seqv(change.prev)?,
+        result: decompress_opt_seqv(change.result)?,
+    })
+}
+
+pub(crate) fn decompress_page(page: PrefixListPage) -> Result<PrefixListPage, MetaError> {
+    let items = page
+        .items
+        .into_iter()
+        .map(|(k, v)| Ok((k, decompress_opt_seqv(v)?)))
+        .collect::<Result<_, MetaError>>()?;
+    Ok(PrefixListPage {
+        items,
+        last_key: page.last_key,
+    })
+}
+
+pub(crate) fn decompress_event(ev: WatchEvent) -> Result<WatchEvent, MetaError> {
+    Ok(WatchEvent {
+        seq: ev.seq,
+        key: ev.key,
+        prev: decompress_opt_seqv(ev.prev)?,
+        current: decompress_opt_seqv(ev.current)?,
+    })
+}
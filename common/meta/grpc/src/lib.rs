@@ -15,12 +15,30 @@
 mod grpc_action;
 mod grpc_client;
 mod grpc_client_conf;
+mod grpc_client_pool;
+mod grpc_metrics;
+mod grpc_retry;
 mod kv_api_impl;
+mod kv_compression;
 mod meta_api_impl;
 
 pub use grpc_action::GetTableExtReq;
 pub use grpc_action::MetaGrpcReadReq;
 pub use grpc_action::MetaGrpcWriteReq;
 pub use grpc_action::RequestFor;
+pub use grpc_client::KVEntryStream;
 pub use grpc_client::MetaGrpcClient;
 pub use grpc_client_conf::MetaGrpcClientConf;
+pub use grpc_client_pool::MetaClientPool;
+pub use grpc_client_pool::MetaClientPoolStats;
+pub use grpc_metrics::ActionMetrics;
+pub use grpc_metrics::METRIC_META_CLIENT_LATENCY_MS;
+pub use grpc_metrics::METRIC_META_CLIENT_REQUESTS;
+pub use grpc_metrics::METRIC_META_CLIENT_RETRIES;
+pub use grpc_metrics::REQUEST_LATENCY_BUCKETS_MS;
+pub use grpc_retry::RequestError;
+pub use grpc_retry::RetryConfig;
+pub use kv_compression::compress_value;
+pub use kv_compression::decompress_value;
+pub use kv_compression::is_compressed_value;
+pub use kv_compression::COMPRESSED_VALUE_TAG;
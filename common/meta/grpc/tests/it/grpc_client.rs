@@ -13,11 +13,14 @@
 // limitations under the License.
 
 use std::time::Duration;
+use std::time::Instant;
 
 use common_base::tokio;
 use common_exception::ErrorCode;
+use common_meta_api::KVApi;
 use common_meta_api::MetaApi;
 use common_meta_grpc::MetaGrpcClient;
+use common_meta_grpc::RetryConfig;
 use common_meta_types::GetDatabaseReq;
 
 use crate::grpc_server::start_grpc_server;
@@ -33,15 +36,78 @@ async fn test_grpc_client_action_timeout() {
 
     let client = MetaGrpcClient::try_create(&srv_addr, "", "", Some(timeout), None)
         .await
-        .unwrap();
+        .unwrap()
+        .with_retry_config(RetryConfig::no_retry());
 
+    // The handshake completes in time, read_msg does not.
     let res = client
         .get_database(GetDatabaseReq::new("tenant1", "xx"))
         .await;
-    let got = res.unwrap_err();
-    let got = ErrorCode::from(got).message();
-    let expect = "ConnectionError:  source: tonic::status::Status: status: Cancelled, message: \"Timeout expired\", details: [], metadata: MetadataMap { headers: {} } source: transport error source: Timeout expired";
-    assert_eq!(got, expect);
+    let got = ErrorCode::from(res.unwrap_err());
+    assert_eq!(got.code(), ErrorCode::MetaServiceTimeout("").code());
+    assert!(
+        got.message()
+            .contains("meta-service GetDatabase is not completed in 3s"),
+        "{}",
+        got.message()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_grpc_client_default_deadline() {
+    let srv_addr = start_grpc_server();
+
+    let client = MetaGrpcClient::try_create(&srv_addr, "", "", Some(Duration::from_secs(60)), None)
+        .await
+        .unwrap()
+        .with_timeout(Duration::from_secs(3));
+
+    // The handshake takes 2 seconds, read_msg never completes: the whole request is bounded.
+    let start = Instant::now();
+    let res = client.get_kv("foo").await;
+    assert!(
+        start.elapsed() < Duration::from_secs(4),
+        "{:?}",
+        start.elapsed()
+    );
+
+    let got = ErrorCode::from(res.unwrap_err());
+    assert_eq!(got.code(), ErrorCode::MetaServiceTimeout("").code());
+    assert!(
+        got.message()
+            .contains("meta-service GetKV is not completed in 3s"),
+        "{}",
+        got.message()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_grpc_client_request_deadline() {
+    let srv_addr = start_grpc_server();
+
+    let client = MetaGrpcClient::try_create(&srv_addr, "", "", Some(Duration::from_secs(60)), None)
+        .await
+        .unwrap();
+
+    // A tighter deadline than the default one of the client.
+    let start = Instant::now();
+    let res = client
+        .mget_kv_with_deadline(&["foo".to_string()], Duration::from_secs(3))
+        .await;
+    assert!(
+        start.elapsed() < Duration::from_secs(4),
+        "{:?}",
+        start.elapsed()
+    );
+
+    let got = ErrorCode::from(res.unwrap_err());
+    assert_eq!(got.code(), ErrorCode::MetaServiceTimeout("").code());
+    assert!(
+        got.message()
+            .contains("meta-service MGetKV is not completed in 3s"),
+        "{}",
+        got.message()
+    );
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
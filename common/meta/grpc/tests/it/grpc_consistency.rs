@@ -0,0 +1,54 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use common_base::tokio;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_meta_api::KVApi;
+use common_meta_grpc::MetaGrpcClient;
+use common_meta_grpc::RetryConfig;
+use common_meta_types::ReadConsistency;
+use common_meta_types::SeqV;
+
+use crate::grpc_server::start_grpc_server;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_grpc_client_read_consistency() -> Result<()> {
+    let srv_addr = start_grpc_server();
+
+    let client = MetaGrpcClient::try_create(&srv_addr, "", "", Some(Duration::from_secs(10)), None)
+        .await?
+        .with_retry_config(RetryConfig::no_retry());
+
+    // The mock server serves a stale read at once, from whatever it has.
+    let got = client
+        .get_kv_with_consistency("foo", ReadConsistency::Stale)
+        .await?;
+    assert_eq!(Some(SeqV::new(1, b"stale".to_vec())), got);
+
+    // The mock server never confirms its leadership.
+    let res = client
+        .get_kv_with_consistency("foo", ReadConsistency::Linearizable)
+        .await;
+    let got = ErrorCode::from(res.unwrap_err());
+    assert!(
+        got.message().contains("leadership is not confirmed"),
+        "{}",
+        got.message()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use common_base::tokio;
+use common_meta_api::KVApi;
+use common_meta_grpc::MetaGrpcClient;
+use common_meta_grpc::RetryConfig;
+use common_meta_grpc::REQUEST_LATENCY_BUCKETS_MS;
+use common_meta_types::MatchSeq;
+use common_meta_types::Operation;
+use common_meta_types::UpsertKVAction;
+use common_metrics::dump_metric_samples;
+use common_metrics::init_default_metrics_recorder;
+use common_metrics::try_handle;
+use common_metrics::MetricValue;
+
+use crate::grpc_server::start_grpc_server;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_grpc_client_metrics() -> common_exception::Result<()> {
+    init_default_metrics_recorder();
+
+    let srv_addr = start_grpc_server();
+    let client = MetaGrpcClient::try_create(&srv_addr, "", "", Some(Duration::from_secs(10)), None)
+        .await?
+        .with_retry_config(RetryConfig::no_retry())
+        .with_slow_call_threshold(Duration::from_millis(100));
+
+    // The mock server rejects every write, and never replies a read.
+    for _ in 0..2 {
+        let res = client
+            .upsert_kv(UpsertKVAction::new(
+                "foo",
+                MatchSeq::Any,
+                Operation::Update(b"secret".to_vec()),
+                None,
+            ))
+            .await;
+        assert!(res.is_err());
+    }
+    let res = client
+        .get_kv_with_deadline("foo", Duration::from_millis(500))
+        .await;
+    assert!(res.is_err());
+
+    let metrics = client.metrics();
+    assert_eq!(
+        vec!["GetKV", "UpsertKV"],
+        metrics.keys().map(|k| k.as_str()).collect::<Vec<_>>()
+    );
+
+    let upsert = &metrics["UpsertKV"];
+    assert_eq!(0, upsert.ok);
+    assert_eq!(2, upsert.error);
+    assert_eq!(0, upsert.retries);
+    assert_eq!(
+        REQUEST_LATENCY_BUCKETS_MS.len(),
+        upsert.latency_buckets.len()
+    );
+    assert_eq!(2, upsert.latency_buckets.iter().sum::<u64>());
+    // The first one includes the 2 seconds handshake.
+    assert_eq!(1, *upsert.latency_buckets.last().unwrap());
+
+    let get = &metrics["GetKV"];
+    assert_eq!(1, get.error);
+    // Timed out in 500 ms.
+    let i = REQUEST_LATENCY_BUCKETS_MS.iter().position(|le| *le == 1000);
+    assert_eq!(1, get.latency_buckets[i.unwrap()]);
+
+    // Exported to the metrics recorder.
+    let samples = dump_metric_samples(try_handle().unwrap())?;
+    let upsert_requests = samples.iter().find(|s| {
+        s.name == "meta_client_requests"
+            && s.labels.get("action").map(|a| a.as_str()) == Some("UpsertKV")
+            && s.labels.get("result").map(|r| r.as_str()) == Some("error")
+    });
+    assert_eq!(
+        MetricValue::Counter(2.0),
+        upsert_requests.unwrap().value,
+        "{:?}",
+        samples
+    );
+    assert!(samples.iter().any(|s| s.name == "meta_client_latency_ms"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_grpc_client_metrics_retries() -> common_exception::Result<()> {
+    // Nothing listens on it: the handshake fails before the request is sent, thus it is retried.
+    let client = MetaGrpcClient::try_create("127.0.0.1:1", "", "", None, None)
+        .await?
+        .with_retry_config(RetryConfig {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(4),
+            total_deadline: Duration::from_secs(10),
+        });
+
+    let res = client.get_kv("foo").await;
+    assert!(res.is_err());
+
+    let get = &client.metrics()["GetKV"];
+    assert_eq!(0, get.ok);
+    assert_eq!(1, get.error);
+    assert_eq!(2, get.retries);
+    Ok(())
+}
@@ -0,0 +1,145 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use common_base::tokio;
+use common_exception::ErrorCode;
+use common_meta_grpc::RequestError;
+use common_meta_grpc::RetryConfig;
+use common_meta_types::AppError;
+use common_meta_types::ConnectionError;
+use common_meta_types::MetaError;
+use common_meta_types::MetaNetworkError;
+use common_meta_types::UnknownDatabase;
+
+fn retry_config(max_retries: u32) -> RetryConfig {
+    RetryConfig {
+        max_retries,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(4),
+        total_deadline: Duration::from_secs(10),
+    }
+}
+
+fn conn_err() -> MetaError {
+    MetaNetworkError::ConnectionError(ConnectionError::new(
+        std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "refused"),
+        "flaky",
+    ))
+    .into()
+}
+
+/// A transport that fails the first `fails` requests with the error built by `err`.
+async fn flaky(
+    calls: &AtomicU32,
+    fails: u32,
+    err: fn(MetaError) -> RequestError,
+) -> Result<u32, RequestError> {
+    let n = calls.fetch_add(1, Ordering::SeqCst);
+    if n < fails {
+        Err(err(conn_err()))
+    } else {
+        Ok(n)
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_retry_read_on_connection_error() -> Result<(), MetaError> {
+    // A read is retried whether or not it is sent.
+    for err in [RequestError::NotSent, RequestError::Sent] {
+        let calls = AtomicU32::new(0);
+        let res = retry_config(3).run(true, || flaky(&calls, 2, err)).await?;
+        assert_eq!(res, 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    // Gives up after max_retries.
+    let calls = AtomicU32::new(0);
+    let res = retry_config(3)
+        .run(true, || flaky(&calls, 10, RequestError::Sent))
+        .await;
+    assert_eq!(
+        ErrorCode::from(res.unwrap_err()).code(),
+        ErrorCode::CannotConnectNode("").code()
+    );
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+    // Gives up at the total deadline.
+    let calls = AtomicU32::new(0);
+    let res = RetryConfig {
+        total_deadline: Duration::from_millis(50),
+        initial_backoff: Duration::from_millis(20),
+        max_backoff: Duration::from_millis(20),
+        max_retries: 100,
+    }
+    .run(true, || flaky(&calls, 100, RequestError::Sent))
+    .await;
+    assert!(res.is_err());
+    assert!(calls.load(Ordering::SeqCst) <= 3);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_retry_write_not_sent() -> Result<(), MetaError> {
+    let calls = AtomicU32::new(0);
+    let res = retry_config(3)
+        .run(false, || flaky(&calls, 2, RequestError::NotSent))
+        .await?;
+    assert_eq!(res, 2);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_retry_write_sent_outcome_unknown() -> Result<(), MetaError> {
+    let calls = AtomicU32::new(0);
+    let res = retry_config(3)
+        .run(false, || flaky(&calls, 2, RequestError::Sent))
+        .await;
+    let err = res.unwrap_err();
+    assert!(matches!(
+        err,
+        MetaError::MetaNetworkError(MetaNetworkError::OutcomeUnknown(_))
+    ));
+    assert_eq!(
+        ErrorCode::from(err).code(),
+        ErrorCode::MetaServiceUnavailable("").code()
+    );
+    assert_eq!(
+        calls.load(Ordering::SeqCst),
+        1,
+        "a sent write is not retried"
+    );
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_retry_non_connection_error() -> Result<(), MetaError> {
+    let calls = AtomicU32::new(0);
+    let res: Result<(), MetaError> = retry_config(3)
+        .run(true, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(RequestError::Sent(
+                AppError::from(UnknownDatabase::new("db", "")).into(),
+            ))
+        })
+        .await;
+    assert!(matches!(res.unwrap_err(), MetaError::AppError(_)));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    Ok(())
+}
@@ -19,12 +19,20 @@ use std::time::Duration;
 use common_base::tokio;
 use common_meta_types::protobuf::meta_service_server::MetaService;
 use common_meta_types::protobuf::meta_service_server::MetaServiceServer;
+use common_meta_types::protobuf::ExportPrefixRequest;
 use common_meta_types::protobuf::ExportedChunk;
+use common_meta_types::protobuf::ExportedKVChunk;
 use common_meta_types::protobuf::HandshakeResponse;
 use common_meta_types::protobuf::RaftReply;
 use common_meta_types::protobuf::RaftRequest;
 use common_meta_types::protobuf::WatchRequest;
 use common_meta_types::protobuf::WatchResponse;
+use common_meta_types::GetKVActionReply;
+use common_meta_types::MetaError;
+use common_meta_types::MetaRaftError;
+use common_meta_types::ReadConsistency;
+use common_meta_types::SeqV;
+use common_meta_types::READ_CONSISTENCY_KEY;
 use futures::Stream;
 use rand::Rng;
 use tonic::transport::Server;
@@ -56,10 +64,30 @@ impl MetaService for GrpcServiceForTestImpl {
         Err(Status::unimplemented("Not yet implemented"))
     }
 
-    async fn read_msg(
-        &self,
-        _request: Request<RaftRequest>,
-    ) -> Result<Response<RaftReply>, Status> {
+    async fn read_msg(&self, request: Request<RaftRequest>) -> Result<Response<RaftReply>, Status> {
+        let consistency = request
+            .metadata()
+            .get(READ_CONSISTENCY_KEY)
+            .and_then(|v| v.to_str().ok())
+            .map(ReadConsistency::parse)
+            .unwrap_or_default();
+
+        // for consistency test: a stale read is served at once, a linearizable one finds no leader.
+        match consistency {
+            ReadConsistency::Stale => {
+                let reply: Result<GetKVActionReply, MetaError> =
+                    Ok(Some(SeqV::new(1, b"stale".to_vec())));
+                return Ok(Response::new(RaftReply::from(reply)));
+            }
+            ReadConsistency::Linearizable => {
+                let reply: Result<GetKVActionReply, MetaError> = Err(MetaError::from(
+                    MetaRaftError::ConsistentReadError("leadership is not confirmed".to_string()),
+                ));
+                return Ok(Response::new(RaftReply::from(reply)));
+            }
+            ReadConsistency::Lease => {}
+        }
+
         // for timeout test
         tokio::time::sleep(Duration::from_secs(60)).await;
         Err(Status::unimplemented("Not yet implemented"))
@@ -75,6 +103,16 @@ impl MetaService for GrpcServiceForTestImpl {
         todo!()
     }
 
+    type ExportPrefixStream =
+        Pin<Box<dyn Stream<Item = Result<ExportedKVChunk, tonic::Status>> + Send + Sync + 'static>>;
+
+    async fn export_prefix(
+        &self,
+        _request: Request<ExportPrefixRequest>,
+    ) -> Result<Response<Self::ExportPrefixStream>, Status> {
+        todo!()
+    }
+
     type WatchStream =
         Pin<Box<dyn Stream<Item = Result<WatchResponse, tonic::Status>> + Send + Sync + 'static>>;
 
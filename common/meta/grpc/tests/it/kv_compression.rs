@@ -0,0 +1,70 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_exception::ErrorCode;
+use common_meta_grpc::compress_value;
+use common_meta_grpc::decompress_value;
+use common_meta_grpc::is_compressed_value;
+use common_meta_grpc::COMPRESSED_VALUE_TAG;
+
+#[test]
+fn test_compress_value() -> common_exception::Result<()> {
+    let json = br#"{"name":"t1","options":{}},"#.repeat(1000);
+
+    // below the threshold
+    let got = compress_value(json.clone(), json.len() + 1);
+    assert_eq!(json, got);
+
+    let got = compress_value(json.clone(), 1024);
+    assert!(is_compressed_value(&got));
+    assert!(got.len() < json.len() / 10, "compressed to {}", got.len());
+    assert_eq!(json, decompress_value(got)?);
+
+    // incompressible
+    let mut x = 0x2545_f491_4f6c_dd1d_u64;
+    let random = (0..4096)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            (x >> 24) as u8
+        })
+        .collect::<Vec<_>>();
+    let got = compress_value(random.clone(), 1024);
+    assert_eq!(random, got);
+    assert_eq!(random, decompress_value(got)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_decompress_value() -> common_exception::Result<()> {
+    // An uncompressed value is returned as is.
+    for v in [vec![], b"{}".to_vec(), b"zstd".to_vec()] {
+        assert_eq!(v, decompress_value(v.clone())?);
+    }
+
+    // A corrupted one is an error rather than garbage.
+    let mut corrupted = COMPRESSED_VALUE_TAG.to_vec();
+    corrupted.extend_from_slice(b"not a zstd frame");
+    let res = decompress_value(corrupted);
+    let got = ErrorCode::from(res.unwrap_err());
+    assert!(
+        got.message().contains("Invalid compressed value"),
+        "{}",
+        got.message()
+    );
+
+    Ok(())
+}
@@ -12,6 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 mod grpc_client;
+mod grpc_consistency;
+mod grpc_metrics;
+mod grpc_retry;
 mod grpc_server;
+mod kv_compression;
 
 pub use grpc_server::start_grpc_server;
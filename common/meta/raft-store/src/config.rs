@@ -135,6 +135,20 @@ pub struct RaftConfig {
     /// For test only: specifies the tree name prefix
     #[clap(long, default_value = "")]
     pub sled_tree_prefix: String,
+
+    /// The keys with one of these prefixes keep the history of their recent versions.
+    /// Every node records the history by its own config, thus it should be the same on all nodes.
+    #[clap(
+        long,
+        env = "METASRV_KV_HISTORY_PREFIXES",
+        multiple_occurrences = true,
+        multiple_values = true
+    )]
+    pub kv_history_prefixes: Vec<String>,
+
+    /// The number of the recent versions kept in the history of a key, 0 to disable the history.
+    #[clap(long, env = "METASRV_KV_HISTORY_DEPTH", default_value = "0")]
+    pub kv_history_depth: u64,
 }
 
 pub fn get_default_raft_advertise_host() -> String {
@@ -168,6 +182,8 @@ impl Default for RaftConfig {
             join: vec![],
             id: 0,
             sled_tree_prefix: "".to_string(),
+            kv_history_prefixes: vec![],
+            kv_history_depth: 0,
         }
     }
 }
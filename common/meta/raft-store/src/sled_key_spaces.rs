@@ -186,6 +186,34 @@ impl SledKeySpace for ShareInbounds {
     type V = SeqV<ShareInboundValue>;
 }
 
+/// The recent versions of a generic kv key, newest first, see `RaftConfig::kv_history_depth`.
+pub struct KVHistory {}
+impl SledKeySpace for KVHistory {
+    const PREFIX: u8 = 24;
+    const NAME: &'static str = "kv-history";
+    type K = String;
+    type V = Vec<SeqV<Vec<u8>>>;
+}
+
+/// The replies of the recently applied logs with a txid, by `RaftTxId::to_string()`, to reply a
+/// duplicate log with instead of applying it again.
+pub struct AppliedTxIds {}
+impl SledKeySpace for AppliedTxIds {
+    const PREFIX: u8 = 25;
+    const NAME: &'static str = "applied-txids";
+    type K = String;
+    type V = ClientLastRespValue;
+}
+
+/// The txid recorded in every slot of the bounded window of `AppliedTxIds`, to evict the oldest.
+pub struct AppliedTxIdSlots {}
+impl SledKeySpace for AppliedTxIdSlots {
+    const PREFIX: u8 = 26;
+    const NAME: &'static str = "applied-txid-slots";
+    type K = u64;
+    type V = String;
+}
+
 /// Enum of key-value pair types of all key spaces.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum KeySpaceKV {
@@ -253,4 +281,16 @@ pub enum KeySpaceKV {
         key: <ShareInbounds as SledKeySpace>::K,
         value: <ShareInbounds as SledKeySpace>::V,
     },
+    KVHistory {
+        key: <KVHistory as SledKeySpace>::K,
+        value: <KVHistory as SledKeySpace>::V,
+    },
+    AppliedTxIds {
+        key: <AppliedTxIds as SledKeySpace>::K,
+        value: <AppliedTxIds as SledKeySpace>::V,
+    },
+    AppliedTxIdSlots {
+        key: <AppliedTxIdSlots as SledKeySpace>::K,
+        value: <AppliedTxIdSlots as SledKeySpace>::V,
+    },
 }
@@ -14,6 +14,7 @@
 
 use std::convert::TryInto;
 use std::fmt::Debug;
+use std::sync::Mutex;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
@@ -27,6 +28,8 @@ use common_meta_sled_store::SledKeySpace;
 use common_meta_sled_store::SledTree;
 use common_meta_sled_store::Store;
 use common_meta_sled_store::TransactionSledTree;
+use common_meta_types::decode_kv_counter;
+use common_meta_types::encode_kv_counter;
 use common_meta_types::error_context::WithContext;
 use common_meta_types::AppError;
 use common_meta_types::AppliedState;
@@ -39,7 +42,10 @@ use common_meta_types::DatabaseMeta;
 use common_meta_types::DropDatabaseReq;
 use common_meta_types::DropShareReq;
 use common_meta_types::DropTableReq;
+use common_meta_types::IllegalKVCounter;
+use common_meta_types::IncrKVReq;
 use common_meta_types::KVMeta;
+use common_meta_types::KVSeqMismatched;
 use common_meta_types::LogEntry;
 use common_meta_types::LogId;
 use common_meta_types::MatchSeq;
@@ -50,16 +56,22 @@ use common_meta_types::MetaStorageResult;
 use common_meta_types::Node;
 use common_meta_types::NodeId;
 use common_meta_types::Operation;
+use common_meta_types::RaftTxId;
 use common_meta_types::RenameTableReq;
 use common_meta_types::SeqV;
 use common_meta_types::ShareInfo;
 use common_meta_types::TableAlreadyExists;
 use common_meta_types::TableMeta;
+use common_meta_types::TxnOp;
+use common_meta_types::TxnOpResponse;
+use common_meta_types::TxnReply;
+use common_meta_types::TxnRequest;
 use common_meta_types::UnknownDatabase;
 use common_meta_types::UnknownDatabaseId;
 use common_meta_types::UnknownShare;
 use common_meta_types::UnknownTable;
 use common_meta_types::UnknownTableId;
+use common_meta_types::UpsertKVBatchReq;
 use common_tracing::tracing;
 use openraft::raft::Entry;
 use openraft::raft::EntryPayload;
@@ -67,10 +79,13 @@ use serde::Deserialize;
 use serde::Serialize;
 
 use crate::config::RaftConfig;
+use crate::sled_key_spaces::AppliedTxIdSlots;
+use crate::sled_key_spaces::AppliedTxIds;
 use crate::sled_key_spaces::ClientLastResps;
 use crate::sled_key_spaces::DatabaseLookup;
 use crate::sled_key_spaces::Databases;
 use crate::sled_key_spaces::GenericKV;
+use crate::sled_key_spaces::KVHistory;
 use crate::sled_key_spaces::Nodes;
 use crate::sled_key_spaces::Sequences;
 use crate::sled_key_spaces::ShareLookup;
@@ -98,6 +113,12 @@ const SEQ_TABLE_ID: &str = "table_id";
 const SEQ_DATABASE_META_ID: &str = "database_meta_id";
 /// seq number key to generate share id
 const SEQ_SHARE_ID: &str = "share_id";
+/// seq number key to count the applied logs with a txid, to find the slot to record one in
+const SEQ_APPLIED_TXID: &str = "applied_txid";
+
+/// The number of the recently applied txids to remember, a duplicate of an older one is applied
+/// again.
+pub const APPLIED_TXID_WINDOW: u64 = 10240;
 
 /// sled db tree name for nodes
 // const TREE_NODES: &str = "nodes";
@@ -106,7 +127,9 @@ const TREE_STATE_MACHINE: &str = "state_machine";
 
 /// StateMachine subscriber trait
 pub trait StateMachineSubscriber: Debug + Sync + Send {
-    fn kv_changed(&self, key: &str, prev: Option<SeqV>, current: Option<SeqV>);
+    /// Called for every change of a generic kv, with the kv seq the change takes: the seq of
+    /// the new value, or a seq allocated for a deletion.
+    fn kv_changed(&self, key: &str, seq: u64, prev: Option<SeqV>, current: Option<SeqV>);
 }
 
 /// The state machine of the `MemStore`.
@@ -121,8 +144,22 @@ pub struct StateMachine {
 
     /// subscriber of statemachine data
     pub subscriber: Option<Box<dyn StateMachineSubscriber>>,
+
+    /// The changes of generic kv made by the cmd being applied, which are told to the
+    /// subscriber once the transaction of the cmd is committed, see [StateMachine::notify_kv_changes].
+    pending_kv_changes: Mutex<Vec<KVChangeNotice>>,
+
+    /// The keys with one of these prefixes keep the history of their recent versions.
+    kv_history_prefixes: Vec<String>,
+
+    /// The number of the recent versions kept in the history of a key.
+    kv_history_depth: usize,
 }
 
+/// A change of a generic kv to tell the subscriber about: the key, the seq the change takes, the
+/// previous and the current value.
+type KVChangeNotice = (String, u64, Option<SeqV>, Option<SeqV>);
+
 /// A key-value pair in a snapshot is a vec of two `Vec<u8>`.
 pub type SnapshotKeyValue = Vec<Vec<u8>>;
 
@@ -174,6 +211,9 @@ impl StateMachine {
         let sm = StateMachine {
             sm_tree,
             subscriber: None,
+            pending_kv_changes: Mutex::new(vec![]),
+            kv_history_prefixes: config.kv_history_prefixes.clone(),
+            kv_history_depth: config.kv_history_depth as usize,
         };
 
         let inited = {
@@ -261,6 +301,11 @@ impl StateMachine {
                         if serial == txid.serial {
                             return Ok(Some(resp));
                         }
+
+                        // A client may have several writes in flight, the last one is not enough.
+                        if let Some(resp) = self.txn_get_applied_txid(txid, &txn_tree)? {
+                            return Ok(Some(resp));
+                        }
                     }
 
                     let res = self.apply_cmd(&data.cmd, &txn_tree);
@@ -272,6 +317,7 @@ impl StateMachine {
                             (txid.serial, applied_state.clone()),
                             &txn_tree,
                         )?;
+                        self.txn_record_applied_txid(txid, &applied_state, &txn_tree)?;
                     }
                     return Ok(Some(applied_state));
                 }
@@ -291,7 +337,10 @@ impl StateMachine {
         });
 
         let opt_applied_state = match result {
-            Ok(x) => x,
+            Ok(x) => {
+                self.notify_kv_changes();
+                x
+            }
             Err(meta_sto_err) => {
                 return match meta_sto_err {
                     MetaStorageError::AppError(app_err) => Ok(AppliedState::AppError(app_err)),
@@ -540,6 +589,35 @@ impl StateMachine {
         value_meta: &Option<KVMeta>,
         txn_tree: &TransactionSledTree,
     ) -> MetaStorageResult<AppliedState> {
+        let change = self.txn_upsert_kv(key, seq, value_op, value_meta, txn_tree)?;
+        Ok(change.into())
+    }
+
+    /// Applies the upserts one by one in order, a mismatching seq leaves only its own key unchanged.
+    #[tracing::instrument(level = "debug", skip(self, txn_tree))]
+    fn apply_upsert_kv_batch_cmd(
+        &self,
+        req: &UpsertKVBatchReq,
+        txn_tree: &TransactionSledTree,
+    ) -> MetaStorageResult<AppliedState> {
+        let mut changes = Vec::with_capacity(req.actions.len());
+        for act in &req.actions {
+            let change =
+                self.txn_upsert_kv(&act.key, &act.seq, &act.value, &act.value_meta, txn_tree)?;
+            changes.push(change);
+        }
+
+        Ok(AppliedState::KVBatch(changes))
+    }
+
+    fn txn_upsert_kv(
+        &self,
+        key: &str,
+        seq: &MatchSeq,
+        value_op: &Operation<Vec<u8>>,
+        value_meta: &Option<KVMeta>,
+        txn_tree: &TransactionSledTree,
+    ) -> MetaStorageResult<Change<Vec<u8>>> {
         let sub_tree = txn_tree.key_space::<GenericKV>();
         let key_str = key.to_string();
         let (prev, result) = self.txn_sub_tree_upsert(
@@ -552,10 +630,282 @@ impl StateMachine {
 
         tracing::debug!("applied UpsertKV: {} {:?}", key, result);
 
+        self.txn_record_kv_history(txn_tree, &key_str, &prev, &result)?;
+        self.txn_notify_kv_changed(txn_tree, &key_str, &prev, &result)?;
+
+        Ok(Change::new(prev, result))
+    }
+
+    /// Prepends the new version of a key to its history, if the key keeps one and is updated.
+    ///
+    /// A deletion is not recorded, the versions before it are kept for finding out what is lost.
+    fn txn_record_kv_history(
+        &self,
+        txn_tree: &TransactionSledTree,
+        key: &str,
+        prev: &Option<SeqV>,
+        result: &Option<SeqV>,
+    ) -> MetaStorageResult<()> {
+        let depth = self.kv_history_depth_of(key);
+        if depth == 0 {
+            return Ok(());
+        }
+
+        let current = match result {
+            // A mismatching seq leaves the key unchanged.
+            Some(r) if prev.as_ref().map(|p| p.seq) != Some(r.seq) => r,
+            _ => return Ok(()),
+        };
+
+        let history_tree = txn_tree.key_space::<KVHistory>();
+        let key = key.to_string();
+        let mut versions = history_tree.get(&key)?.unwrap_or_default();
+        versions.insert(0, current.clone());
+        versions.truncate(depth);
+        history_tree.insert(&key, &versions)?;
+
+        Ok(())
+    }
+
+    /// Records a change of a generic kv to tell the subscriber about, nothing is recorded if
+    /// nothing changed. The change is told by [StateMachine::notify_kv_changes], once the
+    /// transaction is committed; an aborted transaction tells nothing.
+    ///
+    /// A deletion takes a new kv seq, so that every change has its own seq to resume a watch
+    /// from. The seq is taken with or without a subscriber, to keep the state machines of all
+    /// the nodes identical.
+    fn txn_notify_kv_changed(
+        &self,
+        txn_tree: &TransactionSledTree,
+        key: &str,
+        prev: &Option<SeqV>,
+        result: &Option<SeqV>,
+    ) -> MetaStorageResult<()> {
+        let seq = match (prev, result) {
+            (None, None) => return Ok(()),
+            (Some(p), Some(r)) if p.seq == r.seq => return Ok(()),
+            (_, Some(r)) => r.seq,
+            (Some(_), None) => self.txn_incr_seq(GenericKV::NAME, txn_tree)?,
+        };
+
+        if self.subscriber.is_some() {
+            let mut pending = self.pending_kv_changes.lock().unwrap();
+            pending.push((key.to_string(), seq, prev.clone(), result.clone()));
+        }
+
+        Ok(())
+    }
+
+    /// Tells the subscriber about the changes of generic kv made by the last applied cmd.
+    ///
+    /// It must be called after the transaction of the cmd is committed.
+    pub fn notify_kv_changes(&self) {
+        let pending = std::mem::take(&mut *self.pending_kv_changes.lock().unwrap());
         if let Some(subscriber) = &self.subscriber {
-            subscriber.kv_changed(&key_str, prev.clone(), result.clone());
+            for (key, seq, prev, result) in pending {
+                subscriber.kv_changed(&key, seq, prev, result);
+            }
+        }
+    }
+
+    /// Returns the number of the versions kept in the history of `key`, 0 if it keeps none.
+    pub fn kv_history_depth_of(&self, key: &str) -> usize {
+        if self
+            .kv_history_prefixes
+            .iter()
+            .any(|prefix| key.starts_with(prefix.as_str()))
+        {
+            self.kv_history_depth
+        } else {
+            0
+        }
+    }
+
+    /// Executes one of the branches of the transaction by whether the conditions hold, in the
+    /// sled transaction of the raft log, thus atomically.
+    #[tracing::instrument(level = "debug", skip(self, txn_tree))]
+    fn apply_txn_cmd(
+        &self,
+        req: &TxnRequest,
+        txn_tree: &TransactionSledTree,
+    ) -> MetaStorageResult<AppliedState> {
+        let sub_tree = txn_tree.key_space::<GenericKV>();
+
+        let mut success = true;
+        for cond in &req.condition {
+            let prev = Self::unexpired_opt(sub_tree.get(&cond.key)?);
+            if cond.seq.match_seq(&prev).is_err() {
+                success = false;
+                break;
+            }
+        }
+
+        let ops = if success {
+            &req.if_then
+        } else {
+            &req.else_then
+        };
+        let mut responses = Vec::with_capacity(ops.len());
+        for op in ops {
+            let resp = match op {
+                TxnOp::Put {
+                    key,
+                    value,
+                    value_meta,
+                } => {
+                    let (prev, result) = self.txn_sub_tree_upsert(
+                        &sub_tree,
+                        key,
+                        &MatchSeq::Any,
+                        Operation::Update(value.clone()),
+                        value_meta.clone(),
+                    )?;
+                    self.txn_record_kv_history(txn_tree, key, &prev, &result)?;
+                    self.txn_notify_kv_changed(txn_tree, key, &prev, &result)?;
+                    TxnOpResponse::Put(Change::new(prev, result))
+                }
+                TxnOp::Delete { key } => {
+                    let (prev, result) = self.txn_sub_tree_upsert(
+                        &sub_tree,
+                        key,
+                        &MatchSeq::Any,
+                        Operation::Delete,
+                        None,
+                    )?;
+                    self.txn_notify_kv_changed(txn_tree, key, &prev, &result)?;
+                    TxnOpResponse::Delete(Change::new(prev, result))
+                }
+                TxnOp::Get { key } => TxnOpResponse::Get(Self::unexpired_opt(sub_tree.get(key)?)),
+            };
+            responses.push(resp);
+        }
+
+        tracing::debug!("applied Transaction: success: {}", success);
+
+        Ok(TxnReply { success, responses }.into())
+    }
+
+    /// Removes the kv expired before `expire_before`, scanning at most `limit` keys from
+    /// `start` on, or all of them if `limit` is 0.
+    #[tracing::instrument(level = "debug", skip(self, txn_tree))]
+    fn apply_remove_expired_kv_cmd(
+        &self,
+        expire_before: u64,
+        start: &str,
+        limit: u64,
+        txn_tree: &TransactionSledTree,
+    ) -> MetaStorageResult<AppliedState> {
+        let sub_tree = txn_tree.key_space::<GenericKV>();
+        let mut scanned = 0;
+        let mut removed = 0;
+        let mut next = None;
+
+        // Logs are applied one by one, nothing changes between the scan and the removal.
+        for kv in self.kvs().range(start.to_string()..)? {
+            let (key, seq_v) = kv?;
+            if limit > 0 && scanned >= limit {
+                next = Some(key);
+                break;
+            }
+            scanned += 1;
+
+            if seq_v.get_expire_at() >= expire_before {
+                continue;
+            }
+
+            sub_tree.remove(&key)?;
+            self.txn_notify_kv_changed(txn_tree, &key, &Some(seq_v), &None)?;
+            removed += 1;
+        }
+
+        tracing::debug!(
+            "removed {} kv expired before {}, next: {:?}",
+            removed,
+            expire_before,
+            next
+        );
+
+        Ok(AppliedState::KVSwept { removed, next })
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, txn_tree))]
+    fn apply_delete_kv_by_prefix_cmd(
+        &self,
+        prefix: &str,
+        limit: u64,
+        txn_tree: &TransactionSledTree,
+    ) -> MetaStorageResult<AppliedState> {
+        let sub_tree = txn_tree.key_space::<GenericKV>();
+        let mut count = 0;
+
+        // Logs are applied one by one, nothing changes between the scan and the removal.
+        for kv in self.kvs().range(prefix.to_string()..)? {
+            let (key, seq_v) = kv?;
+            if count >= limit || !key.starts_with(prefix) {
+                break;
+            }
+
+            sub_tree.remove(&key)?;
+            // An expired entry is already absent to a reader.
+            self.txn_notify_kv_changed(txn_tree, &key, &Self::unexpired(seq_v), &None)?;
+            count += 1;
+        }
+
+        tracing::debug!("removed {} kv with prefix {}", count, prefix);
+
+        Ok(AppliedState::KVRemoved { count })
+    }
+
+    /// Adds to the counter at the key, an absent key is a counter of 0.
+    ///
+    /// It fails if the seq does not match, or the value is not a counter, or it overflows.
+    #[tracing::instrument(level = "debug", skip(self, txn_tree))]
+    fn apply_incr_kv_cmd(
+        &self,
+        req: &IncrKVReq,
+        txn_tree: &TransactionSledTree,
+    ) -> MetaStorageResult<AppliedState> {
+        let sub_tree = txn_tree.key_space::<GenericKV>();
+        let prev = Self::unexpired_opt(sub_tree.get(&req.key)?);
+
+        if req.seq.match_seq(&prev).is_err() {
+            let curr = prev.as_ref().map(|v| v.seq).unwrap_or(0);
+            return Err(MetaStorageError::AppError(AppError::KVSeqMismatched(
+                KVSeqMismatched::new(&req.key, req.seq, curr, "apply_incr_kv_cmd"),
+            )));
         }
 
+        let curr = match &prev {
+            None => 0,
+            Some(v) => decode_kv_counter(&v.data).ok_or_else(|| {
+                MetaStorageError::AppError(AppError::IllegalKVCounter(IllegalKVCounter::new(
+                    &req.key,
+                    "apply_incr_kv_cmd: not a counter",
+                )))
+            })?,
+        };
+        let value = curr.checked_add(req.delta).ok_or_else(|| {
+            MetaStorageError::AppError(AppError::IllegalKVCounter(IllegalKVCounter::new(
+                &req.key,
+                format!("apply_incr_kv_cmd: {} + {} overflows", curr, req.delta),
+            )))
+        })?;
+
+        // The counter keeps the expiration of the key.
+        let value_meta = prev.as_ref().and_then(|v| v.meta.clone());
+        let result = self.txn_sub_tree_do_update(
+            &sub_tree,
+            &req.key,
+            prev.clone(),
+            value_meta,
+            Operation::Update(encode_kv_counter(value)),
+        )?;
+
+        tracing::debug!("applied IncrKV: {} {:?}", req.key, result);
+
+        self.txn_record_kv_history(txn_tree, &req.key, &prev, &result)?;
+        self.txn_notify_kv_changed(txn_tree, &req.key, &prev, &result)?;
+
         Ok(Change::new(prev, result).into())
     }
 
@@ -625,6 +975,9 @@ impl StateMachine {
     ) -> Result<AppliedState, MetaStorageError> {
         tracing::debug!("apply_cmd: {:?}", cmd);
 
+        // The changes recorded by an aborted transaction, e.g. one being retried, are dropped.
+        self.pending_kv_changes.lock().unwrap().clear();
+
         match cmd {
             Cmd::IncrSeq { ref key } => self.apply_incr_seq_cmd(key, txn_tree),
 
@@ -655,6 +1008,22 @@ impl StateMachine {
             } => self.apply_update_kv_cmd(key, seq, value_op, value_meta, txn_tree),
 
             Cmd::UpsertTableOptions(ref req) => self.apply_upsert_table_options_cmd(req, txn_tree),
+
+            Cmd::Transaction(req) => self.apply_txn_cmd(req, txn_tree),
+
+            Cmd::RemoveExpiredKV {
+                expire_before,
+                start,
+                limit,
+            } => self.apply_remove_expired_kv_cmd(*expire_before, start, *limit, txn_tree),
+
+            Cmd::DeleteKVByPrefix { prefix, limit } => {
+                self.apply_delete_kv_by_prefix_cmd(prefix, *limit, txn_tree)
+            }
+
+            Cmd::IncrKV(req) => self.apply_incr_kv_cmd(req, txn_tree),
+
+            Cmd::UpsertKVBatch(req) => self.apply_upsert_kv_batch_cmd(req, txn_tree),
         }
     }
 
@@ -875,6 +1244,40 @@ impl StateMachine {
         Ok(value.1)
     }
 
+    fn txn_get_applied_txid(
+        &self,
+        txid: &RaftTxId,
+        txn_tree: &TransactionSledTree,
+    ) -> MetaStorageResult<Option<AppliedState>> {
+        let applied = txn_tree.key_space::<AppliedTxIds>();
+        let v = applied.get(&txid.to_string())?;
+        Ok(v.map(|v| v.res))
+    }
+
+    /// Records the reply of a txid in the next slot of the window, evicting the one in the slot.
+    fn txn_record_applied_txid(
+        &self,
+        txid: &RaftTxId,
+        res: &AppliedState,
+        txn_tree: &TransactionSledTree,
+    ) -> MetaStorageResult<()> {
+        let key = txid.to_string();
+        let n = self.txn_incr_seq(SEQ_APPLIED_TXID, txn_tree)?;
+
+        let slots = txn_tree.key_space::<AppliedTxIdSlots>();
+        let applied = txn_tree.key_space::<AppliedTxIds>();
+
+        if let Some(evicted) = slots.insert(&(n % APPLIED_TXID_WINDOW), &key)? {
+            applied.remove(&evicted)?;
+        }
+        applied.insert(&key, &ClientLastRespValue {
+            req_serial_num: txid.serial,
+            res: res.clone(),
+        })?;
+
+        Ok(())
+    }
+
     pub fn get_membership(&self) -> MetaStorageResult<Option<EffectiveMembership>> {
         let sm_meta = self.sm_meta();
         let mem = sm_meta
@@ -967,6 +1370,13 @@ impl StateMachine {
         Ok(res.map(|x| x.0))
     }
 
+    /// Returns the last kv seq taken by a change of a generic kv, 0 if there is none.
+    pub fn get_kv_seq(&self) -> MetaResult<u64> {
+        let sequences = self.sequences();
+        let res = sequences.get(&GenericKV::NAME.to_string())?;
+        Ok(res.map(|x| x.0).unwrap_or_default())
+    }
+
     // TODO(xp): need a better name.
     pub fn get_table_meta_by_id(&self, tid: &u64) -> MetaResult<Option<SeqV<TableMeta>>> {
         let x = self.tables().get(tid)?;
@@ -991,7 +1401,8 @@ impl StateMachine {
     pub fn unexpired<V: Debug>(seq_value: SeqV<V>) -> Option<SeqV<V>> {
         // TODO(xp): log must be assigned with a ts.
 
-        // TODO(xp): background task to clean expired
+        // The expired entries are only hidden here, they are removed by the leader, by submitting
+        // a `Cmd::RemoveExpiredKV` log periodically, see `apply_remove_expired_kv_cmd`.
 
         // TODO(xp): Caveat: The cleanup must be consistent across raft nodes:
         //           A conditional update, e.g. an upsert_kv() with MatchSeq::Eq(some_value),
@@ -1174,6 +1585,11 @@ impl StateMachine {
         self.sm_tree.key_space()
     }
 
+    /// The recent versions of the generic kv keys.
+    pub fn kv_history(&self) -> AsKeySpace<KVHistory> {
+        self.sm_tree.key_space()
+    }
+
     /// storage of auto-incremental number.
     pub fn sequences(&self) -> AsKeySpace<Sequences> {
         self.sm_tree.key_space()
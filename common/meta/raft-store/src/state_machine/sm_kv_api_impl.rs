@@ -12,15 +12,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::ops::Bound;
+
 use common_meta_api::KVApi;
+use common_meta_api::WatchStream;
 use common_meta_types::AppliedState;
 use common_meta_types::Cmd;
+use common_meta_types::DeleteKVReply;
 use common_meta_types::GetKVActionReply;
+use common_meta_types::GetKVMetaReply;
+use common_meta_types::IncrKVReply;
+use common_meta_types::IncrKVReq;
+use common_meta_types::KVHistoryReply;
 use common_meta_types::MGetKVActionReply;
+use common_meta_types::MatchSeq;
 use common_meta_types::MetaError;
+use common_meta_types::Operation;
+use common_meta_types::PrefixListPage;
 use common_meta_types::SeqV;
+use common_meta_types::TxnReply;
+use common_meta_types::TxnRequest;
 use common_meta_types::UpsertKVAction;
 use common_meta_types::UpsertKVActionReply;
+use common_meta_types::UpsertKVBatchReply;
+use common_meta_types::UpsertKVBatchReq;
+use common_meta_types::DELETE_KV_BY_PREFIX_CHUNK_SIZE;
 use common_tracing::tracing;
 
 use crate::state_machine::StateMachine;
@@ -40,6 +56,8 @@ impl KVApi for StateMachine {
             Ok(r)
         })?;
 
+        self.notify_kv_changes();
+
         match res {
             AppliedState::KV(x) => Ok(x),
             _ => {
@@ -48,6 +66,33 @@ impl KVApi for StateMachine {
         }
     }
 
+    async fn upsert_kv_batch(
+        &self,
+        actions: Vec<UpsertKVAction>,
+    ) -> Result<UpsertKVBatchReply, MetaError> {
+        let mut changes = Vec::with_capacity(actions.len());
+
+        for req in UpsertKVBatchReq::split(actions) {
+            let cmd = Cmd::UpsertKVBatch(req);
+
+            let res = self.sm_tree.txn(true, |t| {
+                let r = self.apply_cmd(&cmd, &t)?;
+                Ok(r)
+            })?;
+
+            self.notify_kv_changes();
+
+            match res {
+                AppliedState::KVBatch(x) => changes.extend(x),
+                _ => {
+                    panic!("expect AppliedState::KVBatch");
+                }
+            }
+        }
+
+        Ok(changes)
+    }
+
     async fn get_kv(&self, key: &str) -> Result<GetKVActionReply, MetaError> {
         // TODO(xp) refine get(): a &str is enough for key
         let sv = self.kvs().get(&key.to_string())?;
@@ -60,6 +105,19 @@ impl KVApi for StateMachine {
         Ok(Self::unexpired(sv))
     }
 
+    async fn get_kv_meta(&self, key: &str) -> Result<GetKVMetaReply, MetaError> {
+        let sv = self.kvs().get(&key.to_string())?;
+        let sv = Self::unexpired_opt(sv);
+
+        Ok(sv.map(|sv| (sv.seq, sv.meta.unwrap_or_default())))
+    }
+
+    async fn get_kv_history(&self, key: &str, limit: usize) -> Result<KVHistoryReply, MetaError> {
+        let mut versions = self.kv_history().get(&key.to_string())?.unwrap_or_default();
+        versions.truncate(limit);
+        Ok(versions)
+    }
+
     async fn mget_kv(&self, keys: &[String]) -> Result<MGetKVActionReply, MetaError> {
         let kvs = self.kvs();
         let mut res = vec![];
@@ -90,4 +148,136 @@ impl KVApi for StateMachine {
 
         Ok(x.collect())
     }
+
+    async fn prefix_list_kv_paged(
+        &self,
+        prefix: &str,
+        after_key: Option<String>,
+        limit: usize,
+        keys_only: bool,
+    ) -> Result<PrefixListPage, MetaError> {
+        if limit == 0 {
+            return Err(MetaError::MetaServiceError(
+                "the limit of a page must be positive".to_string(),
+            ));
+        }
+
+        let start = match after_key {
+            Some(k) if k.as_str() >= prefix => Bound::Excluded(k),
+            _ => Bound::Included(prefix.to_string()),
+        };
+
+        let mut page = PrefixListPage::default();
+
+        for kv in self.kvs().range((start, Bound::Unbounded))? {
+            let (key, v) = kv?;
+            if !key.starts_with(prefix) {
+                break;
+            }
+
+            let v = match Self::unexpired(v) {
+                None => continue,
+                Some(v) => v,
+            };
+
+            // One more key than the limit: there is a next page.
+            if page.items.len() == limit {
+                page.last_key = page.items.last().map(|(k, _)| k.clone());
+                break;
+            }
+
+            page.items
+                .push((key, if keys_only { None } else { Some(v) }));
+        }
+
+        Ok(page)
+    }
+
+    async fn transaction(&self, txn: TxnRequest) -> Result<TxnReply, MetaError> {
+        let cmd = Cmd::Transaction(txn);
+
+        let res = self.sm_tree.txn(true, |t| {
+            let r = self.apply_cmd(&cmd, &t).unwrap();
+            Ok(r)
+        })?;
+
+        self.notify_kv_changes();
+
+        match res {
+            AppliedState::TxnReply(x) => Ok(x),
+            _ => {
+                panic!("expect AppliedState::TxnReply");
+            }
+        }
+    }
+
+    async fn delete_kv(&self, key: &str, seq: MatchSeq) -> Result<DeleteKVReply, MetaError> {
+        self.upsert_kv(UpsertKVAction::new(key, seq, Operation::Delete, None))
+            .await
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64, MetaError> {
+        let mut removed = 0;
+
+        loop {
+            let cmd = Cmd::DeleteKVByPrefix {
+                prefix: prefix.to_string(),
+                limit: DELETE_KV_BY_PREFIX_CHUNK_SIZE,
+            };
+
+            let res = self.sm_tree.txn(true, |t| {
+                let r = self.apply_cmd(&cmd, &t).unwrap();
+                Ok(r)
+            })?;
+
+            self.notify_kv_changes();
+
+            let count = match res {
+                AppliedState::KVRemoved { count } => count,
+                _ => {
+                    panic!("expect AppliedState::KVRemoved");
+                }
+            };
+
+            removed += count;
+            if count < DELETE_KV_BY_PREFIX_CHUNK_SIZE {
+                return Ok(removed);
+            }
+        }
+    }
+
+    async fn incr_kv(
+        &self,
+        key: &str,
+        delta: i64,
+        seq: MatchSeq,
+    ) -> Result<IncrKVReply, MetaError> {
+        let cmd = Cmd::IncrKV(IncrKVReq {
+            key: key.to_string(),
+            delta,
+            seq,
+        });
+
+        let res = self.sm_tree.txn(true, |t| {
+            let r = self.apply_cmd(&cmd, &t)?;
+            Ok(r)
+        })?;
+
+        self.notify_kv_changes();
+
+        match res {
+            AppliedState::KV(x) => IncrKVReq::reply(x),
+            _ => {
+                panic!("expect AppliedState::KV");
+            }
+        }
+    }
+
+    async fn watch(&self, prefix: &str, _from_seq: Option<u64>) -> Result<WatchStream, MetaError> {
+        // The changes are only published to watchers by a meta-service node.
+        Err(MetaError::MetaServiceError(format!(
+            "watch is not supported by a local state machine, prefix: {}",
+            prefix
+        )))
+    }
 }
@@ -14,6 +14,8 @@
 
 use std::collections::BTreeMap;
 use std::convert::TryInto;
+use std::sync::Arc;
+use std::sync::Mutex;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
@@ -22,6 +24,7 @@ use common_meta_api::KVApi;
 use common_meta_raft_store::state_machine::testing::pretty_snapshot;
 use common_meta_raft_store::state_machine::testing::snapshot_logs;
 use common_meta_raft_store::state_machine::StateMachine;
+use common_meta_raft_store::state_machine::StateMachineSubscriber;
 use common_meta_sled_store::openraft;
 use common_meta_types::AppError;
 use common_meta_types::AppliedState;
@@ -35,9 +38,11 @@ use common_meta_types::LogEntry;
 use common_meta_types::MatchSeq;
 use common_meta_types::MetaStorageError;
 use common_meta_types::Operation;
+use common_meta_types::RaftTxId;
 use common_meta_types::SeqV;
 use common_meta_types::TableMeta;
 use common_meta_types::UnknownTableId;
+use common_meta_types::UpsertKVAction;
 use common_meta_types::UpsertTableOptionReq;
 use common_tracing::tracing;
 use maplit::btreemap;
@@ -122,6 +127,42 @@ async fn test_state_machine_apply_incr_seq() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_apply_dup_txid_in_window() -> anyhow::Result<()> {
+    // A duplicate of a txid that is not the last of its client is not applied again either.
+
+    let (_log_guards, ut_span) = init_raft_store_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_raft_test_context();
+    let sm = StateMachine::open(&tc.raft_config, 1).await?;
+
+    let cases = vec![
+        ("first", RaftTxId::new("foo", 1), 1),
+        ("second", RaftTxId::new("foo", 2), 2),
+        ("dup of first", RaftTxId::new("foo", 1), 1),
+        ("dup of second", RaftTxId::new("foo", 2), 2),
+        ("third", RaftTxId::new("foo", 3), 3),
+    ];
+
+    for (name, txid, want) in cases.into_iter() {
+        let resp = sm
+            .apply(&Entry {
+                log_id: LogId { term: 0, index: 5 },
+                payload: EntryPayload::Normal(LogEntry {
+                    txid: Some(txid),
+                    cmd: Cmd::IncrSeq {
+                        key: "k1".to_string(),
+                    },
+                }),
+            })
+            .await?;
+        assert_eq!(AppliedState::Seq { seq: want }, resp, "{}", name);
+    }
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_state_machine_apply_add_database() -> anyhow::Result<()> {
     let (_log_guards, ut_span) = init_raft_store_ut!();
@@ -719,6 +760,337 @@ async fn test_state_machine_apply_non_dup_generic_kv_delete() -> anyhow::Result<
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_apply_delete_kv_by_prefix() -> anyhow::Result<()> {
+    // - Removes at most `limit` keys with the prefix in one log.
+
+    let (_log_guards, ut_span) = init_raft_store_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_raft_test_context();
+    let sm = StateMachine::open(&tc.raft_config, 1).await?;
+
+    for key in ["a/1", "a/2", "a/3", "ab", "b/1"] {
+        sm.sm_tree.txn(true, |t| {
+            Ok(sm
+                .apply_cmd(
+                    &Cmd::UpsertKV {
+                        key: key.to_string(),
+                        seq: MatchSeq::Any,
+                        value: Operation::Update(key.as_bytes().to_vec()),
+                        value_meta: None,
+                    },
+                    &t,
+                )
+                .unwrap())
+        })?;
+    }
+
+    let delete = |limit| {
+        sm.sm_tree.txn(true, |t| {
+            Ok(sm
+                .apply_cmd(
+                    &Cmd::DeleteKVByPrefix {
+                        prefix: "a/".to_string(),
+                        limit,
+                    },
+                    &t,
+                )
+                .unwrap())
+        })
+    };
+
+    assert_eq!(AppliedState::KVRemoved { count: 2 }, delete(2)?);
+    assert_eq!(AppliedState::KVRemoved { count: 1 }, delete(2)?);
+    assert_eq!(AppliedState::KVRemoved { count: 0 }, delete(2)?);
+
+    let res = sm.prefix_list_kv("").await?;
+    let keys = res.iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>();
+    assert_eq!(vec!["ab", "b/1"], keys);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_delete_takes_kv_seq() -> anyhow::Result<()> {
+    // - Every change of a generic kv, including a deletion, takes its own kv seq.
+    // - A deletion of an absent key changes nothing and takes no seq.
+
+    let (_log_guards, ut_span) = init_raft_store_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_raft_test_context();
+    let sm = StateMachine::open(&tc.raft_config, 1).await?;
+
+    let upsert =
+        |key: &str, op: Operation<Vec<u8>>| UpsertKVAction::new(key, MatchSeq::Any, op, None);
+
+    assert_eq!(0, sm.get_kv_seq()?);
+
+    sm.upsert_kv(upsert("a", Operation::Update(b"a".to_vec())))
+        .await?;
+    assert_eq!(1, sm.get_kv_seq()?);
+
+    sm.upsert_kv(upsert("a", Operation::Delete)).await?;
+    assert_eq!(2, sm.get_kv_seq()?);
+
+    sm.upsert_kv(upsert("a", Operation::Delete)).await?;
+    assert_eq!(2, sm.get_kv_seq()?);
+
+    let res = sm
+        .upsert_kv(upsert("b", Operation::Update(b"b".to_vec())))
+        .await?;
+    assert_eq!(Some(3), res.result.map(|v| v.seq));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_kv_history() -> anyhow::Result<()> {
+    // - Write a covered key 5 times with a history of depth 3, and an uncovered key.
+    // - Only the last 3 versions of the covered key are kept, newest first.
+
+    let (_log_guards, ut_span) = init_raft_store_ut!();
+    let _ent = ut_span.enter();
+
+    let mut tc = new_raft_test_context();
+    tc.raft_config.kv_history_prefixes = vec!["h/".to_string()];
+    tc.raft_config.kv_history_depth = 3;
+    let sm = StateMachine::open(&tc.raft_config, 1).await?;
+
+    let upsert = |key: &str, seq: MatchSeq, value: &str| {
+        UpsertKVAction::new(key, seq, Operation::Update(value.as_bytes().to_vec()), None)
+    };
+
+    for i in 1..=5 {
+        let v = format!("v{}", i);
+        sm.upsert_kv(upsert("h/a", MatchSeq::Any, &v)).await?;
+        sm.upsert_kv(upsert("x/a", MatchSeq::Any, &v)).await?;
+    }
+
+    // A mismatching seq changes nothing, thus records nothing.
+    sm.upsert_kv(upsert("h/a", MatchSeq::Exact(1), "v6"))
+        .await?;
+
+    let got = sm.get_kv_history("h/a", 10).await?;
+    let got = got
+        .iter()
+        .map(|v| (v.seq, String::from_utf8(v.data.clone()).unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            (9, "v5".to_string()),
+            (7, "v4".to_string()),
+            (5, "v3".to_string())
+        ],
+        got
+    );
+
+    assert_eq!(1, sm.get_kv_history("h/a", 1).await?.len());
+    assert!(sm.get_kv_history("x/a", 10).await?.is_empty());
+    assert!(sm.get_kv_history("h/absent", 10).await?.is_empty());
+
+    // A deletion keeps the history.
+    sm.upsert_kv(UpsertKVAction::new(
+        "h/a",
+        MatchSeq::Any,
+        Operation::Delete,
+        None,
+    ))
+    .await?;
+    assert_eq!(3, sm.get_kv_history("h/a", 10).await?.len());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_apply_remove_expired_kv() -> anyhow::Result<()> {
+    // - Add a long expired, a just expired and a never expiring kv.
+    // - Only the long expired one is removed physically.
+
+    let (_log_guards, ut_span) = init_raft_store_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_raft_test_context();
+    let sm = StateMachine::open(&tc.raft_config, 1).await?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    for (key, expire_at) in [("a", Some(now - 100)), ("b", Some(now - 10)), ("c", None)] {
+        sm.sm_tree.txn(true, |t| {
+            Ok(sm
+                .apply_cmd(
+                    &Cmd::UpsertKV {
+                        key: key.to_string(),
+                        seq: MatchSeq::Any,
+                        value: Operation::Update(key.as_bytes().to_vec()),
+                        value_meta: expire_at.map(|x| KVMeta { expire_at: Some(x) }),
+                    },
+                    &t,
+                )
+                .unwrap())
+        })?;
+    }
+
+    let resp = sm.sm_tree.txn(true, |t| {
+        Ok(sm
+            .apply_cmd(
+                &Cmd::RemoveExpiredKV {
+                    expire_before: now - 50,
+                    start: "".to_string(),
+                    limit: 0,
+                },
+                &t,
+            )
+            .unwrap())
+    })?;
+    assert_eq!(
+        AppliedState::KVSwept {
+            removed: 1,
+            next: None
+        },
+        resp
+    );
+
+    tracing::info!("--- only the long expired is removed");
+    {
+        assert!(sm.kvs().get(&"a".to_string())?.is_none());
+        assert!(sm.kvs().get(&"b".to_string())?.is_some());
+        assert!(sm.kvs().get(&"c".to_string())?.is_some());
+    }
+
+    tracing::info!("--- the expired but not removed is still invisible");
+    {
+        assert_eq!(None, sm.get_kv("b").await?);
+        assert_eq!(Some(SeqV::new(3, b"c".to_vec())), sm.get_kv("c").await?);
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_apply_remove_expired_kv_in_chunks() -> anyhow::Result<()> {
+    // - Add 5 long expired kv.
+    // - Each log scans 2 keys, and tells where the next one continues.
+
+    let (_log_guards, ut_span) = init_raft_store_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_raft_test_context();
+    let sm = StateMachine::open(&tc.raft_config, 1).await?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    for i in 0..5 {
+        let key = format!("k{}", i);
+        sm.sm_tree.txn(true, |t| {
+            Ok(sm
+                .apply_cmd(
+                    &Cmd::UpsertKV {
+                        key: key.clone(),
+                        seq: MatchSeq::Any,
+                        value: Operation::Update(key.as_bytes().to_vec()),
+                        value_meta: Some(KVMeta {
+                            expire_at: Some(now - 100),
+                        }),
+                    },
+                    &t,
+                )
+                .unwrap())
+        })?;
+    }
+
+    let remove = |start: &str| {
+        sm.sm_tree.txn(true, |t| {
+            Ok(sm
+                .apply_cmd(
+                    &Cmd::RemoveExpiredKV {
+                        expire_before: now - 50,
+                        start: start.to_string(),
+                        limit: 2,
+                    },
+                    &t,
+                )
+                .unwrap())
+        })
+    };
+
+    let swept = |removed, next: Option<&str>| AppliedState::KVSwept {
+        removed,
+        next: next.map(|x| x.to_string()),
+    };
+    assert_eq!(swept(2, Some("k2")), remove("")?);
+    assert!(sm.kvs().get(&"k1".to_string())?.is_none());
+    assert!(sm.kvs().get(&"k2".to_string())?.is_some());
+    assert_eq!(swept(2, Some("k4")), remove("k2")?);
+    assert_eq!(swept(1, None), remove("k4")?);
+    assert_eq!(swept(0, None), remove("")?);
+
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+struct ChangedKeys {
+    keys: Arc<Mutex<Vec<String>>>,
+}
+
+impl StateMachineSubscriber for ChangedKeys {
+    fn kv_changed(&self, key: &str, _seq: u64, _prev: Option<SeqV>, _current: Option<SeqV>) {
+        self.keys.lock().unwrap().push(key.to_string());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_machine_notify_kv_changes_after_commit() -> anyhow::Result<()> {
+    // - A change in an aborted transaction is not told.
+    // - A change in an applied log is told, once applied.
+
+    let (_log_guards, ut_span) = init_raft_store_ut!();
+    let _ent = ut_span.enter();
+
+    let tc = new_raft_test_context();
+    let mut sm = StateMachine::open(&tc.raft_config, 1).await?;
+    let changed = ChangedKeys::default();
+    let keys = changed.keys.clone();
+    sm.set_subscriber(Box::new(changed));
+
+    let upsert = |key: &str| Cmd::UpsertKV {
+        key: key.to_string(),
+        seq: MatchSeq::Any,
+        value: Operation::Update(key.as_bytes().to_vec()),
+        value_meta: None,
+    };
+
+    let res = sm.sm_tree.txn(true, |t| {
+        sm.apply_cmd(&upsert("aborted"), &t).unwrap();
+        Err::<(), _>(MetaStorageError::AppError(AppError::UnknownTableId(
+            UnknownTableId::new(0, "abort"),
+        )))
+    });
+    assert!(res.is_err());
+    sm.notify_kv_changes();
+    assert!(keys.lock().unwrap().is_empty());
+    assert!(sm.kvs().get(&"aborted".to_string())?.is_none());
+
+    sm.apply(&Entry {
+        log_id: LogId { term: 0, index: 5 },
+        payload: EntryPayload::Normal(LogEntry {
+            txid: None,
+            cmd: upsert("applied"),
+        }),
+    })
+    .await?;
+    assert_eq!(vec!["applied".to_string()], *keys.lock().unwrap());
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_state_machine_snapshot() -> anyhow::Result<()> {
     // - Feed logs into state machine.
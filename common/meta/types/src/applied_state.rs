@@ -26,6 +26,7 @@ use crate::MetaError;
 use crate::Node;
 use crate::ShareInfo;
 use crate::TableMeta;
+use crate::TxnReply;
 
 /// The state of an applied raft log.
 /// Normally it includes two fields: the state before applying and the state after applying the log.
@@ -53,6 +54,25 @@ pub enum AppliedState {
 
     KV(Change<Vec<u8>>),
 
+    /// The change of every key of a batch, in order.
+    KVBatch(Vec<Change<Vec<u8>>>),
+
+    TxnReply(TxnReply),
+
+    /// The number of the removed kv entries.
+    #[try_into(ignore)]
+    KVRemoved {
+        count: u64,
+    },
+
+    /// The number of the removed expired kv entries, and the key the removal continues from,
+    /// None if all the keys are scanned.
+    #[try_into(ignore)]
+    KVSwept {
+        removed: u64,
+        next: Option<String>,
+    },
+
     AppError(AppError),
 
     #[try_into(ignore)]
@@ -123,6 +143,10 @@ impl AppliedState {
             AppliedState::TableMeta(ref ch) => ch.changed(),
             AppliedState::ShareInfo(ref ch) => ch.changed(),
             AppliedState::KV(ref ch) => ch.changed(),
+            AppliedState::KVBatch(ref chs) => chs.iter().any(|ch| ch.changed()),
+            AppliedState::TxnReply(ref reply) => reply.success,
+            AppliedState::KVRemoved { count } => *count > 0,
+            AppliedState::KVSwept { removed, .. } => *removed > 0,
             AppliedState::None => false,
             AppliedState::AppError(_e) => false,
         }
@@ -153,6 +177,10 @@ impl AppliedState {
             AppliedState::TableMeta(Change { ref prev, .. }) => prev.is_none(),
             AppliedState::ShareInfo(Change { ref prev, .. }) => prev.is_none(),
             AppliedState::KV(Change { ref prev, .. }) => prev.is_none(),
+            AppliedState::KVBatch(_) => true,
+            AppliedState::TxnReply(_) => true,
+            AppliedState::KVRemoved { count } => *count == 0,
+            AppliedState::KVSwept { removed, .. } => *removed == 0,
             AppliedState::None => true,
             AppliedState::AppError(_e) => true,
         }
@@ -167,6 +195,10 @@ impl AppliedState {
             AppliedState::TableMeta(Change { ref result, .. }) => result.is_none(),
             AppliedState::ShareInfo(Change { ref result, .. }) => result.is_none(),
             AppliedState::KV(Change { ref result, .. }) => result.is_none(),
+            AppliedState::KVBatch(_) => true,
+            AppliedState::TxnReply(_) => true,
+            AppliedState::KVRemoved { .. } => true,
+            AppliedState::KVSwept { .. } => true,
             AppliedState::None => true,
             AppliedState::AppError(_e) => true,
         }
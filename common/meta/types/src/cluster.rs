@@ -54,6 +54,8 @@ pub struct NodeInfo {
     pub cpu_nums: u64,
     pub version: u32,
     pub flight_address: String,
+    /// What the node is able to do, e.g., to choose the nodes for a distributed plan.
+    pub capabilities: Vec<String>,
 }
 
 impl TryFrom<Vec<u8>> for NodeInfo {
@@ -77,9 +79,16 @@ impl NodeInfo {
             cpu_nums,
             version: 0,
             flight_address,
+            capabilities: vec![],
         }
     }
 
+    #[must_use]
+    pub fn with_capabilities(mut self, capabilities: Vec<String>) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
     pub fn ip_port(&self) -> Result<(String, u16)> {
         let addr = SocketAddr::from_str(&self.flight_address)?;
 
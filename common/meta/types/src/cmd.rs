@@ -24,11 +24,14 @@ use crate::CreateTableReq;
 use crate::DropDatabaseReq;
 use crate::DropShareReq;
 use crate::DropTableReq;
+use crate::IncrKVReq;
 use crate::KVMeta;
 use crate::MatchSeq;
 use crate::Node;
 use crate::Operation;
 use crate::RenameTableReq;
+use crate::TxnRequest;
+use crate::UpsertKVBatchReq;
 use crate::UpsertTableOptionReq;
 
 /// A Cmd describes what a user want to do to raft state machine
@@ -91,6 +94,34 @@ pub enum Cmd {
         /// Meta data of a value.
         value_meta: Option<KVMeta>,
     },
+
+    /// Update several keys of the general purpose kv store atomically, if the conditions hold.
+    Transaction(TxnRequest),
+
+    /// Remove the general purpose kv entries that expired before `expire_before`, in seconds since 1970.
+    ///
+    /// The time is assigned by the leader, so that every node removes the same entries.
+    /// At most `limit` keys from `start` on are scanned, 0 for no limit; the applied state tells
+    /// where the next log continues.
+    RemoveExpiredKV {
+        expire_before: u64,
+        #[serde(default)]
+        start: String,
+        #[serde(default)]
+        limit: u64,
+    },
+
+    /// Remove at most `limit` general purpose kv entries whose key starts with `prefix`.
+    DeleteKVByPrefix {
+        prefix: String,
+        limit: u64,
+    },
+
+    /// Add to an i64 counter in the general purpose kv store.
+    IncrKV(IncrKVReq),
+
+    /// Update or insert several keys of the general purpose kv store in order, each by its own seq.
+    UpsertKVBatch(UpsertKVBatchReq),
 }
 
 impl fmt::Display for Cmd {
@@ -122,6 +153,29 @@ impl fmt::Display for Cmd {
                     key, seq, value, value_meta
                 )
             }
+            Cmd::Transaction(txn) => {
+                write!(f, "txn: {:?}", txn)
+            }
+            Cmd::RemoveExpiredKV {
+                expire_before,
+                start,
+                limit,
+            } => {
+                write!(
+                    f,
+                    "remove_expired_kv: before {} from {:?} limit {}",
+                    expire_before, start, limit
+                )
+            }
+            Cmd::DeleteKVByPrefix { prefix, limit } => {
+                write!(f, "delete_kv_by_prefix: {} limit {}", prefix, limit)
+            }
+            Cmd::IncrKV(req) => {
+                write!(f, "incr_kv: {}({:?}) += {}", req.key, req.seq, req.delta)
+            }
+            Cmd::UpsertKVBatch(req) => {
+                write!(f, "upsert_kv_batch: {} actions", req.actions.len())
+            }
         }
     }
 }
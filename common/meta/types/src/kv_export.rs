@@ -0,0 +1,158 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protobuf::ExportedKVChunk;
+use crate::protobuf::KVEntryPiece;
+use crate::KVMeta;
+use crate::MetaError;
+use crate::SeqV;
+
+/// The max size of the values in an exported chunk, well below the 4 MiB gRPC message limit.
+pub const EXPORT_KV_CHUNK_MAX_BYTES: usize = 1024 * 1024;
+
+/// A kv entry exported from the meta service.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KVEntry {
+    pub key: String,
+    /// The seq in the exporting store, it is not kept by an import.
+    pub seq: u64,
+    pub meta: Option<KVMeta>,
+    pub value: Vec<u8>,
+}
+
+impl KVEntry {
+    pub fn new(key: impl ToString, seqv: SeqV) -> Self {
+        Self {
+            key: key.to_string(),
+            seq: seqv.seq,
+            meta: seqv.meta,
+            value: seqv.data,
+        }
+    }
+}
+
+/// How an imported entry is written if the key already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Replace the existing value.
+    Overwrite,
+    /// Keep the existing value.
+    SkipExisting,
+    /// Import nothing if any key exists.
+    FailOnConflict,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub imported: u64,
+    /// The entries not imported since the key exists, by `ImportMode::SkipExisting`.
+    pub skipped: u64,
+}
+
+/// Packs the entries into chunks, each with at most `max_bytes` of values.
+///
+/// A value larger than `max_bytes` is split into pieces in consecutive chunks.
+pub fn export_kv_chunks(
+    entries: impl IntoIterator<Item = KVEntry>,
+    max_bytes: usize,
+) -> Vec<ExportedKVChunk> {
+    let max_bytes = max_bytes.max(1);
+    let mut chunks = vec![];
+    let mut chunk = ExportedKVChunk::default();
+    let mut size = 0;
+
+    for entry in entries {
+        let expire_at = entry.meta.as_ref().and_then(|m| m.expire_at);
+        let mut value = &entry.value[..];
+
+        loop {
+            if size >= max_bytes {
+                chunks.push(std::mem::take(&mut chunk));
+                size = 0;
+            }
+
+            let n = std::cmp::min(value.len(), max_bytes - size);
+            let last = n == value.len();
+            chunk.pieces.push(KVEntryPiece {
+                key: entry.key.clone(),
+                seq: entry.seq,
+                expire_at,
+                data: value[..n].to_vec(),
+                last,
+            });
+            size += n;
+            value = &value[n..];
+
+            if last {
+                break;
+            }
+        }
+    }
+
+    if !chunk.pieces.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+/// Assembles the exported entries from the pieces, in the order they are exported.
+#[derive(Debug, Default)]
+pub struct KVEntryAssembler {
+    pending: Option<KVEntry>,
+}
+
+impl KVEntryAssembler {
+    /// Returns the entry completed by the `piece`, if any.
+    pub fn push(&mut self, piece: KVEntryPiece) -> Result<Option<KVEntry>, MetaError> {
+        let mut entry = match self.pending.take() {
+            None => KVEntry {
+                key: piece.key,
+                seq: piece.seq,
+                meta: piece.expire_at.map(|t| KVMeta { expire_at: Some(t) }),
+                value: vec![],
+            },
+            Some(entry) => {
+                if entry.key != piece.key {
+                    return Err(MetaError::MetaServiceError(format!(
+                        "incomplete exported value of key: {}, got a piece of: {}",
+                        entry.key, piece.key
+                    )));
+                }
+                entry
+            }
+        };
+
+        entry.value.extend_from_slice(&piece.data);
+        if piece.last {
+            Ok(Some(entry))
+        } else {
+            self.pending = Some(entry);
+            Ok(None)
+        }
+    }
+
+    /// Checks that no value is left incomplete at the end of the export.
+    pub fn finish(self) -> Result<(), MetaError> {
+        match self.pending {
+            None => Ok(()),
+            Some(entry) => Err(MetaError::MetaServiceError(format!(
+                "incomplete exported value of key: {}",
+                entry.key
+            ))),
+        }
+    }
+}
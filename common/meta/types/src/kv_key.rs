@@ -0,0 +1,136 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Builds the kv keys of the meta service out of segments.
+//!
+//! A key is a `/` separated path, e.g., `__fd_users/<tenant>/<user>`, and a segment that is a
+//! user given name must not add levels to it, or one name would be listed under the prefix of
+//! another. Every byte of a segment except `[0-9a-zA-Z_]` is escaped as `%xx`, by
+//! `common_base::escape_for_key`, thus the keys stored by the earlier versions do not change.
+
+use common_base::escape_for_key;
+use common_exception::ErrorCode;
+use common_exception::Result;
+
+/// Builds a kv key by appending escaped segments to a prefix.
+///
+/// ```ignore
+/// let key = KeyBuilder::new("__fd_users").push("tenant1")?.push("a/b")?.done();
+/// assert_eq!("__fd_users/tenant1/a%2fb", key);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBuilder {
+    key: String,
+}
+
+impl KeyBuilder {
+    /// The prefix is a constant that is not escaped.
+    pub fn new(prefix: impl ToString) -> Self {
+        KeyBuilder {
+            key: prefix.to_string(),
+        }
+    }
+
+    /// Appends a segment, it is validated and escaped.
+    pub fn push(mut self, segment: &str) -> Result<Self> {
+        validate_key_segment(segment)?;
+        self.key.push('/');
+        self.key.push_str(&escape_key_segment(segment));
+        Ok(self)
+    }
+
+    /// Appends a segment that is already escaped, e.g., a number.
+    pub fn push_raw(mut self, segment: impl ToString) -> Self {
+        self.key.push('/');
+        self.key.push_str(&segment.to_string());
+        self
+    }
+
+    /// The prefix to list all the keys below the built one, with a trailing `/`.
+    pub fn dir(self) -> String {
+        format!("{}/", self.key)
+    }
+
+    pub fn done(self) -> String {
+        self.key
+    }
+}
+
+/// Rejects a segment that can not be a level of a key: an empty one, or one with a NUL.
+pub fn validate_key_segment(segment: &str) -> Result<()> {
+    if segment.is_empty() {
+        return Err(ErrorCode::InvalidMetaKey("empty segment of a meta key"));
+    }
+    if segment.contains('\0') {
+        return Err(ErrorCode::InvalidMetaKey(format!(
+            "segment of a meta key contains NUL: {:?}",
+            segment
+        )));
+    }
+    Ok(())
+}
+
+/// Escapes every byte of `segment` except `[0-9a-zA-Z_]` as `%xx`, by `escape_for_key`.
+pub fn escape_key_segment(segment: &str) -> String {
+    // the escaped segment is ascii, always utf8
+    escape_for_key(segment).expect("escaped key segment is ascii")
+}
+
+/// The reverse of `escape_key_segment`.
+///
+/// A `%` that is not followed by two hex digits, or an escape that does not decode to utf8,
+/// is an `ErrorCode::InvalidMetaKey`.
+pub fn unescape_key_segment(segment: &str) -> Result<String> {
+    let bytes = segment.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes
+                .get(i + 1..i + 3)
+                .and_then(|h| std::str::from_utf8(h).ok())
+                .and_then(|h| u8::from_str_radix(h, 16).ok());
+
+            match hex {
+                Some(b) => unescaped.push(b),
+                None => {
+                    return Err(ErrorCode::InvalidMetaKey(format!(
+                        "invalid escape at {} of meta key segment: {:?}",
+                        i, segment
+                    )));
+                }
+            }
+            i += 3;
+        } else {
+            unescaped.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(unescaped).map_err(|e| {
+        ErrorCode::InvalidMetaKey(format!(
+            "meta key segment is not utf8 after unescaping: {:?}, {}",
+            segment, e
+        ))
+    })
+}
+
+/// Unescapes a segment listed from the store, which may be written unescaped by an older version.
+///
+/// Such a segment is returned as is if it is not a valid escaped one, e.g., `50%`.
+/// A valid escape in an unescaped segment can not be told apart and is decoded.
+pub fn unescape_key_segment_compat(segment: &str) -> String {
+    unescape_key_segment(segment).unwrap_or_else(|_| segment.to_string())
+}
@@ -12,9 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::protobuf::event;
+use crate::protobuf::watch_request::FilterType;
+use crate::protobuf::WatchRequest;
+use crate::protobuf::WatchResponse;
 use crate::Change;
 use crate::KVMeta;
 use crate::MatchSeq;
+use crate::MetaError;
+use crate::MetaResultError;
 use crate::Operation;
 use crate::SeqV;
 
@@ -23,6 +29,19 @@ pub struct GetKVReq {
     pub key: String,
 }
 
+/// Gets the seq and the meta of a key, without the value.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct GetKVMetaReq {
+    pub key: String,
+}
+
+/// Gets at most `limit` of the recent versions of a key, newest first.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct GetKVHistoryReq {
+    pub key: String,
+    pub limit: usize,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 pub struct MGetKVReq {
     pub keys: Vec<String>,
@@ -33,9 +52,34 @@ pub struct ListKVReq {
     pub prefix: String,
 }
 
+/// Lists at most `limit` keys starting with `prefix` and after `after_key`, in key order.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct ListKVPageReq {
+    pub prefix: String,
+    pub after_key: Option<String>,
+    pub limit: usize,
+    /// Reply only the keys, without the values.
+    pub keys_only: bool,
+}
+
+/// A page of keys and their values, the values are `None` if only the keys are listed.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PrefixListPage {
+    pub items: Vec<(String, Option<SeqV<Vec<u8>>>)>,
+    /// The last key in this page, to list the next page after.
+    /// It is `None` if there are no more keys.
+    pub last_key: Option<String>,
+}
+
 pub type UpsertKVActionReply = Change<Vec<u8>>;
+pub type DeleteKVReply = Change<Vec<u8>>;
 pub type GetKVActionReply = Option<SeqV<Vec<u8>>>;
+/// The seq and the meta of a key, `None` for an absent key.
+pub type GetKVMetaReply = Option<(u64, KVMeta)>;
+/// One value per requested key, in the order of the keys, with `None` for an absent key.
 pub type MGetKVActionReply = Vec<Option<SeqV<Vec<u8>>>>;
+/// The recent versions of a key, newest first, empty if the key keeps no history.
+pub type KVHistoryReply = Vec<SeqV<Vec<u8>>>;
 pub type PrefixListReply = Vec<(String, SeqV<Vec<u8>>)>;
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
@@ -61,3 +105,242 @@ impl UpsertKVAction {
         }
     }
 }
+
+/// Max number of the actions of an `UpsertKVBatchReq`.
+pub const UPSERT_KV_BATCH_MAX_ACTIONS: usize = 1024;
+
+/// Max total size of the keys and the values of an `UpsertKVBatchReq`, in bytes.
+pub const UPSERT_KV_BATCH_MAX_BYTES: usize = 4 * 1024 * 1024;
+
+/// Upserts several keys in order, by one raft log.
+///
+/// It is not atomic: every action is applied by its own seq, regardless of whether the others
+/// match, and gets its own result.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct UpsertKVBatchReq {
+    pub actions: Vec<UpsertKVAction>,
+}
+
+/// The change of every action of an `UpsertKVBatchReq`, in the order of the actions.
+pub type UpsertKVBatchReply = Vec<UpsertKVActionReply>;
+
+impl UpsertKVBatchReq {
+    /// Splits `actions` into batches within `UPSERT_KV_BATCH_MAX_ACTIONS` and
+    /// `UPSERT_KV_BATCH_MAX_BYTES`, keeping the order.
+    ///
+    /// An action larger than `UPSERT_KV_BATCH_MAX_BYTES` is a batch by itself.
+    pub fn split(actions: Vec<UpsertKVAction>) -> Vec<UpsertKVBatchReq> {
+        let mut batches = vec![];
+        let mut batch = vec![];
+        let mut bytes = 0;
+
+        for act in actions {
+            let size = act.key.len()
+                + match &act.value {
+                    Operation::Update(v) => v.len(),
+                    _ => 0,
+                };
+            if !batch.is_empty()
+                && (batch.len() >= UPSERT_KV_BATCH_MAX_ACTIONS
+                    || bytes + size > UPSERT_KV_BATCH_MAX_BYTES)
+            {
+                batches.push(UpsertKVBatchReq {
+                    actions: std::mem::take(&mut batch),
+                });
+                bytes = 0;
+            }
+            bytes += size;
+            batch.push(act);
+        }
+
+        if !batch.is_empty() {
+            batches.push(UpsertKVBatchReq { actions: batch });
+        }
+        batches
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct DeleteKVReq {
+    pub key: String,
+    pub seq: MatchSeq,
+}
+
+impl DeleteKVReq {
+    pub fn new(key: &str, seq: MatchSeq) -> Self {
+        Self {
+            key: key.to_string(),
+            seq,
+        }
+    }
+}
+
+/// Max number of keys removed by one raft log when deleting a prefix.
+pub const DELETE_KV_BY_PREFIX_CHUNK_SIZE: u64 = 1024;
+
+/// Max number of keys scanned by one raft log when removing the expired keys.
+pub const REMOVE_EXPIRED_KV_CHUNK_SIZE: u64 = 1024;
+
+/// Deletes all the keys starting with `prefix`, replies with the number of the removed keys.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct DeleteKVByPrefixReq {
+    pub prefix: String,
+}
+
+/// Adds `delta` to the i64 counter stored at `key`, if the seq of the key matches.
+///
+/// An absent key is a counter of 0, so it is created if `seq` allows an absent key.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct IncrKVReq {
+    pub key: String,
+    pub delta: i64,
+    pub seq: MatchSeq,
+}
+
+/// The seq and the value of a counter after it is incremented.
+pub type IncrKVReply = (u64, i64);
+
+/// Encodes the value of a counter updated by `IncrKVReq`, as a decimal string.
+pub fn encode_kv_counter(value: i64) -> Vec<u8> {
+    value.to_string().into_bytes()
+}
+
+/// Decodes the value of a counter, returns None if it is not an i64 encoded by `encode_kv_counter`.
+pub fn decode_kv_counter(data: &[u8]) -> Option<i64> {
+    std::str::from_utf8(data).ok()?.parse().ok()
+}
+
+impl IncrKVReq {
+    /// Builds the reply from the change of the key made by applying the request.
+    pub fn reply(change: Change<Vec<u8>>) -> Result<IncrKVReply, MetaError> {
+        let result = change.result.ok_or_else(|| MetaResultError::InvalidType {
+            expect: "a counter".to_string(),
+            got: "None".to_string(),
+        })?;
+        let value =
+            decode_kv_counter(&result.data).ok_or_else(|| MetaResultError::InvalidType {
+                expect: "a counter".to_string(),
+                got: String::from_utf8_lossy(&result.data).to_string(),
+            })?;
+        Ok((result.seq, value))
+    }
+}
+
+/// A condition of a transaction, holds if the seq of the key matches, e.g. `MatchSeq::Exact(0)`
+/// for an absent key.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct TxnCondition {
+    pub key: String,
+    pub seq: MatchSeq,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub enum TxnOp {
+    Put {
+        key: String,
+        value: Vec<u8>,
+        value_meta: Option<KVMeta>,
+    },
+    Delete {
+        key: String,
+    },
+    Get {
+        key: String,
+    },
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub enum TxnOpResponse {
+    Put(Change<Vec<u8>>),
+    Delete(Change<Vec<u8>>),
+    Get(Option<SeqV<Vec<u8>>>),
+}
+
+/// Executes the operations of `if_then` if all the conditions hold, otherwise the operations of
+/// `else_then`, atomically.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct TxnRequest {
+    pub condition: Vec<TxnCondition>,
+    pub if_then: Vec<TxnOp>,
+    pub else_then: Vec<TxnOp>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct TxnReply {
+    /// Whether all the conditions hold, i.e. `if_then` is executed.
+    pub success: bool,
+    /// The responses of the operations executed, in order.
+    pub responses: Vec<TxnOpResponse>,
+}
+
+impl TxnCondition {
+    pub fn new(key: &str, seq: MatchSeq) -> Self {
+        Self {
+            key: key.to_string(),
+            seq,
+        }
+    }
+}
+
+impl TxnOp {
+    pub fn put(key: &str, value: Vec<u8>) -> Self {
+        TxnOp::Put {
+            key: key.to_string(),
+            value,
+            value_meta: None,
+        }
+    }
+
+    pub fn delete(key: &str) -> Self {
+        TxnOp::Delete {
+            key: key.to_string(),
+        }
+    }
+
+    pub fn get(key: &str) -> Self {
+        TxnOp::Get {
+            key: key.to_string(),
+        }
+    }
+}
+
+/// A change of a key that is watched.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct WatchEvent {
+    /// The kv seq taken by this change, to resume a watch from with, on any node.
+    pub seq: u64,
+    pub key: String,
+    pub prev: Option<SeqV>,
+    pub current: Option<SeqV>,
+}
+
+impl TryFrom<WatchResponse> for WatchEvent {
+    type Error = MetaError;
+
+    fn try_from(resp: WatchResponse) -> Result<Self, Self::Error> {
+        let ev = resp.event.ok_or_else(|| {
+            MetaError::MetaServiceError(format!("watch response without event: seq={}", resp.seq))
+        })?;
+
+        let into_seqv = |x: event::SeqV| SeqV::new(x.seq, x.data);
+
+        Ok(WatchEvent {
+            seq: resp.seq,
+            key: ev.key,
+            prev: ev.prev.map(into_seqv),
+            current: ev.current.map(into_seqv),
+        })
+    }
+}
+
+impl WatchRequest {
+    /// Watches all the keys starting with `prefix`, from the event of `from_seq` if it is given.
+    pub fn prefix(prefix: &str, from_seq: Option<u64>) -> Self {
+        WatchRequest {
+            key: prefix.to_string(),
+            key_end: Some(format!("{}{}", prefix, char::MAX)),
+            filter_type: FilterType::All.into(),
+            from_seq,
+        }
+    }
+}
@@ -22,6 +22,8 @@ pub mod config;
 mod database;
 mod endpoint;
 mod errors;
+mod kv_export;
+mod kv_key;
 mod kv_message;
 mod log_entry;
 mod match_seq;
@@ -32,6 +34,7 @@ mod meta_network_errors;
 mod meta_raft_errors;
 mod meta_result_error;
 mod meta_storage_errors;
+mod network_policy;
 mod operation;
 mod raft_txid;
 mod raft_types;
@@ -39,11 +42,14 @@ mod role_info;
 mod seq_num;
 mod seq_value;
 mod table;
+mod user_audit;
 mod user_auth;
 mod user_defined_function;
 mod user_grant;
 mod user_identity;
 mod user_info;
+mod user_info_version;
+mod user_password_policy;
 mod user_privilege;
 mod user_quota;
 mod user_setting;
@@ -84,14 +90,50 @@ pub use database::GetDatabaseReq;
 pub use database::ListDatabaseReq;
 pub use endpoint::Endpoint;
 pub use errors::ConflictSeq;
+pub use kv_export::export_kv_chunks;
+pub use kv_export::ImportMode;
+pub use kv_export::ImportReport;
+pub use kv_export::KVEntry;
+pub use kv_export::KVEntryAssembler;
+pub use kv_export::EXPORT_KV_CHUNK_MAX_BYTES;
+pub use kv_key::escape_key_segment;
+pub use kv_key::unescape_key_segment;
+pub use kv_key::unescape_key_segment_compat;
+pub use kv_key::validate_key_segment;
+pub use kv_key::KeyBuilder;
+pub use kv_message::decode_kv_counter;
+pub use kv_message::encode_kv_counter;
+pub use kv_message::DeleteKVByPrefixReq;
+pub use kv_message::DeleteKVReply;
+pub use kv_message::DeleteKVReq;
 pub use kv_message::GetKVActionReply;
+pub use kv_message::GetKVHistoryReq;
+pub use kv_message::GetKVMetaReply;
+pub use kv_message::GetKVMetaReq;
 pub use kv_message::GetKVReq;
+pub use kv_message::IncrKVReply;
+pub use kv_message::IncrKVReq;
+pub use kv_message::KVHistoryReply;
+pub use kv_message::ListKVPageReq;
 pub use kv_message::ListKVReq;
 pub use kv_message::MGetKVActionReply;
 pub use kv_message::MGetKVReq;
+pub use kv_message::PrefixListPage;
 pub use kv_message::PrefixListReply;
+pub use kv_message::TxnCondition;
+pub use kv_message::TxnOp;
+pub use kv_message::TxnOpResponse;
+pub use kv_message::TxnReply;
+pub use kv_message::TxnRequest;
 pub use kv_message::UpsertKVAction;
 pub use kv_message::UpsertKVActionReply;
+pub use kv_message::UpsertKVBatchReply;
+pub use kv_message::UpsertKVBatchReq;
+pub use kv_message::WatchEvent;
+pub use kv_message::DELETE_KV_BY_PREFIX_CHUNK_SIZE;
+pub use kv_message::REMOVE_EXPIRED_KV_CHUNK_SIZE;
+pub use kv_message::UPSERT_KV_BATCH_MAX_ACTIONS;
+pub use kv_message::UPSERT_KV_BATCH_MAX_BYTES;
 pub use log_entry::LogEntry;
 pub use match_seq::MatchSeq;
 pub use match_seq::MatchSeqExt;
@@ -99,6 +141,8 @@ pub use message::ForwardRequest;
 pub use message::ForwardRequestBody;
 pub use message::ForwardResponse;
 pub use message::JoinRequest;
+pub use message::ReadConsistency;
+pub use message::READ_CONSISTENCY_KEY;
 pub use meta_errors::MetaError;
 pub use meta_errors::MetaResult;
 pub use meta_errors_into::ToMetaError;
@@ -111,6 +155,8 @@ pub use meta_raft_errors::RetryableError;
 pub use meta_result_error::MetaResultError;
 pub use meta_storage_errors::AppError;
 pub use meta_storage_errors::DatabaseAlreadyExists;
+pub use meta_storage_errors::IllegalKVCounter;
+pub use meta_storage_errors::KVSeqMismatched;
 pub use meta_storage_errors::MetaStorageError;
 pub use meta_storage_errors::MetaStorageResult;
 pub use meta_storage_errors::ShareAlreadyExists;
@@ -122,11 +168,15 @@ pub use meta_storage_errors::UnknownDatabaseId;
 pub use meta_storage_errors::UnknownShare;
 pub use meta_storage_errors::UnknownTable;
 pub use meta_storage_errors::UnknownTableId;
+pub use network_policy::IpCidr;
+pub use network_policy::NetworkPolicy;
 pub use operation::MetaId;
 pub use operation::MetaVersion;
 pub use operation::Operation;
 pub use principal_identity::PrincipalIdentity;
 pub use raft_txid::RaftTxId;
+pub use raft_txid::TXID_CLIENT_KEY;
+pub use raft_txid::TXID_SERIAL_KEY;
 pub use raft_types::LogId;
 pub use raft_types::LogIndex;
 pub use raft_types::NodeId;
@@ -156,6 +206,8 @@ pub use table::TableMeta;
 pub use table::TableNameIndent;
 pub use table::UpsertTableOptionReply;
 pub use table::UpsertTableOptionReq;
+pub use user_audit::UserAuditEntry;
+pub use user_audit::UserAuditOperation;
 pub use user_auth::AuthInfo;
 pub use user_auth::AuthType;
 pub use user_auth::PasswordHashMethod;
@@ -163,10 +215,14 @@ pub use user_defined_function::UserDefinedFunction;
 pub use user_grant::GrantEntry;
 pub use user_grant::GrantObject;
 pub use user_grant::UserGrantSet;
+pub use user_identity::like_match;
 pub use user_identity::UserIdentity;
 pub use user_info::UserInfo;
 pub use user_info::UserOption;
 pub use user_info::UserOptionFlag;
+pub use user_info_version::USER_INFO_VERSION;
+pub use user_password_policy::PasswordHistoryEntry;
+pub use user_password_policy::PasswordPolicy;
 pub use user_privilege::UserPrivilegeSet;
 pub use user_privilege::UserPrivilegeType;
 pub use user_quota::UserQuota;
@@ -29,16 +29,22 @@ use crate::DatabaseInfo;
 use crate::Endpoint;
 use crate::GetDatabaseReq;
 use crate::GetKVActionReply;
+use crate::GetKVHistoryReq;
+use crate::GetKVMetaReply;
+use crate::GetKVMetaReq;
 use crate::GetKVReq;
 use crate::GetShareReq;
 use crate::GetTableReq;
+use crate::KVHistoryReply;
 use crate::ListDatabaseReq;
+use crate::ListKVPageReq;
 use crate::ListKVReq;
 use crate::ListTableReq;
 use crate::LogEntry;
 use crate::MGetKVActionReply;
 use crate::MGetKVReq;
 use crate::NodeId;
+use crate::PrefixListPage;
 use crate::PrefixListReply;
 use crate::ShareInfo;
 use crate::TableInfo;
@@ -70,12 +76,57 @@ pub enum ForwardRequestBody {
     GetTable(GetTableReq),
 
     GetKV(GetKVReq),
+    GetKVMeta(GetKVMetaReq),
+    GetKVHistory(GetKVHistoryReq),
     MGetKV(MGetKVReq),
     ListKV(ListKVReq),
+    ListKVPage(ListKVPageReq),
 
     GetShare(GetShareReq),
 }
 
+/// The key of the grpc metadata to carry the `ReadConsistency` of a read.
+pub const READ_CONSISTENCY_KEY: &str = "read-consistency";
+
+/// How up to date a read must be.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadConsistency {
+    /// Read by the leader after it confirms its leadership with a quorum: a read sees every write
+    /// completed before it, at the cost of a round trip to the other nodes.
+    Linearizable,
+
+    /// Read by the node that believes it is the leader, without confirming it.
+    Lease,
+
+    /// Read by the node that receives it, which may lag behind the leader.
+    Stale,
+}
+
+impl Default for ReadConsistency {
+    fn default() -> Self {
+        ReadConsistency::Lease
+    }
+}
+
+impl ReadConsistency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReadConsistency::Linearizable => "linearizable",
+            ReadConsistency::Lease => "lease",
+            ReadConsistency::Stale => "stale",
+        }
+    }
+
+    /// Parses the value of `READ_CONSISTENCY_KEY`, an unknown one is the default.
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "linearizable" => ReadConsistency::Linearizable,
+            "stale" => ReadConsistency::Stale,
+            _ => ReadConsistency::Lease,
+        }
+    }
+}
+
 /// A request that is forwarded from one raft node to another
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ForwardRequest {
@@ -83,6 +134,10 @@ pub struct ForwardRequest {
     pub forward_to_leader: u64,
 
     pub body: ForwardRequestBody,
+
+    /// The consistency of a read, a node of an older version sends none.
+    #[serde(default)]
+    pub consistency: ReadConsistency,
 }
 
 impl ForwardRequest {
@@ -104,8 +159,11 @@ pub enum ForwardResponse {
     ShareInfo(Arc<ShareInfo>),
 
     GetKV(GetKVActionReply),
+    GetKVMeta(GetKVMetaReply),
+    KVHistory(KVHistoryReply),
     MGetKV(MGetKVActionReply),
     ListKV(PrefixListReply),
+    ListKVPage(PrefixListPage),
 }
 
 impl tonic::IntoRequest<RaftRequest> for ForwardRequest {
@@ -24,6 +24,11 @@ pub enum MetaNetworkError {
     #[error(transparent)]
     ConnectionError(#[from] ConnectionError),
 
+    /// The connection is lost after a mutating request is sent, and it is unknown whether
+    /// the request is applied.
+    #[error(transparent)]
+    OutcomeUnknown(ConnectionError),
+
     #[error("{0}")]
     GetNodeAddrError(String),
 
@@ -35,6 +40,10 @@ pub enum MetaNetworkError {
 
     #[error(transparent)]
     BadAddressFormat(AnyError),
+
+    /// A request is not completed before its deadline, a mutating one may have been applied.
+    #[error("{0}")]
+    Timeout(String),
 }
 
 impl From<MetaNetworkError> for ErrorCode {
@@ -46,6 +55,9 @@ impl From<MetaNetworkError> for ErrorCode {
             MetaNetworkError::ConnectionError(any_err) => {
                 ErrorCode::CannotConnectNode(any_err.to_string())
             }
+            MetaNetworkError::OutcomeUnknown(any_err) => {
+                ErrorCode::MetaServiceUnavailable(any_err.to_string())
+            }
             MetaNetworkError::GetNodeAddrError(_) => {
                 ErrorCode::MetaServiceError(net_err.to_string())
             }
@@ -53,6 +65,7 @@ impl From<MetaNetworkError> for ErrorCode {
                 ErrorCode::TLSConfigurationFailure(any_err.to_string())
             }
             MetaNetworkError::DnsParseError(_) => ErrorCode::DnsParseError(net_err.to_string()),
+            MetaNetworkError::Timeout(msg) => ErrorCode::MetaServiceTimeout(msg),
         }
     }
 }
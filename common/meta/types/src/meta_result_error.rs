@@ -24,6 +24,12 @@ pub enum MetaResultError {
 
     #[error("Expect result of type: {expect}, got: {got}")]
     InvalidType { expect: String, got: String },
+
+    #[error("Expect {expect} values of a mget, got: {got}")]
+    InvalidMGetResult { expect: usize, got: usize },
+
+    #[error("Invalid compressed value: {0}")]
+    InvalidCompressedValue(String),
 }
 
 impl MetaResultError {
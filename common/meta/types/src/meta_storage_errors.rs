@@ -222,6 +222,47 @@ impl UnknownShareId {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, thiserror::Error)]
+#[error("KVSeqMismatched: {key} expect `{expect}` but `{curr}` while `{context}`")]
+pub struct KVSeqMismatched {
+    key: String,
+    expect: MatchSeq,
+    curr: u64,
+    context: String,
+}
+
+impl KVSeqMismatched {
+    pub fn new(
+        key: impl Into<String>,
+        expect: MatchSeq,
+        curr: u64,
+        context: impl Into<String>,
+    ) -> Self {
+        Self {
+            key: key.into(),
+            expect,
+            curr,
+            context: context.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, thiserror::Error)]
+#[error("IllegalKVCounter: {key} while {context}")]
+pub struct IllegalKVCounter {
+    key: String,
+    context: String,
+}
+
+impl IllegalKVCounter {
+    pub fn new(key: impl Into<String>, context: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            context: context.into(),
+        }
+    }
+}
+
 #[derive(Error, Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum AppError {
     #[error(transparent)]
@@ -253,6 +294,12 @@ pub enum AppError {
 
     #[error(transparent)]
     UnknownShareId(#[from] UnknownShareId),
+
+    #[error(transparent)]
+    KVSeqMismatched(#[from] KVSeqMismatched),
+
+    #[error(transparent)]
+    IllegalKVCounter(#[from] IllegalKVCounter),
 }
 
 impl AppErrorMessage for UnknownDatabase {
@@ -276,6 +323,8 @@ impl AppErrorMessage for UnknownTable {
 impl AppErrorMessage for UnknownTableId {}
 impl AppErrorMessage for UnknownDatabaseId {}
 impl AppErrorMessage for TableVersionMismatched {}
+impl AppErrorMessage for KVSeqMismatched {}
+impl AppErrorMessage for IllegalKVCounter {}
 
 impl AppErrorMessage for TableAlreadyExists {
     fn message(&self) -> String {
@@ -316,6 +365,8 @@ impl From<AppError> for ErrorCode {
             AppError::ShareAlreadyExists(err) => ErrorCode::ShareAlreadyExists(err.message()),
             AppError::UnknownShare(err) => ErrorCode::UnknownShare(err.message()),
             AppError::UnknownShareId(err) => ErrorCode::UnknownShareId(err.message()),
+            AppError::KVSeqMismatched(err) => ErrorCode::KVSeqMismatched(err.message()),
+            AppError::IllegalKVCounter(err) => ErrorCode::IllegalKVCounter(err.message()),
         }
     }
 }
@@ -0,0 +1,154 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+/// A block of IPv4 or IPv6 addresses, e.g. `192.168.0.0/16` or `fe80::/10`.
+///
+/// An address without the prefix length is the block of the address only.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IpCidr {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpCidr {
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Result<Self> {
+        let max_len = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix_len > max_len {
+            return Err(ErrorCode::IllegalNetworkPolicyFormat(format!(
+                "invalid prefix length {} of {}",
+                prefix_len, addr
+            )));
+        }
+        Ok(IpCidr { addr, prefix_len })
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                u32::from(net) & mask == u32::from(*ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                u128::from(net) & mask == u128::from(*ip) & mask
+            }
+            // An IPv4 client may connect to a IPv6 socket.
+            (IpAddr::V4(_), IpAddr::V6(ip)) => match ip.octets() {
+                [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, a, b, c, d] => {
+                    self.contains(&IpAddr::V4(Ipv4Addr::new(a, b, c, d)))
+                }
+                _ => false,
+            },
+            (IpAddr::V6(_), IpAddr::V4(_)) => false,
+        }
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = ErrorCode;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let illegal = || ErrorCode::IllegalNetworkPolicyFormat(format!("invalid CIDR {}", s));
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, prefix_len)) => {
+                let addr = addr.parse::<IpAddr>().map_err(|_| illegal())?;
+                (addr, prefix_len.parse::<u8>().map_err(|_| illegal())?)
+            }
+            None => {
+                let addr = s.parse::<IpAddr>().map_err(|_| illegal())?;
+                let prefix_len = if addr.is_ipv4() { 32 } else { 128 };
+                (addr, prefix_len)
+            }
+        };
+        IpCidr::new(addr, prefix_len)
+    }
+}
+
+impl fmt::Display for IpCidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+impl Serialize for IpCidr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for IpCidr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse()
+            .map_err(|e: ErrorCode| serde::de::Error::custom(e.message()))
+    }
+}
+
+/// The addresses the users of the policy can connect from, referenced by name by the users.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+#[serde(default)]
+pub struct NetworkPolicy {
+    pub name: String,
+
+    /// The addresses allowed, all the addresses if empty.
+    pub allowed_ip_list: Vec<IpCidr>,
+
+    /// The addresses blocked, even if they are allowed.
+    pub blocked_ip_list: Vec<IpCidr>,
+
+    pub comment: String,
+}
+
+impl NetworkPolicy {
+    pub fn is_allowed(&self, ip: &IpAddr) -> bool {
+        if self.blocked_ip_list.iter().any(|cidr| cidr.contains(ip)) {
+            return false;
+        }
+        self.allowed_ip_list.is_empty() || self.allowed_ip_list.iter().any(|cidr| cidr.contains(ip))
+    }
+}
+
+impl TryFrom<Vec<u8>> for NetworkPolicy {
+    type Error = ErrorCode;
+
+    fn try_from(value: Vec<u8>) -> Result<Self> {
+        match serde_json::from_slice(&value) {
+            Ok(policy) => Ok(policy),
+            Err(serialize_error) => Err(ErrorCode::IllegalNetworkPolicyFormat(format!(
+                "Cannot deserialize network policy from bytes. cause {}",
+                serialize_error
+            ))),
+        }
+    }
+}
@@ -18,6 +18,11 @@ use std::fmt::Formatter;
 use serde::Deserialize;
 use serde::Serialize;
 
+/// The keys of the grpc metadata to carry the `RaftTxId` of a write, which is the same for all the
+/// attempts of the write, for the server to apply it at most once.
+pub const TXID_CLIENT_KEY: &str = "txid-client";
+pub const TXID_SERIAL_KEY: &str = "txid-serial";
+
 /// RaftTxId is the essential info to identify an write operation to raft.
 /// Logs with the same RaftTxId are considered the same and only the first of them will be applied.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -26,7 +31,8 @@ pub struct RaftTxId {
     pub client: String,
     /// The serial number of this request.
     /// TODO(xp): a client must generate consistent `client` and globally unique serial.
-    /// TODO(xp): in this impl the state machine records only one serial, which implies serial must be monotonic incremental for every client.
+    /// The state machine remembers the last serial of every client and a bounded window of the
+    /// recent txids of all clients, a duplicate older than both is applied again.
     pub serial: u64,
 }
 
@@ -0,0 +1,81 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::UserIdentity;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub enum UserAuditOperation {
+    Add,
+    Update,
+    UpdateQuota,
+    Lock,
+    Unlock,
+    SetSetting(String),
+    UnsetSetting(String),
+    /// Attached to the network policy, detached if `None`.
+    SetNetworkPolicy(Option<String>),
+    /// Renamed to the name.
+    Rename(String),
+    GrantPrivileges,
+    RevokePrivileges,
+    GrantRole(String),
+    RevokeRole(String),
+    Drop,
+}
+
+impl fmt::Display for UserAuditOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UserAuditOperation::Add => write!(f, "ADD"),
+            UserAuditOperation::Update => write!(f, "UPDATE"),
+            UserAuditOperation::UpdateQuota => write!(f, "UPDATE QUOTA"),
+            UserAuditOperation::Lock => write!(f, "LOCK"),
+            UserAuditOperation::Unlock => write!(f, "UNLOCK"),
+            UserAuditOperation::SetSetting(name) => write!(f, "SET SETTING '{}'", name),
+            UserAuditOperation::UnsetSetting(name) => write!(f, "UNSET SETTING '{}'", name),
+            UserAuditOperation::SetNetworkPolicy(Some(name)) => {
+                write!(f, "SET NETWORK POLICY '{}'", name)
+            }
+            UserAuditOperation::SetNetworkPolicy(None) => write!(f, "UNSET NETWORK POLICY"),
+            UserAuditOperation::Rename(name) => write!(f, "RENAME TO '{}'", name),
+            UserAuditOperation::GrantPrivileges => write!(f, "GRANT PRIVILEGES"),
+            UserAuditOperation::RevokePrivileges => write!(f, "REVOKE PRIVILEGES"),
+            UserAuditOperation::GrantRole(role) => write!(f, "GRANT ROLE '{}'", role),
+            UserAuditOperation::RevokeRole(role) => write!(f, "REVOKE ROLE '{}'", role),
+            UserAuditOperation::Drop => write!(f, "DROP"),
+        }
+    }
+}
+
+/// An append-only record of a change of a user.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct UserAuditEntry {
+    /// The nanoseconds since the unix epoch, when the user is changed.
+    pub timestamp_ns: u64,
+
+    /// Who changes the user.
+    pub operator: String,
+
+    pub user: UserIdentity,
+
+    pub operation: UserAuditOperation,
+
+    /// The seq of the user before the change, 0 if the user is added.
+    pub prev_seq: u64,
+}
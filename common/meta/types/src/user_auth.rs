@@ -217,10 +217,13 @@ impl AuthInfo {
                 hash_value: p,
                 hash_method: t,
             } => match t {
-                PasswordHashMethod::PlainText => Ok(p == password_input),
+                // the passwords stored in plaintext, before they were hashed, are verified by
+                // the scramble of mysql_native_password as well.
+                PasswordHashMethod::PlainText => Ok(p == password_input
+                    || (password_input.len() == 20
+                        && AuthInfo::auth_double_sha1(&double_sha1(p), password_input, salt)?)),
                 PasswordHashMethod::DoubleSha1 => {
-                    let password_sha1 = AuthInfo::restore_sha1_mysql(salt, password_input, p)?;
-                    Ok(*p == calc_sha1(&password_sha1))
+                    AuthInfo::auth_double_sha1(p, password_input, salt)
                 }
                 PasswordHashMethod::Sha256 => Err(ErrorCode::AuthenticateFailure(
                     "login with sha256_password user for mysql protocol not supported yet.",
@@ -232,6 +235,33 @@ impl AuthInfo {
             ))),
         }
     }
+
+    /// Verifies the response of a client.
+    ///
+    /// With a salt, the response is the scramble of the challenge of the MySQL handshake,
+    /// see [AuthInfo::auth_mysql]. Without a salt, the response is the password in plaintext,
+    /// which is hashed by the method of the password.
+    pub fn auth(&self, client_response: &[u8], salt: &[u8]) -> Result<bool> {
+        if !salt.is_empty() {
+            return self.auth_mysql(client_response, salt);
+        }
+        match self {
+            AuthInfo::None => Ok(true),
+            AuthInfo::Password {
+                hash_value: p,
+                hash_method: t,
+            } => Ok(*p == t.hash(client_response)),
+            _ => Err(ErrorCode::AuthenticateFailure(format!(
+                "user require auth type {}",
+                self.get_type().to_str()
+            ))),
+        }
+    }
+
+    fn auth_double_sha1(password_hash: &[u8], password_input: &[u8], salt: &[u8]) -> Result<bool> {
+        let password_sha1 = AuthInfo::restore_sha1_mysql(salt, password_input, password_hash)?;
+        Ok(*password_hash == calc_sha1(&password_sha1))
+    }
 }
 
 impl Default for AuthInfo {
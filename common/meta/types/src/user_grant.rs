@@ -75,6 +75,14 @@ impl GrantEntry {
         Self { object, privileges }
     }
 
+    pub fn object(&self) -> &GrantObject {
+        &self.object
+    }
+
+    pub fn privileges(&self) -> UserPrivilegeSet {
+        self.privileges.into()
+    }
+
     pub fn verify_privilege(&self, object: &GrantObject, privilege: UserPrivilegeType) -> bool {
         // the verified object should be smaller than the object inside my grant entry.
         if !self.object.contains(object) {
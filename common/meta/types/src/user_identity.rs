@@ -14,7 +14,7 @@
 
 use std::fmt;
 
-#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct UserIdentity {
     pub username: String,
     pub hostname: String,
@@ -31,6 +31,47 @@ impl UserIdentity {
     pub fn is_localhost(&self) -> bool {
         &self.hostname.to_lowercase() == "localhost" || &self.hostname == "127.0.0.1"
     }
+
+    /// Whether the host of a client matches the hostname, which may be a pattern of `%`
+    /// (any characters) and `_` (one character), e.g. '10.0.%'.
+    pub fn host_matches(&self, client_host: &str) -> bool {
+        like_match(&self.hostname.to_lowercase(), &client_host.to_lowercase())
+    }
+
+    /// The specificity of the hostname, as the numbers of its characters which are not
+    /// wildcards and of its `_`. Of the hostnames matching a host, the greater one is the
+    /// more specific one, and `%` is the least specific one.
+    pub fn host_specificity(&self) -> (usize, usize) {
+        let literals = self
+            .hostname
+            .chars()
+            .filter(|c| *c != '%' && *c != '_')
+            .count();
+        let underscores = self.hostname.chars().filter(|c| *c == '_').count();
+        (literals, underscores)
+    }
+}
+
+/// Whether the string matches the pattern, of `%` (any characters) and `_` (one character) as
+/// the LIKE of SQL, without escapes.
+pub fn like_match(pattern: &str, s: &str) -> bool {
+    let s = s.chars().collect::<Vec<_>>();
+
+    // matched[j]: whether the pattern so far matches the first j characters of the string
+    let mut matched = vec![false; s.len() + 1];
+    matched[0] = true;
+    for p in pattern.chars() {
+        let mut next = vec![false; s.len() + 1];
+        for j in 0..=s.len() {
+            next[j] = match p {
+                '%' => matched[j] || (j > 0 && next[j - 1]),
+                '_' => j > 0 && matched[j - 1],
+                c => j > 0 && matched[j - 1] && s[j - 1] == c,
+            };
+        }
+        matched = next;
+    }
+    matched[s.len()]
 }
 
 impl fmt::Display for UserIdentity {
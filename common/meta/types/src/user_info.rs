@@ -13,8 +13,10 @@
 // limitations under the License.
 
 use core::fmt;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 
+use chrono::Utc;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use enumflags2::bitflags;
@@ -24,6 +26,8 @@ use serde::Serialize;
 
 use crate::user_grant::UserGrantSet;
 use crate::AuthInfo;
+use crate::PasswordHistoryEntry;
+use crate::PasswordPolicy;
 use crate::UserIdentity;
 use crate::UserQuota;
 
@@ -41,6 +45,25 @@ pub struct UserInfo {
     pub quota: UserQuota,
 
     pub option: UserOption,
+
+    /// A locked user can not be authenticated, the sessions authenticated already are kept.
+    pub is_locked: bool,
+
+    pub locked_reason: Option<String>,
+
+    /// The default settings of the sessions of the user, the settings set in a session override them.
+    pub settings: HashMap<String, String>,
+
+    pub password_policy: PasswordPolicy,
+
+    /// The name of the network policy restricting the addresses the user can connect from.
+    pub network_policy: Option<String>,
+
+    /// A built-in user can not be dropped, locked or revoked from, unless forced.
+    pub is_builtin: bool,
+
+    /// The digests of the previous passwords, the latest first.
+    pub password_history: Vec<PasswordHistoryEntry>,
 }
 
 impl UserInfo {
@@ -57,6 +80,13 @@ impl UserInfo {
             grants,
             quota,
             option,
+            is_locked: false,
+            locked_reason: None,
+            settings: HashMap::new(),
+            password_policy: PasswordPolicy::default(),
+            network_policy: None,
+            is_builtin: false,
+            password_history: vec![],
         }
     }
 
@@ -74,19 +104,68 @@ impl UserInfo {
     pub fn has_option_flag(&self, flag: UserOptionFlag) -> bool {
         self.option.has_option_flag(flag)
     }
+
+    /// Errors with `BuiltinUserProtected` if the user is built-in and the change is not forced.
+    pub fn check_not_builtin(&self, force: bool) -> Result<()> {
+        if force || !self.is_builtin {
+            return Ok(());
+        }
+        Err(ErrorCode::BuiltinUserProtected(format!(
+            "user {} is built-in",
+            self.identity()
+        )))
+    }
+
+    /// Changes the password to `auth_info`, errors with `PasswordPolicyViolation` if it is one of
+    /// the last `history_depth` passwords, including the current one. The previous password is
+    /// kept in the history, which is trimmed to the `history_depth`.
+    pub fn change_password(&mut self, auth_info: AuthInfo, history_depth: u64) -> Result<()> {
+        let now = Utc::now();
+        let depth = history_depth as usize;
+        let current = PasswordHistoryEntry::from_auth_info(&self.auth_info, now);
+        if depth > 0 {
+            if let Some(new) = PasswordHistoryEntry::from_auth_info(&auth_info, now) {
+                let reused = current
+                    .iter()
+                    .chain(self.password_history.iter())
+                    .take(depth)
+                    .any(|prev| prev.is_same_password(&new));
+                if reused {
+                    return Err(ErrorCode::PasswordPolicyViolation(format!(
+                        "password of user {} must not be any of the last {} passwords",
+                        self.identity(),
+                        depth
+                    )));
+                }
+            }
+        }
+        if let Some(current) = current {
+            self.password_history.insert(0, current);
+        }
+        // The current password is checked besides the history.
+        self.password_history.truncate(depth.saturating_sub(1));
+        self.auth_info = auth_info;
+        Ok(())
+    }
+
+    /// Errors with `AuthenticateFailure` if the user is locked, to be checked before the
+    /// password is verified.
+    pub fn check_not_locked(&self) -> Result<()> {
+        if !self.is_locked {
+            return Ok(());
+        }
+        Err(ErrorCode::AuthenticateFailure(match &self.locked_reason {
+            Some(reason) => format!("user {} is locked: {}", self.identity(), reason),
+            None => format!("user {} is locked", self.identity()),
+        }))
+    }
 }
 
 impl TryFrom<Vec<u8>> for UserInfo {
     type Error = ErrorCode;
 
     fn try_from(value: Vec<u8>) -> Result<Self> {
-        match serde_json::from_slice(&value) {
-            Ok(user_info) => Ok(user_info),
-            Err(serialize_error) => Err(ErrorCode::IllegalUserInfoFormat(format!(
-                "Cannot deserialize user info from bytes. cause {}",
-                serialize_error
-            ))),
-        }
+        UserInfo::from_versioned_json(&value)
     }
 }
 
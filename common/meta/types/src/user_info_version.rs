@@ -0,0 +1,115 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The versions of the serialized [UserInfo].
+//!
+//! - version 0: the password and its hash method as plain fields, without grants or quota.
+//! - version 1: the [UserInfo] without the version field.
+//! - version 2: the [UserInfo] with the version field, the fields added later are defaulted when
+//!   missing, so they do not need a new version unless the existing fields change.
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::AuthInfo;
+use crate::PasswordHashMethod;
+use crate::UserInfo;
+
+/// The version of the [UserInfo] serialized by this version.
+pub const USER_INFO_VERSION: u64 = 2;
+
+const VERSION_FIELD: &str = "version";
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct UserInfoV0 {
+    name: String,
+    hostname: String,
+    password: Vec<u8>,
+    auth_type: PasswordTypeV0,
+}
+
+#[derive(Deserialize)]
+enum PasswordTypeV0 {
+    None,
+    PlainText,
+    DoubleSha1,
+    Sha256,
+}
+
+impl Default for PasswordTypeV0 {
+    fn default() -> Self {
+        PasswordTypeV0::None
+    }
+}
+
+impl From<UserInfoV0> for UserInfo {
+    fn from(v0: UserInfoV0) -> Self {
+        let hash_method = match v0.auth_type {
+            PasswordTypeV0::None => None,
+            PasswordTypeV0::PlainText => Some(PasswordHashMethod::PlainText),
+            PasswordTypeV0::DoubleSha1 => Some(PasswordHashMethod::DoubleSha1),
+            PasswordTypeV0::Sha256 => Some(PasswordHashMethod::Sha256),
+        };
+        let auth_info = match hash_method {
+            None => AuthInfo::None,
+            Some(hash_method) => AuthInfo::Password {
+                hash_value: v0.password,
+                hash_method,
+            },
+        };
+        UserInfo::new(v0.name, v0.hostname, auth_info)
+    }
+}
+
+fn illegal(cause: impl std::fmt::Display) -> ErrorCode {
+    ErrorCode::IllegalUserInfoFormat(format!(
+        "Cannot deserialize user info from bytes. cause {}",
+        cause
+    ))
+}
+
+impl UserInfo {
+    /// Serializes the user info as the latest version.
+    pub fn to_versioned_json(&self) -> Result<Vec<u8>> {
+        let mut value = serde_json::to_value(self)?;
+        if let Value::Object(fields) = &mut value {
+            fields.insert(VERSION_FIELD.to_string(), USER_INFO_VERSION.into());
+        }
+        Ok(serde_json::to_vec(&value)?)
+    }
+
+    /// Deserializes the user info of any version, upgrading it to the latest one.
+    ///
+    /// A version newer than [USER_INFO_VERSION] is read as the latest one, ignoring the fields
+    /// unknown to this version, so that a node of the older version keeps working.
+    pub fn from_versioned_json(bytes: &[u8]) -> Result<UserInfo> {
+        let value = serde_json::from_slice::<Value>(bytes).map_err(illegal)?;
+        let version = match value.get(VERSION_FIELD) {
+            Some(version) => version
+                .as_u64()
+                .ok_or_else(|| illegal(format!("invalid version {}", version)))?,
+            // Serialized before the version field, told by the fields.
+            None if value.get("password").is_some() && value.get("auth_info").is_none() => 0,
+            None => 1,
+        };
+        match version {
+            0 => serde_json::from_value::<UserInfoV0>(value).map(UserInfo::from),
+            _ => serde_json::from_value::<UserInfo>(value),
+        }
+        .map_err(illegal)
+    }
+}
@@ -0,0 +1,120 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::DateTime;
+use chrono::Utc;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::AuthInfo;
+use crate::PasswordHashMethod;
+
+/// The password policy of a user, or of a tenant for the password rules.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+#[serde(default)]
+pub struct PasswordPolicy {
+    /// The password must be changed before any other statement, e.g. `CREATE USER .. PASSWORD EXPIRE`.
+    pub must_change: bool,
+
+    /// The password must be changed since the time.
+    pub expire_at: Option<DateTime<Utc>>,
+
+    /// The min length of a new password, 0 is no limited.
+    pub min_length: u64,
+
+    /// A new password must contain lowercase and uppercase letters, digits and other characters.
+    pub require_complexity: bool,
+
+    /// A new password must not be any of the last N passwords, including the current one, 0 is no limited.
+    pub history_depth: u64,
+}
+
+/// The digest of a previous password of a user, a plaintext password is kept as its sha256 digest.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct PasswordHistoryEntry {
+    pub hash_value: Vec<u8>,
+    pub hash_method: PasswordHashMethod,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl PasswordHistoryEntry {
+    /// The digest of the password of `auth_info`, None if it is not a password.
+    pub fn from_auth_info(auth_info: &AuthInfo, changed_at: DateTime<Utc>) -> Option<Self> {
+        match auth_info {
+            AuthInfo::Password {
+                hash_value,
+                hash_method: PasswordHashMethod::PlainText,
+            } => Some(PasswordHistoryEntry {
+                hash_value: PasswordHashMethod::Sha256.hash(hash_value),
+                hash_method: PasswordHashMethod::Sha256,
+                changed_at,
+            }),
+            AuthInfo::Password {
+                hash_value,
+                hash_method,
+            } => Some(PasswordHistoryEntry {
+                hash_value: hash_value.clone(),
+                hash_method: *hash_method,
+                changed_at,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Whether both are the digests of the same password, only comparable by the same hash method.
+    pub fn is_same_password(&self, other: &PasswordHistoryEntry) -> bool {
+        self.hash_method == other.hash_method && self.hash_value == other.hash_value
+    }
+}
+
+impl PasswordPolicy {
+    /// Whether the password must be changed at `now`.
+    pub fn is_change_required(&self, now: DateTime<Utc>) -> bool {
+        self.must_change || matches!(self.expire_at, Some(expire_at) if expire_at <= now)
+    }
+
+    /// The password rules of both, the stricter one of each rule.
+    pub fn stricter(&self, other: &PasswordPolicy) -> PasswordPolicy {
+        PasswordPolicy {
+            min_length: self.min_length.max(other.min_length),
+            require_complexity: self.require_complexity || other.require_complexity,
+            history_depth: self.history_depth.max(other.history_depth),
+            ..self.clone()
+        }
+    }
+
+    /// Checks a new password by the password rules.
+    pub fn check_password(&self, password: &str) -> Result<()> {
+        if (password.chars().count() as u64) < self.min_length {
+            return Err(ErrorCode::PasswordPolicyViolation(format!(
+                "password must contain at least {} characters",
+                self.min_length
+            )));
+        }
+        if self.require_complexity {
+            let complex = password.chars().any(|c| c.is_lowercase())
+                && password.chars().any(|c| c.is_uppercase())
+                && password.chars().any(|c| c.is_ascii_digit())
+                && password.chars().any(|c| !c.is_alphanumeric());
+            if !complex {
+                return Err(ErrorCode::PasswordPolicyViolation(
+                    "password must contain lowercase and uppercase letters, digits and other characters",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
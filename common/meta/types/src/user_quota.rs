@@ -18,7 +18,7 @@ use serde::Serialize;
 #[derive(Serialize, Deserialize, Clone, Default, Eq, PartialEq)]
 #[serde(default)]
 pub struct UserQuota {
-    // The max cpu can be used (0 is no limited).
+    // The max cpus, i.e. worker threads, a query can use (0 is no limited).
     pub max_cpu: u64,
 
     // The max memory(bytes) can be used(0 is no limited).
@@ -26,6 +26,9 @@ pub struct UserQuota {
 
     // The max storage(bytes) can be used(0 is no limited).
     pub max_storage_in_bytes: u64,
+
+    // The max concurrent sessions of the user(0 is no limited).
+    pub max_sessions: u64,
 }
 
 impl UserQuota {
@@ -34,6 +37,7 @@ impl UserQuota {
             max_cpu: 0,
             max_memory_in_bytes: 0,
             max_storage_in_bytes: 0,
+            max_sessions: 0,
         }
     }
 }
@@ -42,8 +46,8 @@ impl std::fmt::Debug for UserQuota {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "UserQuota<cpu:{},mem:{},store:{}>",
-            self.max_cpu, self.max_memory_in_bytes, self.max_storage_in_bytes
+            "UserQuota<cpu:{},mem:{},store:{},sessions:{}>",
+            self.max_cpu, self.max_memory_in_bytes, self.max_storage_in_bytes, self.max_sessions
         )
     }
 }
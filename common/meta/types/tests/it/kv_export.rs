@@ -0,0 +1,96 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_meta_types::export_kv_chunks;
+use common_meta_types::KVEntry;
+use common_meta_types::KVEntryAssembler;
+use common_meta_types::KVMeta;
+
+fn entry(key: &str, size: usize, expire_at: Option<u64>) -> KVEntry {
+    KVEntry {
+        key: key.to_string(),
+        seq: size as u64 + 1,
+        meta: expire_at.map(|t| KVMeta { expire_at: Some(t) }),
+        value: (0..size).map(|i| (i % 251) as u8).collect(),
+    }
+}
+
+#[test]
+fn test_export_kv_chunks() -> anyhow::Result<()> {
+    assert!(export_kv_chunks(vec![], 10).is_empty());
+
+    let entries = vec![
+        entry("a", 3, None),
+        entry("b", 0, Some(5)),
+        entry("c", 25, None),
+        entry("d", 10, Some(7)),
+    ];
+    let chunks = export_kv_chunks(entries.clone(), 10);
+
+    // Every chunk carries at most 10 bytes of values.
+    for c in &chunks {
+        assert!(c.pieces.iter().map(|p| p.data.len()).sum::<usize>() <= 10);
+    }
+    assert_eq!(
+        vec![
+            vec![("a", 3, true), ("b", 0, true), ("c", 7, false)],
+            vec![("c", 10, false)],
+            vec![("c", 8, true), ("d", 2, false)],
+            vec![("d", 8, true)],
+        ],
+        chunks
+            .iter()
+            .map(|c| c
+                .pieces
+                .iter()
+                .map(|p| (p.key.as_str(), p.data.len(), p.last))
+                .collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+    );
+
+    // Assembled back byte for byte.
+    let mut assembler = KVEntryAssembler::default();
+    let mut got = vec![];
+    for piece in chunks.into_iter().flat_map(|c| c.pieces) {
+        if let Some(e) = assembler.push(piece)? {
+            got.push(e);
+        }
+    }
+    assembler.finish()?;
+    assert_eq!(entries, got);
+
+    Ok(())
+}
+
+#[test]
+fn test_kv_entry_assembler_incomplete() -> anyhow::Result<()> {
+    let chunks = export_kv_chunks(vec![entry("a", 25, None), entry("b", 1, None)], 10);
+    let mut pieces = chunks
+        .into_iter()
+        .flat_map(|c| c.pieces)
+        .collect::<Vec<_>>();
+
+    // The stream ends in the middle of a value.
+    let mut assembler = KVEntryAssembler::default();
+    assert!(assembler.push(pieces[0].clone())?.is_none());
+    assert!(assembler.finish().is_err());
+
+    // A piece of another key before the value is complete.
+    let b = pieces.pop().unwrap();
+    let mut assembler = KVEntryAssembler::default();
+    assert!(assembler.push(pieces[0].clone())?.is_none());
+    assert!(assembler.push(b).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_exception::ErrorCode;
+use common_meta_types::escape_key_segment;
+use common_meta_types::unescape_key_segment;
+use common_meta_types::unescape_key_segment_compat;
+use common_meta_types::KeyBuilder;
+
+#[test]
+fn test_escape_key_segment_round_trip() -> anyhow::Result<()> {
+    let segments = [
+        "abc_XYZ_09",
+        "a/b",
+        "/",
+        "//a//",
+        "%",
+        "%2f",
+        "%%zz",
+        "'root'@'%'",
+        "a b\tc\nd",
+        "\x01\x7f",
+        "中文",
+        "🦀/🦀",
+    ];
+
+    for s in segments {
+        let escaped = escape_key_segment(s);
+        assert!(!escaped.contains('/'), "{}", escaped);
+        assert_eq!(s, unescape_key_segment(&escaped)?, "{:?}", escaped);
+    }
+
+    assert_eq!("data_bend%21%21", escape_key_segment("data_bend!!"));
+    assert_eq!("a%2fb", escape_key_segment("a/b"));
+    Ok(())
+}
+
+#[test]
+fn test_unescape_key_segment_invalid() -> anyhow::Result<()> {
+    let invalid_code = ErrorCode::InvalidMetaKey("").code();
+
+    for s in ["%", "a%2", "%zz", "%ff"] {
+        let res = unescape_key_segment(s);
+        assert_eq!(invalid_code, res.unwrap_err().code(), "{:?}", s);
+    }
+
+    // a segment written unescaped by an older version
+    assert_eq!("50%", unescape_key_segment_compat("50%"));
+    assert_eq!("a b", unescape_key_segment_compat("a b"));
+    assert_eq!("a/b", unescape_key_segment_compat("a%2fb"));
+    Ok(())
+}
+
+#[test]
+fn test_key_builder() -> anyhow::Result<()> {
+    let key = KeyBuilder::new("__fd_users")
+        .push("tenant1")?
+        .push("a/b")?
+        .done();
+    assert_eq!("__fd_users/tenant1/a%2fb", key);
+
+    let key = KeyBuilder::new("__fd_audit").push_raw(3).done();
+    assert_eq!("__fd_audit/3", key);
+
+    let invalid_code = ErrorCode::InvalidMetaKey("").code();
+    let res = KeyBuilder::new("__fd_users").push("");
+    assert_eq!(invalid_code, res.unwrap_err().code());
+    let res = KeyBuilder::new("__fd_users").push("a\0b");
+    assert_eq!(invalid_code, res.unwrap_err().code());
+    Ok(())
+}
+
+#[test]
+fn test_key_builder_prefix_isolation() -> anyhow::Result<()> {
+    // The keys of `a/b` are not listed below `a`, and the keys of `ab` are not listed below `a`.
+    let dir_a = KeyBuilder::new("__fd_users").push("a")?.dir();
+    assert_eq!("__fd_users/a/", dir_a);
+
+    for other in ["a/b", "ab", "a%2fb", "a/"] {
+        let key = KeyBuilder::new("__fd_users").push(other)?.push("u")?.done();
+        assert!(!key.starts_with(&dir_a), "{} {}", other, key);
+    }
+
+    let key = KeyBuilder::new("__fd_users").push("a")?.push("u/v")?.done();
+    assert!(key.starts_with(&dir_a));
+    assert_eq!("u/v", unescape_key_segment(&key[dir_a.len()..])?);
+    Ok(())
+}
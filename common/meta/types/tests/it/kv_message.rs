@@ -0,0 +1,85 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_meta_types::MatchSeq;
+use common_meta_types::Operation;
+use common_meta_types::ReadConsistency;
+use common_meta_types::UpsertKVAction;
+use common_meta_types::UpsertKVBatchReq;
+use common_meta_types::UPSERT_KV_BATCH_MAX_ACTIONS;
+use common_meta_types::UPSERT_KV_BATCH_MAX_BYTES;
+
+fn upsert(key: &str, size: usize) -> UpsertKVAction {
+    UpsertKVAction::new(key, MatchSeq::Any, Operation::Update(vec![0; size]), None)
+}
+
+fn keys(batches: &[UpsertKVBatchReq]) -> Vec<Vec<String>> {
+    batches
+        .iter()
+        .map(|b| b.actions.iter().map(|a| a.key.clone()).collect())
+        .collect()
+}
+
+#[test]
+fn test_upsert_kv_batch_split() -> anyhow::Result<()> {
+    assert!(UpsertKVBatchReq::split(vec![]).is_empty());
+
+    // by the number of the actions, in order
+    let n = UPSERT_KV_BATCH_MAX_ACTIONS * 2 + 1;
+    let batches = UpsertKVBatchReq::split((0..n).map(|i| upsert(&i.to_string(), 1)).collect());
+    assert_eq!(
+        vec![UPSERT_KV_BATCH_MAX_ACTIONS, UPSERT_KV_BATCH_MAX_ACTIONS, 1],
+        batches.iter().map(|b| b.actions.len()).collect::<Vec<_>>()
+    );
+    let flattened = keys(&batches).concat();
+    assert_eq!((0..n).map(|i| i.to_string()).collect::<Vec<_>>(), flattened);
+
+    // by the size, an oversized action is a batch by itself
+    let half = UPSERT_KV_BATCH_MAX_BYTES / 2;
+    let batches = UpsertKVBatchReq::split(vec![
+        upsert("a", half),
+        upsert("b", half - 2),
+        upsert("c", 1),
+        upsert("d", UPSERT_KV_BATCH_MAX_BYTES),
+        upsert("e", 1),
+    ]);
+    assert_eq!(
+        vec![
+            vec!["a".to_string(), "b".to_string()],
+            vec!["c".to_string()],
+            vec!["d".to_string()],
+            vec!["e".to_string()],
+        ],
+        keys(&batches)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_read_consistency_parse() -> anyhow::Result<()> {
+    for c in [
+        ReadConsistency::Linearizable,
+        ReadConsistency::Lease,
+        ReadConsistency::Stale,
+    ] {
+        assert_eq!(c, ReadConsistency::parse(c.as_str()));
+    }
+
+    // An unknown one, e.g., from a newer client, is served as the default.
+    assert_eq!(ReadConsistency::Lease, ReadConsistency::parse("bounded"));
+    assert_eq!(ReadConsistency::Lease, ReadConsistency::default());
+
+    Ok(())
+}
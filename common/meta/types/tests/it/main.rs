@@ -13,10 +13,17 @@
 //  limitations under the License.
 
 mod cluster;
+mod kv_export;
+mod kv_key;
+mod kv_message;
 mod match_seq;
+mod network_policy;
+mod user_auth;
 mod user_defined_function;
 mod user_grant;
+mod user_identity;
 mod user_info;
+mod user_password_policy;
 mod user_privilege;
 mod user_quota;
 mod user_stage;
@@ -0,0 +1,119 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryFrom;
+use std::net::IpAddr;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_meta_types::IpCidr;
+use common_meta_types::NetworkPolicy;
+
+fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+}
+
+#[test]
+fn test_ip_cidr_v4() -> Result<()> {
+    let cidr: IpCidr = "192.168.1.0/24".parse()?;
+    assert!(cidr.contains(&ip("192.168.1.0")));
+    assert!(cidr.contains(&ip("192.168.1.255")));
+    assert!(!cidr.contains(&ip("192.168.2.1")));
+    assert!(!cidr.contains(&ip("fe80::1")));
+    assert!(cidr.contains(&ip("::ffff:192.168.1.10")));
+
+    let cidr: IpCidr = "10.0.0.1".parse()?;
+    assert_eq!(cidr.to_string(), "10.0.0.1/32");
+    assert!(cidr.contains(&ip("10.0.0.1")));
+    assert!(!cidr.contains(&ip("10.0.0.2")));
+
+    let cidr: IpCidr = "0.0.0.0/0".parse()?;
+    assert!(cidr.contains(&ip("8.8.8.8")));
+
+    for illegal in ["192.168.1.0/33", "192.168.1/24", "192.168.1.0/", "abc"] {
+        let err = illegal.parse::<IpCidr>().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::IllegalNetworkPolicyFormat("").code());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_ip_cidr_v6() -> Result<()> {
+    let cidr: IpCidr = "fe80::/10".parse()?;
+    assert!(cidr.contains(&ip("fe80::1")));
+    assert!(cidr.contains(&ip("febf:ffff::1")));
+    assert!(!cidr.contains(&ip("fec0::1")));
+    assert!(!cidr.contains(&ip("192.168.1.1")));
+
+    let cidr: IpCidr = "::1".parse()?;
+    assert_eq!(cidr.to_string(), "::1/128");
+    assert!(cidr.contains(&ip("::1")));
+    assert!(!cidr.contains(&ip("::2")));
+
+    let cidr: IpCidr = "::/0".parse()?;
+    assert!(cidr.contains(&ip("2001:db8::1")));
+
+    let err = "fe80::/129".parse::<IpCidr>().unwrap_err();
+    assert_eq!(err.code(), ErrorCode::IllegalNetworkPolicyFormat("").code());
+    Ok(())
+}
+
+#[test]
+fn test_network_policy_is_allowed() -> Result<()> {
+    // Everything is allowed by an empty policy.
+    let policy = NetworkPolicy::default();
+    assert!(policy.is_allowed(&ip("1.2.3.4")));
+
+    let policy = NetworkPolicy {
+        name: "p1".to_string(),
+        allowed_ip_list: vec!["192.168.0.0/16".parse()?, "2001:db8::/32".parse()?],
+        blocked_ip_list: vec!["192.168.1.0/24".parse()?],
+        comment: "".to_string(),
+    };
+    assert!(policy.is_allowed(&ip("192.168.2.1")));
+    assert!(policy.is_allowed(&ip("2001:db8::1")));
+    assert!(!policy.is_allowed(&ip("10.0.0.1")));
+    assert!(!policy.is_allowed(&ip("2001:db9::1")));
+    // The blocked list takes precedence over the allowed list.
+    assert!(!policy.is_allowed(&ip("192.168.1.1")));
+
+    // Blocked only.
+    let policy = NetworkPolicy {
+        blocked_ip_list: vec!["10.0.0.0/8".parse()?],
+        ..Default::default()
+    };
+    assert!(!policy.is_allowed(&ip("10.1.1.1")));
+    assert!(policy.is_allowed(&ip("11.1.1.1")));
+    Ok(())
+}
+
+#[test]
+fn test_network_policy_serde() -> Result<()> {
+    let policy = NetworkPolicy {
+        name: "p1".to_string(),
+        allowed_ip_list: vec!["192.168.0.0/16".parse()?],
+        blocked_ip_list: vec!["fe80::/10".parse()?],
+        comment: "office".to_string(),
+    };
+    let ser = serde_json::to_vec(&policy)?;
+    assert_eq!(
+        String::from_utf8_lossy(&ser),
+        r#"{"name":"p1","allowed_ip_list":["192.168.0.0/16"],"blocked_ip_list":["fe80::/10"],"comment":"office"}"#
+    );
+    assert_eq!(NetworkPolicy::try_from(ser)?, policy);
+
+    let err = NetworkPolicy::try_from(br#"{"allowed_ip_list":["1.2.3"]}"#.to_vec()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::IllegalNetworkPolicyFormat("").code());
+    Ok(())
+}
@@ -0,0 +1,81 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_exception::exception::Result;
+use common_meta_types::AuthInfo;
+use common_meta_types::AuthType;
+use common_meta_types::PasswordHashMethod;
+use sha1::Digest;
+use sha1::Sha1;
+
+// the response of a client to the challenge of mysql_native_password:
+// SHA1(password) XOR SHA1(salt <concat> SHA1(SHA1(password)))
+fn scramble(password: &[u8], salt: &[u8]) -> Vec<u8> {
+    let stage1 = Sha1::digest(password);
+    let stage2 = Sha1::digest(&stage1);
+    let mut m = Sha1::new();
+    m.update(salt);
+    m.update(&stage2);
+    let mask = m.finalize();
+    stage1.iter().zip(mask.iter()).map(|(l, r)| l ^ r).collect()
+}
+
+#[test]
+fn test_auth_double_sha1_password() -> Result<()> {
+    let salt = b"0123456789abcdefghij";
+    let auth_info = AuthInfo::new(AuthType::DoubleSha1Password, &Some("pwd".to_string()))?;
+
+    // only the digest is kept
+    assert_ne!(Some(b"pwd".to_vec()), auth_info.get_password());
+
+    assert!(auth_info.auth(&scramble(b"pwd", salt), salt)?);
+    assert!(!auth_info.auth(&scramble(b"wrong", salt), salt)?);
+    // the scramble of another challenge
+    assert!(!auth_info.auth(&scramble(b"pwd", b"jihgfedcba9876543210"), salt)?);
+
+    // the password in plaintext, without a challenge
+    assert!(auth_info.auth(b"pwd", b"")?);
+    assert!(!auth_info.auth(b"wrong", b"")?);
+    Ok(())
+}
+
+#[test]
+fn test_auth_sha256_password() -> Result<()> {
+    let salt = b"0123456789abcdefghij";
+    let auth_info = AuthInfo::new(AuthType::Sha256Password, &Some("pwd".to_string()))?;
+    assert_ne!(Some(b"pwd".to_vec()), auth_info.get_password());
+
+    assert!(auth_info.auth(b"pwd", b"")?);
+    assert!(!auth_info.auth(b"wrong", b"")?);
+
+    // the challenge of mysql_native_password can not be verified by the digest of sha256
+    assert!(auth_info.auth(&scramble(b"pwd", salt), salt).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_auth_plaintext_password() -> Result<()> {
+    // the passwords stored in plaintext by the older versions are still verified
+    let salt = b"0123456789abcdefghij";
+    let auth_info = AuthInfo::Password {
+        hash_value: b"pwd".to_vec(),
+        hash_method: PasswordHashMethod::PlainText,
+    };
+
+    assert!(auth_info.auth(&scramble(b"pwd", salt), salt)?);
+    assert!(!auth_info.auth(&scramble(b"wrong", salt), salt)?);
+    assert!(auth_info.auth(b"pwd", b"")?);
+    assert!(!auth_info.auth(b"wrong", b"")?);
+    Ok(())
+}
@@ -0,0 +1,53 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_meta_types::UserIdentity;
+
+#[test]
+fn test_user_identity_host_matches() {
+    let cases = vec![
+        ("%", "10.0.0.1", true),
+        ("%", "", true),
+        ("10.%", "10.0.0.1", true),
+        ("10.%", "100.0.0.1", false),
+        ("10.0.0.%", "10.0.0.12", true),
+        ("10.0.0.%", "10.0.1.1", false),
+        ("10.0.0._", "10.0.0.1", true),
+        ("10.0.0._", "10.0.0.12", false),
+        ("%.example.com", "db.example.com", true),
+        ("LocalHost", "localhost", true),
+        ("10.0.0.1", "10.0.0.1", true),
+        ("10.0.0.1", "10.0.0.10", false),
+    ];
+    for (hostname, client_host, expected) in cases {
+        let identity = UserIdentity::new("u", hostname);
+        assert_eq!(
+            identity.host_matches(client_host),
+            expected,
+            "'{}' matching '{}'",
+            hostname,
+            client_host
+        );
+    }
+}
+
+#[test]
+fn test_user_identity_host_specificity() {
+    let specificity = |hostname: &str| UserIdentity::new("u", hostname).host_specificity();
+
+    assert!(specificity("10.0.0.1") > specificity("10.0.0._"));
+    assert!(specificity("10.0.0._") > specificity("10.0.0.%"));
+    assert!(specificity("10.0.0.%") > specificity("10.%"));
+    assert!(specificity("10.%") > specificity("%"));
+}
@@ -13,9 +13,11 @@
 // limitations under the License.
 
 use common_exception::exception::Result;
+use common_exception::ErrorCode;
 use common_meta_types::AuthInfo;
 use common_meta_types::PasswordHashMethod;
 use common_meta_types::UserInfo;
+use common_meta_types::USER_INFO_VERSION;
 
 #[test]
 fn test_user_info() -> Result<()> {
@@ -53,3 +55,91 @@ fn test_user_info() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_user_info_locked() -> Result<()> {
+    let mut user_info = UserInfo::new("u".to_string(), "%".to_string(), AuthInfo::None);
+    user_info.check_not_locked()?;
+
+    user_info.is_locked = true;
+    let err = user_info.check_not_locked().unwrap_err();
+    assert_eq!(err.code(), ErrorCode::AuthenticateFailure("").code());
+
+    user_info.locked_reason = Some("leaked".to_string());
+    let err = user_info.check_not_locked().unwrap_err();
+    assert!(err.message().contains("leaked"));
+
+    // the user info serialized before the locking is not locked
+    let old = r#"{"name":"u","hostname":"%"}"#;
+    let user_info = serde_json::from_str::<UserInfo>(old)?;
+    assert!(!user_info.is_locked);
+    Ok(())
+}
+
+// The user info serialized by every historical version, as stored in the meta service.
+const USER_INFO_V0: &str =
+    r#"{"name":"u1","hostname":"%","password":[112,119,100],"auth_type":"Sha256"}"#;
+const USER_INFO_V0_NO_PASSWORD: &str =
+    r#"{"name":"u1","hostname":"%","password":[],"auth_type":"None"}"#;
+const USER_INFO_V1: &str = r#"{"name":"u1","hostname":"%","auth_info":{"Password":{"hash_value":[112,119,100],"hash_method":"Sha256"}},"quota":{"max_cpu":0,"max_memory_in_bytes":0,"max_storage_in_bytes":0,"max_sessions":3}}"#;
+const USER_INFO_V2: &str = r#"{"version":2,"name":"u1","hostname":"%","auth_info":{"Password":{"hash_value":[112,119,100],"hash_method":"Sha256"}},"quota":{"max_cpu":0,"max_memory_in_bytes":0,"max_storage_in_bytes":0,"max_sessions":3},"settings":{"max_threads":"4"}}"#;
+
+fn expected_user_info() -> UserInfo {
+    UserInfo::new("u1".to_string(), "%".to_string(), AuthInfo::Password {
+        hash_value: Vec::from("pwd"),
+        hash_method: PasswordHashMethod::Sha256,
+    })
+}
+
+#[test]
+fn test_user_info_versions() -> Result<()> {
+    let v0 = UserInfo::from_versioned_json(USER_INFO_V0.as_bytes())?;
+    assert_eq!(v0, expected_user_info());
+
+    let v0 = UserInfo::from_versioned_json(USER_INFO_V0_NO_PASSWORD.as_bytes())?;
+    assert_eq!(
+        v0,
+        UserInfo::new("u1".to_string(), "%".to_string(), AuthInfo::None)
+    );
+
+    let mut expected = expected_user_info();
+    expected.quota.max_sessions = 3;
+    let v1 = UserInfo::from_versioned_json(USER_INFO_V1.as_bytes())?;
+    assert_eq!(v1, expected);
+
+    expected
+        .settings
+        .insert("max_threads".to_string(), "4".to_string());
+    let v2 = UserInfo::from_versioned_json(USER_INFO_V2.as_bytes())?;
+    assert_eq!(v2, expected);
+
+    // All the versions are written as the latest one.
+    for fixture in [
+        USER_INFO_V0,
+        USER_INFO_V0_NO_PASSWORD,
+        USER_INFO_V1,
+        USER_INFO_V2,
+    ] {
+        let user_info = UserInfo::from_versioned_json(fixture.as_bytes())?;
+        let bytes = user_info.to_versioned_json()?;
+        let value = serde_json::from_slice::<serde_json::Value>(&bytes)?;
+        assert_eq!(value["version"], USER_INFO_VERSION);
+        assert!(value.get("password").is_none());
+        assert_eq!(UserInfo::from_versioned_json(&bytes)?, user_info);
+        assert_eq!(UserInfo::try_from(bytes)?, user_info);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_user_info_newer_version() -> Result<()> {
+    // Written by a newer version, the fields unknown to this version are ignored.
+    let newer = r#"{"version":100,"name":"u1","hostname":"%","auth_info":{"Password":{"hash_value":[112,119,100],"hash_method":"Sha256"}},"some_new_field":[1,2]}"#;
+    let user_info = UserInfo::from_versioned_json(newer.as_bytes())?;
+    assert_eq!(user_info, expected_user_info());
+
+    let invalid = r#"{"version":"x","name":"u1"}"#;
+    let err = UserInfo::from_versioned_json(invalid.as_bytes()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::IllegalUserInfoFormat("").code());
+    Ok(())
+}
@@ -0,0 +1,85 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::Duration;
+use chrono::Utc;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_meta_types::PasswordPolicy;
+
+#[test]
+fn test_password_policy_check_password() -> Result<()> {
+    let policy = PasswordPolicy::default();
+    policy.check_password("")?;
+
+    let policy = PasswordPolicy {
+        min_length: 8,
+        ..Default::default()
+    };
+    policy.check_password("12345678")?;
+    let err = policy.check_password("1234567").unwrap_err();
+    assert_eq!(err.code(), ErrorCode::PasswordPolicyViolation("").code());
+
+    let policy = PasswordPolicy {
+        require_complexity: true,
+        ..Default::default()
+    };
+    policy.check_password("aB3$")?;
+    for password in ["ab3$", "AB3$", "aBc$", "aB34"] {
+        let err = policy.check_password(password).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::PasswordPolicyViolation("").code());
+    }
+
+    // The stricter of the rules of the user and the tenant.
+    let user_policy = PasswordPolicy {
+        min_length: 10,
+        must_change: true,
+        ..Default::default()
+    };
+    let tenant_policy = PasswordPolicy {
+        min_length: 8,
+        require_complexity: true,
+        ..Default::default()
+    };
+    let policy = user_policy.stricter(&tenant_policy);
+    assert_eq!(policy.min_length, 10);
+    assert!(policy.require_complexity);
+    assert!(policy.must_change);
+    Ok(())
+}
+
+#[test]
+fn test_password_policy_change_required() -> Result<()> {
+    let now = Utc::now();
+    assert!(!PasswordPolicy::default().is_change_required(now));
+
+    let policy = PasswordPolicy {
+        must_change: true,
+        ..Default::default()
+    };
+    assert!(policy.is_change_required(now));
+
+    let policy = PasswordPolicy {
+        expire_at: Some(now + Duration::days(1)),
+        ..Default::default()
+    };
+    assert!(!policy.is_change_required(now));
+    assert!(policy.is_change_required(now + Duration::days(2)));
+
+    // serialized before the policy, or by the older versions.
+    let policy = serde_json::from_str::<PasswordPolicy>(r#"{"min_length":8}"#)?;
+    assert_eq!(policy.min_length, 8);
+    assert!(!policy.must_change);
+    Ok(())
+}
@@ -21,6 +21,23 @@ fn test_user_quota() -> Result<()> {
     assert_eq!(quota.max_cpu, 0);
     assert_eq!(quota.max_memory_in_bytes, 0);
     assert_eq!(quota.max_storage_in_bytes, 0);
+    assert_eq!(quota.max_sessions, 0);
+    assert_eq!(
+        format!("{:?}", quota),
+        "UserQuota<cpu:0,mem:0,store:0,sessions:0>"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_user_quota_compatible() -> Result<()> {
+    // The quota serialized before the max_sessions.
+    let old = r#"{"max_cpu":2,"max_memory_in_bytes":1024,"max_storage_in_bytes":0}"#;
+    let quota = serde_json::from_str::<UserQuota>(old)?;
+    assert_eq!(quota.max_cpu, 2);
+    assert_eq!(quota.max_memory_in_bytes, 1024);
+    assert_eq!(quota.max_sessions, 0);
 
     Ok(())
 }
@@ -85,6 +85,7 @@ mod plan_table_optimize;
 mod plan_table_rename;
 mod plan_table_show_create;
 mod plan_table_truncate;
+mod plan_union;
 mod plan_use_database;
 mod plan_user_alter;
 mod plan_user_create;
@@ -158,13 +159,16 @@ pub use plan_limit_by::LimitByPlan;
 pub use plan_list::ListPlan;
 pub use plan_node::PlanNode;
 pub use plan_node_builder::PlanBuilder;
+pub use plan_node_display::format_plan;
 pub use plan_node_extras::Extras;
 pub use plan_node_rewriter::PlanRewriter;
 pub use plan_node_rewriter::RewriteHelper;
 pub use plan_node_s3_stage_table::S3StageTableInfo;
 pub use plan_node_stage::StageKind;
 pub use plan_node_stage::StagePlan;
+pub use plan_node_statistics::PlanStatistics;
 pub use plan_node_statistics::Statistics;
+pub use plan_node_statistics::DEFAULT_FILTER_SELECTIVITY;
 pub use plan_node_visitor::PlanVisitor;
 pub use plan_partition::PartInfo;
 pub use plan_partition::PartInfoPtr;
@@ -209,6 +213,7 @@ pub use plan_table_rename::RenameTableEntity;
 pub use plan_table_rename::RenameTablePlan;
 pub use plan_table_show_create::ShowCreateTablePlan;
 pub use plan_table_truncate::TruncateTablePlan;
+pub use plan_union::UnionPlan;
 pub use plan_use_database::UseDatabasePlan;
 pub use plan_user_alter::AlterUserPlan;
 pub use plan_user_create::CreateUserPlan;
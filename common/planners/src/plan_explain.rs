@@ -23,6 +23,10 @@ pub enum ExplainType {
     Syntax,
     Graph,
     Pipeline,
+    /// The pruning of the blocks of the tables scanned, without running the query.
+    Prune,
+    /// The plan tree with the output schema of every node.
+    Verbose,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq)]
@@ -19,6 +19,7 @@ use common_datavalues::DataField;
 use common_datavalues::DataSchemaRef;
 use common_exception::ErrorCode;
 use common_exception::Result;
+use common_functions::scalars::CastFunction;
 use common_functions::scalars::Function;
 use common_functions::scalars::FunctionContext;
 use common_functions::scalars::FunctionFactory;
@@ -107,9 +108,7 @@ impl ExpressionMonotonicityVisitor {
         }
     }
 
-    fn visit_function(mut self, op: &str, args_size: usize) -> Result<Self> {
-        let mut left_vec = Vec::with_capacity(args_size);
-        let mut right_vec = Vec::with_capacity(args_size);
+    fn pop_arguments(&mut self, args_size: usize) -> Result<(Vec<DataTypePtr>, Vec<Monotonicity>)> {
         let mut arg_types = Vec::with_capacity(args_size);
         let mut monotonicity_vec = Vec::with_capacity(args_size);
 
@@ -122,25 +121,27 @@ impl ExpressionMonotonicityVisitor {
                     )))
                 }
                 Some((arg_type, monotonic)) => {
-                    left_vec.push(monotonic.left.clone());
-                    right_vec.push(monotonic.right.clone());
                     arg_types.push(arg_type);
                     monotonicity_vec.push(monotonic);
                 }
             }
         }
 
-        let instance = FunctionFactory::instance();
-
-        let arg_types: Vec<&DataTypePtr> = arg_types.iter().collect();
-        let func = instance.get(op, &arg_types)?;
+        Ok((arg_types, monotonicity_vec))
+    }
 
+    fn push_function(
+        mut self,
+        name: &str,
+        func: &dyn Function,
+        is_deterministic: bool,
+        monotonicity_vec: Vec<Monotonicity>,
+    ) -> Result<Self> {
         let return_type = func.return_type();
         let mut monotonic = match self.single_point {
             false => func.get_monotonicity(monotonicity_vec.as_ref())?,
             true => {
-                let features = instance.get_features(op)?;
-                if features.is_deterministic {
+                if is_deterministic {
                     Monotonicity::create_constant()
                 } else {
                     Monotonicity::default()
@@ -152,17 +153,44 @@ impl ExpressionMonotonicityVisitor {
         if !monotonic.is_monotonic && !monotonic.is_constant {
             return Err(ErrorCode::UnknownException(format!(
                 "Function '{}' is not monotonic in the variables range",
-                op
+                name
             )));
         }
 
-        monotonic.left = Self::try_calculate_boundary(func.as_ref(), &return_type, left_vec)?;
-        monotonic.right = Self::try_calculate_boundary(func.as_ref(), &return_type, right_vec)?;
+        let left_vec = monotonicity_vec.iter().map(|m| m.left.clone()).collect();
+        let right_vec = monotonicity_vec.iter().map(|m| m.right.clone()).collect();
+        monotonic.left = Self::try_calculate_boundary(func, &return_type, left_vec)?;
+        monotonic.right = Self::try_calculate_boundary(func, &return_type, right_vec)?;
 
         self.stack.push((return_type, monotonic));
         Ok(self)
     }
 
+    fn visit_function(mut self, op: &str, args_size: usize) -> Result<Self> {
+        let (arg_types, monotonicity_vec) = self.pop_arguments(args_size)?;
+
+        let instance = FunctionFactory::instance();
+
+        let arg_types: Vec<&DataTypePtr> = arg_types.iter().collect();
+        let func = instance.get(op, &arg_types)?;
+        let is_deterministic = instance.get_features(op)?.is_deterministic;
+
+        self.push_function(op, func.as_ref(), is_deterministic, monotonicity_vec)
+    }
+
+    fn visit_cast(mut self, data_type: &DataTypePtr) -> Result<Self> {
+        let (_, monotonicity_vec) = self.pop_arguments(1)?;
+
+        let type_name = format!("{:?}", data_type);
+        let func = if data_type.is_nullable() {
+            CastFunction::create_try("cast", &type_name)
+        } else {
+            CastFunction::create("cast", &type_name)
+        }?;
+
+        self.push_function("cast", func.as_ref(), true, monotonicity_vec)
+    }
+
     /// Check whether the expression is monotonic or not. The left should be <= right.
     /// Return the monotonicity information, together with column name if any.
     pub fn check_expression(
@@ -266,7 +294,7 @@ impl ExpressionVisitor for ExpressionMonotonicityVisitor {
             Expression::BinaryExpression { op, .. } => self.visit_function(op, 2),
             Expression::UnaryExpression { op, .. } => self.visit_function(op, 1),
             Expression::ScalarFunction { op, args } => self.visit_function(op, args.len()),
-            // Todo: Expression::Cast
+            Expression::Cast { data_type, .. } => self.visit_cast(data_type),
             _ => Err(ErrorCode::UnknownException("Unable to get monotonicity")),
         }
     }
@@ -69,6 +69,7 @@ use crate::SortPlan;
 use crate::StagePlan;
 use crate::SubQueriesSetPlan;
 use crate::TruncateTablePlan;
+use crate::UnionPlan;
 use crate::UseDatabasePlan;
 
 #[allow(clippy::large_enum_variant)]
@@ -91,6 +92,7 @@ pub enum PlanNode {
     ReadSource(ReadDataSourcePlan),
     SubQueryExpression(SubQueriesSetPlan),
     Sink(SinkPlan),
+    Union(UnionPlan),
 
     // Explain.
     Explain(ExplainPlan),
@@ -190,6 +192,7 @@ impl PlanNode {
             PlanNode::Sort(v) => v.schema(),
             PlanNode::SubQueryExpression(v) => v.schema(),
             PlanNode::Sink(v) => v.schema(),
+            PlanNode::Union(v) => v.schema(),
 
             // Explain.
             PlanNode::Explain(v) => v.schema(),
@@ -288,6 +291,7 @@ impl PlanNode {
             PlanNode::Sort(_) => "SortPlan",
             PlanNode::SubQueryExpression(_) => "CreateSubQueriesSets",
             PlanNode::Sink(_) => "SinkPlan",
+            PlanNode::Union(_) => "UnionPlan",
 
             // Explain.
             PlanNode::Explain(_) => "ExplainPlan",
@@ -383,6 +387,7 @@ impl PlanNode {
             PlanNode::Sort(v) => vec![v.input.clone()],
             PlanNode::SubQueryExpression(v) => v.get_inputs(),
             PlanNode::Sink(v) => vec![v.input.clone()],
+            PlanNode::Union(v) => v.inputs.clone(),
 
             _ => vec![],
         }
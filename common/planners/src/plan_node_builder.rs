@@ -37,6 +37,7 @@ use crate::ProjectionPlan;
 use crate::RewriteHelper;
 use crate::SelectPlan;
 use crate::SortPlan;
+use crate::UnionPlan;
 
 pub enum AggregateMode {
     Partial,
@@ -248,6 +249,30 @@ impl PlanBuilder {
         })))
     }
 
+    /// Apply a UNION ALL with other inputs, the column types are unified to their super types.
+    pub fn union(&self, others: &[PlanNode]) -> Result<Self> {
+        let mut inputs = Vec::with_capacity(others.len() + 1);
+        inputs.push(Arc::new(self.plan.clone()));
+        inputs.extend(others.iter().map(|other| Arc::new(other.clone())));
+        Ok(Self::from(&PlanNode::Union(UnionPlan::try_create(inputs)?)))
+    }
+
+    /// Apply a UNION (DISTINCT), expressed as UNION ALL followed by a group by all columns.
+    pub fn union_distinct(&self, others: &[PlanNode]) -> Result<Self> {
+        let union = self.union(others)?;
+        let schema = union.plan.schema();
+        let group_by = schema
+            .fields()
+            .iter()
+            .map(|field| col(field.name()))
+            .collect::<Vec<_>>();
+
+        union
+            .aggregate_partial(&[], &group_by)?
+            .aggregate_final(schema, &[], &group_by)?
+            .project(&group_by)
+    }
+
     pub fn select(&self) -> Result<Self> {
         Ok(Self::from(&PlanNode::Select(SelectPlan {
             input: Arc::new(self.plan.clone()),
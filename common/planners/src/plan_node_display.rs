@@ -28,6 +28,12 @@ impl PlanNode {
         PlanNodeIndentFormatDisplay::create(0, self, false)
     }
 
+    /// Same as `display_indent_format`, in verbose mode the output schema of
+    /// every node is appended to its line.
+    pub fn display_indent(&self, verbose: bool) -> impl fmt::Display + '_ {
+        PlanNodeIndentFormatDisplay::create(0, self, false).with_verbose(verbose)
+    }
+
     pub fn display_graphviz(&self) -> impl fmt::Display + '_ {
         struct Wrapper<'a>(&'a PlanNode);
         impl<'a> fmt::Display for Wrapper<'a> {
@@ -97,3 +103,8 @@ impl fmt::Debug for PlanNode {
         self.display_indent_format().fmt(f)
     }
 }
+
+/// Formats the plan as an indented tree, one node per line.
+pub fn format_plan(plan: &PlanNode, verbose: bool) -> String {
+    format!("{}", plan.display_indent(verbose))
+}
@@ -30,6 +30,7 @@ use crate::Expression;
 use crate::ExpressionPlan;
 use crate::LimitPlan;
 use crate::PlanNode;
+use crate::PlanStatistics;
 use crate::ProjectionPlan;
 use crate::ReadDataSourcePlan;
 use crate::RenameTablePlan;
@@ -41,6 +42,7 @@ pub struct PlanNodeIndentFormatDisplay<'a> {
     indent: usize,
     node: &'a PlanNode,
     printed_indent: bool,
+    verbose: bool,
 }
 
 impl<'a> PlanNodeIndentFormatDisplay<'a> {
@@ -49,8 +51,15 @@ impl<'a> PlanNodeIndentFormatDisplay<'a> {
             indent,
             node,
             printed_indent: printed,
+            verbose: false,
         }
     }
+
+    /// Also print the output schema of every node.
+    pub fn with_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
 }
 
 impl<'a> fmt::Display for PlanNodeIndentFormatDisplay<'a> {
@@ -70,6 +79,7 @@ impl<'a> fmt::Display for PlanNodeIndentFormatDisplay<'a> {
             PlanNode::Having(plan) => write!(f, "Having: {:?}", plan.predicate),
             PlanNode::Sort(plan) => Self::format_sort(f, plan),
             PlanNode::Limit(plan) => Self::format_limit(f, plan),
+            PlanNode::Union(plan) => write!(f, "Union: {} inputs", plan.inputs.len()),
             PlanNode::SubQueryExpression(plan) => Self::format_subquery_expr(f, plan),
             PlanNode::ReadSource(plan) => Self::format_read_source(f, plan),
             PlanNode::CreateDatabase(plan) => Self::format_create_database(f, plan),
@@ -94,6 +104,7 @@ impl<'a> fmt::Display for PlanNodeIndentFormatDisplay<'a> {
                     }
 
                     PlanNodeIndentFormatDisplay::create(self.indent, input.as_ref(), printed)
+                        .with_verbose(self.verbose)
                         .fmt(f)?;
                     printed = true;
                 }
@@ -102,6 +113,17 @@ impl<'a> fmt::Display for PlanNodeIndentFormatDisplay<'a> {
             }
         }?;
 
+        if self.verbose {
+            let schema = self.node.schema();
+            if !schema.fields().is_empty() {
+                write!(f, ", output schema: {}", PlanNode::display_schema(&schema))?;
+            }
+            let estimated = self.node.estimated_statistics();
+            if estimated != PlanStatistics::default() {
+                write!(f, ", estimated: [{}]", estimated)?;
+            }
+        }
+
         let new_indent = self.indent + 1;
         for input in self.node.inputs() {
             if matches!(input.as_ref(), PlanNode::Empty(_)) {
@@ -109,7 +131,9 @@ impl<'a> fmt::Display for PlanNodeIndentFormatDisplay<'a> {
             }
 
             writeln!(f)?;
-            PlanNodeIndentFormatDisplay::create(new_indent, &input, false).fmt(f)?;
+            PlanNodeIndentFormatDisplay::create(new_indent, &input, false)
+                .with_verbose(self.verbose)
+                .fmt(f)?;
         }
 
         fmt::Result::Ok(())
@@ -225,7 +249,11 @@ impl<'a> PlanNodeIndentFormatDisplay<'a> {
         )?;
 
         if let Some(p) = &plan.push_downs {
-            if p.limit.is_some() || p.projection.is_some() {
+            if p.limit.is_some()
+                || p.projection.is_some()
+                || !p.filters.is_empty()
+                || !p.order_by.is_empty()
+            {
                 write!(f, ", push_downs: [")?;
                 let mut comma = false;
                 if p.projection.is_some() {
@@ -247,6 +275,7 @@ impl<'a> PlanNodeIndentFormatDisplay<'a> {
                     }
 
                     write!(f, "limit: {:?}", p.limit.unwrap())?;
+                    comma = true;
                 }
 
                 if !p.order_by.is_empty() {
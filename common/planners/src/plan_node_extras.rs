@@ -25,6 +25,8 @@ pub struct Extras {
     pub limit: Option<usize>,
     /// Optional order_by expression plan
     pub order_by: Vec<Expression>,
+    /// Whether the blocks should be returned in the order they were inserted
+    pub preserve_order: bool,
 }
 
 impl Extras {
@@ -34,6 +36,7 @@ impl Extras {
             filters: vec![],
             limit: None,
             order_by: vec![],
+            preserve_order: false,
         }
     }
 }
@@ -79,6 +79,7 @@ use crate::SinkPlan;
 use crate::SortPlan;
 use crate::StagePlan;
 use crate::TruncateTablePlan;
+use crate::UnionPlan;
 use crate::UseDatabasePlan;
 
 /// `PlanRewriter` is a visitor that can help to rewrite `PlanNode`
@@ -120,6 +121,7 @@ pub trait PlanRewriter: Sized {
             PlanNode::ReadSource(plan) => self.rewrite_read_data_source(plan),
             PlanNode::SubQueryExpression(plan) => self.rewrite_sub_queries_sets(plan),
             PlanNode::Sink(plan) => self.rewrite_sink(plan),
+            PlanNode::Union(plan) => self.rewrite_union(plan),
 
             // Query.
             PlanNode::Select(plan) => self.rewrite_select(plan),
@@ -197,6 +199,16 @@ pub trait PlanRewriter: Sized {
         }
     }
 
+    /// Rewrite the input of a plan node, the original `Arc` is reused if the input is unchanged,
+    /// so the untouched subtrees are shared between the original and the rewritten plan tree.
+    fn rewrite_input(&mut self, input: &Arc<PlanNode>) -> Result<Arc<PlanNode>> {
+        let new_input = self.rewrite_plan_node(input.as_ref())?;
+        match new_input == *input.as_ref() {
+            true => Ok(input.clone()),
+            false => Ok(Arc::new(new_input)),
+        }
+    }
+
     fn rewrite_subquery_plan(&mut self, subquery_plan: &PlanNode) -> Result<PlanNode> {
         self.rewrite_plan_node(subquery_plan)
     }
@@ -266,13 +278,13 @@ pub trait PlanRewriter: Sized {
         Ok(PlanNode::Stage(StagePlan {
             kind: plan.kind.clone(),
             scatters_expr: plan.scatters_expr.clone(),
-            input: Arc::new(self.rewrite_plan_node(plan.input.as_ref())?),
+            input: self.rewrite_input(&plan.input)?,
         }))
     }
 
     fn rewrite_broadcast(&mut self, plan: &BroadcastPlan) -> Result<PlanNode> {
         Ok(PlanNode::Broadcast(BroadcastPlan {
-            input: Arc::new(self.rewrite_plan_node(plan.input.as_ref())?),
+            input: self.rewrite_input(&plan.input)?,
         }))
     }
 
@@ -283,12 +295,18 @@ pub trait PlanRewriter: Sized {
     fn rewrite_projection(&mut self, plan: &ProjectionPlan) -> Result<PlanNode> {
         let new_input = self.rewrite_plan_node(plan.input.as_ref())?;
         let new_exprs = self.rewrite_exprs(&new_input.schema(), &plan.expr)?;
+        if new_input == *plan.input && new_exprs == plan.expr {
+            return Ok(PlanNode::Projection(plan.clone()));
+        }
         PlanBuilder::from(&new_input).project(&new_exprs)?.build()
     }
 
     fn rewrite_expression(&mut self, plan: &ExpressionPlan) -> Result<PlanNode> {
         let new_input = self.rewrite_plan_node(plan.input.as_ref())?;
         let new_exprs = self.rewrite_exprs(&new_input.schema(), &plan.exprs)?;
+        if new_input == *plan.input && new_exprs == plan.exprs {
+            return Ok(PlanNode::Expression(plan.clone()));
+        }
         PlanBuilder::from(&new_input)
             .expression(&new_exprs, &plan.desc)?
             .build()
@@ -302,23 +320,35 @@ pub trait PlanRewriter: Sized {
     fn rewrite_filter(&mut self, plan: &FilterPlan) -> Result<PlanNode> {
         let new_input = self.rewrite_plan_node(plan.input.as_ref())?;
         let new_predicate = self.rewrite_expr(&new_input.schema(), &plan.predicate)?;
+        if new_input == *plan.input && new_predicate == plan.predicate {
+            return Ok(PlanNode::Filter(plan.clone()));
+        }
         PlanBuilder::from(&new_input).filter(new_predicate)?.build()
     }
 
     fn rewrite_having(&mut self, plan: &HavingPlan) -> Result<PlanNode> {
         let new_input = self.rewrite_plan_node(plan.input.as_ref())?;
         let new_predicate = self.rewrite_expr(&new_input.schema(), &plan.predicate)?;
+        if new_input == *plan.input && new_predicate == plan.predicate {
+            return Ok(PlanNode::Having(plan.clone()));
+        }
         PlanBuilder::from(&new_input).having(new_predicate)?.build()
     }
 
     fn rewrite_sort(&mut self, plan: &SortPlan) -> Result<PlanNode> {
         let new_input = self.rewrite_plan_node(plan.input.as_ref())?;
         let new_order_by = self.rewrite_exprs(&new_input.schema(), &plan.order_by)?;
+        if new_input == *plan.input && new_order_by == plan.order_by {
+            return Ok(PlanNode::Sort(plan.clone()));
+        }
         PlanBuilder::from(&new_input).sort(&new_order_by)?.build()
     }
 
     fn rewrite_limit(&mut self, plan: &LimitPlan) -> Result<PlanNode> {
         let new_input = self.rewrite_plan_node(plan.input.as_ref())?;
+        if new_input == *plan.input {
+            return Ok(PlanNode::Limit(plan.clone()));
+        }
         PlanBuilder::from(&new_input)
             .limit_offset(plan.n, plan.offset)?
             .build()
@@ -326,6 +356,9 @@ pub trait PlanRewriter: Sized {
 
     fn rewrite_limit_by(&mut self, plan: &LimitByPlan) -> Result<PlanNode> {
         let new_input = self.rewrite_plan_node(plan.input.as_ref())?;
+        if new_input == *plan.input {
+            return Ok(PlanNode::LimitBy(plan.clone()));
+        }
         PlanBuilder::from(&new_input)
             .limit_by(plan.limit, &plan.limit_by)?
             .build()
@@ -335,16 +368,30 @@ pub trait PlanRewriter: Sized {
         Ok(PlanNode::ReadSource(plan.clone()))
     }
 
+    fn rewrite_union(&mut self, plan: &UnionPlan) -> Result<PlanNode> {
+        let new_inputs = plan
+            .inputs
+            .iter()
+            .map(|input| self.rewrite_input(input))
+            .collect::<Result<Vec<_>>>()?;
+
+        if new_inputs == plan.inputs {
+            return Ok(PlanNode::Union(plan.clone()));
+        }
+
+        Ok(PlanNode::Union(UnionPlan::try_create(new_inputs)?))
+    }
+
     fn rewrite_select(&mut self, plan: &SelectPlan) -> Result<PlanNode> {
         Ok(PlanNode::Select(SelectPlan {
-            input: Arc::new(self.rewrite_plan_node(plan.input.as_ref())?),
+            input: self.rewrite_input(&plan.input)?,
         }))
     }
 
     fn rewrite_explain(&mut self, plan: &ExplainPlan) -> Result<PlanNode> {
         Ok(PlanNode::Explain(ExplainPlan {
             typ: plan.typ,
-            input: Arc::new(self.rewrite_plan_node(plan.input.as_ref())?),
+            input: self.rewrite_input(&plan.input)?,
         }))
     }
 
@@ -12,6 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fmt;
+
+use crate::PlanNode;
+
 #[derive(serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone, Debug, Default)]
 pub struct Statistics {
     /// Total rows of the query read.
@@ -68,3 +72,164 @@ impl Statistics {
         *self = Self::default();
     }
 }
+
+/// The fraction of the rows assumed to pass a predicate whose selectivity is unknown.
+pub const DEFAULT_FILTER_SELECTIVITY: f64 = 0.5;
+
+/// Size estimates of the output of a plan node, computed bottom-up from the read sources.
+#[derive(serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+pub struct PlanStatistics {
+    /// Estimated output rows, None if unknown.
+    pub estimated_rows: Option<u64>,
+    /// Estimated output bytes, None if unknown.
+    pub estimated_bytes: Option<u64>,
+    /// Are the estimates exact.
+    pub is_exact: bool,
+}
+
+impl PlanStatistics {
+    pub fn new_exact(rows: u64, bytes: u64) -> Self {
+        PlanStatistics {
+            estimated_rows: Some(rows),
+            estimated_bytes: Some(bytes),
+            is_exact: true,
+        }
+    }
+
+    fn inexact(self) -> Self {
+        PlanStatistics {
+            is_exact: false,
+            ..self
+        }
+    }
+
+    /// Keep `rows` of the estimated rows, the bytes are reduced proportionally.
+    fn with_rows(self, rows: u64) -> Self {
+        let bytes = match (self.estimated_rows, self.estimated_bytes) {
+            (Some(0), bytes) => bytes,
+            (Some(old_rows), Some(bytes)) => {
+                Some((bytes as f64 * rows as f64 / old_rows as f64).ceil() as u64)
+            }
+            _ => None,
+        };
+
+        PlanStatistics {
+            estimated_rows: Some(rows),
+            estimated_bytes: bytes,
+            is_exact: self.is_exact,
+        }
+    }
+
+    fn with_selectivity(self, selectivity: f64) -> Self {
+        match self.estimated_rows {
+            None => self.inexact(),
+            Some(rows) => self
+                .with_rows((rows as f64 * selectivity).ceil() as u64)
+                .inexact(),
+        }
+    }
+}
+
+impl fmt::Display for PlanStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn display(value: Option<u64>) -> String {
+            value.map_or_else(|| "unknown".to_string(), |v| v.to_string())
+        }
+
+        write!(
+            f,
+            "rows: {}, bytes: {}, is_exact: {}",
+            display(self.estimated_rows),
+            display(self.estimated_bytes),
+            self.is_exact
+        )
+    }
+}
+
+impl PlanNode {
+    /// Estimate the size of the output of the node, scans take the numbers computed by the
+    /// storage from its snapshot, after pruning. A pushed down filter makes the scan inexact
+    /// but its selectivity is only applied once, by the filter kept above the scan.
+    pub fn estimated_statistics(&self) -> PlanStatistics {
+        match self {
+            PlanNode::ReadSource(plan) => {
+                let statistics = &plan.statistics;
+                if !statistics.is_exact && statistics.read_rows == 0 {
+                    return PlanStatistics::default();
+                }
+
+                let estimated = PlanStatistics {
+                    estimated_rows: Some(statistics.read_rows as u64),
+                    estimated_bytes: Some(statistics.read_bytes as u64),
+                    is_exact: statistics.is_exact,
+                };
+
+                match &plan.push_downs {
+                    Some(extras) if !extras.filters.is_empty() => estimated.inexact(),
+                    _ => estimated,
+                }
+            }
+            PlanNode::Filter(plan) if plan.is_literal_false() => PlanStatistics::new_exact(0, 0),
+            PlanNode::Filter(plan) => plan
+                .input
+                .estimated_statistics()
+                .with_selectivity(DEFAULT_FILTER_SELECTIVITY),
+            PlanNode::Having(plan) => plan
+                .input
+                .estimated_statistics()
+                .with_selectivity(DEFAULT_FILTER_SELECTIVITY),
+            PlanNode::Limit(plan) => {
+                let input = plan.input.estimated_statistics();
+                match (input.estimated_rows, plan.n) {
+                    (Some(rows), n) => {
+                        let rows = rows.saturating_sub(plan.offset as u64);
+                        let rows = n.map_or(rows, |n| std::cmp::min(rows, n as u64));
+                        input.with_rows(rows)
+                    }
+                    (None, Some(n)) => PlanStatistics {
+                        estimated_rows: Some(n as u64),
+                        estimated_bytes: None,
+                        is_exact: false,
+                    },
+                    (None, None) => input,
+                }
+            }
+            PlanNode::AggregatorFinal(plan) if plan.group_expr.is_empty() => PlanStatistics {
+                estimated_rows: Some(1),
+                estimated_bytes: None,
+                is_exact: true,
+            },
+            PlanNode::AggregatorPartial(plan) => plan.input.estimated_statistics().inexact(),
+            PlanNode::AggregatorFinal(plan) => plan.input.estimated_statistics().inexact(),
+            PlanNode::LimitBy(plan) => plan.input.estimated_statistics().inexact(),
+            PlanNode::Union(plan) => {
+                let mut estimated = PlanStatistics::new_exact(0, 0);
+                for input in &plan.inputs {
+                    let input = input.estimated_statistics();
+                    estimated = PlanStatistics {
+                        estimated_rows: estimated
+                            .estimated_rows
+                            .zip(input.estimated_rows)
+                            .map(|(a, b)| a + b),
+                        estimated_bytes: estimated
+                            .estimated_bytes
+                            .zip(input.estimated_bytes)
+                            .map(|(a, b)| a + b),
+                        is_exact: estimated.is_exact && input.is_exact,
+                    };
+                }
+                estimated
+            }
+            // The nodes which don't change the rows: projections, expressions, sorts, stages...
+            PlanNode::Projection(_)
+            | PlanNode::Expression(_)
+            | PlanNode::Sort(_)
+            | PlanNode::Stage(_)
+            | PlanNode::Broadcast(_)
+            | PlanNode::Sink(_)
+            | PlanNode::Select(_) => self.input(0).estimated_statistics(),
+            PlanNode::SubQueryExpression(plan) => plan.input.estimated_statistics(),
+            _ => PlanStatistics::default(),
+        }
+    }
+}
@@ -69,6 +69,7 @@ use crate::SinkPlan;
 use crate::SortPlan;
 use crate::StagePlan;
 use crate::TruncateTablePlan;
+use crate::UnionPlan;
 use crate::UseDatabasePlan;
 
 /// `PlanVisitor` implements visitor pattern(reference [syn](https://docs.rs/syn/1.0.72/syn/visit/trait.Visit.html)) for `PlanNode`.
@@ -132,6 +133,7 @@ pub trait PlanVisitor {
             PlanNode::ReadSource(plan) => self.visit_read_data_source(plan),
             PlanNode::SubQueryExpression(plan) => self.visit_sub_queries_sets(plan),
             PlanNode::Sink(plan) => self.visit_append(plan),
+            PlanNode::Union(plan) => self.visit_union(plan),
 
             // Query.
             PlanNode::Select(plan) => self.visit_select(plan),
@@ -305,6 +307,13 @@ pub trait PlanVisitor {
         Ok(())
     }
 
+    fn visit_union(&mut self, plan: &UnionPlan) -> Result<()> {
+        for input in &plan.inputs {
+            self.visit_plan_node(input.as_ref())?;
+        }
+        Ok(())
+    }
+
     fn visit_select(&mut self, plan: &SelectPlan) -> Result<()> {
         self.visit_plan_node(plan.input.as_ref())
     }
@@ -15,6 +15,9 @@
 use std::sync::Arc;
 
 use bitflags::bitflags;
+use common_datavalues::prelude::ToDataType;
+use common_datavalues::prelude::Vu8;
+use common_datavalues::DataField;
 use common_datavalues::DataSchema;
 use common_datavalues::DataSchemaRef;
 
@@ -24,6 +27,9 @@ bitflags! {
         const PURGE   = 0b00000001;
         const COMPACT = 0b00000010;
         const ALL = Self::PURGE.bits | Self::COMPACT.bits;
+        const VACUUM  = 0b00000100;
+        // only reports the files to be vacuumed
+        const DRY_RUN = 0b00001000;
     }
 }
 
@@ -36,6 +42,12 @@ pub struct OptimizeTablePlan {
 
 impl OptimizeTablePlan {
     pub fn schema(&self) -> DataSchemaRef {
-        Arc::new(DataSchema::empty())
+        if self.operation.contains(Optimization::VACUUM) {
+            // the files vacuumed, or to be vacuumed if it is a dry run
+            let field = DataField::new("file", Vu8::to_data_type());
+            Arc::new(DataSchema::new(vec![field]))
+        } else {
+            Arc::new(DataSchema::empty())
+        }
     }
 }
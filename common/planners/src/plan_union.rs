@@ -0,0 +1,110 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+
+use common_datavalues::aggregate_types;
+use common_datavalues::DataField;
+use common_datavalues::DataSchemaRef;
+use common_datavalues::DataSchemaRefExt;
+use common_datavalues::DataTypePtr;
+use common_exception::ErrorCode;
+use common_exception::Result;
+
+use crate::col;
+use crate::Expression;
+use crate::PlanBuilder;
+use crate::PlanNode;
+
+/// UNION ALL of several inputs, blocks of all inputs are interleaved without deduplication.
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq)]
+pub struct UnionPlan {
+    pub inputs: Vec<Arc<PlanNode>>,
+    pub schema: DataSchemaRef,
+}
+
+impl UnionPlan {
+    /// Reconcile the inputs schemas: the column names come from the first input and
+    /// each column type is the common super type of all inputs, inputs which
+    /// don't match the result schema are wrapped into a casting projection.
+    pub fn try_create(inputs: Vec<Arc<PlanNode>>) -> Result<UnionPlan> {
+        if inputs.is_empty() {
+            return Err(ErrorCode::BadArguments("UNION requires at least one input"));
+        }
+
+        let first_schema = inputs[0].schema();
+        let arity = first_schema.fields().len();
+        for input in inputs.iter().skip(1) {
+            let input_arity = input.schema().fields().len();
+            if input_arity != arity {
+                return Err(ErrorCode::NumberArgumentsNotMatch(format!(
+                    "UNION inputs must have the same number of columns, expect {}, but got {}",
+                    arity, input_arity
+                )));
+            }
+        }
+
+        let mut fields = Vec::with_capacity(arity);
+        for (index, field) in first_schema.fields().iter().enumerate() {
+            let types = inputs
+                .iter()
+                .map(|input| input.schema().field(index).data_type().clone())
+                .collect::<Vec<DataTypePtr>>();
+
+            let data_type = aggregate_types(&types).map_err(|cause| {
+                cause.add_message_back(format!(" (while unifying UNION column {})", field.name()))
+            })?;
+            fields.push(DataField::new(field.name(), data_type));
+        }
+
+        let schema = DataSchemaRefExt::create(fields);
+        let inputs = inputs
+            .into_iter()
+            .map(|input| Self::coerce_input(input, &schema))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(UnionPlan { inputs, schema })
+    }
+
+    pub fn schema(&self) -> DataSchemaRef {
+        self.schema.clone()
+    }
+
+    fn coerce_input(input: Arc<PlanNode>, schema: &DataSchemaRef) -> Result<Arc<PlanNode>> {
+        let input_schema = input.schema();
+        if input_schema == *schema {
+            return Ok(input);
+        }
+
+        let exprs = input_schema
+            .fields()
+            .iter()
+            .zip(schema.fields().iter())
+            .map(|(input_field, field)| {
+                let column = col(input_field.name());
+                match input_field.data_type() == field.data_type() {
+                    true => column.alias(field.name()),
+                    false => Expression::Cast {
+                        expr: Box::new(column),
+                        data_type: field.data_type().clone(),
+                        pg_style: false,
+                    }
+                    .alias(field.name()),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let plan = PlanBuilder::from(input.as_ref()).project(&exprs)?.build()?;
+        Ok(Arc::new(plan))
+    }
+}
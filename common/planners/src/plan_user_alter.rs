@@ -25,6 +25,8 @@ pub struct AlterUserPlan {
     pub user: UserIdentity,
     // None means no change to make
     pub auth_info: Option<AuthInfo>,
+    // The plaintext of the new password, to be checked by the password rules
+    pub password: Option<String>,
     pub user_option: Option<UserOption>,
 }
 
@@ -17,6 +17,7 @@ use std::sync::Arc;
 use common_datavalues::DataSchema;
 use common_datavalues::DataSchemaRef;
 use common_meta_types::AuthInfo;
+use common_meta_types::PasswordPolicy;
 use common_meta_types::UserIdentity;
 use common_meta_types::UserOption;
 
@@ -24,7 +25,10 @@ use common_meta_types::UserOption;
 pub struct CreateUserPlan {
     pub user: UserIdentity,
     pub auth_info: AuthInfo,
+    // The plaintext of the password of `auth_info`, to be checked by the password rules
+    pub password: Option<String>,
     pub user_option: UserOption,
+    pub password_policy: PasswordPolicy,
 }
 
 impl CreateUserPlan {
@@ -13,11 +13,15 @@
 // limitations under the License.
 
 use std::collections::BTreeMap;
+use std::io::Write;
 
 use common_datavalues::prelude::*;
 use common_exception::Result;
 use common_meta_types::TableMeta;
 use common_planners::*;
+use goldenfile::Mint;
+
+use crate::test::Test;
 
 #[test]
 fn test_plan_display_indent() -> Result<()> {
@@ -50,3 +54,53 @@ fn test_plan_display_indent() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_format_plan() -> Result<()> {
+    let mut mint = Mint::new("tests/it/testdata");
+    let mut file = mint.new_goldenfile("plan-display.txt").unwrap();
+
+    let source = Test::create().generate_source_plan_for_test(10000)?;
+    let filtered = PlanBuilder::from(&source)
+        .filter(add(col("number"), lit(1)).eq(lit(4)))?
+        .having(add(col("number"), lit(1)).eq(lit(4)))?
+        .project(&[col("number").alias("c1"), col("number").alias("c2")])?
+        .build()?;
+
+    let pushed_down = match source {
+        PlanNode::ReadSource(plan) => PlanNode::ReadSource(ReadDataSourcePlan {
+            push_downs: Some(Extras {
+                projection: Some(vec![0]),
+                filters: vec![col("number").gt(lit(1))],
+                limit: Some(15),
+                order_by: vec![],
+                preserve_order: false,
+            }),
+            ..plan
+        }),
+        _ => unreachable!(),
+    };
+    let limited = PlanBuilder::from(&pushed_down)
+        .limit_offset(Some(10), 5)?
+        .build()?;
+
+    let cases = [
+        ("filter, having and projection", &filtered),
+        ("limit with offset over pushed down read", &limited),
+    ];
+
+    for (name, plan) in cases {
+        for verbose in [false, true] {
+            writeln!(
+                file,
+                "---------- {} (verbose: {}) ----------",
+                name, verbose
+            )
+            .unwrap();
+            writeln!(file, "{}", format_plan(plan, verbose)).unwrap();
+            writeln!(file).unwrap();
+        }
+    }
+
+    Ok(())
+}
@@ -47,6 +47,26 @@ fn create_u8(d: u8) -> Option<ColumnWithField> {
     Some(ColumnWithField::new(col, data_field))
 }
 
+fn create_i64(d: i64) -> Option<ColumnWithField> {
+    let data_field = DataField::new("x", i64::to_data_type());
+    let col = data_field
+        .data_type()
+        .create_constant_column(&DataValue::Int64(d), 1)
+        .unwrap();
+
+    Some(ColumnWithField::new(col, data_field))
+}
+
+fn create_date16(d: u16) -> Option<ColumnWithField> {
+    let data_field = DataField::new("x", Date16Type::arc());
+    let col = data_field
+        .data_type()
+        .create_constant_column(&DataValue::UInt64(d as u64), 1)
+        .unwrap();
+
+    Some(ColumnWithField::new(col, data_field))
+}
+
 fn create_datetime(d: u32) -> Option<ColumnWithField> {
     let data_field = DataField::new("x", DateTime32Type::arc(None));
     let col = data_field
@@ -540,6 +560,97 @@ fn test_dates_function() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_cast_function() -> Result<()> {
+    let cast = |expr: Expression, data_type: DataTypePtr| Expression::Cast {
+        expr: Box::new(expr),
+        data_type,
+        pg_style: false,
+    };
+
+    let test_suite = vec![
+        Test {
+            name: "f(z) = cast(z as Date16)",
+            expr: cast(col("z"), Date16Type::arc()),
+            column: "z",
+            left: create_datetime(1638288000),
+            right: create_datetime(1638316799),
+            expect_mono: Monotonicity {
+                is_monotonic: true,
+                is_positive: true,
+                is_constant: false,
+                left: create_date16(18961),
+                right: create_date16(18961),
+            },
+        },
+        Test {
+            name: "f(z) = toDate(z)",
+            expr: Expression::create_scalar_function("toDate", vec![col("z")]),
+            column: "z",
+            left: create_datetime(1638288000),
+            right: create_datetime(1638460800),
+            expect_mono: Monotonicity {
+                is_monotonic: true,
+                is_positive: true,
+                is_constant: false,
+                left: create_date16(18961),
+                right: create_date16(18963),
+            },
+        },
+        Test {
+            name: "f(y) = cast(y as Float64)",
+            expr: cast(col("y"), f64::to_data_type()),
+            column: "y",
+            left: create_i64(-10),
+            right: create_i64(10),
+            expect_mono: Monotonicity {
+                is_monotonic: true,
+                is_positive: true,
+                is_constant: false,
+                left: create_f64(-10.0),
+                right: create_f64(10.0),
+            },
+        },
+        Test {
+            name: "f(y) = cast(-y as Float64)",
+            expr: cast(neg(col("y")), f64::to_data_type()),
+            column: "y",
+            left: create_i64(-10),
+            right: create_i64(10),
+            expect_mono: Monotonicity {
+                is_monotonic: true,
+                is_positive: false,
+                is_constant: false,
+                left: create_f64(10.0),
+                right: create_f64(-10.0),
+            },
+        },
+        Test {
+            // Narrowing casts wrap around on overflow.
+            name: "f(y) = cast(y as Int8)",
+            expr: cast(col("y"), i8::to_data_type()),
+            column: "y",
+            left: create_i64(-10),
+            right: create_i64(1000),
+            expect_mono: Monotonicity::default(),
+        },
+        Test {
+            // The source type is unknown without boundaries.
+            name: "f(x) = cast(x as Float64)",
+            expr: cast(col("x"), f64::to_data_type()),
+            column: "x",
+            left: None,
+            right: None,
+            expect_mono: Monotonicity::default(),
+        },
+    ];
+
+    for t in test_suite.into_iter() {
+        verify_test(t)?;
+    }
+    Ok(())
+}
+
 #[test]
 fn test_single_point() -> Result<()> {
     let test_suite = vec![
@@ -19,7 +19,7 @@ use pretty_assertions::assert_eq;
 #[test]
 fn test_plan_extras() -> Result<()> {
     let extras = Extras::default();
-    let expect = "Extras { projection: None, filters: [], limit: None, order_by: [] }";
+    let expect = "Extras { projection: None, filters: [], limit: None, order_by: [], preserve_order: false }";
     let actual = format!("{:?}", extras);
     assert_eq!(expect, actual);
     Ok(())
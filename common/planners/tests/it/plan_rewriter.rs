@@ -226,3 +226,62 @@ fn test_plan_rewriter_1() -> Result<()> {
     assert_eq!(before_rewrite, after_rewrite);
     Ok(())
 }
+
+#[test]
+fn test_rewrite_reuses_untouched_subtrees() -> Result<()> {
+    struct LimitRewriter;
+
+    impl PlanRewriter for LimitRewriter {
+        fn rewrite_aggregate_partial(&mut self, plan: &AggregatorPartialPlan) -> Result<PlanNode> {
+            DefaultRewriter.rewrite_aggregate_partial(plan)
+        }
+
+        fn rewrite_aggregate_final(&mut self, plan: &AggregatorFinalPlan) -> Result<PlanNode> {
+            DefaultRewriter.rewrite_aggregate_final(plan)
+        }
+
+        fn rewrite_limit(&mut self, plan: &LimitPlan) -> Result<PlanNode> {
+            Ok(PlanNode::Limit(LimitPlan {
+                n: Some(1),
+                offset: plan.offset,
+                input: self.rewrite_input(&plan.input)?,
+            }))
+        }
+    }
+
+    let source = Test::create().generate_source_plan_for_test(10000)?;
+    let plan = PlanBuilder::from(&source)
+        .filter(col("number").eq(lit(1)))?
+        .project(&[col("number")])?
+        .limit(10)?
+        .build()?;
+    let plan = PlanNode::Select(SelectPlan {
+        input: Arc::new(plan),
+    });
+
+    // Nothing to rewrite, the whole tree is reused.
+    let rewritten = DefaultRewriter.rewrite_plan_node(&plan)?;
+    match (&plan, &rewritten) {
+        (PlanNode::Select(before), PlanNode::Select(after)) => {
+            assert!(Arc::ptr_eq(&before.input, &after.input));
+        }
+        _ => unreachable!(),
+    }
+
+    // Only the limit is rebuilt, the subtree below it is reused.
+    let rewritten = LimitRewriter.rewrite_plan_node(&plan)?;
+    let (before, after) = match (&plan, &rewritten) {
+        (PlanNode::Select(before), PlanNode::Select(after)) => (&before.input, &after.input),
+        _ => unreachable!(),
+    };
+    assert!(!Arc::ptr_eq(before, after));
+    match (before.as_ref(), after.as_ref()) {
+        (PlanNode::Limit(before), PlanNode::Limit(after)) => {
+            assert_eq!(after.n, Some(1));
+            assert!(Arc::ptr_eq(&before.input, &after.input));
+        }
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_planners::*;
+use pretty_assertions::assert_eq;
+
+fn source(fields: Vec<DataField>) -> Result<PlanNode> {
+    PlanBuilder::create(DataSchemaRefExt::create(fields)).build()
+}
+
+#[test]
+fn test_union_plan_coercion() -> Result<()> {
+    let left = source(vec![
+        DataField::new("a", i32::to_data_type()),
+        DataField::new("b", Vu8::to_data_type()),
+    ])?;
+    let right = source(vec![
+        DataField::new("c", i64::to_data_type()),
+        DataField::new("d", Vu8::to_data_type()),
+    ])?;
+
+    let plan = PlanBuilder::from(&left).union(&[right.clone()])?.build()?;
+    let expect = DataSchemaRefExt::create(vec![
+        DataField::new("a", i64::to_data_type()),
+        DataField::new("b", Vu8::to_data_type()),
+    ]);
+    assert_eq!(plan.schema(), expect);
+
+    let inputs = plan.inputs();
+    assert_eq!(inputs.len(), 2);
+    // The Int32 input is casted, the Int64 one only renamed, all inputs match the union schema.
+    assert!(matches!(inputs[0].as_ref(), PlanNode::Projection(_)));
+    assert!(matches!(inputs[1].as_ref(), PlanNode::Projection(_)));
+    for input in &inputs {
+        assert_eq!(input.schema(), expect);
+    }
+
+    // Inputs with the union schema are kept as they are.
+    let plan = PlanBuilder::from(&right).union(&[right.clone()])?.build()?;
+    assert_eq!(plan.inputs()[0].as_ref(), &right);
+    assert_eq!(plan.inputs()[1].as_ref(), &right);
+    assert!(format!("{:?}", plan).starts_with("Union: 2 inputs"));
+    Ok(())
+}
+
+#[test]
+fn test_union_plan_arity_mismatch() -> Result<()> {
+    let left = source(vec![DataField::new("a", i32::to_data_type())])?;
+    let right = source(vec![
+        DataField::new("a", i32::to_data_type()),
+        DataField::new("b", i32::to_data_type()),
+    ])?;
+
+    let result = PlanBuilder::from(&left).union(&[right]);
+    let error = result.err().unwrap();
+    assert_eq!(error.code(), ErrorCode::NumberArgumentsNotMatch("").code());
+    assert_eq!(
+        error.message(),
+        "UNION inputs must have the same number of columns, expect 1, but got 2"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_union_plan_empty_inputs() -> Result<()> {
+    let result = UnionPlan::try_create(vec![]);
+    let error = result.err().unwrap();
+    assert_eq!(error.code(), ErrorCode::BadArguments("").code());
+    assert_eq!(error.message(), "UNION requires at least one input");
+    Ok(())
+}
+
+#[test]
+fn test_union_distinct_plan() -> Result<()> {
+    let left = source(vec![DataField::new("a", i32::to_data_type())])?;
+    let right = source(vec![DataField::new("a", i64::to_data_type())])?;
+
+    let plan = PlanBuilder::from(&left).union_distinct(&[right])?.build()?;
+    let expect = DataSchemaRefExt::create(vec![DataField::new("a", i64::to_data_type())]);
+    assert_eq!(plan.schema(), expect);
+
+    let aggregator_final = plan.input(0);
+    assert!(matches!(
+        aggregator_final.as_ref(),
+        PlanNode::AggregatorFinal(_)
+    ));
+    let aggregator_partial = aggregator_final.input(0);
+    assert!(matches!(
+        aggregator_partial.as_ref(),
+        PlanNode::AggregatorPartial(_)
+    ));
+    assert!(matches!(
+        aggregator_partial.input(0).as_ref(),
+        PlanNode::Union(_)
+    ));
+    Ok(())
+}
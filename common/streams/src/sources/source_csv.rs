@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use async_trait::async_trait;
+use common_base::MemoryTracker;
 use common_datablocks::DataBlock;
 use common_datavalues::DataSchemaRef;
 use common_exception::ErrorCode;
@@ -161,6 +162,8 @@ where R: AsyncRead + Unpin + Send
             .map(|f| f.data_type().create_deserializer(self.builder.block_size))
             .collect::<Vec<_>>();
 
+        // the bytes deserialized are accounted to the memory of the task until the block is built
+        let mut memory = MemoryTracker::alloc_task_memory(0);
         let mut rows = 0;
         let mut records = self.reader.byte_records();
 
@@ -168,6 +171,8 @@ where R: AsyncRead + Unpin + Send
             let record = record.map_err_to_code(ErrorCode::BadBytes, || {
                 format!("Parse csv error at line {}", self.rows)
             })?;
+            memory.grow(record.as_slice().len() as i64);
+            MemoryTracker::check_task_memory()?;
 
             if record.is_empty() {
                 break;
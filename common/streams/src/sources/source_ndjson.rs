@@ -15,6 +15,7 @@
 use std::borrow::Cow;
 
 use async_trait::async_trait;
+use common_base::MemoryTracker;
 use common_datablocks::DataBlock;
 use common_datavalues::DataSchemaRef;
 use common_exception::ErrorCode;
@@ -116,6 +117,8 @@ where R: AsyncBufRead + Unpin + Send
             .map(|f| (f.name(), f.data_type().name()))
             .collect::<Vec<_>>();
 
+        // the bytes deserialized are accounted to the memory of the task until the block is built
+        let mut memory = MemoryTracker::alloc_task_memory(0);
         let mut rows = 0;
 
         loop {
@@ -136,6 +139,8 @@ where R: AsyncBufRead + Unpin + Send
             if self.buffer.trim().is_empty() {
                 continue;
             }
+            memory.grow(self.buffer.len() as i64);
+            MemoryTracker::check_task_memory()?;
 
             let json: serde_json::Value = serde_json::from_reader(self.buffer.as_bytes())?;
 
@@ -0,0 +1,87 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use common_base::tokio;
+use common_datablocks::*;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+use common_streams::*;
+use futures::stream::StreamExt;
+use futures::TryStreamExt;
+
+// `blocks` blocks of 100 ids, and the counter of the blocks pulled from it.
+fn blocks_of_100(blocks: i32) -> (SendableDataBlockStream, Arc<AtomicUsize>) {
+    let schema = DataSchemaRefExt::create(vec![DataField::new("id", i32::to_data_type())]);
+    let pulled = Arc::new(AtomicUsize::new(0));
+
+    let counter = pulled.clone();
+    let stream = futures::stream::iter(0..blocks).map(move |i| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        let ids = (i * 100..(i + 1) * 100).collect::<Vec<i32>>();
+        Ok(DataBlock::create(schema.clone(), vec![Series::from_data(
+            ids,
+        )]))
+    });
+    (Box::pin(stream), pulled)
+}
+
+fn ids(blocks: &[DataBlock]) -> Result<Vec<i32>> {
+    let mut ids = vec![];
+    for b in blocks {
+        let column: &Int32Column = Series::check_get(b.column(0))?;
+        ids.extend_from_slice(column.values());
+    }
+    Ok(ids)
+}
+
+#[tokio::test]
+async fn test_take_stream_offset_spans_blocks() -> Result<()> {
+    let (input, pulled) = blocks_of_100(20);
+    let stream = TakeStream::new(Box::pin(SkipStream::new(input, 995)), 10);
+    let result = stream.try_collect::<Vec<_>>().await?;
+
+    assert_eq!(ids(&result)?, (995..1005).collect::<Vec<_>>());
+    assert_eq!(
+        pulled.load(Ordering::SeqCst),
+        11,
+        "stop pulling once the limit is reached"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_take_stream_zero_limit() -> Result<()> {
+    let (input, pulled) = blocks_of_100(3);
+    let stream = TakeStream::new(input, 0);
+    let result = stream.try_collect::<Vec<_>>().await?;
+
+    assert!(result.is_empty());
+    assert_eq!(pulled.load(Ordering::SeqCst), 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_take_stream_limit_larger_than_input() -> Result<()> {
+    let (input, pulled) = blocks_of_100(3);
+    let stream = TakeStream::new(input, 1000);
+    let result = stream.try_collect::<Vec<_>>().await?;
+
+    assert_eq!(ids(&result)?, (0..300).collect::<Vec<_>>());
+    assert_eq!(pulled.load(Ordering::SeqCst), 3);
+    Ok(())
+}
@@ -21,16 +21,27 @@ use common_arrow::arrow_format::flight::data::BasicAuth;
 use common_base::tokio::sync::mpsc;
 use common_grpc::GrpcClaim;
 use common_grpc::GrpcToken;
+use common_meta_api::KVApi;
 use common_meta_grpc::MetaGrpcReadReq;
 use common_meta_grpc::MetaGrpcWriteReq;
+use common_meta_types::export_kv_chunks;
 use common_meta_types::protobuf::meta_service_server::MetaService;
+use common_meta_types::protobuf::ExportPrefixRequest;
 use common_meta_types::protobuf::ExportedChunk;
+use common_meta_types::protobuf::ExportedKVChunk;
 use common_meta_types::protobuf::HandshakeRequest;
 use common_meta_types::protobuf::HandshakeResponse;
 use common_meta_types::protobuf::RaftReply;
 use common_meta_types::protobuf::RaftRequest;
 use common_meta_types::protobuf::WatchRequest;
 use common_meta_types::protobuf::WatchResponse;
+use common_meta_types::KVEntry;
+use common_meta_types::RaftTxId;
+use common_meta_types::ReadConsistency;
+use common_meta_types::EXPORT_KV_CHUNK_MAX_BYTES;
+use common_meta_types::READ_CONSISTENCY_KEY;
+use common_meta_types::TXID_CLIENT_KEY;
+use common_meta_types::TXID_SERIAL_KEY;
 use common_tracing::tracing;
 use futures::StreamExt;
 use prost::Message;
@@ -125,10 +136,12 @@ impl MetaService for MetaServiceImpl {
         self.check_token(request.metadata())?;
         common_tracing::extract_remote_span_as_parent(&request);
 
+        let txid = txid_from_metadata(request.metadata());
+
         let action: MetaGrpcWriteReq = request.try_into()?;
-        tracing::info!("Receive write_action: {:?}", action);
+        tracing::info!("Receive write_action: {:?} {:?}", action, txid);
 
-        let body = self.action_handler.execute_write(action).await;
+        let body = self.action_handler.execute_write(action, txid).await;
         Ok(Response::new(body))
     }
 
@@ -136,10 +149,17 @@ impl MetaService for MetaServiceImpl {
         self.check_token(request.metadata())?;
         common_tracing::extract_remote_span_as_parent(&request);
 
+        let consistency = request
+            .metadata()
+            .get(READ_CONSISTENCY_KEY)
+            .and_then(|v| v.to_str().ok())
+            .map(ReadConsistency::parse)
+            .unwrap_or_default();
+
         let action: MetaGrpcReadReq = request.try_into()?;
-        tracing::info!("Receive read_action: {:?}", action);
+        tracing::info!("Receive read_action: {:?} {:?}", action, consistency);
 
-        let res = self.action_handler.execute_read(action).await;
+        let res = self.action_handler.execute_read(action, consistency).await;
 
         Ok(Response::new(res))
     }
@@ -166,6 +186,31 @@ impl MetaService for MetaServiceImpl {
         Ok(Response::new(Box::pin(s)))
     }
 
+    type ExportPrefixStream =
+        Pin<Box<dyn Stream<Item = Result<ExportedKVChunk, tonic::Status>> + Send + Sync + 'static>>;
+
+    // Export the kv entries with a prefix, a large value is split into several chunks.
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn export_prefix(
+        &self,
+        request: Request<ExportPrefixRequest>,
+    ) -> Result<Response<Self::ExportPrefixStream>, Status> {
+        self.check_token(request.metadata())?;
+
+        let prefix = request.into_inner().prefix;
+        let meta_node = &self.action_handler.meta_node;
+        let kvs = meta_node
+            .prefix_list_kv(&prefix)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        let entries = kvs.into_iter().map(|(k, seqv)| KVEntry::new(k, seqv));
+        let chunks = export_kv_chunks(entries, EXPORT_KV_CHUNK_MAX_BYTES);
+
+        let s = futures::stream::iter(chunks.into_iter().map(Ok));
+        Ok(Response::new(Box::pin(s)))
+    }
+
     type WatchStream =
         Pin<Box<dyn Stream<Item = Result<WatchResponse, tonic::Status>> + Send + Sync + 'static>>;
 
@@ -203,3 +248,10 @@ impl Stream for ExportStream {
         Poll::Ready(Some(self.data.drain(0..chunk_size).collect()))
     }
 }
+
+/// The txid of a write is optional, an invalid one is ignored as if it is absent.
+fn txid_from_metadata(metadata: &MetadataMap) -> Option<RaftTxId> {
+    let client = metadata.get(TXID_CLIENT_KEY)?.to_str().ok()?;
+    let serial = metadata.get(TXID_SERIAL_KEY)?.to_str().ok()?.parse().ok()?;
+    Some(RaftTxId::new(client, serial))
+}
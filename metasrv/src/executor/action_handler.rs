@@ -20,6 +20,10 @@ use common_meta_grpc::MetaGrpcWriteReq;
 use common_meta_grpc::RequestFor;
 use common_meta_types::protobuf::RaftReply;
 use common_meta_types::MetaError;
+use common_meta_types::Operation;
+use common_meta_types::RaftTxId;
+use common_meta_types::ReadConsistency;
+use common_meta_types::UpsertKVAction;
 
 use crate::meta_service::MetaNode;
 
@@ -40,12 +44,44 @@ impl ActionHandler {
         ActionHandler { meta_node }
     }
 
-    pub async fn execute_write(&self, action: MetaGrpcWriteReq) -> RaftReply {
+    /// Executes a write, a kv write with a `txid` that is already applied is not applied again.
+    pub async fn execute_write(
+        &self,
+        action: MetaGrpcWriteReq,
+        txid: Option<RaftTxId>,
+    ) -> RaftReply {
         // To keep the code IDE-friendly, we manually expand the enum variants and dispatch them one by one
 
         match action {
             MetaGrpcWriteReq::UpsertKV(a) => {
-                let r = self.meta_node.upsert_kv(a).await;
+                let r = self.meta_node.upsert_kv_with_txid(a, txid).await;
+                RaftReply::from(r)
+            }
+            MetaGrpcWriteReq::DeleteKV(a) => {
+                let act = UpsertKVAction::new(&a.key, a.seq, Operation::Delete, None);
+                let r = self.meta_node.upsert_kv_with_txid(act, txid).await;
+                RaftReply::from(r)
+            }
+            MetaGrpcWriteReq::DeleteKVByPrefix(a) => {
+                let r = self.meta_node.delete_prefix(&a.prefix).await;
+                RaftReply::from(r)
+            }
+            MetaGrpcWriteReq::IncrKV(a) => {
+                let r = self
+                    .meta_node
+                    .incr_kv_with_txid(&a.key, a.delta, a.seq, txid)
+                    .await;
+                RaftReply::from(r)
+            }
+            MetaGrpcWriteReq::UpsertKVBatch(a) => {
+                let r = self
+                    .meta_node
+                    .upsert_kv_batch_with_txid(a.actions, txid)
+                    .await;
+                RaftReply::from(r)
+            }
+            MetaGrpcWriteReq::Transaction(a) => {
+                let r = self.meta_node.transaction_with_txid(a, txid).await;
                 RaftReply::from(r)
             }
             // database
@@ -88,20 +124,50 @@ impl ActionHandler {
         }
     }
 
-    pub async fn execute_read(&self, action: MetaGrpcReadReq) -> RaftReply {
+    /// Executes a read, the kv reads are as up to date as the `consistency` requires, the others
+    /// are always read by the leader.
+    pub async fn execute_read(
+        &self,
+        action: MetaGrpcReadReq,
+        consistency: ReadConsistency,
+    ) -> RaftReply {
         // To keep the code IDE-friendly, we manually expand the enum variants and dispatch them one by one
 
         match action {
             MetaGrpcReadReq::GetKV(a) => {
-                let r = self.meta_node.get_kv(&a.key).await;
+                let r = self
+                    .meta_node
+                    .get_kv_with_consistency(&a.key, consistency)
+                    .await;
+                RaftReply::from(r)
+            }
+            MetaGrpcReadReq::GetKVMeta(a) => {
+                let r = self.meta_node.get_kv_meta(&a.key).await;
+                RaftReply::from(r)
+            }
+            MetaGrpcReadReq::GetKVHistory(a) => {
+                let r = self.meta_node.get_kv_history(&a.key, a.limit).await;
                 RaftReply::from(r)
             }
             MetaGrpcReadReq::MGetKV(a) => {
-                let r = self.meta_node.mget_kv(&a.keys).await;
+                let r = self
+                    .meta_node
+                    .mget_kv_with_consistency(&a.keys, consistency)
+                    .await;
                 RaftReply::from(r)
             }
             MetaGrpcReadReq::PrefixListKV(a) => {
-                let r = self.meta_node.prefix_list_kv(&a.0).await;
+                let r = self
+                    .meta_node
+                    .prefix_list_kv_with_consistency(&a.0, consistency)
+                    .await;
+                RaftReply::from(r)
+            }
+            MetaGrpcReadReq::PrefixListKVPage(a) => {
+                let r = self
+                    .meta_node
+                    .prefix_list_kv_paged(&a.prefix, a.after_key, a.limit, a.keys_only)
+                    .await;
                 RaftReply::from(r)
             }
 
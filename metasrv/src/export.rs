@@ -63,6 +63,9 @@ pub fn serialize_kv_variant(kv: &KeySpaceKV) -> Result<(sled::IVec, sled::IVec),
         KeySpaceKV::ShareInbound { key, value } => ser!(ShareInbounds, key, value),
         KeySpaceKV::ShareOutbound { key, value } => ser!(ShareOutbounds, key, value),
         KeySpaceKV::LogMeta { key, value } => ser!(LogMeta, key, value),
+        KeySpaceKV::KVHistory { key, value } => ser!(KVHistory, key, value),
+        KeySpaceKV::AppliedTxIds { key, value } => ser!(AppliedTxIds, key, value),
+        KeySpaceKV::AppliedTxIdSlots { key, value } => ser!(AppliedTxIdSlots, key, value),
     }
 }
 
@@ -89,7 +92,10 @@ pub fn deserialize_to_kv_variant(d: &[Vec<u8>]) -> Result<KeySpaceKV, MetaStorag
         ClientLastResps,
         TableLookup,
         DatabaseLookup,
-        LogMeta
+        LogMeta,
+        KVHistory,
+        AppliedTxIds,
+        AppliedTxIdSlots
     );
 
     unreachable!("unknown prefix: {}", prefix);
@@ -29,6 +29,7 @@ use common_meta_types::MetaError;
 use common_meta_types::MetaRaftError;
 use common_meta_types::Node;
 use common_meta_types::NodeId;
+use common_meta_types::ReadConsistency;
 use common_tracing::tracing;
 use openraft::raft::ClientWriteRequest;
 
@@ -56,6 +57,10 @@ impl<'a> MetaLeader<'a> {
     ) -> Result<ForwardResponse, MetaError> {
         tracing::debug!("handle_forwardable_req: {:?}", req);
 
+        if req.consistency == ReadConsistency::Linearizable {
+            self.confirm_leadership().await?;
+        }
+
         match req.body {
             ForwardRequestBody::Join(join_req) => {
                 self.join(join_req).await?;
@@ -92,6 +97,16 @@ impl<'a> MetaLeader<'a> {
                 let res = sm.get_kv(&req.key).await?;
                 Ok(ForwardResponse::GetKV(res))
             }
+            ForwardRequestBody::GetKVMeta(req) => {
+                let sm = self.meta_node.get_state_machine().await;
+                let res = sm.get_kv_meta(&req.key).await?;
+                Ok(ForwardResponse::GetKVMeta(res))
+            }
+            ForwardRequestBody::GetKVHistory(req) => {
+                let sm = self.meta_node.get_state_machine().await;
+                let res = sm.get_kv_history(&req.key, req.limit).await?;
+                Ok(ForwardResponse::KVHistory(res))
+            }
             ForwardRequestBody::MGetKV(req) => {
                 let sm = self.meta_node.get_state_machine().await;
                 let res = sm.mget_kv(&req.keys).await?;
@@ -102,6 +117,13 @@ impl<'a> MetaLeader<'a> {
                 let res = sm.prefix_list_kv(&req.prefix).await?;
                 Ok(ForwardResponse::ListKV(res))
             }
+            ForwardRequestBody::ListKVPage(req) => {
+                let sm = self.meta_node.get_state_machine().await;
+                let res = sm
+                    .prefix_list_kv_paged(&req.prefix, req.after_key, req.limit, req.keys_only)
+                    .await?;
+                Ok(ForwardResponse::ListKVPage(res))
+            }
             ForwardRequestBody::GetShare(req) => {
                 let sm = self.meta_node.get_state_machine().await;
                 let res = sm.get_share(req).await?;
@@ -110,6 +132,16 @@ impl<'a> MetaLeader<'a> {
         }
     }
 
+    /// Confirms with a quorum that this node is still the leader, thus the state machine has
+    /// applied every write completed before, i.e., the read index.
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn confirm_leadership(&self) -> Result<(), MetaError> {
+        self.meta_node.raft.client_read().await.map_err(|e| {
+            MetaRaftError::ConsistentReadError(format!("leadership is not confirmed: {}", e))
+        })?;
+        Ok(())
+    }
+
     /// Join a new node to the cluster.
     ///
     /// - Adds the node to cluster as a non-voter persistently and starts replication.
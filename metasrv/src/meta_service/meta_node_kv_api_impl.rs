@@ -13,21 +13,45 @@
 // limitations under the License.
 
 use async_trait::async_trait;
+use common_base::tokio::sync::mpsc;
 use common_meta_api::KVApi;
+use common_meta_api::WatchStream;
+use common_meta_types::protobuf::WatchRequest;
 use common_meta_types::AppliedState;
 use common_meta_types::Cmd;
+use common_meta_types::DeleteKVReply;
 use common_meta_types::GetKVActionReply;
+use common_meta_types::GetKVHistoryReq;
+use common_meta_types::GetKVMetaReply;
+use common_meta_types::GetKVMetaReq;
 use common_meta_types::GetKVReq;
+use common_meta_types::IncrKVReply;
+use common_meta_types::IncrKVReq;
+use common_meta_types::KVHistoryReply;
+use common_meta_types::ListKVPageReq;
 use common_meta_types::ListKVReq;
 use common_meta_types::LogEntry;
 use common_meta_types::MGetKVActionReply;
 use common_meta_types::MGetKVReq;
+use common_meta_types::MatchSeq;
 use common_meta_types::MetaError;
 use common_meta_types::MetaResultError;
+use common_meta_types::Operation;
+use common_meta_types::PrefixListPage;
 use common_meta_types::PrefixListReply;
+use common_meta_types::RaftTxId;
+use common_meta_types::ReadConsistency;
+use common_meta_types::TxnReply;
+use common_meta_types::TxnRequest;
 use common_meta_types::UpsertKVAction;
 use common_meta_types::UpsertKVActionReply;
+use common_meta_types::UpsertKVBatchReply;
+use common_meta_types::UpsertKVBatchReq;
+use common_meta_types::WatchEvent;
+use common_meta_types::DELETE_KV_BY_PREFIX_CHUNK_SIZE;
 use common_tracing::tracing;
+use futures::StreamExt;
+use tokio_stream::wrappers::ReceiverStream;
 
 use crate::meta_service::MetaNode;
 
@@ -39,24 +63,25 @@ use crate::meta_service::MetaNode;
 #[async_trait]
 impl KVApi for MetaNode {
     async fn upsert_kv(&self, act: UpsertKVAction) -> Result<UpsertKVActionReply, MetaError> {
-        let ent = LogEntry {
-            txid: None,
-            cmd: Cmd::UpsertKV {
-                key: act.key,
-                seq: act.seq,
-                value: act.value,
-                value_meta: act.value_meta,
-            },
-        };
-        let rst = self.write(ent).await?;
+        self.upsert_kv_with_txid(act, None).await
+    }
 
-        match rst {
-            AppliedState::KV(x) => Ok(x),
-            _ => Err(MetaError::MetaResultError(MetaResultError::InvalidType {
-                expect: "AppliedState::KV".to_string(),
-                got: "other".to_string(),
-            })),
-        }
+    #[tracing::instrument(level = "debug", skip(self, actions))]
+    async fn upsert_kv_batch(
+        &self,
+        actions: Vec<UpsertKVAction>,
+    ) -> Result<UpsertKVBatchReply, MetaError> {
+        self.upsert_kv_batch_with_txid(actions, None).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn incr_kv(
+        &self,
+        key: &str,
+        delta: i64,
+        seq: MatchSeq,
+    ) -> Result<IncrKVReply, MetaError> {
+        self.incr_kv_with_txid(key, delta, seq, None).await
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
@@ -70,6 +95,67 @@ impl KVApi for MetaNode {
         Ok(res)
     }
 
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn get_kv_meta(&self, key: &str) -> Result<GetKVMetaReply, MetaError> {
+        let res = self
+            .consistent_read(GetKVMetaReq {
+                key: key.to_string(),
+            })
+            .await?;
+
+        Ok(res)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn get_kv_history(&self, key: &str, limit: usize) -> Result<KVHistoryReply, MetaError> {
+        let res = self
+            .consistent_read(GetKVHistoryReq {
+                key: key.to_string(),
+                limit,
+            })
+            .await?;
+
+        Ok(res)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn delete_kv(&self, key: &str, seq: MatchSeq) -> Result<DeleteKVReply, MetaError> {
+        self.upsert_kv(UpsertKVAction::new(key, seq, Operation::Delete, None))
+            .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64, MetaError> {
+        let mut removed = 0;
+
+        // Remove in chunks to bound the size of a raft log and of the transaction applying it.
+        loop {
+            let ent = LogEntry {
+                txid: None,
+                cmd: Cmd::DeleteKVByPrefix {
+                    prefix: prefix.to_string(),
+                    limit: DELETE_KV_BY_PREFIX_CHUNK_SIZE,
+                },
+            };
+            let rst = self.write(ent).await?;
+
+            let count = match rst {
+                AppliedState::KVRemoved { count } => count,
+                _ => {
+                    return Err(MetaError::MetaResultError(MetaResultError::InvalidType {
+                        expect: "AppliedState::KVRemoved".to_string(),
+                        got: "other".to_string(),
+                    }))
+                }
+            };
+
+            removed += count;
+            if count < DELETE_KV_BY_PREFIX_CHUNK_SIZE {
+                return Ok(removed);
+            }
+        }
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     async fn mget_kv(&self, keys: &[String]) -> Result<MGetKVActionReply, MetaError> {
         let res = self
@@ -91,4 +177,185 @@ impl KVApi for MetaNode {
 
         Ok(res)
     }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn prefix_list_kv_paged(
+        &self,
+        prefix: &str,
+        after_key: Option<String>,
+        limit: usize,
+        keys_only: bool,
+    ) -> Result<PrefixListPage, MetaError> {
+        let res = self
+            .consistent_read(ListKVPageReq {
+                prefix: prefix.to_string(),
+                after_key,
+                limit,
+                keys_only,
+            })
+            .await?;
+
+        Ok(res)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, txn))]
+    async fn transaction(&self, txn: TxnRequest) -> Result<TxnReply, MetaError> {
+        self.transaction_with_txid(txn, None).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn watch(&self, prefix: &str, from_seq: Option<u64>) -> Result<WatchStream, MetaError> {
+        let (tx, rx) = mpsc::channel(4);
+        self.create_watcher_stream(WatchRequest::prefix(prefix, from_seq), tx);
+
+        let strm = ReceiverStream::new(rx)
+            .map(|resp| resp.map_err(MetaError::from).and_then(WatchEvent::try_from));
+        Ok(strm.boxed())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn get_kv_with_consistency(
+        &self,
+        key: &str,
+        consistency: ReadConsistency,
+    ) -> Result<GetKVActionReply, MetaError> {
+        let req = GetKVReq {
+            key: key.to_string(),
+        };
+        self.read_with_consistency(req, consistency).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn mget_kv_with_consistency(
+        &self,
+        keys: &[String],
+        consistency: ReadConsistency,
+    ) -> Result<MGetKVActionReply, MetaError> {
+        let req = MGetKVReq {
+            keys: keys.to_vec(),
+        };
+        self.read_with_consistency(req, consistency).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn prefix_list_kv_with_consistency(
+        &self,
+        prefix: &str,
+        consistency: ReadConsistency,
+    ) -> Result<PrefixListReply, MetaError> {
+        let req = ListKVReq {
+            prefix: prefix.to_string(),
+        };
+        self.read_with_consistency(req, consistency).await
+    }
+}
+
+/// The kv writes with an optional txid: a write with a txid that is already applied is not
+/// applied again, and the recorded reply of it is returned.
+impl MetaNode {
+    #[tracing::instrument(level = "debug", skip(self, act))]
+    pub async fn upsert_kv_with_txid(
+        &self,
+        act: UpsertKVAction,
+        txid: Option<RaftTxId>,
+    ) -> Result<UpsertKVActionReply, MetaError> {
+        let ent = LogEntry {
+            txid,
+            cmd: Cmd::UpsertKV {
+                key: act.key,
+                seq: act.seq,
+                value: act.value,
+                value_meta: act.value_meta,
+            },
+        };
+        let rst = self.write(ent).await?;
+
+        match rst {
+            AppliedState::KV(x) => Ok(x),
+            _ => Err(MetaError::MetaResultError(MetaResultError::InvalidType {
+                expect: "AppliedState::KV".to_string(),
+                got: "other".to_string(),
+            })),
+        }
+    }
+
+    /// A batch may be split into several logs, every one of them gets a txid derived from `txid`.
+    #[tracing::instrument(level = "debug", skip(self, actions))]
+    pub async fn upsert_kv_batch_with_txid(
+        &self,
+        actions: Vec<UpsertKVAction>,
+        txid: Option<RaftTxId>,
+    ) -> Result<UpsertKVBatchReply, MetaError> {
+        let mut changes = Vec::with_capacity(actions.len());
+
+        for (i, req) in UpsertKVBatchReq::split(actions).into_iter().enumerate() {
+            let ent = LogEntry {
+                txid: txid
+                    .as_ref()
+                    .map(|t| RaftTxId::new(&format!("{}#{}", t.client, i), t.serial)),
+                cmd: Cmd::UpsertKVBatch(req),
+            };
+            let rst = self.write(ent).await?;
+
+            match rst {
+                AppliedState::KVBatch(x) => changes.extend(x),
+                _ => {
+                    return Err(MetaError::MetaResultError(MetaResultError::InvalidType {
+                        expect: "AppliedState::KVBatch".to_string(),
+                        got: "other".to_string(),
+                    }))
+                }
+            }
+        }
+
+        Ok(changes)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn incr_kv_with_txid(
+        &self,
+        key: &str,
+        delta: i64,
+        seq: MatchSeq,
+        txid: Option<RaftTxId>,
+    ) -> Result<IncrKVReply, MetaError> {
+        let ent = LogEntry {
+            txid,
+            cmd: Cmd::IncrKV(IncrKVReq {
+                key: key.to_string(),
+                delta,
+                seq,
+            }),
+        };
+        let rst = self.write(ent).await?;
+
+        match rst {
+            AppliedState::KV(x) => IncrKVReq::reply(x),
+            _ => Err(MetaError::MetaResultError(MetaResultError::InvalidType {
+                expect: "AppliedState::KV".to_string(),
+                got: "other".to_string(),
+            })),
+        }
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, txn))]
+    pub async fn transaction_with_txid(
+        &self,
+        txn: TxnRequest,
+        txid: Option<RaftTxId>,
+    ) -> Result<TxnReply, MetaError> {
+        let ent = LogEntry {
+            txid,
+            cmd: Cmd::Transaction(txn),
+        };
+        let rst = self.write(ent).await?;
+
+        match rst {
+            AppliedState::TxnReply(x) => Ok(x),
+            _ => Err(MetaError::MetaResultError(MetaResultError::InvalidType {
+                expect: "AppliedState::TxnReply".to_string(),
+                got: "other".to_string(),
+            })),
+        }
+    }
 }
@@ -68,6 +68,7 @@ impl RaftService for RaftServiceImpl {
             .handle_forwardable_request(ForwardRequest {
                 forward_to_leader: 1,
                 body: ForwardRequestBody::Write(ent),
+                consistency: Default::default(),
             })
             .await;
 
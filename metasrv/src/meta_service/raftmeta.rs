@@ -16,12 +16,16 @@ use std::collections::BTreeSet;
 use std::fmt::Debug;
 use std::net::Ipv4Addr;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 use common_base::tokio;
 use common_base::tokio::sync::watch;
 use common_base::tokio::sync::Mutex;
 use common_base::tokio::sync::RwLockReadGuard;
 use common_base::tokio::task::JoinHandle;
+use common_base::tokio::time::MissedTickBehavior;
 use common_grpc::DNSResolver;
 use common_meta_api::MetaApi;
 use common_meta_raft_store::config::RaftConfig;
@@ -49,10 +53,12 @@ use common_meta_types::MetaRaftError;
 use common_meta_types::MetaResult;
 use common_meta_types::Node;
 use common_meta_types::NodeId;
+use common_meta_types::ReadConsistency;
 use common_meta_types::SeqV;
 use common_meta_types::TableInfo;
 use common_meta_types::TableMeta;
 use common_meta_types::ToMetaError;
+use common_meta_types::REMOVE_EXPIRED_KV_CHUNK_SIZE;
 use common_tracing::tracing;
 use common_tracing::tracing::Instrument;
 use openraft::Config;
@@ -71,6 +77,13 @@ use crate::watcher::WatcherManager;
 use crate::watcher::WatcherStreamSender;
 use crate::Opened;
 
+/// How often the leader removes the expired kv entries.
+const EXPIRED_KV_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long an expired kv entry is kept before being removed.
+/// Before removal it is already treated as absent by every read and write.
+const EXPIRED_KV_RETENTION: Duration = Duration::from_secs(300);
+
 // MetaRaft is a impl of the generic Raft handling meta data R/W.
 pub type MetaRaft = Raft<LogEntry, AppliedState, Network, MetaRaftStore>;
 
@@ -121,11 +134,12 @@ impl MetaNodeBuilder {
 
         let (tx, rx) = watch::channel::<()>(());
 
-        let watcher = WatcherManager::create();
-
-        sto.get_state_machine()
-            .await
-            .set_subscriber(Box::new(watcher.subscriber.clone()));
+        let watcher = {
+            let mut sm = sto.get_state_machine().await;
+            let watcher = WatcherManager::create(sm.get_kv_seq()?);
+            sm.set_subscriber(Box::new(watcher.subscriber.clone()));
+            watcher
+        };
 
         let mn = Arc::new(MetaNode {
             sto: sto.clone(),
@@ -141,6 +155,8 @@ impl MetaNodeBuilder {
             MetaNode::subscribe_metrics(mn.clone(), metrics_rx).await;
         }
 
+        MetaNode::sweep_expired_kv(mn.clone()).await;
+
         let endpoint = if let Some(a) = self.endpoint.take() {
             a
         } else {
@@ -415,6 +431,73 @@ impl MetaNode {
         jh.push(h);
     }
 
+    /// Spawns a task to remove the long expired kv entries periodically.
+    ///
+    /// It only takes effect on the leader, by writing a raft log, so that every node removes
+    /// the same entries.
+    async fn sweep_expired_kv(mn: Arc<Self>) {
+        let mut running_rx = mn.running_rx.clone();
+        let mut jh = mn.join_handles.lock().await;
+
+        let span = tracing::span!(tracing::Level::INFO, "sweep-expired-kv");
+
+        let h = tokio::task::spawn(
+            async move {
+                let mut interval = tokio::time::interval(EXPIRED_KV_SWEEP_INTERVAL);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+                loop {
+                    tokio::select! {
+                        _ = running_rx.changed() => {
+                           return Ok::<(), MetaError>(());
+                        }
+                        _ = interval.tick() => {}
+                    }
+
+                    let leader = mn.raft.metrics().borrow().current_leader;
+                    if leader != Some(mn.sto.id) {
+                        continue;
+                    }
+
+                    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+                    let expire_before = now.saturating_sub(EXPIRED_KV_RETENTION).as_secs();
+
+                    // Each log scans a chunk of the keys, the next one continues after it.
+                    let mut start = String::new();
+                    loop {
+                        let rst = mn
+                            .write(LogEntry {
+                                txid: None,
+                                cmd: Cmd::RemoveExpiredKV {
+                                    expire_before,
+                                    start,
+                                    limit: REMOVE_EXPIRED_KV_CHUNK_SIZE,
+                                },
+                            })
+                            .await;
+
+                        match rst {
+                            Ok(AppliedState::KVSwept {
+                                next: Some(next), ..
+                            }) => start = next,
+                            Ok(_) => break,
+                            Err(e) => {
+                                tracing::warn!(
+                                    "fail to remove expired kv: my id={}, {}",
+                                    mn.sto.id,
+                                    e
+                                );
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            .instrument(span),
+        );
+        jh.push(h);
+    }
+
     /// Start MetaNode in either `boot`, `single`, `join` or `open` mode,
     /// according to config.
     #[tracing::instrument(level = "debug", skip(config))]
@@ -460,6 +543,7 @@ impl MetaNode {
                     node_id: conf.id,
                     endpoint: raft_api_advertise_host_endpoint.clone(),
                 }),
+                consistency: Default::default(),
             };
 
             let result: std::result::Result<RaftReply, Status> =
@@ -601,12 +685,37 @@ impl MetaNode {
         ForwardResponse: TryInto<Reply>,
         <ForwardResponse as TryInto<Reply>>::Error: std::fmt::Display,
     {
-        let res = self
-            .handle_forwardable_request(ForwardRequest {
-                forward_to_leader: 1,
-                body: req.into(),
-            })
-            .await?;
+        self.read_with_consistency(req, ReadConsistency::Lease)
+            .await
+    }
+
+    /// Reads by the leader, or by this node if the `consistency` is `ReadConsistency::Stale`.
+    ///
+    /// With `ReadConsistency::Linearizable` the leader confirms its leadership before reading.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn read_with_consistency<Request, Reply>(
+        &self,
+        req: Request,
+        consistency: ReadConsistency,
+    ) -> Result<Reply, MetaError>
+    where
+        Request: Into<ForwardRequestBody> + Debug,
+        ForwardResponse: TryInto<Reply>,
+        <ForwardResponse as TryInto<Reply>>::Error: std::fmt::Display,
+    {
+        let req = ForwardRequest {
+            forward_to_leader: 1,
+            body: req.into(),
+            consistency,
+        };
+
+        let res = match consistency {
+            // Any node serves it from its local state machine, as a leader does.
+            ReadConsistency::Stale => MetaLeader::new(self).handle_forwardable_req(req).await?,
+            ReadConsistency::Lease | ReadConsistency::Linearizable => {
+                self.handle_forwardable_request(req).await?
+            }
+        };
 
         let res: Reply = res.try_into().map_err(|e| {
             MetaRaftError::ConsistentReadError(format!("consistent read recv invalid reply: {}", e))
@@ -750,6 +859,7 @@ impl MetaNode {
             .handle_forwardable_request(ForwardRequest {
                 forward_to_leader: 1,
                 body: ForwardRequestBody::Write(req.clone()),
+                consistency: Default::default(),
             })
             .await?;
 
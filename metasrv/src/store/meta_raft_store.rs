@@ -183,7 +183,7 @@ impl MetaRaftStore {
             .write_state_machine_id(&(sm_id, new_sm_id))
             .await?;
 
-        let new_sm = StateMachine::open(&self.config, new_sm_id).await?;
+        let mut new_sm = StateMachine::open(&self.config, new_sm_id).await?;
         tracing::info!(
             "insert all key-value into new state machine, n={}",
             snap.kvs.len()
@@ -225,6 +225,8 @@ impl MetaRaftStore {
 
         // TODO(xp): use checksum to check consistency?
 
+        // The changes in the snapshot are not told, a watcher finds out the gap by the kv seq.
+        new_sm.subscriber = sm.subscriber.take();
         *sm = new_sm;
         Ok(())
     }
@@ -13,6 +13,7 @@
 //  limitations under the License.
 //
 use core::ops::Range;
+use std::collections::VecDeque;
 
 use common_base::tokio;
 use common_base::tokio::sync::mpsc;
@@ -36,8 +37,13 @@ pub type WatcherStreamSender = Sender<Result<WatchResponse, Status>>;
 
 type CreateWatcherEvent = (WatchRequest, WatcherStreamSender);
 
+/// Max number of the latest events kept for a watcher to resume from.
+const WATCH_HISTORY_SIZE: usize = 1024;
+
 #[derive(Clone, Debug)]
 pub struct StateMachineKvData {
+    /// The kv seq taken by the change, the same on every node.
+    pub seq: u64,
     pub key: String,
     pub prev: Option<SeqV>,
     pub current: Option<SeqV>,
@@ -68,16 +74,27 @@ struct WatcherManagerCore {
     watcher_range_map: RangeMap<String, WatcherId, WatcherStream>,
 
     current_watcher_id: WatcherId,
+
+    /// The kv seq of the last change received.
+    last_seq: u64,
+
+    /// The latest events with consecutive kv seqs ending at `last_seq`, for a watcher to replay
+    /// from.
+    history: VecDeque<StateMachineKvData>,
 }
 
 impl WatcherManager {
-    pub fn create() -> Self {
+    /// Creates a watcher manager receiving the changes after `last_seq`, the current kv seq of
+    /// the state machine.
+    pub fn create(last_seq: u64) -> Self {
         let (event_tx, event_rx) = mpsc::unbounded_channel();
 
         let core = WatcherManagerCore {
             event_rx,
             watcher_range_map: RangeMap::new(),
             current_watcher_id: 1,
+            last_seq,
+            history: VecDeque::with_capacity(WATCH_HISTORY_SIZE),
         };
 
         let _h = tokio::spawn(core.watcher_manager_main());
@@ -126,38 +143,66 @@ impl WatcherManagerCore {
         })
     }
 
+    fn is_filtered_out(filter: FilterType, kv: &StateMachineKvData) -> bool {
+        let is_delete_event = kv.current.is_none();
+        (filter == FilterType::Delete && !is_delete_event)
+            || (filter == FilterType::Update && is_delete_event)
+    }
+
+    fn watch_response(kv: &StateMachineKvData) -> WatchResponse {
+        WatchResponse {
+            event: Some(Event {
+                key: kv.key.clone(),
+                current: WatcherManagerCore::convert_seqv_to_pb(&kv.current),
+                prev: WatcherManagerCore::convert_seqv_to_pb(&kv.prev),
+            }),
+            seq: kv.seq,
+        }
+    }
+
+    /// The seq of the oldest event kept, or of the next event if none is kept.
+    fn oldest_seq(&self) -> u64 {
+        self.history
+            .front()
+            .map(|kv| kv.seq)
+            .unwrap_or(self.last_seq + 1)
+    }
+
     async fn notify_event(&mut self, kv: StateMachineKvData) {
+        // Every change takes the next kv seq. A gap means some changes are not received, e.g.,
+        // they come with an installed snapshot, thus the history before is not continuous.
+        if kv.seq != self.last_seq + 1 {
+            tracing::info!(
+                "watcher history is reset, kv seq jumps from {} to {}",
+                self.last_seq,
+                kv.seq
+            );
+            self.history.clear();
+        }
+        self.last_seq = kv.seq;
+
+        if self.history.len() == WATCH_HISTORY_SIZE {
+            self.history.pop_front();
+        }
+        self.history.push_back(kv.clone());
+
         let set = self.watcher_range_map.get_by_point(&kv.key);
         if set.is_empty() {
             return;
         }
 
-        let current = kv.current;
-        let prev = kv.prev;
-
-        let is_delete_event = current.is_none();
         let mut remove_range_keys: Vec<RangeKey<String, WatcherId>> = vec![];
 
         for range_key_stream in set.iter() {
-            let filter = range_key_stream.1.filter_type;
-
             // filter out event
-            if (filter == FilterType::Delete && !is_delete_event)
-                || (filter == FilterType::Update && is_delete_event)
-            {
+            if WatcherManagerCore::is_filtered_out(range_key_stream.1.filter_type, &kv) {
                 continue;
             }
 
             let watcher_id = range_key_stream.0.key;
             let stream = range_key_stream.1;
             assert_eq!(stream.id, watcher_id);
-            let resp = WatchResponse {
-                event: Some(Event {
-                    key: kv.key.clone(),
-                    current: WatcherManagerCore::convert_seqv_to_pb(&current),
-                    prev: WatcherManagerCore::convert_seqv_to_pb(&prev),
-                }),
-            };
+            let resp = WatcherManagerCore::watch_response(&kv);
 
             if let Err(err) = stream.send(resp).await {
                 tracing::info!(
@@ -186,9 +231,43 @@ impl WatcherManagerCore {
             Err(_) => return,
         };
 
+        let filter = create.filter_type();
+
+        if let Some(from_seq) = create.from_seq {
+            // Only a seq from the oldest kept event to the next one to come is known to miss
+            // nothing, either older events are dropped or the seq is not reached on this node.
+            let oldest = self.oldest_seq();
+            if from_seq < oldest || from_seq > self.last_seq + 1 {
+                let _ = tx
+                    .send(Err(Status::out_of_range(format!(
+                        "can not watch from seq {}, the events kept are from seq {} to {}",
+                        from_seq, oldest, self.last_seq
+                    ))))
+                    .await;
+                return;
+            }
+
+            // Replay before registering the watcher: no event is applied in between, since the
+            // events are handled one by one.
+            for kv in self.history.iter() {
+                if kv.seq < from_seq
+                    || kv.key < range.start
+                    || kv.key > range.end
+                    || WatcherManagerCore::is_filtered_out(filter, kv)
+                {
+                    continue;
+                }
+
+                let resp = WatcherManagerCore::watch_response(kv);
+                if let Err(err) = tx.send(Ok(resp)).await {
+                    tracing::info!("close watcher stream cause replay err: {:?}", err);
+                    return;
+                }
+            }
+        }
+
         self.current_watcher_id += 1;
         let watcher_id = self.current_watcher_id;
-        let filter = create.filter_type();
 
         let watcher_stream = WatcherStream::new(
             watcher_id,
@@ -216,10 +295,11 @@ impl WatcherManagerCore {
 }
 
 impl StateMachineSubscriber for WatcherStateMachineSubscriber {
-    fn kv_changed(&self, key: &str, prev: Option<SeqV>, current: Option<SeqV>) {
+    fn kv_changed(&self, key: &str, seq: u64, prev: Option<SeqV>, current: Option<SeqV>) {
         let _ = self
             .event_tx
             .send(WatcherEvent::StateMachineKvDataEvent(StateMachineKvData {
+                seq,
                 key: key.to_string(),
                 prev,
                 current,
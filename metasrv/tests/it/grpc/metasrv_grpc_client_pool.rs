@@ -0,0 +1,112 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Test sharing the meta-service clients with MetaClientPool.
+
+use common_base::tokio;
+use common_base::Stoppable;
+use common_grpc::RpcClientConf;
+use common_meta_api::KVApi;
+use common_meta_grpc::MetaClientPool;
+use common_meta_grpc::MetaGrpcClientConf;
+use common_meta_types::MatchSeq;
+use common_meta_types::Operation;
+use common_meta_types::UpsertKVAction;
+use common_tracing::tracing;
+use pretty_assertions::assert_eq;
+use tokio::time::Duration;
+
+use crate::init_meta_ut;
+
+fn client_conf(addr: &str, password: &str) -> MetaGrpcClientConf {
+    let conf = RpcClientConf {
+        address: addr.to_string(),
+        username: "root".to_string(),
+        password: password.to_string(),
+        tls_conf: None,
+    };
+    MetaGrpcClientConf {
+        meta_service_config: conf.clone(),
+        kv_service_config: conf,
+        client_timeout_in_second: 10,
+        ..Default::default()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn test_client_pool_share_and_reconnect() -> anyhow::Result<()> {
+    // - Start a metasrv server.
+    // - Get clients from the pool and write with one of them.
+    // - Restart the server, the channel of the pooled client is dropped by the server.
+    // - The next read through the pooled client reconnects instead of failing.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+
+    let (mut tc, addr) = crate::tests::start_metasrv().await?;
+
+    let pool = MetaClientPool::new();
+
+    tracing::info!("--- clients of the same configuration are shared");
+    let client = pool.get(&client_conf(&addr, "xxx"));
+    let client2 = pool.get(&client_conf(&addr, "xxx"));
+    assert!(std::sync::Arc::ptr_eq(&client, &client2));
+
+    let _other = pool.get(&client_conf(&addr, "yyy"));
+
+    let mut conf = client_conf(&addr, "xxx");
+    conf.client_timeout_in_second = 20;
+    let other_timeout = pool.get(&conf);
+    assert!(!std::sync::Arc::ptr_eq(&client, &other_timeout));
+
+    let stats = pool.stats();
+    assert_eq!(3, stats.clients);
+    assert_eq!(1, stats.hits);
+    assert_eq!(3, stats.misses);
+
+    tracing::info!("--- upsert kv");
+    client
+        .upsert_kv(UpsertKVAction::new(
+            "foo",
+            MatchSeq::Any,
+            Operation::Update(b"bar".to_vec()),
+            None,
+        ))
+        .await?;
+
+    tracing::info!("--- restart metasrv");
+    {
+        let mut srv = tc.grpc_srv.take().unwrap();
+        srv.stop(None).await?;
+
+        tokio::time::sleep(Duration::from_millis(1000)).await;
+
+        crate::tests::start_metasrv_with_context(&mut tc).await?;
+    }
+
+    tokio::time::sleep(Duration::from_millis(10_000)).await;
+
+    tracing::info!("--- get kv with the pooled client");
+    {
+        let client = pool.get(&client_conf(&addr, "xxx"));
+        let res = client.get_kv("foo").await?;
+        assert_eq!(Some(b"bar".to_vec()), res.map(|x| x.data));
+    }
+
+    let stats = pool.stats();
+    assert_eq!(3, stats.clients);
+    assert_eq!(3, stats.misses);
+
+    Ok(())
+}
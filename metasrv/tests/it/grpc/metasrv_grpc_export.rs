@@ -13,14 +13,19 @@
 // limitations under the License.
 
 use common_base::tokio;
+use common_exception::ErrorCode;
 use common_meta_api::KVApi;
 use common_meta_grpc::MetaGrpcClient;
 use common_meta_types::protobuf::Empty;
+use common_meta_types::ImportMode;
+use common_meta_types::KVMeta;
 use common_meta_types::MatchSeq;
 use common_meta_types::Operation;
 use common_meta_types::UpsertKVAction;
+use common_meta_types::EXPORT_KV_CHUNK_MAX_BYTES;
 use common_tracing::tracing;
 use common_tracing::tracing::Instrument;
+use futures::TryStreamExt;
 use regex::Regex;
 use tokio_stream::StreamExt;
 
@@ -110,3 +115,135 @@ async fn test_export() -> anyhow::Result<()> {
     .instrument(ut_span)
     .await
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn test_export_import_prefix() -> anyhow::Result<()> {
+    // - Write some data with and without the prefix, including a value larger than a chunk.
+    // - Export the prefix, wipe it and import the exported entries.
+    // - Check the imported values and the conflict handling of each mode.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+
+    async {
+        let (_tc, addr) = crate::tests::start_metasrv().await?;
+
+        let client = MetaGrpcClient::try_create(addr.as_str(), "root", "xxx", None, None).await?;
+
+        let large = (0..EXPORT_KV_CHUNK_MAX_BYTES * 2 + 100)
+            .map(|i| (i % 251) as u8)
+            .collect::<Vec<_>>();
+        let meta = Some(KVMeta {
+            expire_at: Some(4_000_000_000),
+        });
+
+        tracing::info!("--- upsert kv");
+        {
+            let kvs = [
+                ("t1/a", b"a".to_vec(), None),
+                ("t1/b", large.clone(), None),
+                ("t1/c", vec![], meta.clone()),
+                ("t2/a", b"other".to_vec(), None),
+            ];
+            for (k, v, m) in kvs {
+                client
+                    .upsert_kv(UpsertKVAction::new(
+                        k,
+                        MatchSeq::Any,
+                        Operation::Update(v),
+                        m,
+                    ))
+                    .await?;
+            }
+        }
+
+        tracing::info!("--- export t1/");
+        let exported = client
+            .export_prefix("t1/")
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        assert_eq!(
+            vec!["t1/a", "t1/b", "t1/c"],
+            exported.iter().map(|e| e.key.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(large, exported[1].value);
+        assert_eq!(meta, exported[2].meta);
+        let old_seqs = exported.iter().map(|e| e.seq).collect::<Vec<_>>();
+
+        let delete = |k: &str| UpsertKVAction::new(k, MatchSeq::Any, Operation::Delete, None);
+
+        tracing::info!("--- wipe and import");
+        {
+            for e in &exported {
+                client.upsert_kv(delete(&e.key)).await?;
+            }
+
+            let report = client
+                .import_entries(exported.clone(), ImportMode::FailOnConflict)
+                .await?;
+            assert_eq!(3, report.imported);
+
+            for (e, old_seq) in exported.iter().zip(old_seqs) {
+                let got = client.get_kv(&e.key).await?.unwrap();
+                assert_eq!(e.value, got.data, "{}", e.key);
+                assert_eq!(e.meta, got.meta, "{}", e.key);
+                assert!(got.seq > old_seq, "a new seq is assigned to {}", e.key);
+            }
+
+            let got = client.get_kv("t2/a").await?.unwrap();
+            assert_eq!(b"other".to_vec(), got.data);
+        }
+
+        // t1/a is changed, t1/b is removed.
+        client
+            .upsert_kv(UpsertKVAction::new(
+                "t1/a",
+                MatchSeq::Any,
+                Operation::Update(b"changed".to_vec()),
+                None,
+            ))
+            .await?;
+        client.upsert_kv(delete("t1/b")).await?;
+
+        tracing::info!("--- fail on conflict: nothing is imported");
+        {
+            let res = client
+                .import_entries(exported.clone(), ImportMode::FailOnConflict)
+                .await;
+            let err = ErrorCode::from(res.unwrap_err());
+            assert_eq!(ErrorCode::KVSeqMismatched("").code(), err.code());
+
+            assert!(client.get_kv("t1/b").await?.is_none());
+        }
+
+        tracing::info!("--- skip existing");
+        {
+            let report = client
+                .import_entries(exported.clone(), ImportMode::SkipExisting)
+                .await?;
+            assert_eq!(1, report.imported);
+            assert_eq!(2, report.skipped);
+
+            let got = client.get_kv("t1/a").await?.unwrap();
+            assert_eq!(b"changed".to_vec(), got.data);
+            let got = client.get_kv("t1/b").await?.unwrap();
+            assert_eq!(large, got.data);
+        }
+
+        tracing::info!("--- overwrite");
+        {
+            let report = client
+                .import_entries(exported.clone(), ImportMode::Overwrite)
+                .await?;
+            assert_eq!(3, report.imported);
+            assert_eq!(0, report.skipped);
+
+            let got = client.get_kv("t1/a").await?.unwrap();
+            assert_eq!(b"a".to_vec(), got.data);
+        }
+
+        Ok(())
+    }
+    .instrument(ut_span)
+    .await
+}
@@ -17,15 +17,28 @@ use std::sync::Mutex;
 
 use async_trait::async_trait;
 use common_base::tokio;
+use common_meta_api::KVApi;
 use common_meta_api::KVApiBuilder;
 use common_meta_api::KVApiTestSuite;
 use common_meta_grpc::MetaGrpcClient;
+use common_meta_grpc::MetaGrpcWriteReq;
+use common_meta_types::IncrKVReply;
+use common_meta_types::IncrKVReq;
+use common_meta_types::MatchSeq;
+use common_meta_types::MetaError;
+use common_meta_types::Operation;
+use common_meta_types::UpsertKVAction;
+use common_meta_types::TXID_CLIENT_KEY;
+use common_meta_types::TXID_SERIAL_KEY;
 use common_tracing::tracing_futures::Instrument;
+use tonic::metadata::MetadataValue;
+use tonic::IntoRequest;
 
 use crate::init_meta_ut;
 use crate::tests::service::start_metasrv_cluster;
 use crate::tests::service::MetaSrvTestContext;
 use crate::tests::start_metasrv;
+use crate::tests::start_metasrv_with_context;
 
 struct Builder {
     pub test_contexts: Arc<Mutex<Vec<MetaSrvTestContext>>>,
@@ -78,3 +91,105 @@ async fn test_metasrv_kv_api() -> anyhow::Result<()> {
         .instrument(ut_span)
         .await
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn test_metasrv_kv_history() -> anyhow::Result<()> {
+    // - Start a metasrv keeping a history of depth 3 for the keys under `h/`.
+    // - Write a key 5 times, the last 3 versions are read back, newest first.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+
+    async {
+        let mut tc = MetaSrvTestContext::new(0);
+        tc.config.raft_config.kv_history_prefixes = vec!["h/".to_string()];
+        tc.config.raft_config.kv_history_depth = 3;
+        start_metasrv_with_context(&mut tc).await?;
+
+        let client = tc.grpc_client().await?;
+
+        let mut seqs = vec![];
+        for i in 1..=5 {
+            let res = client
+                .upsert_kv(UpsertKVAction::new(
+                    "h/t1",
+                    MatchSeq::Any,
+                    Operation::Update(format!("v{}", i).into_bytes()),
+                    None,
+                ))
+                .await?;
+            seqs.push(res.result.unwrap().seq);
+        }
+
+        let got = client.get_kv_history("h/t1", 10).await?;
+        assert_eq!(
+            vec![
+                (seqs[4], b"v5".to_vec()),
+                (seqs[3], b"v4".to_vec()),
+                (seqs[2], b"v3".to_vec()),
+            ],
+            got.into_iter().map(|v| (v.seq, v.data)).collect::<Vec<_>>()
+        );
+
+        // A key not covered keeps no history.
+        client
+            .upsert_kv(UpsertKVAction::new(
+                "t1",
+                MatchSeq::Any,
+                Operation::Update(b"v1".to_vec()),
+                None,
+            ))
+            .await?;
+        assert!(client.get_kv_history("t1", 10).await?.is_empty());
+
+        Ok(())
+    }
+    .instrument(ut_span)
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn test_metasrv_write_with_txid() -> anyhow::Result<()> {
+    // - Send an incr twice with the same txid, as a retry does: it is applied once and both
+    //   replies are the same.
+    // - An incr with another txid is applied.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+
+    async {
+        let (_tc, addr) = start_metasrv().await?;
+        let client = MetaGrpcClient::try_create(addr.as_str(), "root", "xxx", None, None).await?;
+
+        let send = |serial: u64| {
+            let client = &client;
+            async move {
+                let act = MetaGrpcWriteReq::IncrKV(IncrKVReq {
+                    key: "counter".to_string(),
+                    delta: 1,
+                    seq: MatchSeq::Any,
+                });
+                let mut req = act.into_request();
+                let metadata = req.metadata_mut();
+                metadata.insert(TXID_CLIENT_KEY, MetadataValue::from_static("c1"));
+                metadata.insert(TXID_SERIAL_KEY, MetadataValue::from(serial));
+
+                let mut grpc = client.make_client().await?;
+                let reply = grpc.write_msg(req).await?.into_inner();
+                Ok::<_, anyhow::Error>(reply)
+            }
+        };
+
+        let first = send(1).await?;
+        let retried = send(1).await?;
+        assert_eq!(first, retried);
+
+        let res: Result<IncrKVReply, MetaError> = retried.into();
+        assert_eq!(1, res?.1, "applied once");
+
+        let res: Result<IncrKVReply, MetaError> = send(2).await?.into();
+        assert_eq!(2, res?.1);
+
+        Ok(())
+    }
+    .instrument(ut_span)
+    .await
+}
@@ -0,0 +1,160 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::tokio;
+use common_meta_api::KVApi;
+use common_meta_grpc::is_compressed_value;
+use common_meta_grpc::MetaGrpcClient;
+use common_meta_types::MatchSeq;
+use common_meta_types::Operation;
+use common_meta_types::UpsertKVAction;
+use common_tracing::tracing;
+use common_tracing::tracing::Instrument;
+use futures::TryStreamExt;
+
+use crate::init_meta_ut;
+
+/// A json-like value that compresses well.
+fn compressible(size: usize) -> Vec<u8> {
+    let mut v = vec![];
+    let mut i = 0;
+    while v.len() < size {
+        v.extend_from_slice(format!(r#"{{"segment":"_b/{}","rows":1024}},"#, i).as_bytes());
+        i += 1;
+    }
+    v
+}
+
+/// Pseudo random bytes that do not compress.
+fn incompressible(size: usize) -> Vec<u8> {
+    let mut x = 0x2545_f491_4f6c_dd1d_u64;
+    (0..size)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            (x >> 24) as u8
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn test_kv_value_compression() -> anyhow::Result<()> {
+    // - Write a compressible, an incompressible and a small value with compression enabled.
+    // - Read them back by every kind of read, with and without compression enabled.
+    // - Check the stored values by exporting them.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+
+    async {
+        let (_tc, addr) = crate::tests::start_metasrv().await?;
+
+        let plain = MetaGrpcClient::try_create(addr.as_str(), "root", "xxx", None, None).await?;
+        let client = MetaGrpcClient::try_create(addr.as_str(), "root", "xxx", None, None)
+            .await?
+            .with_value_compression(1024);
+
+        let kvs = vec![
+            ("c/large".to_string(), compressible(200 * 1024)),
+            ("c/random".to_string(), incompressible(64 * 1024)),
+            ("c/small".to_string(), b"small".to_vec()),
+        ];
+
+        tracing::info!("--- upsert with compression");
+        {
+            let (k, v) = &kvs[0];
+            let res = client
+                .upsert_kv(UpsertKVAction::new(
+                    k,
+                    MatchSeq::Any,
+                    Operation::Update(v.clone()),
+                    None,
+                ))
+                .await?;
+            assert_eq!(Some(v), res.result.as_ref().map(|x| &x.data));
+
+            let actions = kvs[1..]
+                .iter()
+                .map(|(k, v)| {
+                    UpsertKVAction::new(k, MatchSeq::Any, Operation::Update(v.clone()), None)
+                })
+                .collect();
+            client.upsert_kv_batch(actions).await?;
+        }
+
+        tracing::info!("--- read back");
+        {
+            let keys = kvs.iter().map(|(k, _)| k.clone()).collect::<Vec<_>>();
+            let want = kvs.iter().map(|(_, v)| v.clone()).collect::<Vec<_>>();
+
+            for c in [&client, &plain] {
+                for (k, v) in &kvs {
+                    let got = c.get_kv(k).await?.unwrap();
+                    assert_eq!(v, &got.data, "{}", k);
+                }
+
+                let got = c.mget_kv(&keys).await?;
+                let got = got.into_iter().map(|x| x.unwrap().data).collect::<Vec<_>>();
+                assert_eq!(want, got);
+
+                let got = c.prefix_list_kv("c/").await?;
+                assert_eq!(keys, got.iter().map(|(k, _)| k.clone()).collect::<Vec<_>>());
+                assert_eq!(
+                    want,
+                    got.into_iter().map(|(_, v)| v.data).collect::<Vec<_>>()
+                );
+            }
+        }
+
+        tracing::info!("--- stored values");
+        {
+            let exported = client
+                .export_prefix("c/")
+                .await?
+                .try_collect::<Vec<_>>()
+                .await?;
+
+            let large = &exported[0].value;
+            assert!(is_compressed_value(large));
+            assert!(
+                large.len() < kvs[0].1.len() / 10,
+                "stored {} bytes",
+                large.len()
+            );
+
+            // The incompressible and the small ones are stored as is.
+            assert_eq!(kvs[1].1, exported[1].value);
+            assert_eq!(kvs[2].1, exported[2].value);
+        }
+
+        tracing::info!("--- an uncompressed value written before is read as is");
+        {
+            let v = compressible(4096);
+            plain
+                .upsert_kv(UpsertKVAction::new(
+                    "c/old",
+                    MatchSeq::Any,
+                    Operation::Update(v.clone()),
+                    None,
+                ))
+                .await?;
+            let got = client.get_kv("c/old").await?.unwrap();
+            assert_eq!(v, got.data);
+        }
+
+        Ok(())
+    }
+    .instrument(ut_span)
+    .await
+}
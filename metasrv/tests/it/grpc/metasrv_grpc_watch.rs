@@ -22,6 +22,8 @@ use common_meta_types::protobuf::WatchRequest;
 use common_meta_types::MatchSeq;
 use common_meta_types::Operation;
 use common_meta_types::UpsertKVAction;
+use common_meta_types::WatchEvent;
+use futures::StreamExt;
 
 use crate::init_meta_ut;
 
@@ -88,6 +90,7 @@ async fn test_watch() -> anyhow::Result<()> {
             key: "a".to_string(),
             key_end: Some("z".to_string()),
             filter_type: FilterType::All.into(),
+            from_seq: None,
         };
 
         let key_a = "a".to_string();
@@ -150,7 +153,8 @@ async fn test_watch() -> anyhow::Result<()> {
             },
         ];
 
-        seq += 3;
+        // a deletion takes a seq too
+        seq += 4;
         // update kv
         let updates = vec![
             UpsertKVAction::new("a", MatchSeq::Any, Operation::Update(val_a), None),
@@ -170,6 +174,7 @@ async fn test_watch() -> anyhow::Result<()> {
             key_end: None,
             // filter only delete events
             filter_type: FilterType::Delete.into(),
+            from_seq: None,
         };
 
         let key = key_str.to_string();
@@ -191,7 +196,7 @@ async fn test_watch() -> anyhow::Result<()> {
             Event {
                 key: key.clone(),
                 prev: Some(SeqV {
-                    seq: seq + 2,
+                    seq: seq + 3,
                     data: val_new.clone(),
                 }),
                 current: None,
@@ -210,3 +215,94 @@ async fn test_watch() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn test_watch_prefix_resume() -> anyhow::Result<()> {
+    // - Watch a prefix with KVApi::watch.
+    // - Upsert and delete keys from another client.
+    // - Assert the events under the prefix arrive in order.
+    // - Resume the watch with from_seq, assert the missed events are replayed.
+
+    let (_log_guards, ut_span) = init_meta_ut!();
+    let _ent = ut_span.enter();
+    let (_tc, addr) = crate::tests::start_metasrv().await?;
+
+    let watcher = MetaGrpcClient::try_create(addr.as_str(), "root", "xxx", None, None).await?;
+    let writer = MetaGrpcClient::try_create(addr.as_str(), "root", "xxx", None, None).await?;
+
+    let upsert =
+        |key: &str, op: Operation<Vec<u8>>| UpsertKVAction::new(key, MatchSeq::Any, op, None);
+
+    let mut strm = watcher.watch("w/", None).await?;
+
+    writer
+        .upsert_kv(upsert("w/a", Operation::Update(b"a".to_vec())))
+        .await?;
+    writer
+        .upsert_kv(upsert("other/a", Operation::Update(b"x".to_vec())))
+        .await?;
+    writer
+        .upsert_kv(upsert("w/b", Operation::Update(b"b".to_vec())))
+        .await?;
+    writer.upsert_kv(upsert("w/a", Operation::Delete)).await?;
+
+    let mut got: Vec<WatchEvent> = vec![];
+    while got.len() < 3 {
+        got.push(strm.next().await.unwrap()?);
+    }
+
+    let summary = got
+        .iter()
+        .map(|ev| {
+            (
+                ev.key.as_str(),
+                ev.prev.as_ref().map(|v| v.data.clone()),
+                ev.current.as_ref().map(|v| v.data.clone()),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            ("w/a", None, Some(b"a".to_vec())),
+            ("w/b", None, Some(b"b".to_vec())),
+            ("w/a", Some(b"a".to_vec()), None),
+        ],
+        summary
+    );
+    assert!(got.windows(2).all(|w| w[0].seq < w[1].seq));
+    assert_eq!(got[0].current.as_ref().map(|v| v.seq), Some(got[0].seq));
+
+    // Disconnect, and miss some changes.
+    drop(strm);
+    let last_seq = got.last().unwrap().seq;
+
+    writer
+        .upsert_kv(upsert("w/c", Operation::Update(b"c".to_vec())))
+        .await?;
+    writer.upsert_kv(upsert("w/b", Operation::Delete)).await?;
+
+    let mut strm = watcher.watch("w/", Some(last_seq + 1)).await?;
+
+    let ev = strm.next().await.unwrap()?;
+    assert_eq!("w/c", ev.key);
+    assert_eq!(None, ev.prev);
+    assert_eq!(Some(b"c".to_vec()), ev.current.map(|v| v.data));
+
+    let ev = strm.next().await.unwrap()?;
+    assert_eq!("w/b", ev.key);
+    assert_eq!(None, ev.current);
+
+    // The resumed watch keeps receiving new changes.
+    writer
+        .upsert_kv(upsert("w/d", Operation::Update(b"d".to_vec())))
+        .await?;
+    let ev = strm.next().await.unwrap()?;
+    assert_eq!("w/d", ev.key);
+
+    // A seq not reached yet can not be proven to miss nothing.
+    let mut strm = watcher.watch("w/", Some(ev.seq + 100)).await?;
+    let res = strm.next().await.unwrap();
+    assert!(res.is_err(), "out of range: {:?}", res);
+
+    Ok(())
+}
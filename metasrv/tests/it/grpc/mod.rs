@@ -13,9 +13,11 @@
 // limitations under the License.
 
 pub mod metasrv_grpc_api;
+pub mod metasrv_grpc_client_pool;
 mod metasrv_grpc_export;
 pub mod metasrv_grpc_kv_api;
 pub mod metasrv_grpc_kv_api_restart_cluster;
+mod metasrv_grpc_kv_compression;
 pub mod metasrv_grpc_meta_api;
 pub mod metasrv_grpc_meta_api_follower_follower;
 pub mod metasrv_grpc_meta_api_leader_follower;
@@ -818,6 +818,7 @@ fn join_req(node_id: NodeId, endpoint: Endpoint, forward: u64) -> ForwardRequest
     ForwardRequest {
         forward_to_leader: forward,
         body: ForwardRequestBody::Join(JoinRequest { node_id, endpoint }),
+        consistency: Default::default(),
     }
 }
 
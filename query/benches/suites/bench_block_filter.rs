@@ -0,0 +1,108 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common_base::tokio;
+use common_datavalues::DataValue;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use databend_query::storages::fuse::meta::BlockMeta;
+use databend_query::storages::fuse::meta::Compression;
+use databend_query::storages::fuse::meta::SegmentInfo;
+use databend_query::storages::fuse::pruning::BlockPruner;
+use databend_query::storages::fuse::pruning::Pred;
+use databend_query::storages::index::BlockStatistics;
+use databend_query::storages::index::ColumnStatistics;
+
+// a segment of `num_blocks` blocks of `num_columns` columns
+fn synthetic_segment(num_columns: u32, num_blocks: u64) -> SegmentInfo {
+    let blocks = (0..num_blocks)
+        .map(|i| {
+            let col_stats = (0..num_columns)
+                .map(|id| {
+                    (id, ColumnStatistics {
+                        min: DataValue::Int64((i % 1000) as i64),
+                        max: DataValue::Int64((i % 1000) as i64 + id as i64),
+                        null_count: 0,
+                        in_memory_size: 1024,
+                        min_max_skipped: false,
+                        histogram: None,
+                    })
+                })
+                .collect::<HashMap<_, _>>();
+            BlockMeta {
+                row_count: 100,
+                block_size: 1024 * num_columns as u64,
+                file_size: 1024 * num_columns as u64,
+                col_stats,
+                col_metas: HashMap::new(),
+                location: (format!("_b/{}.parquet", i), 0),
+                compression: Compression::Lz4Raw,
+                external: false,
+            }
+        })
+        .collect();
+    SegmentInfo::new(blocks, Default::default())
+}
+
+// a predicate of a conjunction on all the columns, like `c0 > 10 AND c1 > 10 AND ...`
+fn conjunctive_pred(num_columns: u32) -> Arc<Pred> {
+    Arc::new(Box::new(move |stats: &BlockStatistics, _: u64| {
+        for id in 0..num_columns {
+            match &stats[&id].max {
+                DataValue::Int64(v) if *v > 10 => continue,
+                _ => return Ok(false),
+            }
+        }
+        Ok(true)
+    }))
+}
+
+fn criterion_benchmark_block_filter(c: &mut Criterion) {
+    let num_columns = 16;
+    let segment_info = Arc::new(synthetic_segment(num_columns, 50_000));
+    let pred = conjunctive_pred(num_columns);
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    let mut group = c.benchmark_group("filter blocks of a segment of 50k blocks");
+    for parallelism in [1, 2, 4, 8] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(parallelism),
+            &parallelism,
+            |b, parallelism| {
+                b.iter(|| {
+                    runtime
+                        .block_on(BlockPruner::filter_blocks(
+                            runtime.handle(),
+                            segment_info.clone(),
+                            pred.clone(),
+                            *parallelism,
+                        ))
+                        .unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark_block_filter);
+criterion_main!(benches);
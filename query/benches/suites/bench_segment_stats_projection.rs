@@ -0,0 +1,87 @@
+// Copyright 2021 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use common_datavalues::DataValue;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::Criterion;
+use databend_query::storages::fuse::meta::BlockMeta;
+use databend_query::storages::fuse::meta::ColumnMeta;
+use databend_query::storages::fuse::meta::Compression;
+use databend_query::storages::fuse::meta::Projected;
+use databend_query::storages::fuse::meta::SegmentInfo;
+use databend_query::storages::index::ColumnStatistics;
+use serde::de::DeserializeSeed;
+
+// serialized segment of a table of `num_columns` columns
+fn wide_segment(num_columns: u32, num_blocks: u64) -> Vec<u8> {
+    let col_stats = (0..num_columns)
+        .map(|id| {
+            (id, ColumnStatistics {
+                min: DataValue::Int64(0),
+                max: DataValue::Int64(id as i64),
+                null_count: 0,
+                in_memory_size: 1024,
+                min_max_skipped: false,
+                histogram: None,
+            })
+        })
+        .collect::<HashMap<_, _>>();
+    let col_metas = (0..num_columns)
+        .map(|id| {
+            (id, ColumnMeta {
+                offset: id as u64 * 1024,
+                len: 1024,
+                num_values: 100,
+            })
+        })
+        .collect::<HashMap<_, _>>();
+    let blocks = (0..num_blocks)
+        .map(|i| BlockMeta {
+            row_count: 100,
+            block_size: 1024 * num_columns as u64,
+            file_size: 1024 * num_columns as u64,
+            col_stats: col_stats.clone(),
+            col_metas: col_metas.clone(),
+            location: (format!("_b/{}.parquet", i), 0),
+            compression: Compression::Lz4Raw,
+            external: false,
+        })
+        .collect();
+    let segment = SegmentInfo::new(blocks, Default::default());
+    serde_json::to_vec(&segment).unwrap()
+}
+
+fn criterion_benchmark_segment_stats_projection(c: &mut Criterion) {
+    let bytes = wide_segment(300, 1000);
+    let projection = HashSet::from([0]);
+
+    c.bench_function("read segment, all column stats", |b| {
+        b.iter(|| serde_json::from_slice::<SegmentInfo>(&bytes).unwrap())
+    });
+    c.bench_function("read segment, column stats of one column", |b| {
+        b.iter(|| {
+            let mut deserializer = serde_json::Deserializer::from_slice(&bytes);
+            Projected::<SegmentInfo>::new(&projection)
+                .deserialize(&mut deserializer)
+                .unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, criterion_benchmark_segment_stats_projection);
+criterion_main!(benches);
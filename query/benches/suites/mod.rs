@@ -24,8 +24,10 @@ use databend_query::sql::PlanParser;
 use futures::StreamExt;
 
 pub mod bench_aggregate_query_sql;
+pub mod bench_block_filter;
 pub mod bench_filter_query_sql;
 pub mod bench_limit_query_sql;
+pub mod bench_segment_stats_projection;
 pub mod bench_sort_query_sql;
 
 pub async fn select_executor(sql: &str) -> Result<()> {
@@ -12,18 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::ops::RangeInclusive;
-use std::sync::atomic::AtomicBool;
-use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
 
 use common_arrow::arrow_format::flight::service::flight_service_client::FlightServiceClient;
-use common_base::tokio;
 use common_base::tokio::sync::Mutex;
-use common_base::tokio::sync::Notify;
-use common_base::tokio::task::JoinHandle;
-use common_base::tokio::time::sleep as tokio_async_sleep;
 use common_base::DummySignalStream;
 use common_base::GlobalUniqName;
 use common_base::SignalStream;
@@ -33,15 +26,13 @@ use common_exception::Result;
 use common_grpc::ConnectionFactory;
 use common_management::ClusterApi;
 use common_management::ClusterMgr;
+use common_management::ClusterNodeCache;
+use common_management::NodeHeartbeat;
 use common_meta_api::KVApi;
 use common_meta_types::NodeInfo;
 use common_tracing::tracing;
-use futures::future::select;
 use futures::future::Either;
-use futures::Future;
 use futures::StreamExt;
-use rand::thread_rng;
-use rand::Rng;
 
 use crate::api::FlightClient;
 use crate::common::MetaClientProvider;
@@ -49,7 +40,9 @@ use crate::configs::Config;
 
 pub struct ClusterDiscovery {
     local_id: String,
-    heartbeat: Mutex<ClusterHeartbeat>,
+    lift_time: Duration,
+    heartbeat: Mutex<Option<NodeHeartbeat>>,
+    node_cache: ClusterNodeCache,
     api_provider: Arc<dyn ClusterApi>,
 }
 
@@ -65,31 +58,39 @@ impl ClusterDiscovery {
     pub async fn create_global(cfg: Config) -> Result<Arc<ClusterDiscovery>> {
         let local_id = GlobalUniqName::unique();
         let meta_client = ClusterDiscovery::create_meta_client(&cfg).await?;
-        let (lift_time, provider) = Self::create_provider(&cfg, meta_client)?;
+        let (lift_time, cluster_manager) = Self::create_provider(&cfg, meta_client)?;
+        let node_cache = match cluster_manager.start_node_cache().await {
+            Ok(node_cache) => node_cache,
+            Err(cause) => return Err(cause.add_message_back("(while start node cache).")),
+        };
 
         Ok(Arc::new(ClusterDiscovery {
-            local_id: local_id.clone(),
-            api_provider: provider.clone(),
-            heartbeat: Mutex::new(ClusterHeartbeat::create(lift_time, provider)),
+            local_id,
+            lift_time,
+            heartbeat: Mutex::new(None),
+            node_cache,
+            api_provider: Arc::new(cluster_manager),
         }))
     }
 
-    fn create_provider(
-        cfg: &Config,
-        api: Arc<dyn KVApi>,
-    ) -> Result<(Duration, Arc<dyn ClusterApi>)> {
+    fn create_provider(cfg: &Config, api: Arc<dyn KVApi>) -> Result<(Duration, ClusterMgr)> {
         // TODO: generate if tenant or cluster id is empty
         let tenant_id = &cfg.query.tenant_id;
         let cluster_id = &cfg.query.cluster_id;
         let lift_time = Duration::from_secs(60);
         let cluster_manager = ClusterMgr::create(api, tenant_id, cluster_id, lift_time)?;
 
-        Ok((lift_time, Arc::new(cluster_manager)))
+        Ok((lift_time, cluster_manager))
+    }
+
+    /// The id of this node in the cluster.
+    pub fn local_id(&self) -> String {
+        self.local_id.clone()
     }
 
     pub async fn discover(&self) -> Result<Arc<Cluster>> {
-        match self.api_provider.get_nodes().await {
-            Err(cause) => Err(cause.add_message_back("(while cluster api get_nodes).")),
+        match self.node_cache.get_nodes().await {
+            Err(cause) => Err(cause.add_message_back("(while node cache get_nodes).")),
             Ok(cluster_nodes) => {
                 let mut res = Vec::with_capacity(cluster_nodes.len());
 
@@ -103,7 +104,7 @@ impl ClusterDiscovery {
     }
 
     async fn drop_invalid_nodes(self: &Arc<Self>, node_info: &NodeInfo) -> Result<()> {
-        let current_nodes_info = match self.api_provider.get_nodes().await {
+        let current_nodes_info = match self.node_cache.get_nodes().await {
             Ok(nodes) => nodes,
             Err(cause) => {
                 return Err(cause.add_message_back("(while drop_invalid_nodes)"));
@@ -125,13 +126,8 @@ impl ClusterDiscovery {
     }
 
     pub async fn unregister_to_metastore(self: &Arc<Self>, signal: &mut SignalStream) {
-        let mut heartbeat = self.heartbeat.lock().await;
-
-        if let Err(shutdown_failure) = heartbeat.shutdown().await {
-            tracing::warn!(
-                "Cannot shutdown cluster heartbeat, cause {:?}",
-                shutdown_failure
-            );
+        if let Some(heartbeat) = self.heartbeat.lock().await.take() {
+            heartbeat.stop().await;
         }
 
         let mut mut_signal_pin = signal.as_mut();
@@ -169,8 +165,10 @@ impl ClusterDiscovery {
     }
 
     async fn start_heartbeat(self: &Arc<Self>, node_info: NodeInfo) -> Result<()> {
-        let mut heartbeat = self.heartbeat.lock().await;
-        heartbeat.start(node_info);
+        // Well below the lift time, so that a failed heartbeat is retried before the node expires.
+        let interval = self.lift_time / 3;
+        let heartbeat = NodeHeartbeat::start(self.api_provider.clone(), node_info, interval)?;
+        *self.heartbeat.lock().await = Some(heartbeat);
         Ok(())
     }
 }
@@ -236,78 +234,3 @@ impl Cluster {
         self.nodes.to_vec()
     }
 }
-
-struct ClusterHeartbeat {
-    timeout: Duration,
-    shutdown: Arc<AtomicBool>,
-    shutdown_notify: Arc<Notify>,
-    cluster_api: Arc<dyn ClusterApi>,
-    shutdown_handler: Option<JoinHandle<()>>,
-}
-
-impl ClusterHeartbeat {
-    pub fn create(timeout: Duration, cluster_api: Arc<dyn ClusterApi>) -> ClusterHeartbeat {
-        ClusterHeartbeat {
-            timeout,
-            cluster_api,
-            shutdown: Arc::new(AtomicBool::new(false)),
-            shutdown_notify: Arc::new(Notify::new()),
-            shutdown_handler: None,
-        }
-    }
-
-    fn heartbeat_loop(&self, node: NodeInfo) -> impl Future<Output = ()> + 'static {
-        let shutdown = self.shutdown.clone();
-        let shutdown_notify = self.shutdown_notify.clone();
-        let cluster_api = self.cluster_api.clone();
-        let sleep_range = self.heartbeat_interval(self.timeout);
-
-        async move {
-            let mut shutdown_notified = Box::pin(shutdown_notify.notified());
-
-            while !shutdown.load(Ordering::Relaxed) {
-                let mills = {
-                    let mut rng = thread_rng();
-                    rng.gen_range(sleep_range.clone())
-                };
-
-                let sleep = tokio_async_sleep(Duration::from_millis(mills as u64));
-
-                match select(shutdown_notified, Box::pin(sleep)).await {
-                    Either::Left((_, _)) => {
-                        break;
-                    }
-                    Either::Right((_, new_shutdown_notified)) => {
-                        shutdown_notified = new_shutdown_notified;
-                        let heartbeat = cluster_api.heartbeat(&node, None);
-                        if let Err(failure) = heartbeat.await {
-                            tracing::error!("Cluster cluster api heartbeat failure: {:?}", failure);
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    fn heartbeat_interval(&self, duration: Duration) -> RangeInclusive<u128> {
-        (duration / 3).as_millis()..=((duration / 3) * 2).as_millis()
-    }
-
-    pub fn start(&mut self, node_info: NodeInfo) {
-        self.shutdown_handler = Some(tokio::spawn(self.heartbeat_loop(node_info)));
-    }
-
-    pub async fn shutdown(&mut self) -> Result<()> {
-        if let Some(shutdown_handler) = self.shutdown_handler.take() {
-            self.shutdown.store(true, Ordering::Relaxed);
-            self.shutdown_notify.notify_waiters();
-            if let Err(shutdown_failure) = shutdown_handler.await {
-                return Err(ErrorCode::TokioError(format!(
-                    "Cannot shutdown cluster heartbeat, cause {:?}",
-                    shutdown_failure
-                )));
-            }
-        }
-        Ok(())
-    }
-}
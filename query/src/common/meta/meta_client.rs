@@ -18,6 +18,7 @@ use std::sync::Arc;
 
 use common_exception::Result;
 use common_meta_api::KVApi;
+use common_meta_grpc::MetaClientPool;
 use common_meta_grpc::MetaGrpcClient;
 use common_meta_grpc::MetaGrpcClientConf;
 
@@ -37,11 +38,12 @@ impl MetaClientProvider {
     }
 
     /// Get meta async client, trait is defined in MetaApi.
+    ///
+    /// The client is shared with the other providers of the same endpoint and credential.
     pub async fn try_get_meta_client(
         &self,
     ) -> std::result::Result<Arc<MetaGrpcClient>, Infallible> {
-        let client = MetaGrpcClient::try_new(&self.grpc_conf).await?;
-        Ok(Arc::new(client))
+        Ok(MetaClientPool::global().get(&self.grpc_conf))
     }
 
     /// Get kv async client, operations trait defined in KVApi.
@@ -51,8 +53,7 @@ impl MetaClientProvider {
             let meta_store = common_meta_embedded::MetaEmbedded::get_meta().await?;
             Ok(meta_store)
         } else {
-            let client = MetaGrpcClient::try_new(&self.grpc_conf).await?;
-            Ok(Arc::new(client))
+            Ok(MetaClientPool::global().get(&self.grpc_conf))
         }
     }
 }
@@ -18,6 +18,7 @@ use clap::Args;
 use common_grpc::RpcClientConf;
 use common_grpc::RpcClientTlsConfig;
 use common_meta_grpc::MetaGrpcClientConf;
+use common_meta_grpc::RetryConfig;
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -55,6 +56,13 @@ pub struct MetaConfig {
     )]
     pub meta_client_timeout_in_second: u64,
 
+    #[clap(
+        long,
+        default_value = "0",
+        help = "Compress a value written to MetaStore of at least this many bytes, 0 to disable. Enable it only after all the query nodes support compression"
+    )]
+    pub meta_compress_value_threshold: u64,
+
     #[clap(
         long,
         env = "META_RPC_TLS_SERVER_ROOT_CA_CERT",
@@ -79,6 +87,7 @@ impl Default for MetaConfig {
             meta_username: "root".to_string(),
             meta_password: "".to_string(),
             meta_client_timeout_in_second: 10,
+            meta_compress_value_threshold: 0,
             rpc_tls_meta_server_root_ca_cert: "".to_string(),
             rpc_tls_meta_service_domain_name: "localhost".to_string(),
         }
@@ -134,6 +143,12 @@ impl MetaConfig {
             meta_service_config: meta_config.clone(),
             kv_service_config: meta_config,
             client_timeout_in_second: self.meta_client_timeout_in_second,
+            retry_config: RetryConfig::default(),
+            slow_call_threshold: None,
+            compress_value_threshold: match self.meta_compress_value_threshold {
+                0 => None,
+                n => Some(n as usize),
+            },
         }
     }
 }
@@ -12,6 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
 use clap::Args;
 use serde::Deserialize;
 use serde::Serialize;
@@ -40,6 +44,7 @@ pub const QUERY_TABLE_CACHE_ENABLED: &str = "QUERY_TABLE_CACHE_ENABLED";
 pub const QUERY_TABLE_CACHE_SNAPSHOT_COUNT: &str = "QUERY_TABLE_CACHE_SNAPSHOT_COUNT";
 pub const QUERY_TABLE_CACHE_SEGMENT_COUNT: &str = "QUERY_TABLE_CACHE_SEGMENT_COUNT";
 pub const QUERY_TABLE_CACHE_BLOCK_META_COUNT: &str = "QUERY_TABLE_CACHE_BLOCK_META_COUNT";
+pub const QUERY_TABLE_CACHE_PRUNE_RESULT_MB_SIZE: &str = "QUERY_TABLE_CACHE_PRUNE_RESULT_MB_SIZE";
 pub const QUERY_TABLE_MEMORY_CACHE_MB_SIZE: &str = "QUERY_TABLE_MEMORY_CACHE_MB_SIZE";
 pub const QUERY_TABLE_DISK_CACHE_ROOT: &str = "QUERY_TABLE_DISK_CACHE_ROOT";
 pub const QUERY_TABLE_DISK_CACHE_MB_SIZE: &str = "QUERY_TABLE_DISK_CACHE_MB_SIZE";
@@ -65,6 +70,12 @@ const QUERY_DATABASE_ENGINE_GITHUB_ENABLED: &str = "QUERY_DATABASE_ENGINE_GITHUB
 
 const QUERY_MANAGEMENT_MODE: &str = "QUERY_MANAGEMENT_MODE";
 const QUERY_JWT_KEY_FILE: &str = "QUERY_JWT_KEY_FILE";
+const QUERY_JWT_AUDIENCE: &str = "QUERY_JWT_AUDIENCE";
+const QUERY_JWT_AUTO_CREATE_USER: &str = "QUERY_JWT_AUTO_CREATE_USER";
+const QUERY_BOOTSTRAP_ADMIN_USER: &str = "QUERY_BOOTSTRAP_ADMIN_USER";
+const QUERY_BOOTSTRAP_ADMIN_PASSWORD: &str = "QUERY_BOOTSTRAP_ADMIN_PASSWORD";
+const QUERY_USER_AUDIT_STRICT: &str = "QUERY_USER_AUDIT_STRICT";
+const QUERY_USER_NAME_CASE_INSENSITIVE: &str = "QUERY_USER_NAME_CASE_INSENSITIVE";
 
 /// Query config group.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Args)]
@@ -199,6 +210,10 @@ pub struct QueryConfig {
     #[clap(long, env = QUERY_TABLE_CACHE_BLOCK_META_COUNT, default_value = "102400")]
     pub table_cache_block_meta_count: u64,
 
+    /// Max size of the cached results of pruning (mb)
+    #[clap(long, env = QUERY_TABLE_CACHE_PRUNE_RESULT_MB_SIZE, default_value = "64")]
+    pub table_cache_prune_result_mb_size: u64,
+
     /// Table memory cache size (mb)
     #[clap(long, env = QUERY_TABLE_MEMORY_CACHE_MB_SIZE, default_value = "256")]
     pub table_memory_cache_mb_size: u64,
@@ -215,8 +230,34 @@ pub struct QueryConfig {
     #[clap(long, env = QUERY_MANAGEMENT_MODE)]
     pub management_mode: bool,
 
+    /// The JWKS url, or the path of a PEM encoded public key, to verify the jwt tokens with.
     #[clap(long, env = QUERY_JWT_KEY_FILE, default_value = "")]
     pub jwt_key_file: String,
+
+    /// The audience the jwt tokens must be issued for, not checked if empty.
+    #[clap(long, env = QUERY_JWT_AUDIENCE, default_value = "")]
+    pub jwt_audience: String,
+
+    /// Create the user of a valid jwt token on its first login if the user does not exist.
+    #[clap(long, env = QUERY_JWT_AUTO_CREATE_USER)]
+    pub jwt_auto_create_user: bool,
+
+    /// The admin user created on start if it does not exist, none if empty.
+    #[clap(long, env = QUERY_BOOTSTRAP_ADMIN_USER, default_value = "")]
+    pub bootstrap_admin_user: String,
+
+    /// The password of the bootstrap admin user, required if the user is set.
+    #[clap(long, env = QUERY_BOOTSTRAP_ADMIN_PASSWORD, default_value = "")]
+    pub bootstrap_admin_password: SecretString,
+
+    /// Fail a change of a user if its audit entry is not recorded, rather than logging it.
+    #[clap(long, env = QUERY_USER_AUDIT_STRICT)]
+    pub user_audit_strict: bool,
+
+    /// Match the user names of the MySQL clients case-insensitively, if no user of the exact
+    /// name matches.
+    #[clap(long, env = QUERY_USER_NAME_CASE_INSENSITIVE)]
+    pub user_name_case_insensitive: bool,
 }
 
 impl Default for QueryConfig {
@@ -256,11 +297,18 @@ impl Default for QueryConfig {
             table_cache_snapshot_count: 256,
             table_cache_segment_count: 10240,
             table_cache_block_meta_count: 102400,
+            table_cache_prune_result_mb_size: 64,
             table_memory_cache_mb_size: 256,
             table_disk_cache_root: "_cache".to_string(),
             table_disk_cache_mb_size: 1024,
             management_mode: false,
             jwt_key_file: "".to_string(),
+            jwt_audience: "".to_string(),
+            jwt_auto_create_user: false,
+            bootstrap_admin_user: "".to_string(),
+            bootstrap_admin_password: SecretString::default(),
+            user_audit_strict: false,
+            user_name_case_insensitive: false,
         }
     }
 }
@@ -493,6 +541,13 @@ impl QueryConfig {
             u64,
             QUERY_TABLE_CACHE_BLOCK_META_COUNT
         );
+        env_helper!(
+            mut_config,
+            query,
+            table_cache_prune_result_mb_size,
+            u64,
+            QUERY_TABLE_CACHE_PRUNE_RESULT_MB_SIZE
+        );
         env_helper!(
             mut_config,
             query,
@@ -521,6 +576,83 @@ impl QueryConfig {
             bool,
             QUERY_MANAGEMENT_MODE
         );
-        env_helper!(mut_config, query, management_mode, bool, QUERY_JWT_KEY_FILE);
+        env_helper!(mut_config, query, jwt_key_file, String, QUERY_JWT_KEY_FILE);
+        env_helper!(mut_config, query, jwt_audience, String, QUERY_JWT_AUDIENCE);
+        env_helper!(
+            mut_config,
+            query,
+            jwt_auto_create_user,
+            bool,
+            QUERY_JWT_AUTO_CREATE_USER
+        );
+        env_helper!(
+            mut_config,
+            query,
+            bootstrap_admin_user,
+            String,
+            QUERY_BOOTSTRAP_ADMIN_USER
+        );
+        if let Some(password) = std::env::var_os(QUERY_BOOTSTRAP_ADMIN_PASSWORD) {
+            mut_config.query.bootstrap_admin_password = password
+                .into_string()
+                .expect("cannot convert QUERY_BOOTSTRAP_ADMIN_PASSWORD to string")
+                .into();
+        }
+        env_helper!(
+            mut_config,
+            query,
+            user_audit_strict,
+            bool,
+            QUERY_USER_AUDIT_STRICT
+        );
+        env_helper!(
+            mut_config,
+            query,
+            user_name_case_insensitive,
+            bool,
+            QUERY_USER_NAME_CASE_INSENSITIVE
+        );
+    }
+}
+
+/// A string config which is masked in the debug output, e.g., a password.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(s: String) -> Self {
+        SecretString(s)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(s: &str) -> Self {
+        SecretString(s.to_string())
+    }
+}
+
+impl FromStr for SecretString {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.into())
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // an empty one is shown, to tell it is not set
+        if self.0.is_empty() {
+            write!(f, "\"\"")
+        } else {
+            write!(f, "\"******\"")
+        }
     }
 }
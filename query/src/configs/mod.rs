@@ -26,6 +26,7 @@ pub use config::DATABEND_COMMIT_VERSION;
 pub use config_log::LogConfig;
 pub use config_meta::MetaConfig;
 pub use config_query::QueryConfig;
+pub use config_query::SecretString;
 pub use config_storage::AzureStorageBlobConfig;
 pub use config_storage::FsStorageConfig;
 pub use config_storage::S3StorageConfig;
@@ -17,8 +17,11 @@ use std::sync::Arc;
 use common_datablocks::DataBlock;
 use common_datavalues::prelude::*;
 use common_exception::Result;
+use common_planners::format_plan;
 use common_planners::ExplainPlan;
 use common_planners::ExplainType;
+use common_planners::PlanVisitor;
+use common_planners::ReadDataSourcePlan;
 use common_streams::DataBlockStream;
 use common_streams::SendableDataBlockStream;
 
@@ -28,6 +31,7 @@ use crate::interpreters::InterpreterPtr;
 use crate::optimizers::Optimizers;
 use crate::pipelines::processors::PipelineBuilder;
 use crate::sessions::QueryContext;
+use crate::storages::fuse::FuseTable;
 
 pub struct ExplainInterpreter {
     ctx: Arc<QueryContext>,
@@ -48,8 +52,10 @@ impl Interpreter for ExplainInterpreter {
 
         let block = match self.explain.typ {
             ExplainType::Graph => self.explain_graph(),
-            ExplainType::Syntax => self.explain_syntax(),
+            ExplainType::Syntax => self.explain_syntax(false),
+            ExplainType::Verbose => self.explain_syntax(true),
             ExplainType::Pipeline => self.explain_pipeline(),
+            ExplainType::Prune => self.explain_prune().await,
         }?;
 
         Ok(Box::pin(DataBlockStream::create(schema, None, vec![block])))
@@ -80,14 +86,14 @@ impl ExplainInterpreter {
         Ok(DataBlock::create(schema, vec![formatted_plan]))
     }
 
-    fn explain_syntax(&self) -> Result<DataBlock> {
+    fn explain_syntax(&self, verbose: bool) -> Result<DataBlock> {
         let schema = self.schema();
         let plan = plan_schedulers::apply_plan_rewrite(
             Optimizers::create(self.ctx.clone()),
             &self.explain.input,
         )?;
         let formatted_plan = Series::from_data(
-            format!("{:?}", plan)
+            format_plan(&plan, verbose)
                 .lines()
                 .map(|s| s.as_bytes())
                 .collect::<Vec<_>>(),
@@ -95,6 +101,40 @@ impl ExplainInterpreter {
         Ok(DataBlock::create(schema, vec![formatted_plan]))
     }
 
+    async fn explain_prune(&self) -> Result<DataBlock> {
+        let schema = self.schema();
+        let plan = plan_schedulers::apply_plan_rewrite(
+            Optimizers::create(self.ctx.clone()),
+            &self.explain.input,
+        )?;
+
+        let mut sources = ReadDataSourceCollector { plans: vec![] };
+        sources.visit_plan_node(&plan)?;
+
+        let mut lines = vec![];
+        for source in sources.plans {
+            let table = self.ctx.build_table_from_source_plan(&source)?;
+            lines.push(format!("table {}:", table.name()));
+            let explanation = match FuseTable::try_from_table(table.as_ref()) {
+                Err(_) => None,
+                Ok(fuse_table) => {
+                    fuse_table
+                        .do_explain_prune(self.ctx.clone(), &source.push_downs)
+                        .await?
+                }
+            };
+            match explanation {
+                None => lines.push("no pruning".to_string()),
+                Some(explanation) => {
+                    lines.extend(format!("{}", explanation).lines().map(|s| s.to_string()))
+                }
+            }
+        }
+
+        let formatted = Series::from_data(lines.iter().map(|s| s.as_bytes()).collect::<Vec<_>>());
+        Ok(DataBlock::create(schema, vec![formatted]))
+    }
+
     fn explain_pipeline(&self) -> Result<DataBlock> {
         let schema = self.schema();
         let optimizer = Optimizers::without_scatters(self.ctx.clone());
@@ -111,3 +151,15 @@ impl ExplainInterpreter {
         Ok(DataBlock::create(schema, vec![formatted_pipeline]))
     }
 }
+
+// collects the scans of the tables of a plan
+struct ReadDataSourceCollector {
+    plans: Vec<ReadDataSourcePlan>,
+}
+
+impl PlanVisitor for ReadDataSourceCollector {
+    fn visit_read_data_source(&mut self, plan: &ReadDataSourcePlan) -> Result<()> {
+        self.plans.push(plan.clone());
+        Ok(())
+    }
+}
@@ -14,6 +14,7 @@
 
 use std::sync::Arc;
 
+use chrono::Utc;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_planners::PlanNode;
@@ -79,6 +80,7 @@ pub struct InterpreterFactory;
 /// Such as: SelectPlan -> SelectInterpreter, ExplainPlan -> ExplainInterpreter, ...
 impl InterpreterFactory {
     pub fn get(ctx: Arc<QueryContext>, plan: PlanNode) -> Result<Arc<dyn Interpreter>> {
+        Self::check_password_change(&ctx, &plan)?;
         let ctx_clone = ctx.clone();
         let inner = match plan.clone() {
             PlanNode::Select(v) => SelectInterpreter::try_create(ctx_clone, v),
@@ -182,4 +184,22 @@ impl InterpreterFactory {
         }?;
         Ok(Arc::new(InterceptorInterpreter::create(ctx, inner, plan)))
     }
+
+    // A user whose password must be changed can run nothing but the change of it.
+    fn check_password_change(ctx: &Arc<QueryContext>, plan: &PlanNode) -> Result<()> {
+        let user = match ctx.get_current_user() {
+            Ok(user) => user,
+            Err(_) => return Ok(()),
+        };
+        if !user.password_policy.is_change_required(Utc::now()) {
+            return Ok(());
+        }
+        match plan {
+            PlanNode::AlterUser(v) if v.user == user.identity() && v.auth_info.is_some() => Ok(()),
+            _ => Err(ErrorCode::PasswordMustBeChanged(format!(
+                "The password of user {} must be changed by ALTER USER ... IDENTIFIED BY first",
+                user.identity()
+            ))),
+        }
+    }
 }
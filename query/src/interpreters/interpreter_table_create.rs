@@ -32,6 +32,7 @@ use crate::catalogs::Catalog;
 use crate::interpreters::Interpreter;
 use crate::interpreters::InterpreterPtr;
 use crate::sessions::QueryContext;
+use crate::sessions::StorageLocation;
 
 pub struct CreateTableInterpreter {
     ctx: Arc<QueryContext>,
@@ -86,6 +87,12 @@ impl Interpreter for CreateTableInterpreter {
             )));
         }
 
+        // a table can not be created in a storage which is not reachable
+        let options = self.plan.options();
+        if let Some(location) = StorageLocation::from_table_options(&self.plan.tenant, options) {
+            self.ctx.check_storage_location(&location).await?;
+        }
+
         match &self.plan.as_select {
             Some(select_plan_node) => {
                 self.create_table_as_select(input_stream, select_plan_node.clone())
@@ -14,6 +14,9 @@
 
 use std::sync::Arc;
 
+use common_datablocks::DataBlock;
+use common_datavalues::Series;
+use common_datavalues::SeriesFrom;
 use common_exception::Result;
 use common_planners::Optimization;
 use common_planners::OptimizeTablePlan;
@@ -55,6 +58,7 @@ impl Interpreter for OptimizeTableInterpreter {
 
         let do_purge = operation.contains(Optimization::PURGE);
         let do_compact = operation.contains(Optimization::COMPACT);
+        let do_vacuum = operation.contains(Optimization::VACUUM);
 
         if do_compact {
             // it is a "simple and violent" strategy, to be optimized later
@@ -65,7 +69,10 @@ impl Interpreter for OptimizeTableInterpreter {
                 PlanParser::parse(self.ctx.clone(), rewritten_query.as_str()).await?;
             let interpreter = InterpreterFactory::get(self.ctx.clone(), rewritten_plan)?;
             let mut stream = interpreter.execute(None).await?;
-            while let Some(Ok(_)) = stream.next().await {}
+            // a compaction which conflicts with other writes fails, it must not be ignored
+            while let Some(block) = stream.next().await {
+                block?;
+            }
             if do_purge {
                 // currently, context caches the table, we have to "refresh"
                 // the table by using the catalog API directly
@@ -82,6 +89,17 @@ impl Interpreter for OptimizeTableInterpreter {
             table.optimize(self.ctx.clone(), true).await?;
         }
 
+        if do_vacuum {
+            let dry_run = operation.contains(Optimization::DRY_RUN);
+            let files = table.vacuum(self.ctx.clone(), dry_run).await?;
+            let block = DataBlock::create(self.plan.schema(), vec![Series::from_data(files)]);
+            return Ok(Box::pin(DataBlockStream::create(
+                self.plan.schema(),
+                None,
+                vec![block],
+            )));
+        }
+
         Ok(Box::pin(DataBlockStream::create(
             self.plan.schema(),
             None,
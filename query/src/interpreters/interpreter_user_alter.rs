@@ -52,8 +52,22 @@ impl Interpreter for AlterUserInterpreter {
         let user_mgr = self.ctx.get_user_manager();
         if plan.auth_info.is_some() || plan.user_option.is_some() {
             user_mgr
-                .update_user(&tenant, plan.user, plan.auth_info, plan.user_option)
+                .update_user(
+                    &tenant,
+                    plan.user.clone(),
+                    plan.auth_info,
+                    plan.password.as_deref(),
+                    plan.user_option,
+                )
                 .await?;
+
+            // Refresh the current user, e.g. the password is not required to be changed anymore.
+            let is_current_user =
+                matches!(self.ctx.get_current_user(), Ok(u) if u.identity() == plan.user);
+            if is_current_user {
+                let user_info = user_mgr.get_user(&tenant, plan.user).await?;
+                self.ctx.get_current_session().set_current_user(user_info);
+            }
         }
 
         Ok(Box::pin(DataBlockStream::create(
@@ -60,8 +60,18 @@ impl Interpreter for CreateUserInterpreter {
             grants: UserGrantSet::empty(),
             quota: UserQuota::no_limit(),
             option: plan.user_option,
+            is_locked: false,
+            locked_reason: None,
+            settings: Default::default(),
+            password_policy: plan.password_policy,
+            network_policy: None,
+            is_builtin: false,
+            password_history: vec![],
         };
-        user_mgr.add_user(&tenant, user_info, false).await?;
+        let password = plan.password.as_deref();
+        user_mgr
+            .add_user(&tenant, user_info, password, false)
+            .await?;
 
         Ok(Box::pin(DataBlockStream::create(
             self.plan.schema(),
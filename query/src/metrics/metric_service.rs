@@ -25,20 +25,37 @@ use poem::IntoResponse;
 use crate::common::service::HttpShutdownHandler;
 use crate::servers::Server;
 use crate::sessions::SessionManager;
+use crate::sessions::QUERY_RUNTIME_NAME;
 
 pub struct MetricService {
+    sessions: Arc<SessionManager>,
     shutdown_handler: HttpShutdownHandler,
 }
 
 #[poem::handler]
-pub async fn metric_handler(prom_extension: Data<&PrometheusHandle>) -> impl IntoResponse {
+pub async fn metric_handler(
+    prom_extension: Data<&PrometheusHandle>,
+    sessions: Data<&Arc<SessionManager>>,
+) -> impl IntoResponse {
+    // The counters of the runtimes are kept by the runtimes, recorded when scraped.
+    let storage_runtime = sessions.0.get_storage_runtime();
+    storage_runtime
+        .metrics()
+        .record(storage_runtime.name().unwrap_or_default());
+    // The runtimes of the queries come and go, recorded as one by their shared counters.
+    sessions
+        .0
+        .get_query_runtime_counters()
+        .snapshot()
+        .record(QUERY_RUNTIME_NAME);
     prom_extension.0.render()
 }
 
 impl MetricService {
     // TODO add session tls handler
-    pub fn create(_sessions: Arc<SessionManager>) -> Box<MetricService> {
+    pub fn create(sessions: Arc<SessionManager>) -> Box<MetricService> {
         Box::new(MetricService {
+            sessions,
             shutdown_handler: HttpShutdownHandler::create("metric api".to_string()),
         })
     }
@@ -49,7 +66,8 @@ impl MetricService {
         })?;
         let app = poem::Route::new()
             .at("/metrics", poem::get(metric_handler))
-            .data(prometheus_handle);
+            .data(prometheus_handle)
+            .data(self.sessions.clone());
         let addr = self
             .shutdown_handler
             .start_service(listening, None, app)
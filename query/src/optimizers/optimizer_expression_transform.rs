@@ -163,93 +163,52 @@ impl ExprTransformImpl {
         let left = Self::constant_transformer(left)?;
         let right = Self::constant_transformer(right)?;
 
-        let mut is_remove = false;
-
-        let mut left_const = false;
-        let new_left = Self::eval_const_cond(
-            column_name.clone(),
-            &left,
-            is_and,
-            &mut left_const,
-            &mut is_remove,
-        )?;
-        if is_remove {
-            return Ok(new_left);
-        }
-
-        let mut right_const = false;
-        let new_right = Self::eval_const_cond(
-            column_name.clone(),
-            &right,
-            is_and,
-            &mut right_const,
-            &mut is_remove,
-        )?;
-        if is_remove {
-            return Ok(new_right);
+        // With the three-valued logic, false absorbs AND and true absorbs OR,
+        // the other boolean is the identity and NULL is neither of them.
+        let absorbing = !is_and;
+        let left_value = Self::eval_const_cond(&left);
+        let right_value = Self::eval_const_cond(&right);
+        if left_value == Some(Some(absorbing)) || right_value == Some(Some(absorbing)) {
+            return Ok(Expression::Literal {
+                value: DataValue::Boolean(absorbing),
+                column_name: Some(column_name),
+                data_type: bool::to_data_type(),
+            });
         }
 
-        match (left_const, right_const) {
-            (true, true) => {
-                if is_and {
-                    Ok(Expression::Literal {
-                        value: DataValue::Boolean(true),
-                        column_name: Some(column_name),
-                        data_type: bool::to_data_type(),
-                    })
-                } else {
-                    Ok(Expression::Literal {
-                        value: DataValue::Boolean(false),
-                        column_name: Some(column_name),
-                        data_type: bool::to_data_type(),
-                    })
-                }
-            }
-            (true, false) => Ok(new_right),
-            (false, true) => Ok(new_left),
-            (false, false) => {
+        match (left_value, right_value) {
+            (Some(Some(_)), _) => Ok(right),
+            (_, Some(Some(_))) => Ok(left),
+            (Some(None), Some(None)) => Ok(left),
+            _ => {
                 if is_and {
-                    Ok(new_left.and(new_right))
+                    Ok(left.and(right))
                 } else {
-                    Ok(new_left.or(new_right))
+                    Ok(left.or(right))
                 }
             }
         }
     }
 
-    fn eval_const_cond(
-        column_name: String,
-        expr: &Expression,
-        is_and: bool,
-        is_const: &mut bool,
-        is_remove: &mut bool,
-    ) -> Result<Expression> {
+    fn predicate_transformer(predicate: &Expression) -> Result<Expression> {
+        let new_predicate = Self::constant_transformer(predicate)?;
+        let new_predicate = Self::boolean_transformer(&new_predicate)?;
+        Self::truth_transformer(&new_predicate, false)
+    }
+
+    // Returns the value of a constant condition, Some(None) for NULL and None if it is not a constant.
+    fn eval_const_cond(expr: &Expression) -> Option<Option<bool>> {
         match expr {
-            Expression::Literal { ref value, .. } => {
-                *is_const = true;
-                let val = value.as_bool()?;
-                if val {
-                    if !is_and {
-                        *is_remove = true;
-                        return Ok(Expression::Literal {
-                            value: DataValue::Boolean(true),
-                            column_name: Some(column_name),
-                            data_type: bool::to_data_type(),
-                        });
-                    }
-                } else if is_and {
-                    *is_remove = true;
-                    return Ok(Expression::Literal {
-                        value: DataValue::Boolean(false),
-                        column_name: Some(column_name),
-                        data_type: bool::to_data_type(),
-                    });
-                }
-            }
-            _ => *is_const = false,
+            Expression::Literal {
+                value: DataValue::Boolean(v),
+                ..
+            } => Some(Some(*v)),
+            Expression::Literal {
+                value: DataValue::Null,
+                ..
+            } => Some(None),
+            _ => None,
         }
-        *is_remove = false;
-        Ok(expr.clone())
     }
 }
 
@@ -293,37 +252,39 @@ impl PlanRewriter for ExprTransformImpl {
     }
 
     fn rewrite_filter(&mut self, plan: &FilterPlan) -> Result<PlanNode> {
-        if plan.is_literal_false() {
-            self.one_time_filter = Some(false);
-            let new_input = self.rewrite_plan_node(plan.input.as_ref())?;
-            self.one_time_filter = None;
-            return PlanBuilder::from(&new_input)
-                .filter(plan.predicate.clone())?
-                .build();
+        let new_predicate = Self::predicate_transformer(&plan.predicate)?;
+        match Self::eval_const_cond(&new_predicate) {
+            // The filter keeps all the rows.
+            Some(Some(true)) => self.rewrite_plan_node(plan.input.as_ref()),
+            // The filter drops all the rows.
+            Some(_) => {
+                self.one_time_filter = Some(false);
+                let new_input = self.rewrite_plan_node(plan.input.as_ref())?;
+                self.one_time_filter = None;
+                PlanBuilder::from(&new_input).filter(new_predicate)?.build()
+            }
+            None => {
+                let new_input = self.rewrite_plan_node(plan.input.as_ref())?;
+                PlanBuilder::from(&new_input).filter(new_predicate)?.build()
+            }
         }
-
-        let new_input = self.rewrite_plan_node(plan.input.as_ref())?;
-        let new_predicate = Self::constant_transformer(&plan.predicate)?;
-        let new_predicate = Self::boolean_transformer(&new_predicate)?;
-        let new_predicate = Self::truth_transformer(&new_predicate, false)?;
-        PlanBuilder::from(&new_input).filter(new_predicate)?.build()
     }
 
     fn rewrite_having(&mut self, plan: &HavingPlan) -> Result<PlanNode> {
-        if plan.is_literal_false() {
-            self.one_time_having = Some(false);
-            let new_input = self.rewrite_plan_node(plan.input.as_ref())?;
-            self.one_time_having = None;
-            return PlanBuilder::from(&new_input)
-                .having(plan.predicate.clone())?
-                .build();
+        let new_predicate = Self::predicate_transformer(&plan.predicate)?;
+        match Self::eval_const_cond(&new_predicate) {
+            Some(Some(true)) => self.rewrite_plan_node(plan.input.as_ref()),
+            Some(_) => {
+                self.one_time_having = Some(false);
+                let new_input = self.rewrite_plan_node(plan.input.as_ref())?;
+                self.one_time_having = None;
+                PlanBuilder::from(&new_input).having(new_predicate)?.build()
+            }
+            None => {
+                let new_input = self.rewrite_plan_node(plan.input.as_ref())?;
+                PlanBuilder::from(&new_input).having(new_predicate)?.build()
+            }
         }
-
-        let new_input = self.rewrite_plan_node(plan.input.as_ref())?;
-        let new_predicate = Self::constant_transformer(&plan.predicate)?;
-        let new_predicate = Self::boolean_transformer(&new_predicate)?;
-        let new_predicate = Self::truth_transformer(&new_predicate, false)?;
-        PlanBuilder::from(&new_input).having(new_predicate)?.build()
     }
 
     fn rewrite_limit(&mut self, plan: &LimitPlan) -> Result<PlanNode> {
@@ -0,0 +1,244 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common_exception::Result;
+use common_planners::*;
+
+use crate::optimizers::Optimizer;
+use crate::sessions::QueryContext;
+
+/// Push the conjuncts of the filters down to `Extras::filters` of the read source,
+/// through the projections and expressions, so that the storage can prune blocks with them.
+/// The filters are kept where they are, the pushed down conjuncts are only used for pruning.
+pub struct PredicatePushDownOptimizer {}
+
+#[derive(Clone, Default)]
+struct PredicatePushDownImpl {
+    // The conjuncts of the filters right above the current node, the read source directly
+    // below a filter already receives them from the query analyzer.
+    filters: Vec<Expression>,
+    // The conjuncts passed through projections or expressions, in terms of the output
+    // columns of the current node.
+    predicates: Vec<Expression>,
+}
+
+impl PlanRewriter for PredicatePushDownImpl {
+    fn rewrite_aggregate_partial(&mut self, plan: &AggregatorPartialPlan) -> Result<PlanNode> {
+        let state = std::mem::take(self);
+        let new_input = self.rewrite_input(&plan.input)?;
+        *self = state;
+
+        Ok(PlanNode::AggregatorPartial(AggregatorPartialPlan {
+            schema: plan.schema.clone(),
+            aggr_expr: plan.aggr_expr.clone(),
+            group_expr: plan.group_expr.clone(),
+            input: new_input,
+        }))
+    }
+
+    fn rewrite_aggregate_final(&mut self, plan: &AggregatorFinalPlan) -> Result<PlanNode> {
+        let state = std::mem::take(self);
+        let new_input = self.rewrite_input(&plan.input)?;
+        *self = state;
+
+        Ok(PlanNode::AggregatorFinal(AggregatorFinalPlan {
+            schema: plan.schema.clone(),
+            schema_before_group_by: plan.schema_before_group_by.clone(),
+            aggr_expr: plan.aggr_expr.clone(),
+            group_expr: plan.group_expr.clone(),
+            input: new_input,
+        }))
+    }
+
+    fn rewrite_subquery_plan(&mut self, subquery_plan: &PlanNode) -> Result<PlanNode> {
+        let mut optimizer = PredicatePushDownOptimizer {};
+        optimizer.optimize(subquery_plan)
+    }
+
+    fn rewrite_filter(&mut self, plan: &FilterPlan) -> Result<PlanNode> {
+        let state = self.clone();
+        let mut conjunctions = vec![];
+        Self::split_conjunctions(&plan.predicate, &mut conjunctions);
+        for conjunction in conjunctions {
+            if !Self::has_sub_queries(&conjunction)? {
+                self.filters.push(conjunction);
+            }
+        }
+        let new_input = self.rewrite_input(&plan.input)?;
+        *self = state;
+
+        Ok(PlanNode::Filter(FilterPlan {
+            predicate: plan.predicate.clone(),
+            schema: plan.schema.clone(),
+            input: new_input,
+        }))
+    }
+
+    fn rewrite_projection(&mut self, plan: &ProjectionPlan) -> Result<PlanNode> {
+        let state = self.clone();
+        self.pass_through(&plan.expr)?;
+        let new_input = self.rewrite_input(&plan.input)?;
+        *self = state;
+
+        Ok(PlanNode::Projection(ProjectionPlan {
+            expr: plan.expr.clone(),
+            schema: plan.schema.clone(),
+            input: new_input,
+        }))
+    }
+
+    fn rewrite_expression(&mut self, plan: &ExpressionPlan) -> Result<PlanNode> {
+        let state = self.clone();
+        self.pass_through(&plan.exprs)?;
+        let new_input = self.rewrite_input(&plan.input)?;
+        *self = state;
+
+        Ok(PlanNode::Expression(ExpressionPlan {
+            exprs: plan.exprs.clone(),
+            schema: plan.schema.clone(),
+            input: new_input,
+            desc: plan.desc.clone(),
+        }))
+    }
+
+    fn rewrite_limit(&mut self, plan: &LimitPlan) -> Result<PlanNode> {
+        // Filtering below a limit changes which rows are taken.
+        let state = std::mem::take(self);
+        let new_input = self.rewrite_input(&plan.input)?;
+        *self = state;
+
+        Ok(PlanNode::Limit(LimitPlan {
+            n: plan.n,
+            offset: plan.offset,
+            input: new_input,
+        }))
+    }
+
+    fn rewrite_limit_by(&mut self, plan: &LimitByPlan) -> Result<PlanNode> {
+        let state = std::mem::take(self);
+        let new_input = self.rewrite_input(&plan.input)?;
+        *self = state;
+
+        Ok(PlanNode::LimitBy(LimitByPlan {
+            limit: plan.limit,
+            limit_by: plan.limit_by.clone(),
+            input: new_input,
+        }))
+    }
+
+    fn rewrite_union(&mut self, plan: &UnionPlan) -> Result<PlanNode> {
+        // The conjuncts are named after the union columns, not after the inputs columns.
+        let state = std::mem::take(self);
+        let new_inputs = plan
+            .inputs
+            .iter()
+            .map(|input| self.rewrite_input(input))
+            .collect::<Result<Vec<_>>>()?;
+        *self = state;
+
+        Ok(PlanNode::Union(UnionPlan::try_create(new_inputs)?))
+    }
+
+    fn rewrite_read_data_source(&mut self, plan: &ReadDataSourcePlan) -> Result<PlanNode> {
+        if self.predicates.is_empty() {
+            return Ok(PlanNode::ReadSource(plan.clone()));
+        }
+
+        let mut extras = plan.push_downs.clone().unwrap_or_else(Extras::default);
+        let mut pushed = vec![];
+        for filter in &extras.filters {
+            Self::split_conjunctions(filter, &mut pushed);
+        }
+
+        for predicate in &self.predicates {
+            if !pushed.contains(predicate) {
+                pushed.push(predicate.clone());
+                extras.filters.push(predicate.clone());
+            }
+        }
+
+        let mut new_plan = plan.clone();
+        new_plan.push_downs = Some(extras);
+        Ok(PlanNode::ReadSource(new_plan))
+    }
+}
+
+impl PredicatePushDownImpl {
+    fn split_conjunctions(expr: &Expression, conjunctions: &mut Vec<Expression>) {
+        match expr {
+            Expression::BinaryExpression { op, left, right } if op.eq_ignore_ascii_case("and") => {
+                Self::split_conjunctions(left, conjunctions);
+                Self::split_conjunctions(right, conjunctions);
+            }
+            _ => conjunctions.push(expr.clone()),
+        }
+    }
+
+    fn has_sub_queries(expr: &Expression) -> Result<bool> {
+        let mut sub_queries = vec![];
+        RewriteHelper::collect_expr_sub_queries(expr, &mut sub_queries)?;
+        Ok(!sub_queries.is_empty())
+    }
+
+    // Rewrite the filters and predicates in terms of the input columns of the projection,
+    // the ones referencing computed expressions are dropped.
+    fn pass_through(&mut self, exprs: &[Expression]) -> Result<()> {
+        let mut columns = HashMap::with_capacity(exprs.len());
+        for expr in exprs {
+            match expr {
+                Expression::Column(name) => {
+                    columns.insert(name.clone(), expr.clone());
+                }
+                Expression::Alias(alias, inner)
+                    if matches!(inner.as_ref(), Expression::Column(_)) =>
+                {
+                    columns.insert(alias.clone(), inner.as_ref().clone());
+                }
+                _ => {}
+            }
+        }
+
+        let mut passed = vec![];
+        for predicate in self.predicates.iter().chain(self.filters.iter()) {
+            let required = RequireColumnsVisitor::collect_columns_from_expr(predicate)?;
+            if !required.is_empty() && required.iter().all(|c| columns.contains_key(c)) {
+                passed.push(RewriteHelper::rewrite_alias_expr(&columns, predicate)?);
+            }
+        }
+
+        self.filters.clear();
+        self.predicates = passed;
+        Ok(())
+    }
+}
+
+impl Optimizer for PredicatePushDownOptimizer {
+    fn name(&self) -> &str {
+        "PredicatePushDown"
+    }
+
+    fn optimize(&mut self, plan: &PlanNode) -> Result<PlanNode> {
+        let mut rewriter = PredicatePushDownImpl::default();
+        rewriter.rewrite_plan_node(plan)
+    }
+}
+
+impl PredicatePushDownOptimizer {
+    pub fn create(_ctx: Arc<QueryContext>) -> PredicatePushDownOptimizer {
+        PredicatePushDownOptimizer {}
+    }
+}
@@ -0,0 +1,262 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use common_datavalues::DataSchema;
+use common_datavalues::DataSchemaRef;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_planners::*;
+
+use crate::optimizers::Optimizer;
+use crate::sessions::QueryContext;
+
+/// Prune the columns which are not referenced above the scan, the query analyzer
+/// only does it for the tables read directly by a query, not through a subquery.
+pub struct ProjectionPushDownOptimizer {
+    ctx: Arc<QueryContext>,
+}
+
+struct ProjectionPushDownImpl {
+    ctx: Arc<QueryContext>,
+    // The columns of the current node required by its parents,
+    // None if the output of the node can't be changed.
+    required_columns: Option<HashSet<String>>,
+    before_group_by_schema: Option<DataSchemaRef>,
+}
+
+impl PlanRewriter for ProjectionPushDownImpl {
+    fn rewrite_aggregate_partial(&mut self, plan: &AggregatorPartialPlan) -> Result<PlanNode> {
+        let mut exprs = plan.aggr_expr.clone();
+        exprs.extend(plan.group_expr.iter().cloned());
+        let new_input = self.rewrite_with_required(&plan.input, Some(Self::columns(&exprs)?))?;
+
+        match self.before_group_by_schema {
+            Some(_) => Err(ErrorCode::LogicalError(
+                "Logical error: before group by schema must be None",
+            )),
+            None => {
+                self.before_group_by_schema = Some(new_input.schema());
+                PlanBuilder::from(&new_input)
+                    .aggregate_partial(&plan.aggr_expr, &plan.group_expr)?
+                    .build()
+            }
+        }
+    }
+
+    fn rewrite_aggregate_final(&mut self, plan: &AggregatorFinalPlan) -> Result<PlanNode> {
+        let new_input = self.rewrite_with_required(&plan.input, None)?;
+
+        match self.before_group_by_schema.take() {
+            None => Err(ErrorCode::LogicalError(
+                "Logical error: before group by schema must be Some",
+            )),
+            Some(schema_before_group_by) => PlanBuilder::from(&new_input)
+                .aggregate_final(schema_before_group_by, &plan.aggr_expr, &plan.group_expr)?
+                .build(),
+        }
+    }
+
+    fn rewrite_subquery_plan(&mut self, subquery_plan: &PlanNode) -> Result<PlanNode> {
+        let mut optimizer = ProjectionPushDownOptimizer::create(self.ctx.clone());
+        optimizer.optimize(subquery_plan)
+    }
+
+    fn rewrite_projection(&mut self, plan: &ProjectionPlan) -> Result<PlanNode> {
+        let exprs = match &self.required_columns {
+            None => plan.expr.clone(),
+            Some(required_columns) => {
+                let exprs = plan
+                    .expr
+                    .iter()
+                    .filter(|expr| required_columns.contains(&expr.column_name()))
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                match exprs.is_empty() {
+                    // SELECT COUNT() FROM (SELECT * FROM table_name).
+                    true => vec![plan.expr[Self::smallest_column(&plan.schema)].clone()],
+                    false => exprs,
+                }
+            }
+        };
+
+        let new_input = self.rewrite_with_required(&plan.input, Some(Self::columns(&exprs)?))?;
+        PlanBuilder::from(&new_input).project(&exprs)?.build()
+    }
+
+    fn rewrite_expression(&mut self, plan: &ExpressionPlan) -> Result<PlanNode> {
+        let required_columns = Some(Self::columns(&plan.exprs)?);
+        let new_input = self.rewrite_with_required(&plan.input, required_columns)?;
+        PlanBuilder::from(&new_input)
+            .expression(&plan.exprs, &plan.desc)?
+            .build()
+    }
+
+    fn rewrite_filter(&mut self, plan: &FilterPlan) -> Result<PlanNode> {
+        let required_columns = self.add_columns(&[plan.predicate.clone()])?;
+        let new_input = self.rewrite_with_required(&plan.input, required_columns)?;
+        PlanBuilder::from(&new_input)
+            .filter(plan.predicate.clone())?
+            .build()
+    }
+
+    fn rewrite_having(&mut self, plan: &HavingPlan) -> Result<PlanNode> {
+        let required_columns = self.add_columns(&[plan.predicate.clone()])?;
+        let new_input = self.rewrite_with_required(&plan.input, required_columns)?;
+        PlanBuilder::from(&new_input)
+            .having(plan.predicate.clone())?
+            .build()
+    }
+
+    fn rewrite_sort(&mut self, plan: &SortPlan) -> Result<PlanNode> {
+        let required_columns = self.add_columns(&plan.order_by)?;
+        let new_input = self.rewrite_with_required(&plan.input, required_columns)?;
+        PlanBuilder::from(&new_input).sort(&plan.order_by)?.build()
+    }
+
+    fn rewrite_limit_by(&mut self, plan: &LimitByPlan) -> Result<PlanNode> {
+        let required_columns = self.add_columns(&plan.limit_by)?;
+        let new_input = self.rewrite_with_required(&plan.input, required_columns)?;
+        PlanBuilder::from(&new_input)
+            .limit_by(plan.limit, &plan.limit_by)?
+            .build()
+    }
+
+    fn rewrite_union(&mut self, plan: &UnionPlan) -> Result<PlanNode> {
+        // All the inputs must keep the same arity, so the union is a barrier.
+        let new_inputs = plan
+            .inputs
+            .iter()
+            .map(|input| Ok(Arc::new(self.rewrite_with_required(input, None)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(PlanNode::Union(UnionPlan::try_create(new_inputs)?))
+    }
+
+    fn rewrite_read_data_source(&mut self, plan: &ReadDataSourcePlan) -> Result<PlanNode> {
+        let (required_columns, extras) = match (&self.required_columns, &plan.push_downs) {
+            (Some(required_columns), Some(extras)) => (required_columns, extras),
+            _ => return Ok(PlanNode::ReadSource(plan.clone())),
+        };
+
+        let projection = match &extras.projection {
+            Some(projection) if !projection.is_empty() => projection,
+            _ => return Ok(PlanNode::ReadSource(plan.clone())),
+        };
+
+        let schema = plan.source_info.schema();
+        let mut new_projection = projection
+            .iter()
+            .filter(|index| required_columns.contains(schema.field(**index).name()))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if new_projection.is_empty() {
+            let projected = schema.project(projection.clone());
+            new_projection.push(projection[Self::smallest_column(&projected)]);
+        }
+
+        if new_projection.len() == projection.len()
+            || !self
+                .ctx
+                .build_table_from_source_plan(plan)?
+                .benefit_column_prune()
+        {
+            return Ok(PlanNode::ReadSource(plan.clone()));
+        }
+
+        let mut new_plan = plan.clone();
+        new_plan.scan_fields = Some(
+            new_projection
+                .iter()
+                .map(|index| (*index, schema.field(*index).clone()))
+                .collect(),
+        );
+        new_plan.push_downs = Some(Extras {
+            projection: Some(new_projection),
+            ..extras.clone()
+        });
+        Ok(PlanNode::ReadSource(new_plan))
+    }
+}
+
+impl ProjectionPushDownImpl {
+    fn rewrite_with_required(
+        &mut self,
+        input: &PlanNode,
+        required_columns: Option<HashSet<String>>,
+    ) -> Result<PlanNode> {
+        let required_columns = std::mem::replace(&mut self.required_columns, required_columns);
+        let new_input = self.rewrite_plan_node(input);
+        self.required_columns = required_columns;
+        new_input
+    }
+
+    fn add_columns(&self, exprs: &[Expression]) -> Result<Option<HashSet<String>>> {
+        match &self.required_columns {
+            None => Ok(None),
+            Some(required_columns) => {
+                let mut required_columns = required_columns.clone();
+                required_columns.extend(Self::columns(exprs)?);
+                Ok(Some(required_columns))
+            }
+        }
+    }
+
+    fn columns(exprs: &[Expression]) -> Result<HashSet<String>> {
+        let mut columns = HashSet::new();
+        for expr in exprs {
+            columns.extend(RequireColumnsVisitor::collect_columns_from_expr(expr)?);
+        }
+        Ok(columns)
+    }
+
+    fn smallest_column(schema: &DataSchema) -> usize {
+        let mut smallest_index = 0;
+        let mut smallest_size = usize::MAX;
+        for (index, field) in schema.fields().iter().enumerate() {
+            if let Ok(bytes) = field.data_type().data_type_id().numeric_byte_size() {
+                if smallest_size > bytes {
+                    smallest_size = bytes;
+                    smallest_index = index;
+                }
+            }
+        }
+        smallest_index
+    }
+}
+
+impl Optimizer for ProjectionPushDownOptimizer {
+    fn name(&self) -> &str {
+        "ProjectionPushDown"
+    }
+
+    fn optimize(&mut self, plan: &PlanNode) -> Result<PlanNode> {
+        let mut rewriter = ProjectionPushDownImpl {
+            ctx: self.ctx.clone(),
+            required_columns: None,
+            before_group_by_schema: None,
+        };
+        rewriter.rewrite_plan_node(plan)
+    }
+}
+
+impl ProjectionPushDownOptimizer {
+    pub fn create(ctx: Arc<QueryContext>) -> ProjectionPushDownOptimizer {
+        ProjectionPushDownOptimizer { ctx }
+    }
+}
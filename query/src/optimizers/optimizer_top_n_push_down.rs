@@ -112,12 +112,14 @@ impl PlanRewriter for TopNPushDownImpl {
                         filters: extras.filters.clone(),
                         limit: Some(new_limit),
                         order_by: self.get_sort_columns(plan.schema())?,
+                        preserve_order: extras.preserve_order,
                     })
                 }
                 None => {
                     let mut extras = Extras::default();
                     extras.limit = Some(n);
                     extras.order_by = self.get_sort_columns(plan.schema())?;
+                    extras.preserve_order = extras.order_by.is_empty();
                     Some(extras)
                 }
             };
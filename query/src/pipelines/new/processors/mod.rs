@@ -15,12 +15,14 @@
 pub mod port;
 pub mod processor;
 
+mod ordered_merge_processor;
 mod port_trigger;
 mod resize_processor;
 mod sinks;
 mod sources;
 mod transforms;
 
+pub use ordered_merge_processor::OrderedMergeProcessor;
 pub use port::connect;
 pub use port_trigger::DirectedEdge;
 pub use port_trigger::UpdateList;
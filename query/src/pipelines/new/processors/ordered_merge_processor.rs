@@ -0,0 +1,107 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+
+use common_exception::Result;
+
+use crate::pipelines::new::processors::port::InputPort;
+use crate::pipelines::new::processors::port::OutputPort;
+use crate::pipelines::new::processors::processor::Event;
+use crate::pipelines::new::processors::Processor;
+
+/// Merges the inputs into one output, by taking one data block from each input in turn.
+///
+/// If the i-th input produces the data blocks [i, i + n, i + 2n, ...] of a sequence,
+/// where n is the number of inputs, the output is the sequence in order.
+pub struct OrderedMergeProcessor {
+    inputs: Vec<Arc<InputPort>>,
+    output: Arc<OutputPort>,
+
+    cur_input_index: usize,
+}
+
+impl OrderedMergeProcessor {
+    pub fn create(inputs: usize) -> Self {
+        let mut inputs_port = Vec::with_capacity(inputs);
+
+        for _index in 0..inputs {
+            inputs_port.push(InputPort::create());
+        }
+
+        OrderedMergeProcessor {
+            inputs: inputs_port,
+            output: OutputPort::create(),
+            cur_input_index: 0,
+        }
+    }
+
+    pub fn get_inputs(&self) -> &[Arc<InputPort>] {
+        &self.inputs
+    }
+
+    pub fn get_output(&self) -> Arc<OutputPort> {
+        self.output.clone()
+    }
+
+    fn finish_inputs(&mut self) {
+        for input in &self.inputs {
+            input.finish();
+        }
+    }
+
+    fn inputs_need_data(&mut self) {
+        for input in &self.inputs {
+            if !input.is_finished() {
+                input.set_need_data();
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Processor for OrderedMergeProcessor {
+    fn name(&self) -> &'static str {
+        "OrderedMerge"
+    }
+
+    fn event(&mut self) -> Result<Event> {
+        if self.output.is_finished() {
+            self.finish_inputs();
+            return Ok(Event::Finished);
+        }
+
+        if !self.output.can_push() {
+            return Ok(Event::NeedConsume);
+        }
+
+        let cur_input = &self.inputs[self.cur_input_index];
+        if cur_input.has_data() {
+            self.output.push_data(cur_input.pull_data().unwrap());
+            self.cur_input_index = (self.cur_input_index + 1) % self.inputs.len();
+            // let the other inputs prepare their next data blocks in the meantime
+            self.inputs_need_data();
+            return Ok(Event::NeedConsume);
+        }
+
+        if cur_input.is_finished() {
+            // the next data block of the sequence would have come from the current input
+            self.finish_inputs();
+            self.output.finish();
+            return Ok(Event::Finished);
+        }
+
+        self.inputs_need_data();
+        Ok(Event::NeedData)
+    }
+}
@@ -179,7 +179,13 @@ impl<const MODE: usize> Processor for TransformLimitImpl<MODE> {
             self.output_data_block = match MODE {
                 ONLY_OFFSET => self.skip_rows(data_block),
                 ONLY_LIMIT => Some(self.take_rows(data_block)),
-                OFFSET_AND_LIMIT if self.skip_remaining != 0 => self.skip_rows(data_block),
+                // The rows left after the offset count toward the limit.
+                OFFSET_AND_LIMIT if self.skip_remaining != 0 => match self.skip_rows(data_block) {
+                    Some(data_block) if self.take_remaining != 0 => {
+                        Some(self.take_rows(data_block))
+                    }
+                    _ => None,
+                },
                 OFFSET_AND_LIMIT => Some(self.take_rows(data_block)),
                 _ => unreachable!(),
             }
@@ -32,6 +32,7 @@ use common_planners::SinkPlan;
 use common_planners::SortPlan;
 use common_planners::StagePlan;
 use common_planners::SubQueriesSetPlan;
+use common_planners::UnionPlan;
 use common_tracing::tracing;
 
 use crate::api::FlightTicket;
@@ -97,6 +98,7 @@ impl PipelineBuilder {
             PlanNode::ReadSource(node) => self.visit_read_data_source(node),
             PlanNode::SubQueryExpression(node) => self.visit_create_sets(node),
             PlanNode::Sink(node) => self.visit_sink(node),
+            PlanNode::Union(node) => self.visit_union(node),
             other => Result::Err(ErrorCode::UnknownPlan(format!(
                 "Build pipeline from the plan node unsupported:{:?}",
                 other.name()
@@ -341,6 +343,21 @@ impl PipelineBuilder {
         Ok(pipeline)
     }
 
+    fn visit_union(&mut self, plan: &UnionPlan) -> Result<Pipeline> {
+        let mut pipeline = Pipeline::create(self.ctx.clone());
+
+        for input in &plan.inputs {
+            // Each input binds its own partitions, build it with a context of its own.
+            let input_ctx = QueryContext::create_from(self.ctx.clone());
+            let input_pipeline = PipelineBuilder::create(input_ctx).build(input)?;
+            for processor in input_pipeline.last_pipe()?.processors() {
+                pipeline.add_source(processor)?;
+            }
+        }
+
+        Ok(pipeline)
+    }
+
     fn visit_create_sets(&mut self, plan: &SubQueriesSetPlan) -> Result<Pipeline> {
         let mut pipeline = self.visit(&*plan.input)?;
         let schema = plan.schema();
@@ -83,10 +83,16 @@ impl Procedure for BootstrapTenantProcedure {
             .await?;
 
         // Create user.
-        let auth_info = AuthInfo::create(&Some(auth_type), &Some(password))?;
+        let auth_info = AuthInfo::create(&Some(auth_type), &Some(password.clone()))?;
+        let password = match &auth_info {
+            AuthInfo::Password { .. } => Some(password.as_str()),
+            _ => None,
+        };
         let mut user_info = UserInfo::new(user_name.clone(), host_name.clone(), auth_info);
         user_info.grants.grant_role(account_admin_role.identity());
-        user_mgr.add_user(&tenant, user_info, true).await?;
+        user_mgr
+            .add_user(&tenant, user_info, password, true)
+            .await?;
 
         Ok(DataBlock::empty())
     }
@@ -12,8 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod fuse_blocks;
 mod fuse_history;
+mod fuse_import;
 mod system;
 
+pub use fuse_blocks::FuseBlocksProcedure;
 pub use fuse_history::FuseHistoryProcedure;
+pub use fuse_import::FuseImportProcedure;
 pub use system::SystemProcedure;
@@ -12,7 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::procedures::systems::FuseBlocksProcedure;
 use crate::procedures::systems::FuseHistoryProcedure;
+use crate::procedures::systems::FuseImportProcedure;
 use crate::procedures::ProcedureFactory;
 
 pub struct SystemProcedure;
@@ -23,5 +25,13 @@ impl SystemProcedure {
             "system$fuse_history",
             Box::new(FuseHistoryProcedure::try_create),
         );
+        factory.register(
+            "system$fuse_import",
+            Box::new(FuseImportProcedure::try_create),
+        );
+        factory.register(
+            "system$fuse_blocks",
+            Box::new(FuseBlocksProcedure::try_create),
+        );
     }
 }
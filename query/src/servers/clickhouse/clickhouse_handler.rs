@@ -126,10 +126,8 @@ impl Server for ClickHouseHandler {
                 "ClickHouseHandler already running.",
             )),
             Some(registration) => {
-                let rejected_rt = Arc::new(Runtime::with_worker_threads(
-                    1,
-                    Some("clickhouse-handler".to_string()),
-                )?);
+                let rejected_rt =
+                    Arc::new(Runtime::with_worker_threads_named(1, "clickhouse-handler")?);
                 let (stream, listener) = Self::listener_tcp(listening).await?;
                 let stream = Abortable::new(stream, registration);
                 self.join_handle = Some(tokio::spawn(self.listen_loop(stream, rejected_rt)));
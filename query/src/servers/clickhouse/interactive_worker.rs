@@ -109,7 +109,14 @@ impl ClickHouseSession for InteractiveWorker {
         let ctx = self.session.create_query_context().await;
         match ctx {
             Ok(c) => {
-                let user_info_auth = c.get_auth_manager().auth(&credential).await;
+                let user_info_auth = match c.get_auth_manager().auth(&credential).await {
+                    Ok(user_info) => self
+                        .session
+                        .check_user_quota(&user_info)
+                        .await
+                        .map(|_| user_info),
+                    Err(failure) => Err(failure),
+                };
                 match user_info_auth {
                     Ok(user_info) => {
                         self.session.set_current_user(user_info);
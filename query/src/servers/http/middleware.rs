@@ -37,7 +37,7 @@ pub struct HTTPSessionMiddleware {
     pub session_manager: Arc<SessionManager>,
 }
 
-fn get_credential(headers: &HeaderMap) -> Result<Option<Credential>> {
+fn get_credential(headers: &HeaderMap, client_ip: Option<String>) -> Result<Option<Credential>> {
     let auth_headers: Vec<_> = headers.get_all(AUTHORIZATION).iter().collect();
     if auth_headers.len() > 1 {
         let msg = &format!("Multiple {} headers detected", AUTHORIZATION);
@@ -56,7 +56,7 @@ fn get_credential(headers: &HeaderMap) -> Result<Option<Credential>> {
                 let c = Credential::Password {
                     name,
                     password,
-                    hostname: None,
+                    hostname: client_ip,
                 };
                 Ok(Some(c))
             }
@@ -66,6 +66,7 @@ fn get_credential(headers: &HeaderMap) -> Result<Option<Credential>> {
         match Bearer::decode(value) {
             Some(bearer) => Ok(Some(Credential::Jwt {
                 token: bearer.token().to_string(),
+                client_ip,
             })),
             None => Err(ErrorCode::AuthenticateFailure("bad Bearer auth header")),
         }
@@ -91,10 +92,19 @@ pub struct HTTPSessionEndpoint<E> {
 
 impl<E> HTTPSessionEndpoint<E> {
     async fn auth(&self, req: &Request) -> Result<UserInfo> {
-        let credential = get_credential(req.headers())?;
+        let client_ip = req
+            .remote_addr()
+            .as_socket_addr()
+            .map(|addr| addr.ip().to_string());
+        let credential = get_credential(req.headers(), client_ip.clone())?;
         match credential {
             Some(c) => self.manager.get_auth_manager().auth(&c).await,
-            None => self.manager.get_auth_manager().no_auth().await,
+            None => {
+                self.manager
+                    .get_auth_manager()
+                    .no_auth(client_ip.as_deref())
+                    .await
+            }
         }
     }
 }
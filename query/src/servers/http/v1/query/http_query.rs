@@ -130,6 +130,8 @@ impl HttpQuery {
                 let session = session_manager
                     .create_session(SessionType::HTTPQuery)
                     .await?;
+                // Bind the user first, the database requested overrides the default one of the user.
+                session.set_current_user(user_info.clone());
                 if let Some(db) = &session_conf.database {
                     session.set_current_database(db.clone());
                 }
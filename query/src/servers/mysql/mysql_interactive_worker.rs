@@ -218,12 +218,23 @@ impl<W: std::io::Write> InteractiveWorkerBase<W> {
 
         let ctx = self.session.create_query_context().await?;
         let user_manager = ctx.get_user_manager();
+        // The settings of the session are not loaded before the user is known.
+        let ignore_case = ctx.get_config().query.user_name_case_insensitive;
         let user_info = user_manager
-            .get_user_with_client_ip(&ctx.get_tenant(), user_name, client_ip)
+            .get_user_with_client_ip(&ctx.get_tenant(), user_name, client_ip, ignore_case)
+            .await?;
+        user_info.check_not_locked()?;
+        user_manager
+            .check_network_policy(
+                &ctx.get_tenant(),
+                &user_info,
+                Some(&info.user_client_address),
+            )
             .await?;
 
         let authed = user_info.auth_info.auth_mysql(&info.user_password, salt)?;
         if authed {
+            self.session.check_user_quota(&user_info).await?;
             self.session.set_current_user(user_info);
         }
         Ok(authed)
@@ -0,0 +1,200 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::io::Error;
+use std::io::ErrorKind;
+use std::io::Result;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common_exception::ErrorCode;
+use common_meta_types::StageStorage;
+use common_meta_types::StageType;
+use common_meta_types::UserStageInfo;
+use opendal::ops::OpCreate;
+use opendal::ops::OpDelete;
+use opendal::ops::OpList;
+use opendal::ops::OpRead;
+use opendal::ops::OpStat;
+use opendal::ops::OpWrite;
+use opendal::Accessor;
+use opendal::BytesReader;
+use opendal::BytesWriter;
+use opendal::Metadata;
+use opendal::ObjectStreamer;
+
+use crate::configs::S3StorageConfig;
+use crate::sessions::SessionManager;
+use crate::storages::fuse::FUSE_OPT_KEY_STORAGE_LOCATION;
+use crate::storages::fuse::FUSE_OPT_KEY_STORAGE_S3_CREDENTIAL_STAGE;
+use crate::storages::fuse::FUSE_OPT_KEY_STORAGE_S3_ENDPOINT_URL;
+use crate::storages::fuse::FUSE_OPT_KEY_STORAGE_S3_REGION;
+
+/// The storage of a table created with the option `storage_location`, e.g. `fs:///data/t1`
+/// or `s3://bucket/root`.
+///
+/// For `s3`, the credential is the one of the external stage named by the table option
+/// `storage_s3_credential_stage`, of the tenant of the table: the credential is never kept in
+/// the table options, and the credential of the configured s3 storage is never lent to a
+/// location. The endpoint and region may be given by the other `storage_s3_*` options, the
+/// ones of the configured s3 storage are used if not.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StorageLocation {
+    pub tenant: String,
+    pub location: String,
+    pub s3_endpoint_url: Option<String>,
+    pub s3_region: Option<String>,
+    pub s3_credential_stage: Option<String>,
+}
+
+impl StorageLocation {
+    pub fn new(tenant: impl Into<String>, location: impl Into<String>) -> Self {
+        Self {
+            tenant: tenant.into(),
+            location: location.into(),
+            s3_endpoint_url: None,
+            s3_region: None,
+            s3_credential_stage: None,
+        }
+    }
+
+    /// Returns `None` if the table has no storage location of its own.
+    pub fn from_table_options(tenant: &str, options: &BTreeMap<String, String>) -> Option<Self> {
+        let location = options.get(FUSE_OPT_KEY_STORAGE_LOCATION)?;
+        Some(Self {
+            tenant: tenant.to_string(),
+            location: location.clone(),
+            s3_endpoint_url: options.get(FUSE_OPT_KEY_STORAGE_S3_ENDPOINT_URL).cloned(),
+            s3_region: options.get(FUSE_OPT_KEY_STORAGE_S3_REGION).cloned(),
+            s3_credential_stage: options
+                .get(FUSE_OPT_KEY_STORAGE_S3_CREDENTIAL_STAGE)
+                .cloned(),
+        })
+    }
+
+    /// The name of the stage holding the s3 credential of this location. Errors with
+    /// `BadOption` if it is not specified.
+    pub fn s3_credential_stage(&self) -> common_exception::Result<&str> {
+        match &self.s3_credential_stage {
+            Some(stage) if !stage.is_empty() => Ok(stage),
+            _ => Err(ErrorCode::BadOption(format!(
+                "storage location {} requires the option {}",
+                self.location, FUSE_OPT_KEY_STORAGE_S3_CREDENTIAL_STAGE
+            ))),
+        }
+    }
+
+    /// The s3 config of this location with the credential of `stage`, the unspecified endpoint
+    /// and region are taken from `default`. Errors with `BadOption` if the stage is not an
+    /// external s3 stage with a credential.
+    pub fn s3_config(
+        &self,
+        default: &S3StorageConfig,
+        stage: &UserStageInfo,
+    ) -> common_exception::Result<S3StorageConfig> {
+        let (access_key_id, secret_access_key) =
+            match (&stage.stage_type, &stage.stage_params.storage) {
+                (StageType::External, StageStorage::S3(s3))
+                    if !s3.credentials_aws_key_id.is_empty()
+                        && !s3.credentials_aws_secret_key.is_empty() =>
+                {
+                    (
+                        s3.credentials_aws_key_id.clone(),
+                        s3.credentials_aws_secret_key.clone(),
+                    )
+                }
+                _ => {
+                    return Err(ErrorCode::BadOption(format!(
+                        "credential stage {} of {} is not an external s3 stage with a key",
+                        stage.stage_name, self.location
+                    )))
+                }
+            };
+
+        let or_default = |v: &Option<String>, d: &String| v.clone().unwrap_or_else(|| d.clone());
+        Ok(S3StorageConfig {
+            endpoint_url: or_default(&self.s3_endpoint_url, &default.endpoint_url),
+            region: or_default(&self.s3_region, &default.region),
+            access_key_id,
+            secret_access_key,
+            ..default.clone()
+        })
+    }
+}
+
+/// Accessor of the storage at a table's `storage_location`.
+///
+/// The storage is resolved on the first access, by
+/// [SessionManager::get_storage_accessor_of_location], so that an operator of it
+/// can be built where no async context is at hand.
+#[derive(Clone)]
+pub struct LocationAccessor {
+    session_mgr: Arc<SessionManager>,
+    location: StorageLocation,
+}
+
+impl std::fmt::Debug for LocationAccessor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocationAccessor")
+            .field("location", &self.location)
+            .finish()
+    }
+}
+
+impl LocationAccessor {
+    pub fn create(
+        session_mgr: Arc<SessionManager>,
+        location: &StorageLocation,
+    ) -> Arc<dyn Accessor> {
+        Arc::new(LocationAccessor {
+            session_mgr,
+            location: location.clone(),
+        })
+    }
+
+    async fn get_inner(&self) -> Result<Arc<dyn Accessor>> {
+        self.session_mgr
+            .get_storage_accessor_of_location(&self.location)
+            .await
+            .map_err(|e| Error::new(ErrorKind::Other, e.message()))
+    }
+}
+
+#[async_trait]
+impl Accessor for LocationAccessor {
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        self.get_inner().await?.create(args).await
+    }
+
+    async fn read(&self, args: &OpRead) -> Result<BytesReader> {
+        self.get_inner().await?.read(args).await
+    }
+
+    async fn write(&self, args: &OpWrite) -> Result<BytesWriter> {
+        self.get_inner().await?.write(args).await
+    }
+
+    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
+        self.get_inner().await?.stat(args).await
+    }
+
+    async fn delete(&self, args: &OpDelete) -> Result<()> {
+        self.get_inner().await?.delete(args).await
+    }
+
+    async fn list(&self, args: &OpList) -> Result<ObjectStreamer> {
+        self.get_inner().await?.list(args).await
+    }
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod location_accessor;
 mod metrics;
 mod query_ctx;
 mod query_ctx_shared;
@@ -21,18 +22,27 @@ mod session_info;
 #[allow(clippy::module_inception)]
 mod session_mgr;
 mod session_mgr_status;
+mod session_quota;
 mod session_ref;
 mod session_settings;
 mod session_status;
 mod session_type;
 
+pub use location_accessor::LocationAccessor;
+pub use location_accessor::StorageLocation;
 pub use query_ctx::QueryContext;
 pub use query_ctx_shared::QueryContextShared;
+pub use query_ctx_shared::StorageWriteRuntime;
+pub use query_ctx_shared::QUERY_RUNTIME_NAME;
 pub use session::Session;
 pub use session_ctx::SessionContext;
 pub use session_info::ProcessInfo;
 pub use session_mgr::SessionManager;
 pub use session_mgr_status::SessionManagerStatus;
+pub use session_quota::check_memory_quota;
+pub use session_quota::check_sessions_quota;
+pub use session_quota::cpu_quota_threads;
+pub use session_quota::UserSessions;
 pub use session_ref::SessionRef;
 pub use session_settings::Settings;
 pub use session_status::SessionStatus;
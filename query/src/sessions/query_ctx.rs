@@ -20,6 +20,8 @@ use std::sync::atomic::Ordering::Acquire;
 use std::sync::Arc;
 
 use common_base::tokio::task::JoinHandle;
+use common_base::AbortRegistration;
+use common_base::MemoryTracker;
 use common_base::Progress;
 use common_base::ProgressValues;
 use common_base::Runtime;
@@ -50,12 +52,16 @@ use crate::catalogs::DatabaseCatalog;
 use crate::clusters::Cluster;
 use crate::configs::Config;
 use crate::servers::http::v1::HttpQueryHandle;
+use crate::sessions::LocationAccessor;
 use crate::sessions::ProcessInfo;
 use crate::sessions::QueryContextShared;
 use crate::sessions::Session;
 use crate::sessions::SessionRef;
 use crate::sessions::Settings;
+use crate::sessions::StorageLocation;
+use crate::sessions::StorageWriteRuntime;
 use crate::storages::cache::CacheManager;
+use crate::storages::fuse::pruning::PruningStatistics;
 use crate::storages::S3StageTable;
 use crate::storages::Table;
 use crate::users::auth::auth_mgr::AuthMgr;
@@ -235,6 +241,27 @@ impl QueryContext {
         Ok(abort_stream)
     }
 
+    /// Whether the query is killed. Long running work which is not driven by the
+    /// abortable sources, e.g. the pruning of the table scans, should check it.
+    pub fn is_aborting(&self) -> bool {
+        self.shared.abort_handle.is_aborted()
+    }
+
+    /// Errors with `AbortedQuery` if the query is killed.
+    pub fn check_aborted(&self) -> Result<()> {
+        self.shared.abort_handle.registration().check_aborted()
+    }
+
+    /// Observes the kill of the query, for the tasks spawned by the query.
+    pub fn get_abort_registration(&self) -> AbortRegistration {
+        self.shared.abort_handle.registration()
+    }
+
+    /// Kills the query of this context.
+    pub fn kill(&self) {
+        self.shared.kill()
+    }
+
     pub fn get_current_database(&self) -> String {
         self.shared.get_current_database()
     }
@@ -350,14 +377,49 @@ impl QueryContext {
         Ok(operator.layer(self.shared.dal_ctx.as_ref().clone()))
     }
 
+    /// Get the operator of the storage at `location`, e.g. `fs:///data/t1` or `s3://bucket/root`.
+    ///
+    /// The storage is resolved on the first access of the operator, see [LocationAccessor].
+    pub fn get_storage_operator_of_location(&self, location: &StorageLocation) -> Result<Operator> {
+        let session_mgr = self.shared.session.session_mgr.clone();
+        let operator = Operator::new(LocationAccessor::create(session_mgr, location));
+
+        Ok(operator.layer(self.shared.dal_ctx.as_ref().clone()))
+    }
+
+    /// Checks that the storage at `location` is valid and reachable.
+    pub async fn check_storage_location(&self, location: &StorageLocation) -> Result<()> {
+        self.shared
+            .session
+            .session_mgr
+            .get_storage_accessor_of_location(location)
+            .await
+            .map(|_| ())
+    }
+
     pub fn get_dal_context(&self) -> &DalContext {
         self.shared.dal_ctx.as_ref()
     }
 
+    /// Accumulates the statistics of the pruning of the table scans of the query.
+    pub fn add_pruning_statistics(&self, statistics: &PruningStatistics) {
+        self.shared.pruning_statistics.write().merge(statistics);
+    }
+
+    pub fn get_pruning_statistics(&self) -> PruningStatistics {
+        self.shared.pruning_statistics.read().clone()
+    }
+
     pub fn get_storage_runtime(&self) -> Arc<Runtime> {
         self.shared.session.session_mgr.get_storage_runtime()
     }
 
+    /// The storage runtime on which the blocks of the query are written, at most
+    /// `max_block_write_concurrency` of them at a time.
+    pub fn get_storage_write_runtime(&self) -> Result<Arc<StorageWriteRuntime>> {
+        self.shared.try_get_storage_write_runtime()
+    }
+
     pub async fn reload_config(&self) -> Result<()> {
         self.shared.reload_config().await
     }
@@ -370,12 +432,31 @@ impl QueryContext {
 impl TrySpawn for QueryContext {
     /// Spawns a new asynchronous task, returning a tokio::JoinHandle for it.
     /// The task will run in the current context thread_pool not the global.
+    fn try_spawn_untraced<T>(&self, task: T) -> Result<JoinHandle<T::Output>>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        let task = MemoryTracker::track_task(self.shared.memory_tracker.clone(), task);
+        self.shared.try_get_runtime()?.try_spawn_untraced(task)
+    }
+
+    /// The task is tagged with the query id, in the current tracing span.
     fn try_spawn<T>(&self, task: T) -> Result<JoinHandle<T::Output>>
     where
         T: Future + Send + 'static,
         T::Output: Send + 'static,
     {
-        Ok(self.shared.try_get_runtime()?.spawn(task))
+        let span = tracing::info_span!("query_task", query_id = %self.get_id());
+        self.try_spawn_in_span(span, task)
+    }
+
+    fn try_spawn_blocking<F, R>(&self, f: F) -> Result<JoinHandle<R>>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        self.shared.try_get_runtime()?.try_spawn_blocking(f)
     }
 }
 
@@ -17,8 +17,11 @@ use std::collections::HashMap;
 use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
 
+use common_base::ConcurrencyLimitedRuntime;
+use common_base::MemoryTracker;
 use common_base::Progress;
 use common_base::Runtime;
+use common_base::RuntimeBuilder;
 use common_contexts::DalContext;
 use common_exception::ErrorCode;
 use common_exception::Result;
@@ -35,14 +38,19 @@ use crate::catalogs::DatabaseCatalog;
 use crate::clusters::Cluster;
 use crate::configs::Config;
 use crate::servers::http::v1::HttpQueryHandle;
+use crate::sessions::cpu_quota_threads;
 use crate::sessions::Session;
 use crate::sessions::Settings;
+use crate::storages::fuse::pruning::PruningStatistics;
 use crate::storages::Table;
 use crate::users::auth::auth_mgr::AuthMgr;
 use crate::users::RoleCacheMgr;
 use crate::users::UserApiProvider;
 
 type DatabaseAndTable = (String, String);
+/// The name of the runtimes of the queries, the prefix of the names of their threads.
+pub const QUERY_RUNTIME_NAME: &str = "query-ctx";
+pub type StorageWriteRuntime = ConcurrencyLimitedRuntime<Arc<Runtime>>;
 
 /// Data that needs to be shared in a query context.
 /// This is very useful, for example, for queries:
@@ -62,9 +70,13 @@ pub struct QueryContextShared {
     pub(in crate::sessions) result_progress: Arc<Progress>,
     pub(in crate::sessions) session: Arc<Session>,
     pub(in crate::sessions) runtime: Arc<RwLock<Option<Arc<Runtime>>>>,
+    /// The storage runtime limited by `max_block_write_concurrency` for the blocks of the query
+    pub(in crate::sessions) storage_write_runtime: Arc<RwLock<Option<Arc<StorageWriteRuntime>>>>,
     pub(in crate::sessions) init_query_id: Arc<RwLock<String>>,
     pub(in crate::sessions) cluster_cache: Arc<Cluster>,
     pub(in crate::sessions) sources_abort_handle: Arc<RwLock<Vec<AbortHandle>>>,
+    /// Aborted once the query is killed, for the work not driven by the abortable sources
+    pub(in crate::sessions) abort_handle: common_base::AbortHandle,
     pub(in crate::sessions) ref_count: Arc<AtomicUsize>,
     pub(in crate::sessions) subquery_index: Arc<AtomicUsize>,
     pub(in crate::sessions) running_query: Arc<RwLock<Option<String>>>,
@@ -72,9 +84,12 @@ pub struct QueryContextShared {
     pub(in crate::sessions) running_plan: Arc<RwLock<Option<PlanNode>>>,
     pub(in crate::sessions) tables_refs: Arc<Mutex<HashMap<DatabaseAndTable, Arc<dyn Table>>>>,
     pub(in crate::sessions) dal_ctx: Arc<DalContext>,
+    pub(in crate::sessions) pruning_statistics: Arc<RwLock<PruningStatistics>>,
     pub(in crate::sessions) user_manager: Arc<UserApiProvider>,
     pub(in crate::sessions) auth_manager: Arc<AuthMgr>,
     pub(in crate::sessions) role_cache_manager: Arc<RoleCacheMgr>,
+    /// The memory accounted by the tasks of the query, limited by `max_memory_usage`
+    pub(in crate::sessions) memory_tracker: Arc<MemoryTracker>,
 }
 
 impl QueryContextShared {
@@ -82,8 +97,14 @@ impl QueryContextShared {
         session: Arc<Session>,
         cluster_cache: Arc<Cluster>,
     ) -> Result<Arc<QueryContextShared>> {
-        let conf = session.get_config();
-        let user_manager = UserApiProvider::create_global(conf.clone()).await?;
+        // The users and the network policies are cached by the managers shared by the sessions.
+        let user_manager = session.session_mgr.get_user_manager();
+        let auth_manager = session.session_mgr.get_auth_manager();
+        let max_memory_usage = session.get_settings().get_max_memory_usage()?;
+        let memory_tracker = MemoryTracker::create_with_limit(
+            Some(session.get_memory_tracker()),
+            max_memory_usage.min(i64::MAX as u64) as i64,
+        );
         Ok(Arc::new(QueryContextShared {
             session,
             cluster_cache,
@@ -92,7 +113,9 @@ impl QueryContextShared {
             result_progress: Arc::new(Progress::create()),
             write_progress: Arc::new(Progress::create()),
             runtime: Arc::new(RwLock::new(None)),
+            storage_write_runtime: Arc::new(RwLock::new(None)),
             sources_abort_handle: Arc::new(RwLock::new(Vec::new())),
+            abort_handle: Default::default(),
             ref_count: Arc::new(AtomicUsize::new(0)),
             subquery_index: Arc::new(AtomicUsize::new(1)),
             running_query: Arc::new(RwLock::new(None)),
@@ -100,13 +123,17 @@ impl QueryContextShared {
             running_plan: Arc::new(RwLock::new(None)),
             tables_refs: Arc::new(Mutex::new(HashMap::new())),
             dal_ctx: Arc::new(Default::default()),
+            pruning_statistics: Arc::new(RwLock::new(Default::default())),
             user_manager: user_manager.clone(),
-            auth_manager: Arc::new(AuthMgr::create(conf, user_manager.clone()).await?),
+            auth_manager,
             role_cache_manager: Arc::new(RoleCacheMgr::new(user_manager)),
+            memory_tracker,
         }))
     }
 
     pub fn kill(&self) {
+        self.abort_handle.abort();
+
         let mut sources_abort_handle = self.sources_abort_handle.write();
 
         while let Some(source_abort_handle) = sources_abort_handle.pop() {
@@ -141,8 +168,15 @@ impl QueryContextShared {
         self.session.get_tenant()
     }
 
+    /// The changes of users made through it are on behalf of the current user, if any, see
+    /// [UserApiProvider::with_operator].
     pub fn get_user_manager(&self) -> Arc<UserApiProvider> {
-        self.user_manager.clone()
+        match self.session.get_current_user() {
+            Ok(user) => self
+                .user_manager
+                .with_operator(&user.identity().to_string()),
+            Err(_) => self.user_manager.clone(),
+        }
     }
 
     pub fn get_auth_manager(&self) -> Arc<AuthMgr> {
@@ -199,17 +233,43 @@ impl QueryContextShared {
             Some(query_runtime) => Ok(query_runtime.clone()),
             None => {
                 let settings = self.get_settings();
-                let max_threads = settings.get_max_threads()? as usize;
-                let runtime = Arc::new(Runtime::with_worker_threads(
-                    max_threads,
-                    Some("query-ctx".to_string()),
-                )?);
+                let mut max_threads = settings.get_max_threads()? as usize;
+                // The cpu quota of the user, if the session is bound to one.
+                if let Ok(user) = self.get_current_user() {
+                    max_threads = cpu_quota_threads(&user, max_threads);
+                }
+                // Counted as one runtime with the ones of the other queries.
+                let counters = self.session.session_mgr.get_query_runtime_counters();
+                let runtime = RuntimeBuilder::new()
+                    .worker_threads(max_threads)
+                    .name(QUERY_RUNTIME_NAME)
+                    .counters(counters)
+                    .build()?;
+                let runtime = Arc::new(runtime);
                 *query_runtime = Some(runtime.clone());
                 Ok(runtime)
             }
         }
     }
 
+    pub fn try_get_storage_write_runtime(&self) -> Result<Arc<StorageWriteRuntime>> {
+        let mut write_runtime = self.storage_write_runtime.write();
+
+        match &*write_runtime {
+            Some(write_runtime) => Ok(write_runtime.clone()),
+            None => {
+                let settings = self.get_settings();
+                let permits = settings.get_max_block_write_concurrency()?.max(1) as usize;
+                let runtime = Arc::new(ConcurrencyLimitedRuntime::create(
+                    self.session.session_mgr.get_storage_runtime(),
+                    permits,
+                ));
+                *write_runtime = Some(runtime.clone());
+                Ok(runtime)
+            }
+        }
+    }
+
     pub fn attach_http_query_handle(&self, handle: HttpQueryHandle) {
         let mut http_query = self.http_query.write();
         *http_query = Some(handle);
@@ -16,19 +16,24 @@ use std::net::SocketAddr;
 use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
 
+use common_base::MemoryTracker;
 use common_exception::ErrorCode;
 use common_exception::Result;
+use common_infallible::Mutex;
 use common_infallible::RwLock;
 use common_macros::MallocSizeOf;
 use common_mem_allocator::malloc_size;
 use common_meta_types::GrantObject;
 use common_meta_types::UserInfo;
 use common_meta_types::UserPrivilegeType;
+use common_tracing::tracing;
 use futures::channel::*;
 use opendal::Operator;
 
 use crate::catalogs::DatabaseCatalog;
 use crate::configs::Config;
+use crate::sessions::check_memory_quota;
+use crate::sessions::check_sessions_quota;
 use crate::sessions::QueryContext;
 use crate::sessions::QueryContextShared;
 use crate::sessions::SessionContext;
@@ -37,6 +42,9 @@ use crate::sessions::SessionStatus;
 use crate::sessions::SessionType;
 use crate::sessions::Settings;
 
+/// The name of the user setting of the default database of the sessions.
+const USER_DATABASE_SETTING: &str = "database";
+
 #[derive(Clone, MallocSizeOf)]
 pub struct Session {
     pub(in crate::sessions) id: String,
@@ -50,6 +58,12 @@ pub struct Session {
     session_settings: Settings,
     #[ignore_malloc_size_of = "insignificant"]
     status: Arc<RwLock<SessionStatus>>,
+    #[ignore_malloc_size_of = "insignificant"]
+    memory_tracker: Arc<MemoryTracker>,
+    // The user this session is counted for in the sessions of all the nodes, see
+    // `check_user_quota`.
+    #[ignore_malloc_size_of = "insignificant"]
+    pub(in crate::sessions) counted_user: Arc<Mutex<Option<String>>>,
 }
 
 impl Session {
@@ -64,6 +78,7 @@ impl Session {
             Settings::try_create(&conf, session_ctx.clone(), session_mgr.get_user_manager())?;
         let ref_count = Arc::new(AtomicUsize::new(0));
         let status = Arc::new(Default::default());
+        let memory_tracker = MemoryTracker::create(Some(session_mgr.get_memory_tracker()));
 
         Ok(Arc::new(Session {
             id,
@@ -73,6 +88,8 @@ impl Session {
             session_ctx,
             session_settings,
             status,
+            memory_tracker,
+            counted_user: Arc::new(Mutex::new(None)),
         }))
     }
 
@@ -128,6 +145,12 @@ impl Session {
     pub async fn get_shared_query_context(self: &Arc<Self>) -> Result<Arc<QueryContextShared>> {
         let discovery = self.session_mgr.get_cluster_discovery();
 
+        // The quota is checked by the user, if the session is bound to one.
+        if let Some(user) = self.session_ctx.get_current_user() {
+            self.check_user_quota(&user).await?;
+            check_memory_quota(&user, self.get_memory_usage())?;
+        }
+
         let session = self.clone();
         let cluster = discovery.discover().await?;
         let shared = QueryContextShared::try_create(session, cluster).await?;
@@ -172,10 +195,52 @@ impl Session {
             .ok_or_else(|| ErrorCode::AuthenticateFailure("unauthenticated"))
     }
 
+    /// Binds the user to the session, the settings of the user are applied if the user is new to
+    /// the session, so they override the default settings but not the ones set in the session.
     pub fn set_current_user(self: &Arc<Self>, user: UserInfo) {
+        let current = self.session_ctx.get_current_user().map(|u| u.identity());
+        if current.as_ref() != Some(&user.identity()) {
+            self.apply_user_settings(&user);
+        }
         self.session_ctx.set_current_user(user)
     }
 
+    fn apply_user_settings(self: &Arc<Self>, user: &UserInfo) {
+        for (name, value) in &user.settings {
+            if name == USER_DATABASE_SETTING {
+                self.set_current_database(value.clone());
+            } else if !self.session_settings.has_setting(name) {
+                // The setting may be set by a query of another version.
+                tracing::warn!(
+                    "Ignore unknown setting {} of user {}",
+                    name,
+                    user.identity()
+                );
+            } else if let Err(cause) =
+                self.session_settings
+                    .set_settings(name.clone(), value.clone(), false)
+            {
+                tracing::warn!(
+                    "Ignore invalid setting {} = {} of user {}: {}",
+                    name,
+                    value,
+                    user.identity(),
+                    cause
+                );
+            }
+        }
+    }
+
+    /// Checks the sessions quota of the user, who is bound or to be bound to the session.
+    ///
+    /// The sessions of this node are checked first, then the ones of all the nodes: the session
+    /// is counted for the user in the cluster-wide count, until it is destroyed or counted for
+    /// another user, see `SessionManager::count_user_session`.
+    pub async fn check_user_quota(self: &Arc<Self>, user: &UserInfo) -> Result<()> {
+        check_sessions_quota(self.session_mgr.as_ref(), &self.id, user)?;
+        self.session_mgr.count_user_session(self, user).await
+    }
+
     pub async fn validate_privilege(
         self: &Arc<Self>,
         object: &GrantObject,
@@ -213,6 +278,10 @@ impl Session {
         Arc::new(self.session_settings.clone())
     }
 
+    pub fn get_memory_tracker(self: &Arc<Self>) -> Arc<MemoryTracker> {
+        self.memory_tracker.clone()
+    }
+
     pub fn get_session_manager(self: &Arc<Self>) -> Arc<SessionManager> {
         self.session_mgr.clone()
     }
@@ -15,17 +15,29 @@
 use std::collections::HashMap;
 use std::env;
 use std::future::Future;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
+use common_base::spawn_interval;
 use common_base::tokio;
+use common_base::GlobalIORuntime;
+use common_base::IntervalTaskHandle;
+use common_base::MemoryTracker;
 use common_base::Runtime;
 use common_base::SignalStream;
+use common_base::TaskCounters;
+use common_base::TrySpawn;
 use common_contexts::DalRuntime;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_infallible::RwLock;
+use common_management::SessionCountApi;
+use common_meta_types::UserIdentity;
+use common_meta_types::UserInfo;
 use common_metrics::label_counter;
 use common_tracing::init_query_logger;
 use common_tracing::tracing;
@@ -36,6 +48,7 @@ use opendal::services::fs;
 use opendal::services::memory;
 use opendal::services::s3;
 use opendal::Accessor;
+use opendal::Layer;
 use opendal::Operator;
 use opendal::Scheme as DalSchema;
 
@@ -48,10 +61,17 @@ use crate::sessions::session_ref::SessionRef;
 use crate::sessions::ProcessInfo;
 use crate::sessions::SessionManagerStatus;
 use crate::sessions::SessionType;
+use crate::sessions::StorageLocation;
+use crate::sessions::UserSessions;
 use crate::storages::cache::CacheManager;
 use crate::users::auth::auth_mgr::AuthMgr;
 use crate::users::UserApiProvider;
 
+// The sessions counted by a node are dropped from the counts of the users, if the node does not
+// renew them for this long, e.g., it died without uncounting them.
+const USER_SESSIONS_LEASE: Duration = Duration::from_secs(60);
+const USER_SESSIONS_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(20);
+
 pub struct SessionManager {
     pub(in crate::sessions) conf: RwLock<Config>,
     pub(in crate::sessions) discovery: RwLock<Arc<ClusterDiscovery>>,
@@ -67,7 +87,17 @@ pub struct SessionManager {
         RwLock<Option<Arc<dyn tracing::Subscriber + Send + Sync>>>,
     pub status: Arc<RwLock<SessionManagerStatus>>,
     storage_operator: RwLock<Operator>,
+    // accessors of the storage locations of tables, keyed by location and its s3 options
+    location_accessors: RwLock<HashMap<StorageLocation, Arc<dyn Accessor>>>,
     storage_runtime: Arc<Runtime>,
+    // The counters of the tasks of the runtimes of all the queries.
+    query_runtime_counters: Arc<TaskCounters>,
+    // The parent of the memory trackers of the sessions.
+    memory_tracker: Arc<MemoryTracker>,
+    // The sessions of the users counted over all the nodes, for the sessions quotas.
+    session_counts: Arc<dyn SessionCountApi>,
+    // Renews the counts of this node and expires the ones of the dead nodes, stopped on drop.
+    _session_counts_task: IntervalTaskHandle,
     _guards: Vec<WorkerGuard>,
 }
 
@@ -79,13 +109,7 @@ impl SessionManager {
         // Cluster discovery.
         let discovery = ClusterDiscovery::create_global(conf.clone()).await?;
 
-        let storage_runtime = {
-            let mut storage_num_cpus = conf.storage.storage_num_cpus as usize;
-            if storage_num_cpus == 0 {
-                storage_num_cpus = std::cmp::max(1, num_cpus::get() / 2)
-            }
-            Runtime::with_worker_threads(storage_num_cpus, Some("IO-worker".to_owned()))?
-        };
+        let storage_runtime = GlobalIORuntime::init(conf.storage.storage_num_cpus as usize)?;
 
         // NOTE: Magic happens here. We will add a layer upon original storage operator
         // so that all underlying storage operations will send to storage runtime.
@@ -95,6 +119,11 @@ impl SessionManager {
 
         // User manager and init the default users.
         let user = UserApiProvider::create_global(conf.clone()).await?;
+        user.bootstrap_admin(&conf).await?;
+        let session_counts =
+            user.get_session_count_api_client(&conf.query.tenant_id, &discovery.local_id())?;
+        let session_counts_task =
+            Self::start_session_counts_task(&storage_runtime, session_counts.clone())?;
         let auth_manager = Arc::new(AuthMgr::create(conf.clone(), user.clone()).await?);
         let http_query_manager = HttpQueryManager::create_global(conf.clone()).await?;
         let max_sessions = conf.query.max_active_sessions as usize;
@@ -122,11 +151,39 @@ impl SessionManager {
             query_logger: RwLock::new(query_logger),
             status,
             storage_operator: RwLock::new(storage_operator),
-            storage_runtime: Arc::new(storage_runtime),
+            location_accessors: RwLock::new(HashMap::new()),
+            storage_runtime,
+            query_runtime_counters: Arc::new(TaskCounters::default()),
+            memory_tracker: MemoryTracker::create(None),
+            session_counts,
+            _session_counts_task: session_counts_task,
             _guards,
         }))
     }
 
+    // Renews the lease of this node in the session counts of the users it has sessions of, and
+    // drops the counts of the nodes which died without decrementing them.
+    fn start_session_counts_task(
+        runtime: &Arc<Runtime>,
+        session_counts: Arc<dyn SessionCountApi>,
+    ) -> Result<IntervalTaskHandle> {
+        spawn_interval(
+            runtime.as_ref(),
+            "user-session-counts",
+            USER_SESSIONS_HEARTBEAT_INTERVAL,
+            move || {
+                let session_counts = session_counts.clone();
+                async move {
+                    session_counts.heartbeat().await?;
+                    session_counts
+                        .expire_dead_nodes(USER_SESSIONS_LEASE)
+                        .await?;
+                    Ok(())
+                }
+            },
+        )
+    }
+
     pub fn get_conf(&self) -> Config {
         self.conf.read().clone()
     }
@@ -156,6 +213,35 @@ impl SessionManager {
         self.storage_operator.read().clone()
     }
 
+    /// Get the accessor of the storage at `location`, e.g. `fs:///data/t1` or `s3://bucket/root`.
+    ///
+    /// The accessors are cached by location, along with its s3 options. A location is checked
+    /// to be reachable, and the credential of its stage is read, when its accessor is created.
+    pub async fn get_storage_accessor_of_location(
+        self: &Arc<Self>,
+        location: &StorageLocation,
+    ) -> Result<Arc<dyn Accessor>> {
+        if let Some(accessor) = self.location_accessors.read().get(location) {
+            return Ok(accessor.clone());
+        }
+
+        let conf = self.get_conf();
+        let accessor = self.init_location_accessor(&conf, location).await?;
+        let accessor = DalRuntime::new(self.storage_runtime.inner()).layer(accessor);
+        let operator = Operator::new(accessor.clone());
+        if let Err(cause) = operator.object("/").list().await {
+            return Err(ErrorCode::StorageOther(format!(
+                "storage location {} is not reachable: {}",
+                location.location, cause
+            )));
+        }
+
+        self.location_accessors
+            .write()
+            .insert(location.clone(), accessor.clone());
+        Ok(accessor)
+    }
+
     pub fn get_storage_cache_manager(&self) -> Arc<CacheManager> {
         self.storage_cache_manager.read().clone()
     }
@@ -164,6 +250,14 @@ impl SessionManager {
         self.storage_runtime.clone()
     }
 
+    pub fn get_query_runtime_counters(&self) -> Arc<TaskCounters> {
+        self.query_runtime_counters.clone()
+    }
+
+    pub fn get_memory_tracker(&self) -> Arc<MemoryTracker> {
+        self.memory_tracker.clone()
+    }
+
     pub async fn create_session(self: &Arc<Self>, typ: SessionType) -> Result<SessionRef> {
         // TODO: maybe deadlock
         let config = self.get_conf();
@@ -261,8 +355,63 @@ impl SessionManager {
             &config.query.cluster_id,
         );
 
-        let mut sessions = self.active_sessions.write();
-        sessions.remove(session_id);
+        let session = self.active_sessions.write().remove(session_id);
+        let counted_user = session.and_then(|session| session.counted_user.lock().take());
+        if let Some(user) = counted_user {
+            let session_counts = self.session_counts.clone();
+            let uncount = self.storage_runtime.try_spawn(async move {
+                if let Err(cause) = session_counts.decrement_user_sessions(&user).await {
+                    tracing::warn!("Cannot uncount the session of user {}: {}", user, cause);
+                }
+            });
+            if let Err(cause) = uncount {
+                tracing::warn!("Cannot uncount the session {}: {}", session_id, cause);
+            }
+        }
+    }
+
+    // Counts the session for the user in the sessions of all the nodes, if the user has a
+    // sessions quota, and uncounts it for the user it was counted for before, if any.
+    //
+    // Errors with `QuotaExceeded` if the sessions of the user exceed the quota, the session is
+    // not counted then.
+    pub(in crate::sessions) async fn count_user_session(
+        &self,
+        session: &Session,
+        user: &UserInfo,
+    ) -> Result<()> {
+        let counted_user = session.counted_user.lock().clone();
+        if counted_user.as_deref() == Some(user.name.as_str()) {
+            return Ok(());
+        }
+
+        let max_sessions = user.quota.max_sessions;
+        if max_sessions != 0 {
+            let sessions = self
+                .session_counts
+                .increment_user_sessions(&user.name)
+                .await?;
+            if sessions > max_sessions {
+                self.session_counts
+                    .decrement_user_sessions(&user.name)
+                    .await?;
+                return Err(ErrorCode::QuotaExceeded(format!(
+                    "user {} has {} sessions over the nodes, exceeds the quota of {} sessions",
+                    user.identity(),
+                    sessions - 1,
+                    max_sessions
+                )));
+            }
+        }
+
+        let counted_user = (max_sessions != 0).then(|| user.name.clone());
+        let previous = std::mem::replace(&mut *session.counted_user.lock(), counted_user);
+        if let Some(previous) = previous {
+            self.session_counts
+                .decrement_user_sessions(&previous)
+                .await?;
+        }
+        Ok(())
     }
 
     pub fn graceful_shutdown(
@@ -384,6 +533,76 @@ impl SessionManager {
         Ok(Operator::new(accessor))
     }
 
+    // Storage location: <scheme>://<path>
+    //
+    // For `s3`, the path is `<bucket>[/<root>]`, the credential is the one of the stage named by
+    // the location, the endpoint and region are the ones of the location, or of the configured
+    // s3 storage if not specified.
+    //
+    // For `fs`, the path must be under the configured fs `data_path`, after the symbolic links
+    // are resolved: a table can not make the server write anywhere else on the host.
+    async fn init_location_accessor(
+        self: &Arc<Self>,
+        conf: &Config,
+        storage_location: &StorageLocation,
+    ) -> Result<Arc<dyn Accessor>> {
+        let location = storage_location.location.as_str();
+        let (scheme, path) = location.split_once("://").ok_or_else(|| {
+            ErrorCode::BadOption(format!(
+                "invalid storage location {}, expects <scheme>://<path>",
+                location
+            ))
+        })?;
+
+        let accessor: Arc<dyn Accessor> = match DalSchema::from_str(scheme)? {
+            DalSchema::Memory => memory::Backend::build().finish().await?,
+            DalSchema::S3 => {
+                let stage_name = storage_location.s3_credential_stage()?;
+                let stage = self
+                    .get_user_manager()
+                    .get_stage(&storage_location.tenant, stage_name)
+                    .await?;
+                let s3_conf = storage_location.s3_config(&conf.storage.s3, &stage)?;
+                let (bucket, root) = path.split_once('/').unwrap_or((path, ""));
+                let mut builder = s3::Backend::build();
+                builder.endpoint(&s3_conf.endpoint_url);
+                builder.region(&s3_conf.region);
+                builder.access_key_id(&s3_conf.access_key_id);
+                builder.secret_access_key(&s3_conf.secret_access_key);
+                builder.bucket(bucket);
+                if !root.is_empty() {
+                    builder.root(&format!("/{}", root));
+                }
+                builder.finish().await?
+            }
+            DalSchema::Fs => {
+                if !path.starts_with('/') {
+                    return Err(ErrorCode::BadOption(format!(
+                        "path of storage location {} must be absolute",
+                        location
+                    )));
+                }
+                let data_root = env::current_dir()?.join(&conf.storage.fs.data_path);
+                let within_data_root = Path::new(path)
+                    .components()
+                    .all(|c| !matches!(c, Component::ParentDir | Component::CurDir))
+                    && canonicalize_prefix(Path::new(path))?
+                        .starts_with(canonicalize_prefix(&data_root)?);
+                if !within_data_root {
+                    return Err(ErrorCode::BadOption(format!(
+                        "path of storage location {} must be under the fs data path {}",
+                        location,
+                        data_root.display()
+                    )));
+                }
+                fs::Backend::build().root(path).finish().await?
+            }
+            _ => return Err(ErrorCode::StorageOther("not supported storage backend")),
+        };
+
+        Ok(accessor)
+    }
+
     pub async fn reload_config(&self) -> Result<()> {
         let config = {
             let mut config = self.conf.write();
@@ -409,6 +628,7 @@ impl SessionManager {
                 .await?
                 .layer(DalRuntime::new(self.storage_runtime.inner()));
             *self.storage_operator.write() = operator;
+            self.location_accessors.write().clear();
         }
 
         {
@@ -419,6 +639,7 @@ impl SessionManager {
         // User manager and init the default users.
         let user = {
             let user = UserApiProvider::create_global(config.clone()).await?;
+            user.bootstrap_admin(&config).await?;
             *self.user_manager.write() = user.clone();
             user
         };
@@ -433,3 +654,40 @@ impl SessionManager {
         self.query_logger.write().to_owned()
     }
 }
+
+impl UserSessions for SessionManager {
+    fn count_user_sessions(&self, user: &UserIdentity, except_id: &str) -> usize {
+        let sessions = self.active_sessions.read();
+        sessions
+            .values()
+            .filter(|session| session.id != except_id)
+            .filter(|session| match session.session_ctx.get_current_user() {
+                Some(current_user) => &current_user.identity() == user,
+                None => false,
+            })
+            .count()
+    }
+}
+
+/// Resolves the symbolic links of the longest existing ancestor of `path`, the rest of it does
+/// not exist yet, thus has no links to resolve.
+fn canonicalize_prefix(path: &Path) -> Result<PathBuf> {
+    let mut existing = path;
+    let mut rest = vec![];
+    // a dangling link exists as well, and fails to be resolved
+    while existing.symlink_metadata().is_err() {
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name);
+                existing = parent;
+            }
+            _ => break,
+        }
+    }
+
+    let mut canonical = std::fs::canonicalize(existing)?;
+    for name in rest.into_iter().rev() {
+        canonical.push(name);
+    }
+    Ok(canonical)
+}
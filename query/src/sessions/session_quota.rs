@@ -0,0 +1,71 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_meta_types::UserIdentity;
+use common_meta_types::UserInfo;
+
+/// The active sessions, counted by the users bound to them.
+pub trait UserSessions {
+    /// The number of the active sessions of the user, except the session `except_id`.
+    fn count_user_sessions(&self, user: &UserIdentity, except_id: &str) -> usize;
+}
+
+/// Checks the `max_sessions` of the quota of the user, before the user is bound to the session
+/// `session_id` or a query of the session starts.
+pub fn check_sessions_quota(
+    sessions: &dyn UserSessions,
+    session_id: &str,
+    user: &UserInfo,
+) -> Result<()> {
+    let max_sessions = user.quota.max_sessions;
+    if max_sessions == 0 {
+        return Ok(());
+    }
+
+    let identity = user.identity();
+    let active_sessions = sessions.count_user_sessions(&identity, session_id) as u64;
+    if active_sessions >= max_sessions {
+        return Err(ErrorCode::QuotaExceeded(format!(
+            "user {} has {} active sessions, exceeds the quota of {} sessions",
+            identity, active_sessions, max_sessions
+        )));
+    }
+    Ok(())
+}
+
+/// Checks the `max_memory_in_bytes` of the quota of the user, before a query starts.
+pub fn check_memory_quota(user: &UserInfo, memory_usage: usize) -> Result<()> {
+    let max_memory = user.quota.max_memory_in_bytes;
+    if max_memory != 0 && memory_usage as u64 > max_memory {
+        return Err(ErrorCode::QuotaExceeded(format!(
+            "user {} uses {} bytes of memory, exceeds the quota of {} bytes",
+            user.identity(),
+            memory_usage,
+            max_memory
+        )));
+    }
+    Ok(())
+}
+
+/// The worker threads of a query of the user, `max_threads` capped by the `max_cpu` of the
+/// quota of the user.
+pub fn cpu_quota_threads(user: &UserInfo, max_threads: usize) -> usize {
+    let max_cpu = user.quota.max_cpu;
+    if max_cpu != 0 && max_threads as u64 > max_cpu {
+        return max_cpu as usize;
+    }
+    max_threads
+}
@@ -106,6 +106,30 @@ impl Settings {
                 desc: "The size of buffer in bytes for buffered reader of dal. By default, it is 1MB.",
             },
 
+            // storage_read_timeout_secs
+            SettingValue {
+                default_value: DataValue::UInt64(60),
+                user_setting: UserSetting::create("storage_read_timeout_secs", DataValue::UInt64(60)),
+                level: ScopeLevel::Session,
+                desc: "The timeout in seconds of a read from storage, 0 means no timeout. By default, it is 60 seconds.",
+            },
+
+            // storage_write_buffer_max_bytes
+            SettingValue {
+                default_value: DataValue::UInt64(256 * 1024 * 1024),
+                user_setting: UserSetting::create("storage_write_buffer_max_bytes", DataValue::UInt64(256 * 1024 * 1024)),
+                level: ScopeLevel::Session,
+                desc: "The max bytes of blocks buffered before being written to storage, 0 for unlimited. By default, it is 256MB.",
+            },
+
+            // storage_vacuum_safety_window_secs
+            SettingValue {
+                default_value: DataValue::UInt64(24 * 60 * 60),
+                user_setting: UserSetting::create("storage_vacuum_safety_window_secs", DataValue::UInt64(24 * 60 * 60)),
+                level: ScopeLevel::Session,
+                desc: "Only the orphan files older than this window in seconds are removed by vacuum. By default, it is 1 day.",
+            },
+
             // storage_backoff_init_delay_ms
             SettingValue {
                 default_value: DataValue::UInt64(5),
@@ -130,6 +154,62 @@ impl Settings {
                 desc: "The maximum elapsed time after the occ starts, beyond which there will be no more retries. By default, it is 2 minutes.",
             },
 
+            // storage_occ_max_retries
+            SettingValue {
+                default_value: DataValue::UInt64(10),
+                user_setting: UserSetting::create("storage_occ_max_retries", DataValue::UInt64(10)),
+                level: ScopeLevel::Session,
+                desc: "The maximum times of retries of the occ, when the table is changed concurrently. By default, it is 10.",
+            },
+
+            // max_rows_per_insert
+            SettingValue {
+                default_value: DataValue::UInt64(0),
+                user_setting: UserSetting::create("max_rows_per_insert", DataValue::UInt64(0)),
+                level: ScopeLevel::Session,
+                desc: "The maximum rows that one insertion may write, 0 means unlimited. By default, it is 0.",
+            },
+
+            // max_bytes_per_insert
+            SettingValue {
+                default_value: DataValue::UInt64(0),
+                user_setting: UserSetting::create("max_bytes_per_insert", DataValue::UInt64(0)),
+                level: ScopeLevel::Session,
+                desc: "The maximum bytes that one insertion may write, 0 means unlimited. By default, it is 0.",
+            },
+
+            // insert_limit_truncate
+            SettingValue {
+                default_value: DataValue::UInt64(0),
+                user_setting: UserSetting::create("insert_limit_truncate", DataValue::UInt64(0)),
+                level: ScopeLevel::Session,
+                desc: "Truncates the insertion with a warning instead of failing it, if it exceeds max_rows_per_insert or max_bytes_per_insert. By default, it is 0.",
+            },
+
+            // max_memory_usage
+            SettingValue {
+                default_value: DataValue::UInt64(0),
+                user_setting: UserSetting::create("max_memory_usage", DataValue::UInt64(0)),
+                level: ScopeLevel::Session,
+                desc: "The maximum bytes of memory tracked for the tasks of a query, 0 means unlimited. By default, it is 0.",
+            },
+
+            // max_pruning_concurrency
+            SettingValue {
+                default_value: DataValue::UInt64(10),
+                user_setting: UserSetting::create("max_pruning_concurrency", DataValue::UInt64(10)),
+                level: ScopeLevel::Session,
+                desc: "The maximum segments of a table read concurrently while pruning. By default, it is 10.",
+            },
+
+            // max_block_write_concurrency
+            SettingValue {
+                default_value: DataValue::UInt64(8),
+                user_setting: UserSetting::create("max_block_write_concurrency", DataValue::UInt64(8)),
+                level: ScopeLevel::Session,
+                desc: "The maximum blocks of a query encoded and written concurrently. By default, it is 8.",
+            },
+
             // enable_new_processor_framework
             SettingValue {
                 default_value: DataValue::UInt64(1),
@@ -235,6 +315,24 @@ impl Settings {
         self.try_get_u64(key)
     }
 
+    // Get storage read timeout in seconds.
+    pub fn get_storage_read_timeout_secs(&self) -> Result<u64> {
+        let key = "storage_read_timeout_secs";
+        self.try_get_u64(key)
+    }
+
+    // Get storage write buffer max bytes.
+    pub fn get_storage_write_buffer_max_bytes(&self) -> Result<u64> {
+        let key = "storage_write_buffer_max_bytes";
+        self.try_get_u64(key)
+    }
+
+    // Get storage vacuum safety window in seconds.
+    pub fn get_storage_vacuum_safety_window_secs(&self) -> Result<u64> {
+        let key = "storage_vacuum_safety_window_secs";
+        self.try_get_u64(key)
+    }
+
     // Get storage occ backoff init delay in ms.
     pub fn get_storage_occ_backoff_init_delay_ms(&self) -> Result<u64> {
         let key = "storage_occ_backoff_init_delay_ms";
@@ -253,6 +351,48 @@ impl Settings {
         self.try_get_u64(key)
     }
 
+    // Get storage occ max retries.
+    pub fn get_storage_occ_max_retries(&self) -> Result<u64> {
+        let key = "storage_occ_max_retries";
+        self.try_get_u64(key)
+    }
+
+    // Get max rows per insert.
+    pub fn get_max_rows_per_insert(&self) -> Result<u64> {
+        let key = "max_rows_per_insert";
+        self.try_get_u64(key)
+    }
+
+    // Get max bytes per insert.
+    pub fn get_max_bytes_per_insert(&self) -> Result<u64> {
+        let key = "max_bytes_per_insert";
+        self.try_get_u64(key)
+    }
+
+    // Get insert limit truncate.
+    pub fn get_insert_limit_truncate(&self) -> Result<u64> {
+        let key = "insert_limit_truncate";
+        self.try_get_u64(key)
+    }
+
+    // Get max memory usage of a query.
+    pub fn get_max_memory_usage(&self) -> Result<u64> {
+        let key = "max_memory_usage";
+        self.try_get_u64(key)
+    }
+
+    // Get max pruning concurrency.
+    pub fn get_max_pruning_concurrency(&self) -> Result<u64> {
+        let key = "max_pruning_concurrency";
+        self.try_get_u64(key)
+    }
+
+    // Get max block write concurrency.
+    pub fn get_max_block_write_concurrency(&self) -> Result<u64> {
+        let key = "max_block_write_concurrency";
+        self.try_get_u64(key)
+    }
+
     pub fn get_enable_new_processor_framework(&self) -> Result<u64> {
         let key = "enable_new_processor_framework";
         self.try_get_u64(key)
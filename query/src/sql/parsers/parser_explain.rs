@@ -38,6 +38,14 @@ impl<'a> DfParser<'a> {
                     self.parser.next_token();
                     ExplainType::Graph
                 }
+                "PRUNE" => {
+                    self.parser.next_token();
+                    ExplainType::Prune
+                }
+                "VERBOSE" => {
+                    self.parser.next_token();
+                    ExplainType::Verbose
+                }
                 _ => ExplainType::Syntax,
             },
             _ => ExplainType::Syntax,
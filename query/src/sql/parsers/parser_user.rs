@@ -49,12 +49,21 @@ impl<'a> DfParser<'a> {
         let (username, hostname) = self.parse_principal_name_and_host()?;
         let with_options = self.parse_user_options()?;
         let auth_option = self.parse_auth_option()?;
+        let password_expire = if self.consume_token("PASSWORD") {
+            if !self.consume_token("EXPIRE") {
+                return self.expected("EXPIRE", self.parser.peek_token());
+            }
+            true
+        } else {
+            false
+        };
 
         let create = DfCreateUser {
             if_not_exists,
             user: UserIdentity { username, hostname },
             auth_option,
             with_options,
+            password_expire,
         };
         Ok(DfStatement::CreateUser(create))
     }
@@ -79,11 +79,15 @@ impl QueryCollectPushDowns {
                 order_by = ir.order_by_expressions.clone();
             }
 
+            // keep the results of a limit query stable, if they are not ordered explicitly
+            let preserve_order = limit.is_some() && order_by.is_empty();
+
             schema.set_table_push_downs(index, Extras {
                 projection: Some(projection),
                 filters: self.require_filters.clone(),
                 limit,
                 order_by,
+                preserve_order,
             });
         }
 
@@ -15,6 +15,7 @@
 use std::sync::Arc;
 
 use common_exception::Result;
+use common_meta_types::AuthInfo;
 use common_meta_types::UserIdentity;
 use common_planners::AlterUserPlan;
 use common_planners::PlanNode;
@@ -47,17 +48,21 @@ impl AnalyzableStatement for DfAlterUser {
                 .await?
         };
 
-        let new_auth_info = if let Some(auth_option) = &self.auth_option {
+        let (new_auth_info, password) = if let Some(auth_option) = &self.auth_option {
             let auth_info = user_info
                 .auth_info
                 .alter(&auth_option.auth_type, &auth_option.by_value)?;
+            let password = match &auth_info {
+                AuthInfo::Password { .. } => auth_option.by_value.clone(),
+                _ => None,
+            };
             if user_info.auth_info == auth_info {
-                None
+                (None, None)
             } else {
-                Some(auth_info)
+                (Some(auth_info), password)
             }
         } else {
-            None
+            (None, None)
         };
 
         let mut user_option = user_info.option.clone();
@@ -74,6 +79,7 @@ impl AnalyzableStatement for DfAlterUser {
             AlterUserPlan {
                 user: user_info.identity(),
                 auth_info: new_auth_info,
+                password,
                 user_option: new_user_option,
             },
         ))))
@@ -42,6 +42,8 @@ use crate::sql::DfStatement;
 use crate::sql::PlanParser;
 use crate::sql::SQLCommon;
 use crate::sql::OPT_KEY_DATABASE_ID;
+use crate::sql::OPT_KEY_LOCATION_LAYOUT;
+use crate::storages::fuse::io::LocationLayout;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct DfCreateTable {
@@ -214,6 +216,12 @@ impl DfCreateTable {
             let db_id = db.get_db_info().database_id;
             meta.options
                 .insert(OPT_KEY_DATABASE_ID.to_owned(), db_id.to_string());
+
+            // new tables lay out their data files by date, existing ones keep their layouts
+            meta.options.insert(
+                OPT_KEY_LOCATION_LAYOUT.to_owned(),
+                LocationLayout::Dated.as_str().to_owned(),
+            );
         }
         Ok(meta)
     }
@@ -17,6 +17,7 @@ use std::sync::Arc;
 
 use common_exception::Result;
 use common_meta_types::AuthInfo;
+use common_meta_types::PasswordPolicy;
 use common_meta_types::UserIdentity;
 use common_meta_types::UserOption;
 use common_meta_types::UserOptionFlag;
@@ -90,24 +91,34 @@ pub struct DfCreateUser {
     pub user: UserIdentity,
     pub auth_option: DfAuthOption,
     pub with_options: Vec<DfUserWithOption>,
+    // The password must be changed on the first login.
+    pub password_expire: bool,
 }
 
 #[async_trait::async_trait]
 impl AnalyzableStatement for DfCreateUser {
-    #[tracing::instrument(level = "debug", skip(self, _ctx), fields(ctx.id = _ctx.get_id().as_str()))]
-    async fn analyze(&self, _ctx: Arc<QueryContext>) -> Result<AnalyzedResult> {
+    #[tracing::instrument(level = "debug", skip(self, ctx), fields(ctx.id = ctx.get_id().as_str()))]
+    async fn analyze(&self, ctx: Arc<QueryContext>) -> Result<AnalyzedResult> {
         let mut user_option = UserOption::default();
         for option in &self.with_options {
             option.apply(&mut user_option);
         }
+        let auth_info = AuthInfo::create(&self.auth_option.auth_type, &self.auth_option.by_value)?;
+        let password = match &auth_info {
+            AuthInfo::Password { .. } => self.auth_option.by_value.clone(),
+            _ => None,
+        };
+        let password_policy = PasswordPolicy {
+            must_change: self.password_expire,
+            ..Default::default()
+        };
         Ok(AnalyzedResult::SimpleQuery(Box::new(PlanNode::CreateUser(
             CreateUserPlan {
                 user: self.user.clone(),
-                auth_info: AuthInfo::create(
-                    &self.auth_option.auth_type,
-                    &self.auth_option.by_value,
-                )?,
+                auth_info,
+                password,
                 user_option,
+                password_policy,
             },
         ))))
     }
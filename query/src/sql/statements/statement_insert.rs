@@ -18,6 +18,7 @@ use common_datavalues::DataSchemaRef;
 use common_datavalues::DataSchemaRefExt;
 use common_exception::ErrorCode;
 use common_exception::Result;
+use common_functions::scalars::check_cast_types;
 use common_planners::InsertInputSource;
 use common_planners::InsertPlan;
 use common_planners::InsertValueBlock;
@@ -88,7 +89,10 @@ impl<'a> AnalyzableStatement for DfInsertStatement<'a> {
                 self.analyze_insert_values(ctx.clone(), *values_str, &schema)
                     .await
             }
-            InsertSource::Select(select) => self.analyze_insert_select(ctx.clone(), select).await,
+            InsertSource::Select(select) => {
+                self.analyze_insert_select(ctx.clone(), select, &schema)
+                    .await
+            }
         }?;
 
         Ok(AnalyzedResult::SimpleQuery(Box::new(PlanNode::Insert(
@@ -170,13 +174,34 @@ impl<'a> DfInsertStatement<'a> {
         &self,
         ctx: Arc<QueryContext>,
         source: &Query,
+        schema: &DataSchemaRef,
     ) -> Result<InsertInputSource> {
         let statement = DfQueryStatement::try_from(source.clone())?;
         let select_plan =
             PlanParser::build_plan(vec![DfStatement::Query(Box::new(statement))], ctx).await?;
+        Self::check_select_schema(&select_plan, schema)?;
         Ok(InsertInputSource::SelectPlan(Box::new(select_plan)))
     }
 
+    // The select output is casted into the insert schema while sinking, reject the columns
+    // which can never be casted before running the query.
+    fn check_select_schema(select_plan: &PlanNode, schema: &DataSchemaRef) -> Result<()> {
+        let select_schema = select_plan.schema();
+        if select_schema.fields().len() < schema.fields().len() {
+            return Err(ErrorCode::BadArguments(
+                "Fields in select statement is less than expected",
+            ));
+        }
+
+        for (select_field, field) in select_schema.fields().iter().zip(schema.fields()) {
+            check_cast_types(select_field.data_type(), field.data_type()).map_err(|cause| {
+                cause.add_message_back(format!(" (while inserting into column {})", field.name()))
+            })?;
+        }
+
+        Ok(())
+    }
+
     fn insert_schema(&self, read_table: Arc<dyn Table>) -> Result<DataSchemaRef> {
         match self.columns.is_empty() {
             true => Ok(read_table.schema()),
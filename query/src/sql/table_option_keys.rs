@@ -20,6 +20,11 @@ pub const OPT_KEY_DATABASE_ID: &str = "database_id";
 
 pub const OPT_KEY_SNAPSHOT_LOCATION: &str = "snapshot_location";
 
+/// Layout of the data locations of fuse table, see [crate::storages::fuse::io::LocationLayout]
+///
+/// Tables created without this option keep the legacy (flat) layout
+pub const OPT_KEY_LOCATION_LAYOUT: &str = "location_layout";
+
 /// Legacy table snapshot location key
 ///
 /// # Deprecated
@@ -38,6 +43,7 @@ lazy_static! {
         let mut r = HashSet::new();
         r.insert(OPT_KEY_DATABASE_ID);
         r.insert(OPT_KEY_SNAPSHOT_LOC);
+        r.insert(OPT_KEY_LOCATION_LAYOUT);
         r
     };
 
@@ -47,6 +53,7 @@ lazy_static! {
         r.insert(OPT_KEY_SNAPSHOT_LOC);
         r.insert(OPT_KEY_SNAPSHOT_LOCATION);
         r.insert(OPT_KEY_DATABASE_ID);
+        r.insert(OPT_KEY_LOCATION_LAYOUT);
         r
     };
 }
@@ -15,6 +15,7 @@
 use crate::configs::QueryConfig;
 use crate::storages::fuse::cache;
 use crate::storages::fuse::cache::MemoryCache;
+use crate::storages::fuse::cache::PruneResultCache;
 use crate::storages::fuse::cache::SegmentInfoCache;
 use crate::storages::fuse::cache::TableSnapshotCache;
 
@@ -22,6 +23,7 @@ use crate::storages::fuse::cache::TableSnapshotCache;
 pub struct CacheManager {
     table_snapshot_cache: Option<TableSnapshotCache>,
     segment_info_cache: Option<SegmentInfoCache>,
+    prune_result_cache: Option<PruneResultCache>,
     cluster_id: String,
     tenant_id: String,
 }
@@ -35,15 +37,21 @@ impl CacheManager {
             Self {
                 table_snapshot_cache: None,
                 segment_info_cache: None,
+                prune_result_cache: None,
                 cluster_id: config.cluster_id.clone(),
                 tenant_id: config.tenant_id.clone(),
             }
         } else {
             let table_snapshot_cache = Self::with_capacity(config.table_cache_snapshot_count);
             let segment_info_cache = Self::with_capacity(config.table_cache_segment_count);
+            let prune_result_cache = match config.table_cache_prune_result_mb_size {
+                0 => None,
+                mb_size => Some(PruneResultCache::new(mb_size * 1024 * 1024)),
+            };
             Self {
                 table_snapshot_cache,
                 segment_info_cache,
+                prune_result_cache,
                 cluster_id: config.cluster_id.clone(),
                 tenant_id: config.tenant_id.clone(),
             }
@@ -58,6 +66,10 @@ impl CacheManager {
         self.segment_info_cache.clone()
     }
 
+    pub fn get_prune_result_cache(&self) -> Option<PruneResultCache> {
+        self.prune_result_cache.clone()
+    }
+
     pub fn get_tenant_id(&self) -> &str {
         self.tenant_id.as_str()
     }
@@ -14,11 +14,13 @@
 
 mod memory_cache;
 mod metrics;
+mod prune_result_cache;
 
 pub use memory_cache::new_memory_cache;
 pub use memory_cache::MemoryCache;
 pub use memory_cache::SegmentInfoCache;
 pub use memory_cache::TableSnapshotCache;
+pub use prune_result_cache::PruneResultCache;
 
 pub use self::metrics::CacheDeferMetrics;
 pub use self::metrics::TenantLabel;
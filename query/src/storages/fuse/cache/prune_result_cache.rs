@@ -0,0 +1,137 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::borrow::Borrow;
+use std::mem::size_of;
+use std::sync::Arc;
+
+use common_base::tokio::sync::RwLock;
+use common_cache::Cache;
+use common_cache::DefaultHashBuilder;
+use common_cache::LruCache;
+use common_cache::Meter;
+use common_datavalues::DataValue;
+use common_planners::Expression;
+
+use crate::storages::fuse::meta::BlockMeta;
+use crate::storages::fuse::meta::ColumnId;
+use crate::storages::fuse::meta::ColumnMeta;
+use crate::storages::fuse::meta::SnapshotId;
+use crate::storages::index::ColumnStatistics;
+
+type BlockMetas = Arc<Vec<BlockMeta>>;
+
+/// Caches the blocks admitted by the pruning of table snapshots.
+///
+/// The cache has its own budget, in bytes of the estimated sizes of the cached blocks, see
+/// [PruneResultMeter]. The key includes the id of the snapshot, a new snapshot always misses.
+#[derive(Clone)]
+pub struct PruneResultCache {
+    cache: Arc<RwLock<LruCache<String, BlockMetas, DefaultHashBuilder, PruneResultMeter>>>,
+}
+
+impl PruneResultCache {
+    pub fn new(capacity_bytes: u64) -> Self {
+        Self {
+            cache: Arc::new(RwLock::new(LruCache::with_meter(
+                capacity_bytes,
+                PruneResultMeter,
+            ))),
+        }
+    }
+
+    /// The key of the pruning of the snapshot by `filters`, with the column statistics of
+    /// the `projection` columns.
+    ///
+    /// The filters are conjunctive, thus they are sorted to be independent of their order.
+    /// The key is the whole normalized filters and projection rather than a hash of them, the
+    /// results of different push-downs never collide. Nor does it with the location of a segment.
+    pub fn key(
+        snapshot_id: &SnapshotId,
+        filters: &[Expression],
+        projection: Option<&[usize]>,
+    ) -> String {
+        let mut filters = filters.iter().map(|f| f.column_name()).collect::<Vec<_>>();
+        filters.sort();
+        let projection = projection.map(|p| {
+            let mut p = p.to_vec();
+            p.sort_unstable();
+            p
+        });
+
+        // The strings are quoted and escaped by the debug format, the key is unambiguous.
+        format!(
+            "_prune_result/{}/{:?}/{:?}",
+            snapshot_id.to_simple(),
+            filters,
+            projection
+        )
+    }
+
+    /// Gets the blocks cached under `key`.
+    ///
+    /// The cache is peeked under the read lock, thus the concurrent pruning do not wait for
+    /// each other; the recency of the result is not updated.
+    pub async fn get(&self, key: &str) -> Option<Vec<BlockMeta>> {
+        let cache = self.cache.read().await;
+        cache.peek(key).map(|blocks| blocks.as_ref().clone())
+    }
+
+    pub async fn put(&self, key: String, blocks: Vec<BlockMeta>) {
+        self.cache.write().await.put(key, Arc::new(blocks));
+    }
+
+    /// The estimated size in bytes of the cached results.
+    pub async fn size(&self) -> u64 {
+        self.cache.read().await.size()
+    }
+}
+
+/// Measures the cached blocks by an estimate of their size in memory: the blocks, their
+/// locations, and the entries of their column statistics and metas.
+pub struct PruneResultMeter;
+
+impl Meter<String, BlockMetas> for PruneResultMeter {
+    type Measure = usize;
+
+    fn measure<Q: ?Sized>(&self, _: &Q, blocks: &BlockMetas) -> usize
+    where String: Borrow<Q> {
+        blocks.iter().map(block_size).sum()
+    }
+}
+
+fn block_size(block: &BlockMeta) -> usize {
+    let col_stats = block
+        .col_stats
+        .values()
+        .map(|stats| {
+            size_of::<ColumnId>()
+                + size_of::<ColumnStatistics>()
+                + value_size(&stats.min)
+                + value_size(&stats.max)
+        })
+        .sum::<usize>();
+    size_of::<BlockMeta>()
+        + block.location.0.len()
+        + col_stats
+        + block.col_metas.len() * (size_of::<ColumnId>() + size_of::<ColumnMeta>())
+}
+
+// the size of the heap allocation of the value, if it is a string, which is the common case
+fn value_size(value: &DataValue) -> usize {
+    match value {
+        DataValue::String(v) => v.len(),
+        _ => 0,
+    }
+}
@@ -15,10 +15,18 @@
 pub const FUSE_OPT_KEY_BLOCK_IN_MEM_SIZE_THRESHOLD: &str = "block_size_threshold";
 pub const FUSE_OPT_KEY_BLOCK_PER_SEGMENT: &str = "block_per_segment";
 pub const FUSE_OPT_KEY_ROW_PER_BLOCK: &str = "row_per_block";
+pub const FUSE_OPT_KEY_PARQUET_VERSION: &str = "parquet_version";
+pub const FUSE_OPT_KEY_PARQUET_COMPATIBLE: &str = "parquet_compatible";
+pub const FUSE_OPT_KEY_STORAGE_LOCATION: &str = "storage_location";
+pub const FUSE_OPT_KEY_STORAGE_S3_ENDPOINT_URL: &str = "storage_s3_endpoint_url";
+pub const FUSE_OPT_KEY_STORAGE_S3_REGION: &str = "storage_s3_region";
+pub const FUSE_OPT_KEY_STORAGE_S3_CREDENTIAL_STAGE: &str = "storage_s3_credential_stage";
+pub const FUSE_OPT_KEY_HISTOGRAM_BUCKETS: &str = "histogram_buckets";
 
 pub const FUSE_TBL_BLOCK_PREFIX: &str = "_b";
 pub const FUSE_TBL_SEGMENT_PREFIX: &str = "_sg";
 pub const FUSE_TBL_SNAPSHOT_PREFIX: &str = "_ss";
+pub const FUSE_TBL_LAST_SNAPSHOT_HINT: &str = "last_snapshot_location_hint";
 
 pub const DEFAULT_BLOCK_PER_SEGMENT: usize = 1000;
 pub const DEFAULT_BLOCK_SIZE_IN_MEM_SIZE_THRESHOLD: usize = 100 * 1024 * 1024;
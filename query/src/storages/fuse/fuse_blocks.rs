@@ -0,0 +1,79 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::sync::Arc;
+
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::Result;
+
+use super::io::MetaReaders;
+use super::FuseTable;
+use crate::sessions::QueryContext;
+
+/// Lists the blocks of the current snapshot of a fuse table, including the external ones
+/// registered by `do_import`.
+pub struct FuseBlocks<'a> {
+    pub ctx: Arc<QueryContext>,
+    pub table: &'a FuseTable,
+}
+
+impl<'a> FuseBlocks<'a> {
+    pub fn new(ctx: Arc<QueryContext>, table: &'a FuseTable) -> Self {
+        Self { ctx, table }
+    }
+
+    pub async fn get_blocks(&self) -> Result<DataBlock> {
+        let mut block_locations: Vec<Vec<u8>> = vec![];
+        let mut row_count: Vec<u64> = vec![];
+        let mut block_size: Vec<u64> = vec![];
+        let mut file_size: Vec<u64> = vec![];
+        let mut external: Vec<bool> = vec![];
+
+        let tbl = self.table;
+        if let Some(snapshot) = tbl.read_table_snapshot(self.ctx.as_ref()).await? {
+            let operator = tbl.get_operator(self.ctx.as_ref())?;
+            let reader =
+                MetaReaders::segment_info_reader_with_operator(self.ctx.as_ref(), Some(operator));
+            for (loc, ver) in &snapshot.segments {
+                let segment = reader.read(loc, None, *ver).await?;
+                for block in &segment.blocks {
+                    block_locations.push(block.location.0.clone().into_bytes());
+                    row_count.push(block.row_count);
+                    block_size.push(block.block_size);
+                    file_size.push(block.file_size);
+                    external.push(block.external);
+                }
+            }
+        }
+
+        Ok(DataBlock::create(FuseBlocks::schema(), vec![
+            Series::from_data(block_locations),
+            Series::from_data(row_count),
+            Series::from_data(block_size),
+            Series::from_data(file_size),
+            Series::from_data(external),
+        ]))
+    }
+
+    pub fn schema() -> Arc<DataSchema> {
+        DataSchemaRefExt::create(vec![
+            DataField::new("block_location", Vu8::to_data_type()),
+            DataField::new("row_count", u64::to_data_type()),
+            DataField::new("bytes_uncompressed", u64::to_data_type()),
+            DataField::new("bytes_compressed", u64::to_data_type()),
+            DataField::new("external", bool::to_data_type()),
+        ])
+    }
+}
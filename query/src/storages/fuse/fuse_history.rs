@@ -37,7 +37,9 @@ impl<'a> FuseHistory<'a> {
         let tbl = self.table;
         let snapshot_location = tbl.snapshot_loc();
         let snapshot_version = tbl.snapshot_format_version();
-        let reader = MetaReaders::table_snapshot_reader(self.ctx.as_ref());
+        let operator = tbl.get_operator(self.ctx.as_ref())?;
+        let reader =
+            MetaReaders::table_snapshot_reader_with_operator(self.ctx.as_ref(), Some(operator));
         let snapshots = reader
             .read_snapshot_history(
                 snapshot_location,
@@ -50,7 +52,7 @@ impl<'a> FuseHistory<'a> {
 
     fn snapshots_to_block(
         &self,
-        snapshots: Vec<Arc<TableSnapshot>>,
+        snapshots: Vec<(String, Arc<TableSnapshot>)>,
         lastest_snapshot_version: u64,
     ) -> Result<DataBlock> {
         let len = snapshots.len();
@@ -63,15 +65,11 @@ impl<'a> FuseHistory<'a> {
         let mut row_count: Vec<u64> = Vec::with_capacity(len);
         let mut compressed: Vec<u64> = Vec::with_capacity(len);
         let mut uncompressed: Vec<u64> = Vec::with_capacity(len);
+        let mut timestamps: Vec<Option<Vec<u8>>> = Vec::with_capacity(len);
         let mut current_snapshot_version = lastest_snapshot_version;
-        let location_generator = &self.table.meta_location_generator;
-        for s in snapshots {
+        for (loc, s) in snapshots {
             snapshot_ids.push(s.snapshot_id.to_simple().to_string().into_bytes());
-            snapshot_locations.push(
-                location_generator
-                    .snapshot_location_from_uuid(&s.snapshot_id, current_snapshot_version)?
-                    .into_bytes(),
-            );
+            snapshot_locations.push(loc.into_bytes());
             let (id, ver) = match s.prev_snapshot_id {
                 Some((id, v)) => (Some(id.to_simple().to_string().into_bytes()), v),
                 None => (None, 0),
@@ -83,6 +81,11 @@ impl<'a> FuseHistory<'a> {
             row_count.push(s.summary.row_count);
             compressed.push(s.summary.compressed_byte_size);
             uncompressed.push(s.summary.uncompressed_byte_size);
+            timestamps.push(s.timestamp.map(|t| {
+                t.format("%Y-%m-%d %H:%M:%S.%3f %z")
+                    .to_string()
+                    .into_bytes()
+            }));
             current_snapshot_version = ver;
         }
 
@@ -96,6 +99,7 @@ impl<'a> FuseHistory<'a> {
             Series::from_data(row_count),
             Series::from_data(uncompressed),
             Series::from_data(compressed),
+            Series::from_data(timestamps),
         ]))
     }
 
@@ -110,6 +114,7 @@ impl<'a> FuseHistory<'a> {
             DataField::new("row_count", u64::to_data_type()),
             DataField::new("bytes_uncompressed", u64::to_data_type()),
             DataField::new("bytes_compressed", u64::to_data_type()),
+            DataField::new_nullable("timestamp", Vu8::to_data_type()),
         ])
     }
 }
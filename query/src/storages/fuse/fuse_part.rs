@@ -49,6 +49,8 @@ pub struct FusePartInfo {
     pub nums_rows: usize,
     pub columns_meta: HashMap<usize, ColumnMeta>,
     pub compression: Compression,
+    /// Position of the block in the table, blocks are sequenced by (segment_idx, block_idx)
+    pub sequence: usize,
 }
 
 #[typetag::serde(name = "fuse")]
@@ -72,6 +74,7 @@ impl FusePartInfo {
         rows_count: u64,
         columns_meta: HashMap<usize, ColumnMeta>,
         compression: Compression,
+        sequence: usize,
     ) -> Arc<Box<dyn PartInfo>> {
         Arc::new(Box::new(FusePartInfo {
             location,
@@ -79,6 +82,7 @@ impl FusePartInfo {
             columns_meta,
             nums_rows: rows_count as usize,
             compression,
+            sequence,
         }))
     }
 
@@ -29,17 +29,26 @@ use common_planners::TruncateTablePlan;
 use common_streams::SendableDataBlockStream;
 use common_tracing::tracing;
 use futures::StreamExt;
+use opendal::Operator;
 
 use crate::pipelines::new::NewPipeline;
 use crate::sessions::QueryContext;
+use crate::sessions::StorageLocation;
 use crate::sql::OPT_KEY_DATABASE_ID;
+use crate::sql::OPT_KEY_LOCATION_LAYOUT;
 use crate::sql::OPT_KEY_SNAPSHOT_LOC;
 use crate::sql::OPT_KEY_SNAPSHOT_LOCATION;
+use crate::storages::fuse::io::BlockWriteOptions;
+use crate::storages::fuse::io::LocationLayout;
 use crate::storages::fuse::io::MetaReaders;
 use crate::storages::fuse::io::TableMetaLocationGenerator;
+use crate::storages::fuse::io::WrittenLocations;
 use crate::storages::fuse::meta::TableSnapshot;
 use crate::storages::fuse::meta::Versioned;
 use crate::storages::fuse::operations::AppendOperationLogEntry;
+use crate::storages::fuse::FUSE_OPT_KEY_HISTOGRAM_BUCKETS;
+use crate::storages::fuse::FUSE_OPT_KEY_PARQUET_COMPATIBLE;
+use crate::storages::fuse::FUSE_OPT_KEY_PARQUET_VERSION;
 use crate::storages::StorageContext;
 use crate::storages::StorageDescription;
 use crate::storages::Table;
@@ -54,12 +63,50 @@ pub struct FuseTable {
 impl FuseTable {
     pub fn try_create(_ctx: StorageContext, table_info: TableInfo) -> Result<Box<dyn Table>> {
         let storage_prefix = Self::parse_storage_prefix(&table_info)?;
+        let layout = Self::parse_location_layout(&table_info)?;
         Ok(Box::new(FuseTable {
             table_info,
-            meta_location_generator: TableMetaLocationGenerator::with_prefix(storage_prefix),
+            meta_location_generator: TableMetaLocationGenerator::with_prefix(storage_prefix)
+                .with_layout(layout),
         }))
     }
 
+    pub fn parse_location_layout(table_info: &TableInfo) -> Result<LocationLayout> {
+        match table_info.options().get(OPT_KEY_LOCATION_LAYOUT) {
+            Some(layout) => layout.parse(),
+            // tables created before the layout option was introduced
+            None => Ok(LocationLayout::Flat),
+        }
+    }
+
+    pub fn parse_block_write_options(table_info: &TableInfo) -> Result<BlockWriteOptions> {
+        let options = table_info.options();
+        let version = options
+            .get(FUSE_OPT_KEY_PARQUET_VERSION)
+            .map(|v| v.as_str())
+            .unwrap_or("2");
+        let compatible = match options.get(FUSE_OPT_KEY_PARQUET_COMPATIBLE) {
+            Some(v) => v.parse::<bool>().map_err(|_| {
+                ErrorCode::BadOption(format!(
+                    "invalid value {} of table option {}, expects true or false",
+                    v, FUSE_OPT_KEY_PARQUET_COMPATIBLE
+                ))
+            })?,
+            None => false,
+        };
+        let histogram_buckets = match options.get(FUSE_OPT_KEY_HISTOGRAM_BUCKETS) {
+            Some(v) => v.parse::<usize>().map_err(|_| {
+                ErrorCode::BadOption(format!(
+                    "invalid value {} of table option {}, expects a number",
+                    v, FUSE_OPT_KEY_HISTOGRAM_BUCKETS
+                ))
+            })?,
+            None => 0,
+        };
+        Ok(BlockWriteOptions::try_create(version, compatible)?
+            .with_histogram_buckets(histogram_buckets))
+    }
+
     pub fn parse_storage_prefix(table_info: &TableInfo) -> Result<String> {
         let table_id = table_info.ident.table_id;
         let db_id = table_info
@@ -138,7 +185,9 @@ impl Table for FuseTable {
         ctx: Arc<QueryContext>,
         stream: SendableDataBlockStream,
     ) -> Result<SendableDataBlockStream> {
-        let log_entry_stream = self.append_trunks(ctx, stream).await?;
+        let log_entry_stream = self
+            .append_trunks(ctx, stream, WrittenLocations::default())
+            .await?;
         let data_block_stream =
             log_entry_stream.map(|append_log_entry_res| match append_log_entry_res {
                 Ok(log_entry) => DataBlock::try_from(log_entry),
@@ -173,6 +222,10 @@ impl Table for FuseTable {
         self.do_optimize(ctx, keep_last_snapshot).await
     }
 
+    async fn vacuum(&self, ctx: Arc<QueryContext>, dry_run: bool) -> Result<Vec<String>> {
+        self.do_vacuum(ctx, dry_run).await
+    }
+
     async fn statistics(&self, ctx: Arc<QueryContext>) -> Result<Option<TableStatistics>> {
         let snapshot = self.read_table_snapshot(ctx.as_ref()).await?;
         Ok(snapshot.map(|s| {
@@ -182,6 +235,11 @@ impl Table for FuseTable {
                 data_length: Some(summary.uncompressed_byte_size),
                 data_length_compressed: Some(summary.compressed_byte_size),
                 index_length: None,
+                column_histograms: summary
+                    .col_stats
+                    .iter()
+                    .filter_map(|(id, stats)| stats.histogram.clone().map(|h| (*id, h)))
+                    .collect(),
             }
         }))
     }
@@ -215,7 +273,8 @@ impl FuseTable {
         ctx: &QueryContext,
     ) -> Result<Option<Arc<TableSnapshot>>> {
         if let Some(loc) = self.snapshot_loc() {
-            let reader = MetaReaders::table_snapshot_reader(ctx);
+            let operator = self.get_operator(ctx)?;
+            let reader = MetaReaders::table_snapshot_reader_with_operator(ctx, Some(operator));
             let ver = self.snapshot_format_version();
             Ok(Some(reader.read(loc.as_str(), None, ver).await?))
         } else {
@@ -223,6 +282,17 @@ impl FuseTable {
         }
     }
 
+    /// Get the operator of the storage which holds the data of this table.
+    ///
+    /// A table created with the option `storage_location` lives in a storage of its own,
+    /// other tables share the storage of the query.
+    pub fn get_operator(&self, ctx: &QueryContext) -> Result<Operator> {
+        match StorageLocation::from_table_options(&ctx.get_tenant(), self.table_info.options()) {
+            Some(location) => ctx.get_storage_operator_of_location(&location),
+            None => ctx.get_storage_operator(),
+        }
+    }
+
     pub fn meta_location_generator(&self) -> &TableMetaLocationGenerator {
         &self.meta_location_generator
     }
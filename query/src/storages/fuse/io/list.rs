@@ -0,0 +1,62 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::ErrorKind;
+
+use common_exception::Result;
+use futures::StreamExt;
+use opendal::ObjectMode;
+use opendal::Operator;
+
+/// An object found by [list_prefix]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ListedObject {
+    pub path: String,
+    /// Last modified time in seconds since the unix epoch, if the storage reports it
+    pub last_modified: Option<i64>,
+}
+
+/// Lists the objects under `prefix` recursively.
+///
+/// Both the flat and the dated location layout are covered, since sub-directories are
+/// walked into. A prefix which does not exist is treated as an empty one.
+pub async fn list_prefix(operator: &Operator, prefix: &str) -> Result<Vec<ListedObject>> {
+    let mut result = vec![];
+    let mut dirs = vec![format!("{}/", prefix.trim_end_matches('/'))];
+    while let Some(dir) = dirs.pop() {
+        let mut objects = match operator.object(&dir).list().await {
+            Ok(objects) => objects,
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(object) = objects.next().await {
+            let mut object = object?;
+            let meta = object.metadata_cached().await?;
+            match meta.mode() {
+                ObjectMode::DIR => dirs.push(meta.path().to_string()),
+                ObjectMode::FILE => {
+                    let path = meta.path().to_string();
+                    // entries of a listing do not always carry the modification time
+                    let meta = operator.object(&path).metadata().await?;
+                    result.push(ListedObject {
+                        path,
+                        last_modified: meta.last_modified().map(|t| t.unix_timestamp()),
+                    });
+                }
+                _ => continue,
+            }
+        }
+    }
+    Ok(result)
+}
@@ -14,41 +14,110 @@
 //
 
 use std::marker::PhantomData;
+use std::str::FromStr;
 
+use chrono::DateTime;
+use chrono::Utc;
 use common_datablocks::DataBlock;
+use common_exception::ErrorCode;
 use common_exception::Result;
 use uuid::Uuid;
 
 use crate::storages::fuse::constants::FUSE_TBL_BLOCK_PREFIX;
+use crate::storages::fuse::constants::FUSE_TBL_LAST_SNAPSHOT_HINT;
 use crate::storages::fuse::constants::FUSE_TBL_SEGMENT_PREFIX;
 use crate::storages::fuse::constants::FUSE_TBL_SNAPSHOT_PREFIX;
 use crate::storages::fuse::meta::SegmentInfo;
 use crate::storages::fuse::meta::SnapshotVersion;
+use crate::storages::fuse::meta::TableSnapshot;
 use crate::storages::fuse::meta::Versioned;
 
 static SNAPSHOT_V0: SnapshotVersion = SnapshotVersion::V0(PhantomData);
 static SNAPHOST_V1: SnapshotVersion = SnapshotVersion::V1(PhantomData);
 
+/// Layout of the locations of blocks, segments and snapshots
+///
+/// - `Flat`: `<table_prefix>/_b/<uuid>_v<ver>.parquet`, the legacy layout
+/// - `Dated`: `<table_prefix>/_b/<yyyy>/<mm>/<dd>/<uuid>_v<ver>.parquet`
+///
+/// A snapshot keeps the location of the previous one, so that the history can be walked
+/// whatever the layout is. For the snapshots written before that, the location is derived
+/// from the snapshot id, in the flat layout.
+///
+/// Readers always use the absolute locations kept in the meta, thus tables of
+/// different layouts can be read in the same way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LocationLayout {
+    Flat,
+    Dated,
+}
+
+impl LocationLayout {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LocationLayout::Flat => "flat",
+            LocationLayout::Dated => "dated",
+        }
+    }
+}
+
+impl FromStr for LocationLayout {
+    type Err = ErrorCode;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "flat" => Ok(LocationLayout::Flat),
+            "dated" => Ok(LocationLayout::Dated),
+            _ => Err(ErrorCode::BadOption(format!(
+                "unknown location layout {}, expects one of [flat, dated]",
+                s
+            ))),
+        }
+    }
+}
+
+/// The clock which dates the locations in the dated layout.
+pub type Clock = fn() -> DateTime<Utc>;
+
 #[derive(Clone)]
 pub struct TableMetaLocationGenerator {
     prefix: String,
+    layout: LocationLayout,
+    clock: Clock,
 }
 
 impl TableMetaLocationGenerator {
     pub fn with_prefix(prefix: String) -> Self {
-        Self { prefix }
+        Self {
+            prefix,
+            layout: LocationLayout::Flat,
+            clock: Utc::now,
+        }
+    }
+
+    pub fn with_layout(mut self, layout: LocationLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
     }
 
     pub fn prefix(&self) -> &str {
         &self.prefix
     }
 
+    pub fn layout(&self) -> LocationLayout {
+        self.layout
+    }
+
     pub fn gen_block_location(&self) -> String {
         let part_uuid = Uuid::new_v4().to_simple().to_string();
         format!(
             "{}/{}/{}_v{}.parquet",
-            &self.prefix,
-            FUSE_TBL_BLOCK_PREFIX,
+            self.dir_of(FUSE_TBL_BLOCK_PREFIX),
             part_uuid,
             DataBlock::VERSION,
         )
@@ -57,19 +126,50 @@ impl TableMetaLocationGenerator {
     pub fn gen_segment_info_location(&self) -> String where {
         let segment_uuid = Uuid::new_v4().to_simple().to_string();
         format!(
-            "{}/{}/{}_v{}.json",
-            &self.prefix,
-            FUSE_TBL_SEGMENT_PREFIX,
+            "{}/{}_v{}.json",
+            self.dir_of(FUSE_TBL_SEGMENT_PREFIX),
             segment_uuid,
             SegmentInfo::VERSION,
         )
     }
 
+    fn dir_of(&self, kind_prefix: &str) -> String {
+        match self.layout {
+            LocationLayout::Flat => format!("{}/{}", &self.prefix, kind_prefix),
+            LocationLayout::Dated => format!(
+                "{}/{}/{}",
+                &self.prefix,
+                kind_prefix,
+                (self.clock)().format("%Y/%m/%d")
+            ),
+        }
+    }
+
+    /// Location of a new snapshot of the current version.
+    pub fn gen_snapshot_location(&self, id: &Uuid) -> Result<String> {
+        let snapshot_version = SnapshotVersion::try_from(TableSnapshot::VERSION)?;
+        Ok(format!(
+            "{}/{}{}",
+            self.dir_of(FUSE_TBL_SNAPSHOT_PREFIX),
+            id.to_simple(),
+            snapshot_version.suffix(),
+        ))
+    }
+
+    /// Location of a snapshot in the flat layout, derived from its id.
     pub fn snapshot_location_from_uuid(&self, id: &Uuid, version: u64) -> Result<String> {
         let snaphost_version = SnapshotVersion::try_from(version)?;
         Ok(snaphost_version.create(id, &self.prefix))
     }
 
+    /// Location of the hint object which records the location of the last committed snapshot.
+    ///
+    /// The hint is written only after the snapshot has been committed to the meta service,
+    /// thus it never points to a snapshot that is partially written or not committed.
+    pub fn gen_last_snapshot_hint_location(&self) -> String {
+        format!("{}/{}", &self.prefix, FUSE_TBL_LAST_SNAPSHOT_HINT)
+    }
+
     pub fn snaphost_version(location: impl AsRef<str>) -> u64 {
         if location.as_ref().ends_with(SNAPHOST_V1.suffix()) {
             SNAPHOST_V1.version()
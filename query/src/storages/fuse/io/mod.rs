@@ -12,15 +12,25 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod list;
 mod locations;
 mod read;
 mod write;
 
+pub use list::list_prefix;
+pub use list::ListedObject;
+pub use locations::LocationLayout;
 pub use locations::TableMetaLocationGenerator;
+pub use read::projected_segment_key;
 pub use read::BlockReader;
 pub use read::MetaReaders;
 pub use read::SegmentInfoReader;
 pub use read::TableSnapshotReader;
 pub use write::BlockCompactor;
 pub use write::BlockStreamWriter;
+pub use write::BlockWriteOptions;
+pub use write::BoundedBlockStream;
+pub use write::InsertLimit;
 pub use write::SegmentInfoStream;
+pub use write::WriteBufferStatus;
+pub use write::WrittenLocations;
@@ -12,7 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
 use common_arrow::arrow::datatypes::Field;
 use common_arrow::arrow::datatypes::Schema;
@@ -25,6 +27,9 @@ use common_arrow::parquet::metadata::ColumnDescriptor;
 use common_arrow::parquet::metadata::SchemaDescriptor;
 use common_arrow::parquet::read::BasicDecompressor;
 use common_arrow::parquet::read::PageIterator;
+use common_base::tokio::runtime::Handle;
+use common_base::with_timeout;
+use common_base::TrySpawn;
 use common_datablocks::DataBlock;
 use common_datavalues::DataSchemaRef;
 use common_exception::ErrorCode;
@@ -50,6 +55,8 @@ pub struct BlockReader {
     arrow_schema: Arc<Schema>,
     projected_schema: DataSchemaRef,
     parquet_schema_descriptor: SchemaDescriptor,
+    // The timeout of reading a column chunk, None for no timeout.
+    read_timeout: Option<Duration>,
 }
 
 impl BlockReader {
@@ -57,6 +64,7 @@ impl BlockReader {
         operator: Operator,
         schema: DataSchemaRef,
         projection: Vec<usize>,
+        read_timeout: Option<Duration>,
     ) -> Result<Arc<BlockReader>> {
         let projected_schema = DataSchemaRef::new(schema.project(projection.clone()));
 
@@ -68,9 +76,21 @@ impl BlockReader {
             projected_schema,
             parquet_schema_descriptor,
             arrow_schema: Arc::new(arrow_schema),
+            read_timeout,
         }))
     }
 
+    async fn read_in_time<F: Future>(
+        read: F,
+        read_timeout: Option<Duration>,
+        operation: &str,
+    ) -> Result<F::Output> {
+        match read_timeout {
+            Some(read_timeout) => with_timeout(read, read_timeout, operation).await,
+            None => Ok(read.await),
+        }
+    }
+
     fn to_deserialize(
         meta: &ColumnMeta,
         chunk: Vec<u8>,
@@ -109,12 +129,15 @@ impl BlockReader {
         for index in &self.projection {
             let column_meta = &part.columns_meta[index];
             let column_reader = self.operator.object(&part.location);
+            let read_timeout = self.read_timeout;
             let fut = async move {
                 // NOTE: move chunk inside future so that alloc only
                 // happen when future is ready to go.
-                let column_chunk = column_reader
-                    .range_read(column_meta.offset..column_meta.offset + column_meta.length)
-                    .await?;
+                let range = column_meta.offset..column_meta.offset + column_meta.length;
+                let operation = format!("read column {} of '{}'", index, part.location);
+                let column_chunk =
+                    Self::read_in_time(column_reader.range_read(range), read_timeout, &operation)
+                        .await??;
                 Ok::<_, ErrorCode>(column_chunk)
             }
             .instrument(debug_span!("read_col_chunk"));
@@ -190,25 +213,36 @@ impl BlockReader {
 
             join_handlers.push(Self::read_column(
                 self.operator.object(&part.location),
+                &part.location,
                 column_meta.offset,
                 column_meta.length,
+                self.read_timeout,
             ));
         }
 
         futures::future::try_join_all(join_handlers).await
     }
 
-    async fn read_column(o: Object, offset: u64, length: u64) -> Result<Vec<u8>> {
-        let handler = common_base::tokio::spawn(async move {
+    async fn read_column(
+        o: Object,
+        location: &str,
+        offset: u64,
+        length: u64,
+        read_timeout: Option<Duration>,
+    ) -> Result<Vec<u8>> {
+        let operation = format!("read {}..{} of '{}'", offset, offset + length, location);
+        let read = async move {
             let mut chunk = vec![0; length as usize];
             let mut r = o.range_reader(offset..offset + length).await?;
             r.read_exact(&mut chunk).await?;
             Result::Ok(chunk)
-        });
+        };
+        let handler = Handle::current()
+            .try_spawn(async move { Self::read_in_time(read, read_timeout, &operation).await })?;
 
         match handler.await {
-            Ok(Ok(data)) => Ok(data),
-            Ok(Err(cause)) => Err(cause),
+            Ok(Ok(res)) => res,
+            Ok(Err(timeout)) => Err(timeout),
             Err(cause) => Err(ErrorCode::TokioError(format!(
                 "Cannot join future {:?}",
                 cause
@@ -233,6 +267,10 @@ impl BlockReader {
         match meta_compression {
             Compression::Lz4 => ParquetCompression::Lz4,
             Compression::Lz4Raw => ParquetCompression::Lz4Raw,
+            Compression::Uncompressed => ParquetCompression::Uncompressed,
+            Compression::Snappy => ParquetCompression::Snappy,
+            Compression::Gzip => ParquetCompression::Gzip,
+            Compression::Zstd => ParquetCompression::Zstd,
         }
     }
 }
@@ -90,6 +90,27 @@ where L: Loader<T> + HasTenantLabel
         }
     }
 
+    /// Gets the object cached under `key`, without loading it.
+    ///
+    /// The cache is peeked, i.e. the recency of the object is not updated.
+    pub async fn get_cached(&self, key: impl AsRef<str>) -> Option<Arc<T>> {
+        match &self.cache {
+            None => None,
+            Some(cache) => cache.read().await.peek(key.as_ref()).cloned(),
+        }
+    }
+
+    /// Puts `item` into the cache under `key`.
+    pub async fn put_cached(&self, key: impl Into<String>, item: Arc<T>) {
+        if let Some(cache) = &self.cache {
+            cache.write().await.put(key.into(), item);
+        }
+    }
+
+    pub fn loader(&self) -> &L {
+        &self.loader
+    }
+
     pub fn name(&self) -> &str {
         self.name.as_str()
     }
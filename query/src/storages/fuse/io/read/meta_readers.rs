@@ -12,12 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use common_exception::ErrorCode;
 use common_exception::Result;
 use futures::io::BufReader;
 use opendal::BytesReader;
+use opendal::Operator;
 
 use super::cached_reader::CachedReader;
 use super::cached_reader::HasTenantLabel;
@@ -26,6 +28,7 @@ use super::versioned_reader::VersionedReader;
 use crate::sessions::QueryContext;
 use crate::storages::fuse::cache::TenantLabel;
 use crate::storages::fuse::io::TableMetaLocationGenerator;
+use crate::storages::fuse::meta::ColumnId;
 use crate::storages::fuse::meta::SegmentInfo;
 use crate::storages::fuse::meta::SegmentInfoVersion;
 use crate::storages::fuse::meta::SnapshotVersion;
@@ -40,42 +43,123 @@ pub trait BufReaderProvider {
     async fn buf_reader(&self, path: &str, len: Option<u64>) -> Result<BufReader<BytesReader>>;
 }
 
-pub type SegmentInfoReader<'a> = CachedReader<SegmentInfo, &'a QueryContext>;
-pub type TableSnapshotReader<'a> = CachedReader<TableSnapshot, &'a QueryContext>;
+/// Loads the meta from the given operator, or from the default storage of the query context
+pub struct MetaLoader<'a> {
+    ctx: &'a QueryContext,
+    operator: Option<Operator>,
+}
+
+pub type SegmentInfoReader<'a> = CachedReader<SegmentInfo, MetaLoader<'a>>;
+pub type TableSnapshotReader<'a> = CachedReader<TableSnapshot, MetaLoader<'a>>;
 
 pub struct MetaReaders;
 
 impl MetaReaders {
     pub fn segment_info_reader(ctx: &QueryContext) -> SegmentInfoReader {
+        Self::segment_info_reader_with_operator(ctx, None)
+    }
+
+    pub fn table_snapshot_reader(ctx: &QueryContext) -> TableSnapshotReader {
+        Self::table_snapshot_reader_with_operator(ctx, None)
+    }
+
+    /// Reads segments from `operator`, e.g. the storage of a table which has its own location
+    pub fn segment_info_reader_with_operator(
+        ctx: &QueryContext,
+        operator: Option<Operator>,
+    ) -> SegmentInfoReader {
         SegmentInfoReader::new(
             ctx.get_storage_cache_manager().get_table_segment_cache(),
-            ctx,
+            MetaLoader { ctx, operator },
             "SEGMENT_INFO_CACHE".to_owned(),
         )
     }
 
-    pub fn table_snapshot_reader(ctx: &QueryContext) -> TableSnapshotReader {
+    /// Reads snapshots from `operator`, e.g. the storage of a table which has its own location
+    pub fn table_snapshot_reader_with_operator(
+        ctx: &QueryContext,
+        operator: Option<Operator>,
+    ) -> TableSnapshotReader {
         TableSnapshotReader::new(
             ctx.get_storage_cache_manager().get_table_snapshot_cache(),
-            ctx,
+            MetaLoader { ctx, operator },
             "SNAPSHOT_CACHE".to_owned(),
         )
     }
 }
 
+impl<'a> SegmentInfoReader<'a> {
+    /// Reads the segment at `location`, with the column statistics of the `projection`
+    /// columns only.
+    ///
+    /// A cached complete segment is returned as is. Otherwise, the partial segment is cached
+    /// under a key of both `location` and `projection`, see [projected_segment_key].
+    pub async fn read_projected(
+        &self,
+        location: impl AsRef<str>,
+        len_hint: Option<u64>,
+        version: u64,
+        projection: &HashSet<ColumnId>,
+    ) -> Result<Arc<SegmentInfo>> {
+        let location = location.as_ref();
+        if let Some(segment) = self.get_cached(location).await {
+            return Ok(segment);
+        }
+        let key = projected_segment_key(location, projection);
+        if let Some(segment) = self.get_cached(&key).await {
+            return Ok(segment);
+        }
+        let version = SegmentInfoVersion::try_from(version)?;
+        let reader = self.loader().buf_reader(location, len_hint).await?;
+        let segment = Arc::new(version.read_projected(reader, projection).await?);
+        self.put_cached(key, segment.clone()).await;
+        Ok(segment)
+    }
+}
+
+/// The cache key of the segment at `location` projected to the columns of `projection`,
+/// which never collides with a location.
+pub fn projected_segment_key(location: &str, projection: &HashSet<ColumnId>) -> String {
+    let mut ids = projection.iter().collect::<Vec<_>>();
+    ids.sort();
+    let ids = ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+    format!("{}#col_stats={}", location, ids.join(","))
+}
+
 impl<'a> TableSnapshotReader<'a> {
     pub async fn read_snapshot_history(
         &self,
         latest_snapshot_location: Option<impl AsRef<str>>,
         format_version: u64,
         location_gen: TableMetaLocationGenerator,
-    ) -> Result<Vec<Arc<TableSnapshot>>> {
+    ) -> Result<Vec<(String, Arc<TableSnapshot>)>> {
+        self.read_chain(
+            latest_snapshot_location,
+            format_version,
+            location_gen,
+            usize::MAX,
+        )
+        .await
+    }
+
+    /// Walks the chain of snapshots backward from the latest one, by the previous snapshot
+    /// locations (or ids), and returns at most `limit` snapshots with their locations, the
+    /// latest one first.
+    ///
+    /// The walk stops at a snapshot which has been purged.
+    pub async fn read_chain(
+        &self,
+        latest_snapshot_location: Option<impl AsRef<str>>,
+        format_version: u64,
+        location_gen: TableMetaLocationGenerator,
+        limit: usize,
+    ) -> Result<Vec<(String, Arc<TableSnapshot>)>> {
         let mut snapshots = vec![];
         if let Some(loc) = latest_snapshot_location {
             let mut ver = format_version;
             let mut loc = loc.as_ref().to_string();
-            loop {
-                let snapshot = match self.read(loc, None, ver).await {
+            while snapshots.len() < limit {
+                let snapshot = match self.read(loc.as_str(), None, ver).await {
                     Ok(s) => s,
                     Err(e) => {
                         if e.code() == ErrorCode::storage_not_found_code() {
@@ -85,13 +169,23 @@ impl<'a> TableSnapshotReader<'a> {
                         }
                     }
                 };
-                if let Some((id, v)) = snapshot.prev_snapshot_id {
-                    ver = v;
-                    loc = location_gen.snapshot_location_from_uuid(&id, v)?;
-                    snapshots.push(snapshot);
-                } else {
-                    snapshots.push(snapshot);
-                    break;
+                let prev = match snapshot.prev_snapshot_id {
+                    Some((id, v)) => {
+                        let prev_loc = match &snapshot.prev_snapshot_location {
+                            Some(prev_loc) => prev_loc.clone(),
+                            None => location_gen.snapshot_location_from_uuid(&id, v)?,
+                        };
+                        Some((prev_loc, v))
+                    }
+                    None => None,
+                };
+                snapshots.push((loc, snapshot));
+                match prev {
+                    Some((prev_loc, v)) => {
+                        loc = prev_loc;
+                        ver = v;
+                    }
+                    None => break,
                 }
             }
         }
@@ -128,9 +222,12 @@ where T: BufReaderProvider + Sync
 }
 
 #[async_trait::async_trait]
-impl BufReaderProvider for &QueryContext {
+impl BufReaderProvider for MetaLoader<'_> {
     async fn buf_reader(&self, path: &str, len: Option<u64>) -> Result<BufReader<BytesReader>> {
-        let operator = self.get_storage_operator()?;
+        let operator = match &self.operator {
+            Some(operator) => operator.clone(),
+            None => self.ctx.get_storage_operator()?,
+        };
         let object = operator.object(path);
 
         let len = match len {
@@ -143,7 +240,7 @@ impl BufReaderProvider for &QueryContext {
         };
 
         let reader = object.range_reader(..len).await?;
-        let read_buffer_size = self.get_settings().get_storage_read_buffer_size()?;
+        let read_buffer_size = self.ctx.get_settings().get_storage_read_buffer_size()?;
         Ok(BufReader::with_capacity(
             read_buffer_size as usize,
             Box::new(reader),
@@ -151,9 +248,9 @@ impl BufReaderProvider for &QueryContext {
     }
 }
 
-impl HasTenantLabel for &QueryContext {
+impl HasTenantLabel for MetaLoader<'_> {
     fn tenant_label(&self) -> TenantLabel {
-        ctx_tenant_label(self)
+        ctx_tenant_label(self.ctx)
     }
 }
 
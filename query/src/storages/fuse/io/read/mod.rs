@@ -18,6 +18,7 @@ mod meta_readers;
 mod versioned_reader;
 
 pub use block_reader::BlockReader;
+pub use meta_readers::projected_segment_key;
 pub use meta_readers::MetaReaders;
 pub use meta_readers::SegmentInfoReader;
 pub use meta_readers::TableSnapshotReader;
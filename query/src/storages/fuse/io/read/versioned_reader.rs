@@ -12,13 +12,18 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
+use std::collections::HashSet;
 use std::marker::PhantomData;
 
 use common_exception::Result;
 use futures::AsyncRead;
 use serde::de::DeserializeOwned;
+use serde::de::DeserializeSeed;
 use serde_json::from_slice;
 
+use crate::storages::fuse::meta::ColumnId;
+use crate::storages::fuse::meta::Projected;
+use crate::storages::fuse::meta::ProjectedDeserialize;
 use crate::storages::fuse::meta::SegmentInfo;
 use crate::storages::fuse::meta::SegmentInfoVersion;
 use crate::storages::fuse::meta::SnapshotVersion;
@@ -42,6 +47,25 @@ impl VersionedReader<TableSnapshot> for SnapshotVersion {
     }
 }
 
+impl SegmentInfoVersion {
+    /// Reads the segment, keeping the column statistics of the `projection` columns only.
+    pub async fn read_projected<R>(
+        &self,
+        reader: R,
+        projection: &HashSet<ColumnId>,
+    ) -> Result<SegmentInfo>
+    where
+        R: AsyncRead + Unpin + Send,
+    {
+        let buffer = read_all(reader).await?;
+        let r = match self {
+            SegmentInfoVersion::V1(v) => decode_projected(&buffer, v, projection)?,
+            SegmentInfoVersion::V0(v) => decode_projected(&buffer, v, projection)?.into(),
+        };
+        Ok(r)
+    }
+}
+
 #[async_trait::async_trait]
 impl VersionedReader<SegmentInfo> for SegmentInfoVersion {
     async fn read<R>(&self, reader: R) -> Result<SegmentInfo>
@@ -54,13 +78,38 @@ impl VersionedReader<SegmentInfo> for SegmentInfoVersion {
     }
 }
 
-async fn load<R, T>(mut reader: R, _v: &PhantomData<T>) -> Result<T>
+async fn load<R, T>(reader: R, v: &PhantomData<T>) -> Result<T>
 where
     T: DeserializeOwned,
     R: AsyncRead + Unpin + Send,
 {
+    let buffer = read_all(reader).await?;
+    decode(&buffer, v)
+}
+
+fn decode<T>(buffer: &[u8], _v: &PhantomData<T>) -> Result<T>
+where T: DeserializeOwned {
+    Ok(from_slice::<T>(buffer)?)
+}
+
+fn decode_projected<T>(
+    buffer: &[u8],
+    _v: &PhantomData<T>,
+    projection: &HashSet<ColumnId>,
+) -> Result<T>
+where
+    T: ProjectedDeserialize,
+{
+    let mut deserializer = serde_json::Deserializer::from_slice(buffer);
+    let r = Projected::<T>::new(projection).deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(r)
+}
+
+async fn read_all<R>(mut reader: R) -> Result<Vec<u8>>
+where R: AsyncRead + Unpin + Send {
     let mut buffer: Vec<u8> = vec![];
     use futures::AsyncReadExt;
     reader.read_to_end(&mut buffer).await?;
-    Ok(from_slice::<T>(&buffer)?)
+    Ok(buffer)
 }
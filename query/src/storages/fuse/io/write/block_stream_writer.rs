@@ -16,10 +16,15 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use common_arrow::parquet::FileMetaData;
+use common_base::AbortRegistration;
+use common_base::MemoryTracker;
+use common_base::Runtime;
+use common_base::TrySpawn;
 use common_datablocks::DataBlock;
 use common_datavalues::DataSchema;
 use common_exception::ErrorCode;
 use common_exception::Result;
+use common_infallible::Mutex;
 use common_streams::SendableDataBlockStream;
 use futures::stream::try_unfold;
 use futures::stream::Stream;
@@ -28,6 +33,7 @@ use futures::TryStreamExt;
 use opendal::Operator;
 
 use super::block_writer;
+use super::block_writer::BlockWriteOptions;
 use crate::storages::fuse::io::TableMetaLocationGenerator;
 use crate::storages::fuse::meta::ColumnId;
 use crate::storages::fuse::meta::ColumnMeta;
@@ -38,23 +44,58 @@ use crate::storages::fuse::statistics::StatisticsAccumulator;
 pub type SegmentInfoStream =
     std::pin::Pin<Box<dyn futures::stream::Stream<Item = Result<SegmentInfo>> + Send>>;
 
-pub struct BlockStreamWriter {
+/// Locations of the objects written by an append, shared with its caller.
+///
+/// A location is recorded before the object is written, so that the objects left behind by
+/// a failed append, including the one being written when it failed, can be cleaned up.
+#[derive(Clone, Debug, Default)]
+pub struct WrittenLocations {
+    locations: Arc<Mutex<Vec<String>>>,
+}
+
+impl WrittenLocations {
+    pub fn record(&self, location: String) {
+        self.locations.lock().push(location);
+    }
+
+    pub fn locations(&self) -> Vec<String> {
+        self.locations.lock().clone()
+    }
+}
+
+pub struct BlockStreamWriter<RT = Runtime> {
+    runtime: Arc<RT>,
+    abort: AbortRegistration,
     num_block_threshold: usize,
     data_accessor: Operator,
     data_schema: Arc<DataSchema>,
     number_of_blocks_accumulated: usize,
     statistics_accumulator: Option<StatisticsAccumulator>,
     meta_locations: TableMetaLocationGenerator,
+    write_options: BlockWriteOptions,
+    written: WrittenLocations,
 }
 
-impl BlockStreamWriter {
+impl<RT> BlockStreamWriter<RT>
+where RT: TrySpawn + Send + Sync + 'static
+{
+    /// The blocks are encoded on the blocking threads of the `runtime`.
+    ///
+    /// The writing stops with `AbortedQuery` once `abort` is aborted, the block being written is
+    /// dropped without waiting for the storage.
+    ///
+    /// The locations of the blocks are recorded in `written`, whether the writing succeeds or not.
     pub async fn write_block_stream(
+        runtime: Arc<RT>,
+        abort: AbortRegistration,
         data_accessor: Operator,
         block_stream: SendableDataBlockStream,
         data_schema: Arc<DataSchema>,
         row_per_block: usize,
         block_per_segment: usize,
         meta_locations: TableMetaLocationGenerator,
+        write_options: BlockWriteOptions,
+        written: WrittenLocations,
     ) -> SegmentInfoStream {
         // filter out empty blocks
         let block_stream =
@@ -71,10 +112,14 @@ impl BlockStreamWriter {
         // Write out the blocks.
         // And transform the stream of DataBlocks into Stream of SegmentInfo at the same time.
         let block_writer = BlockStreamWriter::new(
+            runtime,
+            abort,
             block_per_segment,
             data_accessor,
             data_schema,
             meta_locations,
+            write_options,
+            written,
         );
         let segments = Self::transform(Box::pin(block_stream), block_writer);
 
@@ -82,18 +127,26 @@ impl BlockStreamWriter {
     }
 
     pub fn new(
+        runtime: Arc<RT>,
+        abort: AbortRegistration,
         num_block_threshold: usize,
         data_accessor: Operator,
         data_schema: Arc<DataSchema>,
         meta_locations: TableMetaLocationGenerator,
+        write_options: BlockWriteOptions,
+        written: WrittenLocations,
     ) -> Self {
         Self {
+            runtime,
+            abort,
             num_block_threshold,
             data_accessor,
             data_schema,
             number_of_blocks_accumulated: 0,
             statistics_accumulator: None,
             meta_locations,
+            write_options,
+            written,
         }
     }
 
@@ -126,15 +179,31 @@ impl BlockStreamWriter {
     }
 
     async fn write_block(&mut self, block: DataBlock) -> Result<Option<SegmentInfo>> {
-        let mut acc = self.statistics_accumulator.take().unwrap_or_default();
+        self.abort.check_aborted()?;
+        let histogram_buckets = self.write_options.histogram_buckets;
+        let mut acc = self
+            .statistics_accumulator
+            .take()
+            .unwrap_or_else(|| StatisticsAccumulator::with_histogram_buckets(histogram_buckets));
         let partial_acc = acc.begin(&block)?;
+        // the block is accounted to the memory of the query until it is written
+        let _memory = MemoryTracker::alloc_task_memory(block.memory_size() as i64);
+        MemoryTracker::check_task_memory()?;
         let schema = block.schema().to_arrow();
         let location = self.meta_locations.gen_block_location();
-        let (file_size, file_meta_data) =
-            block_writer::write_block(&schema, block, self.data_accessor.clone(), &location)
-                .await?;
+        self.written.record(location.clone());
+        let write_block = block_writer::write_block(
+            self.runtime.as_ref(),
+            &schema,
+            block,
+            self.data_accessor.clone(),
+            &location,
+            &self.write_options,
+        );
+        let (file_size, file_meta_data) = self.abort.abortable(write_block).await??;
         let col_metas = Self::column_metas(&file_meta_data)?;
-        acc = partial_acc.end(file_size, location, col_metas);
+        let compression = self.write_options.meta_compression();
+        acc = partial_acc.end(file_size, location, col_metas, compression);
         self.number_of_blocks_accumulated += 1;
         if self.number_of_blocks_accumulated >= self.num_block_threshold {
             let summary = acc.summary(self.data_schema.as_ref())?;
@@ -224,7 +293,9 @@ pub trait Compactor<S, T> {
 }
 
 #[async_trait::async_trait]
-impl Compactor<DataBlock, SegmentInfo> for BlockStreamWriter {
+impl<RT> Compactor<DataBlock, SegmentInfo> for BlockStreamWriter<RT>
+where RT: TrySpawn + Send + Sync + 'static
+{
     async fn compact(&mut self, s: DataBlock) -> Result<Option<SegmentInfo>> {
         self.write_block(s).await
     }
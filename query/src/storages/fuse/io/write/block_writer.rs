@@ -20,27 +20,122 @@ use common_arrow::arrow::io::parquet::write::WriteOptions;
 use common_arrow::arrow::io::parquet::write::*;
 use common_arrow::parquet::encoding::Encoding;
 use common_arrow::parquet::FileMetaData;
+use common_base::JoinHandleExt;
+use common_base::TrySpawn;
 use common_datablocks::DataBlock;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use opendal::Operator;
 
-pub async fn write_block(
+use crate::storages::fuse::meta;
+
+/// Options of the parquet files that blocks are written to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockWriteOptions {
+    pub version: Version,
+    /// Avoids the features that some readers (e.g. older Spark) can not handle, so that
+    /// the files could be read from the storage directly by them:
+    ///
+    /// - the LZ4_RAW codec, SNAPPY is used instead
+    /// - the V2 data pages, V1 pages are written whatever `version` is
+    /// - the delta encodings, PLAIN is used instead (it is not used in either mode yet)
+    ///
+    /// The logical types come from the table schema, e.g. the unsigned integers, which some
+    /// old readers reject as well, are not covered by this option.
+    pub compatible: bool,
+    /// Buckets of the histograms kept in the column statistics, 0 to not collect them
+    pub histogram_buckets: usize,
+}
+
+impl Default for BlockWriteOptions {
+    fn default() -> Self {
+        Self {
+            version: Version::V2,
+            compatible: false,
+            histogram_buckets: 0,
+        }
+    }
+}
+
+impl BlockWriteOptions {
+    pub fn try_create(version: &str, compatible: bool) -> Result<Self> {
+        let version = match version {
+            "1" => Version::V1,
+            "2" => Version::V2,
+            _ => {
+                return Err(ErrorCode::BadOption(format!(
+                    "unknown parquet version {}, expects one of [1, 2]",
+                    version
+                )))
+            }
+        };
+        Ok(Self {
+            version,
+            compatible,
+            histogram_buckets: 0,
+        })
+    }
+
+    pub fn with_histogram_buckets(mut self, histogram_buckets: usize) -> Self {
+        self.histogram_buckets = histogram_buckets;
+        self
+    }
+
+    /// The compression recorded in the block meta, which the readers rely on
+    pub fn meta_compression(&self) -> meta::Compression {
+        if self.compatible {
+            meta::Compression::Snappy
+        } else {
+            meta::Compression::Lz4Raw
+        }
+    }
+
+    fn write_options(&self) -> WriteOptions {
+        let (compression, version) = if self.compatible {
+            (Compression::Snappy, Version::V1)
+        } else {
+            (Compression::Lz4Raw, self.version)
+        };
+        WriteOptions {
+            write_statistics: false,
+            compression,
+            version,
+        }
+    }
+}
+
+/// Writes the block to the `location`, the parquet encoding runs on a blocking thread of the `spawner`.
+pub async fn write_block<S: TrySpawn>(
+    spawner: &S,
     arrow_schema: &ArrowSchema,
     block: DataBlock,
     data_accessor: Operator,
     location: &str,
+    write_options: &BlockWriteOptions,
 ) -> Result<(u64, FileMetaData)> {
-    let options = WriteOptions {
-        write_statistics: false,
-        compression: Compression::Lz4Raw,
-        version: Version::V2,
-    };
+    let arrow_schema = arrow_schema.clone();
+    let write_options = *write_options;
+    let (buf, result) = spawner
+        .try_spawn_blocking(move || encode_block(&arrow_schema, block, &write_options))?
+        .err_into()
+        .await??;
+
+    data_accessor.object(location).write(buf).await?;
+
+    Ok(result)
+}
+
+fn encode_block(
+    arrow_schema: &ArrowSchema,
+    block: DataBlock,
+    write_options: &BlockWriteOptions,
+) -> Result<(Vec<u8>, (u64, FileMetaData))> {
+    let options = write_options.write_options();
     let batch = Chunk::try_from(block)?;
     let encodings: Vec<_> = arrow_schema
         .fields
         .iter()
-        .map(|f| col_encoding(&f.data_type))
+        .map(|f| col_encoding(&f.data_type, write_options.compatible))
         .collect();
 
     let iter = vec![Ok(batch)];
@@ -53,12 +148,11 @@ pub async fn write_block(
         common_arrow::write_parquet_file(&mut buf, row_groups, arrow_schema.clone(), options)
             .map_err(|e| ErrorCode::ParquetError(e.to_string()))?;
 
-    data_accessor.object(location).write(buf).await?;
-
-    Ok(result)
+    Ok((buf, result))
 }
 
-fn col_encoding(_data_type: &ArrowDataType) -> Encoding {
+fn col_encoding(_data_type: &ArrowDataType, _compatible: bool) -> Encoding {
+    // NOTE: the delta encodings must not be used if compatible
     // Although encoding does work, parquet2 has not implemented decoding of DeltaLengthByteArray yet, we fallback to Plain
     // From parquet2: Decoding "DeltaLengthByteArray"-encoded required V2 pages is not yet implemented for Binary.
     //
@@ -0,0 +1,125 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use async_stream::stream;
+use common_base::tokio::sync::mpsc;
+use common_base::tokio::sync::Semaphore;
+use common_base::MemoryTracker;
+use common_base::TrySpawn;
+use common_exception::Result;
+use common_streams::SendableDataBlockStream;
+use futures::StreamExt;
+
+/// Bytes of the blocks that are produced, but not yet pulled by the appender.
+#[derive(Default, Debug)]
+pub struct WriteBufferStatus {
+    buffered_bytes: AtomicUsize,
+    peak_buffered_bytes: AtomicUsize,
+}
+
+impl WriteBufferStatus {
+    pub fn buffered_bytes(&self) -> usize {
+        self.buffered_bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn peak_buffered_bytes(&self) -> usize {
+        self.peak_buffered_bytes.load(Ordering::Relaxed)
+    }
+
+    fn inc(&self, size: usize) {
+        let current = self.buffered_bytes.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak_buffered_bytes
+            .fetch_max(current, Ordering::Relaxed);
+    }
+
+    fn dec(&self, size: usize) {
+        self.buffered_bytes.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+/// A byte-budgeted channel between the block producing pipeline and the appender.
+///
+/// The input stream is drained by a task spawned by `spawner`. The buffered blocks are charged
+/// to the memory tracker of that task until the appender pulls them, and the producing fails with
+/// `MemoryExceeded` if the tracker exceeds its limit.
+/// Once the buffered bytes reach `max_buffered_bytes`, the producer awaits until
+/// the appender pulls some blocks out of the channel.
+///
+/// A block larger than the cap is admitted alone, and a cap of 0 disables the budget.
+pub struct BoundedBlockStream;
+
+impl BoundedBlockStream {
+    pub fn try_create<S: TrySpawn>(
+        spawner: &S,
+        input: SendableDataBlockStream,
+        max_buffered_bytes: usize,
+    ) -> Result<(SendableDataBlockStream, Arc<WriteBufferStatus>)> {
+        let status = Arc::new(WriteBufferStatus::default());
+        if max_buffered_bytes == 0 {
+            return Ok((input, status));
+        }
+
+        // permits of tokio semaphore are acquired in u32
+        let cap = max_buffered_bytes.min(u32::MAX as usize);
+        let budget = Arc::new(Semaphore::new(cap));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        let producer_status = status.clone();
+        spawner.try_spawn(async move {
+            let mut input = input;
+            while let Some(item) = input.next().await {
+                let memory_size = match &item {
+                    Ok(block) => block.memory_size(),
+                    Err(_) => 0,
+                };
+                let size = memory_size.min(cap);
+                let permit = match budget.clone().acquire_many_owned(size as u32).await {
+                    Ok(permit) => permit,
+                    Err(_) => break,
+                };
+                let memory = MemoryTracker::alloc_task_memory(memory_size as i64);
+                let item = match MemoryTracker::check_task_memory() {
+                    Ok(_) => item,
+                    Err(e) => Err(e),
+                };
+                producer_status.inc(size);
+                let is_err = item.is_err();
+                if tx.send((item, permit, memory, size)).is_err() {
+                    // the appender has gone away
+                    break;
+                }
+                if is_err {
+                    break;
+                }
+            }
+        })?;
+
+        let consumer_status = status.clone();
+        let output = stream! {
+            while let Some((item, permit, memory, size)) = rx.recv().await {
+                // bytes leave the buffer before the budget is given back to the producer
+                consumer_status.dec(size);
+                drop(memory);
+                drop(permit);
+                yield item;
+            }
+        };
+
+        Ok((Box::pin(output), status))
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_stream::stream;
+use common_exception::ErrorCode;
+use common_streams::SendableDataBlockStream;
+use common_tracing::tracing;
+use futures::StreamExt;
+
+/// Limits of the rows and bytes that one insertion may write, 0 means unlimited.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct InsertLimit {
+    pub max_rows: usize,
+    pub max_bytes: usize,
+    /// Once a limit is exceeded, keeps the data within the limits (and logs a warning),
+    /// instead of failing the insertion.
+    pub truncate: bool,
+}
+
+impl InsertLimit {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_rows == 0 && self.max_bytes == 0
+    }
+
+    /// Cuts the `input` off, once the limits are exceeded.
+    ///
+    /// The input is not pulled any more after that, so that an unbounded source stops
+    /// producing, and no more blocks are written.
+    pub fn apply(self, input: SendableDataBlockStream) -> SendableDataBlockStream {
+        if self.is_unlimited() {
+            return input;
+        }
+
+        let output = stream! {
+            let mut input = input;
+            let mut rows = 0;
+            let mut bytes = 0;
+            while let Some(item) = input.next().await {
+                let block = match item {
+                    Ok(block) => block,
+                    Err(e) => {
+                        yield Err(e);
+                        break;
+                    }
+                };
+
+                let num_rows = block.num_rows();
+                let memory_size = block.memory_size();
+                let rows_exceeded = self.max_rows > 0 && rows + num_rows > self.max_rows;
+                let bytes_exceeded = self.max_bytes > 0 && bytes + memory_size > self.max_bytes;
+                if !rows_exceeded && !bytes_exceeded {
+                    rows += num_rows;
+                    bytes += memory_size;
+                    yield Ok(block);
+                    continue;
+                }
+
+                if !self.truncate {
+                    yield Err(ErrorCode::InsertLimitExceeded(format!(
+                        "insertion exceeds the limits, max_rows_per_insert {}, max_bytes_per_insert {}",
+                        self.max_rows, self.max_bytes
+                    )));
+                    break;
+                }
+
+                // keeps the leading rows of the block which are within the limits
+                let mut keep = num_rows;
+                if rows_exceeded {
+                    keep = keep.min(self.max_rows - rows);
+                }
+                if bytes_exceeded {
+                    let row_size = (memory_size / num_rows.max(1)).max(1);
+                    keep = keep.min((self.max_bytes - bytes) / row_size);
+                }
+                tracing::warn!(
+                    "insertion truncated at {} rows, max_rows_per_insert {}, max_bytes_per_insert {}",
+                    rows + keep,
+                    self.max_rows,
+                    self.max_bytes
+                );
+                if keep > 0 {
+                    yield Ok(block.slice(0, keep));
+                }
+                break;
+            }
+        };
+
+        Box::pin(output)
+    }
+}
@@ -14,8 +14,15 @@
 
 mod block_stream_writer;
 mod block_writer;
+mod bounded_block_stream;
+mod insert_limit;
 
 // for testing only
 pub use block_stream_writer::BlockCompactor;
 pub use block_stream_writer::BlockStreamWriter;
 pub use block_stream_writer::SegmentInfoStream;
+pub use block_stream_writer::WrittenLocations;
+pub use block_writer::BlockWriteOptions;
+pub use bounded_block_stream::BoundedBlockStream;
+pub use bounded_block_stream::WriteBufferStatus;
+pub use insert_limit::InsertLimit;
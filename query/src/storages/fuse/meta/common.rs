@@ -47,6 +47,12 @@ where Self: Sized
 pub enum Compression {
     Lz4,
     Lz4Raw,
+    // the following algos are never used in the write path, they are
+    // kept for blocks imported from external parquet files.
+    Uncompressed,
+    Snappy,
+    Gzip,
+    Zstd,
 }
 
 impl Compression {
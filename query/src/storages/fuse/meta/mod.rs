@@ -17,6 +17,7 @@ mod common;
 
 /// Re-exports meta data structures of current version, i.e. v1
 mod current;
+mod projection;
 mod v0;
 mod v1;
 mod versions;
@@ -28,5 +29,7 @@ pub use common::SnapshotId;
 pub use common::Statistics;
 pub use common::Versioned;
 pub use current::*;
+pub use projection::Projected;
+pub use projection::ProjectedDeserialize;
 pub use versions::SegmentInfoVersion;
 pub use versions::SnapshotVersion;
@@ -0,0 +1,369 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::DeserializeSeed;
+use serde::de::Error;
+use serde::de::IgnoredAny;
+use serde::de::MapAccess;
+use serde::de::SeqAccess;
+use serde::de::Visitor;
+use serde::Deserializer;
+
+use crate::storages::fuse::meta::common::ColumnId;
+use crate::storages::fuse::meta::common::Compression;
+use crate::storages::fuse::meta::common::Statistics;
+use crate::storages::fuse::meta::v0;
+use crate::storages::fuse::meta::v1;
+use crate::storages::index::ColumnStatistics;
+
+/// Meta which could be deserialized with the column statistics projected to some columns.
+pub trait ProjectedDeserialize: Sized {
+    fn deserialize_projected<'de, D>(
+        deserializer: D,
+        projection: &HashSet<ColumnId>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+}
+
+/// A [DeserializeSeed] of `T`, which keeps the column statistics of the `projection`
+/// columns only; the statistics of other columns are skipped instead of being materialized.
+pub struct Projected<'a, T> {
+    projection: &'a HashSet<ColumnId>,
+    _t: PhantomData<T>,
+}
+
+impl<'a, T> Projected<'a, T> {
+    pub fn new(projection: &'a HashSet<ColumnId>) -> Self {
+        Self {
+            projection,
+            _t: PhantomData,
+        }
+    }
+
+    fn of<U>(&self) -> Projected<'a, U> {
+        Projected::new(self.projection)
+    }
+}
+
+impl<'de, 'a, T> DeserializeSeed<'de> for Projected<'a, T>
+where T: ProjectedDeserialize
+{
+    type Value = T;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<T, D::Error>
+    where D: Deserializer<'de> {
+        T::deserialize_projected(deserializer, self.projection)
+    }
+}
+
+impl<T> ProjectedDeserialize for Vec<T>
+where T: ProjectedDeserialize
+{
+    fn deserialize_projected<'de, D>(
+        deserializer: D,
+        projection: &HashSet<ColumnId>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(SeqVisitor(Projected::<T>::new(projection)))
+    }
+}
+
+struct SeqVisitor<'a, T>(Projected<'a, T>);
+
+impl<'de, 'a, T> Visitor<'de> for SeqVisitor<'a, T>
+where T: ProjectedDeserialize
+{
+    type Value = Vec<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where A: SeqAccess<'de> {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element_seed(self.0.of::<T>())? {
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+impl ProjectedDeserialize for HashMap<ColumnId, ColumnStatistics> {
+    fn deserialize_projected<'de, D>(
+        deserializer: D,
+        projection: &HashSet<ColumnId>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(ColStatsVisitor { projection })
+    }
+}
+
+struct ColStatsVisitor<'a> {
+    projection: &'a HashSet<ColumnId>,
+}
+
+impl<'de, 'a> Visitor<'de> for ColStatsVisitor<'a> {
+    type Value = HashMap<ColumnId, ColumnStatistics>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of column statistics")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where A: MapAccess<'de> {
+        let mut col_stats = HashMap::with_capacity(self.projection.len());
+        while let Some(id) = map.next_key::<ColumnId>()? {
+            if self.projection.contains(&id) {
+                col_stats.insert(id, map.next_value()?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(col_stats)
+    }
+}
+
+impl ProjectedDeserialize for Statistics {
+    fn deserialize_projected<'de, D>(
+        deserializer: D,
+        projection: &HashSet<ColumnId>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(StatisticsVisitor(Projected::new(projection)))
+    }
+}
+
+struct StatisticsVisitor<'a>(Projected<'a, Statistics>);
+
+impl<'de, 'a> Visitor<'de> for StatisticsVisitor<'a> {
+    type Value = Statistics;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("struct Statistics")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where A: MapAccess<'de> {
+        let mut row_count = None;
+        let mut block_count = None;
+        let mut uncompressed_byte_size = None;
+        let mut compressed_byte_size = None;
+        let mut col_stats = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "row_count" => row_count = Some(map.next_value()?),
+                "block_count" => block_count = Some(map.next_value()?),
+                "uncompressed_byte_size" => uncompressed_byte_size = Some(map.next_value()?),
+                "compressed_byte_size" => compressed_byte_size = Some(map.next_value()?),
+                "col_stats" => col_stats = Some(map.next_value_seed(self.0.of())?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(Statistics {
+            row_count: row_count.ok_or_else(|| A::Error::missing_field("row_count"))?,
+            block_count: block_count.ok_or_else(|| A::Error::missing_field("block_count"))?,
+            uncompressed_byte_size: uncompressed_byte_size
+                .ok_or_else(|| A::Error::missing_field("uncompressed_byte_size"))?,
+            compressed_byte_size: compressed_byte_size
+                .ok_or_else(|| A::Error::missing_field("compressed_byte_size"))?,
+            col_stats: col_stats.ok_or_else(|| A::Error::missing_field("col_stats"))?,
+        })
+    }
+}
+
+impl ProjectedDeserialize for v1::SegmentInfo {
+    fn deserialize_projected<'de, D>(
+        deserializer: D,
+        projection: &HashSet<ColumnId>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (blocks, summary) =
+            deserializer.deserialize_map(SegmentVisitor(Projected::new(projection)))?;
+        Ok(v1::SegmentInfo::new(blocks, summary))
+    }
+}
+
+impl ProjectedDeserialize for v0::SegmentInfo {
+    fn deserialize_projected<'de, D>(
+        deserializer: D,
+        projection: &HashSet<ColumnId>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (blocks, summary) =
+            deserializer.deserialize_map(SegmentVisitor(Projected::new(projection)))?;
+        Ok(v0::SegmentInfo { blocks, summary })
+    }
+}
+
+/// Visits the blocks and the summary of a segment. The format version of v1, if any, is
+/// ignored: it is implied by the type `B` of the blocks.
+struct SegmentVisitor<'a, B>(Projected<'a, B>);
+
+impl<'de, 'a, B> Visitor<'de> for SegmentVisitor<'a, B>
+where B: ProjectedDeserialize
+{
+    type Value = (Vec<B>, Statistics);
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("struct SegmentInfo")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where A: MapAccess<'de> {
+        let mut blocks = None;
+        let mut summary = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "blocks" => blocks = Some(map.next_value_seed(self.0.of::<Vec<B>>())?),
+                "summary" => summary = Some(map.next_value_seed(self.0.of::<Statistics>())?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok((
+            blocks.ok_or_else(|| A::Error::missing_field("blocks"))?,
+            summary.ok_or_else(|| A::Error::missing_field("summary"))?,
+        ))
+    }
+}
+
+impl ProjectedDeserialize for v1::BlockMeta {
+    fn deserialize_projected<'de, D>(
+        deserializer: D,
+        projection: &HashSet<ColumnId>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(BlockMetaVisitor(Projected::new(projection)))
+    }
+}
+
+impl ProjectedDeserialize for v0::BlockMeta {
+    fn deserialize_projected<'de, D>(
+        deserializer: D,
+        projection: &HashSet<ColumnId>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(BlockMetaVisitor(Projected::new(projection)))
+    }
+}
+
+struct BlockMetaVisitor<'a, T>(Projected<'a, T>);
+
+impl<'de, 'a> Visitor<'de> for BlockMetaVisitor<'a, v1::BlockMeta> {
+    type Value = v1::BlockMeta;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("struct BlockMeta")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where A: MapAccess<'de> {
+        let mut row_count = None;
+        let mut block_size = None;
+        let mut file_size = None;
+        let mut col_stats = None;
+        let mut col_metas = None;
+        let mut location = None;
+        let mut compression = None;
+        let mut external = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "row_count" => row_count = Some(map.next_value()?),
+                "block_size" => block_size = Some(map.next_value()?),
+                "file_size" => file_size = Some(map.next_value()?),
+                "col_stats" => col_stats = Some(map.next_value_seed(self.0.of())?),
+                "col_metas" => col_metas = Some(map.next_value()?),
+                "location" => location = Some(map.next_value()?),
+                "compression" => compression = Some(map.next_value()?),
+                "external" => external = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(v1::BlockMeta {
+            row_count: row_count.ok_or_else(|| A::Error::missing_field("row_count"))?,
+            block_size: block_size.ok_or_else(|| A::Error::missing_field("block_size"))?,
+            file_size: file_size.ok_or_else(|| A::Error::missing_field("file_size"))?,
+            col_stats: col_stats.ok_or_else(|| A::Error::missing_field("col_stats"))?,
+            col_metas: col_metas.ok_or_else(|| A::Error::missing_field("col_metas"))?,
+            location: location.ok_or_else(|| A::Error::missing_field("location"))?,
+            compression: compression.unwrap_or_else(Compression::legacy),
+            external: external.unwrap_or_default(),
+        })
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for BlockMetaVisitor<'a, v0::BlockMeta> {
+    type Value = v0::BlockMeta;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("struct BlockMeta")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where A: MapAccess<'de> {
+        let mut row_count = None;
+        let mut block_size = None;
+        let mut file_size = None;
+        let mut col_stats = None;
+        let mut col_metas = None;
+        let mut location = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "row_count" => row_count = Some(map.next_value()?),
+                "block_size" => block_size = Some(map.next_value()?),
+                "file_size" => file_size = Some(map.next_value()?),
+                "col_stats" => col_stats = Some(map.next_value_seed(self.0.of())?),
+                "col_metas" => col_metas = Some(map.next_value()?),
+                "location" => location = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(v0::BlockMeta {
+            row_count: row_count.ok_or_else(|| A::Error::missing_field("row_count"))?,
+            block_size: block_size.ok_or_else(|| A::Error::missing_field("block_size"))?,
+            file_size: file_size.ok_or_else(|| A::Error::missing_field("file_size"))?,
+            col_stats: col_stats.ok_or_else(|| A::Error::missing_field("col_stats"))?,
+            col_metas: col_metas.ok_or_else(|| A::Error::missing_field("col_metas"))?,
+            location: location.ok_or_else(|| A::Error::missing_field("location"))?,
+        })
+    }
+}
@@ -17,4 +17,5 @@ mod snapshot;
 
 pub use segment::BlockMeta;
 pub use segment::SegmentInfo;
+pub use snapshot::SegmentDigest;
 pub use snapshot::TableSnapshot;
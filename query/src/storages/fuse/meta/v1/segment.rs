@@ -57,6 +57,11 @@ pub struct BlockMeta {
     /// used in the write path.
     #[serde(default = "Compression::legacy")]
     pub compression: Compression,
+
+    /// Set for the blocks imported from external files: the files belong to the user, they
+    /// are read as the other blocks, but never removed by purge or vacuum.
+    #[serde(default)]
+    pub external: bool,
 }
 
 impl SegmentInfo {
@@ -95,6 +100,7 @@ impl From<v0::BlockMeta> for BlockMeta {
             col_metas: s.col_metas,
             location: (s.location.path, DataBlock::VERSION),
             compression: Compression::Lz4,
+            external: false,
         }
     }
 }
@@ -12,15 +12,22 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
+use std::collections::HashMap;
+
+use chrono::DateTime;
+use chrono::Duration;
+use chrono::Utc;
 use common_datavalues::DataSchema;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::storages::fuse::meta::common::ColumnId;
 use crate::storages::fuse::meta::common::FormatVersion;
 use crate::storages::fuse::meta::common::Location;
 use crate::storages::fuse::meta::common::SnapshotId;
 use crate::storages::fuse::meta::common::Statistics;
 use crate::storages::fuse::meta::common::Versioned;
+use crate::storages::index::ColumnStatistics;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TableSnapshot {
@@ -30,8 +37,17 @@ pub struct TableSnapshot {
     /// id of snapshot
     pub snapshot_id: SnapshotId,
 
+    /// commit time of snapshot, `None` for the snapshots written before it was recorded
+    #[serde(default)]
+    pub timestamp: Option<DateTime<Utc>>,
+
     pub prev_snapshot_id: Option<(SnapshotId, FormatVersion)>,
 
+    /// location of the previous snapshot, `None` for the snapshots written before it was
+    /// recorded, whose previous snapshots are located by their ids
+    #[serde(default)]
+    pub prev_snapshot_location: Option<String>,
+
     /// For each snapshot, we keep a schema for it (in case of schema evolution)
     pub schema: DataSchema,
 
@@ -43,26 +59,110 @@ pub struct TableSnapshot {
     /// We rely on background merge tasks to keep merging segments, so that
     /// this the size of this vector could be kept reasonable
     pub segments: Vec<Location>,
+
+    /// Digests of the segments, one for each of `segments` in the same order.
+    ///
+    /// `None` for the snapshots written before the digests were recorded.
+    #[serde(default)]
+    pub segment_digests: Option<Vec<SegmentDigest>>,
+
+    /// Set by `TRUNCATE ... PURGE`: the snapshots before this one, and the files only they
+    /// refer to, are removed by vacuum once this snapshot is older than the safety window.
+    #[serde(default)]
+    pub purge_history: bool,
+}
+
+/// A compact summary of a segment, kept in the snapshot next to the location of the segment,
+/// so that a segment may be pruned without reading it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SegmentDigest {
+    pub row_count: u64,
+    pub block_count: u64,
+    /// min/max of at most [SegmentDigest::MAX_COLUMNS] columns, without histograms
+    pub col_stats: HashMap<ColumnId, ColumnStatistics>,
+}
+
+impl SegmentDigest {
+    pub const MAX_COLUMNS: usize = 8;
+
+    /// Digests the summary of a segment. The leading columns having min/max are designated.
+    pub fn from_summary(summary: &Statistics) -> Self {
+        let mut col_ids = summary
+            .col_stats
+            .iter()
+            .filter(|(_, stats)| !stats.min_max_skipped)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        col_ids.sort_unstable();
+
+        let col_stats = col_ids
+            .into_iter()
+            .take(Self::MAX_COLUMNS)
+            .map(|id| {
+                let stats = &summary.col_stats[&id];
+                (id, ColumnStatistics {
+                    histogram: None,
+                    ..stats.clone()
+                })
+            })
+            .collect();
+
+        Self {
+            row_count: summary.row_count,
+            block_count: summary.block_count,
+            col_stats,
+        }
+    }
 }
 
 impl TableSnapshot {
+    /// The timestamp of the new snapshot is kept after the one of the previous snapshot,
+    /// so that the timestamps increase along the chain even if the clocks of nodes skew.
     pub fn new(
         snapshot_id: SnapshotId,
+        prev_timestamp: &Option<DateTime<Utc>>,
         prev_snapshot_id: Option<(SnapshotId, FormatVersion)>,
         schema: DataSchema,
         summary: Statistics,
         segments: Vec<Location>,
     ) -> Self {
+        let now = Utc::now();
+        let timestamp = match prev_timestamp {
+            Some(prev) if *prev >= now => *prev + Duration::milliseconds(1),
+            _ => now,
+        };
         Self {
             format_version: TableSnapshot::VERSION,
             snapshot_id,
+            timestamp: Some(timestamp),
             prev_snapshot_id,
+            prev_snapshot_location: None,
             schema,
             summary,
             segments,
+            segment_digests: None,
+            purge_history: false,
         }
     }
 
+    /// Sets the digests of the segments, which are ignored unless there is one for each segment.
+    pub fn with_segment_digests(mut self, segment_digests: Option<Vec<SegmentDigest>>) -> Self {
+        self.segment_digests = segment_digests.filter(|d| d.len() == self.segments.len());
+        self
+    }
+
+    /// Sets the location of the previous snapshot, see [TableSnapshot::prev_snapshot_location].
+    pub fn with_prev_snapshot_location(mut self, prev_snapshot_location: Option<String>) -> Self {
+        self.prev_snapshot_location = prev_snapshot_location;
+        self
+    }
+
+    /// Marks the history before this snapshot to be purged, see [TableSnapshot::purge_history].
+    pub fn with_purge_history(mut self, purge_history: bool) -> Self {
+        self.purge_history = purge_history;
+        self
+    }
+
     pub fn format_version(&self) -> u64 {
         self.format_version
     }
@@ -75,10 +175,14 @@ impl From<v0::TableSnapshot> for TableSnapshot {
         Self {
             format_version: TableSnapshot::VERSION,
             snapshot_id: s.snapshot_id,
+            timestamp: None,
             prev_snapshot_id: s.prev_snapshot_id.map(|id| (id, 0)),
+            prev_snapshot_location: None,
             schema: s.schema,
             summary: s.summary,
             segments: s.segments.into_iter().map(|l| (l, 0)).collect(),
+            segment_digests: None,
+            purge_history: false,
         }
     }
 }
@@ -14,6 +14,7 @@
 
 pub mod cache;
 mod constants;
+mod fuse_blocks;
 mod fuse_history;
 mod fuse_part;
 mod fuse_table;
@@ -25,6 +26,7 @@ pub mod statistics;
 mod table_functions;
 
 pub use constants::*;
+pub use fuse_blocks::FuseBlocks;
 pub use fuse_history::FuseHistory;
 pub use fuse_table::FuseTable;
 pub use table_functions::FuseHistoryTable;
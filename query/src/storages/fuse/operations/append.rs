@@ -20,10 +20,17 @@ use async_stream::stream;
 use common_cache::Cache;
 use common_exception::Result;
 use common_streams::SendableDataBlockStream;
+use common_tracing::tracing;
 use futures::StreamExt;
+use opendal::Operator;
 
 use crate::sessions::QueryContext;
+use crate::storages::fuse::cache::SegmentInfoCache;
 use crate::storages::fuse::io::BlockStreamWriter;
+use crate::storages::fuse::io::BoundedBlockStream;
+use crate::storages::fuse::io::InsertLimit;
+use crate::storages::fuse::io::WrittenLocations;
+use crate::storages::fuse::meta::SegmentInfo;
 use crate::storages::fuse::operations::AppendOperationLogEntry;
 use crate::storages::fuse::FuseTable;
 use crate::storages::fuse::DEFAULT_BLOCK_PER_SEGMENT;
@@ -35,26 +42,41 @@ pub type AppendOperationLogEntryStream =
     std::pin::Pin<Box<dyn futures::stream::Stream<Item = Result<AppendOperationLogEntry>> + Send>>;
 
 impl FuseTable {
+    /// The locations of the blocks and segments are recorded in `written` before they are
+    /// written, if the append fails, they are the objects left behind (orphans).
     #[inline]
     pub async fn append_trunks(
         &self,
         ctx: Arc<QueryContext>,
         stream: SendableDataBlockStream,
+        written: WrittenLocations,
     ) -> Result<AppendOperationLogEntryStream> {
         let rows_per_block = self.get_option(FUSE_OPT_KEY_ROW_PER_BLOCK, DEFAULT_ROW_PER_BLOCK);
 
         let block_per_seg =
             self.get_option(FUSE_OPT_KEY_BLOCK_PER_SEGMENT, DEFAULT_BLOCK_PER_SEGMENT);
 
-        let da = ctx.get_storage_operator()?;
+        let write_options = Self::parse_block_write_options(&self.table_info)?;
+
+        let da = self.get_operator(ctx.as_ref())?;
+
+        // back-pressure the producer if the appender can not keep up with it
+        let max_buffered_bytes = ctx.get_settings().get_storage_write_buffer_max_bytes()? as usize;
+        let stream = Self::insert_limit(ctx.as_ref())?.apply(stream);
+        let (stream, buffer_status) =
+            BoundedBlockStream::try_create(ctx.as_ref(), stream, max_buffered_bytes)?;
 
         let mut segment_stream = BlockStreamWriter::write_block_stream(
+            ctx.get_storage_write_runtime()?,
+            ctx.get_abort_registration(),
             da.clone(),
             stream,
             self.table_info.schema().clone(),
             rows_per_block,
             block_per_seg,
             self.meta_location_generator().clone(),
+            write_options,
+            written.clone(),
         )
         .await;
 
@@ -66,29 +88,58 @@ impl FuseTable {
                 let log_entry_res = match segment {
                     Ok(seg) => {
                         let seg_loc = locs.gen_segment_info_location();
-                        let bytes = serde_json::to_vec(&seg)?;
-                        da.object(&seg_loc)
-                        .write(bytes)
-                        .await?;
-                        let seg = Arc::new(seg);
-                        let log_entry = AppendOperationLogEntry::new(seg_loc.clone(), seg.clone());
-                        if let Some(ref cache) = segment_info_cache {
-                            let cache = &mut cache.write().await;
-                            cache.put(seg_loc, seg);
-                        }
-
-                        Ok(log_entry)
-                    },
+                        written.record(seg_loc.clone());
+                        Self::write_segment(&da, seg_loc, seg, &segment_info_cache).await
+                    }
                     Err(err) => Err(err),
                 };
-                yield(log_entry_res);
+                if log_entry_res.is_err() {
+                    tracing::warn!(
+                        "append failed, objects written but not committed (orphans): {:?}",
+                        written.locations()
+                    );
+                    yield log_entry_res;
+                    break;
+                }
+                yield log_entry_res;
             }
+            tracing::debug!(
+                "append ended, peak of the write buffer {} bytes, max {} bytes",
+                buffer_status.peak_buffered_bytes(),
+                max_buffered_bytes
+            );
         };
 
         Ok(Box::pin(log_entries))
     }
 
-    fn get_option<T: FromStr>(&self, opt_key: &str, default: T) -> T {
+    async fn write_segment(
+        da: &Operator,
+        seg_loc: String,
+        seg: SegmentInfo,
+        segment_info_cache: &Option<SegmentInfoCache>,
+    ) -> Result<AppendOperationLogEntry> {
+        let bytes = serde_json::to_vec(&seg)?;
+        da.object(&seg_loc).write(bytes).await?;
+        let seg = Arc::new(seg);
+        let log_entry = AppendOperationLogEntry::new(seg_loc.clone(), seg.clone());
+        if let Some(cache) = segment_info_cache {
+            let cache = &mut cache.write().await;
+            cache.put(seg_loc, seg);
+        }
+        Ok(log_entry)
+    }
+
+    fn insert_limit(ctx: &QueryContext) -> Result<InsertLimit> {
+        let settings = ctx.get_settings();
+        Ok(InsertLimit {
+            max_rows: settings.get_max_rows_per_insert()? as usize,
+            max_bytes: settings.get_max_bytes_per_insert()? as usize,
+            truncate: settings.get_insert_limit_truncate()? != 0,
+        })
+    }
+
+    pub(crate) fn get_option<T: FromStr>(&self, opt_key: &str, default: T) -> T {
         self.table_info
             .options()
             .get(opt_key)
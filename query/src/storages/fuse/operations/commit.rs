@@ -30,13 +30,16 @@ use common_meta_types::TableInfo;
 use common_meta_types::UpsertTableOptionReply;
 use common_meta_types::UpsertTableOptionReq;
 use common_tracing::tracing;
+use opendal::Operator;
 use uuid::Uuid;
 
 use crate::catalogs::Catalog;
 use crate::sessions::QueryContext;
 use crate::sql::OPT_KEY_SNAPSHOT_LOC;
 use crate::sql::OPT_KEY_SNAPSHOT_LOCATION;
+use crate::storages::fuse::io::TableMetaLocationGenerator;
 use crate::storages::fuse::meta::Location;
+use crate::storages::fuse::meta::SegmentDigest;
 use crate::storages::fuse::meta::SegmentInfo;
 use crate::storages::fuse::meta::Statistics;
 use crate::storages::fuse::meta::TableSnapshot;
@@ -48,6 +51,12 @@ use crate::storages::fuse::FuseTable;
 use crate::storages::Table;
 
 impl FuseTable {
+    /// Commits the new segments by a new snapshot, optimistically.
+    ///
+    /// The table meta is updated only if the table has not been changed since it was loaded.
+    /// If it has been, the new segments are rebased on the latest snapshot, and the commit
+    /// is retried, with backoff. An overwrite (e.g. the compaction) can not be rebased,
+    /// since it replaces the segments it has seen only, thus it fails immediately.
     pub async fn do_commit(
         &self,
         ctx: Arc<QueryContext>,
@@ -74,6 +83,9 @@ impl FuseTable {
         // By default, it is 2 minutes
         let max_elapsed = Duration::from_millis(settings.get_storage_occ_backoff_max_elapsed_ms()?);
 
+        // The maximum times of retries, 10 by default.
+        let max_retries = settings.get_storage_occ_max_retries()?;
+
         // see https://aws.amazon.com/blogs/architecture/exponential-backoff-and-jitter/ for more
         // informations. (The strategy that crate backoff implements is “Equal Jitter”)
 
@@ -92,8 +104,20 @@ impl FuseTable {
                 .await
             {
                 Ok(_) => break Ok(()),
+                Err(e) if e.code() == ErrorCode::table_version_mismatched_code() && overwrite => {
+                    break Err(ErrorCode::TableVersionMismatched(format!(
+                        "the overwrite can not be committed, table {} (identity {}) has been changed concurrently",
+                        tbl.table_info.name.as_str(),
+                        tbl.table_info.ident,
+                    )));
+                }
                 Err(e) if e.code() == ErrorCode::table_version_mismatched_code() => {
-                    match backoff.next_backoff() {
+                    let next_backoff = if retry_times < max_retries {
+                        backoff.next_backoff()
+                    } else {
+                        None
+                    };
+                    match next_backoff {
                         Some(d) => {
                             let name = tbl.table_info.name.clone();
                             tracing::warn!(
@@ -154,35 +178,46 @@ impl FuseTable {
             .into_iter()
             .map(|loc| (loc, SegmentInfo::VERSION))
             .collect();
+        let digests = operation_log
+            .iter()
+            .map(|entry| SegmentDigest::from_summary(&entry.segment_info.summary))
+            .collect();
         let new_snapshot = if overwrite {
             TableSnapshot::new(
                 Uuid::new_v4(),
+                &prev.as_ref().and_then(|v| v.timestamp),
                 prev.as_ref().map(|v| (v.snapshot_id, prev_version)),
                 schema,
                 summary,
                 segments,
             )
+            .with_segment_digests(Some(digests))
         } else {
             Self::merge_table_operations(
                 self.table_info.meta.schema.as_ref(),
                 prev,
                 prev_version,
                 segments,
+                digests,
                 summary,
             )?
         };
 
-        let uuid = new_snapshot.snapshot_id;
+        let new_snapshot = new_snapshot.with_prev_snapshot_location(self.snapshot_loc());
         let snapshot_loc = self
             .meta_location_generator()
-            .snapshot_location_from_uuid(&uuid, TableSnapshot::VERSION)?;
+            .gen_snapshot_location(&new_snapshot.snapshot_id)?;
         let bytes = serde_json::to_vec(&new_snapshot)?;
-        let operator = ctx.get_storage_operator()?;
+        let operator = self.get_operator(ctx)?;
         operator.object(&snapshot_loc).write(bytes).await?;
 
         Self::commit_to_meta_server(ctx, self.get_table_info(), snapshot_loc.clone()).await?;
         ctx.get_write_progress().incr(&progress_values);
 
+        // the snapshot is committed, it is safe to expose it by the hint now
+        Self::write_last_snapshot_hint(&operator, self.meta_location_generator(), &snapshot_loc)
+            .await;
+
         if let Some(snapshot_cache) = ctx.get_storage_cache_manager().get_table_snapshot_cache() {
             let cache = &mut snapshot_cache.write().await;
             cache.put(snapshot_loc, Arc::new(new_snapshot));
@@ -196,6 +231,7 @@ impl FuseTable {
         previous: Option<Arc<TableSnapshot>>,
         prev_version: u64,
         mut new_segments: Vec<Location>,
+        mut new_digests: Vec<SegmentDigest>,
         statistics: Statistics,
     ) -> Result<TableSnapshot> {
         // 1. merge stats with previous snapshot, if any
@@ -205,21 +241,32 @@ impl FuseTable {
         } else {
             statistics
         };
+        let prev_timestamp = previous.as_ref().and_then(|v| v.timestamp);
         let prev_snapshot_id = previous.as_ref().map(|v| (v.snapshot_id, prev_version));
 
         // 2. merge segment locations with previous snapshot, if any
-        if let Some(snapshot) = &previous {
-            let mut segments = snapshot.segments.clone();
-            new_segments.append(&mut segments)
+        //    the digests are kept only if the previous snapshot has them as well
+        let digests = match &previous {
+            Some(snapshot) => {
+                let mut segments = snapshot.segments.clone();
+                new_segments.append(&mut segments);
+                snapshot.segment_digests.clone().map(|mut digests| {
+                    new_digests.append(&mut digests);
+                    new_digests
+                })
+            }
+            None => Some(new_digests),
         };
 
         let new_snapshot = TableSnapshot::new(
             Uuid::new_v4(),
+            &prev_timestamp,
             prev_snapshot_id,
             schema.clone(),
             stats,
             new_segments,
-        );
+        )
+        .with_segment_digests(digests);
         Ok(new_snapshot)
     }
 
@@ -276,6 +323,46 @@ impl FuseTable {
         Ok((seg_locs, s))
     }
 
+    /// Writes the location of the last committed snapshot into the hint object.
+    ///
+    /// The meta service is the source of truth of the current snapshot, the hint is only used
+    /// while bootstrapping or listing the history, thus failures are logged and ignored.
+    pub(crate) async fn write_last_snapshot_hint(
+        operator: &Operator,
+        location_generator: &TableMetaLocationGenerator,
+        last_snapshot_location: &str,
+    ) {
+        let hint_path = location_generator.gen_last_snapshot_hint_location();
+        if let Err(e) = operator
+            .object(&hint_path)
+            .write(last_snapshot_location.as_bytes())
+            .await
+        {
+            tracing::warn!(
+                "write last snapshot hint failure. hint location {}, error {:?}",
+                hint_path,
+                e
+            );
+        }
+    }
+
+    /// Reads the location of the last committed snapshot from the hint object, if any.
+    pub async fn read_last_snapshot_hint(&self, ctx: &QueryContext) -> Result<Option<String>> {
+        let hint_path = self
+            .meta_location_generator
+            .gen_last_snapshot_hint_location();
+        let operator = self.get_operator(ctx)?;
+        let object = operator.object(&hint_path);
+        if !object.is_exist().await? {
+            return Ok(None);
+        }
+        let bytes = object.read().await?;
+        let location = String::from_utf8(bytes).map_err(|e| {
+            ErrorCode::LogicalError(format!("invalid last snapshot hint {}, {}", hint_path, e))
+        })?;
+        Ok(Some(location))
+    }
+
     // check if there are any fuse table legacy options
     fn gather_legacy_options(
         table_info: &TableInfo,
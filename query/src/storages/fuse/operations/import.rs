@@ -0,0 +1,330 @@
+//  Copyright 2021 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common_arrow::arrow::io::parquet::read::infer_schema;
+use common_arrow::arrow::io::parquet::read::read_metadata_async;
+use common_arrow::arrow::io::parquet::read::schema::FileMetaData;
+use common_arrow::parquet::compression::Compression as ParquetCompression;
+use common_arrow::parquet::metadata::ColumnChunkMetaData;
+use common_arrow::parquet::metadata::RowGroupMetaData;
+use common_arrow::parquet::schema::types::PhysicalType;
+use common_arrow::parquet::statistics::BinaryStatistics;
+use common_arrow::parquet::statistics::BooleanStatistics;
+use common_arrow::parquet::statistics::PrimitiveStatistics;
+use common_arrow::parquet::types::NativeType;
+use common_cache::Cache;
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+
+use crate::sessions::QueryContext;
+use crate::storages::fuse::meta::BlockMeta;
+use crate::storages::fuse::meta::ColumnId;
+use crate::storages::fuse::meta::ColumnMeta;
+use crate::storages::fuse::meta::Compression;
+use crate::storages::fuse::meta::SegmentInfo;
+use crate::storages::fuse::meta::Statistics;
+use crate::storages::fuse::meta::Versioned;
+use crate::storages::fuse::operations::AppendOperationLogEntry;
+use crate::storages::fuse::statistics;
+use crate::storages::fuse::FuseTable;
+use crate::storages::fuse::DEFAULT_BLOCK_PER_SEGMENT;
+use crate::storages::fuse::FUSE_OPT_KEY_BLOCK_PER_SEGMENT;
+use crate::storages::index::ColumnStatistics;
+
+impl FuseTable {
+    /// Registers external parquet files as blocks of this table, without copying the data.
+    ///
+    /// The footer of each file is read, each row group of it becomes a block, whose row count
+    /// and column statistics are derived from the footer. The files must contain all the
+    /// columns of the table, of the same types.
+    ///
+    /// The imported blocks are marked as external, the files are never removed by purge or
+    /// vacuum. Files under the prefix of the table are rejected, since vacuum would take
+    /// them for orphans once they are no longer referenced.
+    pub async fn do_import(&self, ctx: Arc<QueryContext>, locations: &[String]) -> Result<()> {
+        let operator = self.get_operator(ctx.as_ref())?;
+        let schema = self.table_info.schema();
+        let table_prefix = format!("{}/", self.meta_location_generator().prefix());
+        if let Some(location) = locations.iter().find(|l| l.starts_with(&table_prefix)) {
+            return Err(ErrorCode::BadArguments(format!(
+                "file {} is under the prefix of the table, external files should be kept elsewhere",
+                location
+            )));
+        }
+
+        let mut block_metas = Vec::with_capacity(locations.len());
+        for location in locations {
+            let mut reader = operator.object(location).seekable_reader(..);
+            let file_meta = read_metadata_async(&mut reader).await.map_err(|e| {
+                ErrorCode::ParquetError(format!(
+                    "failed to read the footer of file {}: {}",
+                    location, e
+                ))
+            })?;
+
+            let column_mapping = Self::map_import_columns(location, &schema, &file_meta)?;
+            for row_group in &file_meta.row_groups {
+                block_metas.push(Self::import_block_meta(
+                    location,
+                    &schema,
+                    &column_mapping,
+                    row_group,
+                )?);
+            }
+        }
+
+        if block_metas.is_empty() {
+            return Ok(());
+        }
+
+        let block_per_seg =
+            self.get_option(FUSE_OPT_KEY_BLOCK_PER_SEGMENT, DEFAULT_BLOCK_PER_SEGMENT);
+        let locs = self.meta_location_generator();
+        let segment_info_cache = ctx.get_storage_cache_manager().get_table_segment_cache();
+        let mut log_entries = Vec::new();
+        for blocks in block_metas.chunks(std::cmp::max(1, block_per_seg)) {
+            let seg = Self::import_segment(&schema, blocks.to_vec())?;
+            let seg_loc = locs.gen_segment_info_location();
+            let bytes = serde_json::to_vec(&seg)?;
+            operator.object(&seg_loc).write(bytes).await?;
+            let seg = Arc::new(seg);
+            if let Some(ref cache) = segment_info_cache {
+                let cache = &mut cache.write().await;
+                cache.put(seg_loc.clone(), seg.clone());
+            }
+            log_entries.push(AppendOperationLogEntry::new(seg_loc, seg));
+        }
+
+        self.do_commit(ctx, log_entries, false).await
+    }
+
+    /// Maps each column of the table to the index of the column in the parquet file
+    fn map_import_columns(
+        location: &str,
+        schema: &DataSchema,
+        file_meta: &FileMetaData,
+    ) -> Result<Vec<usize>> {
+        let file_schema = infer_schema(file_meta).map_err(|e| {
+            ErrorCode::ParquetError(format!(
+                "failed to infer the schema of file {}: {}",
+                location, e
+            ))
+        })?;
+
+        // nested types are not supported, each field should be exactly one parquet column
+        if file_schema.fields.len() != file_meta.schema_descr.num_columns() {
+            return Err(ErrorCode::DataStructMissMatch(format!(
+                "file {}: nested columns are not supported",
+                location
+            )));
+        }
+
+        schema
+            .fields()
+            .iter()
+            .map(|field| {
+                let expected = field.to_arrow();
+                let idx = file_schema
+                    .fields
+                    .iter()
+                    .position(|f| f.name == expected.name)
+                    .ok_or_else(|| {
+                        ErrorCode::DataStructMissMatch(format!(
+                            "file {}: column `{}` not found",
+                            location,
+                            field.name()
+                        ))
+                    })?;
+                let actual = &file_schema.fields[idx];
+                if actual.data_type != expected.data_type
+                    || actual.is_nullable != expected.is_nullable
+                {
+                    return Err(ErrorCode::DataStructMissMatch(format!(
+                        "file {}: column `{}` is of type {:?} (nullable: {}), but {:?} (nullable: {}) is expected",
+                        location,
+                        field.name(),
+                        actual.data_type,
+                        actual.is_nullable,
+                        expected.data_type,
+                        expected.is_nullable,
+                    )));
+                }
+                Ok(idx)
+            })
+            .collect()
+    }
+
+    fn import_block_meta(
+        location: &str,
+        schema: &DataSchema,
+        column_mapping: &[usize],
+        row_group: &RowGroupMetaData,
+    ) -> Result<BlockMeta> {
+        let columns = row_group.columns();
+        let compression = Self::import_compression(location, columns)?;
+
+        let mut col_metas = HashMap::with_capacity(column_mapping.len());
+        let mut col_stats = HashMap::with_capacity(column_mapping.len());
+        for (col_id, file_col_idx) in column_mapping.iter().enumerate() {
+            let column = &columns[*file_col_idx];
+            let (offset, len) = column.byte_range();
+            col_metas.insert(col_id as ColumnId, ColumnMeta {
+                offset,
+                len,
+                num_values: column.num_values() as u64,
+            });
+
+            // columns without statistics are kept out of `col_stats`, they will not be pruned
+            if let Some(stats) = Self::import_column_stats(location, schema.field(col_id), column)?
+            {
+                col_stats.insert(col_id as ColumnId, stats);
+            }
+        }
+
+        Ok(BlockMeta {
+            row_count: row_group.num_rows() as u64,
+            block_size: row_group.total_byte_size() as u64,
+            file_size: row_group.compressed_size() as u64,
+            col_stats,
+            col_metas,
+            location: (location.to_owned(), DataBlock::VERSION),
+            compression,
+            external: true,
+        })
+    }
+
+    fn import_compression(location: &str, columns: &[ColumnChunkMetaData]) -> Result<Compression> {
+        let compression = match columns.first() {
+            None => return Ok(Compression::Uncompressed),
+            Some(column) => column.compression(),
+        };
+        if columns.iter().any(|c| c.compression() != compression) {
+            return Err(ErrorCode::ParquetError(format!(
+                "file {}: columns of the same row group should be compressed by the same algorithm",
+                location
+            )));
+        }
+        match compression {
+            ParquetCompression::Uncompressed => Ok(Compression::Uncompressed),
+            ParquetCompression::Snappy => Ok(Compression::Snappy),
+            ParquetCompression::Gzip => Ok(Compression::Gzip),
+            ParquetCompression::Lz4 => Ok(Compression::Lz4),
+            ParquetCompression::Lz4Raw => Ok(Compression::Lz4Raw),
+            ParquetCompression::Zstd => Ok(Compression::Zstd),
+            other => Err(ErrorCode::ParquetError(format!(
+                "file {}: compression {:?} is not supported",
+                location, other
+            ))),
+        }
+    }
+
+    /// Converts the parquet statistics of a column chunk into [ColumnStatistics]
+    ///
+    /// Returns [None] if the statistics are absent, or can not be represented by the
+    /// [DataValue] of the column type.
+    fn import_column_stats(
+        location: &str,
+        field: &DataField,
+        column: &ColumnChunkMetaData,
+    ) -> Result<Option<ColumnStatistics>> {
+        let stats = match column.statistics() {
+            None => return Ok(None),
+            Some(stats) => stats.map_err(|e| {
+                ErrorCode::ParquetError(format!(
+                    "file {}: invalid statistics of column `{}`: {}",
+                    location,
+                    field.name(),
+                    e
+                ))
+            })?,
+        };
+
+        let null_count = match stats.null_count() {
+            None => return Ok(None),
+            Some(null_count) => null_count as u64,
+        };
+
+        let any = stats.as_any();
+        let data_type = remove_nullable(field.data_type());
+        let min_max = match (stats.physical_type(), data_type.default_value()) {
+            (PhysicalType::Boolean, DataValue::Boolean(_)) => any
+                .downcast_ref::<BooleanStatistics>()
+                .and_then(|s| Some((s.min_value?, s.max_value?)))
+                .map(|(min, max)| (DataValue::Boolean(min), DataValue::Boolean(max))),
+            (PhysicalType::Int32, DataValue::Int64(_)) => {
+                primitive_min_max(any, |v: i32| DataValue::Int64(v as i64))
+            }
+            (PhysicalType::Int32, DataValue::UInt64(_)) => {
+                primitive_min_max(any, |v: i32| DataValue::UInt64(v as u32 as u64))
+            }
+            (PhysicalType::Int64, DataValue::Int64(_)) => {
+                primitive_min_max::<i64>(any, DataValue::Int64)
+            }
+            (PhysicalType::Int64, DataValue::UInt64(_)) => {
+                primitive_min_max(any, |v: i64| DataValue::UInt64(v as u64))
+            }
+            (PhysicalType::Float, DataValue::Float64(_)) => {
+                primitive_min_max(any, |v: f32| DataValue::Float64(v as f64))
+            }
+            (PhysicalType::Double, DataValue::Float64(_)) => {
+                primitive_min_max::<f64>(any, DataValue::Float64)
+            }
+            (PhysicalType::ByteArray, DataValue::String(_)) => any
+                .downcast_ref::<BinaryStatistics>()
+                .and_then(|s| Some((s.min_value.clone()?, s.max_value.clone()?)))
+                .map(|(min, max)| (DataValue::String(min), DataValue::String(max))),
+            _ => None,
+        };
+
+        Ok(min_max.map(|(min, max)| ColumnStatistics {
+            min,
+            max,
+            null_count,
+            in_memory_size: column.uncompressed_size() as u64,
+            min_max_skipped: false,
+            histogram: None,
+        }))
+    }
+
+    fn import_segment(schema: &DataSchema, blocks: Vec<BlockMeta>) -> Result<SegmentInfo> {
+        let block_stats = blocks.iter().map(|b| &b.col_stats).collect::<Vec<_>>();
+        let mut col_stats = statistics::reduce_block_stats(&block_stats, schema)?;
+        // the summary of a column is meaningful only if all the blocks have its statistics
+        col_stats.retain(|id, _| blocks.iter().all(|b| b.col_stats.contains_key(id)));
+
+        let summary = Statistics {
+            row_count: blocks.iter().map(|b| b.row_count).sum(),
+            block_count: blocks.len() as u64,
+            uncompressed_byte_size: blocks.iter().map(|b| b.block_size).sum(),
+            compressed_byte_size: blocks.iter().map(|b| b.file_size).sum(),
+            col_stats,
+        };
+        Ok(SegmentInfo::new(blocks, summary))
+    }
+}
+
+fn primitive_min_max<T: NativeType>(
+    stats: &dyn Any,
+    to_value: impl Fn(T) -> DataValue,
+) -> Option<(DataValue, DataValue)> {
+    let stats = stats.downcast_ref::<PrimitiveStatistics<T>>()?;
+    Some((to_value(stats.min_value?), to_value(stats.max_value?)))
+}
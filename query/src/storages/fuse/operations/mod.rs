@@ -14,11 +14,13 @@
 
 mod append;
 mod commit;
+mod import;
 mod operation_log;
 mod optimize;
 mod read;
 mod read_partitions;
 mod truncate;
+mod vacuum;
 
 pub use operation_log::AppendOperationLogEntry;
 pub use operation_log::TableOperationLog;
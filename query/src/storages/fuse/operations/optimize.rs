@@ -33,11 +33,12 @@ impl FuseTable {
         ctx: Arc<QueryContext>,
         keep_last_snapshot: bool,
     ) -> Result<()> {
-        let accessor = ctx.get_storage_operator()?;
+        let accessor = self.get_operator(ctx.as_ref())?;
         let tbl_info = self.get_table_info();
         let snapshot_loc = tbl_info.meta.options.get(OPT_KEY_SNAPSHOT_LOCATION);
         let format_version = self.snapshot_format_version();
-        let reader = MetaReaders::table_snapshot_reader(ctx.as_ref());
+        let reader =
+            MetaReaders::table_snapshot_reader_with_operator(ctx.as_ref(), Some(accessor.clone()));
 
         let mut snapshots = reader
             .read_snapshot_history(
@@ -60,11 +61,11 @@ impl FuseTable {
             // if truncate_all requested, gc root contains nothing;
             current_segments = HashSet::new();
         } else {
-            current_snapshot = snapshots.remove(0);
+            current_snapshot = snapshots.remove(0).1;
             current_segments = HashSet::from_iter(&current_snapshot.segments);
         }
 
-        let prevs = snapshots.iter().fold(HashSet::new(), |mut acc, s| {
+        let prevs = snapshots.iter().fold(HashSet::new(), |mut acc, (_, s)| {
             acc.extend(&s.segments);
             acc
         });
@@ -99,8 +100,7 @@ impl FuseTable {
 
         let locs = self.meta_location_generator();
         // 3. remove the snapshots
-        for s in snapshots.iter().rev() {
-            let loc = locs.snapshot_location_from_uuid(&s.snapshot_id, s.format_version())?;
+        for (loc, _) in snapshots.iter().rev() {
             self.remove_location(accessor.clone(), loc.as_str()).await?;
             if let Some(c) = ctx.get_storage_cache_manager().get_table_snapshot_cache() {
                 let cache = &mut *c.write().await;
@@ -108,29 +108,38 @@ impl FuseTable {
             }
         }
 
+        // 4. remove the hint, since none of the snapshots is kept
+        if !keep_last_snapshot {
+            let hint = locs.gen_last_snapshot_hint_location();
+            self.remove_location(accessor, hint.as_str()).await?;
+        }
+
         Ok(())
     }
 
-    async fn blocks_of(
+    /// Collects the locations of the blocks of the segments, the external blocks (see
+    /// `do_import`) are left out, since they do not belong to the table.
+    pub(crate) async fn blocks_of(
         &self,
         //locations: impl Iterator<Item = impl AsRef<Location>>,
         locations: impl Iterator<Item = &Location>,
         ctx: Arc<QueryContext>,
     ) -> Result<HashSet<String>> {
         let mut result = HashSet::new();
-        let reader = MetaReaders::segment_info_reader(ctx.as_ref());
+        let operator = self.get_operator(ctx.as_ref())?;
+        let reader = MetaReaders::segment_info_reader_with_operator(ctx.as_ref(), Some(operator));
         for l in locations {
             //let (x, ver) = l.as_ref();
             let (x, ver) = l;
             let res = reader.read(x, None, *ver).await?;
-            for block_meta in &res.blocks {
+            for block_meta in res.blocks.iter().filter(|b| !b.external) {
                 result.insert(block_meta.location.0.clone());
             }
         }
         Ok(result)
     }
 
-    async fn remove_location(
+    pub(crate) async fn remove_location(
         &self,
         data_accessor: Operator,
         location: impl AsRef<str>,
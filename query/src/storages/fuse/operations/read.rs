@@ -13,7 +13,9 @@
 //  limitations under the License.
 //
 
+use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::Duration;
 
 use common_base::Progress;
 use common_base::ProgressValues;
@@ -30,10 +32,13 @@ use futures::StreamExt;
 use crate::pipelines::new::processors::port::OutputPort;
 use crate::pipelines::new::processors::processor::Event;
 use crate::pipelines::new::processors::processor::ProcessorPtr;
+use crate::pipelines::new::processors::OrderedMergeProcessor;
 use crate::pipelines::new::processors::Processor;
+use crate::pipelines::new::NewPipe;
 use crate::pipelines::new::NewPipeline;
 use crate::pipelines::new::SourcePipeBuilder;
 use crate::sessions::QueryContext;
+use crate::storages::fuse::fuse_part::FusePartInfo;
 use crate::storages::fuse::io::BlockReader;
 use crate::storages::fuse::operations::read::State::Generated;
 use crate::storages::fuse::FuseTable;
@@ -47,14 +52,18 @@ impl FuseTable {
     ) -> Result<SendableDataBlockStream> {
         let block_reader = self.create_block_reader(&ctx, push_downs)?;
 
-        let iter = std::iter::from_fn(move || match ctx.clone().try_get_partitions(1) {
-            Err(_) => None,
-            Ok(parts) if parts.is_empty() => None,
-            Ok(parts) => Some(parts),
-        })
-        .flatten();
-
-        let part_stream = futures::stream::iter(iter);
+        let part_stream = if Self::preserve_order(push_downs) {
+            let parts = Self::take_ordered_partitions(&ctx)?;
+            futures::stream::iter(parts).boxed()
+        } else {
+            let iter = std::iter::from_fn(move || match ctx.clone().try_get_partitions(1) {
+                Err(_) => None,
+                Ok(parts) if parts.is_empty() => None,
+                Ok(parts) => Some(parts),
+            })
+            .flatten();
+            futures::stream::iter(iter).boxed()
+        };
 
         let stream = part_stream
             .then(move |part| {
@@ -83,9 +92,14 @@ impl FuseTable {
                 .collect::<Vec<usize>>()
         };
 
-        let operator = ctx.get_storage_operator()?;
+        let operator = self.get_operator(ctx.as_ref())?;
         let table_schema = self.table_info.schema();
-        BlockReader::create(operator, table_schema, projection)
+        // 0 for no timeout
+        let read_timeout = match ctx.get_settings().get_storage_read_timeout_secs()? {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        };
+        BlockReader::create(operator, table_schema, projection, read_timeout)
     }
 
     #[inline]
@@ -103,6 +117,40 @@ impl FuseTable {
 
         let mut source_builder = SourcePipeBuilder::create();
 
+        if Self::preserve_order(&plan.push_downs) {
+            // the parts are assigned to the sources in turn, and the outputs of the sources
+            // are merged in the same turn, thus the blocks are returned in sequence
+            let num_sources = std::cmp::max(1, max_threads);
+            let mut assigned_parts = vec![VecDeque::new(); num_sources];
+            for (idx, part) in Self::take_ordered_partitions(&ctx)?.into_iter().enumerate() {
+                assigned_parts[idx % num_sources].push_back(part);
+            }
+
+            let merge = OrderedMergeProcessor::create(num_sources);
+            for parts in assigned_parts {
+                let output = OutputPort::create();
+                source_builder.add_source(
+                    output.clone(),
+                    FuseTableSource::create_with_parts(
+                        ctx.clone(),
+                        output,
+                        block_reader.clone(),
+                        parts,
+                    )?,
+                );
+            }
+            pipeline.add_pipe(source_builder.finalize());
+
+            let inputs_port = merge.get_inputs().to_vec();
+            let outputs_port = vec![merge.get_output()];
+            pipeline.add_pipe(NewPipe::ResizePipe {
+                inputs_port,
+                outputs_port,
+                processor: ProcessorPtr::create(Box::new(merge)),
+            });
+            return Ok(());
+        }
+
         for _index in 0..std::cmp::max(1, max_threads) {
             let output = OutputPort::create();
             source_builder.add_source(
@@ -114,6 +162,30 @@ impl FuseTable {
         pipeline.add_pipe(source_builder.finalize());
         Ok(())
     }
+
+    fn preserve_order(push_downs: &Option<Extras>) -> bool {
+        push_downs
+            .as_ref()
+            .map(|extras| extras.preserve_order)
+            .unwrap_or(false)
+    }
+
+    // Takes all the partitions bound to the context, in the sequence of their blocks
+    fn take_ordered_partitions(ctx: &QueryContext) -> Result<Vec<PartInfoPtr>> {
+        let mut sequenced = vec![];
+        loop {
+            let parts = ctx.try_get_partitions(1)?;
+            if parts.is_empty() {
+                break;
+            }
+            for part in parts {
+                let sequence = FusePartInfo::from_part(&part)?.sequence;
+                sequenced.push((sequence, part));
+            }
+        }
+        sequenced.sort_by_key(|(sequence, _)| *sequence);
+        Ok(sequenced.into_iter().map(|(_, part)| part).collect())
+    }
 }
 
 enum State {
@@ -126,6 +198,8 @@ enum State {
 struct FuseTableSource {
     state: State,
     ctx: Arc<QueryContext>,
+    // the parts assigned to this source, or None if the parts are taken from the context
+    assigned_parts: Option<VecDeque<PartInfoPtr>>,
     scan_progress: Arc<Progress>,
     block_reader: Arc<BlockReader>,
     output: Arc<OutputPort>,
@@ -136,24 +210,50 @@ impl FuseTableSource {
         ctx: Arc<QueryContext>,
         output: Arc<OutputPort>,
         block_reader: Arc<BlockReader>,
+    ) -> Result<ProcessorPtr> {
+        Self::try_create(ctx, output, block_reader, None)
+    }
+
+    pub fn create_with_parts(
+        ctx: Arc<QueryContext>,
+        output: Arc<OutputPort>,
+        block_reader: Arc<BlockReader>,
+        parts: VecDeque<PartInfoPtr>,
+    ) -> Result<ProcessorPtr> {
+        Self::try_create(ctx, output, block_reader, Some(parts))
+    }
+
+    fn try_create(
+        ctx: Arc<QueryContext>,
+        output: Arc<OutputPort>,
+        block_reader: Arc<BlockReader>,
+        assigned_parts: Option<VecDeque<PartInfoPtr>>,
     ) -> Result<ProcessorPtr> {
         let scan_progress = ctx.get_scan_progress();
-        let mut partitions = ctx.try_get_partitions(1)?;
-        match partitions.is_empty() {
-            true => Ok(ProcessorPtr::create(Box::new(FuseTableSource {
-                ctx,
-                output,
-                block_reader,
-                scan_progress,
-                state: State::Finish,
-            }))),
-            false => Ok(ProcessorPtr::create(Box::new(FuseTableSource {
-                ctx,
-                output,
-                block_reader,
-                scan_progress,
-                state: State::ReadData(partitions.remove(0)),
-            }))),
+        let mut source = FuseTableSource {
+            ctx,
+            assigned_parts,
+            output,
+            block_reader,
+            scan_progress,
+            state: State::Finish,
+        };
+        if let Some(part) = source.next_part()? {
+            source.state = State::ReadData(part);
+        }
+        Ok(ProcessorPtr::create(Box::new(source)))
+    }
+
+    fn next_part(&mut self) -> Result<Option<PartInfoPtr>> {
+        match &mut self.assigned_parts {
+            Some(parts) => Ok(parts.pop_front()),
+            None => {
+                let mut partitions = self.ctx.try_get_partitions(1)?;
+                match partitions.is_empty() {
+                    true => Ok(None),
+                    false => Ok(Some(partitions.remove(0))),
+                }
+            }
         }
     }
 }
@@ -202,7 +302,6 @@ impl Processor for FuseTableSource {
         match std::mem::replace(&mut self.state, State::Finish) {
             State::Deserialize(part, chunks) => {
                 let data_block = self.block_reader.deserialize(part, chunks)?;
-                let mut partitions = self.ctx.try_get_partitions(1)?;
 
                 let progress_values = ProgressValues {
                     rows: data_block.num_rows(),
@@ -210,10 +309,7 @@ impl Processor for FuseTableSource {
                 };
                 self.scan_progress.incr(&progress_values);
 
-                self.state = match partitions.is_empty() {
-                    true => State::Generated(None, data_block),
-                    false => State::Generated(Some(partitions.remove(0)), data_block),
-                };
+                self.state = State::Generated(self.next_part()?, data_block);
                 Ok(())
             }
             _ => Err(ErrorCode::LogicalError("It's a bug.")),
@@ -21,6 +21,7 @@ use common_planners::Extras;
 use common_planners::PartInfoPtr;
 use common_planners::Partitions;
 use common_planners::Statistics;
+use common_tracing::tracing;
 
 use crate::sessions::QueryContext;
 use crate::storages::fuse::fuse_part::ColumnMeta;
@@ -28,6 +29,8 @@ use crate::storages::fuse::fuse_part::FusePartInfo;
 use crate::storages::fuse::meta::BlockMeta;
 use crate::storages::fuse::meta::TableSnapshot;
 use crate::storages::fuse::pruning::BlockPruner;
+use crate::storages::fuse::pruning::LazyBlocks;
+use crate::storages::fuse::pruning::PruneExplanation;
 use crate::storages::fuse::FuseTable;
 
 impl FuseTable {
@@ -44,9 +47,16 @@ impl FuseTable {
                     return Ok(result);
                 }
                 let schema = self.table_info.schema();
-                let block_metas = BlockPruner::new(snapshot.clone())
-                    .apply(schema, &push_downs, ctx.as_ref())
+                let (block_metas, pruning_statistics) = BlockPruner::new(snapshot.clone())
+                    .with_operator(self.get_operator(ctx.as_ref())?)
+                    .apply_with_statistics(schema, &push_downs, ctx.as_ref())
                     .await?;
+                tracing::debug!(
+                    "pruning of table {}: {:?}",
+                    self.table_info.name,
+                    pruning_statistics
+                );
+                ctx.add_pruning_statistics(&pruning_statistics);
 
                 let partitions_scanned = block_metas.len();
                 let partitions_total = snapshot.summary.block_count as usize;
@@ -71,6 +81,44 @@ impl FuseTable {
         }
     }
 
+    /// Like [FuseTable::do_read_partitions], but the blocks are admitted lazily, see
+    /// [BlockPruner::apply_lazy]. None if the table has no snapshot.
+    pub async fn do_read_lazy_blocks(
+        &self,
+        ctx: Arc<QueryContext>,
+        push_downs: &Option<Extras>,
+    ) -> Result<Option<LazyBlocks>> {
+        match self.read_table_snapshot(ctx.as_ref()).await? {
+            None => Ok(None),
+            Some(snapshot) => {
+                let lazy_blocks = BlockPruner::new(snapshot)
+                    .with_operator(self.get_operator(ctx.as_ref())?)
+                    .apply_lazy(self.table_info.schema(), push_downs, ctx.as_ref())
+                    .await?;
+                Ok(Some(lazy_blocks))
+            }
+        }
+    }
+
+    /// Explains the pruning of the push-downs, see [BlockPruner::explain].
+    /// None if the table has no snapshot.
+    pub async fn do_explain_prune(
+        &self,
+        ctx: Arc<QueryContext>,
+        push_downs: &Option<Extras>,
+    ) -> Result<Option<PruneExplanation>> {
+        match self.read_table_snapshot(ctx.as_ref()).await? {
+            None => Ok(None),
+            Some(snapshot) => {
+                let explanation = BlockPruner::new(snapshot)
+                    .with_operator(self.get_operator(ctx.as_ref())?)
+                    .explain(self.table_info.schema(), push_downs, ctx.as_ref())
+                    .await?;
+                Ok(Some(explanation))
+            }
+        }
+    }
+
     pub fn to_partitions(
         blocks_metas: &[BlockMeta],
         push_down: Option<Extras>,
@@ -111,9 +159,9 @@ impl FuseTable {
 
         let mut remaining = limit;
 
-        for block_meta in metas {
+        for (sequence, block_meta) in metas.iter().enumerate() {
             let rows = block_meta.row_count as usize;
-            partitions.push(Self::all_columns_part(block_meta));
+            partitions.push(Self::all_columns_part(block_meta, sequence));
             statistics.read_rows += rows;
             statistics.read_bytes += block_meta.block_size as usize;
 
@@ -145,8 +193,8 @@ impl FuseTable {
 
         let mut remaining = limit;
 
-        for block_meta in metas {
-            partitions.push(Self::projection_part(block_meta, indices));
+        for (sequence, block_meta) in metas.iter().enumerate() {
+            partitions.push(Self::projection_part(block_meta, indices, sequence));
 
             let rows = block_meta.row_count as usize;
 
@@ -171,7 +219,7 @@ impl FuseTable {
         (statistics, partitions)
     }
 
-    fn all_columns_part(meta: &BlockMeta) -> PartInfoPtr {
+    fn all_columns_part(meta: &BlockMeta, sequence: usize) -> PartInfoPtr {
         let mut columns_meta = HashMap::with_capacity(meta.col_metas.len());
 
         for (idx, column_meta) in &meta.col_metas {
@@ -190,10 +238,11 @@ impl FuseTable {
             rows_count,
             columns_meta,
             meta.compression,
+            sequence,
         )
     }
 
-    fn projection_part(meta: &BlockMeta, projections: &[usize]) -> PartInfoPtr {
+    fn projection_part(meta: &BlockMeta, projections: &[usize], sequence: usize) -> PartInfoPtr {
         let mut columns_meta = HashMap::with_capacity(projections.len());
 
         for projection in projections {
@@ -214,6 +263,7 @@ impl FuseTable {
             rows_count,
             columns_meta,
             meta.compression,
+            sequence,
         )
     }
 
@@ -24,10 +24,16 @@ use crate::catalogs::Catalog;
 use crate::sessions::QueryContext;
 use crate::sql::OPT_KEY_SNAPSHOT_LOCATION;
 use crate::storages::fuse::meta::TableSnapshot;
-use crate::storages::fuse::meta::Versioned;
 use crate::storages::fuse::FuseTable;
 
 impl FuseTable {
+    /// Truncates the table by committing a new, empty snapshot.
+    ///
+    /// Without `purge`, the history is kept and the previous snapshots are still reachable
+    /// (e.g. by time travel). With `purge`, the new empty snapshot is marked as a purge point:
+    /// the blocks, segments and snapshots older than it are not removed right away, since
+    /// running queries may still read the snapshot they already loaded, but reclaimed by
+    /// vacuum once the new snapshot is older than `storage_vacuum_safety_window_secs`.
     #[inline]
     pub async fn do_truncate(&self, ctx: Arc<QueryContext>, plan: TruncateTablePlan) -> Result<()> {
         if let Some(prev_snapshot) = self.read_table_snapshot(ctx.as_ref()).await? {
@@ -35,29 +41,30 @@ impl FuseTable {
 
             let new_snapshot = TableSnapshot::new(
                 Uuid::new_v4(),
+                &prev_snapshot.timestamp,
                 Some((prev_id, prev_snapshot.format_version())),
                 prev_snapshot.schema.clone(),
                 Default::default(),
                 vec![],
-            );
+            )
+            .with_segment_digests(Some(vec![]))
+            .with_purge_history(plan.purge)
+            .with_prev_snapshot_location(self.snapshot_loc());
             let loc = self.meta_location_generator();
-            let new_snapshot_loc =
-                loc.snapshot_location_from_uuid(&new_snapshot.snapshot_id, TableSnapshot::VERSION)?;
-            let operator = ctx.get_storage_operator()?;
+            let new_snapshot_loc = loc.gen_snapshot_location(&new_snapshot.snapshot_id)?;
+            let operator = self.get_operator(ctx.as_ref())?;
             let bytes = serde_json::to_vec(&new_snapshot)?;
             operator.object(&new_snapshot_loc).write(bytes).await?;
 
-            if plan.purge {
-                let keep_last_snapshot = false;
-                self.do_optimize(ctx.clone(), keep_last_snapshot).await?
-            }
             ctx.get_catalog()
                 .upsert_table_option(UpsertTableOptionReq::new(
                     &self.table_info.ident,
                     OPT_KEY_SNAPSHOT_LOCATION,
-                    new_snapshot_loc,
+                    new_snapshot_loc.clone(),
                 ))
                 .await?;
+
+            Self::write_last_snapshot_hint(&operator, loc, &new_snapshot_loc).await;
         }
 
         Ok(())
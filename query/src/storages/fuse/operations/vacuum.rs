@@ -0,0 +1,108 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use chrono::Utc;
+use common_exception::Result;
+use common_tracing::tracing;
+
+use crate::sessions::QueryContext;
+use crate::sql::OPT_KEY_SNAPSHOT_LOCATION;
+use crate::storages::fuse::io::list_prefix;
+use crate::storages::fuse::io::MetaReaders;
+use crate::storages::fuse::FuseTable;
+use crate::storages::fuse::FUSE_TBL_BLOCK_PREFIX;
+use crate::storages::fuse::FUSE_TBL_SEGMENT_PREFIX;
+use crate::storages::fuse::FUSE_TBL_SNAPSHOT_PREFIX;
+use crate::storages::Table;
+
+impl FuseTable {
+    /// Removes the orphan files of the table, and returns their locations.
+    ///
+    /// Files left by failed or cancelled writes are not referenced by any snapshot, thus
+    /// they are not covered by purging. A file is an orphan if it is not reachable from
+    /// any of the retained snapshots, and it is older than the setting
+    /// `storage_vacuum_safety_window_secs`, so that files of in-flight writes are kept.
+    /// Only the prefix of the table is listed, the external files of imported blocks are
+    /// kept elsewhere and never touched.
+    ///
+    /// The history before a purge point (see `TRUNCATE ... PURGE`) is no longer retained once
+    /// the purge point is older than the safety window, thus its files become orphans too.
+    ///
+    /// With `dry_run`, the orphans are only reported.
+    pub async fn do_vacuum(&self, ctx: Arc<QueryContext>, dry_run: bool) -> Result<Vec<String>> {
+        let operator = self.get_operator(ctx.as_ref())?;
+        let safety_window = ctx.get_settings().get_storage_vacuum_safety_window_secs()?;
+        let expire_before = Utc::now().timestamp() - safety_window as i64;
+
+        let tbl_info = self.get_table_info();
+        let snapshot_loc = tbl_info.meta.options.get(OPT_KEY_SNAPSHOT_LOCATION);
+        let format_version = self.snapshot_format_version();
+        let locs = self.meta_location_generator();
+        let reader =
+            MetaReaders::table_snapshot_reader_with_operator(ctx.as_ref(), Some(operator.clone()));
+        let snapshots = reader
+            .read_snapshot_history(snapshot_loc, format_version, locs.clone())
+            .await?;
+        // the history is ordered from the latest snapshot backwards
+        let retained = snapshots
+            .iter()
+            .position(|(_, s)| {
+                s.purge_history && matches!(s.timestamp, Some(t) if t.timestamp() < expire_before)
+            })
+            .map_or(snapshots.len(), |i| i + 1);
+        let snapshots = &snapshots[..retained];
+
+        // locations reachable from the retained snapshots
+        let mut referenced = HashSet::new();
+        referenced.extend(snapshots.iter().map(|(loc, _)| loc.clone()));
+        let segments = snapshots.iter().fold(HashSet::new(), |mut acc, (_, s)| {
+            acc.extend(&s.segments);
+            acc
+        });
+        referenced.extend(segments.iter().map(|(loc, _)| loc.clone()));
+        referenced.extend(self.blocks_of(segments.into_iter(), ctx.clone()).await?);
+
+        let mut orphans = vec![];
+        for kind in [
+            FUSE_TBL_BLOCK_PREFIX,
+            FUSE_TBL_SEGMENT_PREFIX,
+            FUSE_TBL_SNAPSHOT_PREFIX,
+        ] {
+            let prefix = format!("{}/{}", locs.prefix(), kind);
+            for object in list_prefix(&operator, &prefix).await? {
+                if referenced.contains(&object.path) {
+                    continue;
+                }
+                // objects of unknown age are kept, they might belong to an in-flight write
+                match object.last_modified {
+                    Some(t) if t < expire_before => orphans.push(object.path),
+                    _ => continue,
+                }
+            }
+        }
+
+        if !dry_run {
+            for loc in &orphans {
+                tracing::info!("vacuum orphan file {}", loc);
+                self.remove_location(operator.clone(), loc).await?;
+            }
+        }
+
+        Ok(orphans)
+    }
+}
@@ -13,57 +13,141 @@
 //  limitations under the License.
 //
 
+use std::collections::HashSet;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
+use common_base::tokio;
+use common_base::ConcurrencyLimitedRuntime;
+use common_base::JoinHandleExt;
+use common_base::TrySpawn;
 use common_datavalues::DataSchemaRef;
+use common_exception::ErrorCode;
 use common_exception::Result;
+use common_planners::Expression;
 use common_planners::Extras;
 use common_tracing::tracing;
 use futures::StreamExt;
-use futures::TryStreamExt;
+use opendal::Operator;
 
 use crate::sessions::QueryContext;
+use crate::storages::fuse::cache::PruneResultCache;
 use crate::storages::fuse::io::MetaReaders;
 use crate::storages::fuse::meta::BlockMeta;
+use crate::storages::fuse::meta::ColumnId;
 use crate::storages::fuse::meta::SegmentInfo;
 use crate::storages::fuse::meta::TableSnapshot;
+use crate::storages::fuse::pruning::BlockExplanation;
+use crate::storages::fuse::pruning::LazyBlocks;
+use crate::storages::fuse::pruning::PruneDecision;
+use crate::storages::fuse::pruning::PruneExplanation;
+use crate::storages::fuse::pruning::PruningStatistics;
+use crate::storages::fuse::pruning::SegmentExplanation;
+use crate::storages::fuse::pruning::TopK;
 use crate::storages::index::BlockStatistics;
 use crate::storages::index::RangeFilter;
 
 pub struct BlockPruner {
     table_snapshot: Arc<TableSnapshot>,
+    // the storage of the segments, the storage of the query if not specified
+    operator: Option<Operator>,
 }
 
-type Pred = Box<dyn Fn(&BlockStatistics) -> Result<bool> + Send + Sync + Unpin>;
+pub type Pred = Box<dyn Fn(&BlockStatistics, u64) -> Result<bool> + Send + Sync + Unpin>;
 impl BlockPruner {
+    /// The min number of the blocks of a segment, whose blocks are filtered in parallel.
+    pub const PARALLEL_FILTER_MIN_BLOCKS: usize = 10_000;
+
     pub fn new(table_snapshot: Arc<TableSnapshot>) -> Self {
-        Self { table_snapshot }
+        Self {
+            table_snapshot,
+            operator: None,
+        }
+    }
+
+    pub fn with_operator(mut self, operator: Operator) -> Self {
+        self.operator = Some(operator);
+        self
     }
 
-    #[tracing::instrument(level = "debug", name="block_pruner_apply", skip(self, schema, ctx), fields(ctx.id = ctx.get_id().as_str()))]
     pub async fn apply(
         &self,
         schema: DataSchemaRef,
         push_down: &Option<Extras>,
         ctx: &QueryContext,
     ) -> Result<Vec<BlockMeta>> {
-        let block_pred: Pred = match push_down {
-            Some(exprs) if !exprs.filters.is_empty() => {
-                // for the time being, we only handle the first expr
-                let verifiable_expression =
-                    RangeFilter::try_create(&exprs.filters[0], schema, Arc::new(ctx.clone()))?;
-                Box::new(move |v: &BlockStatistics| verifiable_expression.eval(v))
-            }
-            _ => Box::new(|_: &BlockStatistics| Ok(true)),
+        let (block_metas, _) = self.apply_with_statistics(schema, push_down, ctx).await?;
+        Ok(block_metas)
+    }
+
+    /// Prunes the blocks, and reports how many segments and blocks are pruned.
+    #[tracing::instrument(level = "debug", name="block_pruner_apply", skip(self, schema, ctx), fields(ctx.id = ctx.get_id().as_str()))]
+    pub async fn apply_with_statistics(
+        &self,
+        schema: DataSchemaRef,
+        push_down: &Option<Extras>,
+        ctx: &QueryContext,
+    ) -> Result<(Vec<BlockMeta>, PruningStatistics)> {
+        let prune_result_cache = ctx.get_storage_cache_manager().get_prune_result_cache();
+        let cache_key = self.prune_result_cache_key(push_down);
+        let cached = match (&prune_result_cache, &cache_key) {
+            (Some(cache), Some(key)) => cache.get(key).await,
+            _ => None,
         };
 
+        let (block_metas, mut statistics) = match cached {
+            Some(block_metas) => {
+                let statistics = self.cached_statistics(&block_metas);
+                (block_metas, statistics)
+            }
+            None => {
+                let (block_metas, statistics) =
+                    self.prune_segments(schema.clone(), push_down, ctx).await?;
+                if let (Some(cache), Some(key)) = (prune_result_cache, cache_key) {
+                    cache.put(key, block_metas.clone()).await;
+                }
+                (block_metas, statistics)
+            }
+        };
+
+        // the blocks are ordered by the order-by column, if they are pruned as top-k
+        match TopK::try_create(&schema, push_down) {
+            None => Ok((block_metas, statistics)),
+            Some(top_k) => {
+                let before = Self::sizes(&block_metas);
+                let (block_metas, threshold) = top_k.prune(block_metas);
+                let after = Self::sizes(&block_metas);
+                tracing::debug!("top-k pruning, {:?}, threshold {:?}", top_k, threshold);
+
+                statistics.blocks_pruned += before.0 - after.0;
+                statistics.rows_pruned_estimate += before.1 - after.1;
+                statistics.bytes_pruned_estimate += before.2 - after.2;
+                statistics.top_k_threshold = threshold;
+                Ok((block_metas, statistics))
+            }
+        }
+    }
+
+    async fn prune_segments(
+        &self,
+        schema: DataSchemaRef,
+        push_down: &Option<Extras>,
+        ctx: &QueryContext,
+    ) -> Result<(Vec<BlockMeta>, PruningStatistics)> {
+        let (block_pred, stats_projection) = Self::build_pred(schema, push_down, ctx);
+        let block_pred = Arc::new(block_pred);
+        let parallelism = ctx.get_settings().get_max_threads()? as usize;
+        // the blocks of all the segments read concurrently are evaluated on no more than
+        // `parallelism` blocking threads
+        let spawner =
+            ConcurrencyLimitedRuntime::create(tokio::runtime::Handle::current(), parallelism);
+
         let segment_locs = self.table_snapshot.segments.clone();
         let segment_num = segment_locs.len();
 
         if segment_locs.is_empty() {
-            return Ok(vec![]);
+            return Ok((vec![], PruningStatistics::default()));
         };
 
         let limit = push_down
@@ -72,67 +156,494 @@ impl BlockPruner {
             .and_then(|p| p.limit)
             .unwrap_or(usize::MAX);
 
-        // Segments and blocks are accumulated concurrently, thus an atomic counter is used
-        // to **try** collecting as less blocks as possible. But concurrency is preferred to
-        // "accuracy". In [FuseTable::do_read_partitions], the "limit" will be treated precisely.
+        // Segments are read concurrently, but their blocks are accumulated in the order of the
+        // segments, so that the blocks kept under the limit are the first ones. The counter is
+        // also checked before reading a segment, to skip the reads once the limit is reached.
+        // In [FuseTable::do_read_partitions], the "limit" will be treated precisely.
 
         let accumulated_rows = AtomicUsize::new(0);
 
-        // A !Copy Wrapper of u64
-        struct NonCopy(u64);
+        // With a limit, no more segments than those expected to cover the limit are read
+        // concurrently, so that segments which are not needed are not read at all.
+        // Filters may drop rows, in which case more segments will be read after them.
+        let max_concurrency = std::cmp::max(
+            1,
+            ctx.get_settings().get_max_pruning_concurrency()? as usize,
+        )
+        .min(segment_num);
+        let concurrency = if limit == usize::MAX {
+            max_concurrency
+        } else {
+            let rows_per_segment = std::cmp::max(
+                1,
+                self.table_snapshot.summary.row_count as usize / segment_num,
+            );
+            let expected_segments = limit.saturating_add(rows_per_segment - 1) / rows_per_segment;
+            expected_segments.max(1).min(max_concurrency)
+        };
+
+        // A !Copy Wrapper of u64 and usize
+        struct NonCopy<T>(T);
 
         // convert u64 (which is Copy) into NonCopy( struct which is !Copy)
         // so that "async move" can be avoided in the latter async block
         // See https://github.com/rust-lang/rust/issues/81653
-        let segment_locs = segment_locs.into_iter().map(|(s, v)| (s, NonCopy(v)));
+        let segment_locs = segment_locs
+            .into_iter()
+            .enumerate()
+            .map(|(i, (s, v))| (NonCopy(i), s, NonCopy(v)));
 
-        let stream = futures::stream::iter(segment_locs)
-            .map(|(seg_loc, u)| async {
-                let version = { u }.0; // use block expression to force moving
+        // the digests kept in the snapshot, if any, prune the segments without reading them
+        let digests = self
+            .table_snapshot
+            .segment_digests
+            .as_ref()
+            .filter(|digests| digests.len() == segment_num);
+
+        let aborted = || ErrorCode::AbortedQuery("the query is aborted while pruning the segments");
+
+        let mut stream = futures::stream::iter(segment_locs)
+            .map(|(i, seg_loc, u)| async {
+                let index = { i }.0; // use block expression to force moving
+                let version = { u }.0;
+                // a killed query stops issuing reads of the segments
+                if ctx.is_aborting() {
+                    return Err(aborted());
+                }
+                if let Some(digest) = digests.map(|digests| &digests[index]) {
+                    if !block_pred(&digest.col_stats, digest.row_count)? {
+                        let statistics = PruningStatistics {
+                            segments_pruned: 1,
+                            blocks_pruned: digest.block_count,
+                            rows_pruned_estimate: digest.row_count,
+                            ..Default::default()
+                        };
+                        return Ok((vec![], statistics));
+                    }
+                }
                 if accumulated_rows.load(Ordering::Acquire) < limit {
-                    let reader = MetaReaders::segment_info_reader(ctx);
-                    let segment_info = reader.read(seg_loc, None, version).await?;
-                    Self::filter_segment(
-                        segment_info.as_ref(),
-                        &block_pred,
-                        &accumulated_rows,
-                        limit,
-                    )
+                    let reader =
+                        MetaReaders::segment_info_reader_with_operator(ctx, self.operator.clone());
+                    let segment_info = match &stats_projection {
+                        Some(projection) => {
+                            reader
+                                .read_projected(seg_loc, None, version, projection)
+                                .await?
+                        }
+                        None => reader.read(seg_loc, None, version).await?,
+                    };
+                    Self::filter_segment(segment_info, &block_pred, &spawner, parallelism).await
                 } else {
-                    Ok(vec![])
+                    Ok((vec![], PruningStatistics::default()))
                 }
             })
-            // configuration of the max size of buffered futures
-            .buffered(std::cmp::min(10, segment_num))
-            .try_collect::<Vec<_>>()
-            .await?
-            .into_iter()
-            .flatten();
+            // configuration of the max size of buffered futures.
+            // the segments are yielded in their order, though read concurrently
+            .buffered(concurrency);
+
+        let mut block_metas = vec![];
+        let mut statistics = PruningStatistics {
+            segments_total: segment_num as u64,
+            blocks_total: self.table_snapshot.summary.block_count,
+            ..Default::default()
+        };
+
+        // a killed query drops the pending reads at once, without waiting for them
+        let abort = ctx.get_abort_registration();
+        while let Some(res) = abort
+            .abortable(stream.next())
+            .await
+            .map_err(|_| aborted())?
+        {
+            // the pending reads are dropped on error
+            let (metas, segment_statistics) = res?;
+            if ctx.is_aborting() {
+                return Err(aborted());
+            }
+
+            statistics.merge(&segment_statistics);
+            for block_meta in metas {
+                let num_rows = block_meta.row_count as usize;
+                if accumulated_rows.fetch_add(num_rows, Ordering::Release) >= limit {
+                    break;
+                }
+                block_metas.push(block_meta);
+            }
 
-        Ok(stream.collect::<Vec<_>>())
+            // the segments being read are dropped, once the limit is reached
+            if accumulated_rows.load(Ordering::Acquire) >= limit {
+                break;
+            }
+        }
+
+        Ok((block_metas, statistics))
     }
 
-    #[inline]
-    fn filter_segment(
-        segment_info: &SegmentInfo,
-        pred: &Pred,
-        accumulated_rows: &AtomicUsize,
-        limit: usize,
-    ) -> Result<Vec<BlockMeta>> {
-        if pred(&segment_info.summary.col_stats)? {
-            let block_num = segment_info.blocks.len();
-            let mut acc = Vec::with_capacity(block_num);
+    /// Prunes the segments by the push-downs, but defers the admission of their blocks until
+    /// they are requested, so that the filters discovered while running (e.g. by the build side
+    /// of a join) may prune the blocks as well.
+    ///
+    /// The limit of the push-downs is not applied, it is left to the reading of the blocks.
+    pub async fn apply_lazy(
+        &self,
+        schema: DataSchemaRef,
+        push_down: &Option<Extras>,
+        ctx: &QueryContext,
+    ) -> Result<LazyBlocks> {
+        let (block_pred, stats_projection) = Self::build_pred(schema, push_down, ctx);
+        let segment_locs = self.table_snapshot.segments.clone();
+        let mut statistics = PruningStatistics {
+            segments_total: segment_locs.len() as u64,
+            blocks_total: self.table_snapshot.summary.block_count,
+            ..Default::default()
+        };
+
+        let concurrency = std::cmp::max(
+            1,
+            ctx.get_settings().get_max_pruning_concurrency()? as usize,
+        );
+        let mut stream = futures::stream::iter(segment_locs)
+            .map(|(seg_loc, version)| {
+                let reader =
+                    MetaReaders::segment_info_reader_with_operator(ctx, self.operator.clone());
+                let stats_projection = &stats_projection;
+                async move {
+                    match stats_projection {
+                        Some(projection) => {
+                            reader
+                                .read_projected(seg_loc, None, version, projection)
+                                .await
+                        }
+                        None => reader.read(seg_loc, None, version).await,
+                    }
+                }
+            })
+            .buffered(concurrency);
+
+        let mut segments = vec![];
+        while let Some(segment_info) = stream.next().await {
+            let segment_info = segment_info?;
+            if ctx.is_aborting() {
+                return Err(ErrorCode::AbortedQuery(
+                    "the query is aborted while pruning the segments",
+                ));
+            }
+            let summary = &segment_info.summary;
+            if block_pred(&summary.col_stats, summary.row_count)? {
+                segments.push(segment_info);
+            } else {
+                statistics.segments_pruned += 1;
+                for block_meta in &segment_info.blocks {
+                    Self::block_pruned(&mut statistics, block_meta);
+                }
+            }
+        }
+
+        Ok(LazyBlocks::create(segments, block_pred, statistics))
+    }
+
+    /// Explains what the pruning does, segment by segment and block by block, without
+    /// consulting the cache of the results of pruning.
+    ///
+    /// The filters are evaluated the same as the predicate of [BlockPruner::apply], thus the
+    /// same blocks are admitted, except that the limit of the push-downs is not applied.
+    pub async fn explain(
+        &self,
+        schema: DataSchemaRef,
+        push_down: &Option<Extras>,
+        ctx: &QueryContext,
+    ) -> Result<PruneExplanation> {
+        let (range_filters, stats_projection) =
+            Self::build_range_filters(schema.clone(), push_down, ctx);
+        let segment_locs = &self.table_snapshot.segments;
+        let digests = self
+            .table_snapshot
+            .segment_digests
+            .as_ref()
+            .filter(|digests| digests.len() == segment_locs.len());
+
+        let mut explanation = PruneExplanation::default();
+        let mut admitted = vec![];
+        for (index, (seg_loc, version)) in segment_locs.iter().enumerate() {
+            if ctx.is_aborting() {
+                return Err(ErrorCode::AbortedQuery(
+                    "the query is aborted while pruning the segments",
+                ));
+            }
+
+            if let Some(digest) = digests.map(|digests| &digests[index]) {
+                let rejected =
+                    Self::rejected_by(&range_filters, &digest.col_stats, digest.row_count)?;
+                if let Some(expr) = rejected {
+                    explanation.segments.push(SegmentExplanation {
+                        location: (seg_loc.clone(), *version),
+                        decision: PruneDecision::PrunedByDigest(format!("{:?}", expr)),
+                        blocks: vec![],
+                    });
+                    continue;
+                }
+            }
+
+            let reader = MetaReaders::segment_info_reader_with_operator(ctx, self.operator.clone());
+            let segment_info = match &stats_projection {
+                Some(projection) => {
+                    reader
+                        .read_projected(seg_loc, None, *version, projection)
+                        .await?
+                }
+                None => reader.read(seg_loc, None, *version).await?,
+            };
+
+            let summary = &segment_info.summary;
+            let decision =
+                match Self::rejected_by(&range_filters, &summary.col_stats, summary.row_count)? {
+                    None => PruneDecision::Admitted,
+                    Some(expr) => PruneDecision::PrunedByFilter(format!("{:?}", expr)),
+                };
+            let mut blocks = Vec::with_capacity(segment_info.blocks.len());
             for block_meta in &segment_info.blocks {
-                if pred(&block_meta.col_stats)? {
-                    let num_rows = block_meta.row_count as usize;
-                    if accumulated_rows.fetch_add(num_rows, Ordering::Release) < limit {
-                        acc.push(block_meta.clone());
+                let block_decision = if !decision.is_admitted() {
+                    PruneDecision::Skipped
+                } else {
+                    let col_stats = &block_meta.col_stats;
+                    match Self::rejected_by(&range_filters, col_stats, block_meta.row_count)? {
+                        None => {
+                            admitted.push(block_meta.clone());
+                            PruneDecision::Admitted
+                        }
+                        Some(expr) => PruneDecision::PrunedByFilter(format!("{:?}", expr)),
                     }
+                };
+                blocks.push(BlockExplanation {
+                    location: block_meta.location.clone(),
+                    row_count: block_meta.row_count,
+                    decision: block_decision,
+                });
+            }
+            explanation.segments.push(SegmentExplanation {
+                location: (seg_loc.clone(), *version),
+                decision,
+                blocks,
+            });
+        }
+
+        if let Some(top_k) = TopK::try_create(&schema, push_down) {
+            let (kept, threshold) = top_k.prune(admitted);
+            let kept = kept
+                .into_iter()
+                .map(|block_meta| block_meta.location)
+                .collect::<HashSet<_>>();
+            let threshold = threshold.map(|v| v.to_string()).unwrap_or_default();
+            let blocks = explanation
+                .segments
+                .iter_mut()
+                .flat_map(|segment| segment.blocks.iter_mut());
+            for block in blocks {
+                if block.decision.is_admitted() && !kept.contains(&block.location) {
+                    block.decision = PruneDecision::PrunedByTopK(threshold.clone());
+                }
+            }
+        }
+
+        Ok(explanation)
+    }
+
+    // the predicate of the filters of the push-downs, and the columns whose statistics are
+    // needed by the predicate and the projection, None if all of them are needed
+    fn build_pred(
+        schema: DataSchemaRef,
+        push_down: &Option<Extras>,
+        ctx: &QueryContext,
+    ) -> (Pred, Option<HashSet<ColumnId>>) {
+        let (range_filters, stats_projection) = Self::build_range_filters(schema, push_down, ctx);
+        let block_pred: Pred = match range_filters.is_empty() {
+            true => Box::new(|_: &BlockStatistics, _: u64| Ok(true)),
+            false => Box::new(move |v: &BlockStatistics, row_count: u64| {
+                Ok(Self::rejected_by(&range_filters, v, row_count)?.is_none())
+            }),
+        };
+        (block_pred, stats_projection)
+    }
+
+    // the range filters of the filters of the push-downs, along with the filters they are
+    // built from, and the columns whose statistics are needed
+    fn build_range_filters(
+        schema: DataSchemaRef,
+        push_down: &Option<Extras>,
+        ctx: &QueryContext,
+    ) -> (Vec<(Expression, RangeFilter)>, Option<HashSet<ColumnId>>) {
+        let mut stats_projection = HashSet::new();
+        let mut range_filters = vec![];
+        if let Some(exprs) = push_down {
+            // the filters are conjunctive, a block is kept only if all of them may match.
+            // filters which can not be verified are treated as always true
+            for expr in &exprs.filters {
+                match RangeFilter::try_create(expr, schema.clone(), Arc::new(ctx.clone())) {
+                    Ok(range_filter) => {
+                        stats_projection.extend(range_filter.column_ids());
+                        range_filters.push((expr.clone(), range_filter));
+                    }
+                    Err(e) => {
+                        tracing::debug!("filter {:?} is not used in pruning: {}", expr, e)
+                    }
+                }
+            }
+        }
+
+        // Besides the filter, the statistics of the projected columns are used while
+        // building partitions. Without a projection, the statistics are all kept.
+        let stats_projection = match push_down {
+            Some(Extras {
+                projection: Some(projection),
+                ..
+            }) => {
+                stats_projection.extend(projection.iter().map(|i| *i as ColumnId));
+                Some(stats_projection)
+            }
+            _ => None,
+        };
+
+        (range_filters, stats_projection)
+    }
+
+    // the first of the filters that the statistics fail, None if they may match all the filters
+    fn rejected_by<'a>(
+        range_filters: &'a [(Expression, RangeFilter)],
+        stats: &BlockStatistics,
+        row_count: u64,
+    ) -> Result<Option<&'a Expression>> {
+        for (expr, range_filter) in range_filters {
+            if !range_filter.eval(stats, row_count)? {
+                return Ok(Some(expr));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The key of the result of pruning in the cache, None if the result is not cacheable.
+    ///
+    /// With a limit, the blocks admitted depend on the order of the concurrent reads of the
+    /// segments, such results are not cached.
+    fn prune_result_cache_key(&self, push_down: &Option<Extras>) -> Option<String> {
+        let (filters, projection) = match push_down {
+            None => (&[][..], None),
+            Some(extras) if extras.limit.is_some() && extras.order_by.is_empty() => return None,
+            Some(extras) => (&extras.filters[..], extras.projection.as_deref()),
+        };
+        Some(PruneResultCache::key(
+            &self.table_snapshot.snapshot_id,
+            filters,
+            projection,
+        ))
+    }
+
+    /// The statistics of a cached result of pruning. The segments pruned are not known,
+    /// every block not admitted is counted as pruned.
+    fn cached_statistics(&self, block_metas: &[BlockMeta]) -> PruningStatistics {
+        let summary = &self.table_snapshot.summary;
+        let (_, rows, bytes) = Self::sizes(block_metas);
+        PruningStatistics {
+            segments_total: self.table_snapshot.segments.len() as u64,
+            blocks_total: summary.block_count,
+            blocks_pruned: summary.block_count.saturating_sub(block_metas.len() as u64),
+            rows_pruned_estimate: summary.row_count.saturating_sub(rows),
+            bytes_pruned_estimate: summary.uncompressed_byte_size.saturating_sub(bytes),
+            ..Default::default()
+        }
+    }
+
+    // the number of blocks, rows and bytes of the blocks
+    fn sizes(block_metas: &[BlockMeta]) -> (u64, u64, u64) {
+        block_metas
+            .iter()
+            .fold((0, 0, 0), |(blocks, rows, bytes), b| {
+                (blocks + 1, rows + b.row_count, bytes + b.block_size)
+            })
+    }
+
+    async fn filter_segment<S: TrySpawn>(
+        segment_info: Arc<SegmentInfo>,
+        pred: &Arc<Pred>,
+        spawner: &S,
+        parallelism: usize,
+    ) -> Result<(Vec<BlockMeta>, PruningStatistics)> {
+        let mut statistics = PruningStatistics::default();
+        if pred(
+            &segment_info.summary.col_stats,
+            segment_info.summary.row_count,
+        )? {
+            let block_num = segment_info.blocks.len();
+            let admitted =
+                Self::filter_blocks(spawner, segment_info.clone(), pred.clone(), parallelism)
+                    .await?;
+            let mut acc = Vec::with_capacity(block_num);
+            for (block_meta, admitted) in segment_info.blocks.iter().zip(admitted) {
+                if admitted {
+                    acc.push(block_meta.clone());
+                } else {
+                    Self::block_pruned(&mut statistics, block_meta);
                 }
             }
-            Ok(acc)
+            Ok((acc, statistics))
         } else {
-            Ok(vec![])
+            statistics.segments_pruned = 1;
+            for block_meta in &segment_info.blocks {
+                Self::block_pruned(&mut statistics, block_meta);
+            }
+            Ok((vec![], statistics))
         }
     }
+
+    /// Evaluates the predicate on each of the blocks of the segment, in the order of the blocks.
+    ///
+    /// The blocks of a segment having at least [BlockPruner::PARALLEL_FILTER_MIN_BLOCKS] blocks
+    /// are split into `parallelism` ranges, which are evaluated on the blocking threads of the
+    /// `spawner`, so that the evaluation does not hold the async tasks which are reading the
+    /// other segments.
+    pub async fn filter_blocks<S: TrySpawn>(
+        spawner: &S,
+        segment_info: Arc<SegmentInfo>,
+        pred: Arc<Pred>,
+        parallelism: usize,
+    ) -> Result<Vec<bool>> {
+        let block_num = segment_info.blocks.len();
+        if parallelism <= 1 || block_num < Self::PARALLEL_FILTER_MIN_BLOCKS {
+            return segment_info
+                .blocks
+                .iter()
+                .map(|block_meta| pred(&block_meta.col_stats, block_meta.row_count))
+                .collect();
+        }
+
+        let range_size = (block_num + parallelism - 1) / parallelism;
+        let handles = (0..block_num)
+            .step_by(range_size)
+            .map(|start| {
+                let segment_info = segment_info.clone();
+                let pred = pred.clone();
+                spawner.try_spawn_blocking(move || {
+                    let end = std::cmp::min(start + range_size, segment_info.blocks.len());
+                    segment_info.blocks[start..end]
+                        .iter()
+                        .map(|block_meta| pred(&block_meta.col_stats, block_meta.row_count))
+                        .collect::<Result<Vec<_>>>()
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // the ranges are joined in turn, thus the results are in the order of the blocks
+        let mut admitted = Vec::with_capacity(block_num);
+        for handle in handles {
+            let range_admitted = handle.err_into().await??;
+            admitted.extend(range_admitted);
+        }
+        Ok(admitted)
+    }
+
+    #[inline]
+    fn block_pruned(statistics: &mut PruningStatistics, block_meta: &BlockMeta) {
+        statistics.blocks_pruned += 1;
+        statistics.rows_pruned_estimate += block_meta.row_count;
+        statistics.bytes_pruned_estimate += block_meta.block_size;
+    }
 }
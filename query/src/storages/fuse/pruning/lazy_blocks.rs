@@ -0,0 +1,110 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+
+use std::sync::Arc;
+
+use common_exception::Result;
+use common_infallible::Mutex;
+use common_infallible::RwLock;
+
+use crate::storages::fuse::meta::BlockMeta;
+use crate::storages::fuse::meta::SegmentInfo;
+use crate::storages::fuse::pruning::Pred;
+use crate::storages::fuse::pruning::PruningStatistics;
+use crate::storages::fuse::pruning::RuntimeFilter;
+
+/// The blocks of the segments admitted by the pruning, whose admission is deferred until
+/// they are requested.
+///
+/// The runtime filters added in the meantime are consulted, besides the predicate of the
+/// push-downs, while the blocks are admitted.
+pub struct LazyBlocks {
+    segments: Vec<Arc<SegmentInfo>>,
+    pred: Pred,
+    runtime_pred: RwLock<Option<Pred>>,
+    runtime_filters: Mutex<Vec<RuntimeFilter>>,
+    // the position of the next block, as (index of segment, index of block)
+    cursor: Mutex<(usize, usize)>,
+    statistics: Mutex<PruningStatistics>,
+}
+
+impl LazyBlocks {
+    pub fn create(
+        segments: Vec<Arc<SegmentInfo>>,
+        pred: Pred,
+        statistics: PruningStatistics,
+    ) -> Self {
+        LazyBlocks {
+            segments,
+            pred,
+            runtime_pred: RwLock::new(None),
+            runtime_filters: Mutex::new(vec![]),
+            cursor: Mutex::new((0, 0)),
+            statistics: Mutex::new(statistics),
+        }
+    }
+
+    /// Adds a filter discovered while running, the blocks admitted already are not affected.
+    ///
+    /// The statistics of the segments are read by the projection of the push-downs, a filter of
+    /// a column out of the projection keeps all the blocks.
+    pub fn add_runtime_filter(&self, filter: RuntimeFilter) {
+        let mut filters = self.runtime_filters.lock();
+        filters.push(filter);
+        *self.runtime_pred.write() = Some(RuntimeFilter::as_pred(filters.clone()));
+    }
+
+    /// Admits at most `max_blocks` of the remaining blocks, in the order of the segments.
+    ///
+    /// Returns an empty vector once all the blocks are consumed.
+    pub fn next_blocks(&self, max_blocks: usize) -> Result<Vec<BlockMeta>> {
+        let mut cursor = self.cursor.lock();
+        let runtime_pred = self.runtime_pred.read();
+        let mut statistics = PruningStatistics::default();
+
+        let mut admitted = Vec::with_capacity(max_blocks);
+        while admitted.len() < max_blocks && cursor.0 < self.segments.len() {
+            let segment = &self.segments[cursor.0];
+            let block_meta = match segment.blocks.get(cursor.1) {
+                None => {
+                    *cursor = (cursor.0 + 1, 0);
+                    continue;
+                }
+                Some(block_meta) => block_meta,
+            };
+            cursor.1 += 1;
+
+            let mut keep = (self.pred)(&block_meta.col_stats, block_meta.row_count)?;
+            if let (true, Some(runtime_pred)) = (keep, runtime_pred.as_ref()) {
+                keep = runtime_pred(&block_meta.col_stats, block_meta.row_count)?;
+            }
+            if keep {
+                admitted.push(block_meta.clone());
+            } else {
+                statistics.blocks_pruned += 1;
+                statistics.rows_pruned_estimate += block_meta.row_count;
+                statistics.bytes_pruned_estimate += block_meta.block_size;
+            }
+        }
+
+        self.statistics.lock().merge(&statistics);
+        Ok(admitted)
+    }
+
+    /// The statistics of the pruning so far, including the blocks pruned while admitted.
+    pub fn statistics(&self) -> PruningStatistics {
+        self.statistics.lock().clone()
+    }
+}
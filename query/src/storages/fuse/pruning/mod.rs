@@ -13,5 +13,19 @@
 //  limitations under the License.
 
 mod block_pruner;
+mod lazy_blocks;
+mod prune_explanation;
+mod pruning_statistics;
+mod runtime_filter;
+mod top_k;
 
 pub use block_pruner::BlockPruner;
+pub use block_pruner::Pred;
+pub use lazy_blocks::LazyBlocks;
+pub use prune_explanation::BlockExplanation;
+pub use prune_explanation::PruneDecision;
+pub use prune_explanation::PruneExplanation;
+pub use prune_explanation::SegmentExplanation;
+pub use pruning_statistics::PruningStatistics;
+pub use runtime_filter::RuntimeFilter;
+pub use top_k::TopK;
@@ -0,0 +1,121 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use crate::storages::fuse::meta::Location;
+
+/// The decision of the pruning on a segment or a block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PruneDecision {
+    Admitted,
+    /// Pruned by the min/max of the segment or block, failing the filter.
+    PrunedByFilter(String),
+    /// Pruned by the digest of the segment kept in the snapshot, failing the filter.
+    PrunedByDigest(String),
+    /// Pruned as the min/max of the order-by column is worse than the threshold.
+    PrunedByTopK(String),
+    /// Not decided, as the segment is pruned.
+    Skipped,
+}
+
+impl PruneDecision {
+    pub fn is_admitted(&self) -> bool {
+        matches!(self, PruneDecision::Admitted)
+    }
+}
+
+impl Display for PruneDecision {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PruneDecision::Admitted => write!(f, "admitted"),
+            PruneDecision::PrunedByFilter(filter) => write!(f, "pruned by filter {}", filter),
+            PruneDecision::PrunedByDigest(filter) => {
+                write!(f, "pruned by digest with filter {}", filter)
+            }
+            PruneDecision::PrunedByTopK(threshold) => {
+                write!(f, "pruned by top-k with threshold {}", threshold)
+            }
+            PruneDecision::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BlockExplanation {
+    pub location: Location,
+    pub row_count: u64,
+    pub decision: PruneDecision,
+}
+
+#[derive(Debug, Clone)]
+pub struct SegmentExplanation {
+    pub location: Location,
+    pub decision: PruneDecision,
+    /// The blocks of the segment, empty if the segment is pruned without being read.
+    pub blocks: Vec<BlockExplanation>,
+}
+
+/// What the pruning of a table scan does, segment by segment and block by block.
+///
+/// Produced by [crate::storages::fuse::pruning::BlockPruner::explain].
+#[derive(Debug, Clone, Default)]
+pub struct PruneExplanation {
+    pub segments: Vec<SegmentExplanation>,
+}
+
+impl PruneExplanation {
+    /// The locations of the blocks admitted, in the order of the segments.
+    pub fn admitted_blocks(&self) -> Vec<&Location> {
+        self.segments
+            .iter()
+            .flat_map(|segment| segment.blocks.iter())
+            .filter(|block| block.decision.is_admitted())
+            .map(|block| &block.location)
+            .collect()
+    }
+
+    pub fn segments_pruned(&self) -> usize {
+        self.segments
+            .iter()
+            .filter(|segment| !segment.decision.is_admitted())
+            .count()
+    }
+}
+
+impl Display for PruneExplanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let blocks_admitted = self.admitted_blocks().len();
+        writeln!(
+            f,
+            "segments: {}, segments pruned: {}, blocks admitted: {}",
+            self.segments.len(),
+            self.segments_pruned(),
+            blocks_admitted
+        )?;
+        for segment in &self.segments {
+            writeln!(f, "segment {}: {}", segment.location.0, segment.decision)?;
+            for block in &segment.blocks {
+                writeln!(
+                    f,
+                    "  block {}, rows {}: {}",
+                    block.location.0, block.row_count, block.decision
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
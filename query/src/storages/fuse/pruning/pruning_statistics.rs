@@ -0,0 +1,45 @@
+//  Copyright 2021 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use common_datavalues::DataValue;
+
+/// Statistics of the pruning of a table scan.
+///
+/// The numbers of segments and blocks are exact, while the rows and bytes pruned are
+/// estimated from the statistics of the pruned blocks.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PruningStatistics {
+    pub segments_total: u64,
+    pub segments_pruned: u64,
+    pub blocks_total: u64,
+    pub blocks_pruned: u64,
+    pub rows_pruned_estimate: u64,
+    pub bytes_pruned_estimate: u64,
+
+    /// The threshold of the top-k pruning of an order-by-limit scan, which the values of the
+    /// result are not worse than; `None` if no block is pruned as top-k by a threshold.
+    pub top_k_threshold: Option<DataValue>,
+}
+
+impl PruningStatistics {
+    pub fn merge(&mut self, other: &PruningStatistics) {
+        self.segments_total += other.segments_total;
+        self.segments_pruned += other.segments_pruned;
+        self.blocks_total += other.blocks_total;
+        self.blocks_pruned += other.blocks_pruned;
+        self.rows_pruned_estimate += other.rows_pruned_estimate;
+        self.bytes_pruned_estimate += other.bytes_pruned_estimate;
+        // the threshold is of a single scan, nothing to merge
+    }
+}
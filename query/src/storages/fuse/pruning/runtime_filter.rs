@@ -0,0 +1,113 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+
+use std::cmp::Ordering;
+
+use common_datavalues::DataValue;
+use common_exception::Result;
+
+use crate::storages::fuse::meta::ColumnId;
+use crate::storages::fuse::pruning::Pred;
+use crate::storages::index::range_filter::compare_value;
+use crate::storages::index::BlockStatistics;
+
+/// A filter of a column, which is only known while the query is running, e.g. the keys
+/// collected by the build side of a hash join.
+///
+/// The filter is an equality one: NULLs never match it.
+#[derive(Debug, Clone)]
+pub enum RuntimeFilter {
+    /// The values are in the range of `[min, max]`.
+    MinMax {
+        column_id: ColumnId,
+        min: DataValue,
+        max: DataValue,
+    },
+    /// The values are one of `values`, which are sorted and have no NULLs.
+    Values {
+        column_id: ColumnId,
+        values: Vec<DataValue>,
+    },
+}
+
+impl RuntimeFilter {
+    pub fn create_min_max(column_id: ColumnId, min: DataValue, max: DataValue) -> Self {
+        RuntimeFilter::MinMax {
+            column_id,
+            min,
+            max,
+        }
+    }
+
+    pub fn create_values(column_id: ColumnId, values: Vec<DataValue>) -> Self {
+        let mut values = values
+            .into_iter()
+            .filter(|v| !v.is_null())
+            .collect::<Vec<_>>();
+        values.sort_by(|l, r| compare_value(l, r).unwrap_or(Ordering::Equal));
+        values.dedup();
+        RuntimeFilter::Values { column_id, values }
+    }
+
+    pub fn column_id(&self) -> ColumnId {
+        match self {
+            RuntimeFilter::MinMax { column_id, .. } => *column_id,
+            RuntimeFilter::Values { column_id, .. } => *column_id,
+        }
+    }
+
+    /// Returns false if none of the rows of the block may match the filter.
+    ///
+    /// Like the range filters, the block is kept if the min/max of the column are not known.
+    pub fn eval(&self, stats: &BlockStatistics, row_count: u64) -> Result<bool> {
+        let stats = match stats.get(&self.column_id()) {
+            Some(stats) if !stats.min_max_skipped => stats,
+            _ => return Ok(true),
+        };
+        if row_count > 0 && stats.null_count >= row_count {
+            return Ok(false);
+        }
+        if stats.min.is_null() || stats.max.is_null() {
+            return Ok(true);
+        }
+
+        let may_match = match self {
+            RuntimeFilter::MinMax { min, max, .. } => {
+                !Self::is_less(&stats.max, min) && !Self::is_less(max, &stats.min)
+            }
+            RuntimeFilter::Values { values, .. } => values
+                .iter()
+                .any(|v| !Self::is_less(v, &stats.min) && !Self::is_less(&stats.max, v)),
+        };
+        Ok(may_match)
+    }
+
+    /// The filters as a predicate of the pruning, all of them must be passed.
+    pub fn as_pred(filters: Vec<RuntimeFilter>) -> Pred {
+        Box::new(move |stats: &BlockStatistics, row_count: u64| {
+            for filter in &filters {
+                if !filter.eval(stats, row_count)? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        })
+    }
+
+    // the values which are not comparable are not considered to be less
+    fn is_less(l: &DataValue, r: &DataValue) -> bool {
+        compare_value(l, r) == Some(Ordering::Less)
+    }
+}
@@ -0,0 +1,202 @@
+//  Copyright 2022 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::cmp::Ordering;
+
+use common_datavalues::DataSchemaRef;
+use common_datavalues::DataValue;
+use common_planners::Expression;
+use common_planners::Extras;
+
+use crate::storages::fuse::meta::BlockMeta;
+use crate::storages::fuse::meta::ColumnId;
+use crate::storages::index::range_filter::compare_value;
+use crate::storages::index::ColumnStatistics;
+
+/// Prunes the blocks of `ORDER BY col [ASC | DESC] LIMIT n` by the min/max of the column.
+///
+/// The blocks are taken in the order of their best values, i.e. the max for DESC and the
+/// min for ASC. Once the blocks taken have `n` rows, the worst of their worst values is a
+/// threshold, which the values of the result are not worse than. The blocks whose best values
+/// are worse than the threshold are pruned, the blocks tie with it are kept.
+///
+/// NULLs are ranked before or after all the values, according to `nulls_first`.
+#[derive(Debug, Clone)]
+pub struct TopK {
+    column_id: ColumnId,
+    asc: bool,
+    nulls_first: bool,
+    limit: usize,
+}
+
+impl TopK {
+    /// Only the push-downs of an order-by of a single column and a limit are supported.
+    ///
+    /// With filters, the rows of a block may not be in the result, thus such push-downs are
+    /// not supported either.
+    pub fn try_create(schema: &DataSchemaRef, push_down: &Option<Extras>) -> Option<TopK> {
+        let extras = push_down.as_ref()?;
+        let limit = extras.limit?;
+        if !extras.filters.is_empty() || extras.order_by.len() != 1 {
+            return None;
+        }
+
+        match &extras.order_by[0] {
+            Expression::Sort {
+                expr,
+                asc,
+                nulls_first,
+                ..
+            } => match expr.as_ref() {
+                Expression::Column(name) => Some(TopK {
+                    column_id: schema.index_of(name).ok()? as ColumnId,
+                    asc: *asc,
+                    nulls_first: *nulls_first,
+                    limit,
+                }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the blocks kept, in the order of their best values, and the threshold if any.
+    ///
+    /// If some blocks have no comparable min/max of the column, the blocks are returned as is.
+    pub fn prune(&self, blocks: Vec<BlockMeta>) -> (Vec<BlockMeta>, Option<DataValue>) {
+        let mut all_stats = Vec::with_capacity(blocks.len());
+        for block in &blocks {
+            match block.col_stats.get(&self.column_id) {
+                Some(stats) if !stats.min_max_skipped => all_stats.push(stats),
+                _ => return (blocks, None),
+            }
+        }
+
+        let mut incomparable = false;
+        let mut order = (0..blocks.len()).collect::<Vec<_>>();
+        order.sort_by(|l, r| {
+            self.rank(&blocks[*l], all_stats[*l], &blocks[*r], all_stats[*r])
+                .unwrap_or_else(|| {
+                    incomparable = true;
+                    Ordering::Equal
+                })
+        });
+        if incomparable {
+            return (blocks, None);
+        }
+
+        let mut kept = Vec::with_capacity(order.len());
+        let mut rows = 0;
+        let mut threshold: Option<&DataValue> = None;
+        for index in order {
+            let (block, stats) = (&blocks[index], all_stats[index]);
+            let all_null = Self::all_null(block, stats);
+
+            if rows >= self.limit && !self.kept_always(stats) {
+                let worse = match threshold {
+                    // the rows taken are all NULLs ranked first
+                    None => true,
+                    Some(_) if all_null => true,
+                    Some(threshold) => self.is_worse(self.best(stats), threshold),
+                };
+                if worse {
+                    continue;
+                }
+            }
+
+            if rows < self.limit && !all_null {
+                let worst = self.worst(stats);
+                threshold = match threshold {
+                    Some(threshold) if !self.is_worse(worst, threshold) => Some(threshold),
+                    _ => Some(worst),
+                };
+            }
+
+            // the NULLs ranked last are not counted, they are worse than the threshold
+            rows += match self.nulls_first {
+                true => block.row_count as usize,
+                false => block.row_count.saturating_sub(stats.null_count) as usize,
+            };
+            kept.push(block.clone());
+        }
+
+        let threshold = match rows >= self.limit {
+            true => threshold.cloned(),
+            false => None,
+        };
+        (kept, threshold)
+    }
+
+    // ranks the blocks by their NULLs and best values, Less if the left one is better
+    fn rank(
+        &self,
+        l_block: &BlockMeta,
+        l: &ColumnStatistics,
+        r_block: &BlockMeta,
+        r: &ColumnStatistics,
+    ) -> Option<Ordering> {
+        let (l_all_null, r_all_null) = (Self::all_null(l_block, l), Self::all_null(r_block, r));
+        match (l_all_null, r_all_null) {
+            (true, true) => return Some(Ordering::Equal),
+            (true, false) if self.nulls_first => return Some(Ordering::Less),
+            (true, false) => return Some(Ordering::Greater),
+            (false, true) if self.nulls_first => return Some(Ordering::Greater),
+            (false, true) => return Some(Ordering::Less),
+            (false, false) => {}
+        }
+
+        match (self.kept_always(l), self.kept_always(r)) {
+            (true, false) => return Some(Ordering::Less),
+            (false, true) => return Some(Ordering::Greater),
+            _ => {}
+        }
+
+        let ordering = compare_value(self.best(l), self.best(r))?;
+        match self.asc {
+            true => Some(ordering),
+            false => Some(ordering.reverse()),
+        }
+    }
+
+    // the blocks having NULLs ranked first always have rows in the result
+    fn kept_always(&self, stats: &ColumnStatistics) -> bool {
+        self.nulls_first && stats.null_count > 0
+    }
+
+    fn all_null(block: &BlockMeta, stats: &ColumnStatistics) -> bool {
+        stats.null_count >= block.row_count || stats.min.is_null() || stats.max.is_null()
+    }
+
+    fn best<'a>(&self, stats: &'a ColumnStatistics) -> &'a DataValue {
+        match self.asc {
+            true => &stats.min,
+            false => &stats.max,
+        }
+    }
+
+    fn worst<'a>(&self, stats: &'a ColumnStatistics) -> &'a DataValue {
+        match self.asc {
+            true => &stats.max,
+            false => &stats.min,
+        }
+    }
+
+    fn is_worse(&self, value: &DataValue, threshold: &DataValue) -> bool {
+        match (compare_value(value, threshold), self.asc) {
+            (Some(Ordering::Greater), true) => true,
+            (Some(Ordering::Less), false) => true,
+            _ => false,
+        }
+    }
+}
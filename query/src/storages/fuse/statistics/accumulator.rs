@@ -27,6 +27,7 @@ use crate::storages::fuse::meta::Compression;
 use crate::storages::fuse::meta::Versioned;
 use crate::storages::index::BlockStatistics;
 use crate::storages::index::ColumnStatistics;
+use crate::storages::index::Histogram;
 
 #[derive(Default)]
 pub struct StatisticsAccumulator {
@@ -36,6 +37,8 @@ pub struct StatisticsAccumulator {
     pub summary_block_count: u64,
     pub in_memory_size: u64,
     pub file_size: u64,
+    /// Buckets of the column histograms, 0 if histograms are not collected
+    pub histogram_buckets: usize,
 }
 
 impl StatisticsAccumulator {
@@ -43,6 +46,13 @@ impl StatisticsAccumulator {
         Default::default()
     }
 
+    pub fn with_histogram_buckets(histogram_buckets: usize) -> Self {
+        Self {
+            histogram_buckets,
+            ..Default::default()
+        }
+    }
+
     pub fn begin(mut self, block: &DataBlock) -> common_exception::Result<PartiallyAccumulated> {
         let row_count = block.num_rows() as u64;
         let block_in_memory_size = block.memory_size() as u64;
@@ -50,7 +60,7 @@ impl StatisticsAccumulator {
         self.summary_block_count += 1;
         self.summary_row_count += row_count;
         self.in_memory_size += block_in_memory_size;
-        let block_stats = Self::acc_columns(block)?;
+        let block_stats = Self::acc_columns_with_histogram(block, self.histogram_buckets)?;
         self.blocks_statistics.push(block_stats.clone());
         Ok(PartiallyAccumulated {
             accumulator: self,
@@ -65,6 +75,15 @@ impl StatisticsAccumulator {
     }
 
     pub fn acc_columns(data_block: &DataBlock) -> common_exception::Result<BlockStatistics> {
+        Self::acc_columns_with_histogram(data_block, 0)
+    }
+
+    /// Besides min/max, builds the histograms of `histogram_buckets` buckets of the numeric
+    /// and date columns. No histogram is built if `histogram_buckets` is 0.
+    pub fn acc_columns_with_histogram(
+        data_block: &DataBlock,
+        histogram_buckets: usize,
+    ) -> common_exception::Result<BlockStatistics> {
         let mut statistics = BlockStatistics::new();
 
         let rows = data_block.num_rows();
@@ -76,12 +95,8 @@ impl StatisticsAccumulator {
             let mut min = DataValue::Null;
             let mut max = DataValue::Null;
 
-            // TODO(b41sh): support max/min aggregate functions for variant
-            let nonull_data_type = remove_nullable(field.data_type());
-            if nonull_data_type.data_type_id() != TypeID::Variant
-                && nonull_data_type.data_type_id() != TypeID::VariantArray
-                && nonull_data_type.data_type_id() != TypeID::VariantObject
-            {
+            let min_max_skipped = !is_min_max_supported(field.data_type());
+            if !min_max_skipped {
                 let mins = eval_aggr("min", vec![], &[column_field.clone()], rows)?;
                 let maxs = eval_aggr("max", vec![], &[column_field], rows)?;
 
@@ -99,12 +114,20 @@ impl StatisticsAccumulator {
                 (false, None) => 0,
             };
 
+            let histogram = if histogram_buckets > 0 && Histogram::is_supported(field.data_type()) {
+                Histogram::try_create(col, histogram_buckets)?
+            } else {
+                None
+            };
+
             let in_memory_size = col.memory_size() as u64;
             let col_stats = ColumnStatistics {
                 min,
                 max,
                 null_count: null_count as u64,
                 in_memory_size,
+                min_max_skipped,
+                histogram,
             };
 
             statistics.insert(idx as u32, col_stats);
@@ -113,6 +136,18 @@ impl StatisticsAccumulator {
     }
 }
 
+/// Whether the min/max of a column of `data_type` are meaningful, i.e. the ordering of
+/// the type is defined.
+///
+/// For the other types (e.g. struct, array, variant), only null count and size are kept.
+pub fn is_min_max_supported(data_type: &DataTypePtr) -> bool {
+    let type_id = remove_nullable(data_type).data_type_id();
+    type_id.is_numeric()
+        || type_id.is_date_or_date_time()
+        || type_id.is_string()
+        || type_id == TypeID::Boolean
+}
+
 pub struct PartiallyAccumulated {
     accumulator: StatisticsAccumulator,
     block_row_count: u64,
@@ -126,6 +161,7 @@ impl PartiallyAccumulated {
         file_size: u64,
         location: String,
         col_metas: HashMap<ColumnId, ColumnMeta>,
+        compression: Compression,
     ) -> StatisticsAccumulator {
         let mut stats = &mut self.accumulator;
         stats.file_size += file_size;
@@ -136,7 +172,8 @@ impl PartiallyAccumulated {
             col_stats: self.block_column_statistics,
             col_metas,
             location: (location, DataBlock::VERSION),
-            compression: Compression::Lz4Raw,
+            compression,
+            external: false,
         };
         stats.blocks_metas.push(block_meta);
         self.accumulator
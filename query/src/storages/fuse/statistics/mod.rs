@@ -15,6 +15,7 @@
 pub mod accumulator;
 pub mod reducers;
 
+pub use accumulator::is_min_max_supported;
 pub use accumulator::PartiallyAccumulated;
 pub use accumulator::StatisticsAccumulator;
 pub use reducers::merge_statistics;
@@ -26,8 +26,10 @@ use common_functions::aggregates::eval_aggr;
 
 use crate::storages::fuse::meta::ColumnId;
 use crate::storages::fuse::meta::Statistics;
+use crate::storages::fuse::statistics::is_min_max_supported;
 use crate::storages::index::BlockStatistics;
 use crate::storages::index::ColumnStatistics;
+use crate::storages::index::Histogram;
 
 pub fn reduce_block_stats<T: Borrow<BlockStatistics>>(
     stats: &[T],
@@ -61,8 +63,17 @@ pub fn reduce_block_stats<T: Borrow<BlockStatistics>>(
             let mut max_stats = Vec::with_capacity(stats.len());
             let mut null_count = 0;
             let mut in_memory_size = 0;
+            let mut min_max_skipped = false;
+            // the histograms are merged only if all of the parts have one
+            let mut histograms = Vec::with_capacity(stats.len());
+            let mut histogram_complete = true;
 
             for col_stats in stats {
+                match &col_stats.histogram {
+                    Some(histogram) => histograms.push(histogram),
+                    None => histogram_complete = false,
+                }
+                min_max_skipped |= col_stats.min_max_skipped;
                 // to be optimized, with DataType and the value of data, we may
                 // able to compare the min/max here
                 min_stats.push(col_stats.min.clone());
@@ -78,12 +89,8 @@ pub fn reduce_block_stats<T: Borrow<BlockStatistics>>(
             let mut min = DataValue::Null;
             let mut max = DataValue::Null;
 
-            // TODO(b41sh): support max/min aggregate functions for variant
-            let nonull_data_type = remove_nullable(data_type);
-            if nonull_data_type.data_type_id() != TypeID::Variant
-                && nonull_data_type.data_type_id() != TypeID::VariantArray
-                && nonull_data_type.data_type_id() != TypeID::VariantObject
-            {
+            min_max_skipped |= !is_min_max_supported(data_type);
+            if !min_max_skipped {
                 let field = schema.field((*id) as usize);
                 // TODO
                 // for some data types, we shall balance the accuracy and the length
@@ -112,11 +119,20 @@ pub fn reduce_block_stats<T: Borrow<BlockStatistics>>(
                 }
             }
 
+            let histogram = if histogram_complete {
+                let num_buckets = histograms.iter().map(|h| h.buckets.len()).max();
+                num_buckets.and_then(|n| Histogram::merge(&histograms, n))
+            } else {
+                None
+            };
+
             acc.insert(*id, ColumnStatistics {
                 min,
                 max,
                 null_count,
                 in_memory_size,
+                min_max_skipped,
+                histogram,
             });
             Ok(acc)
         })
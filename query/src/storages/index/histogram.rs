@@ -0,0 +1,198 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp::Ordering;
+
+use common_datavalues::prelude::*;
+use common_exception::Result;
+
+/// Max number of rows sampled from a column to build its histogram
+const MAX_SAMPLES: usize = 4096;
+
+/// An equi-depth histogram of the values of a numeric or date column.
+///
+/// Values are divided into buckets of about the same number of rows. Bucket `i` covers the
+/// values in `(upper of bucket i-1, upper of bucket i]`, the first bucket starts at `lower`.
+/// Within a bucket, values are assumed to be distributed uniformly. Nulls are not counted.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Histogram {
+    pub lower: f64,
+    /// upper bound and number of rows of each bucket
+    pub buckets: Vec<(f64, u64)>,
+}
+
+impl Histogram {
+    pub fn is_supported(data_type: &DataTypePtr) -> bool {
+        let type_id = remove_nullable(data_type).data_type_id();
+        type_id.is_numeric() || type_id.is_date_or_date_time()
+    }
+
+    /// Builds the histogram of `column`, of at most `num_buckets` buckets, from evenly
+    /// spaced samples of its rows.
+    ///
+    /// The histogram of a column whose values are all null has no buckets.
+    pub fn try_create(column: &ColumnRef, num_buckets: usize) -> Result<Option<Self>> {
+        if num_buckets == 0 {
+            return Ok(None);
+        }
+        let rows = column.len();
+        if rows == 0 {
+            return Ok(Some(Histogram::default()));
+        }
+
+        let step = (rows + MAX_SAMPLES - 1) / MAX_SAMPLES;
+        let mut samples = Vec::with_capacity(rows / step + 1);
+        for row in (0..rows).step_by(step) {
+            let value = column.get(row);
+            if !value.is_null() {
+                let value = value.as_f64()?;
+                if !value.is_nan() {
+                    samples.push(value);
+                }
+            }
+        }
+        if samples.is_empty() {
+            return Ok(Some(Histogram::default()));
+        }
+        samples.sort_by(cmp_f64);
+
+        let (is_all_null, bitmap) = column.validity();
+        let null_count = match (is_all_null, bitmap) {
+            (true, _) => rows,
+            (false, Some(bitmap)) => bitmap.null_count(),
+            (false, None) => 0,
+        };
+        let scale = (rows - null_count) as f64 / samples.len() as f64;
+        let scaled = |n: usize| (n as f64 * scale).round() as u64;
+
+        let num_buckets = num_buckets.min(samples.len());
+        let mut buckets: Vec<(f64, u64)> = Vec::with_capacity(num_buckets);
+        let mut start = 0;
+        for i in 1..=num_buckets {
+            let end = i * samples.len() / num_buckets;
+            let upper = samples[end - 1];
+            let count = scaled(end) - scaled(start);
+            start = end;
+            match buckets.last_mut() {
+                // repeated values never span buckets
+                Some((last_upper, last_count)) if *last_upper == upper => *last_count += count,
+                _ => buckets.push((upper, count)),
+            }
+        }
+
+        Ok(Some(Histogram {
+            lower: samples[0],
+            buckets,
+        }))
+    }
+
+    /// Merges the histograms into one of at most `num_buckets` buckets.
+    pub fn merge(histograms: &[&Histogram], num_buckets: usize) -> Option<Histogram> {
+        let histograms = histograms
+            .iter()
+            .filter(|h| h.rows() > 0)
+            .collect::<Vec<_>>();
+        let total: u64 = histograms.iter().map(|h| h.rows()).sum();
+        if total == 0 {
+            return Some(Histogram::default());
+        }
+        if num_buckets == 0 {
+            return None;
+        }
+
+        let lower = histograms.iter().map(|h| h.lower).reduce(f64::min)?;
+        // the combined distribution is linear between the bounds of the buckets
+        let mut points = histograms
+            .iter()
+            .flat_map(|h| std::iter::once(h.lower).chain(h.buckets.iter().map(|(u, _)| *u)))
+            .collect::<Vec<_>>();
+        points.sort_by(cmp_f64);
+        points.dedup();
+        let cdf = |x: f64| histograms.iter().map(|h| h.cdf(x)).sum::<f64>();
+
+        let mut buckets: Vec<(f64, u64)> = Vec::with_capacity(num_buckets);
+        let mut assigned = 0;
+        let mut idx = 0;
+        let mut prev = (lower, 0.0);
+        for i in 1..=num_buckets {
+            let target = (total as f64 * i as f64 / num_buckets as f64).round();
+            while idx < points.len() && cdf(points[idx]) < target {
+                prev = (points[idx], cdf(points[idx]));
+                idx += 1;
+            }
+            let upper = match points.get(idx) {
+                Some(point) if i < num_buckets => {
+                    let (p0, c0) = prev;
+                    let c1 = cdf(*point);
+                    if c1 > c0 && *point > p0 {
+                        p0 + (target - c0) / (c1 - c0) * (*point - p0)
+                    } else {
+                        *point
+                    }
+                }
+                _ => *points.last()?,
+            };
+            let count = target as u64 - assigned;
+            assigned = target as u64;
+            if count == 0 {
+                continue;
+            }
+            match buckets.last_mut() {
+                Some((last_upper, last_count)) if *last_upper >= upper => *last_count += count,
+                _ => buckets.push((upper, count)),
+            }
+        }
+
+        Some(Histogram { lower, buckets })
+    }
+
+    /// Number of rows described by the histogram
+    pub fn rows(&self) -> u64 {
+        self.buckets.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Estimated number of rows whose value is less than or equal to `x`
+    pub fn cdf(&self, x: f64) -> f64 {
+        if x < self.lower {
+            return 0.0;
+        }
+        let mut acc = 0.0;
+        let mut prev = self.lower;
+        for (upper, count) in &self.buckets {
+            if x >= *upper {
+                acc += *count as f64;
+                prev = *upper;
+                continue;
+            }
+            let width = *upper - prev;
+            let fraction = if width > 0.0 { (x - prev) / width } else { 1.0 };
+            return acc + *count as f64 * fraction;
+        }
+        acc
+    }
+
+    /// Estimated fraction of the rows whose value is in `(low, high]`
+    pub fn selectivity(&self, low: f64, high: f64) -> f64 {
+        let rows = self.rows();
+        if rows == 0 || high <= low {
+            return 0.0;
+        }
+        ((self.cdf(high) - self.cdf(low)) / rows as f64).clamp(0.0, 1.0)
+    }
+}
+
+// NaNs are excluded from the histograms
+fn cmp_f64(a: &f64, b: &f64) -> Ordering {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
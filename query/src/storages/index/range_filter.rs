@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt;
@@ -32,15 +33,27 @@ use common_planners::RequireColumnsVisitor;
 
 use crate::pipelines::transforms::ExpressionExecutor;
 use crate::sessions::QueryContext;
+use crate::storages::index::Histogram;
 
 pub type BlockStatistics = HashMap<u32, ColumnStatistics>;
 
+/// IN lists up to this size are checked value by value, larger ones are checked by their bounds.
+const IN_LIST_EXACT_CHECK_LIMIT: usize = 16;
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct ColumnStatistics {
     pub min: DataValue,
     pub max: DataValue,
     pub null_count: u64,
     pub in_memory_size: u64,
+    /// Set if the ordering of the column type is not defined (e.g. struct, variant), in which
+    /// case `min` and `max` are Null and can not be used to prune.
+    #[serde(default)]
+    pub min_max_skipped: bool,
+    /// Equi-depth histogram of the values, collected if the table option
+    /// `histogram_buckets` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub histogram: Option<Histogram>,
 }
 
 #[derive(Debug, Clone)]
@@ -84,10 +97,19 @@ impl RangeFilter {
         })
     }
 
-    pub fn eval(&self, stats: &BlockStatistics) -> Result<bool> {
+    /// Ids of the columns whose statistics are used by the filter.
+    pub fn column_ids(&self) -> HashSet<u32> {
+        self.stat_columns
+            .iter()
+            .flat_map(|c| c.column_fields.keys().cloned())
+            .collect()
+    }
+
+    /// Checks if the block (or segment) of `row_count` rows may contain the matched rows.
+    pub fn eval(&self, stats: &BlockStatistics, row_count: u64) -> Result<bool> {
         let mut columns = Vec::with_capacity(self.stat_columns.len());
         for col in self.stat_columns.iter() {
-            let val_opt = col.apply_stat_value(stats, self.origin.clone())?;
+            let val_opt = col.apply_stat_value(stats, row_count, self.origin.clone())?;
             if val_opt.is_none() {
                 return Ok(true);
             }
@@ -97,6 +119,7 @@ impl RangeFilter {
         let executed_data_block = self.executor.execute(&data_block)?;
 
         match executed_data_block.column(0).get(0) {
+            // only the bounds of all-null columns are NULLs here, no row matches
             DataValue::Null => Ok(false),
             other => other.as_bool(),
         }
@@ -114,6 +137,15 @@ pub fn build_verifiable_expr(
 
     let (exprs, op) = match expr {
         Expression::Literal { .. } => return expr.clone(),
+        Expression::ScalarFunction { op, args }
+            if matches!(op.to_lowercase().as_str(), "in" | "not_in") =>
+        {
+            let negated = op.to_lowercase() == "not_in";
+            return match rewrite_in_list(negated, args) {
+                Some(rewritten) => build_verifiable_expr(&rewritten, schema, stat_columns),
+                None => unhandled,
+            };
+        }
         Expression::ScalarFunction { op, args } => (args.clone(), op.clone()),
         Expression::BinaryExpression { left, op, right } => match op.to_lowercase().as_str() {
             "and" => {
@@ -131,6 +163,12 @@ pub fn build_verifiable_expr(
                 op.clone(),
             ),
         },
+        Expression::UnaryExpression { op, expr } if op.to_lowercase() == "not" => {
+            return match negate_expr(expr) {
+                Some(negated) => build_verifiable_expr(&negated, schema, stat_columns),
+                None => unhandled,
+            };
+        }
         _ => return unhandled,
     };
 
@@ -138,6 +176,142 @@ pub fn build_verifiable_expr(
         .map_or(unhandled.clone(), |mut v| v.build().unwrap_or(unhandled))
 }
 
+/// Pushes the negation down to the atoms of the expression, e.g.
+/// `not (a < 1 or b is null)` => `a >= 1 and b is not null`.
+///
+/// Returns None if the negation can not be expressed.
+fn negate_expr(expr: &Expression) -> Option<Expression> {
+    match expr {
+        Expression::Literal {
+            value: DataValue::Boolean(v),
+            ..
+        } => Some(lit(!*v)),
+        Expression::UnaryExpression { op, expr } if op.to_lowercase() == "not" => {
+            Some(expr.as_ref().clone())
+        }
+        Expression::ScalarFunction { op, args } => match op.to_lowercase().as_str() {
+            "isnull" => Some(Expression::create_scalar_function(
+                "isNotNull",
+                args.clone(),
+            )),
+            "isnotnull" => Some(Expression::create_scalar_function("isNull", args.clone())),
+            "in" => Some(Expression::create_scalar_function("NOT_IN", args.clone())),
+            "not_in" => Some(Expression::create_scalar_function("IN", args.clone())),
+            _ => None,
+        },
+        Expression::BinaryExpression { left, op, right } => {
+            let negated_op = match op.to_lowercase().as_str() {
+                // not (l and r) => not l or not r
+                "and" => return Some(negate_expr(left)?.or(negate_expr(right)?)),
+                // not (l or r) => not l and not r, the side which can not be negated is
+                // unknown (thus true), and can be left out
+                "or" => {
+                    return match (negate_expr(left), negate_expr(right)) {
+                        (Some(l), Some(r)) => Some(l.and(r)),
+                        (Some(negated), None) | (None, Some(negated)) => Some(negated),
+                        (None, None) => None,
+                    };
+                }
+                "=" => "!=",
+                "!=" | "<>" => "=",
+                "<" => ">=",
+                "<=" => ">",
+                ">" => "<=",
+                ">=" => "<",
+                "like" => "not like",
+                "not like" => "like",
+                "ilike" => "not ilike",
+                "not ilike" => "ilike",
+                _ => return None,
+            };
+            Some(Expression::create_binary_expression(negated_op, vec![
+                left.as_ref().clone(),
+                right.as_ref().clone(),
+            ]))
+        }
+        _ => None,
+    }
+}
+
+/// Rewrites `x [not] in (v1, v2, ...)` into comparisons, which can be verified.
+///
+/// - small lists: `x = v1 or x = v2 ...`, or `x != v1 and x != v2 ...` if negated
+/// - large lists: `x >= min(list) and x <= max(list)`, the values must be comparable literals
+///
+/// NULL never equals to anything, it is ignored in `in` lists, and makes `not in` never true.
+///
+/// Returns None if the expression can not be rewritten.
+fn rewrite_in_list(negated: bool, args: &[Expression]) -> Option<Expression> {
+    let (target, list) = args.split_first()?;
+
+    let mut values = Vec::with_capacity(list.len());
+    for item in list {
+        match item {
+            Expression::Literal {
+                value: DataValue::Null,
+                ..
+            } => {
+                if negated {
+                    return Some(lit(false));
+                }
+            }
+            other => values.push(other.clone()),
+        }
+    }
+
+    if values.is_empty() {
+        // `x in ()` is never true, while `x not in ()` is true for any x which is not NULL
+        return if negated { None } else { Some(lit(false)) };
+    }
+
+    if values.len() <= IN_LIST_EXACT_CHECK_LIMIT {
+        let op = if negated { "!=" } else { "=" };
+        let mut comparisons = values
+            .into_iter()
+            .map(|v| Expression::create_binary_expression(op, vec![target.clone(), v]));
+        let first = comparisons.next()?;
+        return Some(comparisons.fold(first, |acc, c| match negated {
+            true => acc.and(c),
+            false => acc.or(c),
+        }));
+    }
+
+    if negated {
+        return None;
+    }
+
+    let mut min = &values[0];
+    let mut max = &values[0];
+    for item in &values[1..] {
+        if compare_literal(item, min)? == Ordering::Less {
+            min = item;
+        }
+        if compare_literal(item, max)? == Ordering::Greater {
+            max = item;
+        }
+    }
+    Some(target.gt_eq(min.clone()).and(target.lt_eq(max.clone())))
+}
+
+fn compare_literal(left: &Expression, right: &Expression) -> Option<Ordering> {
+    match (left, right) {
+        (Expression::Literal { value: l, .. }, Expression::Literal { value: r, .. }) => {
+            compare_value(l, r)
+        }
+        _ => None,
+    }
+}
+
+/// Compares two values of the statistics, None if they are not comparable.
+pub(crate) fn compare_value(l: &DataValue, r: &DataValue) -> Option<Ordering> {
+    match (l, r) {
+        (DataValue::Int64(l), DataValue::Int64(r)) => Some(l.cmp(r)),
+        (DataValue::UInt64(l), DataValue::UInt64(r)) => Some(l.cmp(r)),
+        (DataValue::String(l), DataValue::String(r)) => Some(l.cmp(r)),
+        (l, r) => l.as_f64().ok()?.partial_cmp(&r.as_f64().ok()?),
+    }
+}
+
 fn inverse_operator(op: &str) -> Result<&str> {
     match op {
         "<" => Ok(">"),
@@ -157,6 +331,7 @@ enum StatType {
     Min,
     Max,
     Nulls,
+    Rows,
 }
 
 impl fmt::Display for StatType {
@@ -165,6 +340,7 @@ impl fmt::Display for StatType {
             StatType::Min => write!(f, "min"),
             StatType::Max => write!(f, "max"),
             StatType::Nulls => write!(f, "nulls"),
+            StatType::Rows => write!(f, "rows"),
         }
     }
 }
@@ -188,7 +364,7 @@ impl StatColumn {
         expr: Expression,
     ) -> Self {
         let column_new = format!("{}_{}", stat_type, field.name());
-        let data_type = if matches!(stat_type, StatType::Nulls) {
+        let data_type = if matches!(stat_type, StatType::Nulls | StatType::Rows) {
             u64::to_data_type()
         } else {
             field.data_type().clone()
@@ -206,29 +382,36 @@ impl StatColumn {
     fn apply_stat_value(
         &self,
         stats: &BlockStatistics,
+        row_count: u64,
         schema: DataSchemaRef,
     ) -> Result<Option<ColumnRef>> {
+        // columns without statistics can not be used to prune
         if self.stat_type == StatType::Nulls {
             // The len of column_fields is 1.
             let (k, _) = self.column_fields.iter().next().unwrap();
-            let stat = stats.get(k).ok_or_else(|| {
-                ErrorCode::UnknownException(format!(
-                    "Unable to get the colStats by ColumnId: {}",
-                    k
-                ))
-            })?;
-            return Ok(Some(Series::from_data(vec![stat.null_count])));
+            return Ok(stats
+                .get(k)
+                .map(|stat| Series::from_data(vec![stat.null_count])));
+        }
+
+        if self.stat_type == StatType::Rows {
+            return Ok(Some(Series::from_data(vec![row_count])));
         }
 
         let mut single_point = true;
         let mut variables = HashMap::with_capacity(self.column_fields.len());
         for (k, v) in &self.column_fields {
-            let stat = stats.get(k).ok_or_else(|| {
-                ErrorCode::UnknownException(format!(
-                    "Unable to get the colStats by ColumnId: {}",
-                    k
-                ))
-            })?;
+            let stat = match stats.get(k) {
+                Some(stat) if !stat.min_max_skipped => stat,
+                _ => return Ok(None),
+            };
+
+            // A NULL bound of a column having values is unknown, the block must be kept.
+            // If all the values are NULLs, comparisons with the NULL bounds are NULLs, which
+            // rightly prune the block.
+            if (stat.min.is_null() || stat.max.is_null()) && stat.null_count < row_count {
+                return Ok(None);
+            }
 
             if single_point && stat.min != stat.max {
                 single_point = false;
@@ -376,7 +559,6 @@ impl<'a> VerifiableExprBuilder<'a> {
     }
 
     fn build(&mut self) -> Result<Expression> {
-        // TODO: support in/not in.
         match self.op {
             "isnull" => {
                 let nulls_expr = self.nulls_column_expr(0)?;
@@ -384,10 +566,10 @@ impl<'a> VerifiableExprBuilder<'a> {
                 Ok(nulls_expr.gt(scalar_expr))
             }
             "isnotnull" => {
-                let left_min = self.min_column_expr(0)?;
-                Ok(Expression::create_scalar_function("isNotNull", vec![
-                    left_min,
-                ]))
+                // not all the values are null
+                let nulls_expr = self.nulls_column_expr(0)?;
+                let rows_expr = self.rows_column_expr(0)?;
+                Ok(nulls_expr.lt(rows_expr))
             }
             "=" => {
                 // left = right => min_left <= max_right and max_left >= min_right
@@ -469,6 +651,8 @@ impl<'a> VerifiableExprBuilder<'a> {
                 } = &self.args[1]
                 {
                     // e.g. col like 'a%' => max_col >= 'a' and min_col < 'b'
+                    // This relies on the min/max of strings being exact values rather than
+                    // truncated prefixes, e.g. a block with max 'ab' is pruned by 'abc%'.
                     let left = left_bound_for_like_pattern(v);
                     if !left.is_empty() {
                         let right = right_bound_for_like_pattern(left.clone());
@@ -561,12 +745,18 @@ impl<'a> VerifiableExprBuilder<'a> {
     fn nulls_column_expr(&mut self, index: usize) -> Result<Expression> {
         self.stat_column_expr(StatType::Nulls, index)
     }
+
+    fn rows_column_expr(&mut self, index: usize) -> Result<Expression> {
+        self.stat_column_expr(StatType::Rows, index)
+    }
 }
 
 fn is_like_pattern_escape(c: u8) -> bool {
     c == b'%' || c == b'_' || c == b'\\'
 }
 
+/// The literal prefix of a like pattern, up to the first unescaped wildcard.
+/// Every string matching the pattern starts with it.
 pub fn left_bound_for_like_pattern(pattern: &[u8]) -> Vec<u8> {
     let mut index = 0;
     let len = pattern.len();
@@ -590,6 +780,8 @@ pub fn left_bound_for_like_pattern(pattern: &[u8]) -> Vec<u8> {
     prefix
 }
 
+/// The smallest string greater than every string starting with `prefix`: the last byte
+/// is incremented after dropping the trailing 0xFF bytes. Empty means no such bound.
 pub fn right_bound_for_like_pattern(prefix: Vec<u8>) -> Vec<u8> {
     let mut res = prefix;
     while !res.is_empty() && *res.last().unwrap() == u8::MAX {
@@ -14,6 +14,7 @@
 
 use std::any::Any;
 use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use common_datablocks::DataBlock;
@@ -32,6 +33,7 @@ use common_streams::SendableDataBlockStream;
 
 use crate::pipelines::new::NewPipeline;
 use crate::sessions::QueryContext;
+use crate::storages::index::Histogram;
 
 #[async_trait::async_trait]
 pub trait Table: Sync + Send {
@@ -140,6 +142,11 @@ pub trait Table: Sync + Send {
         Ok(())
     }
 
+    /// Removes the files which are not referenced by the table any more, and returns them.
+    async fn vacuum(&self, _ctx: Arc<QueryContext>, _dry_run: bool) -> Result<Vec<String>> {
+        Ok(vec![])
+    }
+
     async fn statistics(&self, _ctx: Arc<QueryContext>) -> Result<Option<TableStatistics>> {
         Ok(None)
     }
@@ -150,4 +157,16 @@ pub struct TableStatistics {
     pub data_length: Option<u64>,
     pub data_length_compressed: Option<u64>,
     pub index_length: Option<u64>,
+    /// Histograms of the columns, keyed by the index of column, if collected
+    pub column_histograms: HashMap<u32, Histogram>,
+}
+
+impl TableStatistics {
+    /// Estimated fraction of the rows, whose value of column `column_index` is
+    /// in `(low, high]`, or `None` if the histogram of the column is not collected.
+    pub fn selectivity(&self, column_index: u32, low: f64, high: f64) -> Option<f64> {
+        self.column_histograms
+            .get(&column_index)
+            .map(|h| h.selectivity(low, high))
+    }
 }
@@ -47,7 +47,10 @@ impl SyncSystemTable for ConfigsTable {
         let mut groups: Vec<String> = vec![];
         let mut descs: Vec<String> = vec![];
 
-        let query_config = config.query;
+        let mut query_config = config.query;
+        // mask sensitive data in query
+        query_config.bootstrap_admin_password =
+            mask_string(query_config.bootstrap_admin_password.expose(), 3).into();
         let query_config_value = serde_json::to_value(query_config)?;
         ConfigsTable::extract_config(
             &mut names,
@@ -52,12 +52,14 @@ impl AsyncSystemTable for UsersTable {
             .iter()
             .map(|x| x.auth_info.get_auth_string())
             .collect();
+        let is_locked: Vec<bool> = users.iter().map(|x| x.is_locked).collect();
 
         Ok(DataBlock::create(self.table_info.schema(), vec![
             Series::from_data(names),
             Series::from_data(hostnames),
             Series::from_data(auth_types),
             Series::from_data(auth_strings),
+            Series::from_data(is_locked),
         ]))
     }
 }
@@ -69,6 +71,7 @@ impl UsersTable {
             DataField::new("hostname", Vu8::to_data_type()),
             DataField::new("auth_type", Vu8::to_data_type()),
             DataField::new("auth_string", Vu8::to_data_type()),
+            DataField::new("is_locked", bool::to_data_type()),
         ]);
 
         let table_info = TableInfo {
@@ -33,6 +33,7 @@ pub struct AuthMgr {
 pub enum Credential {
     Jwt {
         token: String,
+        client_ip: Option<String>,
     },
     Password {
         name: String,
@@ -50,22 +51,45 @@ impl AuthMgr {
         })
     }
 
-    pub async fn no_auth(&self) -> Result<UserInfo> {
-        self.users
+    pub async fn no_auth(&self, client_ip: Option<&str>) -> Result<UserInfo> {
+        let user = self
+            .users
             .get_user(&self.tenant, UserIdentity::new("root", "127.0.0.1"))
-            .await
+            .await?;
+        self.users
+            .check_network_policy(&self.tenant, &user, client_ip)
+            .await?;
+        Ok(user)
     }
 
     pub async fn auth(&self, credential: &Credential) -> Result<UserInfo> {
         match credential {
-            Credential::Jwt { token: t } => {
-                let user_name = match &self.jwt {
-                    Some(j) => j.get_user(t.as_str())?,
+            Credential::Jwt {
+                token: t,
+                client_ip,
+            } => {
+                let jwt = match &self.jwt {
+                    Some(j) => j,
                     None => return Err(ErrorCode::AuthenticateFailure("jwt auth not configured.")),
                 };
+                let user_name = jwt.get_user(t.as_str()).await?;
+                let identity = UserIdentity::new(&user_name, "%");
+                let user = match self.users.get_user(&self.tenant, identity.clone()).await {
+                    Ok(user) => user,
+                    Err(e)
+                        if e.code() == ErrorCode::unknown_user_code() && jwt.auto_create_user() =>
+                    {
+                        let user = UserInfo::new(user_name.clone(), "%".to_string(), AuthInfo::JWT);
+                        self.users.add_user(&self.tenant, user, None, true).await?;
+                        self.users.get_user(&self.tenant, identity).await?
+                    }
+                    Err(e) => return Err(e),
+                };
+                user.check_not_locked()?;
                 self.users
-                    .get_user(&self.tenant, UserIdentity::new(&user_name, "%"))
-                    .await
+                    .check_network_policy(&self.tenant, &user, client_ip.as_deref())
+                    .await?;
+                Ok(user)
             }
             Credential::Password {
                 name: n,
@@ -78,8 +102,13 @@ impl AuthMgr {
                         &self.tenant,
                         n,
                         h.as_ref().unwrap_or(&"%".to_string()),
+                        false,
                     )
                     .await?;
+                user.check_not_locked()?;
+                self.users
+                    .check_network_policy(&self.tenant, &user, h.as_deref())
+                    .await?;
                 match &user.auth_info {
                     AuthInfo::None => Ok(user),
                     AuthInfo::Password {
@@ -13,11 +13,15 @@
 // limitations under the License.
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 
 use common_exception::ErrorCode;
 use common_exception::Result;
 use jwt_simple::algorithms::RS256PublicKey;
 use jwt_simple::algorithms::RSAPublicKeyLike;
+use jwt_simple::prelude::Token;
+use jwt_simple::prelude::VerificationOptions;
+use jwt_simple::JWTError;
 
 use crate::configs::Config;
 use crate::users::auth::jwt::jwk;
@@ -27,34 +31,89 @@ pub enum PubKey {
     RSA256(RS256PublicKey),
 }
 
+enum KeyStore {
+    Jwks(jwk::JwkKeyStore),
+    Static(PubKey),
+}
+
 pub struct JwtAuthenticator {
     //Todo(youngsofun): verify settings, like issuer
-    key_store: jwk::JwkKeyStore,
+    key_store: KeyStore,
+    audience: Option<String>,
+    auto_create_user: bool,
 }
 
 // to use user specified (in config) fields
 type CustomClaims = HashMap<String, serde_json::Value>;
 
 impl JwtAuthenticator {
+    /// `jwt_key_file` is either the url of a JWKS, or the path of a PEM encoded RSA public key.
     pub async fn try_create(cfg: Config) -> Result<Option<Self>> {
-        if cfg.query.jwt_key_file.is_empty() {
+        let key_file = cfg.query.jwt_key_file;
+        if key_file.is_empty() {
             return Ok(None);
         }
-        let key_store = jwk::JwkKeyStore::new(cfg.query.jwt_key_file).await?;
-        Ok(Some(JwtAuthenticator { key_store }))
+        let key_store = if key_file.starts_with("http://") || key_file.starts_with("https://") {
+            KeyStore::Jwks(jwk::JwkKeyStore::new(key_file).await?)
+        } else {
+            let pem = std::fs::read_to_string(&key_file).map_err(|e| {
+                ErrorCode::InvalidConfig(format!("Could not read jwt key file {}: {}", key_file, e))
+            })?;
+            let key = RS256PublicKey::from_pem(&pem).map_err(|e| {
+                ErrorCode::InvalidConfig(format!("Invalid jwt key file {}: {}", key_file, e))
+            })?;
+            KeyStore::Static(PubKey::RSA256(key))
+        };
+        let audience = match cfg.query.jwt_audience.is_empty() {
+            true => None,
+            false => Some(cfg.query.jwt_audience),
+        };
+        Ok(Some(JwtAuthenticator {
+            key_store,
+            audience,
+            auto_create_user: cfg.query.jwt_auto_create_user,
+        }))
+    }
+
+    pub fn auto_create_user(&self) -> bool {
+        self.auto_create_user
     }
 
-    pub fn get_user(&self, token: &str) -> Result<String> {
-        let pub_key = self.key_store.get_key(None)?;
+    pub async fn get_user(&self, token: &str) -> Result<String> {
+        let pub_key = match &self.key_store {
+            KeyStore::Static(key) => key.clone(),
+            KeyStore::Jwks(key_store) => {
+                let metadata = Token::decode_metadata(token)
+                    .map_err(|e| ErrorCode::AuthenticateFailure(format!("invalid jwt: {}", e)))?;
+                key_store
+                    .get_key(metadata.key_id().map(|kid| kid.to_string()))
+                    .await?
+            }
+        };
+        let options = VerificationOptions {
+            allowed_audiences: self
+                .audience
+                .as_ref()
+                .map(|audience| HashSet::from([audience.clone()])),
+            ..Default::default()
+        };
         match &pub_key {
-            PubKey::RSA256(pk) => match pk.verify_token::<CustomClaims>(token, None) {
+            PubKey::RSA256(pk) => match pk.verify_token::<CustomClaims>(token, Some(options)) {
                 Ok(c) => match c.subject {
                     None => Err(ErrorCode::AuthenticateFailure(
                         "missing  field `subject` in jwt",
                     )),
                     Some(subject) => Ok(subject),
                 },
-                Err(err) => Err(ErrorCode::AuthenticateFailure(err.to_string())),
+                Err(err) => Err(ErrorCode::AuthenticateFailure(
+                    match err.downcast_ref::<JWTError>() {
+                        Some(JWTError::TokenHasExpired) => "jwt has expired".to_string(),
+                        Some(JWTError::RequiredAudienceMismatch) => {
+                            "jwt is not issued for this audience".to_string()
+                        }
+                        _ => format!("invalid jwt: {}", err),
+                    },
+                )),
             },
         }
     }
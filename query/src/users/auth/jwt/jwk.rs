@@ -15,12 +15,14 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
 
 use base64::decode_config;
 use base64::URL_SAFE_NO_PAD;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_infallible::RwLock;
+use common_tracing::tracing;
 use jwt_simple::prelude::RS256PublicKey;
 use serde::Deserialize;
 use serde::Serialize;
@@ -28,6 +30,7 @@ use serde::Serialize;
 use crate::users::auth::jwt::PubKey;
 
 const JWK_REFRESH_INTERVAL: u64 = 15;
+const JWK_MIN_RELOAD_INTERVAL: Duration = Duration::from_secs(10);
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JwkKey {
@@ -69,17 +72,19 @@ pub struct JwkKeys {
 pub struct JwkKeyStore {
     url: String,
     keys: Arc<RwLock<HashMap<String, PubKey>>>,
-    _refresh_interval: Duration,
+    loaded_at: RwLock<Instant>,
+    refresh_interval: Duration,
 }
 
 impl JwkKeyStore {
     pub async fn new(url: String) -> Result<Self> {
-        let _refresh_interval = Duration::from_secs(JWK_REFRESH_INTERVAL * 60);
+        let refresh_interval = Duration::from_secs(JWK_REFRESH_INTERVAL * 60);
         let keys = Arc::new(RwLock::new(HashMap::new()));
-        let mut s = JwkKeyStore {
+        let s = JwkKeyStore {
             url,
             keys,
-            _refresh_interval,
+            loaded_at: RwLock::new(Instant::now()),
+            refresh_interval,
         };
         s.load_keys().await?;
         Ok(s)
@@ -87,7 +92,7 @@ impl JwkKeyStore {
 }
 
 impl JwkKeyStore {
-    pub async fn load_keys(&mut self) -> Result<()> {
+    pub async fn load_keys(&self) -> Result<()> {
         let response = reqwest::get(&self.url).await.map_err(|e| {
             ErrorCode::NetworkRequestError(format!("Could not download JWKS: {}", e))
         })?;
@@ -98,17 +103,34 @@ impl JwkKeyStore {
         for k in &jwk_keys.keys {
             new_keys.insert(k.kid.to_string(), k.get_public_key()?);
         }
-        let mut keys = self.keys.write();
-        *keys = new_keys;
+        *self.keys.write() = new_keys;
+        *self.loaded_at.write() = Instant::now();
         Ok(())
     }
 
-    pub(super) fn get_key(&self, key_id: Option<String>) -> Result<PubKey> {
+    /// Get the key by its id, reloading the keys first if they are older than `refresh_interval`,
+    /// or if the id is unknown (the keys may have been rotated), at most once every
+    /// `JWK_MIN_RELOAD_INTERVAL` in the latter case.
+    pub(super) async fn get_key(&self, key_id: Option<String>) -> Result<PubKey> {
+        let elapsed = self.loaded_at.read().elapsed();
+        let unknown = match &key_id {
+            Some(kid) => !self.keys.read().contains_key(kid),
+            None => false,
+        };
+        if elapsed >= self.refresh_interval || (unknown && elapsed >= JWK_MIN_RELOAD_INTERVAL) {
+            if let Err(cause) = self.load_keys().await {
+                tracing::warn!("failed to reload JWKS from {}: {}", self.url, cause);
+            }
+        }
+        self.get_loaded_key(key_id)
+    }
+
+    fn get_loaded_key(&self, key_id: Option<String>) -> Result<PubKey> {
         let keys = self.keys.read();
         match key_id {
             Some(kid) => match keys.get(&kid) {
                 None => Err(ErrorCode::AuthenticateFailure(format!(
-                    "key id {} not found",
+                    "unknown key id {} of jwt",
                     &kid
                 ))),
                 Some(k) => Ok((*k).clone()),
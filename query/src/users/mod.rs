@@ -16,6 +16,7 @@ mod role_mgr;
 mod user;
 mod user_api;
 mod user_mgr;
+mod user_network_policy;
 mod user_stage;
 mod user_udf;
 
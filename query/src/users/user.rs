@@ -48,6 +48,13 @@ impl From<&User> for UserInfo {
             grants,
             quota,
             option,
+            is_locked: false,
+            locked_reason: None,
+            settings: Default::default(),
+            password_policy: Default::default(),
+            network_policy: None,
+            is_builtin: false,
+            password_history: vec![],
         }
     }
 }
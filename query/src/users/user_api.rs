@@ -12,11 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
 use common_exception::Result;
+use common_infallible::RwLock;
+use common_management::CachedUserMgr;
+use common_management::NetworkPolicyApi;
+use common_management::NetworkPolicyMgr;
 use common_management::RoleApi;
 use common_management::RoleMgr;
+use common_management::SessionCountApi;
+use common_management::SessionCountMgr;
 use common_management::SettingApi;
 use common_management::SettingMgr;
 use common_management::StageApi;
@@ -28,12 +37,33 @@ use common_management::UserMgr;
 use common_management::WarehouseApi;
 use common_management::WarehouseMgr;
 use common_meta_api::KVApi;
+use common_meta_types::NetworkPolicy;
 
 use crate::common::MetaClientProvider;
 use crate::configs::Config;
 
+// The users read are served from the cache for at most this long, see CachedUserMgr.
+const USER_CACHE_TTL: Duration = Duration::from_secs(5);
+
+// A read of a user, e.g., to authenticate a login, fails rather than stalls for longer than this.
+const USER_READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+// The operator recorded in the audit entries of the changes of users not made by a session,
+// e.g., bootstrapping the admin.
+const SYSTEM_OPERATOR: &str = "system";
+
+// The network policies resolved on connecting are cached for at most this long.
+pub(crate) const NETWORK_POLICY_CACHE_TTL: Duration = Duration::from_secs(5);
+
 pub struct UserApiProvider {
     client: Arc<dyn KVApi>,
+    user_api_clients: Arc<RwLock<HashMap<String, Arc<CachedUserMgr>>>>,
+    // (tenant, policy name) => (loaded at, policy)
+    pub(crate) network_policies: Arc<RwLock<HashMap<(String, String), (Instant, NetworkPolicy)>>>,
+    // who makes the changes of users through this provider, see `with_operator`
+    operator: Option<String>,
+    // fail the changes of users if their audit entries are not recorded
+    audit_strict: bool,
 }
 
 impl UserApiProvider {
@@ -42,11 +72,49 @@ impl UserApiProvider {
             .try_get_kv_client()
             .await?;
 
-        Ok(Arc::new(UserApiProvider { client }))
+        Ok(Arc::new(UserApiProvider {
+            client,
+            user_api_clients: Arc::new(RwLock::new(HashMap::new())),
+            network_policies: Arc::new(RwLock::new(HashMap::new())),
+            operator: None,
+            audit_strict: conf.query.user_audit_strict,
+        }))
+    }
+
+    /// Returns the provider which makes the changes of users on behalf of `operator`, e.g., the
+    /// user of a session, who is recorded in the audit entries. The clients and the caches are
+    /// shared with this one.
+    pub fn with_operator(&self, operator: &str) -> Arc<UserApiProvider> {
+        Arc::new(UserApiProvider {
+            client: self.client.clone(),
+            user_api_clients: self.user_api_clients.clone(),
+            network_policies: self.network_policies.clone(),
+            operator: Some(operator.to_string()),
+            audit_strict: self.audit_strict,
+        })
     }
 
     pub fn get_user_api_client(&self, tenant: &str) -> Result<Arc<dyn UserApi>> {
-        Ok(Arc::new(UserMgr::create(self.client.clone(), tenant)?))
+        let client = self.get_cached_user_api_client(tenant)?;
+        match &self.operator {
+            Some(operator) => Ok(client.with_operator(operator)),
+            None => Ok(client),
+        }
+    }
+
+    fn get_cached_user_api_client(&self, tenant: &str) -> Result<Arc<CachedUserMgr>> {
+        if let Some(client) = self.user_api_clients.read().get(tenant) {
+            return Ok(client.clone());
+        }
+
+        // The failures of recording the audit entries are logged, unless the audit is strict.
+        let user_mgr = UserMgr::create(self.client.clone(), tenant)?
+            .with_read_timeout(USER_READ_TIMEOUT)
+            .with_audit(SYSTEM_OPERATOR, self.audit_strict);
+        let client = Arc::new(CachedUserMgr::create(Arc::new(user_mgr), USER_CACHE_TTL));
+        let mut clients = self.user_api_clients.write();
+        let client = clients.entry(tenant.to_string()).or_insert(client);
+        Ok(client.clone())
     }
 
     pub fn get_role_api_client(&self, tenant: &str) -> Result<Arc<dyn RoleApi>> {
@@ -65,6 +133,26 @@ impl UserApiProvider {
         Ok(Arc::new(SettingMgr::create(self.client.clone(), tenant)?))
     }
 
+    pub fn get_network_policy_api_client(&self, tenant: &str) -> Result<Arc<dyn NetworkPolicyApi>> {
+        Ok(Arc::new(NetworkPolicyMgr::create(
+            self.client.clone(),
+            tenant,
+        )?))
+    }
+
+    /// The sessions of the users of the tenant, counted over the nodes, `node_id` is this node.
+    pub fn get_session_count_api_client(
+        &self,
+        tenant: &str,
+        node_id: &str,
+    ) -> Result<Arc<dyn SessionCountApi>> {
+        Ok(Arc::new(SessionCountMgr::create(
+            self.client.clone(),
+            tenant,
+            node_id,
+        )?))
+    }
+
     pub fn get_warehouse_api_client(&self, tenant: &str) -> Result<Arc<dyn WarehouseApi>> {
         Ok(Arc::new(WarehouseMgr::create(self.client.clone(), tenant)?))
     }
@@ -14,13 +14,21 @@
 
 use common_exception::ErrorCode;
 use common_exception::Result;
+use common_management::ImportMode;
+use common_management::ResolvedGrants;
+use common_management::UserImportStatus;
 use common_meta_types::AuthInfo;
+use common_meta_types::AuthType;
 use common_meta_types::GrantObject;
+use common_meta_types::PasswordPolicy;
+use common_meta_types::UserAuditEntry;
 use common_meta_types::UserIdentity;
 use common_meta_types::UserInfo;
 use common_meta_types::UserOption;
 use common_meta_types::UserPrivilegeSet;
+use common_meta_types::UserQuota;
 
+use crate::configs::Config;
 use crate::users::UserApiProvider;
 
 impl UserApiProvider {
@@ -49,12 +57,17 @@ impl UserApiProvider {
     }
 
     /// find the matched user with the client ip address, like 'u1'@'127.0.0.1', if the specific
-    /// user@host is not found, try 'u1'@'%'.
+    /// user@host is not found, try the most specific host pattern matching the client ip, like
+    /// 'u1'@'127.0.%', and 'u1'@'%' at last.
+    ///
+    /// If `ignore_case`, the users of the name in other cases match too, but the users of the
+    /// exact name are preferred. Errors if the users of more than one such names match.
     pub async fn get_user_with_client_ip(
         &self,
         tenant: &str,
         username: &str,
         client_ip: &str,
+        ignore_case: bool,
     ) -> Result<UserInfo> {
         let user = self
             .get_user(tenant, UserIdentity::new(username, client_ip))
@@ -67,13 +80,63 @@ impl UserApiProvider {
                     Err(e)
                 }
             })?;
-        match user {
-            Some(user) => Ok(user),
-            None => {
-                self.get_user(tenant, UserIdentity::new(username, "%"))
-                    .await
+        if let Some(user) = user {
+            return Ok(user);
+        }
+
+        let client = self.get_user_api_client(tenant)?;
+        let mut candidates = client
+            .get_users_of_name(username)
+            .await?
+            .into_iter()
+            .map(|user| user.data)
+            .filter(|user| user.identity().host_matches(client_ip))
+            .collect::<Vec<_>>();
+
+        // The names in other cases are not under the keys of the name, thus all are listed.
+        if candidates.is_empty() && ignore_case {
+            candidates = self
+                .get_users(tenant)
+                .await?
+                .into_iter()
+                .filter(|user| user.name.eq_ignore_ascii_case(username))
+                .filter(|user| user.identity().host_matches(client_ip))
+                .collect::<Vec<_>>();
+
+            let mut names = candidates
+                .iter()
+                .map(|user| user.name.as_str())
+                .collect::<Vec<_>>();
+            names.sort_unstable();
+            names.dedup();
+            if names.len() > 1 {
+                return Err(ErrorCode::AmbiguousUser(format!(
+                    "user '{}' is ambiguous ignoring the case, of users {}",
+                    username,
+                    names.join(", ")
+                )));
             }
         }
+
+        let matched = candidates.into_iter().max_by_key(|user| {
+            let identity = user.identity();
+            (identity.host_specificity(), identity.hostname)
+        });
+        matched.ok_or_else(|| {
+            ErrorCode::UnknownUser(format!(
+                "unknown user {}",
+                UserIdentity::new(username, client_ip)
+            ))
+        })
+    }
+
+    // Check whether a user exists by name and hostname.
+    pub async fn exists_user(&self, tenant: &str, user: UserIdentity) -> Result<bool> {
+        let client = self.get_user_api_client(tenant)?;
+        client
+            .exists_user(user)
+            .await
+            .map_err(|e| e.add_message_back("(while check user exists)"))
     }
 
     // Get the tenant all users list.
@@ -94,25 +157,88 @@ impl UserApiProvider {
         }
     }
 
-    // Add a new user info.
+    // Export all the users of the tenant, to be imported into another tenant or cluster.
+    pub async fn export_users(&self, tenant: &str) -> Result<Vec<UserInfo>> {
+        let client = self.get_user_api_client(tenant)?;
+        client
+            .export_users()
+            .await
+            .map_err(|e| e.add_message_back("(while export users)."))
+    }
+
+    // Import the users into the tenant, with the result of every user.
+    pub async fn import_users(
+        &self,
+        tenant: &str,
+        users: Vec<UserInfo>,
+        mode: ImportMode,
+    ) -> Result<Vec<(UserIdentity, Result<UserImportStatus>)>> {
+        let client = self.get_user_api_client(tenant)?;
+        client
+            .import_users(users, mode)
+            .await
+            .map_err(|e| e.add_message_back("(while import users)."))
+    }
+
+    // Add a new user info, `password` is the plaintext of its password to be checked by the
+    // password rules of the user and the tenant.
     pub async fn add_user(
         &self,
         tenant: &str,
         user_info: UserInfo,
+        password: Option<&str>,
         if_not_exists: bool,
     ) -> Result<u64> {
         let client = self.get_user_api_client(tenant)?;
-        let add_user = client.add_user(user_info);
-        match add_user.await {
-            Ok(res) => Ok(res),
-            Err(e) => {
-                if if_not_exists && e.code() == ErrorCode::user_already_exists_code() {
-                    Ok(0)
-                } else {
-                    Err(e.add_message_back("(while add user)"))
+        if let Some(password) = password {
+            // The existing one is kept, e.g. the bootstrap admin, whatever the password rules.
+            if if_not_exists {
+                if let Some(seq) = client.get_user_seq(user_info.identity()).await? {
+                    return Ok(seq);
                 }
             }
+            let tenant_policy = client.get_password_policy().await?;
+            user_info
+                .password_policy
+                .stricter(&tenant_policy)
+                .check_password(password)?;
+        }
+        client
+            .add_user(user_info, if_not_exists)
+            .await
+            .map_err(|e| e.add_message_back("(while add user)"))
+    }
+
+    // Create the configured admin user of all the privileges if it does not exist, as a built-in
+    // user. Nothing is changed if the user exists, with the password changed or not.
+    //
+    // An admin without a password is never created: the user can connect from anywhere.
+    pub async fn bootstrap_admin(&self, conf: &Config) -> Result<()> {
+        let name = &conf.query.bootstrap_admin_user;
+        if name.is_empty() {
+            return Ok(());
+        }
+
+        let password = conf.query.bootstrap_admin_password.expose();
+        if password.is_empty() {
+            return Err(ErrorCode::InvalidConfig(format!(
+                "bootstrap_admin_password is required to bootstrap the admin user {}",
+                name
+            )));
         }
+        let auth_info = AuthInfo::new(AuthType::Sha256Password, &Some(password.to_string()))?;
+        let mut user_info = UserInfo::new(name.clone(), "%".to_string(), auth_info);
+        user_info.grants.grant_privileges(
+            &GrantObject::Global,
+            UserPrivilegeSet::available_privileges_on_global(),
+        );
+        user_info.option.set_all_flag();
+        user_info.is_builtin = true;
+
+        // Exact(0) on adding keeps the existing one.
+        self.add_user(&conf.query.tenant_id, user_info, Some(password), true)
+            .await?;
+        Ok(())
     }
 
     pub async fn grant_privileges_to_user(
@@ -138,7 +264,7 @@ impl UserApiProvider {
     ) -> Result<Option<u64>> {
         let client = self.get_user_api_client(tenant)?;
         client
-            .revoke_privileges(user, object, privileges, None)
+            .revoke_privileges(user, object, privileges, None, false)
             .await
             .map_err(|e| e.add_message_back("(while revoke user privileges)"))
     }
@@ -164,40 +290,188 @@ impl UserApiProvider {
     ) -> Result<Option<u64>> {
         let client = self.get_user_api_client(tenant)?;
         client
-            .revoke_role(user, revoke_role.clone(), None)
+            .revoke_role(user, revoke_role.clone(), None, false)
             .await
             .map_err(|e| e.add_message_back("(while revoke role from user)"))
     }
 
+    // Resolve the privileges of a user, granted directly or by the roles.
+    pub async fn get_user_grants(
+        &self,
+        tenant: &str,
+        user: UserIdentity,
+    ) -> Result<ResolvedGrants> {
+        let client = self.get_user_api_client(tenant)?;
+        client
+            .get_user_grants(user)
+            .await
+            .map_err(|e| e.add_message_back("(while get user grants)."))
+    }
+
     // Drop a user by name and hostname.
     pub async fn drop_user(&self, tenant: &str, user: UserIdentity, if_exists: bool) -> Result<()> {
         let client = self.get_user_api_client(tenant)?;
-        let drop_user = client.drop_user(user, None);
-        match drop_user.await {
-            Ok(res) => Ok(res),
-            Err(e) => {
-                if if_exists && e.code() == ErrorCode::unknown_user_code() {
-                    Ok(())
-                } else {
-                    Err(e.add_message_back("(while set drop user)"))
-                }
-            }
-        }
+        client
+            .drop_user(user, None, if_exists, false)
+            .await
+            .map_err(|e| e.add_message_back("(while set drop user)"))
     }
 
-    // Update a user by name and hostname.
+    // Update a user by name and hostname, `password` is the plaintext of the password of
+    // `auth_info` to be checked by the password rules of the user and the tenant.
     pub async fn update_user(
         &self,
         tenant: &str,
         user: UserIdentity,
         auth_info: Option<AuthInfo>,
+        password: Option<&str>,
         user_option: Option<UserOption>,
     ) -> Result<Option<u64>> {
         let client = self.get_user_api_client(tenant)?;
+        if let Some(password) = password {
+            let tenant_policy = client.get_password_policy().await?;
+            client
+                .get_user(user.clone(), None)
+                .await?
+                .data
+                .password_policy
+                .stricter(&tenant_policy)
+                .check_password(password)?;
+        }
         let update_user = client.update_user(user, auth_info, user_option, None);
         match update_user.await {
             Ok(res) => Ok(res),
             Err(e) => Err(e.add_message_back("(while alter user).")),
         }
     }
+
+    // Rename a user by name and hostname, keeping the hostname.
+    pub async fn rename_user(
+        &self,
+        tenant: &str,
+        user: UserIdentity,
+        new_name: &str,
+    ) -> Result<u64> {
+        let client = self.get_user_api_client(tenant)?;
+        client
+            .rename_user(user, new_name, None)
+            .await
+            .map_err(|e| e.add_message_back("(while rename user)."))
+    }
+
+    // Lock a user by name and hostname.
+    pub async fn lock_user(
+        &self,
+        tenant: &str,
+        user: UserIdentity,
+        reason: Option<String>,
+    ) -> Result<Option<u64>> {
+        let client = self.get_user_api_client(tenant)?;
+        client
+            .lock_user(user, reason, None, false)
+            .await
+            .map_err(|e| e.add_message_back("(while lock user)."))
+    }
+
+    // Unlock a user by name and hostname.
+    pub async fn unlock_user(&self, tenant: &str, user: UserIdentity) -> Result<Option<u64>> {
+        let client = self.get_user_api_client(tenant)?;
+        client
+            .unlock_user(user, None)
+            .await
+            .map_err(|e| e.add_message_back("(while unlock user)."))
+    }
+
+    // Set a default setting of the sessions of a user.
+    pub async fn set_user_setting(
+        &self,
+        tenant: &str,
+        user: UserIdentity,
+        name: &str,
+        value: &str,
+    ) -> Result<Option<u64>> {
+        let client = self.get_user_api_client(tenant)?;
+        client
+            .set_user_setting(user, name.to_string(), value.to_string(), None)
+            .await
+            .map_err(|e| e.add_message_back("(while set user setting)."))
+    }
+
+    // Unset a default setting of the sessions of a user.
+    pub async fn unset_user_setting(
+        &self,
+        tenant: &str,
+        user: UserIdentity,
+        name: &str,
+    ) -> Result<Option<u64>> {
+        let client = self.get_user_api_client(tenant)?;
+        client
+            .unset_user_setting(user, name.to_string(), None)
+            .await
+            .map_err(|e| e.add_message_back("(while unset user setting)."))
+    }
+
+    // Attach the user to an existing network policy, or detach it if `None`.
+    pub async fn set_user_network_policy(
+        &self,
+        tenant: &str,
+        user: UserIdentity,
+        network_policy: Option<&str>,
+    ) -> Result<Option<u64>> {
+        if let Some(name) = network_policy {
+            let client = self.get_network_policy_api_client(tenant)?;
+            client.get_network_policy(name, None).await?;
+        }
+        let client = self.get_user_api_client(tenant)?;
+        client
+            .set_user_network_policy(user, network_policy.map(|s| s.to_string()), None)
+            .await
+            .map_err(|e| e.add_message_back("(while set user network policy)."))
+    }
+
+    // Get the password rules of the tenant.
+    pub async fn get_password_policy(&self, tenant: &str) -> Result<PasswordPolicy> {
+        let client = self.get_user_api_client(tenant)?;
+        client
+            .get_password_policy()
+            .await
+            .map_err(|e| e.add_message_back("(while get password policy)."))
+    }
+
+    // Set the password rules of the tenant.
+    pub async fn set_password_policy(&self, tenant: &str, policy: PasswordPolicy) -> Result<u64> {
+        let client = self.get_user_api_client(tenant)?;
+        client
+            .set_password_policy(policy)
+            .await
+            .map_err(|e| e.add_message_back("(while set password policy)."))
+    }
+
+    // List the audit entries of the changes of a user by name, the newest first.
+    pub async fn list_user_audit(
+        &self,
+        tenant: &str,
+        username: &str,
+        limit: usize,
+    ) -> Result<Vec<UserAuditEntry>> {
+        let client = self.get_user_api_client(tenant)?;
+        client
+            .list_user_audit(username, limit)
+            .await
+            .map_err(|e| e.add_message_back("(while list user audit)."))
+    }
+
+    // Update the quota of a user by name and hostname.
+    pub async fn update_user_quota(
+        &self,
+        tenant: &str,
+        user: UserIdentity,
+        quota: UserQuota,
+    ) -> Result<Option<u64>> {
+        let client = self.get_user_api_client(tenant)?;
+        client
+            .update_user_quota(user, quota, None)
+            .await
+            .map_err(|e| e.add_message_back("(while update user quota)."))
+    }
 }
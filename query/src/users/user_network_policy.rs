@@ -0,0 +1,159 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_meta_types::NetworkPolicy;
+use common_meta_types::UserInfo;
+
+use crate::users::user_api::NETWORK_POLICY_CACHE_TTL;
+use crate::users::UserApiProvider;
+
+/// user network policy operations.
+impl UserApiProvider {
+    // Add a new network policy.
+    pub async fn add_network_policy(
+        &self,
+        tenant: &str,
+        policy: NetworkPolicy,
+        if_not_exists: bool,
+    ) -> Result<u64> {
+        let client = self.get_network_policy_api_client(tenant)?;
+        match client.add_network_policy(policy).await {
+            Ok(res) => Ok(res),
+            Err(e) => {
+                if if_not_exists && e.code() == ErrorCode::network_policy_already_exists_code() {
+                    Ok(u64::MIN)
+                } else {
+                    Err(e.add_message_back("(while add network policy)."))
+                }
+            }
+        }
+    }
+
+    // Get one network policy by name, served from the cache if it is loaded lately.
+    pub async fn get_network_policy(&self, tenant: &str, name: &str) -> Result<NetworkPolicy> {
+        let cache_key = (tenant.to_string(), name.to_string());
+        if let Some((cached_at, policy)) = self.network_policies.read().get(&cache_key) {
+            if cached_at.elapsed() < NETWORK_POLICY_CACHE_TTL {
+                return Ok(policy.clone());
+            }
+        }
+
+        let client = self.get_network_policy_api_client(tenant)?;
+        let policy = client.get_network_policy(name, None).await?.data;
+        self.network_policies
+            .write()
+            .insert(cache_key, (Instant::now(), policy.clone()));
+        Ok(policy)
+    }
+
+    // Get the tenant all network policy list.
+    pub async fn get_network_policies(&self, tenant: &str) -> Result<Vec<NetworkPolicy>> {
+        let client = self.get_network_policy_api_client(tenant)?;
+        client
+            .get_network_policies()
+            .await
+            .map_err(|e| e.add_message_back("(while get network policies)."))
+    }
+
+    // Replace a network policy.
+    pub async fn update_network_policy(&self, tenant: &str, policy: NetworkPolicy) -> Result<u64> {
+        let cache_key = (tenant.to_string(), policy.name.clone());
+        let client = self.get_network_policy_api_client(tenant)?;
+        let res = client.update_network_policy(policy, None).await;
+        self.network_policies.write().remove(&cache_key);
+        res.map_err(|e| e.add_message_back("(while update network policy)."))
+    }
+
+    // Drop a network policy by name.
+    pub async fn drop_network_policy(
+        &self,
+        tenant: &str,
+        name: &str,
+        if_exists: bool,
+    ) -> Result<()> {
+        let cache_key = (tenant.to_string(), name.to_string());
+        let client = self.get_network_policy_api_client(tenant)?;
+        let res = client.drop_network_policy(name, None).await;
+        self.network_policies.write().remove(&cache_key);
+        match res {
+            Ok(res) => Ok(res),
+            Err(e) => {
+                if if_exists && e.code() == ErrorCode::unknown_network_policy_code() {
+                    Ok(())
+                } else {
+                    Err(e.add_message_back("(while drop network policy)."))
+                }
+            }
+        }
+    }
+
+    // Errors with `AuthenticateFailure` if the user can not connect from the client address.
+    //
+    // The address is an IP, optionally with a port. A user attached to a network policy is
+    // rejected if the address is not known or can not be parsed, or the policy does not
+    // exist anymore.
+    pub async fn check_network_policy(
+        &self,
+        tenant: &str,
+        user: &UserInfo,
+        client_addr: Option<&str>,
+    ) -> Result<()> {
+        let name = match &user.network_policy {
+            None => return Ok(()),
+            Some(name) => name,
+        };
+
+        let rejected = |reason: &str| {
+            ErrorCode::AuthenticateFailure(format!(
+                "user {} can not connect from {}: {}",
+                user.identity(),
+                client_addr.unwrap_or("an unknown address"),
+                reason
+            ))
+        };
+        let client_ip = client_addr
+            .and_then(parse_client_ip)
+            .ok_or_else(|| rejected("the client address is unknown"))?;
+        let policy = self.get_network_policy(tenant, name).await.map_err(|e| {
+            match e.code() == ErrorCode::unknown_network_policy_code() {
+                true => rejected(&format!("unknown network policy {}", name)),
+                false => e,
+            }
+        })?;
+
+        match policy.is_allowed(&client_ip) {
+            true => Ok(()),
+            false => Err(rejected(&format!("not allowed by network policy {}", name))),
+        }
+    }
+}
+
+// Accepts `ip`, `ip:port`, `[ipv6]` and `[ipv6]:port`.
+fn parse_client_ip(client_addr: &str) -> Option<IpAddr> {
+    let client_addr = client_addr.trim();
+    if let Ok(addr) = client_addr.parse::<SocketAddr>() {
+        return Some(addr.ip());
+    }
+    client_addr
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+        .ok()
+}
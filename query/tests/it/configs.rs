@@ -70,11 +70,18 @@ table_cache_enabled = false
 table_cache_snapshot_count = 256
 table_cache_segment_count = 10240
 table_cache_block_meta_count = 102400
+table_cache_prune_result_mb_size = 64
 table_memory_cache_mb_size = 256
 table_disk_cache_root = \"_cache\"
 table_disk_cache_mb_size = 1024
 management_mode = false
 jwt_key_file = \"\"
+jwt_audience = \"\"
+jwt_auto_create_user = false
+bootstrap_admin_user = \"\"
+bootstrap_admin_password = \"\"
+user_audit_strict = false
+user_name_case_insensitive = false
 
 [log]
 log_level = \"INFO\"
@@ -87,6 +94,7 @@ meta_address = \"\"
 meta_username = \"root\"
 meta_password = \"\"
 meta_client_timeout_in_second = 10
+meta_compress_value_threshold = 0
 rpc_tls_meta_server_root_ca_cert = \"\"
 rpc_tls_meta_service_domain_name = \"localhost\"
 
@@ -227,3 +235,15 @@ fn test_fuse_commit_version() -> Result<()> {
     assert!(v.len() > 0);
     Ok(())
 }
+
+#[test]
+fn test_config_debug_masks_secrets() -> Result<()> {
+    let mut conf = Config::default();
+    conf.query.bootstrap_admin_user = "admin".to_string();
+    conf.query.bootstrap_admin_password = "admin_password".into();
+
+    let debug = format!("{:?}", conf);
+    assert!(debug.contains("bootstrap_admin_user: \"admin\""));
+    assert!(!debug.contains("admin_password"));
+    Ok(())
+}
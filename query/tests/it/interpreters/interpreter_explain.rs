@@ -52,3 +52,48 @@ async fn test_explain_interpreter() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_explain_prune_interpreter() -> Result<()> {
+    let ctx = crate::tests::create_query_context().await?;
+
+    let query = "EXPLAIN PRUNE SELECT number FROM numbers_mt(10) WHERE number > 4";
+    let plan = PlanParser::parse(ctx.clone(), query).await?;
+    let executor = InterpreterFactory::get(ctx, plan)?;
+    assert_eq!(executor.name(), "ExplainInterpreter");
+
+    // only the tables of the FUSE engine are pruned
+    let stream = executor.execute(None).await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let formatted = common_datablocks::pretty_format_blocks(&result)?;
+    assert!(formatted.contains("table numbers_mt:"), "{}", formatted);
+    assert!(formatted.contains("no pruning"), "{}", formatted);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_explain_verbose_interpreter() -> Result<()> {
+    let ctx = crate::tests::create_query_context().await?;
+
+    let query = "EXPLAIN VERBOSE SELECT number FROM numbers_mt(10) LIMIT 3";
+    let plan = PlanParser::parse(ctx.clone(), query).await?;
+    let executor = InterpreterFactory::get(ctx, plan)?;
+    assert_eq!(executor.name(), "ExplainInterpreter");
+
+    let stream = executor.execute(None).await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let formatted = common_datablocks::pretty_format_blocks(&result)?;
+    assert!(
+        formatted.contains("Limit: 3, output schema: [number:UInt64]"),
+        "{}",
+        formatted
+    );
+    assert!(
+        formatted.contains("Projection: number:UInt64, output schema: [number:UInt64]"),
+        "{}",
+        formatted
+    );
+
+    Ok(())
+}
@@ -47,6 +47,7 @@ async fn test_grant_privilege_interpreter() -> Result<()> {
         .add_user(
             &tenant,
             UserInfo::new(name.to_string(), hostname.to_string(), auth_info),
+            None,
             false,
         )
         .await?;
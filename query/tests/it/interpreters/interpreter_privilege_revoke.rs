@@ -44,7 +44,9 @@ async fn test_revoke_privilege_interpreter() -> Result<()> {
     let user_info = UserInfo::new(name.to_string(), hostname.to_string(), auth_info);
     assert_eq!(user_info.grants, UserGrantSet::empty());
     let user_mgr = ctx.get_user_manager();
-    user_mgr.add_user(&tenant, user_info.clone(), false).await?;
+    user_mgr
+        .add_user(&tenant, user_info.clone(), None, false)
+        .await?;
     let query = format!("REVOKE ALL ON *.* FROM '{}'@'{}'", name, hostname);
     let plan = PlanParser::parse(ctx.clone(), &query).await?;
     let executor = InterpreterFactory::get(ctx, plan.clone())?;
@@ -56,7 +56,9 @@ async fn test_grant_role_interpreter() -> Result<()> {
     // Grant role to normal user.
     {
         let user_info = UserInfo::new_no_auth("test_user".to_string(), "%".to_string());
-        user_mgr.add_user(&tenant, user_info.clone(), false).await?;
+        user_mgr
+            .add_user(&tenant, user_info.clone(), None, false)
+            .await?;
         let user_info = user_mgr.get_user(&tenant, user_info.identity()).await?;
         assert_eq!(user_info.grants.roles().len(), 0);
 
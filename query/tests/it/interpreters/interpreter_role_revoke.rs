@@ -43,7 +43,9 @@ async fn test_revoke_role_interpreter() -> Result<()> {
     {
         let mut test_user = UserInfo::new_no_auth("test_user".to_string(), "%".to_string());
         test_user.grants.grant_role("test".to_string());
-        user_mgr.add_user(&tenant, test_user.clone(), false).await?;
+        user_mgr
+            .add_user(&tenant, test_user.clone(), None, false)
+            .await?;
         let user_info = user_mgr.get_user(&tenant, test_user.identity()).await?;
         assert_eq!(user_info.grants.roles().len(), 1);
 
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use common_base::tokio;
+use common_datavalues::prelude::*;
 use common_exception::Result;
 use databend_query::interpreters::*;
 use databend_query::sql::*;
@@ -76,3 +77,43 @@ async fn test_select_interpreter() -> Result<()> {
     }
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_select_interpreter_limit_offset() -> Result<()> {
+    common_tracing::init_default_ut_tracing();
+    let ctx = crate::tests::create_query_context().await?;
+    // One partition of numbers in blocks of 100 rows, in order.
+    ctx.get_settings().set_max_threads(1)?;
+    ctx.get_settings()
+        .set_settings("max_block_size".to_string(), "100".to_string(), false)?;
+
+    let cases = vec![
+        // the offset ends in the middle of a block, the limit spans two blocks
+        (
+            "select number from numbers(2000) limit 10 offset 995",
+            995..1005,
+        ),
+        ("select number from numbers(2000) limit 0", 0..0),
+        ("select number from numbers(2000) limit 0 offset 10", 0..0),
+        (
+            "select number from numbers(200) limit 1000 offset 150",
+            150..200,
+        ),
+    ];
+
+    for (query, want) in cases {
+        let plan = PlanParser::parse(ctx.clone(), query).await?;
+        let executor = InterpreterFactory::get(ctx.clone(), plan)?;
+        let stream = executor.execute(None).await?;
+        let result = stream.try_collect::<Vec<_>>().await?;
+
+        let mut got = vec![];
+        for block in &result {
+            let column: &UInt64Column = Series::check_get(block.column(0))?;
+            got.extend_from_slice(column.values());
+        }
+        assert_eq!(got, want.collect::<Vec<u64>>(), "{}", query);
+    }
+
+    Ok(())
+}
@@ -34,6 +34,7 @@ async fn test_show_grant_interpreter() -> Result<()> {
         .add_user(
             &tenant,
             UserInfo::new_no_auth("test".to_string(), "localhost".to_string()),
+            None,
             false,
         )
         .await?;
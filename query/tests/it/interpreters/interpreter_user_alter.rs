@@ -13,9 +13,12 @@
 // limitations under the License.
 
 use common_base::tokio;
+use common_exception::ErrorCode;
 use common_exception::Result;
 use common_meta_types::AuthInfo;
 use common_meta_types::PasswordHashMethod;
+use common_meta_types::PasswordPolicy;
+use common_meta_types::UserIdentity;
 use common_meta_types::UserInfo;
 use common_meta_types::UserOptionFlag;
 use databend_query::interpreters::*;
@@ -39,7 +42,9 @@ async fn test_alter_user_interpreter() -> Result<()> {
 
     let user_info = UserInfo::new(name.to_string(), hostname.to_string(), auth_info);
     let user_mgr = ctx.get_user_manager();
-    user_mgr.add_user(tenant, user_info.clone(), false).await?;
+    user_mgr
+        .add_user(tenant, user_info.clone(), None, false)
+        .await?;
 
     let old_user = user_mgr.get_user(tenant, user_info.identity()).await?;
     assert_eq!(
@@ -96,3 +101,72 @@ async fn test_alter_user_interpreter() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_alter_user_password_must_change() -> Result<()> {
+    common_tracing::init_default_ut_tracing();
+
+    let ctx = crate::tests::create_query_context().await?;
+    let tenant = ctx.get_tenant();
+    let user_mgr = ctx.get_user_manager();
+
+    // The password rules of the tenant are checked on a new password, by the user manager.
+    user_mgr
+        .set_password_policy(&tenant, PasswordPolicy {
+            min_length: 8,
+            require_complexity: true,
+            ..Default::default()
+        })
+        .await?;
+    let plan = PlanParser::parse(ctx.clone(), "CREATE USER 'u1' IDENTIFIED BY 'short'").await?;
+    let res = InterpreterFactory::get(ctx.clone(), plan)?
+        .execute(None)
+        .await;
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::PasswordPolicyViolation("").code()
+    );
+
+    let plan = PlanParser::parse(
+        ctx.clone(),
+        "CREATE USER 'u1' IDENTIFIED BY 'Passw0rd!' PASSWORD EXPIRE",
+    )
+    .await?;
+    let executor = InterpreterFactory::get(ctx.clone(), plan)?;
+    executor.execute(None).await?;
+    let identity = UserIdentity::new("u1", "%");
+    let user_info = user_mgr.get_user(&tenant, identity.clone()).await?;
+    assert!(user_info.password_policy.must_change);
+
+    // Nothing but the change of the password can be run.
+    ctx.get_current_session().set_current_user(user_info);
+    let plan = PlanParser::parse(ctx.clone(), "SELECT 1").await?;
+    let res = InterpreterFactory::get(ctx.clone(), plan);
+    assert_eq!(
+        res.err().unwrap().code(),
+        ErrorCode::PasswordMustBeChanged("").code()
+    );
+
+    let plan = PlanParser::parse(ctx.clone(), "ALTER USER USER() IDENTIFIED BY 'password'").await?;
+    let res = InterpreterFactory::get(ctx.clone(), plan)?
+        .execute(None)
+        .await;
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::PasswordPolicyViolation("").code()
+    );
+
+    let plan = PlanParser::parse(
+        ctx.clone(),
+        "ALTER USER USER() IDENTIFIED BY 'N3w-Passw0rd'",
+    )
+    .await?;
+    let executor = InterpreterFactory::get(ctx.clone(), plan)?;
+    executor.execute(None).await?;
+    let user_info = user_mgr.get_user(&tenant, identity).await?;
+    assert!(!user_info.password_policy.must_change);
+
+    let plan = PlanParser::parse(ctx.clone(), "SELECT 1").await?;
+    InterpreterFactory::get(ctx.clone(), plan)?;
+    Ok(())
+}
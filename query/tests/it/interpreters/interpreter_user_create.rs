@@ -27,10 +27,24 @@ async fn test_create_user_interpreter() -> Result<()> {
 
     let query = "CREATE USER 'test'@'localhost' IDENTIFIED BY 'password'";
     let plan = PlanParser::parse(ctx.clone(), query).await?;
-    let executor = InterpreterFactory::get(ctx, plan.clone())?;
+    let executor = InterpreterFactory::get(ctx.clone(), plan.clone())?;
     assert_eq!(executor.name(), "CreateUserInterpreter");
     let mut stream = executor.execute(None).await?;
     while let Some(_block) = stream.next().await {}
 
+    // the change is recorded on behalf of the user of the session
+    let query = "CREATE USER 'test_audit'@'localhost' IDENTIFIED BY 'password'";
+    let plan = PlanParser::parse(ctx.clone(), query).await?;
+    let executor = InterpreterFactory::get(ctx.clone(), plan.clone())?;
+    let mut stream = executor.execute(None).await?;
+    while let Some(_block) = stream.next().await {}
+    let entries = ctx
+        .get_user_manager()
+        .list_user_audit(&ctx.get_tenant(), "test_audit", 10)
+        .await?;
+    assert_eq!(entries.len(), 1);
+    let operator = ctx.get_current_user()?.identity().to_string();
+    assert_eq!(entries[0].operator, operator);
+
     Ok(())
 }
@@ -57,7 +57,9 @@ async fn test_drop_user_interpreter() -> Result<()> {
 
         let user_info = UserInfo::new(name.to_string(), hostname.to_string(), auth_info);
         let user_mgr = ctx.get_user_manager();
-        user_mgr.add_user(&tenant, user_info.clone(), false).await?;
+        user_mgr
+            .add_user(&tenant, user_info.clone(), None, false)
+            .await?;
 
         let old_user = user_mgr.get_user(&tenant, user_info.identity()).await?;
         assert_eq!(
@@ -15,6 +15,8 @@
 mod optimizer;
 mod optimizer_constant_folding;
 mod optimizer_expression_transform;
+mod optimizer_predicate_push_down;
+mod optimizer_projection_push_down;
 mod optimizer_scatters;
 mod optimizer_statistics_exact;
 mod optimizer_top_n_push_down;
@@ -84,6 +84,53 @@ async fn test_literal_false_filter() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_constant_folding_filter() -> Result<()> {
+    struct Test {
+        name: &'static str,
+        query: &'static str,
+        expect: &'static str,
+    }
+
+    let tests: Vec<Test> = vec![
+        Test {
+            name: "Trivial predicate is eliminated",
+            query: "select * from numbers_mt(10) where 1 = 1",
+            expect: "\
+                Projection: number:UInt64\
+                \n  ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80, partitions_scanned: 1, partitions_total: 1], push_downs: [projections: [0], filters: [(1 = 1)]]",
+        },
+        Test {
+            name: "Trivial conjunct is eliminated and arithmetic is folded",
+            query: "select * from numbers_mt(10) where 1 = 1 and number > 2 + 3",
+            expect: "\
+                Projection: number:UInt64\
+                \n  Filter: (number > 5)\
+                \n    ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80, partitions_scanned: 1, partitions_total: 1], push_downs: [projections: [0], filters: [((1 = 1) AND (number > (2 + 3)))]]",
+        },
+        Test {
+            name: "False conjunct skips the scan",
+            query: "select * from numbers_mt(10) where 1 > 2 and number > 1",
+            expect: "\
+                Projection: number:UInt64\
+                \n  Filter: false\
+                \n    ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 0, read_bytes: 0, partitions_scanned: 0, partitions_total: 0], push_downs: [projections: [0], filters: [((1 > 2) AND (number > 1))]]",
+        },
+    ];
+
+    for test in tests {
+        let ctx = crate::tests::create_query_context().await?;
+
+        let plan = PlanParser::parse(ctx.clone(), test.query).await?;
+        let mut optimizer = Optimizers::without_scatters(ctx);
+        let optimized = optimizer.optimize(&plan)?;
+        let actual = format!("{:?}", optimized);
+        assert_eq!(test.expect, actual, "{:#?}", test.name);
+    }
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_skip_read_data_source() -> Result<()> {
     struct Test {
@@ -106,7 +153,7 @@ async fn test_skip_read_data_source() -> Result<()> {
             query: "select * from numbers_mt(10) where true limit 0",
             expect: "\
                 Limit: 0\
-                \n  Projection: number:UInt64\n    Filter: true\n      ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 0, read_bytes: 0, partitions_scanned: 0, partitions_total: 0], push_downs: [projections: [0], filters: [true], limit: 0]",
+                \n  Projection: number:UInt64\n    ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 0, read_bytes: 0, partitions_scanned: 0, partitions_total: 0], push_downs: [projections: [0], filters: [true], limit: 0]",
         },
         Test {
             name: "Having with 'having 1+1=3' should skip the scan",
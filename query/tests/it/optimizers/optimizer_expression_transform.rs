@@ -112,8 +112,7 @@ async fn test_expression_transform_optimizer() -> Result<()> {
                 expect: "\
                 Limit: 0\
                 \n  Projection: number:UInt64\
-                \n    Filter: true\
-                \n      ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 0, read_bytes: 0, partitions_scanned: 0, partitions_total: 0], push_downs: [projections: [0], filters: [true], limit: 0]",
+                \n    ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 0, read_bytes: 0, partitions_scanned: 0, partitions_total: 0], push_downs: [projections: [0], filters: [true], limit: 0]",
             },
             Test {
                 name: "Filter true and cond",
@@ -137,7 +136,7 @@ async fn test_expression_transform_optimizer() -> Result<()> {
                 expect: "\
                 Projection: number:UInt64\
                 \n  Filter: false\
-                \n    ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80, partitions_scanned: 1, partitions_total: 1], push_downs: [projections: [0], filters: [(false AND (number > 1))]]",
+                \n    ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 0, read_bytes: 0, partitions_scanned: 0, partitions_total: 0], push_downs: [projections: [0], filters: [(false AND (number > 1))]]",
             },
             Test {
                 name: "Filter cond and false",
@@ -145,7 +144,7 @@ async fn test_expression_transform_optimizer() -> Result<()> {
                 expect: "\
                 Projection: number:UInt64\
                 \n  Filter: false\
-                \n    ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80, partitions_scanned: 1, partitions_total: 1], push_downs: [projections: [0], filters: [((number > 1) AND false)]]",
+                \n    ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 0, read_bytes: 0, partitions_scanned: 0, partitions_total: 0], push_downs: [projections: [0], filters: [((number > 1) AND false)]]",
             },
             Test {
                 name: "Filter false or cond",
@@ -168,16 +167,61 @@ async fn test_expression_transform_optimizer() -> Result<()> {
                 query: "SELECT number from numbers(10) where true OR number > 1",
                 expect: "\
                 Projection: number:UInt64\
-                \n  Filter: true\
-                \n    ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80, partitions_scanned: 1, partitions_total: 1], push_downs: [projections: [0], filters: [(true OR (number > 1))]]",
+                \n  ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80, partitions_scanned: 1, partitions_total: 1], push_downs: [projections: [0], filters: [(true OR (number > 1))]]",
             },
             Test {
                 name: "Filter cond or true",
                 query: "SELECT number from numbers(10) where number > 1 OR true",
                 expect: "\
                 Projection: number:UInt64\
-                \n  Filter: true\
-                \n    ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80, partitions_scanned: 1, partitions_total: 1], push_downs: [projections: [0], filters: [((number > 1) OR true)]]",
+                \n  ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80, partitions_scanned: 1, partitions_total: 1], push_downs: [projections: [0], filters: [((number > 1) OR true)]]",
+            },
+            Test {
+                name: "Filter null and cond",
+                query: "SELECT number from numbers(10) where null and number > 1",
+                expect: "\
+                Projection: number:UInt64\
+                \n  Filter: (NULL and (number > 1))\
+                \n    ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80, partitions_scanned: 1, partitions_total: 1], push_downs: [projections: [0], filters: [(NULL AND (number > 1))]]",
+            },
+            Test {
+                name: "Filter null and false",
+                query: "SELECT number from numbers(10) where null and false",
+                expect: "\
+                Projection: number:UInt64\
+                \n  Filter: false\
+                \n    ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 0, read_bytes: 0, partitions_scanned: 0, partitions_total: 0], push_downs: [projections: [0], filters: [(NULL AND false)]]",
+            },
+            Test {
+                name: "Filter null and true",
+                query: "SELECT number from numbers(10) where null and true",
+                expect: "\
+                Projection: number:UInt64\
+                \n  Filter: NULL\
+                \n    ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 0, read_bytes: 0, partitions_scanned: 0, partitions_total: 0], push_downs: [projections: [0], filters: [(NULL AND true)]]",
+            },
+            Test {
+                name: "Filter null or true",
+                query: "SELECT number from numbers(10) where null or true",
+                expect: "\
+                Projection: number:UInt64\
+                \n  ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80, partitions_scanned: 1, partitions_total: 1], push_downs: [projections: [0], filters: [(NULL OR true)]]",
+            },
+            Test {
+                name: "Filter null or false",
+                query: "SELECT number from numbers(10) where null or false",
+                expect: "\
+                Projection: number:UInt64\
+                \n  Filter: NULL\
+                \n    ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 0, read_bytes: 0, partitions_scanned: 0, partitions_total: 0], push_downs: [projections: [0], filters: [(NULL OR false)]]",
+            },
+            Test {
+                name: "Filter not not",
+                query: "SELECT number from numbers(10) where not not (number > 1)",
+                expect: "\
+                Projection: number:UInt64\
+                \n  Filter: (number > 1)\
+                \n    ReadDataSource: scan schema: [number:UInt64], statistics: [read_rows: 10, read_bytes: 80, partitions_scanned: 1, partitions_total: 1], push_downs: [projections: [0], filters: [(NOT (NOT (number > 1)))]]",
             },
             Test {
                 name: "Projection logics const",
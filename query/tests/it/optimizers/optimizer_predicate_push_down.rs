@@ -0,0 +1,98 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::tokio;
+use common_exception::Result;
+use common_planners::*;
+use databend_query::optimizers::*;
+use databend_query::pipelines::processors::PipelineBuilder;
+use databend_query::sql::PlanParser;
+use futures::TryStreamExt;
+use pretty_assertions::assert_eq;
+
+#[derive(Default)]
+struct PushDownFilters {
+    filters: Vec<Expression>,
+}
+
+impl PlanVisitor for PushDownFilters {
+    fn visit_read_data_source(&mut self, plan: &ReadDataSourcePlan) -> Result<()> {
+        if let Some(extras) = &plan.push_downs {
+            self.filters.extend(extras.filters.iter().cloned());
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_predicate_push_down_optimizer() -> Result<()> {
+    struct Test {
+        name: &'static str,
+        query: &'static str,
+        expect: &'static str,
+    }
+
+    let tests = vec![
+        Test {
+            name: "filter on the scan",
+            query: "select number from numbers(10) where number > 1",
+            expect: "[(number > 1)]",
+        },
+        Test {
+            name: "filter through projection",
+            query: "select number from (select number from numbers(10)) where number > 1",
+            expect: "[(number > 1)]",
+        },
+        Test {
+            name: "filter through alias",
+            query: "select a from (select number as a from numbers(10)) where a > 1 and a < 8",
+            expect: "[(number > 1), (number < 8)]",
+        },
+        Test {
+            name: "filter on computed expression",
+            query: "select b from (select number + 1 as b from numbers(10)) where b > 1",
+            expect: "[]",
+        },
+        Test {
+            name: "filter above limit",
+            query: "select number from (select number from numbers(10) limit 5) where number > 1",
+            expect: "[]",
+        },
+    ];
+
+    for test in tests {
+        let ctx = crate::tests::create_query_context().await?;
+        let plan = PlanParser::parse(ctx.clone(), test.query).await?;
+
+        let mut optimizer = PredicatePushDownOptimizer::create(ctx.clone());
+        let optimized = optimizer.optimize(&plan)?;
+
+        let mut collector = PushDownFilters::default();
+        collector.visit_plan_node(&optimized)?;
+        let actual = format!("{:?}", collector.filters);
+        assert_eq!(test.expect, actual, "{:#?}", test.name);
+
+        // The pushed down filters only prune, the results are the same.
+        let mut results = vec![];
+        for plan in [&plan, &optimized] {
+            let mut pipeline = PipelineBuilder::create(ctx.clone()).build(plan)?;
+            let stream = pipeline.execute().await?;
+            let blocks = stream.try_collect::<Vec<_>>().await?;
+            results.push(common_datablocks::pretty_format_blocks(&blocks)?);
+        }
+        assert_eq!(results[0], results[1], "{:#?}", test.name);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::tokio;
+use common_exception::Result;
+use common_planners::*;
+use databend_query::interpreters::*;
+use databend_query::optimizers::*;
+use databend_query::pipelines::processors::PipelineBuilder;
+use databend_query::sql::PlanParser;
+use futures::TryStreamExt;
+use pretty_assertions::assert_eq;
+
+#[derive(Default)]
+struct ScanColumns {
+    columns: Vec<String>,
+}
+
+impl PlanVisitor for ScanColumns {
+    fn visit_read_data_source(&mut self, plan: &ReadDataSourcePlan) -> Result<()> {
+        let schema = plan.schema();
+        self.columns
+            .extend(schema.fields().iter().map(|f| f.name().clone()));
+        Ok(())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_projection_push_down_optimizer() -> Result<()> {
+    struct Test {
+        name: &'static str,
+        query: &'static str,
+        expect: &'static str,
+    }
+
+    let ctx = crate::tests::create_query_context().await?;
+
+    for query in [
+        "create table default.t(a UInt64 not null, b UInt64 not null, c String not null) Engine = Memory",
+        "insert into default.t values(1, 1, 'x'), (2, 2, 'y'), (3, 3, 'z')",
+    ] {
+        let plan = PlanParser::parse(ctx.clone(), query).await?;
+        let executor = InterpreterFactory::get(ctx.clone(), plan)?;
+        let _ = executor.execute(None).await?;
+    }
+
+    let tests = vec![
+        Test {
+            name: "all columns",
+            query: "select * from default.t",
+            expect: "[\"a\", \"b\", \"c\"]",
+        },
+        Test {
+            name: "subquery projection",
+            query: "select a from (select * from default.t)",
+            expect: "[\"a\"]",
+        },
+        Test {
+            name: "subquery projection with filter",
+            query: "select a from (select * from default.t) where b > 1",
+            expect: "[\"a\", \"b\"]",
+        },
+        Test {
+            name: "subquery projection with order by",
+            query: "select c, a from (select * from default.t) order by a",
+            expect: "[\"a\", \"c\"]",
+        },
+        Test {
+            name: "subquery without columns",
+            query: "select count(*) from (select * from default.t)",
+            expect: "[\"a\"]",
+        },
+    ];
+
+    for test in tests {
+        let plan = PlanParser::parse(ctx.clone(), test.query).await?;
+
+        let mut optimizer = ProjectionPushDownOptimizer::create(ctx.clone());
+        let optimized = optimizer.optimize(&plan)?;
+
+        let mut collector = ScanColumns::default();
+        collector.visit_plan_node(&optimized)?;
+        let actual = format!("{:?}", collector.columns);
+        assert_eq!(test.expect, actual, "{:#?}", test.name);
+        assert_eq!(plan.schema(), optimized.schema(), "{:#?}", test.name);
+
+        let mut results = vec![];
+        for plan in [&plan, &optimized] {
+            let mut pipeline = PipelineBuilder::create(ctx.clone()).build(plan)?;
+            let stream = pipeline.execute().await?;
+            let blocks = stream.try_collect::<Vec<_>>().await?;
+            results.push(common_datablocks::pretty_format_blocks(&blocks)?);
+        }
+        assert_eq!(results[0], results[1], "{:#?}", test.name);
+    }
+
+    Ok(())
+}
@@ -14,6 +14,8 @@
 
 use common_base::tokio;
 use common_exception::Result;
+use common_planners::PlanBuilder;
+use common_planners::PlanNode;
 use databend_query::pipelines::processors::*;
 use databend_query::sql::PlanParser;
 use futures::TryStreamExt;
@@ -191,3 +193,33 @@ async fn test_local_pipeline_builds() -> Result<()> {
     }
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_union_pipeline_builds() -> Result<()> {
+    let ctx = crate::tests::create_query_context().await?;
+    let left = PlanParser::parse(
+        ctx.clone(),
+        "select toInt32(number) as a from numbers_mt(3)",
+    )
+    .await?;
+    let right = PlanParser::parse(
+        ctx.clone(),
+        "select toInt64(number + 10) as b from numbers_mt(2)",
+    )
+    .await?;
+    let plan = PlanBuilder::from(&left).union(&[right])?.build()?;
+    assert_eq!(
+        "[a:Int64]",
+        PlanNode::display_schema(&plan.schema()).to_string()
+    );
+
+    let pipeline_builder = PipelineBuilder::create(ctx.clone());
+    let mut pipeline = pipeline_builder.build(&plan)?;
+    let stream = pipeline.execute().await?;
+    let result = stream.try_collect::<Vec<_>>().await?;
+    let expected = vec![
+        "+----+", "| a  |", "+----+", "| 0  |", "| 1  |", "| 10 |", "| 11 |", "| 2  |", "+----+",
+    ];
+    common_datablocks::assert_blocks_sorted_eq(expected, result.as_slice());
+    Ok(())
+}
@@ -23,6 +23,7 @@ use common_base::tokio;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_meta_types::AuthInfo;
+use common_meta_types::UserIdentity;
 use common_meta_types::UserInfo;
 use databend_query::servers::http::middleware::HTTPSessionEndpoint;
 use databend_query::servers::http::middleware::HTTPSessionMiddleware;
@@ -38,6 +39,7 @@ use headers::Header;
 use hyper::header;
 use jwt_simple::algorithms::RS256KeyPair;
 use jwt_simple::algorithms::RSAKeyPairLike;
+use jwt_simple::claims::Audiences;
 use jwt_simple::claims::JWTClaims;
 use jwt_simple::claims::NoCustomClaims;
 use jwt_simple::prelude::Clock;
@@ -454,24 +456,7 @@ async fn test_auth_jwt() -> Result<()> {
 
     let kid = "test_kid";
     let key_pair = RS256KeyPair::generate(2048)?.with_key_id(kid);
-    let rsa_components = key_pair.public_key().to_components();
-    let e = encode_config(rsa_components.e, URL_SAFE_NO_PAD);
-    let n = encode_config(rsa_components.n, URL_SAFE_NO_PAD);
-    let j =
-        serde_json::json!({"keys": [ {"kty": "RSA", "kid": kid, "e": e, "n": n, } ] }).to_string();
-
-    let server = MockServer::start().await;
-    let json_path = "/jwks.json";
-    // Create a mock on the server.
-    let template = ResponseTemplate::new(200).set_body_raw(j, "application/json");
-    Mock::given(method("GET"))
-        .and(path(json_path))
-        .respond_with(template)
-        .expect(1..)
-        // Mounting the mock on the mock server - it's now effective!
-        .mount(&server)
-        .await;
-    let jwks_url = format!("http://{}{}", server.address(), json_path);
+    let (_server, jwks_url) = start_jwks_server(kid, &key_pair).await;
 
     let session_manager = SessionManagerBuilder::create()
         .jwt_key_file(jwks_url)
@@ -485,34 +470,188 @@ async fn test_auth_jwt() -> Result<()> {
         grants: Default::default(),
         quota: Default::default(),
         option: Default::default(),
+        is_locked: false,
+        locked_reason: None,
+        settings: Default::default(),
+        password_policy: Default::default(),
+        network_policy: None,
+        is_builtin: false,
+        password_history: vec![],
     };
 
     let tenant = "test";
     session_manager
         .get_user_manager()
-        .add_user(tenant, user_info, false)
+        .add_user(tenant, user_info, None, false)
+        .await?;
+
+    let ep = Route::new()
+        .nest("/v1/query", query_route())
+        .with(HTTPSessionMiddleware { session_manager });
+
+    let claims = jwt_claims(user_name, None, false);
+    let token = key_pair.sign(claims)?;
+    let bear = headers::Authorization::bearer(&token).unwrap();
+    test_auth_post(&ep, user_name, bear).await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_auth_jwt_rejected() -> Result<()> {
+    let user_name = "user1";
+
+    let kid = "test_kid";
+    let key_pair = RS256KeyPair::generate(2048)?.with_key_id(kid);
+    let (_server, jwks_url) = start_jwks_server(kid, &key_pair).await;
+
+    let session_manager = SessionManagerBuilder::create()
+        .jwt_key_file(jwks_url)
+        .jwt_audience("databend")
+        .build()
+        .unwrap();
+    let user_info = UserInfo::new(user_name.to_string(), "%".to_string(), AuthInfo::JWT);
+    session_manager
+        .get_user_manager()
+        .add_user("test", user_info, None, false)
         .await?;
 
     let ep = Route::new()
         .nest("/v1/query", query_route())
         .with(HTTPSessionMiddleware { session_manager });
 
-    let now = Some(Clock::now_since_epoch());
-    let claims = JWTClaims {
-        issued_at: now,
-        expires_at: Some(now.unwrap() + jwt_simple::prelude::Duration::from_secs(10)),
-        invalid_before: now,
-        audiences: None,
+    // valid
+    let token = key_pair.sign(jwt_claims(user_name, Some("databend"), false))?;
+    let bear = headers::Authorization::bearer(&token).unwrap();
+    test_auth_post(&ep, user_name, bear).await?;
+
+    // expired
+    let token = key_pair.sign(jwt_claims(user_name, Some("databend"), true))?;
+    let bear = headers::Authorization::bearer(&token).unwrap();
+    test_auth_rejected(&ep, bear, "jwt has expired").await?;
+
+    // wrong audience
+    let token = key_pair.sign(jwt_claims(user_name, Some("other"), false))?;
+    let bear = headers::Authorization::bearer(&token).unwrap();
+    test_auth_rejected(&ep, bear, "jwt is not issued for this audience").await?;
+
+    // tampered: the claims of another token with the signature of this one
+    let token = key_pair.sign(jwt_claims(user_name, Some("databend"), false))?;
+    let other = key_pair.sign(jwt_claims("root", Some("databend"), false))?;
+    let parts = token.split('.').collect::<Vec<_>>();
+    let other_parts = other.split('.').collect::<Vec<_>>();
+    let tampered = format!("{}.{}.{}", parts[0], other_parts[1], parts[2]);
+    let bear = headers::Authorization::bearer(&tampered).unwrap();
+    test_auth_rejected(&ep, bear, "invalid jwt").await?;
+
+    // unknown key id
+    let other_key_pair = RS256KeyPair::generate(2048)?.with_key_id("other_kid");
+    let token = other_key_pair.sign(jwt_claims(user_name, Some("databend"), false))?;
+    let bear = headers::Authorization::bearer(&token).unwrap();
+    test_auth_rejected(&ep, bear, "unknown key id other_kid").await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_auth_jwt_static_key_and_auto_create_user() -> Result<()> {
+    let user_name = "user_auto";
+
+    let key_pair = RS256KeyPair::generate(2048)?;
+    let dir = tempfile::tempdir()?;
+    let key_file = dir.path().join("jwt_key.pem");
+    std::fs::write(&key_file, key_pair.public_key().to_pem()?)?;
+
+    let session_manager = SessionManagerBuilder::create()
+        .jwt_key_file(key_file.to_str().unwrap())
+        .jwt_auto_create_user(true)
+        .build()
+        .unwrap();
+    let user_mgr = session_manager.get_user_manager();
+
+    let ep = Route::new()
+        .nest("/v1/query", query_route())
+        .with(HTTPSessionMiddleware { session_manager });
+
+    let token = key_pair.sign(jwt_claims(user_name, None, false))?;
+    let bear = headers::Authorization::bearer(&token).unwrap();
+    test_auth_post(&ep, user_name, bear).await?;
+
+    let user = user_mgr
+        .get_user("test", UserIdentity::new(user_name, "%"))
+        .await?;
+    assert_eq!(user.auth_info, AuthInfo::JWT);
+
+    // The user created is used on the next login.
+    let bear = headers::Authorization::bearer(&token).unwrap();
+    test_auth_post(&ep, user_name, bear).await?;
+    Ok(())
+}
+
+async fn start_jwks_server(kid: &str, key_pair: &RS256KeyPair) -> (MockServer, String) {
+    let rsa_components = key_pair.public_key().to_components();
+    let e = encode_config(rsa_components.e, URL_SAFE_NO_PAD);
+    let n = encode_config(rsa_components.n, URL_SAFE_NO_PAD);
+    let j =
+        serde_json::json!({"keys": [ {"kty": "RSA", "kid": kid, "e": e, "n": n, } ] }).to_string();
+
+    let server = MockServer::start().await;
+    let json_path = "/jwks.json";
+    // Create a mock on the server.
+    let template = ResponseTemplate::new(200).set_body_raw(j, "application/json");
+    Mock::given(method("GET"))
+        .and(path(json_path))
+        .respond_with(template)
+        .expect(1..)
+        // Mounting the mock on the mock server - it's now effective!
+        .mount(&server)
+        .await;
+    let jwks_url = format!("http://{}{}", server.address(), json_path);
+    (server, jwks_url)
+}
+
+fn jwt_claims(user_name: &str, audience: Option<&str>, expired: bool) -> JWTClaims<NoCustomClaims> {
+    let now = Clock::now_since_epoch();
+    let hour = jwt_simple::prelude::Duration::from_hours(1);
+    let (issued_at, expires_at) = match expired {
+        true => (now - hour - hour, now - hour),
+        false => (now, now + jwt_simple::prelude::Duration::from_secs(10)),
+    };
+    JWTClaims {
+        issued_at: Some(issued_at),
+        expires_at: Some(expires_at),
+        invalid_before: Some(issued_at),
+        audiences: audience.map(|a| Audiences::AsString(a.to_string())),
         issuer: None,
         jwt_id: None,
         subject: Some(user_name.to_string()),
         nonce: None,
         custom: NoCustomClaims {},
-    };
+    }
+}
 
-    let token = key_pair.sign(claims)?;
-    let bear = headers::Authorization::bearer(&token).unwrap();
-    test_auth_post(&ep, user_name, bear).await?;
+async fn test_auth_rejected(ep: &EndpointType, header: impl Header, message: &str) -> Result<()> {
+    let json = serde_json::json!({"sql": "select current_user()"});
+    let response = ep
+        .call(
+            Request::builder()
+                .uri("/v1/query".parse().unwrap())
+                .method(Method::POST)
+                .header(header::CONTENT_TYPE, "application/json")
+                .typed_header(header)
+                .body(serde_json::to_vec(&json)?),
+        )
+        .await;
+    let err = match response {
+        Ok(response) => panic!("expect rejected, got {:?}", response.status()),
+        Err(err) => err,
+    };
+    assert_eq!(err.as_response().status(), StatusCode::UNAUTHORIZED);
+    assert!(
+        err.to_string().contains(message),
+        "expect '{}' in '{}'",
+        message,
+        err
+    );
     Ok(())
 }
 
@@ -0,0 +1,121 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use common_exception::ErrorCode;
+use common_meta_types::StageParams;
+use common_meta_types::StageS3Storage;
+use common_meta_types::StageStorage;
+use common_meta_types::StageType;
+use common_meta_types::UserStageInfo;
+use databend_query::configs::S3StorageConfig;
+use databend_query::sessions::StorageLocation;
+
+fn s3_stage(name: &str, key_id: &str, secret: &str) -> UserStageInfo {
+    UserStageInfo {
+        stage_name: name.to_string(),
+        stage_type: StageType::External,
+        stage_params: StageParams {
+            storage: StageStorage::S3(StageS3Storage {
+                bucket: "stage_bucket".to_string(),
+                credentials_aws_key_id: key_id.to_string(),
+                credentials_aws_secret_key: secret.to_string(),
+                ..Default::default()
+            }),
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_storage_location_s3_options() {
+    let default = S3StorageConfig {
+        region: "us-east-1".to_string(),
+        access_key_id: "default_key".to_string(),
+        secret_access_key: "default_secret".to_string(),
+        ..Default::default()
+    };
+
+    let options = |pairs: &[(&str, &str)]| {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<BTreeMap<_, _>>()
+    };
+    let t1 = options(&[
+        ("storage_location", "s3://bucket1/t1"),
+        ("storage_s3_endpoint_url", "http://127.0.0.1:9000"),
+        ("storage_s3_region", "eu-west-1"),
+        ("storage_s3_credential_stage", "stage1"),
+    ]);
+    let t2 = options(&[
+        ("storage_location", "s3://bucket2/t2"),
+        ("storage_s3_region", "ap-east-1"),
+        ("storage_s3_credential_stage", "stage2"),
+    ]);
+
+    let loc1 = StorageLocation::from_table_options("tenant1", &t1).unwrap();
+    let loc2 = StorageLocation::from_table_options("tenant1", &t2).unwrap();
+    assert_ne!(loc1, loc2);
+    assert_eq!("stage1", loc1.s3_credential_stage().unwrap());
+
+    // each table has its own endpoint and region, and the credential of its stage
+    let conf1 = loc1
+        .s3_config(&default, &s3_stage("stage1", "key1", "secret1"))
+        .unwrap();
+    assert_eq!("http://127.0.0.1:9000", conf1.endpoint_url);
+    assert_eq!("eu-west-1", conf1.region);
+    assert_eq!("key1", conf1.access_key_id);
+    assert_eq!("secret1", conf1.secret_access_key);
+
+    // the unspecified endpoint and region fall back to the configured s3 storage
+    let conf2 = loc2
+        .s3_config(&default, &s3_stage("stage2", "key2", "secret2"))
+        .unwrap();
+    assert_eq!(default.endpoint_url, conf2.endpoint_url);
+    assert_eq!("ap-east-1", conf2.region);
+    assert_eq!("key2", conf2.access_key_id);
+    assert_eq!("secret2", conf2.secret_access_key);
+
+    // the credential of the configured s3 storage is never used for a location
+    let mut t = t2.clone();
+    t.remove("storage_s3_credential_stage");
+    let loc = StorageLocation::from_table_options("tenant1", &t).unwrap();
+    let res = loc.s3_credential_stage();
+    assert_eq!(ErrorCode::BadOption("").code(), res.unwrap_err().code());
+
+    // the stage must be an external s3 one with a credential
+    let mut internal = s3_stage("stage2", "key2", "secret2");
+    internal.stage_type = StageType::Internal;
+    for stage in [s3_stage("stage2", "", "secret2"), internal] {
+        let res = loc2.s3_config(&default, &stage);
+        assert_eq!(ErrorCode::BadOption("").code(), res.unwrap_err().code());
+    }
+
+    // the same location of another tenant or stage is another storage
+    let loc3 = StorageLocation::from_table_options("tenant2", &t1).unwrap();
+    assert_eq!(loc1.location, loc3.location);
+    assert_ne!(loc1, loc3);
+    let mut t4 = t1.clone();
+    t4.insert(
+        "storage_s3_credential_stage".to_string(),
+        "stage4".to_string(),
+    );
+    let loc4 = StorageLocation::from_table_options("tenant1", &t4).unwrap();
+    assert_ne!(loc1, loc4);
+
+    // tables without a storage location of their own
+    assert!(StorageLocation::from_table_options("tenant1", &options(&[])).is_none());
+}
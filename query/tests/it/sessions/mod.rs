@@ -12,7 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod location_accessor;
 mod query_ctx;
 mod session;
 mod session_context;
+mod session_quota;
 mod session_setting;
@@ -15,9 +15,12 @@
 use common_base::tokio;
 use common_exception::Result;
 use common_mem_allocator::malloc_size;
+use common_meta_types::AuthInfo;
+use common_meta_types::UserInfo;
 use databend_query::sessions::Session;
 use databend_query::sessions::SessionManager;
 use databend_query::sessions::SessionType;
+use databend_query::users::UserApiProvider;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn test_session() -> Result<()> {
@@ -50,3 +53,80 @@ async fn test_session() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_session_user_settings() -> Result<()> {
+    let conf = crate::tests::ConfigBuilder::create().config();
+    let tenant = conf.query.tenant_id.clone();
+    let session_manager = SessionManager::from_conf(conf).await?;
+    let user_mgr = session_manager.get_user_manager();
+
+    let user = UserInfo::new("u1".to_string(), "%".to_string(), AuthInfo::None);
+    let identity = user.identity();
+    user_mgr.add_user(&tenant, user, None, false).await?;
+    user_mgr
+        .set_user_setting(&tenant, identity.clone(), "database", "system")
+        .await?;
+    user_mgr
+        .set_user_setting(&tenant, identity.clone(), "max_threads", "5")
+        .await?;
+    // Unknown to this version, ignored.
+    user_mgr
+        .set_user_setting(&tenant, identity.clone(), "no_such_setting", "1")
+        .await?;
+
+    let session = session_manager.create_session(SessionType::Test).await?;
+    session.set_current_user(user_mgr.get_user(&tenant, identity.clone()).await?);
+    assert_eq!(session.get_current_database(), "system");
+    assert_eq!(session.get_settings().get_max_threads()?, 5);
+
+    // The settings set in the session are kept on binding the same user again.
+    session.get_settings().set_max_threads(2)?;
+    session.set_current_database("default".to_string());
+    session.set_current_user(user_mgr.get_user(&tenant, identity.clone()).await?);
+    assert_eq!(session.get_current_database(), "default");
+    assert_eq!(session.get_settings().get_max_threads()?, 2);
+
+    user_mgr
+        .unset_user_setting(&tenant, identity.clone(), "database")
+        .await?;
+    let session = session_manager.create_session(SessionType::Test).await?;
+    session.set_current_user(user_mgr.get_user(&tenant, identity).await?);
+    assert_eq!(session.get_current_database(), "default");
+    assert_eq!(session.get_settings().get_max_threads()?, 5);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_sessions_share_user_cache() -> Result<()> {
+    let conf = crate::tests::ConfigBuilder::create().config();
+    let tenant = conf.query.tenant_id.clone();
+    let session_manager = SessionManager::from_conf(conf.clone()).await?;
+
+    // Changed behind the back of the session manager.
+    let other = UserApiProvider::create_global(conf).await?;
+    let user = UserInfo::new("u_shared".to_string(), "%".to_string(), AuthInfo::None);
+    let identity = user.identity();
+    other.add_user(&tenant, user, None, false).await?;
+
+    let session = session_manager.create_session(SessionType::Test).await?;
+    let ctx = session.create_query_context().await?;
+    ctx.get_user_manager()
+        .get_user(&tenant, identity.clone())
+        .await?;
+
+    other.drop_user(&tenant, identity.clone(), false).await?;
+
+    // Another session hits the user cached by the first one, until it expires.
+    let session = session_manager.create_session(SessionType::Test).await?;
+    let ctx = session.create_query_context().await?;
+    let got = ctx
+        .get_user_manager()
+        .get_user(&tenant, identity.clone())
+        .await?;
+    assert_eq!(got.name, "u_shared");
+
+    let res = other.get_user(&tenant, identity).await;
+    assert!(res.is_err());
+    Ok(())
+}
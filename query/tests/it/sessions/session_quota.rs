@@ -0,0 +1,165 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use common_base::tokio;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_management::SessionCountApi;
+use common_meta_types::AuthInfo;
+use common_meta_types::UserIdentity;
+use common_meta_types::UserInfo;
+use common_meta_types::UserQuota;
+use databend_query::sessions::check_memory_quota;
+use databend_query::sessions::check_sessions_quota;
+use databend_query::sessions::cpu_quota_threads;
+use databend_query::sessions::SessionManager;
+use databend_query::sessions::SessionType;
+use databend_query::sessions::UserSessions;
+
+// The sessions of the users, as (user, session id).
+struct FakeSessions(Vec<(UserIdentity, String)>);
+
+impl UserSessions for FakeSessions {
+    fn count_user_sessions(&self, user: &UserIdentity, except_id: &str) -> usize {
+        self.0
+            .iter()
+            .filter(|(u, id)| u == user && id != except_id)
+            .count()
+    }
+}
+
+fn user_with_quota(name: &str, quota: UserQuota) -> UserInfo {
+    let mut user = UserInfo::new(name.to_string(), "%".to_string(), AuthInfo::None);
+    user.quota = quota;
+    user
+}
+
+#[test]
+fn test_sessions_quota() -> Result<()> {
+    let quota = UserQuota {
+        max_sessions: 2,
+        ..UserQuota::no_limit()
+    };
+    let user1 = user_with_quota("u1", quota);
+    let user2 = user_with_quota("u2", UserQuota::no_limit());
+    let mut sessions = FakeSessions(vec![
+        (user1.identity(), "s1".to_string()),
+        (user2.identity(), "s2".to_string()),
+        (user2.identity(), "s3".to_string()),
+        (user2.identity(), "s4".to_string()),
+    ]);
+
+    // within the quota
+    check_sessions_quota(&sessions, "s5", &user1)?;
+
+    // the sessions exceed the quota, except the session itself
+    sessions.0.push((user1.identity(), "s5".to_string()));
+    check_sessions_quota(&sessions, "s5", &user1)?;
+    let res = check_sessions_quota(&sessions, "s6", &user1);
+    assert_eq!(res.unwrap_err().code(), ErrorCode::QuotaExceeded("").code());
+
+    // no limit
+    check_sessions_quota(&sessions, "s6", &user2)?;
+    Ok(())
+}
+
+#[test]
+fn test_memory_quota() -> Result<()> {
+    let quota = UserQuota {
+        max_memory_in_bytes: 1024,
+        ..UserQuota::no_limit()
+    };
+    let user = user_with_quota("u1", quota);
+    check_memory_quota(&user, 1024)?;
+    let res = check_memory_quota(&user, 1025);
+    assert_eq!(res.unwrap_err().code(), ErrorCode::QuotaExceeded("").code());
+
+    check_memory_quota(&user_with_quota("u2", UserQuota::no_limit()), usize::MAX)?;
+    Ok(())
+}
+
+#[test]
+fn test_cpu_quota() -> Result<()> {
+    let quota = UserQuota {
+        max_cpu: 4,
+        ..UserQuota::no_limit()
+    };
+    let user = user_with_quota("u1", quota);
+    assert_eq!(cpu_quota_threads(&user, 16), 4);
+    assert_eq!(cpu_quota_threads(&user, 2), 2);
+
+    let user = user_with_quota("u2", UserQuota::no_limit());
+    assert_eq!(cpu_quota_threads(&user, 16), 16);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_sessions_quota_of_session_manager() -> Result<()> {
+    let conf = crate::tests::ConfigBuilder::create().config();
+    let tenant = conf.query.tenant_id.clone();
+    let session_manager = SessionManager::from_conf(conf).await?;
+
+    let quota = UserQuota {
+        max_sessions: 1,
+        ..UserQuota::no_limit()
+    };
+    let user = user_with_quota("u1", quota);
+
+    let session1 = session_manager.create_session(SessionType::Test).await?;
+    session1.check_user_quota(&user).await?;
+    session1.set_current_user(user.clone());
+
+    // the queries of the session itself are in the quota
+    session1.create_query_context().await?;
+
+    let session2 = session_manager.create_session(SessionType::Test).await?;
+    let res = session2.check_user_quota(&user).await;
+    assert_eq!(res.unwrap_err().code(), ErrorCode::QuotaExceeded("").code());
+
+    let identity = user.identity();
+    assert_eq!(
+        session_manager.count_user_sessions(&identity, &session2.get_id()),
+        1
+    );
+    assert_eq!(
+        session_manager.count_user_sessions(&identity, &session1.get_id()),
+        0
+    );
+
+    // the sessions of the other nodes count as well
+    let session_counts = session_manager
+        .get_user_manager()
+        .get_session_count_api_client(&tenant, "other-node")?;
+    assert_eq!(session_counts.get_user_sessions("u1").await?, 1);
+    drop(session1);
+    for _ in 0..100 {
+        if session_counts.get_user_sessions("u1").await? == 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(session_counts.get_user_sessions("u1").await?, 0);
+
+    session_counts.increment_user_sessions("u1").await?;
+    let res = session2.check_user_quota(&user).await;
+    assert_eq!(res.unwrap_err().code(), ErrorCode::QuotaExceeded("").code());
+    assert_eq!(session_counts.get_user_sessions("u1").await?, 1);
+
+    session_counts.decrement_user_sessions("u1").await?;
+    session2.check_user_quota(&user).await?;
+    assert_eq!(session_counts.get_user_sessions("u1").await?, 1);
+    Ok(())
+}
@@ -67,11 +67,29 @@ fn optimize_table() -> Result<()> {
         expect_parse_ok(sql, expected)?;
     }
 
+    {
+        let sql = "optimize TABLE t1 vacuum";
+        let expected = DfStatement::OptimizeTable(DfOptimizeTable {
+            name: ObjectName(vec![Ident::new("t1")]),
+            operation: Optimization::VACUUM,
+        });
+        expect_parse_ok(sql, expected)?;
+    }
+
+    {
+        let sql = "optimize TABLE t1 vacuum dry run";
+        let expected = DfStatement::OptimizeTable(DfOptimizeTable {
+            name: ObjectName(vec![Ident::new("t1")]),
+            operation: Optimization::VACUUM | Optimization::DRY_RUN,
+        });
+        expect_parse_ok(sql, expected)?;
+    }
+
     {
         let sql = "optimize TABLE t1 unacceptable";
         expect_parse_err(
             sql,
-            "sql parser error: Expected one of PURGE, COMPACT, ALL, found: unacceptable"
+            "sql parser error: Expected one of PURGE, COMPACT, ALL, VACUUM, found: unacceptable"
                 .to_string(),
         )?;
     }
@@ -80,7 +98,7 @@ fn optimize_table() -> Result<()> {
         let sql = "optimize TABLE t1 (";
         expect_parse_err(
             sql,
-            "sql parser error: Expected Nothing, or one of PURGE, COMPACT, ALL, found: ("
+            "sql parser error: Expected Nothing, or one of PURGE, COMPACT, ALL, VACUUM, found: ("
                 .to_string(),
         )?;
     }
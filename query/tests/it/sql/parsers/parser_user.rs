@@ -48,6 +48,7 @@ fn create_user_auth_test(
                 by_value: auth_string,
             },
             with_options: Default::default(),
+            password_expire: false,
         }),
     )
 }
@@ -119,6 +120,7 @@ fn create_user_test() -> Result<()> {
             user: UserIdentity::new("test@localhost", "%"),
             auth_option: DfAuthOption::default(),
             with_options: Default::default(),
+            password_expire: false,
         }),
     )?;
 
@@ -131,6 +133,7 @@ fn create_user_test() -> Result<()> {
             user: UserIdentity::new("operator", "%"),
             auth_option: DfAuthOption::no_password(),
             with_options,
+            password_expire: false,
         }),
     )?;
 
@@ -145,9 +148,30 @@ fn create_user_test() -> Result<()> {
             user: UserIdentity::new("operator", "%"),
             auth_option: DfAuthOption::no_password(),
             with_options,
+            password_expire: false,
         }),
     )?;
 
+    // create user with the password to be changed on the first login
+    expect_parse_ok(
+        "CREATE USER 'test' IDENTIFIED BY 'password' PASSWORD EXPIRE",
+        DfStatement::CreateUser(DfCreateUser {
+            if_not_exists: false,
+            user: UserIdentity::new("test", "%"),
+            auth_option: DfAuthOption {
+                auth_type: None,
+                by_value: Some("password".to_string()),
+            },
+            with_options: Default::default(),
+            password_expire: true,
+        }),
+    )?;
+
+    expect_parse_err(
+        "CREATE USER 'test' IDENTIFIED BY 'password' PASSWORD",
+        String::from("sql parser error: Expected EXPIRE, found: EOF"),
+    )?;
+
     // create user with option
     expect_parse_err(
         "CREATE USER 'operator' NOT IDENTIFIED WITH TENANTSETTINGS",
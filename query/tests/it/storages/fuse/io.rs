@@ -13,16 +13,30 @@
 //  limitations under the License.
 //
 
+use std::future::Future;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
+use chrono::TimeZone;
+use chrono::Utc;
 use common_base::tokio;
+use common_base::tokio::task::JoinHandle;
+use common_base::AbortHandle;
+use common_base::MemoryTracker;
+use common_base::Runtime;
+use common_base::TrySpawn;
 use common_datablocks::DataBlock;
 use common_datavalues::prelude::*;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use databend_query::storages::fuse::io::BlockCompactor;
 use databend_query::storages::fuse::io::BlockStreamWriter;
+use databend_query::storages::fuse::io::BlockWriteOptions;
+use databend_query::storages::fuse::io::BoundedBlockStream;
+use databend_query::storages::fuse::io::LocationLayout;
 use databend_query::storages::fuse::io::TableMetaLocationGenerator;
+use databend_query::storages::fuse::io::WrittenLocations;
 use databend_query::storages::fuse::meta::TableSnapshot;
 use databend_query::storages::fuse::meta::Versioned;
 use databend_query::storages::fuse::DEFAULT_BLOCK_PER_SEGMENT;
@@ -48,6 +62,7 @@ async fn test_fuse_table_block_appender() {
             .unwrap(),
     );
     let schema = DataSchemaRefExt::create(vec![DataField::new("a", i32::to_data_type())]);
+    let runtime = Arc::new(Runtime::with_worker_threads(2, None).unwrap());
 
     // single segment
     let block = DataBlock::create(schema.clone(), vec![Series::from_data(vec![1, 2, 3])]);
@@ -55,12 +70,16 @@ async fn test_fuse_table_block_appender() {
 
     let locs = TableMetaLocationGenerator::with_prefix(".".to_owned());
     let segments = BlockStreamWriter::write_block_stream(
+        runtime.clone(),
+        AbortHandle::default().registration(),
         local_fs.clone(),
         Box::pin(block_stream),
         schema.clone(),
         DEFAULT_BLOCK_PER_SEGMENT,
         0,
         locs.clone(),
+        BlockWriteOptions::default(),
+        WrittenLocations::default(),
     )
     .await
     .collect::<Vec<_>>()
@@ -82,12 +101,16 @@ async fn test_fuse_table_block_appender() {
     let block_stream = futures::stream::iter(blocks);
 
     let segments = BlockStreamWriter::write_block_stream(
+        runtime.clone(),
+        AbortHandle::default().registration(),
         local_fs.clone(),
         Box::pin(block_stream),
         schema.clone(),
         max_rows_per_block,
         max_blocks_per_segment,
         locs.clone(),
+        BlockWriteOptions::default(),
+        WrittenLocations::default(),
     )
     .await
     .collect::<Vec<_>>()
@@ -102,12 +125,16 @@ async fn test_fuse_table_block_appender() {
     // empty blocks
     let block_stream = futures::stream::iter(vec![]);
     let segments = BlockStreamWriter::write_block_stream(
+        runtime.clone(),
+        AbortHandle::default().registration(),
         local_fs,
         Box::pin(block_stream),
         schema,
         DEFAULT_BLOCK_PER_SEGMENT,
         0,
         locs,
+        BlockWriteOptions::default(),
+        WrittenLocations::default(),
     )
     .await
     .collect::<Vec<_>>()
@@ -271,13 +298,18 @@ async fn test_block_stream_writer() -> common_exception::Result<()> {
         let data_accessor = Arc::new(MockDataAccessor::new());
         let operator = Operator::new(data_accessor.clone());
         let locs = TableMetaLocationGenerator::with_prefix(".".to_owned());
+        let runtime = Arc::new(Runtime::with_worker_threads(2, None)?);
         let stream = BlockStreamWriter::write_block_stream(
+            runtime,
+            AbortHandle::default().registration(),
             operator,
             Box::pin(block_stream),
             schema,
             max_rows_per_block,
             max_blocks_per_segment,
             locs,
+            BlockWriteOptions::default(),
+            WrittenLocations::default(),
         )
         .await;
         let segs = stream.try_collect::<Vec<_>>().await?;
@@ -343,6 +375,34 @@ fn test_meta_locations() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_meta_locations_dated_layout() -> Result<()> {
+    let test_prefix = "test_pref";
+    let locs = TableMetaLocationGenerator::with_prefix(test_prefix.to_owned())
+        .with_layout(LocationLayout::Dated)
+        .with_clock(|| Utc.ymd(2022, 3, 1).and_hms(23, 59, 59));
+
+    let block_loc = locs.gen_block_location();
+    assert!(block_loc.starts_with(&format!("{}/_b/2022/03/01/", test_prefix)));
+    let seg_loc = locs.gen_segment_info_location();
+    assert!(seg_loc.starts_with(&format!("{}/_sg/2022/03/01/", test_prefix)));
+    let uuid = Uuid::new_v4();
+    let snapshot_loc = locs.gen_snapshot_location(&uuid)?;
+    assert_eq!(
+        snapshot_loc,
+        format!(
+            "{}/_ss/2022/03/01/{}_v1.json",
+            test_prefix,
+            uuid.to_simple()
+        )
+    );
+
+    // the locations derived from the snapshot ids are kept flat
+    let snapshot_loc = locs.snapshot_location_from_uuid(&uuid, TableSnapshot::VERSION)?;
+    assert!(snapshot_loc.starts_with(&format!("{}/_ss/{}", test_prefix, uuid.to_simple())));
+    Ok(())
+}
+
 use common_infallible::Mutex;
 
 #[derive(Debug)]
@@ -369,3 +429,196 @@ impl Accessor for MockDataAccessor {
         Ok(Box::new(vec![]))
     }
 }
+
+#[derive(Debug)]
+struct SlowDataAccessor {
+    delay: std::time::Duration,
+}
+
+#[async_trait::async_trait]
+impl Accessor for SlowDataAccessor {
+    async fn write(&self, _args: &OpWrite) -> std::io::Result<BytesWriter> {
+        tokio::time::sleep(self.delay).await;
+        Ok(Box::new(vec![]))
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_bounded_block_stream_back_pressure() -> Result<()> {
+    let ctx = crate::tests::create_query_context().await?;
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", i32::to_data_type())]);
+    let block = DataBlock::create(schema.clone(), vec![Series::from_data(vec![1i32; 1000])]);
+    let block_size = block.memory_size();
+    let num_blocks = 20;
+    let block_stream = futures::stream::iter(std::iter::repeat(Ok(block)).take(num_blocks));
+
+    // the producer is way faster than the storage
+    let max_buffered_bytes = block_size * 2 + block_size / 2;
+    let (stream, status) =
+        BoundedBlockStream::try_create(&ctx, Box::pin(block_stream), max_buffered_bytes)?;
+
+    let operator = Operator::new(Arc::new(SlowDataAccessor {
+        delay: std::time::Duration::from_millis(10),
+    }));
+    let locs = TableMetaLocationGenerator::with_prefix(".".to_owned());
+    let segments = BlockStreamWriter::write_block_stream(
+        ctx.get_storage_runtime(),
+        ctx.get_abort_registration(),
+        operator,
+        stream,
+        schema,
+        1000,
+        5,
+        locs,
+        BlockWriteOptions::default(),
+        WrittenLocations::default(),
+    )
+    .await;
+    let segments = segments.try_collect::<Vec<_>>().await?;
+
+    let blocks: usize = segments.iter().map(|s| s.blocks.len()).sum();
+    assert_eq!(num_blocks, blocks);
+    assert!(status.peak_buffered_bytes() > 0);
+    assert!(status.peak_buffered_bytes() <= max_buffered_bytes);
+    assert_eq!(0, status.buffered_bytes());
+    Ok(())
+}
+
+/// Spawns the tasks tracked by `tracker`.
+struct TrackedSpawner {
+    tracker: Arc<MemoryTracker>,
+}
+
+impl TrySpawn for TrackedSpawner {
+    fn try_spawn_untraced<T>(&self, task: T) -> Result<JoinHandle<T::Output>>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        Ok(tokio::spawn(MemoryTracker::track_task(
+            self.tracker.clone(),
+            task,
+        )))
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_bounded_block_stream_memory_tracking() -> Result<()> {
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", i32::to_data_type())]);
+    let block = DataBlock::create(schema, vec![Series::from_data(vec![1i32; 1000])]);
+    let block_size = block.memory_size();
+    let num_blocks = 3;
+
+    // the buffered blocks are charged until they are pulled
+    {
+        let spawner = TrackedSpawner {
+            tracker: MemoryTracker::create(None),
+        };
+        let block_stream =
+            futures::stream::iter(std::iter::repeat(Ok(block.clone())).take(num_blocks));
+        let (mut stream, status) =
+            BoundedBlockStream::try_create(&spawner, Box::pin(block_stream), block_size * 10)?;
+        while status.buffered_bytes() < block_size * num_blocks {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(
+            spawner.tracker.get_memory_usage(),
+            (block_size * num_blocks) as i64
+        );
+
+        stream.next().await.unwrap()?;
+        assert_eq!(
+            spawner.tracker.get_memory_usage(),
+            (block_size * (num_blocks - 1)) as i64
+        );
+        while let Some(item) = stream.next().await {
+            item?;
+        }
+        assert_eq!(spawner.tracker.get_memory_usage(), 0);
+    }
+
+    // the producing fails once the limit of the tracker is exceeded
+    {
+        let spawner = TrackedSpawner {
+            tracker: MemoryTracker::create_with_limit(None, (block_size - 1) as i64),
+        };
+        let block_stream =
+            futures::stream::iter(std::iter::repeat(Ok(block.clone())).take(num_blocks));
+        let (stream, _) =
+            BoundedBlockStream::try_create(&spawner, Box::pin(block_stream), block_size * 10)?;
+        let items = stream.collect::<Vec<_>>().await;
+        assert_eq!(items.len(), 1);
+        assert_eq!(
+            items[0].as_ref().unwrap_err().code(),
+            ErrorCode::MemoryExceeded("").code()
+        );
+        assert_eq!(spawner.tracker.get_memory_usage(), 0);
+    }
+
+    Ok(())
+}
+
+#[derive(Debug)]
+struct ThrottledDataAccessor {
+    delay: std::time::Duration,
+    writes: Arc<AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl Accessor for ThrottledDataAccessor {
+    async fn write(&self, _args: &OpWrite) -> std::io::Result<BytesWriter> {
+        self.writes.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(self.delay).await;
+        Ok(Box::new(vec![]))
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_block_stream_writer_abort() -> Result<()> {
+    let ctx = crate::tests::create_query_context().await?;
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", i32::to_data_type())]);
+    let block = DataBlock::create(schema.clone(), vec![Series::from_data(vec![1i32; 1000])]);
+    let num_blocks = 100;
+    let block_stream = futures::stream::iter(std::iter::repeat(Ok(block)).take(num_blocks));
+
+    let writes = Arc::new(AtomicUsize::new(0));
+    let operator = Operator::new(Arc::new(ThrottledDataAccessor {
+        delay: std::time::Duration::from_millis(200),
+        writes: writes.clone(),
+    }));
+    let locs = TableMetaLocationGenerator::with_prefix(".".to_owned());
+    let written = WrittenLocations::default();
+    let segments = BlockStreamWriter::write_block_stream(
+        ctx.get_storage_runtime(),
+        ctx.get_abort_registration(),
+        operator,
+        Box::pin(block_stream),
+        schema,
+        1000,
+        5,
+        locs,
+        BlockWriteOptions::default(),
+        written.clone(),
+    )
+    .await;
+    let appending = tokio::spawn(segments.try_collect::<Vec<_>>());
+
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    ctx.kill();
+
+    // the write in progress is dropped, without waiting for the storage
+    let res = tokio::time::timeout(std::time::Duration::from_millis(100), appending)
+        .await
+        .expect("the append is not aborted in time")
+        .unwrap();
+    assert_eq!(res.unwrap_err().code(), ErrorCode::AbortedQuery("").code());
+
+    // no more writes are issued after the abort
+    let writes_at_abort = writes.load(Ordering::SeqCst);
+    assert!(writes_at_abort < num_blocks);
+    // including the one dropped, the blocks written are reported
+    assert_eq!(written.locations().len(), writes_at_abort);
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    assert_eq!(writes.load(Ordering::SeqCst), writes_at_abort);
+    Ok(())
+}
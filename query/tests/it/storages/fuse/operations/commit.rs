@@ -14,10 +14,16 @@
 //
 use common_base::tokio;
 use common_datablocks::DataBlock;
+use common_exception::ErrorCode;
 use common_exception::Result;
+use databend_query::storages::fuse::io::MetaReaders;
+use databend_query::storages::fuse::FuseTable;
 use futures::TryStreamExt;
 
+use crate::storages::fuse::table_test_fixture::append_sample_data;
+use crate::storages::fuse::table_test_fixture::check_data_dir;
 use crate::storages::fuse::table_test_fixture::execute_query;
+use crate::storages::fuse::table_test_fixture::expects_ok;
 use crate::storages::fuse::table_test_fixture::TestFixture;
 
 #[tokio::test]
@@ -77,3 +83,217 @@ async fn test_fuse_occ_retry() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_fuse_commit_crash_after_segment_written() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let db = fixture.default_db_name();
+    let tbl = fixture.default_table_name();
+    let ctx = fixture.ctx();
+    fixture.create_default_table().await?;
+
+    // one committed insertion: 1 snapshot, 1 segment, 1 block
+    append_sample_data(1, &fixture).await?;
+    let table = fixture.latest_default_table().await?;
+    let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+    let committed = fuse_table.snapshot_loc();
+    assert!(committed.is_some());
+    assert_eq!(
+        committed,
+        fuse_table.read_last_snapshot_hint(ctx.as_ref()).await?
+    );
+
+    // blocks and segment are written, but the "crash" happens before the snapshot is committed
+    {
+        let stream = TestFixture::gen_sample_blocks_stream(1, 1);
+        let r = table.append_data(ctx.clone(), stream).await?;
+        r.try_collect::<Vec<DataBlock>>().await?;
+    }
+    check_data_dir(&fixture, "crash_after_segment_written", 1, 2, 2).await;
+
+    // neither the table meta nor the hint observe the partial write
+    let table = fixture.latest_default_table().await?;
+    let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+    assert_eq!(committed, fuse_table.snapshot_loc());
+    assert_eq!(
+        committed,
+        fuse_table.read_last_snapshot_hint(ctx.as_ref()).await?
+    );
+
+    // and the previous snapshot is still read cleanly
+    let qry = format!("select count(*) as count from '{}'.'{}'", db, tbl);
+    let expected = vec![
+        "+-------+",
+        "| count |",
+        "+-------+",
+        "| 3     |",
+        "+-------+",
+    ];
+    expects_ok(
+        "read_previous_snapshot_after_crash",
+        execute_query(ctx, qry.as_str()).await,
+        expected,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_fuse_snapshot_chain() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let ctx = fixture.ctx();
+    fixture.create_default_table().await?;
+
+    // three insertions, three snapshots
+    for _ in 0..3 {
+        append_sample_data(1, &fixture).await?;
+    }
+
+    let table = fixture.latest_default_table().await?;
+    let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+    let reader = MetaReaders::table_snapshot_reader(ctx.as_ref());
+    let chain = reader
+        .read_chain(
+            fuse_table.snapshot_loc(),
+            fuse_table.snapshot_format_version(),
+            fuse_table.meta_location_generator().clone(),
+            usize::MAX,
+        )
+        .await?;
+    assert_eq!(3, chain.len());
+
+    // walks backward, from the latest snapshot to the first one
+    assert!(chain[2].1.prev_snapshot_id.is_none());
+    assert_eq!(Some(&chain[0].0), fuse_table.snapshot_loc().as_ref());
+    for pair in chain.windows(2) {
+        let ((_, newer), (older_loc, older)) = (&pair[0], &pair[1]);
+        assert_eq!(
+            Some(older.snapshot_id),
+            newer.prev_snapshot_id.map(|(id, _)| id)
+        );
+        assert_eq!(Some(older_loc), newer.prev_snapshot_location.as_ref());
+        assert!(older.summary.row_count < newer.summary.row_count);
+        assert!(newer.timestamp.is_some());
+        assert!(older.timestamp < newer.timestamp);
+    }
+
+    // the walk is bounded by the limit
+    let chain = reader
+        .read_chain(
+            fuse_table.snapshot_loc(),
+            fuse_table.snapshot_format_version(),
+            fuse_table.meta_location_generator().clone(),
+            2,
+        )
+        .await?;
+    assert_eq!(2, chain.len());
+    assert_eq!(
+        Some(chain[1].1.snapshot_id),
+        chain[0].1.prev_snapshot_id.map(|(id, _)| id)
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fuse_occ_concurrent_appends() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let db = fixture.default_db_name();
+    let tbl = fixture.default_table_name();
+    let ctx = fixture.ctx();
+    fixture.create_default_table().await?;
+
+    // both of the appends are based on the same (empty) version of table
+    let table = fixture.latest_default_table().await?;
+    let append = |num_blocks, rows_per_block, value_start_from| {
+        let table = table.clone();
+        let ctx = ctx.clone();
+        async move {
+            let stream = TestFixture::gen_sample_blocks_stream_ex(
+                num_blocks,
+                rows_per_block,
+                value_start_from,
+            );
+            let r = table.append_data(ctx.clone(), stream).await?;
+            table
+                .commit_insertion(ctx, r.try_collect().await?, false)
+                .await
+        }
+    };
+    let (r1, r2) = futures::future::join(append(2, 3, 1), append(3, 2, 100)).await;
+    r1?;
+    r2?;
+
+    // the batches are both visible
+    let qry = format!("select count(*) as count from '{}'.'{}'", db, tbl);
+    let expected = vec![
+        "+-------+",
+        "| count |",
+        "+-------+",
+        "| 12    |",
+        "+-------+",
+    ];
+    expects_ok(
+        "both_appends_are_visible",
+        execute_query(ctx.clone(), qry.as_str()).await,
+        expected,
+    )
+    .await?;
+
+    // and the statistics are the sum of them
+    let table = fixture.latest_default_table().await?;
+    let stats = table.statistics(ctx.clone()).await?.unwrap();
+    assert_eq!(Some(12), stats.num_rows);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fuse_occ_overwrite_conflict() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let db = fixture.default_db_name();
+    let tbl = fixture.default_table_name();
+    let ctx = fixture.ctx();
+    fixture.create_default_table().await?;
+
+    let table = fixture.latest_default_table().await?;
+
+    // an overwrite, not committed yet
+    let pending = {
+        let stream = TestFixture::gen_sample_blocks_stream_ex(1, 1, 1);
+        table.append_data(ctx.clone(), stream).await?
+    };
+
+    // meanwhile, another insertion is committed
+    {
+        let stream = TestFixture::gen_sample_blocks_stream_ex(1, 1, 5);
+        let r = table.append_data(ctx.clone(), stream).await?;
+        table
+            .commit_insertion(ctx.clone(), r.try_collect().await?, false)
+            .await?;
+    }
+
+    // the overwrite can not be rebased, it fails without retrying
+    let r = table
+        .commit_insertion(ctx.clone(), pending.try_collect().await?, true)
+        .await;
+    assert_eq!(
+        ErrorCode::table_version_mismatched_code(),
+        r.unwrap_err().code()
+    );
+
+    // the concurrent insertion is kept
+    let qry = format!("select * from '{}'.'{}' order by id", db, tbl);
+    let expected = vec![
+        "+----+", //
+        "| id |", //
+        "+----+", //
+        "| 5  |", //
+        "+----+", //
+    ];
+    expects_ok(
+        "concurrent_insertion_is_kept",
+        execute_query(ctx, qry.as_str()).await,
+        expected,
+    )
+    .await
+}
@@ -0,0 +1,255 @@
+//  Copyright 2021 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+
+use common_arrow::arrow::chunk::Chunk;
+use common_arrow::arrow::io::parquet::write::*;
+use common_arrow::parquet::encoding::Encoding;
+use common_base::tokio;
+use common_datablocks::DataBlock;
+use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_planners::col;
+use common_planners::lit;
+use common_planners::Extras;
+use databend_query::storages::fuse::FuseTable;
+use databend_query::storages::Table;
+use futures::TryStreamExt;
+use opendal::Operator;
+
+use crate::storages::fuse::table_test_fixture::append_sample_data;
+use crate::storages::fuse::table_test_fixture::execute_command;
+use crate::storages::fuse::table_test_fixture::execute_query;
+use crate::storages::fuse::table_test_fixture::expects_err;
+use crate::storages::fuse::table_test_fixture::expects_ok;
+use crate::storages::fuse::table_test_fixture::TestFixture;
+
+// writes the block as a parquet file, as an external writer would do
+async fn write_external_parquet(
+    operator: &Operator,
+    location: &str,
+    block: DataBlock,
+) -> Result<()> {
+    let arrow_schema = block.schema().to_arrow();
+    let options = WriteOptions {
+        write_statistics: true,
+        compression: Compression::Snappy,
+        version: Version::V1,
+    };
+    let encodings = vec![Encoding::Plain; arrow_schema.fields.len()];
+    let batch = Chunk::try_from(block)?;
+    let row_groups = RowGroupIterator::try_new(
+        vec![Ok(batch)].into_iter(),
+        &arrow_schema,
+        options,
+        encodings,
+    )?;
+    let mut buf = vec![];
+    common_arrow::write_parquet_file(&mut buf, row_groups, arrow_schema.clone(), options)
+        .map_err(|e| ErrorCode::ParquetError(e.to_string()))?;
+    operator.object(location).write(buf).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fuse_import_external_parquet() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let db = fixture.default_db_name();
+    let tbl = fixture.default_table_name();
+    let ctx = fixture.ctx();
+    fixture.create_default_table().await?;
+
+    let operator = ctx.get_storage_operator()?;
+    let schema = TestFixture::default_schema();
+    let locations = vec![
+        "external/a.parquet".to_owned(),
+        "external/b.parquet".to_owned(),
+    ];
+    let values = vec![vec![1i32, 2, 3], vec![7i32, 8, 9]];
+    for (location, values) in locations.iter().zip(values) {
+        let block = DataBlock::create(schema.clone(), vec![Series::from_data(values)]);
+        write_external_parquet(&operator, location, block).await?;
+    }
+
+    let table = fixture.latest_default_table().await?;
+    FuseTable::try_from_table(table.as_ref())?
+        .do_import(ctx.clone(), &locations)
+        .await?;
+
+    // all the rows are there
+    let qry = format!("select * from {}.{} where id > 1", db, tbl);
+    let expected = vec![
+        "+----+", //
+        "| id |", //
+        "+----+", //
+        "| 2  |", //
+        "| 3  |", //
+        "| 7  |", //
+        "| 8  |", //
+        "| 9  |", //
+        "+----+", //
+    ];
+    expects_ok(
+        "import_external_parquet",
+        execute_query(ctx.clone(), qry.as_str()).await,
+        expected,
+    )
+    .await?;
+
+    // the statistics derived from the footers are used in pruning
+    let table = fixture.latest_default_table().await?;
+    let mut extras = Extras::default();
+    extras.filters = vec![col("id").gt(lit(5i32))];
+    let (_, parts) = table.read_partitions(ctx.clone(), Some(extras)).await?;
+    assert_eq!(1, parts.len());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fuse_import_external_files_survive_purge() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let db = fixture.default_db_name();
+    let tbl = fixture.default_table_name();
+    let ctx = fixture.ctx();
+    fixture.create_default_table().await?;
+
+    let operator = ctx.get_storage_operator()?;
+    let schema = TestFixture::default_schema();
+    let location = "external/survivor.parquet".to_owned();
+    let block = DataBlock::create(schema, vec![Series::from_data(vec![1i32, 2, 3])]);
+    write_external_parquet(&operator, &location, block).await?;
+
+    let table = fixture.latest_default_table().await?;
+    FuseTable::try_from_table(table.as_ref())?
+        .do_import(ctx.clone(), &[location.clone()])
+        .await?;
+
+    // the imported blocks are only referenced by the history now, purge removes the history
+    let qry = format!("truncate table {}.{}", db, tbl);
+    execute_command(ctx.clone(), qry.as_str()).await?;
+    let qry = format!("optimize table {}.{} purge", db, tbl);
+    execute_command(ctx.clone(), qry.as_str()).await?;
+    assert!(operator.object(&location).is_exist().await?);
+
+    // files under the prefix of the table are rejected
+    let table = fixture.latest_default_table().await?;
+    let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+    let location = format!(
+        "{}/external.parquet",
+        fuse_table.meta_location_generator().prefix()
+    );
+    expects_err(
+        "import_under_table_prefix",
+        ErrorCode::bad_arguments_code(),
+        fuse_table.do_import(ctx.clone(), &[location]).await,
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fuse_import_schema_mismatch() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let ctx = fixture.ctx();
+    fixture.create_default_table().await?;
+    let operator = ctx.get_storage_operator()?;
+    let table = fixture.latest_default_table().await?;
+    let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+
+    // missing column
+    let location = "external/missing_column.parquet".to_owned();
+    let schema = DataSchemaRefExt::create(vec![DataField::new("c", i32::to_data_type())]);
+    let block = DataBlock::create(schema, vec![Series::from_data(vec![1i32, 2, 3])]);
+    write_external_parquet(&operator, &location, block).await?;
+    expects_err(
+        "import_missing_column",
+        ErrorCode::data_struct_miss_match_code(),
+        fuse_table.do_import(ctx.clone(), &[location]).await,
+    );
+
+    // incompatible type
+    let location = "external/incompatible_type.parquet".to_owned();
+    let schema = DataSchemaRefExt::create(vec![DataField::new("id", u64::to_data_type())]);
+    let block = DataBlock::create(schema, vec![Series::from_data(vec![1u64, 2, 3])]);
+    write_external_parquet(&operator, &location, block).await?;
+    expects_err(
+        "import_incompatible_type",
+        ErrorCode::data_struct_miss_match_code(),
+        fuse_table.do_import(ctx.clone(), &[location]).await,
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fuse_import_by_call() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let db = fixture.default_db_name();
+    let tbl = fixture.default_table_name();
+    let ctx = fixture.ctx();
+    fixture.create_default_table().await?;
+    // one block written by the table itself
+    append_sample_data(1, &fixture).await?;
+
+    let operator = ctx.get_storage_operator()?;
+    let schema = TestFixture::default_schema();
+    let location = "external/called.parquet";
+    let block = DataBlock::create(schema, vec![Series::from_data(vec![7i32, 8, 9])]);
+    write_external_parquet(&operator, location, block).await?;
+
+    let qry = format!(
+        "call system$fuse_import('{}', '{}', '{}')",
+        db, tbl, location
+    );
+    execute_command(ctx.clone(), qry.as_str()).await?;
+
+    // the external block is listed next to the one of the table
+    let qry = format!("call system$fuse_blocks('{}', '{}')", db, tbl);
+    let blocks = execute_query(ctx.clone(), qry.as_str())
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let mut listed = vec![];
+    for block in &blocks {
+        let locations = block.try_column_by_name("block_location")?;
+        let external = block.try_column_by_name("external")?;
+        for i in 0..block.num_rows() {
+            listed.push((locations.get(i), external.get(i)));
+        }
+    }
+    assert_eq!(2, listed.len());
+    assert!(listed.contains(&(
+        DataValue::String(location.as_bytes().to_vec()),
+        DataValue::Boolean(true)
+    )));
+    assert_eq!(
+        1,
+        listed
+            .iter()
+            .filter(|(_, external)| *external == DataValue::Boolean(false))
+            .count()
+    );
+
+    // at least one location is required
+    let qry = format!("call system$fuse_import('{}', '{}')", db, tbl);
+    expects_err(
+        "import_without_location",
+        ErrorCode::number_arguments_not_match_code(),
+        execute_command(ctx.clone(), qry.as_str()).await,
+    );
+
+    Ok(())
+}
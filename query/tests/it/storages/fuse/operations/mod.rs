@@ -14,7 +14,9 @@
 //
 
 mod commit;
+mod import;
 mod optimize;
 mod purge_drop;
 mod purge_truncate;
 mod read_plan;
+mod vacuum;
@@ -12,12 +12,16 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 //
+use std::time::Duration;
+
 use common_base::tokio;
 use common_exception::Result;
 
 use crate::storages::fuse::table_test_fixture::append_sample_data;
 use crate::storages::fuse::table_test_fixture::check_data_dir;
 use crate::storages::fuse::table_test_fixture::execute_command;
+use crate::storages::fuse::table_test_fixture::execute_query;
+use crate::storages::fuse::table_test_fixture::expects_ok;
 use crate::storages::fuse::table_test_fixture::history_should_have_only_one_item;
 use crate::storages::fuse::table_test_fixture::TestFixture;
 
@@ -40,14 +44,104 @@ async fn test_fuse_truncate_purge_stmt() -> Result<()> {
     let qry = format!("truncate table '{}'.'{}' purge", db, tbl);
     execute_command(ctx.clone(), qry.as_str()).await?;
 
+    // the table is empty
+    let qry = format!("select count(*) as count from '{}'.'{}'", db, tbl);
+    let expected = vec![
+        "+-------+",
+        "| count |",
+        "+-------+",
+        "| 0     |",
+        "+-------+",
+    ];
+    expects_ok(
+        "truncate_purge_table_is_empty",
+        execute_query(ctx.clone(), qry.as_str()).await,
+        expected,
+    )
+    .await?;
+
+    // the previous files are kept, running queries may still read them
+    check_data_dir(&fixture, "truncate_after_purge_check_file_items", 3, 2, 2).await;
+
+    // within the safety window, vacuum keeps them as well
+    let qry = format!("optimize table '{}'.'{}' vacuum", db, tbl);
+    execute_command(ctx.clone(), qry.as_str()).await?;
+    check_data_dir(&fixture, "truncate_purge_vacuum_within_window", 3, 2, 2).await;
+
+    // once the truncation is older than the safety window, the history is reclaimed
+    ctx.get_settings().set_settings(
+        "storage_vacuum_safety_window_secs".to_string(),
+        "0".to_string(),
+        false,
+    )?;
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    execute_command(ctx.clone(), qry.as_str()).await?;
+
     // one history item left there
     history_should_have_only_one_item(
         &fixture,
-        "after_truncate_there_should_be_one_history_item_left",
+        "after_vacuum_there_should_be_one_history_item_left",
     )
     .await?;
 
     // there should be only a snapshot file left there, no segments or blocks
-    check_data_dir(&fixture, "truncate_after_purge_check_file_items", 1, 0, 0).await;
+    check_data_dir(&fixture, "truncate_purge_vacuum_after_window", 1, 0, 0).await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fuse_truncate_keep_history_stmt() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let db = fixture.default_db_name();
+    let tbl = fixture.default_table_name();
+    let ctx = fixture.ctx();
+    fixture.create_default_table().await?;
+
+    // ingests some test data
+    append_sample_data(1, &fixture).await?;
+    append_sample_data(1, &fixture).await?;
+    check_data_dir(&fixture, "truncate_keep_history", 2, 2, 2).await;
+
+    // let's truncate, without purging
+    let qry = format!("truncate table '{}'.'{}'", db, tbl);
+    execute_command(ctx.clone(), qry.as_str()).await?;
+
+    // the table is empty
+    let qry = format!("select count(*) as count from '{}'.'{}'", db, tbl);
+    let expected = vec![
+        "+-------+",
+        "| count |",
+        "+-------+",
+        "| 0     |",
+        "+-------+",
+    ];
+    expects_ok(
+        "truncate_keep_history_table_is_empty",
+        execute_query(ctx.clone(), qry.as_str()).await,
+        expected,
+    )
+    .await?;
+
+    // but the history is kept: 2 previous snapshots + the new empty one
+    let qry = format!(
+        "select count(*) as count from fuse_history('{}', '{}')",
+        db, tbl
+    );
+    let expected = vec![
+        "+-------+",
+        "| count |",
+        "+-------+",
+        "| 3     |",
+        "+-------+",
+    ];
+    expects_ok(
+        "truncate_keep_history_history_items",
+        execute_query(ctx.clone(), qry.as_str()).await,
+        expected,
+    )
+    .await?;
+
+    // none of the data files are removed
+    check_data_dir(&fixture, "truncate_keep_history_check_file_items", 3, 2, 2).await;
     Ok(())
 }
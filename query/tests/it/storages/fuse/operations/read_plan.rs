@@ -20,7 +20,12 @@ use common_base::tokio;
 use common_datavalues::DataValue;
 use common_exception::Result;
 use common_planners::Extras;
+use common_planners::PlanBuilder;
+use common_planners::PlanNode;
+use common_planners::DEFAULT_FILTER_SELECTIVITY;
 use databend_query::interpreters::CreateTableInterpreter;
+use databend_query::sql::PlanParser;
+use databend_query::storages::fuse::io::MetaReaders;
 use databend_query::storages::fuse::meta::BlockMeta;
 use databend_query::storages::fuse::meta::ColumnMeta;
 use databend_query::storages::fuse::meta::Compression;
@@ -28,6 +33,7 @@ use databend_query::storages::fuse::FuseTable;
 use databend_query::storages::index::ColumnStatistics;
 use futures::TryStreamExt;
 
+use crate::storages::fuse::table_test_fixture::append_sample_data;
 use crate::storages::fuse::table_test_fixture::TestFixture;
 
 #[test]
@@ -41,6 +47,8 @@ fn test_to_partitions() -> Result<()> {
         max: DataValue::Int64(2),
         null_count: 0,
         in_memory_size: col_size as u64,
+        min_max_skipped: false,
+        histogram: None,
     };
 
     let col_metas_gen = || ColumnMeta {
@@ -70,6 +78,7 @@ fn test_to_partitions() -> Result<()> {
         col_metas: cols_metas,
         location: ("".to_owned(), 0),
         compression: Compression::Lz4Raw,
+        external: false,
     };
 
     let blocks_metas = (0..num_of_block)
@@ -105,6 +114,7 @@ fn test_to_partitions() -> Result<()> {
         filters: vec![],
         limit: None,
         order_by: vec![],
+        preserve_order: false,
     });
     let (stats, _) = FuseTable::to_partitions(&blocks_metas, push_down);
     assert_eq!(expected_block_size * num_of_block, stats.read_bytes as u64);
@@ -142,6 +152,7 @@ async fn test_fuse_table_exact_statistic() -> Result<()> {
             filters: vec![],
             limit: None,
             order_by: vec![],
+            preserve_order: false,
         };
         let (stats, parts) = table.read_partitions(ctx.clone(), Some(push_downs)).await?;
         assert_eq!(stats.read_rows, num_blocks * rows_per_block);
@@ -150,3 +161,73 @@ async fn test_fuse_table_exact_statistic() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_fuse_table_plan_statistics() -> Result<()> {
+    fn find(plan: &PlanNode, name: &str) -> Option<PlanNode> {
+        match plan.name() == name {
+            true => Some(plan.clone()),
+            false => plan.inputs().iter().find_map(|input| find(input, name)),
+        }
+    }
+
+    let fixture = TestFixture::new().await;
+    let ctx = fixture.ctx();
+    fixture.create_default_table().await?;
+
+    // 9 rows, with values 1, 2 and 3
+    append_sample_data(3, &fixture).await?;
+
+    let table = fixture.latest_default_table().await?;
+    let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+    let reader = MetaReaders::table_snapshot_reader(ctx.as_ref());
+    let snapshots = reader
+        .read_chain(
+            fuse_table.snapshot_loc(),
+            fuse_table.snapshot_format_version(),
+            fuse_table.meta_location_generator().clone(),
+            1,
+        )
+        .await?;
+    let summary = &snapshots[0].1.summary;
+
+    let db = fixture.default_db_name();
+    let tbl = fixture.default_table_name();
+
+    // the scan estimates are exact, and carried through the projection
+    {
+        let query = format!("select id from {}.{}", db, tbl);
+        let plan = PlanParser::parse(ctx.clone(), &query).await?;
+        let scan = find(&plan, "ReadSourcePlan").unwrap();
+        let estimated = scan.estimated_statistics();
+        assert!(estimated.is_exact);
+        assert_eq!(estimated.estimated_rows, Some(summary.row_count));
+        assert_eq!(plan.estimated_statistics(), estimated);
+    }
+
+    // the scan is inexact below a pushed down filter, the filter and the limit compose on top of it
+    {
+        let query = format!("select id from {}.{} where id > 1", db, tbl);
+        let plan = PlanParser::parse(ctx.clone(), &query).await?;
+        let scan = find(&plan, "ReadSourcePlan").unwrap();
+        let scan_estimated = scan.estimated_statistics();
+        assert!(!scan_estimated.is_exact);
+        assert!(scan_estimated.estimated_rows.unwrap() <= summary.row_count);
+
+        let filter = find(&plan, "FilterPlan").unwrap();
+        let filter_estimated = filter.estimated_statistics();
+        let expected_rows = (scan_estimated.estimated_rows.unwrap() as f64
+            * DEFAULT_FILTER_SELECTIVITY)
+            .ceil() as u64;
+        assert!(!filter_estimated.is_exact);
+        assert_eq!(filter_estimated.estimated_rows, Some(expected_rows));
+
+        let projection = find(&plan, "ProjectionPlan").unwrap();
+        assert_eq!(projection.estimated_statistics(), filter_estimated);
+
+        let limit = PlanBuilder::from(&projection).limit(1)?.build()?;
+        assert_eq!(limit.estimated_statistics().estimated_rows, Some(1));
+    }
+
+    Ok(())
+}
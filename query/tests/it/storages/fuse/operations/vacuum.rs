@@ -0,0 +1,84 @@
+//  Copyright 2021 Datafuse Labs.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+
+use std::path::Path;
+
+use common_base::tokio;
+use common_exception::Result;
+use databend_query::storages::fuse::FuseTable;
+use filetime::FileTime;
+
+use crate::storages::fuse::table_test_fixture::append_sample_data;
+use crate::storages::fuse::table_test_fixture::check_data_dir;
+use crate::storages::fuse::table_test_fixture::execute_command;
+use crate::storages::fuse::table_test_fixture::TestFixture;
+
+// creates a file which is not referenced by any snapshot, as a failed write would leave
+fn create_orphan(table_dir: &Path, location: &str, mtime: Option<i64>) {
+    let path = table_dir.join(location);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, b"orphan").unwrap();
+    if let Some(secs) = mtime {
+        filetime::set_file_mtime(&path, FileTime::from_unix_time(secs, 0)).unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_fuse_vacuum_orphan_files() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let db = fixture.default_db_name();
+    let tbl = fixture.default_table_name();
+    let ctx = fixture.ctx();
+    fixture.create_default_table().await?;
+
+    append_sample_data(1, &fixture).await?;
+    check_data_dir(&fixture, "vacuum_before_orphans", 1, 1, 1).await;
+
+    let table = fixture.latest_default_table().await?;
+    let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+    let prefix = fuse_table.meta_location_generator().prefix().to_owned();
+    let data_path = ctx.get_config().storage.fs.data_path;
+    let table_dir = Path::new(&data_path).join(&prefix);
+
+    // beyond the default safety window (1 day)
+    let two_days_ago = chrono::Utc::now().timestamp() - 2 * 24 * 60 * 60;
+    create_orphan(&table_dir, "_b/orphan_old.parquet", Some(two_days_ago));
+    create_orphan(
+        &table_dir,
+        "_sg/2020/01/01/orphan_old.json",
+        Some(two_days_ago),
+    );
+    // might be a file of an in-flight write
+    create_orphan(&table_dir, "_b/orphan_new.parquet", None);
+    check_data_dir(&fixture, "vacuum_orphans_created", 1, 2, 3).await;
+
+    // dry run only reports the old orphans
+    let mut orphans = fuse_table.do_vacuum(ctx.clone(), true).await?;
+    orphans.sort();
+    let expected = vec![
+        format!("{}/_b/orphan_old.parquet", prefix),
+        format!("{}/_sg/2020/01/01/orphan_old.json", prefix),
+    ];
+    assert_eq!(expected, orphans);
+    check_data_dir(&fixture, "vacuum_dry_run", 1, 2, 3).await;
+
+    // only the old orphans are removed, referenced files are kept
+    let qry = format!("optimize table {}.{} vacuum", db, tbl);
+    execute_command(ctx.clone(), qry.as_str()).await?;
+    check_data_dir(&fixture, "vacuum_after", 1, 1, 2).await;
+    assert!(table_dir.join("_b/orphan_new.parquet").exists());
+
+    Ok(())
+}
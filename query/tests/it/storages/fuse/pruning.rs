@@ -13,11 +13,21 @@
 //  limitations under the License.
 //
 
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use common_base::tokio;
+use common_base::tokio::sync::mpsc;
+use common_base::tokio::sync::Semaphore;
+use common_base::AbortHandle;
+use common_base::Runtime;
+use common_cache::Cache;
 use common_datablocks::DataBlock;
 use common_datavalues::prelude::*;
+use common_exception::ErrorCode;
 use common_exception::Result;
 use common_meta_types::TableMeta;
 use common_planners::add;
@@ -25,19 +35,50 @@ use common_planners::col;
 use common_planners::lit;
 use common_planners::sub;
 use common_planners::CreateTablePlan;
+use common_planners::Expression;
 use common_planners::Extras;
 use databend_query::catalogs::Catalog;
 use databend_query::interpreters::CreateTableInterpreter;
 use databend_query::sessions::QueryContext;
 use databend_query::sql::OPT_KEY_DATABASE_ID;
 use databend_query::sql::OPT_KEY_SNAPSHOT_LOCATION;
+use databend_query::storages::fuse::cache::PruneResultCache;
+use databend_query::storages::fuse::io::projected_segment_key;
+use databend_query::storages::fuse::io::BlockStreamWriter;
+use databend_query::storages::fuse::io::BlockWriteOptions;
 use databend_query::storages::fuse::io::MetaReaders;
+use databend_query::storages::fuse::io::TableMetaLocationGenerator;
+use databend_query::storages::fuse::io::WrittenLocations;
 use databend_query::storages::fuse::meta::BlockMeta;
+use databend_query::storages::fuse::meta::Compression;
+use databend_query::storages::fuse::meta::Projected;
+use databend_query::storages::fuse::meta::SegmentDigest;
+use databend_query::storages::fuse::meta::SegmentInfo;
+use databend_query::storages::fuse::meta::Statistics;
 use databend_query::storages::fuse::meta::TableSnapshot;
+use databend_query::storages::fuse::meta::Versioned;
 use databend_query::storages::fuse::pruning::BlockPruner;
+use databend_query::storages::fuse::pruning::Pred;
+use databend_query::storages::fuse::pruning::PruneDecision;
+use databend_query::storages::fuse::pruning::PruningStatistics;
+use databend_query::storages::fuse::pruning::RuntimeFilter;
+use databend_query::storages::fuse::statistics::merge_statistics;
 use databend_query::storages::fuse::FUSE_OPT_KEY_BLOCK_PER_SEGMENT;
 use databend_query::storages::fuse::FUSE_OPT_KEY_ROW_PER_BLOCK;
+use databend_query::storages::index::BlockStatistics;
+use databend_query::storages::index::ColumnStatistics;
 use futures::TryStreamExt;
+use opendal::ops::OpRead;
+use opendal::ops::OpStat;
+use opendal::ops::OpWrite;
+use opendal::services::memory;
+use opendal::Accessor;
+use opendal::BytesReader;
+use opendal::BytesWriter;
+use opendal::Metadata;
+use opendal::Operator;
+use serde::de::DeserializeSeed;
+use uuid::Uuid;
 
 use crate::storages::fuse::table_test_fixture::TestFixture;
 
@@ -190,6 +231,97 @@ async fn test_block_pruner() -> Result<()> {
 
     assert_eq!((num_blocks - max_val_of_b as usize - 1), blocks.len());
 
+    // only the statistics of the columns of the filter and the projection are read
+    let mut extra = Extras::default();
+    let pred = col("b").gt(lit(max_val_of_b));
+    extra.filters = vec![pred];
+    extra.projection = Some(vec![]);
+
+    let blocks = apply_block_pruning(
+        snapshot.clone(),
+        table.get_table_info().schema(),
+        &Some(extra),
+        ctx.clone(),
+    )
+    .await?;
+
+    assert_eq!((num_blocks - max_val_of_b as usize - 1), blocks.len());
+    for block in &blocks {
+        assert_eq!(vec![&1], block.col_stats.keys().collect::<Vec<_>>());
+    }
+
+    // statistics of the pruning
+    let all_blocks = apply_block_pruning(
+        snapshot.clone(),
+        table.get_table_info().schema(),
+        &None,
+        ctx.clone(),
+    )
+    .await?;
+    let blocks_pruned_size = all_blocks
+        .iter()
+        .filter(|b| b.col_stats[&1].max.as_u64().unwrap() <= max_val_of_b)
+        .map(|b| b.block_size)
+        .sum::<u64>();
+    let mut extra = Extras::default();
+    extra.filters = vec![col("b").gt(lit(max_val_of_b))];
+    let (blocks, statistics) = BlockPruner::new(snapshot.clone())
+        .apply_with_statistics(
+            table.get_table_info().schema(),
+            &Some(extra.clone()),
+            ctx.as_ref(),
+        )
+        .await?;
+    let num_pruned = max_val_of_b + 1;
+    assert_eq!((num_blocks - num_pruned as usize), blocks.len());
+    assert_eq!(
+        PruningStatistics {
+            segments_total: num_blocks as u64,
+            // one block per segment, thus the segments are pruned by their summaries
+            segments_pruned: num_pruned,
+            blocks_total: num_blocks as u64,
+            blocks_pruned: num_pruned,
+            rows_pruned_estimate: num_pruned * row_per_block as u64,
+            bytes_pruned_estimate: blocks_pruned_size,
+            top_k_threshold: None,
+        },
+        statistics
+    );
+
+    // and they are recorded in the context, while reading the partitions
+    table.read_partitions(ctx.clone(), Some(extra)).await?;
+    assert_eq!(statistics, ctx.get_pruning_statistics());
+
+    // all the filters are used, each of them prunes some blocks
+    let mut extra = Extras::default();
+    extra.filters = vec![col("b").gt(lit(3u64)), col("b").lt(lit(6u64))];
+
+    let blocks = apply_block_pruning(
+        snapshot.clone(),
+        table.get_table_info().schema(),
+        &Some(extra),
+        ctx.clone(),
+    )
+    .await?;
+    // blocks of b in [4, 5]
+    assert_eq!(2, blocks.len());
+
+    // filters which can not be verified do not prune, nor fail the others
+    let mut extra = Extras::default();
+    extra.filters = vec![
+        Expression::create_scalar_function("no_such_function", vec![col("b")]),
+        col("b").gt(lit(max_val_of_b)),
+    ];
+
+    let blocks = apply_block_pruning(
+        snapshot.clone(),
+        table.get_table_info().schema(),
+        &Some(extra),
+        ctx.clone(),
+    )
+    .await?;
+    assert_eq!((num_blocks - max_val_of_b as usize - 1), blocks.len());
+
     Ok(())
 }
 
@@ -312,3 +444,1052 @@ async fn test_block_pruner_monotonic() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_block_pruner_with_unorderable_column() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let ctx = fixture.ctx();
+
+    let test_tbl_name = "test_pruner_unorderable";
+    let struct_type: DataTypePtr = Arc::new(StructType::create(
+        vec!["x".to_owned(), "y".to_owned()],
+        vec![u64::to_data_type(), Vu8::to_data_type()],
+    ));
+    let test_schema = DataSchemaRefExt::create(vec![
+        DataField::new("a", u64::to_data_type()),
+        DataField::new("s", struct_type.clone()),
+    ]);
+
+    let num_blocks = 10;
+    let row_per_block = 10;
+
+    let create_table_plan = CreateTablePlan {
+        if_not_exists: false,
+        tenant: fixture.default_tenant(),
+        db: fixture.default_db_name(),
+        table: test_tbl_name.to_string(),
+        table_meta: TableMeta {
+            schema: test_schema.clone(),
+            engine: "FUSE".to_string(),
+            options: [
+                (
+                    FUSE_OPT_KEY_ROW_PER_BLOCK.to_owned(),
+                    row_per_block.to_string(),
+                ),
+                (FUSE_OPT_KEY_BLOCK_PER_SEGMENT.to_owned(), "1".to_owned()),
+                (OPT_KEY_DATABASE_ID.to_owned(), "1".to_owned()),
+            ]
+            .into(),
+            ..Default::default()
+        },
+        as_select: None,
+    };
+    let interpreter = CreateTableInterpreter::try_create(ctx.clone(), create_table_plan)?;
+    interpreter.execute(None).await?;
+
+    let catalog = ctx.get_catalog();
+    let table = catalog
+        .get_table(
+            fixture.default_tenant().as_str(),
+            fixture.default_db_name().as_str(),
+            test_tbl_name,
+        )
+        .await?;
+
+    // for the block of index `i`, all the values of column a equal `i`
+    let blocks = (0..num_blocks)
+        .into_iter()
+        .map(|idx| {
+            let a = vec![idx as u64; row_per_block];
+            let x = vec![idx as u64; row_per_block];
+            let y = vec!["v"; row_per_block];
+            let s = StructColumn::from_data(
+                vec![Series::from_data(x), Series::from_data(y)],
+                struct_type.clone(),
+            );
+            Ok(DataBlock::create(test_schema.clone(), vec![
+                Series::from_data(a),
+                Arc::new(s),
+            ]))
+        })
+        .collect::<Vec<_>>();
+
+    // appending blocks of unorderable columns should not fail
+    let stream = Box::pin(futures::stream::iter(blocks));
+    let r = table.append_data(ctx.clone(), stream).await?;
+    table
+        .commit_insertion(ctx.clone(), r.try_collect().await?, false)
+        .await?;
+
+    let table = catalog
+        .get_table(
+            fixture.default_tenant().as_str(),
+            fixture.default_db_name().as_str(),
+            test_tbl_name,
+        )
+        .await?;
+    let snapshot_loc = table
+        .get_table_info()
+        .options()
+        .get(OPT_KEY_SNAPSHOT_LOCATION)
+        .unwrap();
+    let reader = MetaReaders::table_snapshot_reader(ctx.as_ref());
+    let snapshot = reader.read(snapshot_loc.as_str(), None, 1).await?;
+
+    // min/max of the struct column are skipped, null count is kept
+    let struct_stats = snapshot.summary.col_stats.get(&1).unwrap();
+    assert!(struct_stats.min_max_skipped);
+    assert_eq!(DataValue::Null, struct_stats.min);
+    assert_eq!(0, struct_stats.null_count);
+
+    // the other columns could still be used to prune
+    let mut extra = Extras::default();
+    extra.filters = vec![col("a").gt(lit(5u64))];
+    let blocks = apply_block_pruning(
+        snapshot.clone(),
+        table.get_table_info().schema(),
+        &Some(extra),
+        ctx.clone(),
+    )
+    .await?;
+    assert_eq!(4, blocks.len());
+
+    Ok(())
+}
+
+// counts the reads of the underlying accessor, which may be slowed down
+#[derive(Debug)]
+struct CountingAccessor {
+    inner: Arc<dyn Accessor>,
+    reads: AtomicUsize,
+    gate: Option<Gate>,
+}
+
+// the reads of the `held` paths are announced to `started`, then held until the gate is opened
+struct Gate {
+    held: Box<dyn Fn(&str) -> bool + Send + Sync>,
+    semaphore: Semaphore,
+    started: mpsc::UnboundedSender<String>,
+}
+
+impl CountingAccessor {
+    async fn create() -> Result<Arc<CountingAccessor>> {
+        Ok(Arc::new(CountingAccessor {
+            inner: memory::Backend::build().finish().await?,
+            reads: AtomicUsize::new(0),
+            gate: None,
+        }))
+    }
+
+    // reads from the storage of `accessor`, holding the reads of the `held` paths
+    fn gated(
+        accessor: &CountingAccessor,
+        held: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> (Arc<CountingAccessor>, mpsc::UnboundedReceiver<String>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let accessor = Arc::new(CountingAccessor {
+            inner: accessor.inner.clone(),
+            reads: AtomicUsize::new(0),
+            gate: Some(Gate {
+                held: Box::new(held),
+                semaphore: Semaphore::new(0),
+                started: tx,
+            }),
+        });
+        (accessor, rx)
+    }
+
+    fn open_gate(&self) {
+        if let Some(gate) = &self.gate {
+            gate.semaphore.close();
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Accessor for CountingAccessor {
+    async fn read(&self, args: &OpRead) -> std::io::Result<BytesReader> {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        match &self.gate {
+            Some(gate) if (gate.held)(&args.path) => {
+                let _ = gate.started.send(args.path.clone());
+                // fails once the gate is opened, i.e. closed for good
+                let _ = gate.semaphore.acquire().await;
+            }
+            _ => {}
+        }
+        self.inner.read(args).await
+    }
+
+    async fn write(&self, args: &OpWrite) -> std::io::Result<BytesWriter> {
+        self.inner.write(args).await
+    }
+
+    async fn stat(&self, args: &OpStat) -> std::io::Result<Metadata> {
+        self.inner.stat(args).await
+    }
+}
+
+// writes the segments, of one block each, and returns the snapshot of them
+async fn write_segments(
+    operator: Operator,
+    schema: DataSchemaRef,
+    num_segments: usize,
+    row_per_block: usize,
+) -> Result<TableSnapshot> {
+    let block = DataBlock::create(schema.clone(), vec![Series::from_data(vec![
+        1u64;
+        row_per_block
+    ])]);
+    let blocks = std::iter::repeat(block).take(num_segments).collect();
+    write_blocks(operator, schema, blocks).await
+}
+
+// writes each of the blocks as a segment, and returns the snapshot of them
+async fn write_blocks(
+    operator: Operator,
+    schema: DataSchemaRef,
+    blocks: Vec<DataBlock>,
+) -> Result<TableSnapshot> {
+    let num_blocks = blocks.len();
+    let row_per_block = blocks.iter().map(|b| b.num_rows()).max().unwrap_or(1);
+    let stream = Box::pin(futures::stream::iter(blocks.into_iter().map(Ok)));
+    let locs = TableMetaLocationGenerator::with_prefix("_t".to_owned());
+    let runtime = Arc::new(Runtime::with_worker_threads(2, None)?);
+    let segment_infos = BlockStreamWriter::write_block_stream(
+        runtime,
+        AbortHandle::default().registration(),
+        operator.clone(),
+        stream,
+        schema.clone(),
+        row_per_block,
+        1,
+        locs.clone(),
+        BlockWriteOptions::default(),
+        WrittenLocations::default(),
+    )
+    .await
+    .try_collect::<Vec<_>>()
+    .await?;
+
+    let mut segments = vec![];
+    let mut row_count = 0;
+    for segment_info in &segment_infos {
+        let location = locs.gen_segment_info_location();
+        let bytes = serde_json::to_vec(segment_info)?;
+        operator.object(&location).write(bytes).await?;
+        segments.push((location, SegmentInfo::VERSION));
+        row_count += segment_info.summary.row_count;
+    }
+    let summary = Statistics {
+        row_count,
+        block_count: num_blocks as u64,
+        ..Default::default()
+    };
+    Ok(TableSnapshot::new(
+        Uuid::new_v4(),
+        &None,
+        None,
+        schema.as_ref().clone(),
+        summary,
+        segments,
+    ))
+}
+
+#[tokio::test]
+async fn test_block_pruner_limit() -> Result<()> {
+    let ctx = crate::tests::create_query_context().await?;
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", u64::to_data_type())]);
+
+    let accessor = CountingAccessor::create().await?;
+    let operator = Operator::new(accessor.clone());
+
+    // 100 segments, of one block of 10 rows each
+    let snapshot = write_segments(operator.clone(), schema.clone(), 100, 10).await?;
+
+    let mut extras = Extras::default();
+    extras.limit = Some(5);
+    let push_down = Some(extras);
+    accessor.reads.store(0, Ordering::SeqCst);
+    let blocks = BlockPruner::new(Arc::new(snapshot))
+        .with_operator(operator)
+        .apply(schema, &push_down, ctx.as_ref())
+        .await?;
+
+    // the first segment covers the limit, the others are not read at all
+    assert_eq!(1, blocks.len());
+    assert_eq!(1, accessor.reads.load(Ordering::SeqCst));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_block_pruner_limit_in_segment_order() -> Result<()> {
+    let ctx = crate::tests::create_query_context().await?;
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", u64::to_data_type())]);
+
+    let accessor = CountingAccessor::create().await?;
+    // 10 segments, of one block of 10 rows each
+    let snapshot = write_segments(Operator::new(accessor.clone()), schema.clone(), 10, 10).await?;
+    let first_segment = snapshot.segments[0].0.clone();
+    let held = first_segment.clone();
+    let (accessor, mut started) =
+        CountingAccessor::gated(&accessor, move |path| path.ends_with(&held));
+
+    // the limit is covered by 3 segments, which are read concurrently
+    let mut extras = Extras::default();
+    extras.limit = Some(25);
+    let push_down = Some(extras);
+    let expected = snapshot
+        .segments
+        .iter()
+        .take(3)
+        .map(|(location, _)| location.clone())
+        .collect::<Vec<_>>();
+    let prune = BlockPruner::new(Arc::new(snapshot))
+        .with_operator(Operator::new(accessor.clone()))
+        .apply(schema, &push_down, ctx.as_ref());
+    // the read of the first segment completes after the reads of the others
+    let release = async {
+        assert!(started.recv().await.unwrap().ends_with(&first_segment));
+        while accessor.reads.load(Ordering::SeqCst) < 3 {
+            tokio::task::yield_now().await;
+        }
+        accessor.open_gate();
+    };
+    let (blocks, _) = futures::join!(prune, release);
+
+    // the blocks of the first segments are kept, in the order of the segments
+    let expected = read_segments_of(&accessor, &expected).await?;
+    let blocks = blocks?
+        .into_iter()
+        .map(|block_meta| block_meta.location.0)
+        .collect::<Vec<_>>();
+    assert_eq!(expected, blocks);
+    Ok(())
+}
+
+// the locations of the blocks of the segments at `locations`
+async fn read_segments_of(
+    accessor: &CountingAccessor,
+    locations: &[String],
+) -> Result<Vec<String>> {
+    let operator = Operator::new(accessor.inner.clone());
+    let mut blocks = vec![];
+    for location in locations {
+        let bytes = operator.object(location).read().await?;
+        let segment = serde_json::from_slice::<SegmentInfo>(&bytes)?;
+        blocks.extend(segment.blocks.into_iter().map(|b| b.location.0));
+    }
+    Ok(blocks)
+}
+
+#[tokio::test]
+async fn test_block_pruner_abort() -> Result<()> {
+    let ctx = crate::tests::create_query_context().await?;
+    ctx.get_settings()
+        .set_settings("max_pruning_concurrency".to_owned(), "2".to_owned(), false)?;
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", u64::to_data_type())]);
+
+    let accessor = CountingAccessor::create().await?;
+    let snapshot = write_segments(Operator::new(accessor.clone()), schema.clone(), 100, 10).await?;
+    let (accessor, mut started) = CountingAccessor::gated(&accessor, |_| true);
+    let operator = Operator::new(accessor.clone());
+
+    let prune = BlockPruner::new(Arc::new(snapshot))
+        .with_operator(operator)
+        .apply(schema, &None, ctx.as_ref());
+    // kills the query, while the reads of both the concurrent slots are held
+    let kill = async {
+        for _ in 0..2 {
+            started.recv().await;
+        }
+        ctx.kill();
+    };
+    let (res, _) = futures::join!(prune, kill);
+
+    // the held reads are dropped at once, without waiting for them
+    let err = res.unwrap_err();
+    assert_eq!(ErrorCode::AbortedQuery("").code(), err.code());
+
+    // the setting bounds the concurrent reads, and no more reads are issued after the
+    // query is aborted
+    accessor.open_gate();
+    assert_eq!(2, accessor.reads.load(Ordering::SeqCst));
+    assert!(started.try_recv().is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_block_pruner_result_cache() -> Result<()> {
+    let mut config = crate::tests::ConfigBuilder::create().config();
+    config.query.table_cache_enabled = true;
+    let ctx = crate::tests::create_query_context_with_config(config, None).await?;
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", u64::to_data_type())]);
+
+    let accessor = CountingAccessor::create().await?;
+    let operator = Operator::new(accessor.clone());
+    let num_segments = 10;
+    let snapshot =
+        Arc::new(write_segments(operator.clone(), schema.clone(), num_segments, 10).await?);
+
+    // with a projection, the segments are read partially, and cached by the projection
+    let push_down = |filters: Vec<Expression>| {
+        let mut extras = Extras::default();
+        extras.filters = filters;
+        extras.projection = Some(vec![0]);
+        Some(extras)
+    };
+    let a_gt_0 = col("a").gt(lit(0u64));
+    let a_lt_5 = col("a").lt(lit(5u64));
+
+    accessor.reads.store(0, Ordering::SeqCst);
+    let blocks = BlockPruner::new(snapshot.clone())
+        .with_operator(operator.clone())
+        .apply(
+            schema.clone(),
+            &push_down(vec![a_gt_0.clone(), a_lt_5.clone()]),
+            ctx.as_ref(),
+        )
+        .await?;
+    assert_eq!(num_segments, blocks.len());
+    assert_eq!(num_segments, accessor.reads.load(Ordering::SeqCst));
+    let segment_cache = ctx
+        .get_storage_cache_manager()
+        .get_table_segment_cache()
+        .unwrap();
+    for (location, _) in &snapshot.segments {
+        let cache = segment_cache.read().await;
+        assert!(!cache.contains(location));
+        assert!(cache.contains(&projected_segment_key(location, &HashSet::from([0]))));
+    }
+
+    // the same filters, in another order, hit the cache
+    accessor.reads.store(0, Ordering::SeqCst);
+    let (cached_blocks, statistics) = BlockPruner::new(snapshot.clone())
+        .with_operator(operator.clone())
+        .apply_with_statistics(
+            schema.clone(),
+            &push_down(vec![a_lt_5.clone(), a_gt_0.clone()]),
+            ctx.as_ref(),
+        )
+        .await?;
+    assert_eq!(blocks.len(), cached_blocks.len());
+    assert_eq!(0, accessor.reads.load(Ordering::SeqCst));
+    assert_eq!(0, statistics.blocks_pruned);
+
+    // other filters miss, the projected segments are cached though
+    let (blocks, statistics) = BlockPruner::new(snapshot.clone())
+        .with_operator(operator.clone())
+        .apply_with_statistics(
+            schema.clone(),
+            &push_down(vec![col("a").gt(lit(1u64))]),
+            ctx.as_ref(),
+        )
+        .await?;
+    assert!(blocks.is_empty());
+    assert_eq!(num_segments as u64, statistics.blocks_pruned);
+    assert_eq!(0, accessor.reads.load(Ordering::SeqCst));
+
+    // a new snapshot of the same segments misses
+    segment_cache.write().await.clear();
+    accessor.reads.store(0, Ordering::SeqCst);
+    let new_snapshot = TableSnapshot::new(
+        Uuid::new_v4(),
+        &None,
+        None,
+        schema.as_ref().clone(),
+        snapshot.summary.clone(),
+        snapshot.segments.clone(),
+    );
+    let blocks = BlockPruner::new(Arc::new(new_snapshot))
+        .with_operator(operator)
+        .apply(schema, &push_down(vec![a_gt_0, a_lt_5]), ctx.as_ref())
+        .await?;
+    assert_eq!(num_segments, blocks.len());
+    assert_eq!(num_segments, accessor.reads.load(Ordering::SeqCst));
+    Ok(())
+}
+
+#[test]
+fn test_prune_result_cache_key() {
+    let snapshot_id = Uuid::new_v4();
+    let a_gt_0 = col("a").gt(lit(0u64));
+    let a_lt_5 = col("a").lt(lit(5u64));
+
+    // the whole filters and projection are kept in the key, independent of their order
+    let key = PruneResultCache::key(&snapshot_id, &[a_gt_0.clone(), a_lt_5.clone()], None);
+    assert!(key.contains(&a_gt_0.column_name()));
+    assert!(key.contains(&a_lt_5.column_name()));
+    assert_eq!(
+        key,
+        PruneResultCache::key(&snapshot_id, &[a_lt_5.clone(), a_gt_0.clone()], None)
+    );
+
+    let keys = [
+        PruneResultCache::key(&snapshot_id, &[], None),
+        PruneResultCache::key(&snapshot_id, &[a_gt_0.clone()], None),
+        PruneResultCache::key(&snapshot_id, &[a_gt_0.clone()], Some(&[0])),
+        PruneResultCache::key(&snapshot_id, &[a_gt_0], Some(&[0, 1])),
+        PruneResultCache::key(&Uuid::new_v4(), &[a_lt_5], None),
+    ];
+    let distinct = keys.iter().collect::<HashSet<_>>();
+    assert_eq!(keys.len(), distinct.len());
+}
+
+#[tokio::test]
+async fn test_prune_result_cache_budget() -> Result<()> {
+    let blocks = |n: usize| {
+        (0..n)
+            .map(|i| BlockMeta {
+                row_count: 10,
+                block_size: 0,
+                file_size: 0,
+                col_stats: HashMap::new(),
+                col_metas: HashMap::new(),
+                location: (format!("_b/{}.parquet", i), 0),
+                compression: Compression::Lz4Raw,
+                external: false,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // the results are measured by their sizes, rather than counted
+    let cache = PruneResultCache::new(u64::MAX);
+    cache.put("small".to_owned(), blocks(1)).await;
+    let small = cache.size().await;
+    cache.put("large".to_owned(), blocks(10)).await;
+    let large = cache.size().await - small;
+    assert!(large >= small * 10);
+
+    // the least recently put results are evicted, once the budget is exceeded
+    let cache = PruneResultCache::new(large + small);
+    cache.put("large".to_owned(), blocks(10)).await;
+    cache.put("small".to_owned(), blocks(1)).await;
+    assert_eq!(10, cache.get("large").await.unwrap().len());
+    cache.put("other".to_owned(), blocks(1)).await;
+    assert!(cache.get("large").await.is_none());
+    assert_eq!(1, cache.get("small").await.unwrap().len());
+    assert_eq!(1, cache.get("other").await.unwrap().len());
+    Ok(())
+}
+
+#[test]
+fn test_segment_projected_deserialize() -> Result<()> {
+    let col_stats = (0..3)
+        .map(|id| {
+            (id, ColumnStatistics {
+                min: DataValue::UInt64(0),
+                max: DataValue::UInt64(id as u64),
+                null_count: 0,
+                in_memory_size: 0,
+                min_max_skipped: false,
+                histogram: None,
+            })
+        })
+        .collect::<HashMap<_, _>>();
+    let block = BlockMeta {
+        row_count: 10,
+        block_size: 1,
+        file_size: 2,
+        col_stats: col_stats.clone(),
+        col_metas: HashMap::new(),
+        location: ("_b/0.parquet".to_owned(), 0),
+        compression: Compression::Lz4Raw,
+        external: true,
+    };
+    let summary = Statistics {
+        row_count: 10,
+        block_count: 1,
+        col_stats,
+        ..Default::default()
+    };
+    let bytes = serde_json::to_vec(&SegmentInfo::new(vec![block], summary))?;
+
+    let projection = HashSet::from([1]);
+    let mut deserializer = serde_json::Deserializer::from_slice(&bytes);
+    let segment = Projected::<SegmentInfo>::new(&projection).deserialize(&mut deserializer)?;
+    assert_eq!(SegmentInfo::VERSION, segment.format_version());
+    assert_eq!(
+        vec![&1],
+        segment.summary.col_stats.keys().collect::<Vec<_>>()
+    );
+    assert_eq!(10, segment.summary.row_count);
+    let block = &segment.blocks[0];
+    assert_eq!(vec![&1], block.col_stats.keys().collect::<Vec<_>>());
+    assert_eq!(DataValue::UInt64(1), block.col_stats[&1].max);
+    assert_eq!(
+        (10, 1, 2),
+        (block.row_count, block.block_size, block.file_size)
+    );
+    assert_eq!("_b/0.parquet", block.location.0);
+    assert_eq!(Compression::Lz4Raw, block.compression);
+    assert!(block.external);
+
+    // the fields absent from the older segments take their defaults
+    let mut value = serde_json::from_slice::<serde_json::Value>(&bytes)?;
+    let block = value["blocks"][0].as_object_mut().unwrap();
+    block.remove("compression");
+    block.remove("external");
+    let bytes = serde_json::to_vec(&value)?;
+    let mut deserializer = serde_json::Deserializer::from_slice(&bytes);
+    let segment = Projected::<SegmentInfo>::new(&projection).deserialize(&mut deserializer)?;
+    assert_eq!(Compression::legacy(), segment.blocks[0].compression);
+    assert!(!segment.blocks[0].external);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_block_pruner_top_k() -> Result<()> {
+    let ctx = crate::tests::create_query_context().await?;
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("ts", u64::to_data_type()),
+        DataField::new_nullable("v", i64::to_data_type()),
+    ]);
+    let operator = Operator::new(memory::Backend::build().finish().await?);
+
+    // 100 blocks of 10 rows, ts of the block i in [i * 10, i * 10 + 9],
+    // v of the block i in [i, i] and NULLs, except v of the block 0 are all NULLs
+    let blocks = (0..100u64)
+        .map(|i| {
+            let ts = (i * 10..i * 10 + 10).collect::<Vec<_>>();
+            let v = (0..10)
+                .map(|j| {
+                    if i == 0 || j == 0 {
+                        None
+                    } else {
+                        Some(i as i64)
+                    }
+                })
+                .collect::<Vec<_>>();
+            DataBlock::create(schema.clone(), vec![
+                Series::from_data(ts),
+                Series::from_data(v),
+            ])
+        })
+        .collect();
+    let snapshot = Arc::new(write_blocks(operator.clone(), schema.clone(), blocks).await?);
+
+    let top_k = |column: &str, asc: bool, nulls_first: bool, limit: usize| {
+        let mut extras = Extras::default();
+        extras.limit = Some(limit);
+        extras.order_by = vec![Expression::Sort {
+            expr: Box::new(col(column)),
+            asc,
+            nulls_first,
+            origin_expr: Box::new(col(column)),
+        }];
+        Some(extras)
+    };
+    // the first ts of the blocks, to identify them
+    let first_ts = |blocks: &[BlockMeta]| {
+        blocks
+            .iter()
+            .map(|b| b.col_stats[&0].min.as_u64().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    struct Test {
+        name: &'static str,
+        push_down: Option<Extras>,
+        expected: Vec<u64>,
+        threshold: Option<DataValue>,
+    }
+
+    let tests = vec![
+        Test {
+            name: "order by ts desc limit 15",
+            push_down: top_k("ts", false, false, 15),
+            expected: vec![990, 980],
+            threshold: Some(DataValue::UInt64(980)),
+        },
+        Test {
+            name: "order by ts asc limit 10",
+            push_down: top_k("ts", true, false, 10),
+            expected: vec![0],
+            threshold: Some(DataValue::UInt64(9)),
+        },
+        Test {
+            name: "order by ts asc limit 0",
+            push_down: top_k("ts", true, false, 0),
+            expected: vec![],
+            threshold: None,
+        },
+        Test {
+            // the NULLs ranked last are not counted
+            name: "order by v desc nulls last limit 9",
+            push_down: top_k("v", false, false, 9),
+            expected: vec![990],
+            threshold: Some(DataValue::Int64(99)),
+        },
+        Test {
+            name: "order by v desc nulls last limit 10",
+            push_down: top_k("v", false, false, 10),
+            expected: vec![990, 980],
+            threshold: Some(DataValue::Int64(98)),
+        },
+        Test {
+            // all the blocks having NULLs are kept
+            name: "order by v desc nulls first limit 1",
+            push_down: top_k("v", false, true, 1),
+            expected: std::iter::once(0)
+                .chain((1..100).rev().map(|i| i * 10))
+                .collect(),
+            threshold: None,
+        },
+    ];
+
+    for test in tests {
+        let (blocks, statistics) = BlockPruner::new(snapshot.clone())
+            .with_operator(operator.clone())
+            .apply_with_statistics(schema.clone(), &test.push_down, ctx.as_ref())
+            .await?;
+        assert_eq!(test.expected, first_ts(&blocks), "{}", test.name);
+        assert_eq!(
+            100 - test.expected.len() as u64,
+            statistics.blocks_pruned,
+            "{}",
+            test.name
+        );
+        assert_eq!(test.threshold, statistics.top_k_threshold, "{}", test.name);
+    }
+
+    // ties are kept: with the same values, no block is worse than the threshold
+    let tie_blocks = (0..10)
+        .map(|_| {
+            DataBlock::create(schema.clone(), vec![
+                Series::from_data(vec![7u64; 10]),
+                Series::from_data(vec![Some(1i64); 10]),
+            ])
+        })
+        .collect();
+    let tie_snapshot = write_blocks(operator.clone(), schema.clone(), tie_blocks).await?;
+    let blocks = BlockPruner::new(Arc::new(tie_snapshot))
+        .with_operator(operator)
+        .apply(schema, &top_k("ts", false, false, 5), ctx.as_ref())
+        .await?;
+    assert_eq!(10, blocks.len());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_block_pruner_segment_digests() -> Result<()> {
+    let ctx = crate::tests::create_query_context().await?;
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", u64::to_data_type())]);
+
+    let accessor = CountingAccessor::create().await?;
+    let operator = Operator::new(accessor.clone());
+
+    // 10 segments, of one block of 10 rows each, segment i holds the values i * 10 .. i * 10 + 9
+    let num_segments = 10u64;
+    let blocks = (0..num_segments)
+        .map(|i| {
+            DataBlock::create(schema.clone(), vec![Series::from_data(
+                (i * 10..i * 10 + 10).collect::<Vec<u64>>(),
+            )])
+        })
+        .collect();
+    let snapshot = write_blocks(operator.clone(), schema.clone(), blocks).await?;
+
+    let mut digests = vec![];
+    for (location, _) in &snapshot.segments {
+        let bytes = operator.object(location).read().await?;
+        let segment_info: SegmentInfo = serde_json::from_slice(&bytes)?;
+        digests.push(SegmentDigest::from_summary(&segment_info.summary));
+    }
+    let with_digests = Arc::new(snapshot.clone().with_segment_digests(Some(digests)));
+    let without_digests = Arc::new(snapshot);
+
+    let mut extras = Extras::default();
+    extras.filters = vec![col("a").gt_eq(lit(75u64))];
+    let push_down = Some(extras);
+
+    // without digests, every segment is read
+    accessor.reads.store(0, Ordering::SeqCst);
+    let (blocks, statistics) = BlockPruner::new(without_digests)
+        .with_operator(operator.clone())
+        .apply_with_statistics(schema.clone(), &push_down, ctx.as_ref())
+        .await?;
+    assert_eq!(3, blocks.len());
+    assert_eq!(num_segments as usize, accessor.reads.load(Ordering::SeqCst));
+    assert_eq!(7, statistics.segments_pruned);
+
+    // with digests, the segments failing the filter are pruned without being read
+    accessor.reads.store(0, Ordering::SeqCst);
+    let (blocks, statistics) = BlockPruner::new(with_digests)
+        .with_operator(operator)
+        .apply_with_statistics(schema, &push_down, ctx.as_ref())
+        .await?;
+    assert_eq!(3, blocks.len());
+    assert_eq!(3, accessor.reads.load(Ordering::SeqCst));
+    assert_eq!(
+        PruningStatistics {
+            segments_total: num_segments,
+            segments_pruned: 7,
+            blocks_total: num_segments,
+            blocks_pruned: 7,
+            rows_pruned_estimate: 70,
+            bytes_pruned_estimate: 0,
+            top_k_threshold: None,
+        },
+        statistics
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_block_pruner_lazy_runtime_filter() -> Result<()> {
+    let ctx = crate::tests::create_query_context().await?;
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", u64::to_data_type())]);
+    let operator = Operator::new(CountingAccessor::create().await?);
+
+    // 10 segments, of one block of 10 rows each, segment i holds the values i * 10 .. i * 10 + 9
+    let blocks = (0..10u64)
+        .map(|i| {
+            DataBlock::create(schema.clone(), vec![Series::from_data(
+                (i * 10..i * 10 + 10).collect::<Vec<u64>>(),
+            )])
+        })
+        .collect();
+    let snapshot = write_blocks(operator.clone(), schema.clone(), blocks).await?;
+
+    let mut extras = Extras::default();
+    extras.filters = vec![col("a").gt_eq(lit(20u64))];
+    let lazy_blocks = BlockPruner::new(Arc::new(snapshot))
+        .with_operator(operator)
+        .apply_lazy(schema, &Some(extras), ctx.as_ref())
+        .await?;
+    let min_a = |blocks: &[BlockMeta]| {
+        blocks
+            .iter()
+            .map(|b| b.col_stats[&0].min.as_u64().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    // the segments are pruned by the push-downs, the blocks are admitted on request
+    assert_eq!(2, lazy_blocks.statistics().segments_pruned);
+    assert_eq!(vec![20, 30, 40], min_a(&lazy_blocks.next_blocks(3)?));
+
+    // the join keys, e.g. collected by the build side, prune the remaining blocks
+    lazy_blocks.add_runtime_filter(RuntimeFilter::create_values(0, vec![
+        DataValue::UInt64(95),
+        DataValue::Null,
+        DataValue::UInt64(55),
+        DataValue::UInt64(300),
+    ]));
+    assert_eq!(vec![50, 90], min_a(&lazy_blocks.next_blocks(10)?));
+    assert!(lazy_blocks.next_blocks(10)?.is_empty());
+
+    let statistics = lazy_blocks.statistics();
+    assert_eq!(2, statistics.segments_pruned);
+    assert_eq!(2 + 3, statistics.blocks_pruned);
+
+    // a range of the keys
+    let filter = RuntimeFilter::create_min_max(0, DataValue::UInt64(12), DataValue::UInt64(20));
+    let stats = |min: u64, max: u64| {
+        let mut stats = HashMap::new();
+        stats.insert(0, ColumnStatistics {
+            min: DataValue::UInt64(min),
+            max: DataValue::UInt64(max),
+            null_count: 0,
+            in_memory_size: 0,
+            min_max_skipped: false,
+            histogram: None,
+        });
+        stats
+    };
+    assert!(!filter.eval(&stats(0, 11), 10)?);
+    assert!(filter.eval(&stats(0, 12), 10)?);
+    assert!(filter.eval(&stats(20, 29), 10)?);
+    assert!(!filter.eval(&stats(21, 29), 10)?);
+    // the blocks without the statistics of the column are kept
+    assert!(filter.eval(&HashMap::new(), 10)?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_block_pruner_explain() -> Result<()> {
+    let ctx = crate::tests::create_query_context().await?;
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", u64::to_data_type())]);
+
+    let operator = Operator::new(CountingAccessor::create().await?);
+
+    // 10 segments, of two blocks of 5 rows each, segment i holds the values i * 10 .. i * 10 + 9
+    let blocks = (0..20u64)
+        .map(|i| {
+            DataBlock::create(schema.clone(), vec![Series::from_data(
+                (i * 5..i * 5 + 5).collect::<Vec<u64>>(),
+            )])
+        })
+        .collect::<Vec<_>>();
+    let mut segments = vec![];
+    let mut row_count = 0;
+    for pair in blocks.chunks(2) {
+        let snapshot = write_blocks(operator.clone(), schema.clone(), pair.to_vec()).await?;
+        let segment_info = merge_segments(&operator, &snapshot).await?;
+        let location =
+            TableMetaLocationGenerator::with_prefix("_t".to_owned()).gen_segment_info_location();
+        operator
+            .object(&location)
+            .write(serde_json::to_vec(&segment_info)?)
+            .await?;
+        segments.push((location, SegmentInfo::VERSION));
+        row_count += segment_info.summary.row_count;
+    }
+    let summary = Statistics {
+        row_count,
+        block_count: 20,
+        ..Default::default()
+    };
+    let snapshot = Arc::new(TableSnapshot::new(
+        Uuid::new_v4(),
+        &None,
+        None,
+        schema.as_ref().clone(),
+        summary,
+        segments,
+    ));
+
+    let filters = |filters: Vec<Expression>| {
+        let mut extras = Extras::default();
+        extras.filters = filters;
+        Some(extras)
+    };
+    let mut top_k = Extras::default();
+    top_k.order_by = vec![Expression::Sort {
+        expr: Box::new(col("a")),
+        asc: false,
+        nulls_first: false,
+        origin_expr: Box::new(col("a")),
+    }];
+    top_k.limit = Some(12);
+
+    let push_downs = vec![
+        None,
+        filters(vec![col("a").gt_eq(lit(75u64))]),
+        filters(vec![col("a").gt_eq(lit(33u64)), col("a").lt(lit(56u64))]),
+        filters(vec![col("a").lt(lit(5u64)), col("a").gt(lit(90u64))]),
+        Some(top_k),
+    ];
+
+    // the explanation admits the same blocks as the pruning
+    for push_down in &push_downs {
+        let pruner = BlockPruner::new(snapshot.clone()).with_operator(operator.clone());
+        let explanation = pruner
+            .explain(schema.clone(), push_down, ctx.as_ref())
+            .await?;
+        let blocks = pruner
+            .apply(schema.clone(), push_down, ctx.as_ref())
+            .await?;
+
+        let mut expected = blocks.iter().map(|b| &b.location).collect::<Vec<_>>();
+        let mut admitted = explanation.admitted_blocks();
+        expected.sort();
+        admitted.sort();
+        assert_eq!(expected, admitted, "{:?}", push_down);
+    }
+
+    // the decisions of the segments and the blocks
+    let explanation = BlockPruner::new(snapshot.clone())
+        .with_operator(operator.clone())
+        .explain(schema.clone(), &push_downs[1], ctx.as_ref())
+        .await?;
+    assert_eq!(10, explanation.segments.len());
+    assert_eq!(7, explanation.segments_pruned());
+    let pruned_by = PruneDecision::PrunedByFilter(format!("{:?}", col("a").gt_eq(lit(75u64))));
+    assert_eq!(pruned_by, explanation.segments[0].decision);
+    assert_eq!(
+        PruneDecision::Skipped,
+        explanation.segments[0].blocks[0].decision
+    );
+    // the segment of the values 70 .. 79 is admitted, its block of 70 .. 74 is pruned
+    let segment = &explanation.segments[7];
+    assert_eq!(PruneDecision::Admitted, segment.decision);
+    assert_eq!(pruned_by, segment.blocks[0].decision);
+    assert_eq!(PruneDecision::Admitted, segment.blocks[1].decision);
+
+    // the top-k prunes the blocks of less values
+    let explanation = BlockPruner::new(snapshot.clone())
+        .with_operator(operator.clone())
+        .explain(schema.clone(), &push_downs[4], ctx.as_ref())
+        .await?;
+    assert_eq!(3, explanation.admitted_blocks().len());
+    assert!(matches!(
+        explanation.segments[0].blocks[0].decision,
+        PruneDecision::PrunedByTopK(_)
+    ));
+    assert!(format!("{}", explanation).contains("pruned by top-k with threshold 85"));
+
+    Ok(())
+}
+
+// merges the segments of the snapshot into one
+async fn merge_segments(operator: &Operator, snapshot: &TableSnapshot) -> Result<SegmentInfo> {
+    let mut blocks = vec![];
+    let mut summary = Statistics::default();
+    for (location, _) in &snapshot.segments {
+        let bytes = operator.object(location).read().await?;
+        let segment_info: SegmentInfo = serde_json::from_slice(&bytes)?;
+        summary = merge_statistics(&snapshot.schema, &summary, &segment_info.summary)?;
+        blocks.extend(segment_info.blocks);
+    }
+    Ok(SegmentInfo::new(blocks, summary))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_block_pruner_filter_blocks_in_parallel() -> Result<()> {
+    // a segment of many blocks, the column of block i ranges from i % 100 to i % 100 + 10
+    let num_blocks = BlockPruner::PARALLEL_FILTER_MIN_BLOCKS * 2 + 7;
+    let blocks = (0..num_blocks)
+        .map(|i| {
+            let mut col_stats = HashMap::new();
+            col_stats.insert(0, ColumnStatistics {
+                min: DataValue::UInt64((i % 100) as u64),
+                max: DataValue::UInt64((i % 100) as u64 + 10),
+                null_count: 0,
+                in_memory_size: 0,
+                min_max_skipped: false,
+                histogram: None,
+            });
+            BlockMeta {
+                row_count: 10,
+                block_size: 0,
+                file_size: 0,
+                col_stats,
+                col_metas: HashMap::new(),
+                location: (format!("_b/{}.parquet", i), 0),
+                compression: Compression::Lz4Raw,
+                external: false,
+            }
+        })
+        .collect();
+    let segment_info = Arc::new(SegmentInfo::new(blocks, Default::default()));
+
+    let pred: Arc<Pred> = Arc::new(Box::new(|stats: &BlockStatistics, _: u64| {
+        Ok(stats[&0].max.as_u64()? < 20)
+    }));
+
+    let handle = tokio::runtime::Handle::current();
+    let serial = BlockPruner::filter_blocks(&handle, segment_info.clone(), pred.clone(), 1).await?;
+    let expected = (0..num_blocks).map(|i| i % 100 < 10).collect::<Vec<_>>();
+    assert_eq!(expected, serial);
+
+    // the ranges evaluated in parallel are in the order of the blocks
+    for parallelism in [2, 3, 8, num_blocks, num_blocks * 2] {
+        let parallel =
+            BlockPruner::filter_blocks(&handle, segment_info.clone(), pred.clone(), parallelism)
+                .await?;
+        assert_eq!(serial, parallel, "parallelism {}", parallelism);
+    }
+
+    // the errors of the predicate are returned
+    let failing: Arc<Pred> = Arc::new(Box::new(|_: &BlockStatistics, _: u64| {
+        Err(ErrorCode::LogicalError("failed to evaluate"))
+    }));
+    let err = BlockPruner::filter_blocks(&handle, segment_info, failing, 4)
+        .await
+        .unwrap_err();
+    assert_eq!(ErrorCode::LogicalError("").code(), err.code());
+    Ok(())
+}
@@ -17,6 +17,7 @@ use std::collections::HashMap;
 
 use common_datablocks::DataBlock;
 use common_datavalues::prelude::*;
+use databend_query::storages::fuse::meta::Compression;
 use databend_query::storages::fuse::statistics::accumulator;
 use databend_query::storages::fuse::statistics::reducers;
 use databend_query::storages::fuse::statistics::StatisticsAccumulator;
@@ -64,9 +65,95 @@ fn test_ft_stats_accumulator() -> common_exception::Result<()> {
     let test_file_size = 1;
     for item in blocks {
         let block_acc = stats_acc.begin(&item?)?;
-        stats_acc = block_acc.end(test_file_size, "".to_owned(), HashMap::new());
+        stats_acc = block_acc.end(
+            test_file_size,
+            "".to_owned(),
+            HashMap::new(),
+            Compression::Lz4Raw,
+        );
     }
     assert_eq!(10, stats_acc.blocks_statistics.len());
     // TODO more cases here pls
     Ok(())
 }
+
+#[test]
+fn test_ft_stats_block_stats_unorderable() -> common_exception::Result<()> {
+    let struct_type: DataTypePtr =
+        std::sync::Arc::new(StructType::create(vec!["x".to_owned()], vec![
+            i32::to_data_type(),
+        ]));
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("a", i32::to_data_type()),
+        DataField::new("s", struct_type.clone()),
+    ]);
+    let s = StructColumn::from_data(vec![Series::from_data(vec![1, 2, 3])], struct_type);
+    let block = DataBlock::create(schema.clone(), vec![
+        Series::from_data(vec![1, 2, 3]),
+        std::sync::Arc::new(s),
+    ]);
+    let r = StatisticsAccumulator::acc_columns(&block)?;
+    assert_eq!(2, r.len());
+    let col_stats = r.get(&0).unwrap();
+    assert!(!col_stats.min_max_skipped);
+    assert_eq!(col_stats.max, DataValue::Int64(3));
+    let col_stats = r.get(&1).unwrap();
+    assert!(col_stats.min_max_skipped);
+    assert_eq!(col_stats.min, DataValue::Null);
+    assert_eq!(col_stats.max, DataValue::Null);
+
+    let r = reducers::reduce_block_stats(&[r.clone(), r], &schema)?;
+    assert!(r.get(&1).unwrap().min_max_skipped);
+    assert!(!r.get(&0).unwrap().min_max_skipped);
+    Ok(())
+}
+
+#[test]
+fn test_ft_stats_histogram_reduce() -> common_exception::Result<()> {
+    let num_of_blocks = 10;
+    let rows_per_block = 1000;
+    let total = num_of_blocks * rows_per_block;
+    let num_buckets = 16;
+
+    // a skewed dataset, most of the values are small
+    let value_of = |k: usize| ((k as f64 / total as f64).powi(3) * 10000.0) as i64;
+    let mut values = (0..total).map(value_of).collect::<Vec<_>>();
+
+    // each block gets a slice of the whole range of values
+    let schema = DataSchemaRefExt::create(vec![DataField::new("a", i64::to_data_type())]);
+    let col_stats = (0..num_of_blocks)
+        .map(|b| {
+            let column = (0..rows_per_block)
+                .map(|i| value_of(i * num_of_blocks + b))
+                .collect::<Vec<_>>();
+            let block = DataBlock::create(schema.clone(), vec![Series::from_data(column)]);
+            StatisticsAccumulator::acc_columns_with_histogram(&block, num_buckets)
+        })
+        .collect::<common_exception::Result<Vec<_>>>()?;
+    let r = reducers::reduce_block_stats(&col_stats, &schema)?;
+    let histogram = r.get(&0).unwrap().histogram.as_ref().unwrap();
+    assert_eq!(total as u64, histogram.rows());
+    assert!(histogram.buckets.len() <= num_buckets);
+
+    // the estimations are close to the true distribution
+    values.sort_unstable();
+    for quantile in [0.1, 0.25, 0.5, 0.75, 0.9] {
+        let x = values[(total as f64 * quantile) as usize];
+        let actual = values.iter().filter(|v| **v <= x).count() as f64;
+        let estimated = histogram.cdf(x as f64);
+        assert!(
+            (estimated - actual).abs() / (total as f64) < 0.05,
+            "quantile {}, value {}, estimated {}, actual {}",
+            quantile,
+            x,
+            estimated,
+            actual
+        );
+    }
+
+    // not collected, unless required
+    let block = DataBlock::create(schema, vec![Series::from_data(values)]);
+    let r = StatisticsAccumulator::acc_columns(&block)?;
+    assert!(r.get(&0).unwrap().histogram.is_none());
+    Ok(())
+}
@@ -14,8 +14,18 @@
 //
 
 use std::default::Default;
-
+use std::io::Read;
+use std::io::Seek;
+
+use common_arrow::arrow::io::parquet::read::read_metadata;
+use common_arrow::arrow::io::parquet::write::Version;
+use common_arrow::parquet::compression::Compression as ParquetCompression;
+use common_arrow::parquet::encoding::Encoding as ParquetEncoding;
+use common_arrow::parquet::page::CompressedDataPage;
+use common_arrow::parquet::page::DataPageHeader;
+use common_arrow::parquet::read::get_page_iterator;
 use common_base::tokio;
+use common_exception::ErrorCode;
 use common_exception::Result;
 use common_meta_types::TableInfo;
 use common_planners::ReadDataSourcePlan;
@@ -25,10 +35,30 @@ use databend_query::interpreters::CreateTableInterpreter;
 use databend_query::interpreters::InterpreterFactory;
 use databend_query::sql::PlanParser;
 use databend_query::sql::OPT_KEY_DATABASE_ID;
+use databend_query::sql::OPT_KEY_LOCATION_LAYOUT;
+use databend_query::sql::OPT_KEY_SNAPSHOT_LOCATION;
+use databend_query::storages::fuse::io::BlockWriteOptions;
+use databend_query::storages::fuse::io::LocationLayout;
+use databend_query::storages::fuse::io::MetaReaders;
+use databend_query::storages::fuse::io::WrittenLocations;
+use databend_query::storages::fuse::meta::Compression;
 use databend_query::storages::fuse::FuseTable;
+use databend_query::storages::fuse::FUSE_OPT_KEY_BLOCK_PER_SEGMENT;
+use databend_query::storages::fuse::FUSE_OPT_KEY_PARQUET_COMPATIBLE;
+use databend_query::storages::fuse::FUSE_OPT_KEY_PARQUET_VERSION;
+use databend_query::storages::fuse::FUSE_OPT_KEY_ROW_PER_BLOCK;
+use databend_query::storages::fuse::FUSE_TBL_BLOCK_PREFIX;
+use databend_query::storages::fuse::FUSE_TBL_SEGMENT_PREFIX;
+use databend_query::storages::fuse::FUSE_TBL_SNAPSHOT_PREFIX;
 use databend_query::storages::ToReadDataSourcePlan;
 use futures::TryStreamExt;
+use tempfile::TempDir;
+use walkdir::WalkDir;
 
+use crate::storages::fuse::table_test_fixture::append_sample_data;
+use crate::storages::fuse::table_test_fixture::execute_command;
+use crate::storages::fuse::table_test_fixture::execute_query;
+use crate::storages::fuse::table_test_fixture::expects_ok;
 use crate::storages::fuse::table_test_fixture::TestFixture;
 
 #[tokio::test]
@@ -292,3 +322,641 @@ fn test_parse_storage_prefix() -> Result<()> {
     assert_eq!(format!("{}/{}", db_id, tbl_id), prefix);
     Ok(())
 }
+
+#[test]
+fn test_parse_location_layout() -> Result<()> {
+    let mut tbl_info = TableInfo::default();
+
+    // tables created without the option keep the legacy layout
+    let layout = FuseTable::parse_location_layout(&tbl_info)?;
+    assert_eq!(LocationLayout::Flat, layout);
+
+    tbl_info.meta.options.insert(
+        OPT_KEY_LOCATION_LAYOUT.to_owned(),
+        LocationLayout::Dated.as_str().to_owned(),
+    );
+    let layout = FuseTable::parse_location_layout(&tbl_info)?;
+    assert_eq!(LocationLayout::Dated, layout);
+
+    tbl_info
+        .meta
+        .options
+        .insert(OPT_KEY_LOCATION_LAYOUT.to_owned(), "unknown".to_owned());
+    assert!(FuseTable::parse_location_layout(&tbl_info).is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fuse_table_dated_location_layout() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let db = fixture.default_db_name();
+    let ctx = fixture.ctx();
+
+    // the default table is created without the layout option, i.e. the legacy flat layout
+    fixture.create_default_table().await?;
+    append_sample_data(1, &fixture).await?;
+
+    // tables created by DDL use the dated layout, two insertions, two snapshots
+    let qry = format!("create table {}.dated(id int)", db);
+    execute_command(ctx.clone(), qry.as_str()).await?;
+    let qry = format!("insert into {}.dated values(1)", db);
+    execute_command(ctx.clone(), qry.as_str()).await?;
+    let qry = format!("insert into {}.dated values(2)", db);
+    execute_command(ctx.clone(), qry.as_str()).await?;
+
+    // new writes land under the dated prefixes, i.e. `<kind>/<yyyy>/<mm>/<dd>/<file>`,
+    // the dates themselves are checked by `test_meta_locations_dated_layout`
+    let is_date = |parts: &[&str]| {
+        parts
+            .iter()
+            .zip([4, 2, 2])
+            .all(|(p, len)| p.len() == len && p.chars().all(|c| c.is_ascii_digit()))
+    };
+    let kinds = [
+        FUSE_TBL_BLOCK_PREFIX,
+        FUSE_TBL_SEGMENT_PREFIX,
+        FUSE_TBL_SNAPSHOT_PREFIX,
+    ];
+    let (mut dated, mut flat) = (0, 0);
+    let data_path = ctx.get_config().storage.fs.data_path;
+    for entry in WalkDir::new(data_path) {
+        let entry = entry.unwrap();
+        if entry.file_type().is_file() {
+            let path = entry.path().to_str().unwrap().to_owned();
+            let parts = path.split('/').collect::<Vec<_>>();
+            match parts.iter().position(|p| kinds.contains(p)) {
+                Some(i) if parts.len() == i + 5 && is_date(&parts[i + 1..i + 4]) => dated += 1,
+                Some(i) if parts.len() == i + 2 => flat += 1,
+                _ => continue,
+            }
+        }
+    }
+    // two blocks, two segments and two snapshots of the dated table,
+    // one of each of the flat table
+    assert_eq!(6, dated);
+    assert_eq!(3, flat);
+
+    // the history of the dated table is walked by the locations kept in the snapshots
+    let expected = vec![
+        "+-------+",
+        "| count |",
+        "+-------+",
+        "| 2     |",
+        "+-------+",
+    ];
+    let qry = format!(
+        "select count(*) as count from fuse_history('{}', 'dated')",
+        db
+    );
+    expects_ok(
+        "dated_layout_history",
+        execute_query(ctx.clone(), qry.as_str()).await,
+        expected,
+    )
+    .await?;
+
+    // tables of both layouts are readable
+    let expected = vec![
+        "+-------+",
+        "| count |",
+        "+-------+",
+        "| 3     |",
+        "+-------+",
+    ];
+    let qry = format!(
+        "select count(*) as count from '{}'.'{}'",
+        db,
+        fixture.default_table_name()
+    );
+    expects_ok(
+        "flat_layout_readable",
+        execute_query(ctx.clone(), qry.as_str()).await,
+        expected,
+    )
+    .await?;
+
+    let expected = vec![
+        "+-------+",
+        "| count |",
+        "+-------+",
+        "| 2     |",
+        "+-------+",
+    ];
+    let qry = format!("select count(*) as count from {}.dated", db);
+    expects_ok(
+        "dated_layout_readable",
+        execute_query(ctx, qry.as_str()).await,
+        expected,
+    )
+    .await
+}
+
+#[test]
+fn test_parse_block_write_options() -> Result<()> {
+    let mut tbl_info = TableInfo::default();
+
+    let options = FuseTable::parse_block_write_options(&tbl_info)?;
+    assert_eq!(BlockWriteOptions::default(), options);
+
+    let opts = &mut tbl_info.meta.options;
+    opts.insert(FUSE_OPT_KEY_PARQUET_VERSION.to_owned(), "1".to_owned());
+    opts.insert(
+        FUSE_OPT_KEY_PARQUET_COMPATIBLE.to_owned(),
+        "true".to_owned(),
+    );
+    let options = FuseTable::parse_block_write_options(&tbl_info)?;
+    assert_eq!(Version::V1, options.version);
+    assert!(options.compatible);
+
+    let opts = &mut tbl_info.meta.options;
+    opts.insert(FUSE_OPT_KEY_PARQUET_VERSION.to_owned(), "3".to_owned());
+    assert!(FuseTable::parse_block_write_options(&tbl_info).is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fuse_table_parquet_versions() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let db = fixture.default_db_name();
+    let ctx = fixture.ctx();
+
+    let cases = [
+        ("v1", "parquet_version = '1' parquet_compatible = 'true'"),
+        ("v2", "parquet_version = '2'"),
+        (
+            "v2_compatible",
+            "parquet_version = '2' parquet_compatible = 'true'",
+        ),
+    ];
+    for (tbl, options) in cases {
+        let qry = format!(
+            "create table {}.{}(id int) ENGINE = Fuse {}",
+            db, tbl, options
+        );
+        execute_command(ctx.clone(), qry.as_str()).await?;
+        let qry = format!("insert into {}.{} values(1),(2),(3)", db, tbl);
+        execute_command(ctx.clone(), qry.as_str()).await?;
+
+        // blocks of either version are readable
+        let expected = vec![
+            "+-----+", //
+            "| sum |", "+-----+", "| 6   |", "+-----+",
+        ];
+        let qry = format!("select sum(id) as sum from {}.{}", db, tbl);
+        expects_ok(
+            tbl,
+            execute_query(ctx.clone(), qry.as_str()).await,
+            expected,
+        )
+        .await?;
+    }
+
+    // the V1 file could be parsed by a plain parquet reader, with the compatible codec
+    let table = ctx.get_table(&db, "v1").await?;
+    let snapshot_loc = table
+        .get_table_info()
+        .options()
+        .get(OPT_KEY_SNAPSHOT_LOCATION)
+        .unwrap();
+    let snapshot = MetaReaders::table_snapshot_reader(ctx.as_ref())
+        .read(snapshot_loc.as_str(), None, 1)
+        .await?;
+    let (seg_loc, seg_ver) = &snapshot.segments[0];
+    let segment = MetaReaders::segment_info_reader(ctx.as_ref())
+        .read(seg_loc, None, *seg_ver)
+        .await?;
+    let block_meta = &segment.blocks[0];
+    assert_eq!(Compression::Snappy, block_meta.compression);
+
+    let bytes = ctx
+        .get_storage_operator()?
+        .object(&block_meta.location.0)
+        .read()
+        .await?;
+    let file_meta = read_metadata(&mut std::io::Cursor::new(bytes))?;
+    assert_eq!(3, file_meta.num_rows);
+    let column = &file_meta.row_groups[0].columns()[0];
+    assert_eq!(ParquetCompression::Snappy, column.compression());
+
+    // the compatible file sticks to what an old writer (the fixture, by impala 1.3) produces,
+    // even if the V2 is asked: V1 data pages, no delta encodings, and no LZ4_RAW codec
+    let mut old_file = std::fs::File::open("../tests/data/alltypes_plain.parquet")?;
+    let old_meta = read_metadata(&mut old_file)?;
+    assert!(old_meta
+        .created_by
+        .as_ref()
+        .map_or(false, |v| v.starts_with("impala")));
+    let old_pages = data_pages(&mut old_file)?;
+    let mut old_encodings = vec![ParquetEncoding::Plain, ParquetEncoding::Rle];
+    for page in &old_pages {
+        match page.header() {
+            DataPageHeader::V1(header) => old_encodings.push(page_encoding(header.encoding)?),
+            DataPageHeader::V2(_) => panic!("the fixture is expected to have V1 pages only"),
+        }
+    }
+
+    let table = ctx.get_table(&db, "v2_compatible").await?;
+    let snapshot_loc = table
+        .get_table_info()
+        .options()
+        .get(OPT_KEY_SNAPSHOT_LOCATION)
+        .unwrap();
+    let snapshot = MetaReaders::table_snapshot_reader(ctx.as_ref())
+        .read(snapshot_loc.as_str(), None, 1)
+        .await?;
+    let (seg_loc, seg_ver) = &snapshot.segments[0];
+    let segment = MetaReaders::segment_info_reader(ctx.as_ref())
+        .read(seg_loc, None, *seg_ver)
+        .await?;
+    let bytes = ctx
+        .get_storage_operator()?
+        .object(&segment.blocks[0].location.0)
+        .read()
+        .await?;
+    let pages = data_pages(&mut std::io::Cursor::new(bytes))?;
+    assert!(!pages.is_empty());
+    for page in &pages {
+        match page.header() {
+            DataPageHeader::V1(header) => {
+                assert!(old_encodings.contains(&page_encoding(header.encoding)?))
+            }
+            DataPageHeader::V2(_) => panic!("V2 page written in the compatible mode"),
+        }
+        assert!(matches!(
+            page.compression(),
+            ParquetCompression::Uncompressed
+                | ParquetCompression::Snappy
+                | ParquetCompression::Gzip
+        ));
+    }
+    Ok(())
+}
+
+fn data_pages<R: Read + Seek>(reader: &mut R) -> Result<Vec<CompressedDataPage>> {
+    let file_meta = read_metadata(reader)?;
+    let mut pages = vec![];
+    for row_group in &file_meta.row_groups {
+        for column in row_group.columns() {
+            let iter = get_page_iterator(column, &mut *reader, None, vec![])
+                .map_err(|e| ErrorCode::ParquetError(e.to_string()))?;
+            for page in iter {
+                pages.push(page.map_err(|e| ErrorCode::ParquetError(e.to_string()))?);
+            }
+        }
+    }
+    Ok(pages)
+}
+
+fn page_encoding<T>(encoding: T) -> Result<ParquetEncoding>
+where
+    ParquetEncoding: TryFrom<T>,
+    <ParquetEncoding as TryFrom<T>>::Error: ToString,
+{
+    ParquetEncoding::try_from(encoding).map_err(|e| ErrorCode::ParquetError(e.to_string()))
+}
+
+#[tokio::test]
+async fn test_fuse_table_storage_location() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let db = fixture.default_db_name();
+    let ctx = fixture.ctx();
+
+    // the locations must be under the fs data path
+    let data_path = ctx.get_config().storage.fs.data_path;
+    let dirs = [
+        TempDir::new_in(&data_path).unwrap(),
+        TempDir::new_in(&data_path).unwrap(),
+    ];
+    for (i, dir) in dirs.iter().enumerate() {
+        let qry = format!(
+            "create table {}.t{}(id int) ENGINE = Fuse storage_location = 'fs://{}'",
+            db,
+            i,
+            dir.path().display()
+        );
+        execute_command(ctx.clone(), qry.as_str()).await?;
+        let rows = (0..=i).map(|v| format!("({})", v)).collect::<Vec<_>>();
+        let qry = format!("insert into {}.t{} values{}", db, i, rows.join(","));
+        execute_command(ctx.clone(), qry.as_str()).await?;
+    }
+
+    for (i, dir) in dirs.iter().enumerate() {
+        // the objects of each table land in its own storage
+        let table = ctx.get_table(&db, &format!("t{}", i)).await?;
+        let prefix = FuseTable::parse_storage_prefix(table.get_table_info())?;
+        let blocks = WalkDir::new(dir.path().join(&prefix).join(FUSE_TBL_BLOCK_PREFIX))
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .count();
+        assert_eq!(1, blocks);
+
+        let count = format!("| {:<3} |", i + 1);
+        let expected = vec!["+-----+", "| cnt |", "+-----+", count.as_str(), "+-----+"];
+        let qry = format!("select count(*) as cnt from {}.t{}", db, i);
+        expects_ok(
+            "read from storage location",
+            execute_query(ctx.clone(), qry.as_str()).await,
+            expected,
+        )
+        .await?;
+    }
+
+    // invalid locations are rejected while creating the table
+    let outside = TempDir::new().unwrap();
+    let link = dirs[0].path().join("link_to_outside");
+    std::os::unix::fs::symlink(outside.path(), &link).unwrap();
+    for (location, options) in [
+        ("fs://relative/path".to_string(), ""),
+        ("unknown:///path".to_string(), ""),
+        ("/no/scheme".to_string(), ""),
+        (format!("fs://{}", outside.path().display()), ""),
+        (format!("fs://{}/../escaped", data_path), ""),
+        // the links are resolved before the check
+        (format!("fs://{}", link.display()), ""),
+        (format!("fs://{}/not_yet_created", link.display()), ""),
+        // s3 locations without a known stage of their credential
+        ("s3://bucket/root".to_string(), ""),
+        (
+            "s3://bucket/root".to_string(),
+            "storage_s3_credential_stage = 'unknown_stage'",
+        ),
+    ] {
+        let qry = format!(
+            "create table {}.t_invalid(id int) ENGINE = Fuse storage_location = '{}' {}",
+            db, location, options
+        );
+        assert!(execute_command(ctx.clone(), qry.as_str()).await.is_err());
+    }
+    assert!(ctx.get_table(&db, "t_invalid").await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fuse_table_histogram_option() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let db = fixture.default_db_name();
+    let ctx = fixture.ctx();
+
+    let cases = [
+        ("t_hist", "histogram_buckets = '8'", true),
+        ("t_no_hist", "", false),
+    ];
+    for (tbl, options, collected) in cases {
+        let qry = format!(
+            "create table {}.{}(id int, name varchar) ENGINE = Fuse {}",
+            db, tbl, options
+        );
+        execute_command(ctx.clone(), qry.as_str()).await?;
+        let qry = format!(
+            "insert into {}.{} values(1, 'a'),(2, 'b'),(3, 'c'),(40, 'd')",
+            db, tbl
+        );
+        execute_command(ctx.clone(), qry.as_str()).await?;
+
+        let table = ctx.get_table(&db, tbl).await?;
+        let stats = table.statistics(ctx.clone()).await?.unwrap();
+        // histograms are kept for the numeric columns only
+        assert_eq!(!collected, stats.column_histograms.is_empty());
+        assert!(!stats.column_histograms.contains_key(&1));
+        if collected {
+            assert_eq!(4, stats.column_histograms[&0].rows());
+            let selectivity = stats.selectivity(0, 0.0, 3.0).unwrap();
+            assert!(selectivity > 0.5 && selectivity <= 0.75);
+        } else {
+            assert!(stats.selectivity(0, 0.0, 3.0).is_none());
+        }
+    }
+
+    let qry = format!(
+        "create table {}.t_bad(id int) ENGINE = Fuse histogram_buckets = 'many'",
+        db
+    );
+    execute_command(ctx.clone(), qry.as_str()).await?;
+    let qry = format!("insert into {}.t_bad values(1)", db);
+    assert!(execute_command(ctx.clone(), qry.as_str()).await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fuse_table_insert_limit() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let ctx = fixture.ctx();
+    fixture.create_default_table().await?;
+
+    let settings = ctx.get_settings();
+    settings.set_settings("max_rows_per_insert".to_string(), "5".to_string(), false)?;
+
+    // 3 blocks of 3 rows exceed the limit, the insertion fails
+    {
+        let table = fixture.latest_default_table().await?;
+        let stream = TestFixture::gen_sample_blocks_stream_ex(3, 3, 1);
+        let r = table.append_data(ctx.clone(), stream).await?;
+        let res = r.try_collect::<Vec<_>>().await;
+        assert!(res.is_err());
+        assert_eq!(
+            res.unwrap_err().code(),
+            ErrorCode::InsertLimitExceeded("").code()
+        );
+    }
+
+    // in truncate mode, the rows within the limit are kept
+    settings.set_settings("insert_limit_truncate".to_string(), "1".to_string(), false)?;
+    {
+        let table = fixture.latest_default_table().await?;
+        let stream = TestFixture::gen_sample_blocks_stream_ex(3, 3, 1);
+        let r = table.append_data(ctx.clone(), stream).await?;
+        table
+            .commit_insertion(ctx.clone(), r.try_collect().await?, false)
+            .await?;
+
+        let table = fixture.latest_default_table().await?;
+        let (stats, _) = table.read_partitions(ctx.clone(), None).await?;
+        assert_eq!(stats.read_rows, 5);
+    }
+
+    // an insertion within the limit is not affected
+    settings.set_settings("insert_limit_truncate".to_string(), "0".to_string(), false)?;
+    {
+        let table = fixture.latest_default_table().await?;
+        let stream = TestFixture::gen_sample_blocks_stream_ex(1, 5, 1);
+        let r = table.append_data(ctx.clone(), stream).await?;
+        table
+            .commit_insertion(ctx.clone(), r.try_collect().await?, false)
+            .await?;
+
+        let table = fixture.latest_default_table().await?;
+        let (stats, _) = table.read_partitions(ctx.clone(), None).await?;
+        assert_eq!(stats.read_rows, 10);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fuse_table_read_in_order() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let ctx = fixture.ctx();
+    fixture.create_default_table().await?;
+
+    // 8 segments, of 2 blocks each
+    for start in 0..8 {
+        let table = fixture.latest_default_table().await?;
+        let stream = TestFixture::gen_sample_blocks_stream_ex(2, 3, start * 2);
+        let r = table.append_data(ctx.clone(), stream).await?;
+        table
+            .commit_insertion(ctx.clone(), r.try_collect().await?, false)
+            .await?;
+    }
+
+    let settings = ctx.get_settings();
+    let qry = format!(
+        "select * from {}.{} limit 100",
+        fixture.default_db_name(),
+        fixture.default_table_name()
+    );
+    let mut results = vec![];
+    for max_threads in ["1", "8", "8"] {
+        settings.set_settings("max_threads".to_string(), max_threads.to_string(), false)?;
+        let blocks = execute_query(ctx.clone(), qry.as_str())
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        results.push(common_datablocks::pretty_format_blocks(&blocks)?);
+    }
+
+    // the scans return the rows in the same order, no matter how many threads are used
+    assert_eq!(results[0], results[1]);
+    assert_eq!(results[1], results[2]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fuse_table_insert_select() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let db = fixture.default_db_name();
+    let ctx = fixture.ctx();
+
+    for qry in [
+        format!("create table {}.src(id int, d Date) ENGINE = Fuse", db),
+        format!("create table {}.same(id int, d Date) ENGINE = Fuse", db),
+        format!(
+            "create table {}.wider(id bigint, d String) ENGINE = Fuse",
+            db
+        ),
+        format!("create table {}.variant(v Variant) ENGINE = Fuse", db),
+        format!(
+            "insert into {}.src values(1, '2022-01-01'),(2, '2022-01-02'),(3, '2022-01-03')",
+            db
+        ),
+    ] {
+        execute_command(ctx.clone(), qry.as_str()).await?;
+    }
+
+    // matching schemas, the written rows are reported
+    let written_rows = ctx.get_write_progress_value().rows;
+    let qry = format!("insert into {}.same select * from {}.src", db, db);
+    execute_command(ctx.clone(), qry.as_str()).await?;
+    assert_eq!(ctx.get_write_progress_value().rows - written_rows, 3);
+
+    let expected = vec![
+        "+----+------------+",
+        "| id | d          |",
+        "+----+------------+",
+        "| 1  | 2022-01-01 |",
+        "| 2  | 2022-01-02 |",
+        "| 3  | 2022-01-03 |",
+        "+----+------------+",
+    ];
+    let qry = format!("select * from {}.same", db);
+    expects_ok(
+        "same",
+        execute_query(ctx.clone(), qry.as_str()).await,
+        expected,
+    )
+    .await?;
+
+    // the columns are casted into the table schema
+    let qry = format!("insert into {}.wider select * from {}.src", db, db);
+    execute_command(ctx.clone(), qry.as_str()).await?;
+
+    let expected = vec![
+        "+----+------------+",
+        "| id | d          |",
+        "+----+------------+",
+        "| 1  | 2022-01-01 |",
+        "| 2  | 2022-01-02 |",
+        "| 3  | 2022-01-03 |",
+        "+----+------------+",
+    ];
+    let qry = format!("select * from {}.wider", db);
+    expects_ok(
+        "wider",
+        execute_query(ctx.clone(), qry.as_str()).await,
+        expected,
+    )
+    .await?;
+
+    // dates can't be casted into variants, rejected while planning
+    let qry = format!("insert into {}.variant select d from {}.src", db, db);
+    let result = PlanParser::parse(ctx.clone(), qry.as_str()).await;
+    assert_eq!(
+        result.err().map(|e| e.code()),
+        Some(ErrorCode::BadDataValueType("").code())
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fuse_table_failed_append_reports_written() -> Result<()> {
+    let fixture = TestFixture::new().await;
+    let ctx = fixture.ctx();
+
+    // one block of 3 rows per segment
+    let mut create_table_plan = fixture.default_crate_table_plan();
+    let options = &mut create_table_plan.table_meta.options;
+    options.insert(FUSE_OPT_KEY_ROW_PER_BLOCK.to_owned(), "3".to_owned());
+    options.insert(FUSE_OPT_KEY_BLOCK_PER_SEGMENT.to_owned(), "1".to_owned());
+    let interpreter = CreateTableInterpreter::try_create(ctx.clone(), create_table_plan)?;
+    interpreter.execute(None).await?;
+
+    // the first block is written, the second one exceeds the limit
+    let settings = ctx.get_settings();
+    settings.set_settings("max_rows_per_insert".to_string(), "5".to_string(), false)?;
+
+    let table = fixture.latest_default_table().await?;
+    let fuse_table = FuseTable::try_from_table(table.as_ref())?;
+    let written = WrittenLocations::default();
+    let stream = TestFixture::gen_sample_blocks_stream_ex(3, 3, 1);
+    let res = fuse_table
+        .append_trunks(ctx.clone(), stream, written.clone())
+        .await?
+        .try_collect::<Vec<_>>()
+        .await;
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::InsertLimitExceeded("").code()
+    );
+
+    // the block and the segment written before the failure are reported
+    let locations = written.locations();
+    assert_eq!(locations.len(), 2, "{:?}", locations);
+    let under = |loc: &str, prefix: &str| loc.contains(&format!("/{}/", prefix));
+    assert!(under(&locations[0], FUSE_TBL_BLOCK_PREFIX));
+    assert!(under(&locations[1], FUSE_TBL_SEGMENT_PREFIX));
+    let operator = fuse_table.get_operator(ctx.as_ref())?;
+    for location in &locations {
+        assert!(operator.object(location).is_exist().await?);
+    }
+
+    // and nothing is committed
+    let table = fixture.latest_default_table().await?;
+    let (stats, _) = table.read_partitions(ctx.clone(), None).await?;
+    assert_eq!(stats.read_rows, 0);
+
+    Ok(())
+}
@@ -34,27 +34,66 @@ async fn test_range_filter() -> Result<()> {
         DataField::new("a", i64::to_data_type()),
         DataField::new("b", i32::to_data_type()),
         DataField::new("c", Vu8::to_data_type()),
+        DataField::new_nullable("d", i64::to_data_type()),
+        DataField::new("e", Date32Type::arc()),
     ]);
 
+    // a block of 10 rows
+    let num_rows = 10;
     let mut stats: BlockStatistics = HashMap::new();
     stats.insert(0u32, ColumnStatistics {
         min: DataValue::Int64(1),
         max: DataValue::Int64(20),
         null_count: 1,
         in_memory_size: 0,
+        min_max_skipped: false,
+        histogram: None,
     });
     stats.insert(1u32, ColumnStatistics {
         min: DataValue::Int64(3),
         max: DataValue::Int64(10),
         null_count: 0,
         in_memory_size: 0,
+        min_max_skipped: false,
+        histogram: None,
     });
     stats.insert(2u32, ColumnStatistics {
         min: DataValue::String("abc".as_bytes().to_vec()),
         max: DataValue::String("bcd".as_bytes().to_vec()),
         null_count: 0,
         in_memory_size: 0,
+        min_max_skipped: false,
+        histogram: None,
     });
+    // all the values of d are null
+    stats.insert(3u32, ColumnStatistics {
+        min: DataValue::Null,
+        max: DataValue::Null,
+        null_count: 10,
+        in_memory_size: 0,
+        min_max_skipped: false,
+        histogram: None,
+    });
+    // e in ['2021-01-01', '2021-01-31']
+    stats.insert(4u32, ColumnStatistics {
+        min: DataValue::Int64(18628),
+        max: DataValue::Int64(18658),
+        null_count: 0,
+        in_memory_size: 0,
+        min_max_skipped: false,
+        histogram: None,
+    });
+
+    let date = |v: &str| Expression::Cast {
+        expr: Box::new(lit(v.as_bytes())),
+        data_type: Date32Type::arc(),
+        pg_style: false,
+    };
+    let in_list = |negated: bool, args: Vec<Expression>| {
+        let op = if negated { "NOT_IN" } else { "IN" };
+        Expression::create_scalar_function(op, args)
+    };
+    let null = || Expression::create_literal(DataValue::Null);
 
     struct Test {
         name: &'static str,
@@ -129,6 +168,69 @@ async fn test_range_filter() -> Result<()> {
             expect: false,
             error: "",
         },
+        Test {
+            name: "c like 'abc%'",
+            expr: Expression::create_binary_expression("like", vec![
+                col("c"),
+                lit("abc%".as_bytes()),
+            ]),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "c like 'aa%'",
+            expr: Expression::create_binary_expression("like", vec![
+                col("c"),
+                lit("aa%".as_bytes()),
+            ]),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "c like 'bc%'",
+            expr: Expression::create_binary_expression("like", vec![
+                col("c"),
+                lit("bc%".as_bytes()),
+            ]),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "c like 'bcd%'",
+            expr: Expression::create_binary_expression("like", vec![
+                col("c"),
+                lit("bcd%".as_bytes()),
+            ]),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "c like 'bcde%'",
+            expr: Expression::create_binary_expression("like", vec![
+                col("c"),
+                lit("bcde%".as_bytes()),
+            ]),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "c like '%bc'",
+            expr: Expression::create_binary_expression("like", vec![
+                col("c"),
+                lit("%bc".as_bytes()),
+            ]),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "c like 0x62ff%",
+            expr: Expression::create_binary_expression("like", vec![
+                col("c"),
+                lit(vec![b'b', 255u8, b'%']),
+            ]),
+            expect: false,
+            error: "",
+        },
         Test {
             name: "c not like 'ac%'",
             expr: Expression::create_binary_expression("not like", vec![
@@ -169,13 +271,239 @@ async fn test_range_filter() -> Result<()> {
             expect: false,
             error: "",
         },
+        Test {
+            name: "a < 1 or b > 3",
+            expr: col("a").lt(lit(1)).or(col("b").gt(lit(3i32))),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "a < 1 or b > 30",
+            expr: col("a").lt(lit(1)).or(col("b").gt(lit(30i32))),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "not (a > 0)",
+            expr: not(col("a").gt(lit(0))),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "not (a < 1 or b > 30)",
+            expr: not(col("a").lt(lit(1)).or(col("b").gt(lit(30i32)))),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "not (b >= 3 and b <= 10)",
+            expr: not(col("b").gt_eq(lit(3i32)).and(col("b").lt_eq(lit(10i32)))),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "not (not (a < 1))",
+            expr: not(not(col("a").lt(lit(1)))),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "(a < 1 or b > 30) or (not (a > 10) and b = 5)",
+            expr: col("a")
+                .lt(lit(1))
+                .or(col("b").gt(lit(30i32)))
+                .or(not(col("a").gt(lit(10))).and(col("b").eq(lit(5i32)))),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "not (a is not null)",
+            expr: not(Expression::create_scalar_function("isNotNull", vec![col(
+                "a",
+            )])),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "not (b is not null)",
+            expr: not(Expression::create_scalar_function("isNotNull", vec![col(
+                "b",
+            )])),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "d is null",
+            expr: Expression::create_scalar_function("isNull", vec![col("d")]),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "d > 5",
+            expr: col("d").gt(lit(5)),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "d is null or d > 5",
+            expr: Expression::create_scalar_function("isNull", vec![col("d")])
+                .or(col("d").gt(lit(5))),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "not (d is null)",
+            expr: not(Expression::create_scalar_function("isNull", vec![col("d")])),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "b is null",
+            expr: Expression::create_scalar_function("isNull", vec![col("b")]),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "b is not null",
+            expr: Expression::create_scalar_function("isNotNull", vec![col("b")]),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "d is not null",
+            expr: Expression::create_scalar_function("isNotNull", vec![col("d")]),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "d is not null and d > 5",
+            expr: Expression::create_scalar_function("isNotNull", vec![col("d")])
+                .and(col("d").gt(lit(5))),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "a is not null and a > 5",
+            expr: Expression::create_scalar_function("isNotNull", vec![col("a")])
+                .and(col("a").gt(lit(5))),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "a is not null and a > 30",
+            expr: Expression::create_scalar_function("isNotNull", vec![col("a")])
+                .and(col("a").gt(lit(30))),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "b in (1, 2)",
+            expr: in_list(false, vec![col("b"), lit(1i32), lit(2i32)]),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "b in (1, 5)",
+            expr: in_list(false, vec![col("b"), lit(1i32), lit(5i32)]),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "b in (11, 12, null)",
+            expr: in_list(false, vec![col("b"), lit(11i32), lit(12i32), null()]),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "b in ()",
+            expr: in_list(false, vec![col("b")]),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "b not in (3)",
+            expr: in_list(true, vec![col("b"), lit(3i32)]),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "b not in (1, null)",
+            expr: in_list(true, vec![col("b"), lit(1i32), null()]),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "not (b in (1, 2))",
+            expr: not(in_list(false, vec![col("b"), lit(1i32), lit(2i32)])),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "b in (11, 12, ..., 30)",
+            expr: in_list(
+                false,
+                std::iter::once(col("b"))
+                    .chain((11..=30).map(|v| lit(v as i32)))
+                    .collect(),
+            ),
+            expect: false,
+            error: "",
+        },
+        Test {
+            // large lists are checked by their bounds only
+            name: "b in (1, 2, 20, 21, ..., 37)",
+            expr: in_list(
+                false,
+                std::iter::once(col("b"))
+                    .chain([1, 2].into_iter().chain(20..=37).map(|v| lit(v as i32)))
+                    .collect(),
+            ),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "e in ('2021-02-05', '2021-03-01')",
+            expr: in_list(false, vec![
+                col("e"),
+                date("2021-02-05"),
+                date("2021-03-01"),
+            ]),
+            expect: false,
+            error: "",
+        },
+        Test {
+            name: "e in ('2020-12-31', '2021-01-15')",
+            expr: in_list(false, vec![
+                col("e"),
+                date("2020-12-31"),
+                date("2021-01-15"),
+            ]),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "e between '2021-01-10' and '2021-01-20'",
+            expr: col("e")
+                .gt_eq(date("2021-01-10"))
+                .and(col("e").lt_eq(date("2021-01-20"))),
+            expect: true,
+            error: "",
+        },
+        Test {
+            name: "e between '2021-02-01' and '2021-02-10'",
+            expr: col("e")
+                .gt_eq(date("2021-02-01"))
+                .and(col("e").lt_eq(date("2021-02-10"))),
+            expect: false,
+            error: "",
+        },
     ];
 
     let ctx = create_query_context().await?;
     for test in tests {
         let prune = RangeFilter::try_create(&test.expr, schema.clone(), ctx.clone())?;
 
-        match prune.eval(&stats) {
+        match prune.eval(&stats, num_rows) {
             Ok(actual) => assert_eq!(test.expect, actual, "{:#?}", test.name),
             Err(e) => assert_eq!(test.error, e.to_string(), "{}", test.name),
         }
@@ -184,6 +512,247 @@ async fn test_range_filter() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_range_filter_null_bounds() -> Result<()> {
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new_nullable("a", i64::to_data_type()),
+        DataField::new_nullable("b", i64::to_data_type()),
+        DataField::new_nullable("c", i64::to_data_type()),
+    ]);
+
+    let num_rows = 10;
+    let stats_of = |min: DataValue, max: DataValue, null_count: u64| ColumnStatistics {
+        min,
+        max,
+        null_count,
+        in_memory_size: 0,
+        min_max_skipped: false,
+        histogram: None,
+    };
+    let mut stats: BlockStatistics = HashMap::new();
+    // the min of a is unknown, while a has values
+    stats.insert(0u32, stats_of(DataValue::Null, DataValue::Int64(20), 1));
+    // both bounds of b are unknown, while b has no NULLs
+    stats.insert(1u32, stats_of(DataValue::Null, DataValue::Null, 0));
+    // all the values of c are NULLs
+    stats.insert(2u32, stats_of(DataValue::Null, DataValue::Null, 10));
+
+    struct Test {
+        name: &'static str,
+        expr: Expression,
+        expect: bool,
+    }
+
+    let tests: Vec<Test> = vec![
+        Test {
+            name: "a < 5",
+            expr: col("a").lt(lit(5)),
+            expect: true,
+        },
+        Test {
+            name: "a > 30",
+            expr: col("a").gt(lit(30)),
+            expect: true,
+        },
+        Test {
+            name: "b = 3",
+            expr: col("b").eq(lit(3)),
+            expect: true,
+        },
+        Test {
+            name: "not (b != 3)",
+            expr: not(col("b").not_eq(lit(3))),
+            expect: true,
+        },
+        Test {
+            name: "b in (1, 2)",
+            expr: Expression::create_scalar_function("IN", vec![col("b"), lit(1), lit(2)]),
+            expect: true,
+        },
+        Test {
+            name: "c = 3",
+            expr: col("c").eq(lit(3)),
+            expect: false,
+        },
+        Test {
+            name: "c = 3 or b = 3",
+            expr: col("c").eq(lit(3)).or(col("b").eq(lit(3))),
+            expect: true,
+        },
+        Test {
+            name: "c is null",
+            expr: Expression::create_scalar_function("isNull", vec![col("c")]),
+            expect: true,
+        },
+    ];
+
+    let ctx = create_query_context().await?;
+    for test in tests {
+        let prune = RangeFilter::try_create(&test.expr, schema.clone(), ctx.clone())?;
+        let actual = prune.eval(&stats, num_rows)?;
+        assert_eq!(test.expect, actual, "{}", test.name);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_range_filter_monotonic_functions() -> Result<()> {
+    let schema = DataSchemaRefExt::create(vec![
+        DataField::new("a", i64::to_data_type()),
+        DataField::new("f", i64::to_data_type()),
+        DataField::new("e", Date32Type::arc()),
+        DataField::new("ts", DateTime32Type::arc(None)),
+    ]);
+
+    let num_rows = 10;
+    let mut stats: BlockStatistics = HashMap::new();
+    stats.insert(0u32, ColumnStatistics {
+        min: DataValue::Int64(1),
+        max: DataValue::Int64(20),
+        null_count: 0,
+        in_memory_size: 0,
+        min_max_skipped: false,
+        histogram: None,
+    });
+    // f spans zero
+    stats.insert(1u32, ColumnStatistics {
+        min: DataValue::Int64(-5),
+        max: DataValue::Int64(5),
+        null_count: 0,
+        in_memory_size: 0,
+        min_max_skipped: false,
+        histogram: None,
+    });
+    // e in ['2021-01-01', '2021-01-31']
+    stats.insert(2u32, ColumnStatistics {
+        min: DataValue::Int64(18628),
+        max: DataValue::Int64(18658),
+        null_count: 0,
+        in_memory_size: 0,
+        min_max_skipped: false,
+        histogram: None,
+    });
+    // ts in ['2021-01-01 10:00:00', '2021-01-01 20:00:00']
+    stats.insert(3u32, ColumnStatistics {
+        min: DataValue::UInt64(1609495200),
+        max: DataValue::UInt64(1609531200),
+        null_count: 0,
+        in_memory_size: 0,
+        min_max_skipped: false,
+        histogram: None,
+    });
+
+    let cast = |expr: Expression, data_type: DataTypePtr| Expression::Cast {
+        expr: Box::new(expr),
+        data_type,
+        pg_style: false,
+    };
+    let date16 = |v: &str| cast(lit(v.as_bytes()), Date16Type::arc());
+    let date32 = |v: &str| cast(lit(v.as_bytes()), Date32Type::arc());
+    let func = |op: &str, args: Vec<Expression>| Expression::create_scalar_function(op, args);
+
+    struct Test {
+        name: &'static str,
+        expr: Expression,
+        expect: bool,
+    }
+
+    let tests: Vec<Test> = vec![
+        Test {
+            name: "a + 3 > 30",
+            expr: add(col("a"), lit(3)).gt(lit(30)),
+            expect: false,
+        },
+        Test {
+            name: "a * 2 >= 40",
+            expr: Expression::create_binary_expression("*", vec![col("a"), lit(2)]).gt_eq(lit(40)),
+            expect: true,
+        },
+        Test {
+            name: "10 - a > 9",
+            expr: sub(lit(10), col("a")).gt(lit(9)),
+            expect: false,
+        },
+        Test {
+            name: "10 - a < -10",
+            expr: sub(lit(10), col("a")).lt(lit(-10)),
+            expect: false,
+        },
+        Test {
+            name: "10 - a < -5",
+            expr: sub(lit(10), col("a")).lt(lit(-5)),
+            expect: true,
+        },
+        Test {
+            name: "-a > 0",
+            expr: neg(col("a")).gt(lit(0)),
+            expect: false,
+        },
+        Test {
+            name: "toYYYYMM(e) = 202101",
+            expr: func("toYYYYMM", vec![col("e")]).eq(lit(202101u32)),
+            expect: true,
+        },
+        Test {
+            name: "toYYYYMM(e) = 202102",
+            expr: func("toYYYYMM", vec![col("e")]).eq(lit(202102u32)),
+            expect: false,
+        },
+        Test {
+            name: "toStartOfMonth(e) > '2021-01-01'",
+            expr: func("toStartOfMonth", vec![col("e")]).gt(date16("2021-01-01")),
+            expect: false,
+        },
+        Test {
+            name: "toDate(ts) = '2021-01-01'",
+            expr: func("toDate", vec![col("ts")]).eq(date16("2021-01-01")),
+            expect: true,
+        },
+        Test {
+            name: "toDate(ts) = '2021-01-02'",
+            expr: func("toDate", vec![col("ts")]).eq(date16("2021-01-02")),
+            expect: false,
+        },
+        Test {
+            name: "cast(ts as Date32) < '2021-01-01'",
+            expr: cast(col("ts"), Date32Type::arc()).lt(date32("2021-01-01")),
+            expect: false,
+        },
+        Test {
+            name: "cast(a as Float64) > 20.5",
+            expr: cast(col("a"), f64::to_data_type()).gt(lit(20.5f64)),
+            expect: false,
+        },
+        Test {
+            // narrowing casts may wrap around, never prune by them
+            name: "cast(a as Int8) > 100",
+            expr: cast(col("a"), i8::to_data_type()).gt(lit(100i8)),
+            expect: true,
+        },
+        Test {
+            // abs is not monotonic over a range spanning zero
+            name: "abs(f) > 5",
+            expr: func("abs", vec![col("f")]).gt(lit(5)),
+            expect: true,
+        },
+        Test {
+            name: "abs(a) > 20",
+            expr: func("abs", vec![col("a")]).gt(lit(20)),
+            expect: false,
+        },
+    ];
+
+    let ctx = create_query_context().await?;
+    for test in tests {
+        let prune = RangeFilter::try_create(&test.expr, schema.clone(), ctx.clone())?;
+        let actual = prune.eval(&stats, num_rows)?;
+        assert_eq!(test.expect, actual, "{}", test.name);
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_build_verifiable_function() -> Result<()> {
     let schema = DataSchemaRefExt::create(vec![
@@ -222,7 +791,7 @@ fn test_build_verifiable_function() -> Result<()> {
         Test {
             name: "a is not null",
             expr: Expression::create_scalar_function("isNotNull", vec![col("a")]),
-            expect: "isNotNull(min_a)",
+            expect: "(nulls_a < rows_a)",
         },
         Test {
             name: "b >= 0 and c like 0xffffff",
@@ -314,6 +883,46 @@ fn test_build_verifiable_function() -> Result<()> {
             expr: add(col("a"), col("b")).lt_eq(sub(lit(10), col("a"))),
             expect: "true",
         },
+        Test {
+            name: "not (a < 1 or b > 3)",
+            expr: not(col("a").lt(lit(1)).or(col("b").gt(lit(3)))),
+            expect: "((max_a >= 1) and (min_b <= 3))",
+        },
+        Test {
+            name: "not (a = 1)",
+            expr: not(col("a").eq(lit(1))),
+            expect: "((min_a != 1) or (max_a != 1))",
+        },
+        Test {
+            name: "not (a is null)",
+            expr: not(Expression::create_scalar_function("isNull", vec![col("a")])),
+            expect: "(nulls_a < rows_a)",
+        },
+        Test {
+            name: "not (a < 1 or a + b <= 10 - a)",
+            expr: not(col("a")
+                .lt(lit(1))
+                .or(add(col("a"), col("b")).lt_eq(sub(lit(10), col("a"))))),
+            expect: "((max_a >= 1) and true)",
+        },
+        Test {
+            name: "not (c like 'sys%')",
+            expr: not(Expression::create_binary_expression("like", vec![
+                col("c"),
+                lit("sys%".as_bytes()),
+            ])),
+            expect: "((min_c < sys) or (max_c >= syt))",
+        },
+        Test {
+            name: "a in (1, 3)",
+            expr: Expression::create_scalar_function("IN", vec![col("a"), lit(1), lit(3)]),
+            expect: "(((min_a <= 1) and (max_a >= 1)) or ((min_a <= 3) and (max_a >= 3)))",
+        },
+        Test {
+            name: "a not in (1, 3)",
+            expr: Expression::create_scalar_function("NOT_IN", vec![col("a"), lit(1), lit(3)]),
+            expect: "(((min_a != 1) or (max_a != 1)) and ((min_a != 3) or (max_a != 3)))",
+        },
         Test {
             name: "a <= b + rand()",
             expr: add(
@@ -380,6 +989,12 @@ fn test_bound_for_like_pattern() -> Result<()> {
             left: vec![],
             right: vec![],
         },
+        Test {
+            name: "trailing 0xff",
+            pattern: vec![b'a', 255u8, b'%'],
+            left: vec![b'a', 255u8],
+            right: vec![b'b'],
+        },
         Test {
             name: "right is empty",
             pattern: vec![255u8, 255, 255],
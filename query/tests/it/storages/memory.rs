@@ -78,6 +78,7 @@ async fn test_memorytable() -> Result<()> {
                         filters: vec![],
                         limit: None,
                         order_by: vec![],
+                        preserve_order: false,
                     })
                 })
                 .collect();
@@ -44,6 +44,8 @@ async fn test_configs_table() -> Result<()> {
         "| azure_storage_blob.account           |                          | storage |             |",
         "| azure_storage_blob.container         |                          | storage |             |",
         "| azure_storage_blob.master_key        |                          | storage |             |",
+        "| bootstrap_admin_password             |                          | query   |             |",
+        "| bootstrap_admin_user                 |                          | query   |             |",
         "| clickhouse_handler_host              | 127.0.0.1                | query   |             |",
         "| clickhouse_handler_port              | 9000                     | query   |             |",
         "| cluster_id                           |                          | query   |             |",
@@ -56,6 +58,8 @@ async fn test_configs_table() -> Result<()> {
         "| http_handler_tls_server_cert         |                          | query   |             |",
         "| http_handler_tls_server_key          |                          | query   |             |",
         "| http_handler_tls_server_root_ca_cert |                          | query   |             |",
+        "| jwt_audience                         |                          | query   |             |",
+        "| jwt_auto_create_user                 | false                    | query   |             |",
         "| jwt_key_file                         |                          | query   |             |",
         "| log_dir                              | ./_logs                  | log     |             |",
         "| log_level                            | INFO                     | log     |             |",
@@ -65,6 +69,7 @@ async fn test_configs_table() -> Result<()> {
         "| max_query_log_size                   | 10000                    | query   |             |",
         "| meta_address                         |                          | meta    |             |",
         "| meta_client_timeout_in_second        | 10                       | meta    |             |",
+        "| meta_compress_value_threshold        | 0                        | meta    |             |",
         "| meta_embedded_dir                    | ./_meta_embedded         | meta    |             |",
         "| meta_password                        |                          | meta    |             |",
         "| meta_username                        | root                     | meta    |             |",
@@ -89,6 +94,7 @@ async fn test_configs_table() -> Result<()> {
         "| storage_type                         | fs                       | storage |             |",
         "| table_cache_block_meta_count         | 102400                   | query   |             |",
         "| table_cache_enabled                  | false                    | query   |             |",
+        "| table_cache_prune_result_mb_size     | 64                       | query   |             |",
         "| table_cache_segment_count            | 10240                    | query   |             |",
         "| table_cache_snapshot_count           | 256                      | query   |             |",
         "| table_disk_cache_mb_size             | 1024                     | query   |             |",
@@ -98,6 +104,8 @@ async fn test_configs_table() -> Result<()> {
         "| table_engine_parquet_enabled         | false                    | query   |             |",
         "| table_memory_cache_mb_size           | 256                      | query   |             |",
         "| tenant_id                            | test                     | query   |             |",
+        "| user_audit_strict                    | false                    | query   |             |",
+        "| user_name_case_insensitive           | false                    | query   |             |",
         "| wait_timeout_mills                   | 5000                     | query   |             |",
         "+--------------------------------------+--------------------------+---------+-------------+",
     ];
@@ -110,6 +118,7 @@ async fn test_configs_table_redact() -> Result<()> {
     let mut conf = crate::tests::ConfigBuilder::create().config();
     conf.storage.s3.access_key_id = "access_key_id".to_string();
     conf.storage.s3.secret_access_key = "secret_access_key".to_string();
+    conf.query.bootstrap_admin_password = "admin_password".into();
     let ctx = crate::tests::create_query_context_with_config(conf, None).await?;
     ctx.get_settings().set_max_threads(8)?;
 
@@ -132,6 +141,8 @@ async fn test_configs_table_redact() -> Result<()> {
         "| azure_storage_blob.account           |                          | storage |             |",
         "| azure_storage_blob.container         |                          | storage |             |",
         "| azure_storage_blob.master_key        |                          | storage |             |",
+        "| bootstrap_admin_password             | ******ord                | query   |             |",
+        "| bootstrap_admin_user                 |                          | query   |             |",
         "| clickhouse_handler_host              | 127.0.0.1                | query   |             |",
         "| clickhouse_handler_port              | 9000                     | query   |             |",
         "| cluster_id                           |                          | query   |             |",
@@ -144,6 +155,8 @@ async fn test_configs_table_redact() -> Result<()> {
         "| http_handler_tls_server_cert         |                          | query   |             |",
         "| http_handler_tls_server_key          |                          | query   |             |",
         "| http_handler_tls_server_root_ca_cert |                          | query   |             |",
+        "| jwt_audience                         |                          | query   |             |",
+        "| jwt_auto_create_user                 | false                    | query   |             |",
         "| jwt_key_file                         |                          | query   |             |",
         "| log_dir                              | ./_logs                  | log     |             |",
         "| log_level                            | INFO                     | log     |             |",
@@ -153,6 +166,7 @@ async fn test_configs_table_redact() -> Result<()> {
         "| max_query_log_size                   | 10000                    | query   |             |",
         "| meta_address                         |                          | meta    |             |",
         "| meta_client_timeout_in_second        | 10                       | meta    |             |",
+        "| meta_compress_value_threshold        | 0                        | meta    |             |",
         "| meta_embedded_dir                    | ./_meta_embedded         | meta    |             |",
         "| meta_password                        |                          | meta    |             |",
         "| meta_username                        | root                     | meta    |             |",
@@ -177,6 +191,7 @@ async fn test_configs_table_redact() -> Result<()> {
         "| storage_type                         | fs                       | storage |             |",
         "| table_cache_block_meta_count         | 102400                   | query   |             |",
         "| table_cache_enabled                  | false                    | query   |             |",
+        "| table_cache_prune_result_mb_size     | 64                       | query   |             |",
         "| table_cache_segment_count            | 10240                    | query   |             |",
         "| table_cache_snapshot_count           | 256                      | query   |             |",
         "| table_disk_cache_mb_size             | 1024                     | query   |             |",
@@ -186,6 +201,8 @@ async fn test_configs_table_redact() -> Result<()> {
         "| table_engine_parquet_enabled         | false                    | query   |             |",
         "| table_memory_cache_mb_size           | 256                      | query   |             |",
         "| tenant_id                            | test                     | query   |             |",
+        "| user_audit_strict                    | false                    | query   |             |",
+        "| user_name_case_insensitive           | false                    | query   |             |",
         "| wait_timeout_mills                   | 5000                     | query   |             |",
         "+--------------------------------------+--------------------------+---------+-------------+",
     ];
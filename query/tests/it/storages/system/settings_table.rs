@@ -30,24 +30,34 @@ async fn test_settings_table() -> Result<()> {
     let result = stream.try_collect::<Vec<_>>().await?;
 
     let expected = vec![
-        "+------------------------------------+---------+---------+---------+--------------------------------------------------------------------------------------------------------------------------------------------+--------+",
-        "| name                               | value   | default | level   | description                                                                                                                                | type   |",
-        "+------------------------------------+---------+---------+---------+--------------------------------------------------------------------------------------------------------------------------------------------+--------+",
-        "|                                    |         |         |         |                                                                                                                                            |        |",
-        "| empty_as_default                   | 1       | 1       | SESSION | Format empty_as_default, default value: 1                                                                                                  | UInt64 |",
-        "| enable_new_processor_framework     | 1       | 1       | SESSION | Enable new processor framework if value != 0, default value: 1                                                                             | UInt64 |",
-        "| field_delimiter                    | ,       | ,       | SESSION | Format field delimiter, default value: ,                                                                                                   | String |",
-        "| flight_client_timeout              | 60      | 60      | SESSION | Max duration the flight client request is allowed to take in seconds. By default, it is 60 seconds                                         | UInt64 |",
-        "| max_block_size                     | 10000   | 10000   | SESSION | Maximum block size for reading                                                                                                             | UInt64 |",
-        "| max_threads                        | 2       | 16      | SESSION | The maximum number of threads to execute the request. By default, it is determined automatically.                                          | UInt64 |",
-        "| record_delimiter                   |         |         | SESSION | Format record_delimiter, default value:                                                                                                    | String |",
-        "| skip_header                        | 0       | 0       | SESSION | Whether to skip the input header, default value: 0                                                                                         | UInt64 |",
-        "| storage_occ_backoff_init_delay_ms  | 5       | 5       | SESSION | The initial retry delay in millisecond. By default, it is 5 ms.                                                                            | UInt64 |",
-        "| storage_occ_backoff_max_delay_ms   | 20000   | 20000   | SESSION | The maximum  back off delay in millisecond, once the retry interval reaches this value, it stops increasing. By default, it is 20 seconds. | UInt64 |",
-        "| storage_occ_backoff_max_elapsed_ms | 120000  | 120000  | SESSION | The maximum elapsed time after the occ starts, beyond which there will be no more retries. By default, it is 2 minutes.                    | UInt64 |",
-        "| storage_read_buffer_size           | 1048576 | 1048576 | SESSION | The size of buffer in bytes for buffered reader of dal. By default, it is 1MB.                                                             | UInt64 |",
-        "| timezone                           | UTC     | UTC     | SESSION | Timezone, default value: UTC,                                                                                                              | String |",
-        "+------------------------------------+---------+---------+---------+--------------------------------------------------------------------------------------------------------------------------------------------+--------+",
+        "+------------------------------------+-----------+-----------+---------+-----------------------------------------------------------------------------------------------------------------------------------------------+--------+",
+        "| name                               | value     | default   | level   | description                                                                                                                                   | type   |",
+        "+------------------------------------+-----------+-----------+---------+-----------------------------------------------------------------------------------------------------------------------------------------------+--------+",
+        "|                                    |           |           |         |                                                                                                                                               |        |",
+        "| empty_as_default                   | 1         | 1         | SESSION | Format empty_as_default, default value: 1                                                                                                     | UInt64 |",
+        "| enable_new_processor_framework     | 1         | 1         | SESSION | Enable new processor framework if value != 0, default value: 1                                                                                | UInt64 |",
+        "| field_delimiter                    | ,         | ,         | SESSION | Format field delimiter, default value: ,                                                                                                      | String |",
+        "| flight_client_timeout              | 60        | 60        | SESSION | Max duration the flight client request is allowed to take in seconds. By default, it is 60 seconds                                            | UInt64 |",
+        "| insert_limit_truncate              | 0         | 0         | SESSION | Truncates the insertion with a warning instead of failing it, if it exceeds max_rows_per_insert or max_bytes_per_insert. By default, it is 0. | UInt64 |",
+        "| max_block_size                     | 10000     | 10000     | SESSION | Maximum block size for reading                                                                                                                | UInt64 |",
+        "| max_block_write_concurrency        | 8         | 8         | SESSION | The maximum blocks of a query encoded and written concurrently. By default, it is 8.                                                          | UInt64 |",
+        "| max_bytes_per_insert               | 0         | 0         | SESSION | The maximum bytes that one insertion may write, 0 means unlimited. By default, it is 0.                                                       | UInt64 |",
+        "| max_memory_usage                   | 0         | 0         | SESSION | The maximum bytes of memory tracked for the tasks of a query, 0 means unlimited. By default, it is 0.                                         | UInt64 |",
+        "| max_pruning_concurrency            | 10        | 10        | SESSION | The maximum segments of a table read concurrently while pruning. By default, it is 10.                                                        | UInt64 |",
+        "| max_rows_per_insert                | 0         | 0         | SESSION | The maximum rows that one insertion may write, 0 means unlimited. By default, it is 0.                                                        | UInt64 |",
+        "| max_threads                        | 2         | 16        | SESSION | The maximum number of threads to execute the request. By default, it is determined automatically.                                             | UInt64 |",
+        "| record_delimiter                   |           |           | SESSION | Format record_delimiter, default value:                                                                                                       | String |",
+        "| skip_header                        | 0         | 0         | SESSION | Whether to skip the input header, default value: 0                                                                                            | UInt64 |",
+        "| storage_occ_backoff_init_delay_ms  | 5         | 5         | SESSION | The initial retry delay in millisecond. By default, it is 5 ms.                                                                               | UInt64 |",
+        "| storage_occ_backoff_max_delay_ms   | 20000     | 20000     | SESSION | The maximum  back off delay in millisecond, once the retry interval reaches this value, it stops increasing. By default, it is 20 seconds.    | UInt64 |",
+        "| storage_occ_backoff_max_elapsed_ms | 120000    | 120000    | SESSION | The maximum elapsed time after the occ starts, beyond which there will be no more retries. By default, it is 2 minutes.                       | UInt64 |",
+        "| storage_occ_max_retries            | 10        | 10        | SESSION | The maximum times of retries of the occ, when the table is changed concurrently. By default, it is 10.                                        | UInt64 |",
+        "| storage_read_buffer_size           | 1048576   | 1048576   | SESSION | The size of buffer in bytes for buffered reader of dal. By default, it is 1MB.                                                                | UInt64 |",
+        "| storage_read_timeout_secs          | 60        | 60        | SESSION | The timeout in seconds of a read from storage, 0 means no timeout. By default, it is 60 seconds.                                              | UInt64 |",
+        "| storage_vacuum_safety_window_secs  | 86400     | 86400     | SESSION | Only the orphan files older than this window in seconds are removed by vacuum. By default, it is 1 day.                                       | UInt64 |",
+        "| storage_write_buffer_max_bytes     | 268435456 | 268435456 | SESSION | The max bytes of blocks buffered before being written to storage, 0 for unlimited. By default, it is 256MB.                                   | UInt64 |",
+        "| timezone                           | UTC       | UTC       | SESSION | Timezone, default value: UTC,                                                                                                                 | String |",
+        "+------------------------------------+-----------+-----------+---------+-----------------------------------------------------------------------------------------------------------------------------------------------+--------+",
     ];
     common_datablocks::assert_blocks_sorted_eq(expected, result.as_slice());
 
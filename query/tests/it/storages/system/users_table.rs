@@ -42,7 +42,15 @@ async fn test_users_table() -> Result<()> {
                 grants: UserGrantSet::empty(),
                 quota: UserQuota::no_limit(),
                 option: UserOption::default(),
+                is_locked: false,
+                locked_reason: None,
+                settings: Default::default(),
+                password_policy: Default::default(),
+                network_policy: None,
+                is_builtin: false,
+                password_history: vec![],
             },
+            None,
             false,
         )
         .await?;
@@ -60,7 +68,15 @@ async fn test_users_table() -> Result<()> {
                 grants: UserGrantSet::empty(),
                 quota: UserQuota::no_limit(),
                 option: UserOption::default(),
+                is_locked: false,
+                locked_reason: None,
+                settings: Default::default(),
+                password_policy: Default::default(),
+                network_policy: None,
+                is_builtin: false,
+                password_history: vec![],
             },
+            None,
             false,
         )
         .await?;
@@ -76,7 +92,15 @@ async fn test_users_table() -> Result<()> {
                 grants: UserGrantSet::empty(),
                 quota: UserQuota::no_limit(),
                 option: UserOption::default(),
+                is_locked: false,
+                locked_reason: None,
+                settings: Default::default(),
+                password_policy: Default::default(),
+                network_policy: None,
+                is_builtin: false,
+                password_history: vec![],
             },
+            None,
             false,
         )
         .await?;
@@ -87,16 +111,16 @@ async fn test_users_table() -> Result<()> {
     let stream = table.read(ctx, &source_plan).await?;
     let result = stream.try_collect::<Vec<_>>().await?;
     let block = &result[0];
-    assert_eq!(block.num_columns(), 4);
+    assert_eq!(block.num_columns(), 5);
 
     let expected = vec![
-        "+-------+-----------+--------------------+------------------------------------------------------------------+",
-        "| name  | hostname  | auth_type          | auth_string                                                      |",
-        "+-------+-----------+--------------------+------------------------------------------------------------------+",
-        "| test  | localhost | no_password        |                                                                  |",
-        "| test1 | %         | plaintext_password | 123456789                                                        |",
-        "| test2 | %         | sha256_password    | 15e2b0d3c33891ebb0f1ef609ec419420c20e320ce94c65fbc8c3312448eb225 |",
-        "+-------+-----------+--------------------+------------------------------------------------------------------+",
+        "+-------+-----------+--------------------+------------------------------------------------------------------+-----------+",
+        "| name  | hostname  | auth_type          | auth_string                                                      | is_locked |",
+        "+-------+-----------+--------------------+------------------------------------------------------------------+-----------+",
+        "| test  | localhost | no_password        |                                                                  | false     |",
+        "| test1 | %         | plaintext_password | 123456789                                                        | false     |",
+        "| test2 | %         | sha256_password    | 15e2b0d3c33891ebb0f1ef609ec419420c20e320ce94c65fbc8c3312448eb225 | false     |",
+        "+-------+-----------+--------------------+------------------------------------------------------------------+-----------+",
     ];
     common_datablocks::assert_blocks_sorted_eq(expected, result.as_slice());
     Ok(())
@@ -75,6 +75,18 @@ impl SessionManagerBuilder {
         SessionManagerBuilder::create_with_conf(new_config)
     }
 
+    pub fn jwt_audience(self, value: impl Into<String>) -> SessionManagerBuilder {
+        let mut new_config = self.config;
+        new_config.query.jwt_audience = value.into();
+        SessionManagerBuilder::create_with_conf(new_config)
+    }
+
+    pub fn jwt_auto_create_user(self, value: bool) -> SessionManagerBuilder {
+        let mut new_config = self.config;
+        new_config.query.jwt_auto_create_user = value;
+        SessionManagerBuilder::create_with_conf(new_config)
+    }
+
     pub fn http_handler_result_time_out(self, value: impl Into<u64>) -> SessionManagerBuilder {
         let mut new_config = self.config;
         new_config.query.http_handler_result_timeout_millis = value.into();
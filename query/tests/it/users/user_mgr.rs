@@ -16,8 +16,10 @@ use common_base::tokio;
 use common_exception::ErrorCode;
 use common_exception::Result;
 use common_meta_types::AuthInfo;
+use common_meta_types::AuthType;
 use common_meta_types::GrantObject;
 use common_meta_types::PasswordHashMethod;
+use common_meta_types::PasswordPolicy;
 use common_meta_types::UserGrantSet;
 use common_meta_types::UserIdentity;
 use common_meta_types::UserInfo;
@@ -46,13 +48,17 @@ async fn test_user_manager() -> Result<()> {
     // add user hostname.
     {
         let user_info = User::new(username, hostname, auth_info.clone());
-        user_mgr.add_user(tenant, user_info.into(), false).await?;
+        user_mgr
+            .add_user(tenant, user_info.into(), None, false)
+            .await?;
     }
 
     // add user hostname again, error.
     {
         let user_info = User::new(username, hostname, auth_info.clone());
-        let res = user_mgr.add_user(tenant, user_info.into(), false).await;
+        let res = user_mgr
+            .add_user(tenant, user_info.into(), None, false)
+            .await;
         assert!(res.is_err());
         assert_eq!(
             res.err().unwrap().code(),
@@ -63,13 +69,17 @@ async fn test_user_manager() -> Result<()> {
     // add user hostname again, ok.
     {
         let user_info = User::new(username, hostname, auth_info.clone());
-        user_mgr.add_user(tenant, user_info.into(), true).await?;
+        user_mgr
+            .add_user(tenant, user_info.into(), None, true)
+            .await?;
     }
 
     // add user hostname2.
     {
         let user_info = User::new(username, hostname2, auth_info.clone());
-        user_mgr.add_user(tenant, user_info.into(), false).await?;
+        user_mgr
+            .add_user(tenant, user_info.into(), None, false)
+            .await?;
     }
 
     // get all users.
@@ -125,7 +135,9 @@ async fn test_user_manager() -> Result<()> {
     // grant privileges
     {
         let user_info: UserInfo = User::new(username, hostname, auth_info.clone()).into();
-        user_mgr.add_user(tenant, user_info.clone(), false).await?;
+        user_mgr
+            .add_user(tenant, user_info.clone(), None, false)
+            .await?;
         let old_user = user_mgr.get_user(tenant, user_info.identity()).await?;
         assert_eq!(old_user.grants, UserGrantSet::empty());
 
@@ -149,7 +161,9 @@ async fn test_user_manager() -> Result<()> {
     // revoke privileges
     {
         let user_info: UserInfo = User::new(username, hostname, auth_info.clone()).into();
-        user_mgr.add_user(tenant, user_info.clone(), false).await?;
+        user_mgr
+            .add_user(tenant, user_info.clone(), None, false)
+            .await?;
         user_mgr
             .grant_privileges_to_user(
                 tenant,
@@ -186,7 +200,9 @@ async fn test_user_manager() -> Result<()> {
             hash_method: PasswordHashMethod::PlainText,
         };
         let user_info: UserInfo = User::new(user, hostname, auth_info.clone()).into();
-        user_mgr.add_user(tenant, user_info.clone(), false).await?;
+        user_mgr
+            .add_user(tenant, user_info.clone(), None, false)
+            .await?;
 
         let old_user = user_mgr.get_user(tenant, user_info.identity()).await?;
         assert_eq!(old_user.auth_info.get_password().unwrap(), Vec::from(pwd));
@@ -198,7 +214,7 @@ async fn test_user_manager() -> Result<()> {
             hash_method: PasswordHashMethod::Sha256,
         };
         user_mgr
-            .update_user(tenant, user_info.identity(), Some(auth_info), None)
+            .update_user(tenant, user_info.identity(), Some(auth_info), None, None)
             .await?;
         let new_user = user_mgr.get_user(tenant, user_info.identity()).await?;
         assert_eq!(
@@ -217,7 +233,13 @@ async fn test_user_manager() -> Result<()> {
             hash_method: PasswordHashMethod::Sha256,
         };
         user_mgr
-            .update_user(tenant, user_info.identity(), Some(auth_info.clone()), None)
+            .update_user(
+                tenant,
+                user_info.identity(),
+                Some(auth_info.clone()),
+                None,
+                None,
+            )
             .await?;
         let new_new_user = user_mgr.get_user(tenant, user_info.identity()).await?;
         assert_eq!(
@@ -231,6 +253,7 @@ async fn test_user_manager() -> Result<()> {
                 UserIdentity::new("user", hostname),
                 Some(auth_info.clone()),
                 None,
+                None,
             )
             .await;
         // ErrorCode::UnknownUser
@@ -359,3 +382,315 @@ async fn test_user_manager_with_root_user() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_user_manager_with_host_patterns() -> Result<()> {
+    let conf = crate::tests::ConfigBuilder::create().config();
+
+    let tenant = "test";
+    let username = "test-user1";
+    let username2 = "test-user2";
+    let auth_info = AuthInfo::None;
+    let user_mgr = UserApiProvider::create_global(conf).await?;
+
+    for hostname in ["%", "10.%", "10.0.0.%", "10.0.0.1"] {
+        let user_info = User::new(username, hostname, auth_info.clone());
+        user_mgr
+            .add_user(tenant, user_info.into(), None, false)
+            .await?;
+    }
+    let user_info = User::new(username2, "10.%", auth_info.clone());
+    user_mgr
+        .add_user(tenant, user_info.into(), None, false)
+        .await?;
+
+    // The exact host is preferred, then the most specific pattern, then '%'.
+    {
+        let cases = vec![
+            ("10.0.0.1", "10.0.0.1"),
+            ("10.0.0.2", "10.0.0.%"),
+            ("10.1.0.1", "10.%"),
+            ("192.168.1.1", "%"),
+        ];
+        for (client_ip, expected) in cases {
+            let user = user_mgr
+                .get_user_with_client_ip(tenant, username, client_ip, false)
+                .await?;
+            assert_eq!(user.hostname, expected, "client ip {}", client_ip);
+        }
+    }
+
+    // Only the hosts matching the patterns of the user are accepted.
+    {
+        let user = user_mgr
+            .get_user_with_client_ip(tenant, username2, "10.2.0.1", false)
+            .await?;
+        assert_eq!(user.hostname, "10.%");
+
+        let res = user_mgr
+            .get_user_with_client_ip(tenant, username2, "192.168.1.1", false)
+            .await;
+        assert_eq!(res.err().unwrap().code(), ErrorCode::unknown_user_code());
+    }
+
+    // The user whose name is prefixed by the name and a quote is another user.
+    {
+        let user_info = User::new(format!("{}'@x", username2), "%", auth_info.clone());
+        user_mgr
+            .add_user(tenant, user_info.into(), None, false)
+            .await?;
+        let res = user_mgr
+            .get_user_with_client_ip(tenant, username2, "192.168.1.1", false)
+            .await;
+        assert_eq!(res.err().unwrap().code(), ErrorCode::unknown_user_code());
+    }
+
+    // The user dropped by its pattern is no longer matched.
+    {
+        user_mgr
+            .drop_user(tenant, UserIdentity::new(username, "10.0.0.%"), false)
+            .await?;
+        let user = user_mgr
+            .get_user_with_client_ip(tenant, username, "10.0.0.2", false)
+            .await?;
+        assert_eq!(user.hostname, "10.%");
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_user_manager_ignore_case() -> Result<()> {
+    let conf = crate::tests::ConfigBuilder::create().config();
+
+    let tenant = "test";
+    let user_mgr = UserApiProvider::create_global(conf).await?;
+    for (username, hostname) in [
+        ("Alice", "%"),
+        ("Bob", "%"),
+        ("BOB", "10.%"),
+        ("carol", "%"),
+    ] {
+        let user_info = User::new(username, hostname, AuthInfo::None);
+        user_mgr
+            .add_user(tenant, user_info.into(), None, false)
+            .await?;
+    }
+
+    assert!(
+        user_mgr
+            .exists_user(tenant, UserIdentity::new("Alice", "%"))
+            .await?
+    );
+    assert!(
+        !user_mgr
+            .exists_user(tenant, UserIdentity::new("alice", "%"))
+            .await?
+    );
+
+    // Only matched in another case if ignoring the case.
+    {
+        let res = user_mgr
+            .get_user_with_client_ip(tenant, "alice", "10.0.0.1", false)
+            .await;
+        assert_eq!(res.err().unwrap().code(), ErrorCode::unknown_user_code());
+
+        let user = user_mgr
+            .get_user_with_client_ip(tenant, "alice", "10.0.0.1", true)
+            .await?;
+        assert_eq!(user.name, "Alice");
+
+        let user = user_mgr
+            .get_user_with_client_ip(tenant, "CAROL", "10.0.0.1", true)
+            .await?;
+        assert_eq!(user.name, "carol");
+    }
+
+    // The exact name is preferred, and the other names ignoring the case are ambiguous.
+    {
+        let user = user_mgr
+            .get_user_with_client_ip(tenant, "BOB", "10.0.0.1", true)
+            .await?;
+        assert_eq!(user.name, "BOB");
+
+        let res = user_mgr
+            .get_user_with_client_ip(tenant, "bob", "10.0.0.1", true)
+            .await;
+        assert_eq!(res.err().unwrap().code(), ErrorCode::ambiguous_user_code());
+
+        // 'BOB'@'10.%' does not match the host.
+        let user = user_mgr
+            .get_user_with_client_ip(tenant, "bob", "192.168.1.1", true)
+            .await?;
+        assert_eq!(user.name, "Bob");
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_user_manager_lock_user() -> Result<()> {
+    let conf = crate::tests::ConfigBuilder::create().config();
+
+    let tenant = "test";
+    let username = "test-user1";
+    let user_mgr = UserApiProvider::create_global(conf).await?;
+    let user_info = User::new(username, "%", AuthInfo::None);
+    user_mgr
+        .add_user(tenant, user_info.into(), None, false)
+        .await?;
+
+    // A locked user is rejected before the password is verified.
+    {
+        user_mgr
+            .lock_user(
+                tenant,
+                UserIdentity::new(username, "%"),
+                Some("password leaked".to_string()),
+            )
+            .await?;
+        let user = user_mgr
+            .get_user_with_client_ip(tenant, username, "10.0.0.1", false)
+            .await?;
+        assert!(user.is_locked);
+        let err = user.check_not_locked().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::authenticate_failure_code());
+        assert!(err.message().contains("password leaked"));
+    }
+
+    {
+        user_mgr
+            .unlock_user(tenant, UserIdentity::new(username, "%"))
+            .await?;
+        let user = user_mgr
+            .get_user_with_client_ip(tenant, username, "10.0.0.1", false)
+            .await?;
+        user.check_not_locked()?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_user_manager_bootstrap_admin() -> Result<()> {
+    let mut conf = crate::tests::ConfigBuilder::create().config();
+    conf.query.bootstrap_admin_user = "admin".to_string();
+
+    let tenant = conf.query.tenant_id.clone();
+    let identity = UserIdentity::new("admin", "%");
+    let user_mgr = UserApiProvider::create_global(conf.clone()).await?;
+
+    // The admin is never created without a password.
+    let res = user_mgr.bootstrap_admin(&conf).await;
+    assert_eq!(res.unwrap_err().code(), ErrorCode::InvalidConfig("").code());
+    let res = user_mgr.get_user(&tenant, identity.clone()).await;
+    assert_eq!(res.unwrap_err().code(), ErrorCode::UnknownUser("").code());
+
+    conf.query.bootstrap_admin_password = "admin-pwd".into();
+    user_mgr.bootstrap_admin(&conf).await?;
+    let user = user_mgr.get_user(&tenant, identity.clone()).await?;
+    assert!(user.is_builtin);
+    assert!(user
+        .grants
+        .verify_privilege(&GrantObject::Global, UserPrivilegeType::Create));
+    assert_eq!(
+        user.auth_info.get_password(),
+        Some(PasswordHashMethod::Sha256.hash(b"admin-pwd"))
+    );
+
+    // The existing admin is kept, even if the password is changed.
+    user_mgr
+        .update_user(
+            &tenant,
+            identity.clone(),
+            Some(AuthInfo::Password {
+                hash_value: PasswordHashMethod::Sha256.hash(b"new-pwd"),
+                hash_method: PasswordHashMethod::Sha256,
+            }),
+            None,
+            None,
+        )
+        .await?;
+    user_mgr.bootstrap_admin(&conf).await?;
+    let user = user_mgr.get_user(&tenant, identity.clone()).await?;
+    assert_eq!(
+        user.auth_info.get_password(),
+        Some(PasswordHashMethod::Sha256.hash(b"new-pwd"))
+    );
+
+    // The admin is protected.
+    let res = user_mgr.drop_user(&tenant, identity.clone(), false).await;
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::BuiltinUserProtected("").code()
+    );
+    let res = user_mgr.lock_user(&tenant, identity.clone(), None).await;
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::BuiltinUserProtected("").code()
+    );
+    assert!(user_mgr.get_user(&tenant, identity).await.is_ok());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_user_manager_password_policy() -> Result<()> {
+    let mut conf = crate::tests::ConfigBuilder::create().config();
+    conf.query.bootstrap_admin_user = "admin".to_string();
+    conf.query.bootstrap_admin_password = "admin-pwd".into();
+
+    let tenant = conf.query.tenant_id.clone();
+    let user_mgr = UserApiProvider::create_global(conf.clone()).await?;
+    user_mgr.bootstrap_admin(&conf).await?;
+    user_mgr
+        .set_password_policy(&tenant, PasswordPolicy {
+            min_length: 12,
+            ..Default::default()
+        })
+        .await?;
+
+    // The password of a new user is checked.
+    let auth_info = AuthInfo::new(AuthType::Sha256Password, &Some("short".to_string()))?;
+    let user_info: UserInfo = User::new("test", "%", auth_info).into();
+    let res = user_mgr
+        .add_user(&tenant, user_info.clone(), Some("short"), false)
+        .await;
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::PasswordPolicyViolation("").code()
+    );
+    user_mgr
+        .add_user(&tenant, user_info.clone(), None, false)
+        .await?;
+
+    // So is the new password of a user.
+    let auth_info = AuthInfo::new(AuthType::Sha256Password, &Some("short2".to_string()))?;
+    let res = user_mgr
+        .update_user(
+            &tenant,
+            user_info.identity(),
+            Some(auth_info),
+            Some("short2"),
+            None,
+        )
+        .await;
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::PasswordPolicyViolation("").code()
+    );
+    let auth_info = AuthInfo::new(AuthType::Sha256Password, &Some("long-password".to_string()))?;
+    user_mgr
+        .update_user(
+            &tenant,
+            user_info.identity(),
+            Some(auth_info),
+            Some("long-password"),
+            None,
+        )
+        .await?;
+
+    // The existing admin is kept, though its password is shorter than required now.
+    user_mgr.bootstrap_admin(&conf).await?;
+    Ok(())
+}
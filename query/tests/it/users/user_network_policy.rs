@@ -0,0 +1,138 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::tokio;
+use common_exception::ErrorCode;
+use common_exception::Result;
+use common_meta_types::AuthInfo;
+use common_meta_types::NetworkPolicy;
+use common_meta_types::UserInfo;
+use databend_query::users::UserApiProvider;
+use pretty_assertions::assert_eq;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_user_network_policy() -> Result<()> {
+    let conf = crate::tests::ConfigBuilder::create().config();
+
+    let tenant = "test";
+    let user_mgr = UserApiProvider::create_global(conf).await?;
+
+    let policy = NetworkPolicy {
+        name: "office".to_string(),
+        allowed_ip_list: vec!["192.168.0.0/16".parse()?, "fd00::/8".parse()?],
+        blocked_ip_list: vec!["192.168.1.0/24".parse()?],
+        comment: "".to_string(),
+    };
+    user_mgr
+        .add_network_policy(tenant, policy.clone(), false)
+        .await?;
+    user_mgr
+        .add_network_policy(tenant, policy.clone(), true)
+        .await?;
+    assert_eq!(user_mgr.get_network_policies(tenant).await?, vec![
+        policy.clone()
+    ]);
+
+    let user = UserInfo::new("u1".to_string(), "%".to_string(), AuthInfo::None);
+    let identity = user.identity();
+    user_mgr.add_user(tenant, user, None, false).await?;
+
+    // Attaching to an unknown policy is rejected.
+    let res = user_mgr
+        .set_user_network_policy(tenant, identity.clone(), Some("unknown"))
+        .await;
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::UnknownNetworkPolicy("").code()
+    );
+
+    // Every address is allowed without a policy.
+    let user = user_mgr.get_user(tenant, identity.clone()).await?;
+    user_mgr
+        .check_network_policy(tenant, &user, Some("10.0.0.1:3307"))
+        .await?;
+    user_mgr.check_network_policy(tenant, &user, None).await?;
+
+    user_mgr
+        .set_user_network_policy(tenant, identity.clone(), Some("office"))
+        .await?;
+    let user = user_mgr.get_user(tenant, identity.clone()).await?;
+    for allowed in [
+        "192.168.2.1",
+        "192.168.2.1:3307",
+        "[fd00::1]:3307",
+        "fd00::1",
+    ] {
+        user_mgr
+            .check_network_policy(tenant, &user, Some(allowed))
+            .await?;
+    }
+    for rejected in [
+        Some("10.0.0.1:3307"),
+        Some("192.168.1.1:3307"),
+        Some("[fe80::1]:3307"),
+        Some("unknown"),
+        None,
+    ] {
+        let res = user_mgr.check_network_policy(tenant, &user, rejected).await;
+        assert_eq!(
+            res.unwrap_err().code(),
+            ErrorCode::AuthenticateFailure("").code(),
+            "{:?}",
+            rejected
+        );
+    }
+
+    // The policy in use can not be dropped.
+    let res = user_mgr.drop_network_policy(tenant, "office", false).await;
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::NetworkPolicyIsUsedByUser("").code()
+    );
+
+    // The updated policy takes effect at once on this node.
+    let updated = NetworkPolicy {
+        allowed_ip_list: vec!["10.0.0.0/8".parse()?],
+        blocked_ip_list: vec![],
+        ..policy.clone()
+    };
+    user_mgr.update_network_policy(tenant, updated).await?;
+    user_mgr
+        .check_network_policy(tenant, &user, Some("10.0.0.1:3307"))
+        .await?;
+
+    user_mgr
+        .set_user_network_policy(tenant, identity.clone(), None)
+        .await?;
+    user_mgr
+        .drop_network_policy(tenant, "office", false)
+        .await?;
+    user_mgr.drop_network_policy(tenant, "office", true).await?;
+    assert_eq!(user_mgr.get_network_policies(tenant).await?, vec![]);
+
+    // A user left attached to a dropped policy is rejected.
+    let user = UserInfo {
+        network_policy: Some("office".to_string()),
+        ..UserInfo::new_no_auth("u2".to_string(), "%".to_string())
+    };
+    let res = user_mgr
+        .check_network_policy(tenant, &user, Some("10.0.0.1:3307"))
+        .await;
+    assert_eq!(
+        res.unwrap_err().code(),
+        ErrorCode::AuthenticateFailure("").code()
+    );
+
+    Ok(())
+}
@@ -11,10 +11,10 @@ insert into t values (7);
 -- expects 3 history items
 select count(*) from fuse_history('db_09_0007', 't');
 
--- truncate table will remove all the historical data but the latest snapshot
--- only the latest snapshot will be kept, no segments or block, but unfortunately, we can not verify it by sql (yet)
+-- truncate table with purge marks the historical data to be removed by vacuum, once the
+-- truncation is older than the vacuum safety window, running queries may still read it until then
 truncate table 't' purge;
--- expect 1 snapshot left
+-- expect 4 history items, the history is kept within the safety window
 select count(*) from fuse_history('db_09_0007', 't');
 -- but no data, since it is truncated
 select * from t;